@@ -0,0 +1,165 @@
+use shared::{BackEndRequest, asset::Asset};
+use yew::prelude::*;
+
+/* the metadata of a robot as shown on its card, only the fields that are set are shown */
+pub fn render(asset: &Asset) -> Html {
+    if asset.is_empty() {
+        return html! {};
+    }
+    let fields = [
+        ("Serial numbers", &asset.serial_numbers),
+        ("Hardware revision", &asset.hardware_revision),
+        ("Last maintenance", &asset.last_maintenance),
+        ("Known issues", &asset.known_issues),
+        ("Notes", &asset.notes),
+    ];
+    html! {
+        <table class="table is-narrow is-fullwidth">
+            <tbody> {
+                fields.iter()
+                    .filter(|(_, value)| !value.is_empty())
+                    .map(|(label, value)| html! {
+                        <tr>
+                            <th>{ label }</th>
+                            <td style="white-space: pre-wrap;">{ value }</td>
+                        </tr>
+                    })
+                    .collect::<Html>()
+            } </tbody>
+        </table>
+    }
+}
+
+pub struct Editor {
+    link: ComponentLink<Self>,
+    props: Props,
+    /* the metadata as it is being edited */
+    asset: Asset,
+    error: Option<String>,
+}
+
+#[derive(Clone, Properties)]
+pub struct Props {
+    pub asset: Asset,
+    pub parent: ComponentLink<crate::UserInterface>,
+    pub onclose: Callback<()>,
+}
+
+pub enum Field {
+    SerialNumbers,
+    HardwareRevision,
+    LastMaintenance,
+    KnownIssues,
+    Notes,
+}
+
+pub enum Msg {
+    Set(Field, String),
+    Save,
+    Saved(Result<(), String>),
+}
+
+impl Component for Editor {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(props: Props, link: ComponentLink<Self>) -> Self {
+        Editor { link, asset: props.asset.clone(), props, error: None }
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            Msg::Set(field, value) => {
+                let target = match field {
+                    Field::SerialNumbers => &mut self.asset.serial_numbers,
+                    Field::HardwareRevision => &mut self.asset.hardware_revision,
+                    Field::LastMaintenance => &mut self.asset.last_maintenance,
+                    Field::KnownIssues => &mut self.asset.known_issues,
+                    Field::Notes => &mut self.asset.notes,
+                };
+                *target = value;
+                false
+            },
+            Msg::Save => {
+                let callback = Some(self.link.callback(Msg::Saved));
+                let request = BackEndRequest::AssetRequest(self.asset.clone());
+                self.props.parent.send_message(crate::Msg::SendRequest(request, callback));
+                false
+            },
+            Msg::Saved(Ok(_)) => {
+                self.props.onclose.emit(());
+                false
+            },
+            Msg::Saved(Err(error)) => {
+                self.error = Some(error);
+                true
+            },
+        }
+    }
+
+    /* the metadata that is being edited is not replaced by updates from the supervisor */
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        self.props = props;
+        false
+    }
+
+    fn view(&self) -> Html {
+        let close_onclick = self.props.onclose.reform(|_| ());
+        let save_onclick = self.link.callback(|_| Msg::Save);
+        html! {
+            <div class="modal is-active">
+                <div class="modal-background" onclick=close_onclick.clone() />
+                <div class="modal-card">
+                    <header class="modal-card-head">
+                        <p class="modal-card-title">{ format!("Metadata of {}", self.asset.robot) }</p>
+                    </header>
+                    <section class="modal-card-body">
+                        { self.render_input("Serial numbers", &self.asset.serial_numbers, || Field::SerialNumbers) }
+                        { self.render_input("Hardware revision", &self.asset.hardware_revision, || Field::HardwareRevision) }
+                        { self.render_input("Last maintenance (YYYY-MM-DD)", &self.asset.last_maintenance, || Field::LastMaintenance) }
+                        { self.render_textarea("Known issues", &self.asset.known_issues, || Field::KnownIssues) }
+                        { self.render_textarea("Notes", &self.asset.notes, || Field::Notes) }
+                        {
+                            match &self.error {
+                                Some(error) => html! {
+                                    <div class="notification is-danger">{ error }</div>
+                                },
+                                None => html! {},
+                            }
+                        }
+                    </section>
+                    <footer class="modal-card-foot">
+                        <button class="button is-success" onclick=save_onclick>{ "Save" }</button>
+                        <button class="button" onclick=close_onclick>{ "Cancel" }</button>
+                    </footer>
+                </div>
+            </div>
+        }
+    }
+}
+
+impl Editor {
+    fn render_input(&self, label: &str, value: &str, field: fn() -> Field) -> Html {
+        let oninput = self.link.callback(move |data: InputData| Msg::Set(field(), data.value));
+        html! {
+            <div class="field">
+                <label class="label">{ label }</label>
+                <div class="control">
+                    <input class="input" type="text" value=value.to_owned() oninput=oninput />
+                </div>
+            </div>
+        }
+    }
+
+    fn render_textarea(&self, label: &str, value: &str, field: fn() -> Field) -> Html {
+        let oninput = self.link.callback(move |data: InputData| Msg::Set(field(), data.value));
+        html! {
+            <div class="field">
+                <label class="label">{ label }</label>
+                <div class="control">
+                    <textarea class="textarea" rows="2" value=value.to_owned() oninput=oninput />
+                </div>
+            </div>
+        }
+    }
+}
@@ -0,0 +1,131 @@
+use std::{collections::VecDeque, rc::Rc};
+use shared::audit::Entry;
+use yew::prelude::*;
+
+pub struct Panel {
+    link: ComponentLink<Self>,
+    props: Props,
+    query: String,
+    failed_only: bool,
+}
+
+#[derive(Clone, Properties)]
+pub struct Props {
+    pub entries: Rc<VecDeque<Entry>>,
+}
+
+pub enum Msg {
+    SetQuery(String),
+    SetFailedOnly(bool),
+}
+
+/* an entry matches the query if any of its fields contains each of the words of the query */
+fn matches(entry: &Entry, query: &str) -> bool {
+    let fields = [
+        Some(entry.timestamp.as_str()),
+        Some(entry.operator.as_str()),
+        Some(entry.arena.as_str()),
+        entry.robot.as_deref(),
+        Some(entry.action.as_str()),
+        entry.error.as_deref(),
+    ];
+    query.split_whitespace().all(|word| {
+        let word = word.to_lowercase();
+        fields.iter().flatten().any(|field| field.to_lowercase().contains(&word))
+    })
+}
+
+impl Component for Panel {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(props: Props, link: ComponentLink<Self>) -> Self {
+        Panel { link, props, query: String::new(), failed_only: false }
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            Msg::SetQuery(query) => self.query = query,
+            Msg::SetFailedOnly(failed_only) => self.failed_only = failed_only,
+        }
+        true
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        self.props = props;
+        true
+    }
+
+    fn view(&self) -> Html {
+        let query_oninput = self.link.callback(|data: InputData| Msg::SetQuery(data.value));
+        let failed_only = self.failed_only;
+        let failed_onclick = self.link.callback(move |_| Msg::SetFailedOnly(!failed_only));
+        /* the most recent entries are shown first */
+        let entries = self.props.entries.iter().rev()
+            .filter(|entry| !self.failed_only || entry.error.is_some())
+            .filter(|entry| matches(entry, &self.query));
+        html! {
+            <div class="column is-full">
+                <div class="card">
+                    <header class="card-header">
+                        <p class="card-header-title">{ "Audit trail" }</p>
+                        <div class="card-header-icon">
+                            <div class="field is-grouped">
+                                <div class="control">
+                                    <input class="input is-small"
+                                           type="search"
+                                           placeholder="Search"
+                                           value=self.query.clone()
+                                           oninput=query_oninput />
+                                </div>
+                                <div class="control">
+                                    <label class="checkbox">
+                                        <input type="checkbox" checked=self.failed_only onclick=failed_onclick />
+                                        { " Failed only" }
+                                    </label>
+                                </div>
+                            </div>
+                        </div>
+                    </header>
+                    <div class="card-content" style="max-height: 75vh; overflow-y: auto;">
+                        <table class="table is-fullwidth is-narrow">
+                            <thead>
+                                <tr>
+                                    <th>{ "Time" }</th>
+                                    <th>{ "Operator" }</th>
+                                    <th>{ "Arena" }</th>
+                                    <th>{ "Robot" }</th>
+                                    <th>{ "Action" }</th>
+                                    <th>{ "Result" }</th>
+                                </tr>
+                            </thead>
+                            <tbody> {
+                                entries.map(|entry| html! {
+                                    <tr>
+                                        <td class="is-family-monospace">{ &entry.timestamp }</td>
+                                        <td class="is-family-monospace">{ &entry.operator }</td>
+                                        <td>{ &entry.arena }</td>
+                                        <td>{ entry.robot.as_deref().unwrap_or("") }</td>
+                                        <td class="is-family-monospace">{ &entry.action }</td>
+                                        <td> {
+                                            match &entry.error {
+                                                Some(error) => html! {
+                                                    <>
+                                                        <span class="tag is-danger">{ "Failed" }</span>
+                                                        <span class="is-family-monospace">{ format!(" {}", error) }</span>
+                                                    </>
+                                                },
+                                                None => html! { <span class="tag is-success">{ "Succeeded" }</span> },
+                                            }
+                                        } </td>
+                                    </tr>
+                                }).collect::<Html>()
+                            } </tbody>
+                        </table>
+                        <p class="has-text-grey">{ "The most recent 1000 entries are shown, the complete audit trail is kept by the supervisor." }</p>
+                    </div>
+                </div>
+            </div>
+        }
+    }
+}
@@ -0,0 +1,228 @@
+use std::{cell::RefCell, collections::{HashMap, VecDeque}, rc::Rc};
+use yew::prelude::*;
+
+use crate::{builderbot, drone, pipuck};
+
+/* the duration in milliseconds over which the discharge of a battery is estimated */
+const WINDOW: f64 = 10.0 * 60.0 * 1000.0;
+/* the minimum duration in milliseconds that the samples must span before the discharge is estimated */
+const MIN_SPAN: f64 = 60.0 * 1000.0;
+/* the battery level in percent at which a robot should no longer take part in an experiment */
+const RESERVE: i32 = 20;
+/* a swap is recommended below this battery level in percent or if the reserve is reached within
+   this number of minutes */
+const SWAP_LEVEL: i32 = 30;
+const SWAP_MINUTES: f64 = 10.0;
+
+/* the battery levels that were received from a robot within the window */
+#[derive(Default)]
+pub struct History {
+    /* the time at which the level was received in milliseconds since the epoch and the level in percent */
+    samples: VecDeque<(f64, i32)>,
+}
+
+impl History {
+    pub fn record(&mut self, level: i32) {
+        let now = js_sys::Date::now();
+        self.samples.push_back((now, level));
+        while let Some((timestamp, _)) = self.samples.front() {
+            match now - timestamp > WINDOW {
+                true => self.samples.pop_front(),
+                false => break,
+            };
+        }
+    }
+
+    /* the battery is disconnected or swapped, the previous levels do not apply anymore */
+    pub fn clear(&mut self) {
+        self.samples.clear();
+    }
+
+    pub fn level(&self) -> Option<i32> {
+        self.samples.back().map(|(_, level)| *level)
+    }
+
+    /* the change of the battery level in percent per minute, fitted to the samples by least squares */
+    pub fn slope(&self) -> Option<f64> {
+        let (first, _) = self.samples.front()?;
+        let (last, _) = self.samples.back()?;
+        if last - first < MIN_SPAN {
+            return None;
+        }
+        let count = self.samples.len() as f64;
+        let mean_t = self.samples.iter().map(|(t, _)| (t - first) / 60000.0).sum::<f64>() / count;
+        let mean_l = self.samples.iter().map(|(_, l)| *l as f64).sum::<f64>() / count;
+        let (covariance, variance) = self.samples.iter()
+            .map(|(t, l)| ((t - first) / 60000.0 - mean_t, *l as f64 - mean_l))
+            .fold((0.0, 0.0), |(covariance, variance), (dt, dl)| (covariance + dt * dl, variance + dt * dt));
+        match variance > 0.0 {
+            true => Some(covariance / variance),
+            false => None,
+        }
+    }
+
+    /* the minutes until the battery reaches the reserve, unknown if the battery is not discharging */
+    pub fn remaining(&self) -> Option<f64> {
+        let level = self.level()?;
+        match self.slope()? {
+            slope if slope < 0.0 => Some(((level - RESERVE).max(0) as f64 / -slope).max(0.0)),
+            _ => None,
+        }
+    }
+
+    pub fn swap_recommended(&self) -> bool {
+        self.level().map_or(false, |level| level < SWAP_LEVEL) ||
+            self.remaining().map_or(false, |remaining| remaining < SWAP_MINUTES)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Column {
+    Robot,
+    Level,
+    Remaining,
+}
+
+pub struct Panel {
+    link: ComponentLink<Self>,
+    props: Props,
+    sort: Column,
+}
+
+#[derive(Clone, Properties)]
+pub struct Props {
+    pub builderbots: HashMap<String, Rc<RefCell<builderbot::Instance>>>,
+    pub drones: HashMap<String, Rc<RefCell<drone::Instance>>>,
+    pub pipucks: HashMap<String, Rc<RefCell<pipuck::Instance>>>,
+}
+
+pub enum Msg {
+    SortBy(Column),
+}
+
+/* a row of the table, i.e., the type and identifier of the robot and its battery */
+struct Entry<'a> {
+    kind: &'static str,
+    id: &'a str,
+    level: Option<i32>,
+    remaining: Option<f64>,
+    swap: bool,
+}
+
+impl<'a> Entry<'a> {
+    fn new(kind: &'static str, id: &'a str, history: &History) -> Self {
+        Entry {
+            kind,
+            id,
+            level: history.level(),
+            remaining: history.remaining(),
+            swap: history.swap_recommended(),
+        }
+    }
+}
+
+impl Component for Panel {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(props: Props, link: ComponentLink<Self>) -> Self {
+        Panel { link, props, sort: Column::Level }
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            Msg::SortBy(column) => {
+                self.sort = column;
+                true
+            }
+        }
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        self.props = props;
+        true
+    }
+
+    fn view(&self) -> Html {
+        let builderbots = self.props.builderbots.values()
+            .map(|builderbot| builderbot.borrow())
+            .collect::<Vec<_>>();
+        let drones = self.props.drones.values()
+            .map(|drone| drone.borrow())
+            .collect::<Vec<_>>();
+        let pipucks = self.props.pipucks.values()
+            .map(|pipuck| pipuck.borrow())
+            .collect::<Vec<_>>();
+        let mut entries = builderbots.iter()
+            .map(|builderbot| Entry::new("BuilderBot", &builderbot.descriptor.id, &builderbot.battery_history))
+            .chain(drones.iter()
+                .map(|drone| Entry::new("Drone", &drone.descriptor.id, &drone.battery_history)))
+            .chain(pipucks.iter()
+                .map(|pipuck| Entry::new("Pi-Puck", &pipuck.descriptor.id, &pipuck.battery_history)))
+            .collect::<Vec<_>>();
+        /* the robots that need attention first, unknown levels and estimates last */
+        entries.sort_by(|lhs, rhs| match self.sort {
+            Column::Robot => lhs.id.cmp(rhs.id),
+            Column::Level => (lhs.level.is_none(), lhs.level, lhs.id)
+                .cmp(&(rhs.level.is_none(), rhs.level, rhs.id)),
+            Column::Remaining => lhs.remaining.is_none().cmp(&rhs.remaining.is_none())
+                .then(lhs.remaining.partial_cmp(&rhs.remaining).unwrap_or(std::cmp::Ordering::Equal))
+                .then(lhs.id.cmp(rhs.id)),
+        });
+        html! {
+            <div class="column is-full">
+                <div class="card">
+                    <header class="card-header">
+                        <p class="card-header-title">{ "Batteries" }</p>
+                    </header>
+                    <div class="card-content">
+                        <table class="table is-fullwidth is-narrow is-hoverable">
+                            <thead>
+                                <tr>
+                                    { self.render_header("Robot", Column::Robot) }
+                                    <th>{ "Type" }</th>
+                                    { self.render_header("Level", Column::Level) }
+                                    { self.render_header("Remaining", Column::Remaining) }
+                                    <th />
+                                </tr>
+                            </thead>
+                            <tbody> {
+                                entries.iter().map(|entry| html! {
+                                    <tr>
+                                        <td>{ entry.id }</td>
+                                        <td>{ entry.kind }</td>
+                                        <td>{ entry.level.map_or(String::from("Unknown"), |level| format!("{}%", level)) }</td>
+                                        <td>{ entry.remaining.map_or(String::from("Unknown"), |remaining| format!("{:.0} min", remaining)) }</td>
+                                        <td> {
+                                            match entry.swap {
+                                                true => html! { <span class="tag is-danger">{ "Swap recommended" }</span> },
+                                                false => html! {},
+                                            }
+                                        } </td>
+                                    </tr>
+                                }).collect::<Html>()
+                            } </tbody>
+                        </table>
+                        <p class="has-text-grey">{
+                            format!("The remaining time is estimated from the discharge over the last {} minutes until the battery reaches {}%.",
+                                WINDOW / 60000.0, RESERVE)
+                        }</p>
+                    </div>
+                </div>
+            </div>
+        }
+    }
+}
+
+impl Panel {
+    fn render_header(&self, label: &str, column: Column) -> Html {
+        let onclick = self.link.callback(move |_| Msg::SortBy(column));
+        let label = match self.sort == column {
+            true => format!("{} ▴", label),
+            false => label.to_owned(),
+        };
+        html! {
+            <th><a onclick=onclick>{ label }</a></th>
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use std::{cell::RefCell, collections::HashMap, net::Ipv4Addr, rc::Rc};
+use std::{cell::RefCell, collections::{HashMap, HashSet}, net::Ipv4Addr, rc::Rc};
 use shared::{BackEndRequest, builderbot::{Descriptor, Request, Update}};
 use web_sys::HtmlInputElement;
 use yew::{prelude::*, web_sys::HtmlTextAreaElement};
@@ -16,8 +16,22 @@ enum DuoVero {
 pub struct Instance {
     pub descriptor: Descriptor,
     pub optitrack_pos: [f32; 3],
+    pub open_tickets: usize,
     duovero: DuoVero,
-    camera_stream: HashMap<String, Result<String, String>>,
+    pub camera_stream: HashMap<String, crate::camera::Stream>,
+    pub battery_history: crate::battery::History,
+    /* the cameras that are being streamed */
+    pub cameras_enabled: HashSet<String>,
+    /* the robot is excluded from the arena */
+    pub quarantined: bool,
+    /* the health of the robot as assessed by the supervisor */
+    pub health: Option<shared::health::Health>,
+    /* the recent values of the metrics of the robot, if they have been requested */
+    pub telemetry: Option<shared::telemetry::Window>,
+    /* the metadata of the robot, e.g., its serial numbers */
+    pub asset: shared::asset::Asset,
+    /* the file that is being uploaded, the number of bytes sent, and the total number of bytes */
+    upload: Option<(String, u64, u64)>,
 }
 
 // a lot of stuff here seems like it should be implemented directly on the component,
@@ -25,22 +39,40 @@ pub struct Instance {
 impl Instance {
     pub fn new(descriptor: Descriptor) -> Self {
         Self { 
+            asset: shared::asset::Asset { robot: descriptor.id.clone(), ..Default::default() },
             descriptor, 
             optitrack_pos: [0.0, 0.0, 0.0],
+            open_tickets: 0,
             duovero: DuoVero::Disconnected,
             camera_stream: Default::default(),
+            battery_history: Default::default(),
+            cameras_enabled: Default::default(),
+            quarantined: false,
+            health: None,
+            telemetry: None,
+            upload: None,
         }
     }
 
+    pub fn set_camera_enabled(&mut self, camera: &str, enable: bool) {
+        self.camera_stream.remove(camera);
+        match enable {
+            true => self.cameras_enabled.insert(camera.to_owned()),
+            false => self.cameras_enabled.remove(camera),
+        };
+    }
+
     pub fn update(&mut self, update: Update) {
         match update {
             Update::Battery(reading) => if let DuoVero::Connected { battery, ..} = &mut self.duovero {
                 *battery = Ok(reading);
+                self.battery_history.record(reading);
             },
             Update::Camera { camera, result } => {
                 self.camera_stream
-                    .insert(camera, result
-                        .map(|bytes| base64::encode(bytes)));
+                    .entry(camera)
+                    .or_default()
+                    .update(result.map(|bytes| base64::encode(bytes)));
             },
             Update::FernbedienungConnected(addr) => 
                 self.duovero = DuoVero::Connected {
@@ -49,16 +81,26 @@ impl Instance {
                     signal: Err(String::from("Unknown")),
                     terminal: Default::default(),
                 },
-            Update::FernbedienungDisconnected => 
-                self.duovero = DuoVero::Disconnected,
+            Update::FernbedienungDisconnected => {
+                self.duovero = DuoVero::Disconnected;
+                self.battery_history.clear();
+            },
             Update::FernbedienungSignal(strength) => {
                 if let DuoVero::Connected { signal, ..} = &mut self.duovero {
                     *signal = Ok(strength);
                 }
             },
+            /* the temperature is only used for the health of the robot */
+            Update::Temperature(_) => {},
             Update::Bash(response) => if let DuoVero::Connected { terminal, ..} = &mut self.duovero {
                 terminal.push_str(&response);
             },
+            Update::Upload { filename, sent, total } => {
+                self.upload = match sent < total {
+                    true => Some((filename, sent, total)),
+                    false => None,
+                };
+            },
         }
     }
 }
@@ -70,6 +112,8 @@ pub struct Card {
     bash_textarea: NodeRef,
     bash_input: NodeRef,
     camera_dialog_active: bool,
+    asset_dialog_active: bool,
+    telemetry_dialog_active: bool,
     error: Result<(), String>,
 }
 
@@ -83,6 +127,8 @@ pub enum Msg {
     SetError(Result<(), String>),
     ToggleBashTerminal,
     ToggleCameraStream,
+    ToggleAssetDialog,
+    ToggleTelemetryDialog,
     SendBashCommand,
 }
 
@@ -100,6 +146,8 @@ impl Component for Card {
             bash_textarea: NodeRef::default(),
             bash_input: NodeRef::default(),
             camera_dialog_active: false,
+            asset_dialog_active: false,
+            telemetry_dialog_active: false,
             error: Ok(())
         }
     }
@@ -150,6 +198,14 @@ impl Component for Card {
                 }
                 true
             },
+            Msg::ToggleAssetDialog => {
+                self.asset_dialog_active = !self.asset_dialog_active;
+                true
+            },
+            Msg::ToggleTelemetryDialog => {
+                self.telemetry_dialog_active = !self.telemetry_dialog_active;
+                true
+            },
             Msg::ToggleCameraStream => {
                 match self.camera_dialog_active {
                     false => {
@@ -158,6 +214,9 @@ impl Component for Card {
                         let request = BackEndRequest::BuilderBotRequest(builderbot.descriptor.id.clone(), builderbot_request);
                         self.props.parent.send_message(crate::Msg::SendRequest(request, callback));
                         builderbot.camera_stream.clear();
+                        builderbot.cameras_enabled = builderbot.descriptor.cameras.iter()
+                            .map(|camera| camera.device.clone())
+                            .collect();
                         self.camera_dialog_active = true;
                     },
                     true => {
@@ -165,6 +224,7 @@ impl Component for Card {
                         let builderbot_request = Request::CameraStreamEnable(false);
                         let request = BackEndRequest::BuilderBotRequest(builderbot.descriptor.id.clone(), builderbot_request);
                         self.props.parent.send_message(crate::Msg::SendRequest(request, callback));
+                        builderbot.cameras_enabled.clear();
                         self.camera_dialog_active = false;
                     }
                 }
@@ -193,14 +253,27 @@ impl Component for Card {
             }
         };
 
+        /* quarantined robots are greyed out */
+        let style = match builderbot.quarantined {
+            true => "opacity: 0.5",
+            false => "",
+        };
         html! {
-            <div class="card">
+            <div class="card" style=style>
                 <header class="card-header">
                     <nav class="card-header-title is-shadowless has-background-white-ter level is-mobile">
                         <div class="level-left">
                             <p class="level-item subtitle is-size-4">{ &builderbot.descriptor.id }</p>
                         </div>
                         <div class="level-right">
+                            {
+                                match builderbot.quarantined {
+                                    true => html! { <span class="level-item tag is-warning">{ "Quarantined" }</span> },
+                                    false => html! {},
+                                }
+                            }
+                            { crate::health::render(&builderbot.health) }
+                            { render_open_tickets(builderbot.open_tickets) }
                             <figure class="level-item image mx-0 is-48x48">
                                 <img src=format!("images/batt{}.svg", batt_level) title=batt_info/>
                             </figure>
@@ -210,18 +283,69 @@ impl Component for Card {
                 <div class="card-content">
                     <div class="content">
                         { self.render_duovero(&builderbot) }
+                        { render_upload(&builderbot.upload) }
                         { self.render_identifiers(&builderbot) }
+                        { crate::asset::render(&builderbot.asset) }
                     </div>
                 </div>
                 { self.render_menu(&builderbot) }
                 { self.render_camera_modal(&builderbot) }
+                { self.render_asset_modal(&builderbot) }
+                { self.render_telemetry_modal(&builderbot) }
                 { self.render_error_modal() }
             </div>
         }
     }
 }
 
+fn render_upload(upload: &Option<(String, u64, u64)>) -> Html {
+    match upload {
+        Some((filename, sent, total)) => html! {
+            <div>
+                <p>{ format!("Uploading {}: {:.1} of {:.1} MB", filename,
+                    *sent as f64 / 1.0e6, *total as f64 / 1.0e6) }</p>
+                <progress class="progress is-small is-info" value=sent.to_string() max=total.to_string() />
+            </div>
+        },
+        None => html! {},
+    }
+}
+
+fn render_open_tickets(open_tickets: usize) -> Html {
+    match open_tickets {
+        0 => html! {},
+        count => html! {
+            <span class="level-item icon is-medium has-text-danger" title=format!("{} open ticket(s)", count)>
+                <i class="mdi mdi-24px mdi-ticket" />
+            </span>
+        }
+    }
+}
+
 impl Card {
+    fn render_telemetry_modal(&self, builderbot: &Instance) -> Html {
+        match self.telemetry_dialog_active {
+            true => html! {
+                <crate::telemetry::Viewer robot=builderbot.descriptor.id.clone()
+                                          window=builderbot.telemetry.clone()
+                                          parent=self.props.parent.clone()
+                                          onclose=self.link.callback(|_| Msg::ToggleTelemetryDialog) />
+            },
+            false => html! {},
+        }
+    }
+
+    fn render_asset_modal(&self, builderbot: &Instance) -> Html {
+        match self.asset_dialog_active {
+            true => html! {
+                <crate::asset::Editor asset=builderbot.asset.clone()
+                                      parent=self.props.parent.clone()
+                                      onclose=self.link.callback(|_| Msg::ToggleAssetDialog) />
+            },
+            false => html! {},
+        }
+    }
+
     fn render_camera_modal(&self, builderbot: &Instance) -> Html {
         if self.camera_dialog_active {
             let disable_onclick = self.link.callback(|_| Msg::ToggleCameraStream);
@@ -231,7 +355,7 @@ impl Card {
                     <div style="width:50%" class="modal-content">
                         <div class="container is-clipped">
                             <div class="columns is-multiline is-mobile"> { 
-                                builderbot.camera_stream.iter().map(|(id, result)| match result {
+                                builderbot.camera_stream.iter().map(|(id, stream)| match &stream.frame {
                                     Ok(encoded) => html! {
                                         <div class="column is-half">
                                             <figure class="image">
@@ -414,7 +538,14 @@ impl Card {
     }
 
     fn render_menu(&self, builderbot: &Instance) -> Html {
+        let callback = Some(self.link.callback(|result| Msg::SetError(result)));
+        let request = BackEndRequest::QuarantineRequest(builderbot.descriptor.id.clone(), !builderbot.quarantined);
+        let quarantine_onclick =
+            self.props.parent.callback(move |_| crate::Msg::SendRequest(request.clone(), callback.clone()));
+
         let toggle_camera_stream_onclick = self.link.callback(|_| Msg::ToggleCameraStream);
+        let toggle_asset_dialog_onclick = self.link.callback(|_| Msg::ToggleAssetDialog);
+        let toggle_telemetry_dialog_onclick = self.link.callback(|_| Msg::ToggleTelemetryDialog);
 
         let callback = Some(self.link.callback(|result| Msg::SetError(result)));
         let builderbot_request = Request::DuoVeroReboot;
@@ -436,6 +567,11 @@ impl Card {
 
         html! {
             <footer class="card-footer">
+                <a class="card-footer-item" onclick=quarantine_onclick> {
+                    if builderbot.quarantined { "Release" } else { "Quarantine" }
+                } </a>
+                <a class="card-footer-item" onclick=toggle_asset_dialog_onclick>{ "Metadata" }</a>
+                <a class="card-footer-item" onclick=toggle_telemetry_dialog_onclick>{ "Telemetry" }</a>
                 {
                     match builderbot.duovero {
                         DuoVero::Connected {..} => html! {
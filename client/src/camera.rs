@@ -0,0 +1,238 @@
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+use shared::BackEndRequest;
+use wasm_bindgen::JsValue;
+use yew::prelude::*;
+
+use crate::{builderbot, drone, pipuck};
+
+/* the weight of the most recent frame in the moving average of the frame rate */
+const FPS_SMOOTHING: f64 = 0.2;
+
+/* the most recent frame from a camera, encoded in base64 */
+pub struct Stream {
+    pub frame: Result<String, String>,
+    /* the time at which the frame was received in milliseconds since the epoch */
+    pub timestamp: f64,
+    pub fps: f64,
+}
+
+impl Default for Stream {
+    fn default() -> Self {
+        Self {
+            frame: Err(String::from("Waiting for frames")),
+            timestamp: 0.0,
+            fps: 0.0,
+        }
+    }
+}
+
+impl Stream {
+    pub fn update(&mut self, frame: Result<String, String>) {
+        let now = js_sys::Date::now();
+        if self.timestamp > 0.0 && now > self.timestamp {
+            let fps = 1000.0 / (now - self.timestamp);
+            self.fps = match self.fps > 0.0 {
+                true => FPS_SMOOTHING * fps + (1.0 - FPS_SMOOTHING) * self.fps,
+                false => fps,
+            };
+        }
+        self.timestamp = now;
+        self.frame = frame;
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum Robot {
+    BuilderBot,
+    Drone,
+    PiPuck,
+}
+
+impl Robot {
+    /* the path under which the images of the cameras of this type of robot are captured */
+    fn snapshot_path(self) -> &'static str {
+        match self {
+            Robot::BuilderBot => "snapshot/builderbot",
+            Robot::Drone => "snapshot/drone",
+            Robot::PiPuck => "snapshot/pipuck",
+        }
+    }
+}
+
+pub struct Panel {
+    link: ComponentLink<Self>,
+    props: Props,
+}
+
+#[derive(Clone, Properties)]
+pub struct Props {
+    pub parent: ComponentLink<crate::UserInterface>,
+    pub builderbots: HashMap<String, Rc<RefCell<builderbot::Instance>>>,
+    pub drones: HashMap<String, Rc<RefCell<drone::Instance>>>,
+    pub pipucks: HashMap<String, Rc<RefCell<pipuck::Instance>>>,
+}
+
+pub enum Msg {
+    SetCameraEnabled(Robot, String, String, bool),
+}
+
+/* a camera in the grid, i.e., the robot, the device, whether it is enabled, and its stream */
+struct Entry<'a> {
+    robot: Robot,
+    id: &'a str,
+    device: &'a str,
+    enabled: bool,
+    stream: Option<&'a Stream>,
+}
+
+impl Component for Panel {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(props: Props, link: ComponentLink<Self>) -> Self {
+        Panel { link, props }
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            Msg::SetCameraEnabled(robot, id, camera, enable) => {
+                /* update the state of the instance and discard any frame from when the camera was last enabled */
+                let request = match robot {
+                    Robot::BuilderBot => self.props.builderbots.get(&id).map(|builderbot| {
+                        let mut builderbot = builderbot.borrow_mut();
+                        builderbot.set_camera_enabled(&camera, enable);
+                        let request = shared::builderbot::Request::CameraDeviceStreamEnable(camera, enable);
+                        BackEndRequest::BuilderBotRequest(id, request)
+                    }),
+                    Robot::Drone => self.props.drones.get(&id).map(|drone| {
+                        let mut drone = drone.borrow_mut();
+                        drone.set_camera_enabled(&camera, enable);
+                        let request = shared::drone::Request::CameraDeviceStreamEnable(camera, enable);
+                        BackEndRequest::DroneRequest(id, request)
+                    }),
+                    Robot::PiPuck => self.props.pipucks.get(&id).map(|pipuck| {
+                        let mut pipuck = pipuck.borrow_mut();
+                        pipuck.set_camera_enabled(&camera, enable);
+                        let request = shared::pipuck::Request::CameraDeviceStreamEnable(camera, enable);
+                        BackEndRequest::PiPuckRequest(id, request)
+                    }),
+                };
+                if let Some(request) = request {
+                    self.props.parent.send_message(crate::Msg::SendRequest(request, None));
+                }
+                true
+            }
+        }
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        self.props = props;
+        true
+    }
+
+    fn view(&self) -> Html {
+        let builderbots = self.props.builderbots.values()
+            .map(|builderbot| builderbot.borrow())
+            .collect::<Vec<_>>();
+        let drones = self.props.drones.values()
+            .map(|drone| drone.borrow())
+            .collect::<Vec<_>>();
+        let pipucks = self.props.pipucks.values()
+            .map(|pipuck| pipuck.borrow())
+            .collect::<Vec<_>>();
+        let mut entries = builderbots.iter()
+            .flat_map(|builderbot| builderbot.descriptor.cameras.iter().map(move |camera| Entry {
+                robot: Robot::BuilderBot,
+                id: &builderbot.descriptor.id,
+                device: &camera.device,
+                enabled: builderbot.cameras_enabled.contains(&camera.device),
+                stream: builderbot.camera_stream.get(&camera.device),
+            }))
+            .chain(drones.iter()
+                .flat_map(|drone| drone.descriptor.cameras.iter().map(move |camera| Entry {
+                    robot: Robot::Drone,
+                    id: &drone.descriptor.id,
+                    device: &camera.device,
+                    enabled: drone.cameras_enabled.contains(&camera.device),
+                    stream: drone.camera_stream.get(&camera.device),
+                })))
+            .chain(pipucks.iter()
+                .flat_map(|pipuck| pipuck.descriptor.cameras.iter().map(move |camera| Entry {
+                    robot: Robot::PiPuck,
+                    id: &pipuck.descriptor.id,
+                    device: &camera.device,
+                    enabled: pipuck.cameras_enabled.contains(&camera.device),
+                    stream: pipuck.camera_stream.get(&camera.device),
+                })))
+            .collect::<Vec<_>>();
+        entries.sort_by(|lhs, rhs| (lhs.id, lhs.device).cmp(&(rhs.id, rhs.device)));
+        html! {
+            <>
+                <div class="column is-full">
+                    <p class="has-text-grey">{ "Enable the cameras to stream in the grid below. Only the enabled cameras are streamed by the robots." }</p>
+                </div>
+                { entries.iter().map(|entry| self.render_entry(entry)).collect::<Html>() }
+            </>
+        }
+    }
+}
+
+impl Panel {
+    fn render_entry(&self, entry: &Entry) -> Html {
+        let (robot, id, device, enable) =
+            (entry.robot, entry.id.to_owned(), entry.device.to_owned(), !entry.enabled);
+        let onchange = self.link.callback(move |_| Msg::SetCameraEnabled(robot, id.clone(), device.clone(), enable));
+        /* a single image at the resolution for still images, e.g., for calibration */
+        let snapshot = format!("{}/{}?camera={}", entry.robot.snapshot_path(), entry.id,
+            String::from(js_sys::encode_uri_component(entry.device)));
+        let (content, caption) = match (entry.enabled, entry.stream) {
+            (false, _) => (html! {
+                <p class="has-text-grey">{ "Disabled" }</p>
+            }, String::new()),
+            (true, None) => (html! {
+                <p class="has-text-grey">{ "Waiting for frames" }</p>
+            }, String::new()),
+            (true, Some(stream)) => {
+                let content = match &stream.frame {
+                    Ok(encoded) => html! {
+                        <img src=format!("data:image/jpeg;base64,{}", encoded) />
+                    },
+                    Err(error) => html! {
+                        <p class="has-text-danger">{ error }</p>
+                    },
+                };
+                let caption = match stream.timestamp > 0.0 {
+                    true => {
+                        let timestamp = js_sys::Date::new(&JsValue::from_f64(stream.timestamp))
+                            .to_locale_time_string("en-GB");
+                        format!("{} ({:.1} FPS)", String::from(timestamp), stream.fps)
+                    },
+                    false => String::new(),
+                };
+                (content, caption)
+            }
+        };
+        html! {
+            <div class="column is-full-mobile is-half-tablet is-one-third-widescreen is-one-quarter-fullhd">
+                <div class="card">
+                    <header class="card-header">
+                        <p class="card-header-title">{ format!("{}: {}", entry.id, entry.device) }</p>
+                        <div class="card-header-icon">
+                            <label class="checkbox">
+                                <input type="checkbox" checked=entry.enabled onchange=onchange />
+                                { " Enabled" }
+                            </label>
+                        </div>
+                    </header>
+                    <div class="card-image">
+                        <figure class="image">{ content }</figure>
+                    </div>
+                    <footer class="card-footer">
+                        <p class="card-footer-item is-family-monospace">{ caption }</p>
+                        <a class="card-footer-item" href=snapshot download="">{ "Capture image" }</a>
+                    </footer>
+                </div>
+            </div>
+        }
+    }
+}
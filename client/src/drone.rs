@@ -1,14 +1,19 @@
-use std::{cell::RefCell, collections::HashMap, net::Ipv4Addr, rc::Rc};
-use shared::{BackEndRequest, drone::{Descriptor, Request, Update}};
+use std::{cell::RefCell, collections::{HashMap, HashSet}, net::Ipv4Addr, rc::Rc};
+use shared::{BackEndRequest, drone::{Descriptor, FirmwareUpload, Frame, LogDownload, MissionItem, PixhawkLog, Request, Update, XbeeNode}};
 use web_sys::HtmlInputElement;
 use yew::{prelude::*, web_sys::HtmlTextAreaElement};
+use yew::services::reader::{File, FileData, ReaderService, ReaderTask};
 
 enum Xbee {
     Connected {
         addr: Ipv4Addr,
         signal: Result<i32, String>,
         battery: Result<i32, String>,
+        identifier: Option<u8>,
+        firmware_version: Option<u32>,
+        parameters: HashMap<String, Vec<u8>>,
         terminal: String,
+        mavlink_loss: Option<f32>,
     },
     Disconnected,
 }
@@ -25,11 +30,32 @@ enum UpCore {
 pub struct Instance {
     pub descriptor: Descriptor,
     pub optitrack_pos: [f32; 3],
+    pub open_tickets: usize,
     upcore: UpCore,
     upcore_power: bool,
     xbee: Xbee,
     pixhawk_power: bool,
-    camera_stream: HashMap<String, Result<String, String>>,
+    /* the state of the most recent firmware upload to the Pixhawk */
+    pixhawk_firmware: Option<FirmwareUpload>,
+    /* the logs on the Pixhawk and the state of the most recent download */
+    pixhawk_logs: Option<Vec<PixhawkLog>>,
+    pixhawk_log_download: Option<LogDownload>,
+    pub camera_stream: HashMap<String, crate::camera::Stream>,
+    pub battery_history: crate::battery::History,
+    /* the cameras that are being streamed */
+    pub cameras_enabled: HashSet<String>,
+    /* the robot is excluded from the arena */
+    pub quarantined: bool,
+    /* the health of the robot as assessed by the supervisor */
+    pub health: Option<shared::health::Health>,
+    /* the recent values of the metrics of the robot, if they have been requested */
+    pub telemetry: Option<shared::telemetry::Window>,
+    /* the metadata of the robot, e.g., its serial numbers */
+    pub asset: shared::asset::Asset,
+    /* the file that is being uploaded, the number of bytes sent, and the total number of bytes */
+    upload: Option<(String, u64, u64)>,
+    /* the nodes found by the most recent Xbee node discovery */
+    pub xbee_nodes: Option<Vec<XbeeNode>>,
 }
 
 // a lot of stuff here seems like it should be implemented directly on the component,
@@ -37,25 +63,54 @@ pub struct Instance {
 impl Instance {
     pub fn new(descriptor: Descriptor) -> Self {
         Self { 
+            asset: shared::asset::Asset { robot: descriptor.id.clone(), ..Default::default() },
             descriptor, 
             optitrack_pos: [0.0, 0.0, 0.0],
+            open_tickets: 0,
             upcore: UpCore::Disconnected,
             upcore_power: false,
             xbee: Xbee::Disconnected,
             pixhawk_power: false,
+            pixhawk_firmware: None,
+            pixhawk_logs: None,
+            pixhawk_log_download: None,
             camera_stream: Default::default(),
+            battery_history: Default::default(),
+            cameras_enabled: Default::default(),
+            quarantined: false,
+            health: None,
+            telemetry: None,
+            upload: None,
+            xbee_nodes: None,
         }
     }
 
+    pub fn is_xbee_connected(&self) -> bool {
+        match self.xbee {
+            Xbee::Connected { .. } => true,
+            Xbee::Disconnected => false,
+        }
+    }
+
+    pub fn set_camera_enabled(&mut self, camera: &str, enable: bool) {
+        self.camera_stream.remove(camera);
+        match enable {
+            true => self.cameras_enabled.insert(camera.to_owned()),
+            false => self.cameras_enabled.remove(camera),
+        };
+    }
+
     pub fn update(&mut self, update: Update) {
         match update {
             Update::Battery(reading) => if let Xbee::Connected { battery, ..} = &mut self.xbee {
                 *battery = Ok(reading);
+                self.battery_history.record(reading);
             },
             Update::Camera { camera, result } => {
                 self.camera_stream
-                    .insert(camera, result
-                        .map(|bytes| base64::encode(bytes)));
+                    .entry(camera)
+                    .or_default()
+                    .update(result.map(|bytes| base64::encode(bytes)));
             },
             Update::FernbedienungConnected(addr) => 
                 self.upcore = UpCore::Connected {
@@ -74,23 +129,65 @@ impl Instance {
                     addr,
                     battery: Err(String::from("Unknown")),
                     signal: Err(String::from("Unknown")),
+                    identifier: None,
+                    firmware_version: None,
+                    parameters: Default::default(),
                     terminal: Default::default(),
+                    mavlink_loss: None,
                 },
-            Update::XbeeDisconnected => 
-                self.xbee = Xbee::Disconnected,
+            Update::XbeeDisconnected => {
+                self.xbee = Xbee::Disconnected;
+                self.battery_history.clear();
+            },
             Update::XbeeSignal(strength) => if let Xbee::Connected { signal, ..} = &mut self.xbee {
                     *signal = Ok(strength);
             },
             Update::Bash(response) => if let UpCore::Connected { terminal, ..} = &mut self.upcore {
                 terminal.push_str(&response);
             },
+            Update::Upload { filename, sent, total } => {
+                self.upload = match sent < total {
+                    true => Some((filename, sent, total)),
+                    false => None,
+                };
+            },
             Update::Mavlink(response) => if let Xbee::Connected { terminal, ..} = &mut self.xbee {
                 terminal.push_str(&response);
             },
+            Update::MavlinkLoss(loss) => if let Xbee::Connected { mavlink_loss, ..} = &mut self.xbee {
+                *mavlink_loss = Some(loss);
+            },
+            Update::XbeeIdentifier(reading) => if let Xbee::Connected { identifier, ..} = &mut self.xbee {
+                *identifier = Some(reading);
+            },
+            Update::XbeeFirmwareVersion(version) => if let Xbee::Connected { firmware_version, ..} = &mut self.xbee {
+                *firmware_version = Some(version);
+            },
+            Update::XbeeParameter { parameter, value } => if let Xbee::Connected { parameters, ..} = &mut self.xbee {
+                parameters.insert(parameter, value);
+            },
+            Update::XbeeNodes(nodes) => {
+                self.xbee_nodes = Some(nodes);
+            },
             Update::PowerState { upcore, pixhawk } => {
                 self.pixhawk_power = pixhawk;
                 self.upcore_power = upcore;
             },
+            Update::PixhawkFirmware(state) => {
+                self.pixhawk_firmware = Some(state);
+            },
+            Update::PixhawkLogs(logs) => {
+                self.pixhawk_logs = Some(logs);
+            },
+            Update::PixhawkLogDownload(state) => {
+                self.pixhawk_log_download = Some(state);
+            },
+            /* the armed state is only used for the usage of the drone */
+            Update::Armed(_) => {},
+            /* the temperature is only used for the health of the drone */
+            Update::Temperature(_) => {},
+            /* faults in the kernel messages are reported as arena events */
+            Update::KernelFault(_) => {},
         }
     }
 }
@@ -108,7 +205,19 @@ pub struct Card {
     mavlink_terminal_visible: bool,
     mavlink_textarea: NodeRef,
     mavlink_input: NodeRef,
+    xbee_parameters_visible: bool,
+    xbee_parameter_input: NodeRef,
+    xbee_value_input: NodeRef,
+    mission_visible: bool,
+    mission_textarea: NodeRef,
+    supervised_flight_visible: bool,
+    supervised_flight_inputs: [NodeRef; 3],
+    firmware_visible: bool,
+    firmware_task: Option<ReaderTask>,
+    logs_visible: bool,
     camera_dialog_active: bool,
+    asset_dialog_active: bool,
+    telemetry_dialog_active: bool,
     error: Result<(), String>,
 }
 
@@ -124,8 +233,23 @@ pub enum Msg {
     ToggleBashTerminal,
     ToggleMavlinkTerminal,
     ToggleCameraStream,
+    ToggleAssetDialog,
+    ToggleTelemetryDialog,
     SendBashCommand,
     SendMavlinkCommand,
+    ToggleXbeeParameters,
+    GetXbeeParameter,
+    SetXbeeParameter,
+    ToggleMission,
+    UploadMission,
+    ToggleSupervisedFlight,
+    StartSupervisedFlight,
+    ToggleFirmware,
+    ReadFirmware(File),
+    UploadFirmware(Vec<u8>),
+    ToggleLogs,
+    ListLogs,
+    DownloadLog(u16),
 }
 
 // is it possible to just add a callback to the update method
@@ -144,7 +268,19 @@ impl Component for Card {
             mavlink_terminal_visible: false,
             mavlink_textarea: NodeRef::default(),
             mavlink_input: NodeRef::default(),
+            xbee_parameters_visible: false,
+            xbee_parameter_input: NodeRef::default(),
+            xbee_value_input: NodeRef::default(),
+            mission_visible: false,
+            mission_textarea: NodeRef::default(),
+            supervised_flight_visible: false,
+            supervised_flight_inputs: Default::default(),
+            firmware_visible: false,
+            firmware_task: None,
+            logs_visible: false,
             camera_dialog_active: false,
+            asset_dialog_active: false,
+            telemetry_dialog_active: false,
             error: Ok(()),
         }
     }
@@ -234,6 +370,137 @@ impl Component for Card {
                 }
                 true
             },
+            Msg::ToggleXbeeParameters => {
+                self.xbee_parameters_visible = !self.xbee_parameters_visible;
+                true
+            },
+            Msg::GetXbeeParameter => match self.xbee_parameter_input.cast::<HtmlInputElement>() {
+                Some(parameter) => {
+                    let callback = Some(self.link.callback(|result| Msg::SetError(result)));
+                    let drone_request = Request::XbeeParameterGet(parameter.value());
+                    let request = BackEndRequest::DroneRequest(drone.descriptor.id.clone(), drone_request);
+                    self.props.parent.send_message(crate::Msg::SendRequest(request, callback));
+                    false
+                },
+                _ => false
+            },
+            Msg::SetXbeeParameter => match (self.xbee_parameter_input.cast::<HtmlInputElement>(),
+                                            self.xbee_value_input.cast::<HtmlInputElement>()) {
+                (Some(parameter), Some(value)) => match parse_parameter_value(&value.value()) {
+                    Ok(value) => {
+                        let callback = Some(self.link.callback(|result| Msg::SetError(result)));
+                        let drone_request = Request::XbeeParameterSet(parameter.value(), value);
+                        let request = BackEndRequest::DroneRequest(drone.descriptor.id.clone(), drone_request);
+                        self.props.parent.send_message(crate::Msg::SendRequest(request, callback));
+                        false
+                    },
+                    Err(error) => {
+                        self.error = Err(error);
+                        true
+                    }
+                },
+                _ => false
+            },
+            Msg::ToggleMission => {
+                self.mission_visible = !self.mission_visible;
+                true
+            },
+            Msg::UploadMission => match self.mission_textarea.cast::<HtmlTextAreaElement>() {
+                Some(textarea) => match parse_mission(&textarea.value()) {
+                    Ok(items) => {
+                        let callback = Some(self.link.callback(|result| Msg::SetError(result)));
+                        let drone_request = Request::MissionUpload(items);
+                        let request = BackEndRequest::DroneRequest(drone.descriptor.id.clone(), drone_request);
+                        self.props.parent.send_message(crate::Msg::SendRequest(request, callback));
+                        false
+                    },
+                    Err(error) => {
+                        self.error = Err(error);
+                        true
+                    }
+                },
+                _ => false
+            },
+            Msg::ToggleSupervisedFlight => {
+                self.supervised_flight_visible = !self.supervised_flight_visible;
+                true
+            },
+            Msg::StartSupervisedFlight => {
+                let target = self.supervised_flight_inputs.iter()
+                    .map(|input| input.cast::<HtmlInputElement>()
+                        .and_then(|input| input.value().trim().parse::<f32>().ok()))
+                    .collect::<Option<Vec<_>>>();
+                match target.as_deref() {
+                    Some(&[x, y, z]) => {
+                        let callback = Some(self.link.callback(|result| Msg::SetError(result)));
+                        let drone_request = Request::SupervisedFlight(Some([x, y, z]));
+                        let request = BackEndRequest::DroneRequest(drone.descriptor.id.clone(), drone_request);
+                        self.props.parent.send_message(crate::Msg::SendRequest(request, callback));
+                        false
+                    },
+                    _ => {
+                        self.error = Err(String::from("The target position must consist of three numbers"));
+                        true
+                    }
+                }
+            },
+            Msg::ToggleFirmware => {
+                self.firmware_visible = !self.firmware_visible;
+                true
+            },
+            Msg::ReadFirmware(file) => {
+                let callback = self.link.callback(|FileData { content, .. }| Msg::UploadFirmware(content));
+                match ReaderService::read_file(file, callback) {
+                    Ok(task) => {
+                        self.firmware_task = Some(task);
+                        false
+                    },
+                    Err(error) => {
+                        self.error = Err(format!("Could not read firmware: {}", error));
+                        true
+                    }
+                }
+            },
+            Msg::UploadFirmware(firmware) => {
+                self.firmware_task = None;
+                let callback = Some(self.link.callback(|result| Msg::SetError(result)));
+                let drone_request = Request::PixhawkFirmwareUpload(firmware);
+                let request = BackEndRequest::DroneRequest(drone.descriptor.id.clone(), drone_request);
+                self.props.parent.send_message(crate::Msg::SendRequest(request, callback));
+                drone.pixhawk_firmware = Some(FirmwareUpload::Progress(String::from("Upload"), 0.0));
+                true
+            },
+            Msg::ToggleLogs => {
+                self.logs_visible = !self.logs_visible;
+                true
+            },
+            Msg::ListLogs => {
+                let callback = Some(self.link.callback(|result| Msg::SetError(result)));
+                let drone_request = Request::PixhawkLogList;
+                let request = BackEndRequest::DroneRequest(drone.descriptor.id.clone(), drone_request);
+                self.props.parent.send_message(crate::Msg::SendRequest(request, callback));
+                false
+            },
+            Msg::DownloadLog(log) => {
+                let callback = Some(self.link.callback(|result| Msg::SetError(result)));
+                let drone_request = Request::PixhawkLogDownload(log);
+                let request = BackEndRequest::DroneRequest(drone.descriptor.id.clone(), drone_request);
+                self.props.parent.send_message(crate::Msg::SendRequest(request, callback));
+                let size = drone.pixhawk_logs.iter()
+                    .flatten()
+                    .find(|entry| entry.id == log)
+                    .map_or(0, |entry| entry.size);
+                drone.pixhawk_log_download = Some(LogDownload::Progress(log, 0, size));
+                true
+            },
+            Msg::ToggleAssetDialog => {
+                self.asset_dialog_active = !self.asset_dialog_active;
+                true
+            },
+            Msg::ToggleTelemetryDialog => {
+                self.telemetry_dialog_active = !self.telemetry_dialog_active;
+                true
+            },
             Msg::ToggleCameraStream => {
                 match self.camera_dialog_active {
                     false => {
@@ -242,6 +509,9 @@ impl Component for Card {
                         let request = BackEndRequest::DroneRequest(drone.descriptor.id.clone(), drone_request);
                         self.props.parent.send_message(crate::Msg::SendRequest(request, callback));
                         drone.camera_stream.clear();
+                        drone.cameras_enabled = drone.descriptor.cameras.iter()
+                            .map(|camera| camera.device.clone())
+                            .collect();
                         self.camera_dialog_active = true;
                     },
                     true => {
@@ -249,6 +519,7 @@ impl Component for Card {
                         let drone_request = Request::CameraStreamEnable(false);
                         let request = BackEndRequest::DroneRequest(drone.descriptor.id.clone(), drone_request);
                         self.props.parent.send_message(crate::Msg::SendRequest(request, callback));
+                        drone.cameras_enabled.clear();
                         self.camera_dialog_active = false;
                     }
                 }
@@ -277,14 +548,27 @@ impl Component for Card {
             }
         };
 
+        /* quarantined robots are greyed out */
+        let style = match drone.quarantined {
+            true => "opacity: 0.5",
+            false => "",
+        };
         html! {
-            <div class="card">
+            <div class="card" style=style>
                 <header class="card-header">
                     <nav class="card-header-title is-shadowless has-background-white-ter level is-mobile">
                         <div class="level-left">
                             <p class="level-item subtitle is-size-4">{ &drone.descriptor.id }</p>
                         </div>
                         <div class="level-right">
+                            {
+                                match drone.quarantined {
+                                    true => html! { <span class="level-item tag is-warning">{ "Quarantined" }</span> },
+                                    false => html! {},
+                                }
+                            }
+                            { crate::health::render(&drone.health) }
+                            { render_open_tickets(drone.open_tickets) }
                             <figure class="level-item image mx-0 is-48x48">
                                 <img src=format!("images/batt{}.svg", batt_level) title=batt_info/>
                             </figure>
@@ -294,19 +578,125 @@ impl Component for Card {
                 <div class="card-content">
                     <div class="content">
                         { self.render_upcore(&drone) }
+                        { render_upload(&drone.upload) }
                         { self.render_xbee(&drone) }
+                        { self.render_xbee_parameters(&drone) }
+                        { self.render_mission(&drone) }
+                        { self.render_supervised_flight(&drone) }
+                        { self.render_firmware(&drone) }
+                        { self.render_logs(&drone) }
                         { self.render_identifiers(&drone) }
+                        { crate::asset::render(&drone.asset) }
                     </div>
                 </div>
                 { self.render_menu(&drone) }
                 { self.render_camera_modal(&drone) }
+                { self.render_asset_modal(&drone) }
+                { self.render_telemetry_modal(&drone) }
                 { self.render_error_modal() }
             </div>
         }
     }
 }
 
+/* values starting with 0x are interpreted as hexadecimal, all other values as text */
+fn parse_parameter_value(value: &str) -> Result<Vec<u8>, String> {
+    match value.trim().strip_prefix("0x") {
+        Some(hex) if !hex.is_ascii() => Err(format!("\"{}\" is not a valid hexadecimal value", value)),
+        Some(hex) => {
+            let hex = match hex.len() % 2 {
+                0 => hex.to_owned(),
+                _ => format!("0{}", hex),
+            };
+            (0..hex.len()).step_by(2)
+                .map(|index| u8::from_str_radix(&hex[index..index + 2], 16))
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|_| format!("\"{}\" is not a valid hexadecimal value", value))
+        },
+        None => Ok(value.as_bytes().to_vec()),
+    }
+}
+
+/* parse a mission with one item per line: `takeoff <altitude>`,
+   `waypoint <local|global> <x> <y> <altitude> [<hold>]`, or `land` */
+fn parse_mission(mission: &str) -> Result<Vec<MissionItem>, String> {
+    mission.lines()
+        .map(str::trim)
+        .enumerate()
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .map(|(index, line)| {
+            let error = || format!("Could not parse line {} of the mission: \"{}\"", index + 1, line);
+            let words = line.split_whitespace().collect::<Vec<_>>();
+            match words.as_slice() {
+                ["takeoff", altitude] => Ok(MissionItem::Takeoff {
+                    altitude: altitude.parse().map_err(|_| error())?
+                }),
+                ["waypoint", frame, x, y, altitude, hold @ ..] if hold.len() <= 1 => Ok(MissionItem::Waypoint {
+                    frame: match *frame {
+                        "local" => Frame::Local,
+                        "global" => Frame::Global,
+                        _ => return Err(error()),
+                    },
+                    x: x.parse().map_err(|_| error())?,
+                    y: y.parse().map_err(|_| error())?,
+                    altitude: altitude.parse().map_err(|_| error())?,
+                    hold: hold.first().map_or(Ok(0.0), |hold| hold.parse()).map_err(|_| error())?,
+                }),
+                ["land"] => Ok(MissionItem::Land),
+                _ => Err(error()),
+            }
+        })
+        .collect()
+}
+
+fn render_upload(upload: &Option<(String, u64, u64)>) -> Html {
+    match upload {
+        Some((filename, sent, total)) => html! {
+            <div>
+                <p>{ format!("Uploading {}: {:.1} of {:.1} MB", filename,
+                    *sent as f64 / 1.0e6, *total as f64 / 1.0e6) }</p>
+                <progress class="progress is-small is-info" value=sent.to_string() max=total.to_string() />
+            </div>
+        },
+        None => html! {},
+    }
+}
+
+fn render_open_tickets(open_tickets: usize) -> Html {
+    match open_tickets {
+        0 => html! {},
+        count => html! {
+            <span class="level-item icon is-medium has-text-danger" title=format!("{} open ticket(s)", count)>
+                <i class="mdi mdi-24px mdi-ticket" />
+            </span>
+        }
+    }
+}
+
 impl Card {
+    fn render_telemetry_modal(&self, drone: &Instance) -> Html {
+        match self.telemetry_dialog_active {
+            true => html! {
+                <crate::telemetry::Viewer robot=drone.descriptor.id.clone()
+                                          window=drone.telemetry.clone()
+                                          parent=self.props.parent.clone()
+                                          onclose=self.link.callback(|_| Msg::ToggleTelemetryDialog) />
+            },
+            false => html! {},
+        }
+    }
+
+    fn render_asset_modal(&self, drone: &Instance) -> Html {
+        match self.asset_dialog_active {
+            true => html! {
+                <crate::asset::Editor asset=drone.asset.clone()
+                                      parent=self.props.parent.clone()
+                                      onclose=self.link.callback(|_| Msg::ToggleAssetDialog) />
+            },
+            false => html! {},
+        }
+    }
+
     fn render_camera_modal(&self, drone: &Instance) -> Html {
         if self.camera_dialog_active {
             let disable_onclick = self.link.callback(|_| Msg::ToggleCameraStream);
@@ -316,7 +706,7 @@ impl Card {
                     <div style="width:50%" class="modal-content">
                         <div class="container is-clipped">
                             <div class="columns is-multiline is-mobile"> { 
-                                drone.camera_stream.iter().map(|(id, result)| match result {
+                                drone.camera_stream.iter().map(|(id, stream)| match &stream.frame {
                                     Ok(encoded) => html! {
                                         <div class="column is-half">
                                             <figure class="image">
@@ -470,14 +860,20 @@ impl Card {
     fn render_xbee(&self, drone: &Instance) -> Html {
         let (wifi_signal_level, wifi_signal_info) = match &drone.xbee {
             Xbee::Disconnected => (0, String::from("Disconnected")),
-            Xbee::Connected { signal, .. } => match signal {
-                Err(message) => (0, message.clone()),
-                Ok(level) => (match level {
-                    0..=24 => 1,
-                    25..=49 => 2,
-                    50..=74 => 3,
-                    _ => 4,
-                }, format!("{}%", level))
+            Xbee::Connected { signal, mavlink_loss, .. } => {
+                let (level, info) = match signal {
+                    Err(message) => (0, message.clone()),
+                    Ok(level) => (match level {
+                        0..=24 => 1,
+                        25..=49 => 2,
+                        50..=74 => 3,
+                        _ => 4,
+                    }, format!("{}%", level))
+                };
+                match mavlink_loss {
+                    Some(loss) => (level, format!("{}, {:.1}% MAVLink messages lost", info, loss)),
+                    None => (level, info),
+                }
             }
         };
         let (term_disabled, term_content) = match &drone.xbee {
@@ -489,6 +885,7 @@ impl Card {
             term_classes.push("is-hidden");
         }
         let term_btn_onclick = self.link.callback(|_| Msg::ToggleMavlinkTerminal);
+        let parameters_btn_onclick = self.link.callback(|_| Msg::ToggleXbeeParameters);
         let term_onkeydown = self.link.batch_callback(|event: KeyboardEvent| match event.key().as_ref() {
             "Enter" => Some(Msg::SendMavlinkCommand),
             _ => None,
@@ -500,6 +897,14 @@ impl Card {
                         <p class="level-item">{ "Xbee" }</p>
                     </div>
                     <div class="level-right">
+                        <button class="level-item button" onclick=parameters_btn_onclick disabled=term_disabled> {
+                            if self.xbee_parameters_visible {
+                                "Hide parameters"
+                            }
+                            else {
+                                "Show parameters"
+                            }
+                        } </button>
                         <button class="level-item button" onclick=term_btn_onclick disabled=term_disabled> {
                             if self.mavlink_terminal_visible {
                                 "Close Mavlink terminal"
@@ -564,9 +969,315 @@ impl Card {
         }
     }
 
+    fn render_xbee_parameters(&self, drone: &Instance) -> Html {
+        let (firmware_version, parameters) = match &drone.xbee {
+            Xbee::Connected { firmware_version, parameters, .. } if self.xbee_parameters_visible =>
+                (firmware_version, parameters),
+            _ => return html! {},
+        };
+        let get_onclick = self.link.callback(|_| Msg::GetXbeeParameter);
+        let set_onclick = self.link.callback(|_| Msg::SetXbeeParameter);
+        let callback = Some(self.link.callback(|result| Msg::SetError(result)));
+        let request = BackEndRequest::DroneRequest(drone.descriptor.id.clone(), Request::XbeeParameterWrite);
+        let write_onclick =
+            self.props.parent.callback(move |_| crate::Msg::SendRequest(request.clone(), callback.clone()));
+        let mut parameters = parameters.iter().collect::<Vec<_>>();
+        parameters.sort_by(|(a, _), (b, _)| a.cmp(b));
+        html! {
+            <>
+                <nav class="level is-mobile">
+                    <div class="level-left">
+                        <p class="level-item">{ "Xbee parameters" }</p>
+                    </div>
+                    <div class="level-right">
+                        <p class="level-item"> {
+                            firmware_version.map_or_else(|| "Firmware: unknown".to_owned(),
+                                |version| format!("Firmware: {:X}", version))
+                        } </p>
+                    </div>
+                </nav>
+                <div class="field has-addons">
+                    <div class="control">
+                        <input ref=self.xbee_parameter_input.clone()
+                            class="input is-family-monospace"
+                            type="text"
+                            maxlength="2"
+                            size="4"
+                            placeholder="PL"
+                            title="AT command, e.g., PL (power level), NI (node identifier), KY (encryption key)" />
+                    </div>
+                    <div class="control is-expanded">
+                        <input ref=self.xbee_value_input.clone()
+                            class="input is-family-monospace"
+                            type="text"
+                            placeholder="Value as text or as hexadecimal with a 0x prefix" />
+                    </div>
+                    <div class="control">
+                        <button class="button" onclick=get_onclick>{ "Read" }</button>
+                    </div>
+                    <div class="control">
+                        <button class="button" onclick=set_onclick>{ "Set" }</button>
+                    </div>
+                    <div class="control">
+                        <button class="button is-warning" onclick=write_onclick
+                            title="Write the parameters to non-volatile memory">{ "Write" }</button>
+                    </div>
+                </div>
+                <table class="table is-fullwidth is-narrow">
+                    <tbody> {
+                        parameters.into_iter().map(|(parameter, value)| html! {
+                            <tr>
+                                <td class="is-family-monospace">{ parameter }</td>
+                                <td class="is-family-monospace"> {
+                                    value.iter().map(|byte| format!("{:02X}", byte)).collect::<String>()
+                                } </td>
+                                <td>{ String::from_utf8_lossy(value) }</td>
+                            </tr>
+                        }).collect::<Html>()
+                    } </tbody>
+                </table>
+            </>
+        }
+    }
+
+    fn render_mission(&self, drone: &Instance) -> Html {
+        if !self.mission_visible {
+            return html! {};
+        }
+        let disabled = matches!(drone.xbee, Xbee::Disconnected);
+        let upload_onclick = self.link.callback(|_| Msg::UploadMission);
+        let callback = Some(self.link.callback(|result| Msg::SetError(result)));
+        let request = BackEndRequest::DroneRequest(drone.descriptor.id.clone(), Request::MissionStart);
+        let start_onclick =
+            self.props.parent.callback(move |_| crate::Msg::SendRequest(request.clone(), callback.clone()));
+        html! {
+            <>
+                <nav class="level is-mobile">
+                    <div class="level-left">
+                        <p class="level-item">{ "Mission" }</p>
+                    </div>
+                    <div class="level-right">
+                        <button class="level-item button" onclick=upload_onclick disabled=disabled>{ "Upload" }</button>
+                        <button class="level-item button is-warning" onclick=start_onclick disabled=disabled
+                            title="Arm the drone and start the uploaded mission">{ "Start" }</button>
+                    </div>
+                </nav>
+                <div class="field">
+                    <div class="control">
+                        <textarea ref=self.mission_textarea.clone()
+                            class="textarea is-family-monospace"
+                            placeholder="takeoff 1.5\nwaypoint local 1.0 0.0 1.5 5\nland" />
+                    </div>
+                </div>
+            </>
+        }
+    }
+
+    fn render_supervised_flight(&self, drone: &Instance) -> Html {
+        if !self.supervised_flight_visible {
+            return html! {};
+        }
+        let disabled = matches!(drone.xbee, Xbee::Disconnected) || drone.descriptor.optitrack_id.is_none();
+        let start_onclick = self.link.callback(|_| Msg::StartSupervisedFlight);
+        let callback = Some(self.link.callback(|result| Msg::SetError(result)));
+        let request = BackEndRequest::DroneRequest(drone.descriptor.id.clone(), Request::SupervisedFlight(None));
+        let stop_onclick =
+            self.props.parent.callback(move |_| crate::Msg::SendRequest(request.clone(), callback.clone()));
+        html! {
+            <>
+                <nav class="level is-mobile">
+                    <div class="level-left">
+                        <p class="level-item">{ "Supervised flight" }</p>
+                    </div>
+                </nav>
+                <div class="field has-addons"> {
+                    self.supervised_flight_inputs.iter().zip(["x", "y", "z"].iter()).map(|(input, axis)| html! {
+                        <div class="control is-expanded">
+                            <input ref=input.clone()
+                                class="input is-family-monospace"
+                                type="number"
+                                step="0.1"
+                                placeholder=*axis />
+                        </div>
+                    }).collect::<Html>()
+                }
+                    <div class="control">
+                        <button class="button is-warning" onclick=start_onclick disabled=disabled
+                            title="Stream setpoints for this position (in meters) to the Pixhawk">{ "Fly to" }</button>
+                    </div>
+                    <div class="control">
+                        <button class="button" onclick=stop_onclick disabled=disabled>{ "Stop" }</button>
+                    </div>
+                </div>
+            </>
+        }
+    }
+
+    fn render_firmware(&self, drone: &Instance) -> Html {
+        if !self.firmware_visible {
+            return html! {};
+        }
+        let disabled = matches!(drone.upcore, UpCore::Disconnected) ||
+            matches!(drone.pixhawk_firmware, Some(FirmwareUpload::Progress(..)));
+        let input_id = format!("{}_firmware", drone.descriptor.id);
+        let onchange = self.link.batch_callback(|value| match value {
+            ChangeData::Files(files) => files.get(0).map(Msg::ReadFirmware),
+            _ => None,
+        });
+        let state = match &drone.pixhawk_firmware {
+            Some(FirmwareUpload::Progress(stage, progress)) => html! {
+                <>
+                    <p>{ format!("{}: {:.1}%", stage, progress) }</p>
+                    <progress class="progress is-info" value=progress.to_string() max="100" />
+                </>
+            },
+            Some(FirmwareUpload::Complete) => html! {
+                <p class="has-text-success">{ "The firmware was programmed and verified" }</p>
+            },
+            Some(FirmwareUpload::Failed(error)) => html! {
+                <p class="has-text-danger">{ error }</p>
+            },
+            None => html! {},
+        };
+        html! {
+            <>
+                <nav class="level is-mobile">
+                    <div class="level-left">
+                        <p class="level-item">{ "Firmware" }</p>
+                    </div>
+                    <div class="level-right">
+                        <input id=input_id.clone() class="is-hidden" type="file" accept=".px4"
+                            onchange=onchange disabled=disabled />
+                        <label class="level-item button is-warning" for=input_id disabled=disabled
+                            title="Upload a PX4 firmware file (*.px4) to the Pixhawk">{ "Upload" }</label>
+                    </div>
+                </nav>
+                { state }
+            </>
+        }
+    }
+
+    fn render_logs(&self, drone: &Instance) -> Html {
+        if !self.logs_visible {
+            return html! {};
+        }
+        let disabled = matches!(drone.xbee, Xbee::Disconnected) ||
+            matches!(drone.pixhawk_log_download, Some(LogDownload::Progress(..)));
+        let list_onclick = self.link.callback(|_| Msg::ListLogs);
+        let logs = match &drone.pixhawk_logs {
+            Some(logs) if logs.is_empty() => html! {
+                <p class="has-text-grey">{ "There are no logs on the Pixhawk" }</p>
+            },
+            Some(logs) => html! {
+                <table class="table is-narrow is-fullwidth">
+                    <thead>
+                        <tr>
+                            <th>{ "Log" }</th>
+                            <th>{ "Time" }</th>
+                            <th>{ "Size" }</th>
+                            <th></th>
+                        </tr>
+                    </thead>
+                    <tbody> {
+                        logs.iter().map(|log| {
+                            let id = log.id;
+                            let onclick = self.link.callback(move |_| Msg::DownloadLog(id));
+                            /* the Pixhawk reports a time of zero if it did not know the time when the log was created */
+                            let time = match log.time_utc {
+                                0 => String::from("Unknown"),
+                                time => {
+                                    let time = wasm_bindgen::JsValue::from_f64(time as f64 * 1000.0);
+                                    js_sys::Date::new(&time).to_locale_string("en-GB", &wasm_bindgen::JsValue::UNDEFINED).into()
+                                },
+                            };
+                            html! {
+                                <tr>
+                                    <td>{ log.id }</td>
+                                    <td>{ time }</td>
+                                    <td>{ format!("{:.1} MB", log.size as f64 / 1e6) }</td>
+                                    <td>
+                                        <button class="button is-small" onclick=onclick disabled=disabled>{ "Download" }</button>
+                                    </td>
+                                </tr>
+                            }
+                        }).collect::<Html>()
+                    } </tbody>
+                </table>
+            },
+            None => html! {},
+        };
+        let state = match &drone.pixhawk_log_download {
+            Some(LogDownload::Progress(log, received, size)) => {
+                let progress = match size {
+                    0 => 0.0,
+                    size => *received as f64 * 100.0 / *size as f64,
+                };
+                html! {
+                    <>
+                        <p>{ format!("Downloading log {}: {:.1}%", log, progress) }</p>
+                        <progress class="progress is-info" value=progress.to_string() max="100" />
+                    </>
+                }
+            },
+            Some(LogDownload::Complete(log, path)) => html! {
+                <p class="has-text-success">{ format!("Log {} was saved to {}", log, path) }</p>
+            },
+            Some(LogDownload::Failed(log, error)) => html! {
+                <p class="has-text-danger">{ format!("Could not download log {}: {}", log, error) }</p>
+            },
+            None => html! {},
+        };
+        html! {
+            <>
+                <nav class="level is-mobile">
+                    <div class="level-left">
+                        <p class="level-item">{ "Logs" }</p>
+                    </div>
+                    <div class="level-right">
+                        <button class="level-item button" onclick=list_onclick disabled=disabled
+                            title="List the flight logs on the SD card of the Pixhawk">{ "Refresh" }</button>
+                    </div>
+                </nav>
+                { logs }
+                { state }
+            </>
+        }
+    }
+
     fn render_identifiers(&self, drone: &Instance) -> Html {
+        let configured = drone.descriptor.xbee_identifier;
+        let read = match drone.xbee {
+            Xbee::Connected { identifier, .. } => identifier,
+            Xbee::Disconnected => None,
+        };
+        /* flag drones whose Xbee reports a different identifier than the configuration */
+        let mismatch = match (configured, read) {
+            (Some(configured), Some(read)) => configured != read,
+            _ => false,
+        };
         html! {
             <>
+                <nav class="level is-mobile">
+                    <div class="level-left">
+                        <p class="level-item">{ "Xbee identifier" }</p>
+                    </div>
+                </nav>
+                <div class="columns is-multiline is-mobile">
+                    <div class="column is-half">
+                        <div class="notification has-text-centered">
+                            <p style="line-height:32px"> {
+                                configured.map_or_else(|| "-".to_owned(), |id| format!("Configured: {}", id))
+                            } </p>
+                        </div>
+                    </div>
+                    <div class="column is-half">
+                        <div class=classes!("notification", "has-text-centered", mismatch.then(|| "is-danger"))>
+                            <p style="line-height:32px"> {
+                                read.map_or_else(|| "-".to_owned(), |id| format!("Read: {}", id))
+                            } </p>
+                        </div>
+                    </div>
+                </div>
                 <nav class="level is-mobile">
                     <div class="level-left">
                         <p class="level-item">{ "Optitrack" }</p>
@@ -596,7 +1307,18 @@ impl Card {
     }
 
     fn render_menu(&self, drone: &Instance) -> Html {
+        let callback = Some(self.link.callback(|result| Msg::SetError(result)));
+        let request = BackEndRequest::QuarantineRequest(drone.descriptor.id.clone(), !drone.quarantined);
+        let quarantine_onclick =
+            self.props.parent.callback(move |_| crate::Msg::SendRequest(request.clone(), callback.clone()));
+
         let toggle_camera_stream_onclick = self.link.callback(|_| Msg::ToggleCameraStream);
+        let toggle_asset_dialog_onclick = self.link.callback(|_| Msg::ToggleAssetDialog);
+        let toggle_telemetry_dialog_onclick = self.link.callback(|_| Msg::ToggleTelemetryDialog);
+        let toggle_mission_onclick = self.link.callback(|_| Msg::ToggleMission);
+        let toggle_supervised_flight_onclick = self.link.callback(|_| Msg::ToggleSupervisedFlight);
+        let toggle_firmware_onclick = self.link.callback(|_| Msg::ToggleFirmware);
+        let toggle_logs_onclick = self.link.callback(|_| Msg::ToggleLogs);
 
         let callback = Some(self.link.callback(|result| Msg::SetError(result)));
         let drone_request = Request::PixhawkPowerEnable(true);
@@ -634,6 +1356,8 @@ impl Card {
         let halt_upcore_onclick =
             self.props.parent.callback(move |_| crate::Msg::SendRequest(request.clone(), callback.clone()));
 
+        let kernel_messages = format!("kernel_messages/drone/{}", drone.descriptor.id);
+
         let callback = Some(self.link.callback(|result| Msg::SetError(result)));
         let drone_request = Request::Identify;
         let request = BackEndRequest::DroneRequest(drone.descriptor.id.clone(), drone_request);
@@ -642,6 +1366,11 @@ impl Card {
 
         html! {
             <footer class="card-footer">
+                <a class="card-footer-item" onclick=quarantine_onclick> {
+                    if drone.quarantined { "Release" } else { "Quarantine" }
+                } </a>
+                <a class="card-footer-item" onclick=toggle_asset_dialog_onclick>{ "Metadata" }</a>
+                <a class="card-footer-item" onclick=toggle_telemetry_dialog_onclick>{ "Telemetry" }</a>
                 {
                     match drone.upcore {
                         UpCore::Connected {..} => html! {
@@ -686,6 +1415,15 @@ impl Card {
                                     <p class="dropdown-item has-text-grey-light">{ "Reboot" }</p>
                                 },
                             }
+                        } {
+                            match drone.upcore {
+                                UpCore::Connected {..} => html! {
+                                    <a class="dropdown-item" href=kernel_messages download="">{ "Kernel messages" }</a>
+                                },
+                                UpCore::Disconnected => html! {
+                                    <p class="dropdown-item has-text-grey-light">{ "Kernel messages" }</p>
+                                },
+                            }
                         } {
                             match drone.xbee {
                                 Xbee::Connected { .. } => match drone.upcore_power {
@@ -727,7 +1465,20 @@ impl Card {
                                     <p class="dropdown-item has-text-grey-light">{ "Power On" }</p>
                                 }
                             }
-                        } </div>
+                        }
+                            <a class="dropdown-item" onclick=toggle_mission_onclick> {
+                                if self.mission_visible { "Hide mission" } else { "Show mission" }
+                            } </a>
+                            <a class="dropdown-item" onclick=toggle_supervised_flight_onclick> {
+                                if self.supervised_flight_visible { "Hide supervised flight" } else { "Show supervised flight" }
+                            } </a>
+                            <a class="dropdown-item" onclick=toggle_firmware_onclick> {
+                                if self.firmware_visible { "Hide firmware" } else { "Show firmware" }
+                            } </a>
+                            <a class="dropdown-item" onclick=toggle_logs_onclick> {
+                                if self.logs_visible { "Hide logs" } else { "Show logs" }
+                            } </a>
+                        </div>
                     </div>
                 </div>
             </footer>
@@ -0,0 +1,78 @@
+use std::{collections::VecDeque, rc::Rc};
+use shared::event::{Event, Severity};
+use yew::prelude::*;
+
+pub struct Panel {
+    props: Props,
+}
+
+#[derive(Clone, Properties)]
+pub struct Props {
+    pub events: Rc<VecDeque<Event>>,
+}
+
+fn severity_tag(severity: Severity) -> Html {
+    let (class, label) = match severity {
+        Severity::Info => ("is-info", "Info"),
+        Severity::Warning => ("is-warning", "Warning"),
+        Severity::Error => ("is-danger", "Error"),
+    };
+    html! {
+        <span class=classes!("tag", class)>{ label }</span>
+    }
+}
+
+impl Component for Panel {
+    type Message = ();
+    type Properties = Props;
+
+    fn create(props: Props, _: ComponentLink<Self>) -> Self {
+        Panel { props }
+    }
+
+    fn update(&mut self, _: Self::Message) -> ShouldRender {
+        false
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        self.props = props;
+        true
+    }
+
+    fn view(&self) -> Html {
+        html! {
+            <div class="column is-full">
+                <div class="card">
+                    <header class="card-header">
+                        <p class="card-header-title">{ "Events" }</p>
+                    </header>
+                    <div class="card-content" style="max-height: 75vh; overflow-y: auto;">
+                        <table class="table is-fullwidth is-narrow">
+                            <thead>
+                                <tr>
+                                    <th>{ "Time" }</th>
+                                    <th>{ "Severity" }</th>
+                                    <th>{ "Robot" }</th>
+                                    <th>{ "Subsystem" }</th>
+                                    <th>{ "Message" }</th>
+                                </tr>
+                            </thead>
+                            <tbody> {
+                                /* the most recent events are shown first */
+                                self.props.events.iter().rev().map(|event| html! {
+                                    <tr>
+                                        <td class="is-family-monospace">{ &event.timestamp }</td>
+                                        <td>{ severity_tag(event.severity) }</td>
+                                        <td>{ event.robot.as_deref().unwrap_or("") }</td>
+                                        <td>{ &event.subsystem }</td>
+                                        <td>{ &event.message }</td>
+                                    </tr>
+                                }).collect::<Html>()
+                            } </tbody>
+                        </table>
+                    </div>
+                </div>
+            </div>
+        }
+    }
+}
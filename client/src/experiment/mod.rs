@@ -2,9 +2,10 @@ use std::cell::RefCell;
 use std::rc::Rc;
 use yew::prelude::*;
 
-use yew::{html, Component, ComponentLink, Html, ShouldRender};
+use yew::{html, ChangeData, Component, ComponentLink, Html, ShouldRender};
 
-use shared::experiment::{software::Software, Request};
+use shared::experiment::{software::Software, Readiness, Request};
+use shared::group::Group;
 
 use shared::BackEndRequest;
 
@@ -17,6 +18,12 @@ pub mod pipuck;
 pub struct Interface {
     link: ComponentLink<Self>,
     props: Props,
+    rigid_body_check: Option<Result<(), String>>,
+    start_result: Option<Result<(), String>>,
+    /* the group that is targeted by the control panel, all robots if none */
+    group: Option<String>,
+    group_result: Option<Result<(), String>>,
+    maintenance_result: Option<Result<(), String>>,
 }
 
 // what if properties was just drone::Instance itself?
@@ -26,11 +33,25 @@ pub struct Props {
     pub builderbot_software: Rc<RefCell<Software>>,
     pub drone_software: Rc<RefCell<Software>>,
     pub pipuck_software: Rc<RefCell<Software>>,
+    pub rigid_body_proposals: Rc<Vec<(String, Option<i32>)>>,
+    pub readiness: Rc<Option<Readiness>>,
+    pub groups: Rc<Vec<Group>>,
 }
 
 pub enum Msg {
-    StartExperiment,
+    /* start the experiment, skipping the readiness check if true */
+    StartExperiment(bool),
+    SetStartResult(Result<(), String>),
+    CheckReadiness,
     StopExperiment,
+    CheckRigidBodies,
+    SetRigidBodyCheck(Result<(), String>),
+    IdentifyRigidBodies,
+    SetGroup(Option<String>),
+    SendGroupRequest(shared::group::Request),
+    SetGroupResult(Result<(), String>),
+    RunMaintenance,
+    SetMaintenanceResult(Result<(), String>),
 }
 
 impl Component for Interface {
@@ -42,16 +63,37 @@ impl Component for Interface {
         Interface { 
             props,
             link,
+            rigid_body_check: None,
+            start_result: None,
+            group: None,
+            group_result: None,
+            maintenance_result: None,
         }
     }
 
     fn update(&mut self, message: Self::Message) -> ShouldRender {
         match message {
-            Msg::StartExperiment => {
+            Msg::StartExperiment(force) => {
                 let request = BackEndRequest::ExperimentRequest(Request::Start {
                     builderbot_software: self.props.builderbot_software.borrow().clone(),
                     pipuck_software: self.props.pipuck_software.borrow().clone(),
                     drone_software: self.props.drone_software.borrow().clone(),
+                    force,
+                    group: self.group.clone(),
+                });
+                let callback = self.link.callback(|result| Msg::SetStartResult(result));
+                self.props.parent.send_message(crate::Msg::SendRequest(request, Some(callback)));
+            },
+            Msg::SetStartResult(result) => {
+                self.start_result = Some(result);
+                return true;
+            },
+            Msg::CheckReadiness => {
+                let request = BackEndRequest::ExperimentRequest(Request::CheckReadiness {
+                    builderbot_software: self.props.builderbot_software.borrow().clone(),
+                    pipuck_software: self.props.pipuck_software.borrow().clone(),
+                    drone_software: self.props.drone_software.borrow().clone(),
+                    group: self.group.clone(),
                 });
                 self.props.parent.send_message(crate::Msg::SendRequest(request, None));
             },
@@ -59,11 +101,49 @@ impl Component for Interface {
                 let request = BackEndRequest::ExperimentRequest(Request::Stop);
                 self.props.parent.send_message(crate::Msg::SendRequest(request, None));
             },
+            Msg::CheckRigidBodies => {
+                let request = BackEndRequest::ExperimentRequest(Request::CheckRigidBodies);
+                let callback = self.link.callback(|result| Msg::SetRigidBodyCheck(result));
+                self.props.parent.send_message(crate::Msg::SendRequest(request, Some(callback)));
+            },
+            Msg::IdentifyRigidBodies => {
+                let request = BackEndRequest::ExperimentRequest(Request::IdentifyRigidBodies);
+                self.props.parent.send_message(crate::Msg::SendRequest(request, None));
+            },
+            Msg::SetRigidBodyCheck(result) => {
+                self.rigid_body_check = Some(result);
+                return true;
+            },
+            Msg::SetGroup(group) => {
+                self.group = group;
+                self.group_result = None;
+                return true;
+            },
+            Msg::SendGroupRequest(request) => {
+                if let Some(group) = self.group.clone() {
+                    let request = BackEndRequest::GroupRequest(group, request);
+                    let callback = self.link.callback(|result| Msg::SetGroupResult(result));
+                    self.props.parent.send_message(crate::Msg::SendRequest(request, Some(callback)));
+                }
+            },
+            Msg::SetGroupResult(result) => {
+                self.group_result = Some(result);
+                return true;
+            },
+            Msg::RunMaintenance => {
+                let callback = self.link.callback(|result| Msg::SetMaintenanceResult(result));
+                self.props.parent.send_message(crate::Msg::SendRequest(BackEndRequest::RunMaintenance, Some(callback)));
+            },
+            Msg::SetMaintenanceResult(result) => {
+                self.maintenance_result = Some(result);
+                return true;
+            },
         }
         false
     }
 
-    fn change(&mut self, _: Self::Properties) -> ShouldRender {
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        self.props = props;
         true
     }
 
@@ -88,11 +168,64 @@ impl Component for Interface {
                             </div>
                         </nav>
                     </header>
+                    {
+                        match &self.rigid_body_check {
+                            Some(Ok(_)) => html! {
+                                <div class="card-content">
+                                    <div class="notification is-success">{ "All rigid bodies are being tracked" }</div>
+                                </div>
+                            },
+                            Some(Err(error)) => html! {
+                                <div class="card-content">
+                                    <div class="notification is-danger">{ error }</div>
+                                </div>
+                            },
+                            None => html! {},
+                        }
+                    }
+                    {
+                        match &self.start_result {
+                            Some(Err(error)) => html! {
+                                <div class="card-content">
+                                    <div class="notification is-danger">{ error }</div>
+                                </div>
+                            },
+                            _ => html! {},
+                        }
+                    }
+                    {
+                        match &self.maintenance_result {
+                            Some(Ok(_)) => html! {
+                                <div class="card-content">
+                                    <div class="notification is-info">{ "Maintenance is running, the results are reported as events" }</div>
+                                </div>
+                            },
+                            Some(Err(error)) => html! {
+                                <div class="card-content">
+                                    <div class="notification is-danger">{ error }</div>
+                                </div>
+                            },
+                            None => html! {},
+                        }
+                    }
+                    { self.render_groups() }
+                    { self.render_readiness() }
+                    { self.render_rigid_body_proposals() }
                     <footer class="card-footer">
                         <a class="card-footer-item" 
-                           onclick=self.link.callback(|_| Msg::StartExperiment)>{ "Start experiment" }</a>
+                           onclick=self.link.callback(|_| Msg::CheckReadiness)>{ "Check readiness" }</a>
+                        <a class="card-footer-item" 
+                           onclick=self.link.callback(|_| Msg::StartExperiment(false))>{ "Start experiment" }</a>
+                        <a class="card-footer-item has-text-danger" 
+                           onclick=self.link.callback(|_| Msg::StartExperiment(true))>{ "Force start" }</a>
                         <a class="card-footer-item" 
                            onclick=self.link.callback(|_| Msg::StopExperiment)>{ "Stop experiment" }</a>
+                        <a class="card-footer-item" 
+                           onclick=self.link.callback(|_| Msg::CheckRigidBodies)>{ "Check rigid bodies" }</a>
+                        <a class="card-footer-item" 
+                           onclick=self.link.callback(|_| Msg::IdentifyRigidBodies)>{ "Identify rigid bodies" }</a>
+                        <a class="card-footer-item" 
+                           onclick=self.link.callback(|_| Msg::RunMaintenance)>{ "Run maintenance" }</a>
                     </footer>
                     </div>
                 </div>
@@ -100,4 +233,135 @@ impl Component for Interface {
             
         }
     }
-}
\ No newline at end of file
+}
+
+impl Interface {
+    fn render_groups(&self) -> Html {
+        if self.props.groups.is_empty() {
+            return html! {};
+        }
+        let onchange = self.link.callback(|value| match value {
+            ChangeData::Select(select) => match select.value() {
+                value if value.is_empty() => Msg::SetGroup(None),
+                value => Msg::SetGroup(Some(value)),
+            },
+            _ => Msg::SetGroup(None),
+        });
+        let selected = self.group.as_deref().unwrap_or("");
+        html! {
+            <div class="card-content">
+                <div class="field">
+                    <label class="label">{ "Group" }</label>
+                    <div class="control">
+                        <div class="select is-fullwidth">
+                            <select onchange=onchange>
+                                <option value="" selected=selected.is_empty()>{ "All robots" }</option>
+                                {
+                                    self.props.groups.iter().map(|group| html! {
+                                        <option value=group.name.clone() selected=group.name == selected>
+                                            { format!("{} ({})", group.name, group.members.join(", ")) }
+                                        </option>
+                                    }).collect::<Html>()
+                                }
+                            </select>
+                        </div>
+                    </div>
+                </div>
+                {
+                    match self.group {
+                        Some(_) => html! {
+                            <div class="buttons">
+                                <button class="button"
+                                    onclick=self.link.callback(|_| Msg::SendGroupRequest(shared::group::Request::StopExperiment))>
+                                    { "Stop group" }
+                                </button>
+                                <button class="button"
+                                    onclick=self.link.callback(|_| Msg::SendGroupRequest(shared::group::Request::Reboot))>
+                                    { "Reboot group" }
+                                </button>
+                                <button class="button is-danger"
+                                    onclick=self.link.callback(|_| Msg::SendGroupRequest(shared::group::Request::Halt))>
+                                    { "Halt group" }
+                                </button>
+                            </div>
+                        },
+                        None => html! {},
+                    }
+                }
+                {
+                    match &self.group_result {
+                        Some(Err(error)) => html! {
+                            <div class="notification is-danger">{ error }</div>
+                        },
+                        _ => html! {},
+                    }
+                }
+            </div>
+        }
+    }
+
+    fn render_readiness(&self) -> Html {
+        let readiness = match self.props.readiness.as_ref() {
+            Some(readiness) => readiness,
+            None => return html! {},
+        };
+        let (class, verdict) = match readiness.is_ready() {
+            true => ("is-success", "Go"),
+            false => ("is-danger", "No go"),
+        };
+        html! {
+            <div class="card-content">
+                <div class=classes!("notification", class)>{ verdict }</div>
+                <table class="table is-fullwidth">
+                    <tbody> {
+                        readiness.0.iter().map(|check| html! {
+                            <tr>
+                                <td>{ &check.subject }</td>
+                                <td> {
+                                    match check.failures.is_empty() {
+                                        true => html! { <span class="tag is-success">{ "Go" }</span> },
+                                        false => html! { <span class="tag is-danger">{ "No go" }</span> },
+                                    }
+                                } </td>
+                                <td> {
+                                    check.failures.iter().map(|failure| html! {
+                                        <p>{ failure }</p>
+                                    }).collect::<Html>()
+                                } </td>
+                            </tr>
+                        }).collect::<Html>()
+                    } </tbody>
+                </table>
+            </div>
+        }
+    }
+
+    fn render_rigid_body_proposals(&self) -> Html {
+        if self.props.rigid_body_proposals.is_empty() {
+            return html! {};
+        }
+        html! {
+            <div class="card-content">
+                <p class="has-text-weight-bold">{ "Proposed optitrack_id assignments" }</p>
+                <table class="table is-fullwidth">
+                    <thead>
+                        <tr><th>{ "Robot" }</th><th>{ "optitrack_id" }</th></tr>
+                    </thead>
+                    <tbody> {
+                        self.props.rigid_body_proposals.iter().map(|(robot, proposal)| html! {
+                            <tr>
+                                <td>{ robot }</td>
+                                <td> {
+                                    match proposal {
+                                        Some(optitrack_id) => optitrack_id.to_string(),
+                                        None => String::from("Not identified"),
+                                    }
+                                } </td>
+                            </tr>
+                        }).collect::<Html>()
+                    } </tbody>
+                </table>
+            </div>
+        }
+    }
+}
@@ -0,0 +1,30 @@
+use shared::health::{Health, Trend};
+use yew::prelude::*;
+
+/* the health of a robot as shown in the header of its card, the issues are shown on hover */
+pub fn render(health: &Option<Health>) -> Html {
+    let health = match health {
+        Some(health) => health,
+        None => return html! {},
+    };
+    let class = match health.score {
+        75..=100 => "level-item tag is-success",
+        40..=74 => "level-item tag is-warning",
+        _ => "level-item tag is-danger",
+    };
+    let icon = match health.trend {
+        Trend::Improving => "mdi mdi-trending-up",
+        Trend::Steady => "mdi mdi-trending-neutral",
+        Trend::Declining => "mdi mdi-trending-down",
+    };
+    let title = match health.issues.is_empty() {
+        true => String::from("No issues"),
+        false => health.issues.join("\n"),
+    };
+    html! {
+        <span class=class title=title>
+            { health.score }
+            <span class="icon is-small ml-1"><i class=icon /></span>
+        </span>
+    }
+}
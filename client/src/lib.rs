@@ -1,6 +1,6 @@
-use std::{cell::RefCell, collections::HashMap, convert::AsRef, rc::Rc};
+use std::{cell::RefCell, collections::{HashMap, VecDeque}, convert::AsRef, rc::Rc};
 use shared::experiment::software::Software;
-use shared::{DownMessage, UpMessage};
+use shared::{DownMessage, UpMessage, UpdateProfile};
 use strum::{EnumProperty, IntoEnumIterator};
 use strum_macros::{AsRefStr, EnumIter, EnumProperty};
 use uuid::Uuid;
@@ -9,10 +9,31 @@ use yew::prelude::*;
 use yew::services::websocket::{WebSocketService, WebSocketStatus, WebSocketTask};
 use yew::services::ConsoleService;
 
+mod asset;
+mod audit;
+mod battery;
 mod builderbot;
+mod camera;
 mod drone;
+mod event;
+mod logging;
 mod pipuck;
+mod telemetry;
 mod experiment;
+mod health;
+mod map;
+mod message;
+mod ticket;
+mod xbee;
+
+/* the number of arena events that are kept in the event console */
+const MAX_EVENTS: usize = 500;
+/* the number of records of the supervisor's log that are kept in the log viewer */
+const MAX_LOG_RECORDS: usize = 1000;
+/* the number of entries of the audit trail that are kept in the audit view */
+const MAX_AUDIT_ENTRIES: usize = 1000;
+/* the number of messages of the router that are kept in the router inspector */
+const MAX_ROUTER_MESSAGES: usize = 1000;
 
 #[derive(AsRefStr, EnumProperty, EnumIter, Copy, Clone, PartialEq)]
 pub enum Tab {
@@ -22,8 +43,24 @@ pub enum Tab {
     Drones,
     #[strum(serialize = "Pi-Pucks", props(icon = "mdi-circle-slice-8"))]
     PiPucks,
+    #[strum(serialize = "Cameras", props(icon = "mdi-camera"))]
+    Cameras,
+    #[strum(serialize = "Batteries", props(icon = "mdi-battery"))]
+    Batteries,
+    #[strum(serialize = "Map", props(icon = "mdi-map"))]
+    Map,
+    #[strum(serialize = "Messages", props(icon = "mdi-message-text-outline"))]
+    Messages,
     #[strum(serialize = "Experiment", props(icon = "mdi-play"))]
     Experiment,
+    #[strum(serialize = "Tickets", props(icon = "mdi-ticket"))]
+    Tickets,
+    #[strum(serialize = "Events", props(icon = "mdi-console"))]
+    Events,
+    #[strum(serialize = "Log", props(icon = "mdi-text-box-search-outline"))]
+    Log,
+    #[strum(serialize = "Audit", props(icon = "mdi-clipboard-text-search-outline"))]
+    Audit,
 }
 
 pub struct UserInterface {
@@ -41,6 +78,40 @@ pub struct UserInterface {
     pipuck_software: Rc<RefCell<Software>>,
     pipuck_config_comp: Option<ComponentLink<experiment::pipuck::ConfigCard>>,
     control_config_comp: Option<ComponentLink<experiment::Interface>>,
+    tickets: Rc<Vec<shared::ticket::Ticket>>,
+    usage: Rc<Vec<shared::usage::Usage>>,
+    update_profile: UpdateProfile,
+    rigid_body_proposals: Rc<Vec<(String, Option<i32>)>>,
+    readiness: Rc<Option<shared::experiment::Readiness>>,
+    groups: Rc<Vec<shared::group::Group>>,
+    /* the arenas managed by the supervisor and the arena selected by the query of the URL */
+    arenas: Vec<String>,
+    arena: Option<String>,
+    /* the robots that are quarantined, these may be received before the robots are added */
+    quarantined: Vec<String>,
+    /* the health of the robots, which may also be received before the robots are added */
+    health: Vec<shared::health::Health>,
+    /* the metadata of the robots, which may be received before the robots are added */
+    assets: Vec<shared::asset::Asset>,
+    map_snapshot: Rc<Option<shared::map::Snapshot>>,
+    /* the most recent decoded message of each sender for each schema */
+    decoded_messages: Rc<Vec<shared::router::Decoded>>,
+    /* the messages of the router are only received while the router is inspected */
+    inspecting: bool,
+    router_messages: Rc<VecDeque<shared::router::Message>>,
+    control: shared::control::Owner,
+    events: Rc<VecDeque<shared::event::Event>>,
+    log_records: Rc<VecDeque<shared::logging::Record>>,
+    audit_entries: Rc<VecDeque<shared::audit::Entry>>,
+    pipuck_power_result: Option<Result<(), String>>,
+    land_all_result: Option<Result<(), String>>,
+    shutting_down: bool,
+    /* destructive actions are validated but not sent to the robots */
+    rehearsal: bool,
+    /* the protocol version of the supervisor if it differs from the protocol version of the user interface */
+    protocol_mismatch: Option<u32>,
+    /* some messages from the supervisor could not be decoded, e.g., since the supervisor was updated */
+    undecodable: bool,
 }
 
 
@@ -50,11 +121,21 @@ pub enum Msg {
     WebSocketNotifcation(WebSocketStatus),
     WebSocketRxData(Result<Vec<u8>, anyhow::Error>),
     SetActiveTab(Tab),
+    SelectArena(String),
+    SetUpdateProfile(UpdateProfile),
+    SetInspecting(bool),
+    ClearRouterMessages,
     SendRequest(shared::BackEndRequest, Option<Callback<Result<(), String>>>),
     SetBuilderBotConfigComp(ComponentLink<experiment::builderbot::ConfigCard>),
     SetDroneConfigComp(ComponentLink<experiment::drone::ConfigCard>),
     SetPiPuckConfigComp(ComponentLink<experiment::pipuck::ConfigCard>),
     SetControlConfigComp(ComponentLink<experiment::Interface>),
+    SetPiPuckPower(shared::pipuck::Power),
+    SetPiPuckPowerResult(Result<(), String>),
+    RecallPiPucks,
+    LandAll(shared::drone::Landing),
+    LandAllResult(Result<(), String>),
+    Reload,
 }
 
 impl Component for UserInterface {
@@ -62,12 +143,18 @@ impl Component for UserInterface {
     type Properties = ();
 
     fn create(_props: Self::Properties, link: ComponentLink<Self>) -> Self {
-        let service_addr = yew::utils::document()
+        let location = yew::utils::document()
             .location()
-            .unwrap()
-            .host()
             .unwrap();
-        let service_addr = format!("ws://{}/socket", service_addr);
+        let service_addr = location.host().unwrap();
+        /* the arena is selected by the query of the URL, e.g., ?arena=cage1 */
+        let arena = location.search().ok()
+            .and_then(|search| web_sys::UrlSearchParams::new_with_str(&search).ok())
+            .and_then(|params| params.get("arena"));
+        let service_addr = match &arena {
+            Some(arena) => format!("ws://{}/socket?arena={}", service_addr, String::from(js_sys::encode_uri_component(arena))),
+            None => format!("ws://{}/socket", service_addr),
+        };
         let callback_data =
             link.callback(|data| Msg::WebSocketRxData(data));
         let callback_notification =
@@ -98,6 +185,31 @@ impl Component for UserInterface {
             builderbot_software: Default::default(),
             drone_software: Default::default(),
             pipuck_software: Default::default(),
+            tickets: Default::default(),
+            usage: Default::default(),
+            update_profile: detect_update_profile(),
+            rigid_body_proposals: Default::default(),
+            readiness: Default::default(),
+            groups: Default::default(),
+            arenas: Default::default(),
+            arena,
+            quarantined: Default::default(),
+            health: Default::default(),
+            assets: Default::default(),
+            map_snapshot: Default::default(),
+            decoded_messages: Default::default(),
+            inspecting: false,
+            router_messages: Default::default(),
+            control: Default::default(),
+            events: Default::default(),
+            log_records: Default::default(),
+            audit_entries: Default::default(),
+            pipuck_power_result: None,
+            land_all_result: None,
+            shutting_down: false,
+            rehearsal: false,
+            protocol_mismatch: None,
+            undecodable: false,
         }
     }
 
@@ -107,6 +219,74 @@ impl Component for UserInterface {
                 self.active_tab = tab;
                 true
             }
+            /* reload the page to connect to the selected arena */
+            Msg::SelectArena(arena) => {
+                let search = format!("arena={}", String::from(js_sys::encode_uri_component(&arena)));
+                if yew::utils::document().location().unwrap().set_search(&search).is_err() {
+                    ConsoleService::log("Could not select arena");
+                }
+                false
+            }
+            Msg::SetUpdateProfile(profile) => {
+                self.update_profile = profile;
+                let request = shared::BackEndRequest::SetUpdateProfile(profile);
+                self.link.send_message(Msg::SendRequest(request, None));
+                true
+            }
+            Msg::SetInspecting(inspecting) => {
+                self.inspecting = inspecting;
+                let request = shared::BackEndRequest::InspectRouter(inspecting);
+                self.link.send_message(Msg::SendRequest(request, None));
+                true
+            }
+            Msg::ClearRouterMessages => {
+                self.router_messages = Default::default();
+                true
+            }
+            Msg::SetPiPuckPower(power) => {
+                let mut ids = self.pipucks.iter()
+                    .filter(|(_, pipuck)| pipuck.borrow().is_connected())
+                    .map(|(id, _)| id.clone())
+                    .collect::<Vec<_>>();
+                ids.sort();
+                let request = shared::BackEndRequest::PiPuckPowerRequest(ids, power);
+                let callback = self.link.callback(Msg::SetPiPuckPowerResult);
+                self.link.send_message(Msg::SendRequest(request, Some(callback)));
+                false
+            },
+            Msg::SetPiPuckPowerResult(result) => {
+                self.pipuck_power_result = Some(result);
+                true
+            },
+            Msg::RecallPiPucks => {
+                let request = shared::BackEndRequest::RecallPiPucksRequest;
+                let callback = self.link.callback(Msg::SetPiPuckPowerResult);
+                self.link.send_message(Msg::SendRequest(request, Some(callback)));
+                false
+            },
+            Msg::LandAll(landing) => {
+                let request = shared::BackEndRequest::LandAllRequest(landing);
+                let callback = self.link.callback(Msg::LandAllResult);
+                self.link.send_message(Msg::SendRequest(request, Some(callback)));
+                false
+            },
+            Msg::LandAllResult(result) => {
+                self.land_all_result = Some(result);
+                true
+            },
+            Msg::Reload => {
+                if yew::utils::document().location().unwrap().reload().is_err() {
+                    ConsoleService::log("Could not reload the page");
+                }
+                false
+            },
+            /* requests could be decoded as different requests by a supervisor with another protocol */
+            Msg::SendRequest(_, callback) if self.protocol_mismatch.is_some() => {
+                if let Some(callback) = callback {
+                    callback.emit(Err(String::from("The user interface is out of date, please refresh the page")));
+                }
+                false
+            },
             Msg::SendRequest(request, callback) => {
                 match self.socket.as_mut() {
                     Some(websocket) => {
@@ -133,10 +313,29 @@ impl Component for UserInterface {
             Msg::WebSocketRxData(data) => match data {
                 Ok(data) => match bincode::deserialize::<DownMessage>(&data) {
                     Ok(decoded) => match decoded {
+                        DownMessage::Hello(version) => {
+                            if version != shared::PROTOCOL_VERSION {
+                                ConsoleService::log(&format!("Supervisor uses protocol version {} instead of {}",
+                                    version, shared::PROTOCOL_VERSION));
+                                self.protocol_mismatch = Some(version);
+                            }
+                            self.protocol_mismatch.is_some()
+                        },
+                        /* the other messages may have been decoded incorrectly */
+                        _ if self.protocol_mismatch.is_some() => false,
                         DownMessage::Request(_uuid, request) => match request {
                             shared::FrontEndRequest::AddBuilderBot(desc) => {
-                                self.builderbots.entry(desc.id.clone())
-                                    .or_insert_with(|| Rc::new(RefCell::new(builderbot::Instance::new(desc))));
+                                let open_tickets = self.open_tickets(&desc.id);
+                                let quarantined = self.quarantined.contains(&desc.id);
+                                let asset = self.asset(&desc.id);
+                                let health = self.health(&desc.id);
+                                let mut builderbot = self.builderbots.entry(desc.id.clone())
+                                    .or_insert_with(|| Rc::new(RefCell::new(builderbot::Instance::new(desc))))
+                                    .borrow_mut();
+                                builderbot.open_tickets = open_tickets;
+                                builderbot.quarantined = quarantined;
+                                builderbot.asset = asset;
+                                builderbot.health = health;
                                 true
                             },
                             shared::FrontEndRequest::UpdateBuilderBot(id, update) => {
@@ -146,8 +345,17 @@ impl Component for UserInterface {
                                 true
                             },
                             shared::FrontEndRequest::AddDrone(desc) => {
-                                self.drones.entry(desc.id.clone())
-                                    .or_insert_with(|| Rc::new(RefCell::new(drone::Instance::new(desc))));
+                                let open_tickets = self.open_tickets(&desc.id);
+                                let quarantined = self.quarantined.contains(&desc.id);
+                                let asset = self.asset(&desc.id);
+                                let health = self.health(&desc.id);
+                                let mut drone = self.drones.entry(desc.id.clone())
+                                    .or_insert_with(|| Rc::new(RefCell::new(drone::Instance::new(desc))))
+                                    .borrow_mut();
+                                drone.open_tickets = open_tickets;
+                                drone.quarantined = quarantined;
+                                drone.asset = asset;
+                                drone.health = health;
                                 true
                             },
                             shared::FrontEndRequest::UpdateDrone(id, update) => {
@@ -157,8 +365,17 @@ impl Component for UserInterface {
                                 true
                             },
                             shared::FrontEndRequest::AddPiPuck(desc) => {
-                                self.pipucks.entry(desc.id.clone())
-                                    .or_insert_with(|| Rc::new(RefCell::new(pipuck::Instance::new(desc))));
+                                let open_tickets = self.open_tickets(&desc.id);
+                                let quarantined = self.quarantined.contains(&desc.id);
+                                let asset = self.asset(&desc.id);
+                                let health = self.health(&desc.id);
+                                let mut pipuck = self.pipucks.entry(desc.id.clone())
+                                    .or_insert_with(|| Rc::new(RefCell::new(pipuck::Instance::new(desc))))
+                                    .borrow_mut();
+                                pipuck.open_tickets = open_tickets;
+                                pipuck.quarantined = quarantined;
+                                pipuck.asset = asset;
+                                pipuck.health = health;
                                 true
                             },
                             shared::FrontEndRequest::UpdatePiPuck(id, update) => {
@@ -167,7 +384,151 @@ impl Component for UserInterface {
                                 }
                                 true
                             },
-                            shared::FrontEndRequest::UpdateExperiment(_) => todo!(),
+                            shared::FrontEndRequest::UpdateTickets(tickets) => {
+                                self.tickets = Rc::new(tickets);
+                                for (id, builderbot) in self.builderbots.iter() {
+                                    builderbot.borrow_mut().open_tickets = self.open_tickets(id);
+                                }
+                                for (id, drone) in self.drones.iter() {
+                                    drone.borrow_mut().open_tickets = self.open_tickets(id);
+                                }
+                                for (id, pipuck) in self.pipucks.iter() {
+                                    pipuck.borrow_mut().open_tickets = self.open_tickets(id);
+                                }
+                                true
+                            },
+                            shared::FrontEndRequest::UpdateUsage(usage) => {
+                                self.usage = Rc::new(usage);
+                                true
+                            },
+                            shared::FrontEndRequest::UpdateExperiment(update) => match update {
+                                shared::experiment::Update::RigidBodyProposals(proposals) => {
+                                    self.rigid_body_proposals = Rc::new(proposals);
+                                    true
+                                },
+                                shared::experiment::Update::Readiness(readiness) => {
+                                    self.readiness = Rc::new(Some(readiness));
+                                    true
+                                },
+                                /* the state of the experiment is not shown, there is nothing to render */
+                                shared::experiment::Update::State(_) => false,
+                            },
+                            shared::FrontEndRequest::UpdateGroups(groups) => {
+                                self.groups = Rc::new(groups);
+                                self.active_tab == Tab::Experiment
+                            },
+                            shared::FrontEndRequest::UpdateArenas(arenas) => {
+                                self.arenas = arenas;
+                                true
+                            },
+                            shared::FrontEndRequest::UpdateQuarantined(quarantined) => {
+                                for (id, builderbot) in self.builderbots.iter() {
+                                    builderbot.borrow_mut().quarantined = quarantined.contains(id);
+                                }
+                                for (id, drone) in self.drones.iter() {
+                                    drone.borrow_mut().quarantined = quarantined.contains(id);
+                                }
+                                for (id, pipuck) in self.pipucks.iter() {
+                                    pipuck.borrow_mut().quarantined = quarantined.contains(id);
+                                }
+                                self.quarantined = quarantined;
+                                true
+                            },
+                            shared::FrontEndRequest::UpdateHealth(health) => {
+                                self.health = health;
+                                for (id, builderbot) in self.builderbots.iter() {
+                                    builderbot.borrow_mut().health = self.health(id);
+                                }
+                                for (id, drone) in self.drones.iter() {
+                                    drone.borrow_mut().health = self.health(id);
+                                }
+                                for (id, pipuck) in self.pipucks.iter() {
+                                    pipuck.borrow_mut().health = self.health(id);
+                                }
+                                true
+                            },
+                            shared::FrontEndRequest::UpdateTelemetry(window) => {
+                                if let Some(builderbot) = self.builderbots.get(&window.robot) {
+                                    builderbot.borrow_mut().telemetry = Some(window);
+                                }
+                                else if let Some(drone) = self.drones.get(&window.robot) {
+                                    drone.borrow_mut().telemetry = Some(window);
+                                }
+                                else if let Some(pipuck) = self.pipucks.get(&window.robot) {
+                                    pipuck.borrow_mut().telemetry = Some(window);
+                                }
+                                true
+                            },
+                            shared::FrontEndRequest::UpdateAssets(assets) => {
+                                self.assets = assets;
+                                for (id, builderbot) in self.builderbots.iter() {
+                                    builderbot.borrow_mut().asset = self.asset(id);
+                                }
+                                for (id, drone) in self.drones.iter() {
+                                    drone.borrow_mut().asset = self.asset(id);
+                                }
+                                for (id, pipuck) in self.pipucks.iter() {
+                                    pipuck.borrow_mut().asset = self.asset(id);
+                                }
+                                true
+                            },
+                            shared::FrontEndRequest::UpdateControl(owner) => {
+                                self.control = owner;
+                                true
+                            },
+                            shared::FrontEndRequest::AddEvent(event) => {
+                                let events = Rc::make_mut(&mut self.events);
+                                if events.len() == MAX_EVENTS {
+                                    events.pop_front();
+                                }
+                                events.push_back(event);
+                                self.active_tab == Tab::Events
+                            },
+                            shared::FrontEndRequest::AddLogRecord(record) => {
+                                let log_records = Rc::make_mut(&mut self.log_records);
+                                if log_records.len() == MAX_LOG_RECORDS {
+                                    log_records.pop_front();
+                                }
+                                log_records.push_back(record);
+                                self.active_tab == Tab::Log
+                            },
+                            shared::FrontEndRequest::AddAuditEntry(entry) => {
+                                let audit_entries = Rc::make_mut(&mut self.audit_entries);
+                                if audit_entries.len() == MAX_AUDIT_ENTRIES {
+                                    audit_entries.pop_front();
+                                }
+                                audit_entries.push_back(entry);
+                                self.active_tab == Tab::Audit
+                            },
+                            shared::FrontEndRequest::UpdateRehearsal(rehearsal) => {
+                                self.rehearsal = rehearsal;
+                                true
+                            },
+                            shared::FrontEndRequest::Shutdown => {
+                                self.shutting_down = true;
+                                true
+                            },
+                            shared::FrontEndRequest::AddDecodedMessage(decoded) => {
+                                let decoded_messages = Rc::make_mut(&mut self.decoded_messages);
+                                let key = |message: &shared::router::Decoded| (message.schema.clone(), message.from.clone());
+                                match decoded_messages.binary_search_by_key(&key(&decoded), key) {
+                                    Ok(index) => decoded_messages[index] = decoded,
+                                    Err(index) => decoded_messages.insert(index, decoded),
+                                }
+                                self.active_tab == Tab::Messages
+                            },
+                            shared::FrontEndRequest::AddRouterMessage(message) => {
+                                let router_messages = Rc::make_mut(&mut self.router_messages);
+                                if router_messages.len() == MAX_ROUTER_MESSAGES {
+                                    router_messages.pop_front();
+                                }
+                                router_messages.push_back(message);
+                                self.active_tab == Tab::Messages
+                            },
+                            shared::FrontEndRequest::UpdateMap(snapshot) => {
+                                self.map_snapshot = Rc::new(Some(snapshot));
+                                self.active_tab == Tab::Map
+                            },
                             shared::FrontEndRequest::UpdateTrackingSystem(updates) => {
                                 for update in updates {
                                     for builderbot in self.builderbots.values() {
@@ -210,7 +571,8 @@ impl Component for UserInterface {
                     },
                     Err(error) => {
                         ConsoleService::log(&format!("Could not deserialize backend message: {}", error));
-                        false
+                        /* only render the notification once */
+                        !std::mem::replace(&mut self.undecodable, true)
                     }
                 },
                 Err(error) => {
@@ -220,6 +582,21 @@ impl Component for UserInterface {
             },
             Msg::WebSocketNotifcation(notification) => {
                 ConsoleService::log(&format!("Connection to backend: {:?}", notification));
+                /* negotiate the update profile once connected */
+                if let WebSocketStatus::Opened = notification {
+                    /* the protocol version is sent first so that the supervisor can refuse requests that
+                       it would decode incorrectly */
+                    match bincode::serialize(&UpMessage::Hello(shared::PROTOCOL_VERSION)) {
+                        Ok(serialized) => if let Some(websocket) = self.socket.as_mut() {
+                            websocket.send_binary(Ok(serialized));
+                        },
+                        Err(error) => ConsoleService::log(&format!("Could not serialize protocol version: {}", error)),
+                    }
+                    self.link.send_message(Msg::SetUpdateProfile(self.update_profile));
+                    if self.inspecting {
+                        self.link.send_message(Msg::SetInspecting(true));
+                    }
+                }
                 false
             }
             Msg::SetBuilderBotConfigComp(link) => {
@@ -250,6 +627,9 @@ impl Component for UserInterface {
         html! {
             <>
                 { self.render_hero() }
+                { self.render_shutdown() }
+                { self.render_protocol() }
+                { self.render_rehearsal() }
                 { self.render_tabs() }
                 <section class="section">
                     <div class="container is-fluid">
@@ -262,25 +642,96 @@ impl Component for UserInterface {
                                             <builderbot::Card key=id.clone() instance=builderbot.clone() parent=self.link.clone() />
                                         </div>
                                     }).collect::<Html>(),
-                                Tab::Drones => self.drones
-                                    .iter()
-                                    .map(|(id, drone)| html! {
-                                        <div class="column is-full-mobile is-full-tablet is-full-desktop is-half-widescreen is-one-third-fullhd">
-                                            <drone::Card key=id.clone() instance=drone.clone() parent=self.link.clone() />
-                                        </div>
-                                    }).collect::<Html>(),
-                                Tab::PiPucks => self.pipucks
-                                    .iter()
-                                    .map(|(id, pipuck)| html! {
-                                        <div class="column is-full-mobile is-full-tablet is-full-desktop is-half-widescreen is-one-third-fullhd">
-                                            <pipuck::Card key=id.clone() instance=pipuck.clone() parent=self.link.clone() />
-                                        </div>
-                                    }).collect::<Html>(),
+                                Tab::Drones => html! {
+                                    <>
+                                        { self.render_land_all() }
+                                        <xbee::Panel parent=self.link.clone() drones=self.drones.clone() />
+                                        {
+                                            self.drones
+                                                .iter()
+                                                .map(|(id, drone)| html! {
+                                                    <div class="column is-full-mobile is-full-tablet is-full-desktop is-half-widescreen is-one-third-fullhd">
+                                                        <drone::Card key=id.clone() instance=drone.clone() parent=self.link.clone() />
+                                                    </div>
+                                                }).collect::<Html>()
+                                        }
+                                    </>
+                                },
+                                Tab::PiPucks => html! {
+                                    <>
+                                        { self.render_pipuck_power() }
+                                        {
+                                            self.pipucks
+                                                .iter()
+                                                .map(|(id, pipuck)| html! {
+                                                    <div class="column is-full-mobile is-full-tablet is-full-desktop is-half-widescreen is-one-third-fullhd">
+                                                        <pipuck::Card key=id.clone() instance=pipuck.clone() parent=self.link.clone() />
+                                                    </div>
+                                                }).collect::<Html>()
+                                        }
+                                    </>
+                                },
+                                Tab::Cameras => html! {
+                                    <camera::Panel parent=self.link.clone()
+                                        builderbots=self.builderbots.clone()
+                                        drones=self.drones.clone()
+                                        pipucks=self.pipucks.clone() />
+                                },
+                                Tab::Batteries => html! {
+                                    <battery::Panel
+                                        builderbots=self.builderbots.clone()
+                                        drones=self.drones.clone()
+                                        pipucks=self.pipucks.clone() />
+                                },
+                                Tab::Map => html! {
+                                    <map::Panel snapshot=self.map_snapshot.clone() />
+                                },
+                                Tab::Messages => html! {
+                                    <message::Panel
+                                        decoded=self.decoded_messages.clone()
+                                        messages=self.router_messages.clone()
+                                        inspecting=self.inspecting
+                                        parent=self.link.clone() />
+                                },
+                                Tab::Events => html! {
+                                    <event::Panel events=self.events.clone() />
+                                },
+                                Tab::Log => {
+                                    let mut robots = self.builderbots.keys()
+                                        .chain(self.drones.keys())
+                                        .chain(self.pipucks.keys())
+                                        .cloned()
+                                        .collect::<Vec<_>>();
+                                    robots.sort();
+                                    html! {
+                                        <logging::Panel records=self.log_records.clone() robots=Rc::new(robots) />
+                                    }
+                                },
+                                Tab::Audit => html! {
+                                    <audit::Panel entries=self.audit_entries.clone() />
+                                },
                                 Tab::Experiment => html! {
                                     <experiment::Interface parent=self.link.clone()
                                         builderbot_software=self.builderbot_software.clone()
                                         drone_software=self.drone_software.clone()
-                                        pipuck_software=self.pipuck_software.clone() />
+                                        pipuck_software=self.pipuck_software.clone()
+                                        rigid_body_proposals=self.rigid_body_proposals.clone()
+                                        readiness=self.readiness.clone()
+                                        groups=self.groups.clone() />
+                                },
+                                Tab::Tickets => {
+                                    let mut robots = self.builderbots.keys()
+                                        .chain(self.drones.keys())
+                                        .chain(self.pipucks.keys())
+                                        .cloned()
+                                        .collect::<Vec<_>>();
+                                    robots.sort();
+                                    html! {
+                                        <ticket::Panel parent=self.link.clone()
+                                            tickets=self.tickets.clone()
+                                            usage=self.usage.clone()
+                                            robots=Rc::new(robots) />
+                                    }
                                 }
                             }
                         } </div>
@@ -292,6 +743,26 @@ impl Component for UserInterface {
 }
 
 impl UserInterface {
+    fn open_tickets(&self, robot: &str) -> usize {
+        self.tickets.iter()
+            .filter(|ticket| ticket.is_open() && ticket.robot == robot)
+            .count()
+    }
+
+    /* robots without metadata have an empty asset so that it can be edited */
+    fn asset(&self, robot: &str) -> shared::asset::Asset {
+        self.assets.iter()
+            .find(|asset| asset.robot == robot)
+            .cloned()
+            .unwrap_or_else(|| shared::asset::Asset { robot: robot.to_owned(), ..Default::default() })
+    }
+
+    fn health(&self, robot: &str) -> Option<shared::health::Health> {
+        self.health.iter()
+            .find(|health| health.robot == robot)
+            .cloned()
+    }
+
     fn render_hero(&self) -> Html {
         html!{
             <section class="hero is-link">
@@ -305,12 +776,203 @@ impl UserInterface {
                         <div class="column">
                             <p class="title is-2">{ "Supervisor" }</p>
                         </div>
+                        <div class="column is-narrow">
+                            { self.render_arena_select() }
+                        </div>
+                        <div class="column is-narrow">
+                            { self.render_control() }
+                        </div>
+                        <div class="column is-narrow">
+                            { self.render_rehearsal_toggle() }
+                        </div>
+                        <div class="column is-narrow">
+                            { self.render_update_profile_select() }
+                        </div>
                     </div>
                 </div>
             </section>
         }
     }
 
+    fn render_shutdown(&self) -> Html {
+        match self.shutting_down {
+            true => html! {
+                <div class="notification is-warning is-radiusless mb-0">
+                    { "The supervisor is shutting down, experiments on the robots are being stopped" }
+                </div>
+            },
+            false => html! {},
+        }
+    }
+
+    fn render_rehearsal(&self) -> Html {
+        match self.rehearsal {
+            true => html! {
+                <div class="notification is-info is-radiusless mb-0">
+                    { "Rehearsal mode: powering off, rebooting, updating firmware, and starting experiments \
+                       are validated and logged but not sent to the robots" }
+                </div>
+            },
+            false => html! {},
+        }
+    }
+
+    fn render_rehearsal_toggle(&self) -> Html {
+        let rehearsal = self.rehearsal;
+        let onclick = self.link.callback(move |_| {
+            Msg::SendRequest(shared::BackEndRequest::SetRehearsal(!rehearsal), None)
+        });
+        let (class, label) = match rehearsal {
+            true => ("button is-info", "Leave rehearsal"),
+            false => ("button", "Rehearse"),
+        };
+        html! {
+            <button class=class onclick=onclick>{ label }</button>
+        }
+    }
+
+    fn render_protocol(&self) -> Html {
+        let message = match (self.protocol_mismatch, self.undecodable) {
+            (Some(version), _) => format!("This user interface (protocol version {}) does not match the supervisor \
+                (protocol version {}), please refresh the page", shared::PROTOCOL_VERSION, version),
+            (None, true) => String::from("Some messages from the supervisor could not be decoded, if the \
+                supervisor has been updated, please refresh the page"),
+            (None, false) => return html! {},
+        };
+        let onclick = self.link.callback(|_| Msg::Reload);
+        html! {
+            <div class="notification is-danger is-radiusless mb-0">
+                <div class="level">
+                    <div class="level-left">
+                        <div class="level-item">{ message }</div>
+                    </div>
+                    <div class="level-right">
+                        <div class="level-item">
+                            <button class="button is-small" onclick=onclick>{ "Refresh" }</button>
+                        </div>
+                    </div>
+                </div>
+            </div>
+        }
+    }
+
+    fn render_pipuck_power(&self) -> Html {
+        use shared::pipuck::Power;
+        let halt_onclick = self.link.callback(|_| Msg::SetPiPuckPower(Power::Halt));
+        let reboot_onclick = self.link.callback(|_| Msg::SetPiPuckPower(Power::Reboot));
+        let recall_onclick = self.link.callback(|_| Msg::RecallPiPucks);
+        html! {
+            <div class="column is-full">
+                <div class="buttons">
+                    <button class="button" onclick=halt_onclick>{ "Halt connected Pi-Pucks" }</button>
+                    <button class="button" onclick=reboot_onclick>{ "Reboot connected Pi-Pucks" }</button>
+                    <button class="button" onclick=recall_onclick>{ "Recall Pi-Pucks to docks" }</button>
+                </div>
+                {
+                    match &self.pipuck_power_result {
+                        Some(Err(error)) => html! {
+                            <div class="notification is-danger">{ error }</div>
+                        },
+                        _ => html! {},
+                    }
+                }
+            </div>
+        }
+    }
+
+    fn render_land_all(&self) -> Html {
+        use shared::drone::Landing;
+        let land_onclick = self.link.callback(|_| Msg::LandAll(Landing::Land));
+        let return_onclick = self.link.callback(|_| Msg::LandAll(Landing::ReturnToLaunch));
+        html! {
+            <div class="column is-full">
+                <div class="buttons">
+                    <button class="button is-warning" onclick=land_onclick>{ "Land all drones" }</button>
+                    <button class="button is-warning" onclick=return_onclick>{ "Return all drones to launch" }</button>
+                </div>
+                {
+                    match &self.land_all_result {
+                        Some(Err(error)) => html! {
+                            <div class="notification is-danger">{ error }</div>
+                        },
+                        _ => html! {},
+                    }
+                }
+            </div>
+        }
+    }
+
+    fn render_control(&self) -> Html {
+        use shared::control::{Owner, Request};
+        let (status, label, request) = match &self.control {
+            Owner::This =>
+                (String::from("You have control"), "Release control", Request::Release),
+            Owner::Other(name) =>
+                (format!("Read only, {} has control", name), "Take control", Request::TakeOver),
+            Owner::Nobody =>
+                (String::from("Nobody has control"), "Take control", Request::TakeOver),
+        };
+        let onclick = self.link.callback(move |_| {
+            Msg::SendRequest(shared::BackEndRequest::ControlRequest(request.clone()), None)
+        });
+        html! {
+            <div class="field is-grouped is-align-items-center">
+                <p class="control">
+                    <span class="tag is-medium">{ status }</span>
+                </p>
+                <p class="control">
+                    <button class="button" onclick=onclick>{ label }</button>
+                </p>
+            </div>
+        }
+    }
+
+    /* the arena can only be selected if the supervisor manages more than one arena */
+    fn render_arena_select(&self) -> Html {
+        if self.arenas.len() < 2 {
+            return html! {};
+        }
+        let selected = self.arena.as_ref().or_else(|| self.arenas.first());
+        let onchange = self.link.batch_callback(|data| match data {
+            ChangeData::Select(select) => Some(Msg::SelectArena(select.value())),
+            _ => None,
+        });
+        html! {
+            <div class="select">
+                <select onchange=onchange> {
+                    self.arenas.iter()
+                        .map(|arena| html! {
+                            <option selected=Some(arena) == selected>{ arena }</option>
+                        })
+                        .collect::<Html>()
+                } </select>
+            </div>
+        }
+    }
+
+    fn render_update_profile_select(&self) -> Html {
+        let onchange = self.link.batch_callback(|data| match data {
+            ChangeData::Select(select) => match select.value().as_str() {
+                "Desktop" => Some(Msg::SetUpdateProfile(UpdateProfile::Desktop)),
+                "Tablet" => Some(Msg::SetUpdateProfile(UpdateProfile::Tablet)),
+                "Phone" => Some(Msg::SetUpdateProfile(UpdateProfile::Phone)),
+                _ => None,
+            },
+            _ => None,
+        });
+        html! {
+            <div class="select">
+                <select onchange=onchange> {
+                    [UpdateProfile::Desktop, UpdateProfile::Tablet, UpdateProfile::Phone].iter()
+                        .map(|profile| html! {
+                            <option selected=self.update_profile == *profile>{ format!("{:?}", profile) }</option>
+                        })
+                        .collect::<Html>()
+                } </select>
+            </div>
+        }
+    }
+
     fn render_tabs(&self) -> Html {
         html! {
             <div class="tabs is-centered is-boxed is-medium">
@@ -344,6 +1006,23 @@ impl UserInterface {
     }
 }
 
+/* pick an initial update profile using the same breakpoints as Bulma */
+fn detect_update_profile() -> UpdateProfile {
+    let width = yew::utils::window()
+        .inner_width()
+        .ok()
+        .and_then(|width| width.as_f64())
+        .unwrap_or(f64::MAX);
+    if width < 769.0 {
+        UpdateProfile::Phone
+    }
+    else if width < 1024.0 {
+        UpdateProfile::Tablet
+    }
+    else {
+        UpdateProfile::Desktop
+    }
+}
 
 #[wasm_bindgen]
 pub fn launch() -> Result<(), JsValue> {
@@ -0,0 +1,180 @@
+use std::{collections::{BTreeSet, VecDeque}, rc::Rc};
+use shared::logging::{Level, Record};
+use yew::prelude::*;
+
+pub struct Panel {
+    link: ComponentLink<Self>,
+    props: Props,
+    robot: Option<String>,
+    subsystem: Option<String>,
+    level: Level,
+}
+
+#[derive(Clone, Properties)]
+pub struct Props {
+    pub records: Rc<VecDeque<Record>>,
+    /* the identifiers of the robots, sorted */
+    pub robots: Rc<Vec<String>>,
+}
+
+pub enum Msg {
+    SetRobot(Option<String>),
+    SetSubsystem(Option<String>),
+    SetLevel(Level),
+}
+
+const LEVELS: [(Level, &str); 5] = [
+    (Level::Trace, "Trace"),
+    (Level::Debug, "Debug"),
+    (Level::Info, "Info"),
+    (Level::Warn, "Warning"),
+    (Level::Error, "Error"),
+];
+
+fn level_tag(level: Level) -> Html {
+    let (class, label) = match level {
+        Level::Trace => ("is-light", "Trace"),
+        Level::Debug => ("is-light", "Debug"),
+        Level::Info => ("is-info", "Info"),
+        Level::Warn => ("is-warning", "Warning"),
+        Level::Error => ("is-danger", "Error"),
+    };
+    html! {
+        <span class=classes!("tag", class)>{ label }</span>
+    }
+}
+
+/* the log records do not identify the robot that they concern, a record is therefore considered to be
+   about a robot if its message mentions the identifier of that robot as a whole word */
+fn mentions(message: &str, id: &str) -> bool {
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    message.match_indices(id).any(|(index, _)| {
+        let before = message[..index].chars().next_back();
+        let after = message[index + id.len()..].chars().next();
+        !before.map_or(false, is_word) && !after.map_or(false, is_word)
+    })
+}
+
+/* the empty value of a select stands for all options */
+fn selected(value: String) -> Option<String> {
+    match value.is_empty() {
+        true => None,
+        false => Some(value),
+    }
+}
+
+impl Component for Panel {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(props: Props, link: ComponentLink<Self>) -> Self {
+        Panel { link, props, robot: None, subsystem: None, level: Level::Info }
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            Msg::SetRobot(robot) => self.robot = robot,
+            Msg::SetSubsystem(subsystem) => self.subsystem = subsystem,
+            Msg::SetLevel(level) => self.level = level,
+        }
+        true
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        self.props = props;
+        true
+    }
+
+    fn view(&self) -> Html {
+        let subsystems = self.props.records.iter()
+            .map(|record| record.subsystem.as_str())
+            .collect::<BTreeSet<_>>();
+        let robot_onchange = self.link.batch_callback(|data| match data {
+            ChangeData::Select(select) => Some(Msg::SetRobot(selected(select.value()))),
+            _ => None,
+        });
+        let subsystem_onchange = self.link.batch_callback(|data| match data {
+            ChangeData::Select(select) => Some(Msg::SetSubsystem(selected(select.value()))),
+            _ => None,
+        });
+        let level_onchange = self.link.batch_callback(|data| match data {
+            ChangeData::Select(select) => LEVELS.iter()
+                .find(|(_, label)| *label == select.value())
+                .map(|(level, _)| Msg::SetLevel(*level)),
+            _ => None,
+        });
+        /* the most recent records are shown first */
+        let records = self.props.records.iter().rev()
+            .filter(|record| record.level >= self.level)
+            .filter(|record| self.subsystem.as_ref().map_or(true, |subsystem| &record.subsystem == subsystem))
+            .filter(|record| self.robot.as_ref().map_or(true, |robot| mentions(&record.message, robot)));
+        html! {
+            <div class="column is-full">
+                <div class="card">
+                    <header class="card-header">
+                        <p class="card-header-title">{ "Log" }</p>
+                        <div class="card-header-icon">
+                            <div class="field is-grouped">
+                                <div class="control">
+                                    <div class="select is-small">
+                                        <select onchange=robot_onchange>
+                                            <option value="" selected=self.robot.is_none()>{ "All robots" }</option>
+                                            {
+                                                self.props.robots.iter().map(|robot| html! {
+                                                    <option selected=self.robot.as_ref() == Some(robot)>{ robot }</option>
+                                                }).collect::<Html>()
+                                            }
+                                        </select>
+                                    </div>
+                                </div>
+                                <div class="control">
+                                    <div class="select is-small">
+                                        <select onchange=subsystem_onchange>
+                                            <option value="" selected=self.subsystem.is_none()>{ "All subsystems" }</option>
+                                            {
+                                                subsystems.iter().map(|subsystem| html! {
+                                                    <option selected=self.subsystem.as_deref() == Some(*subsystem)>{ subsystem }</option>
+                                                }).collect::<Html>()
+                                            }
+                                        </select>
+                                    </div>
+                                </div>
+                                <div class="control">
+                                    <div class="select is-small">
+                                        <select onchange=level_onchange> {
+                                            LEVELS.iter().map(|(level, label)| html! {
+                                                <option selected=*level == self.level>{ label }</option>
+                                            }).collect::<Html>()
+                                        } </select>
+                                    </div>
+                                </div>
+                            </div>
+                        </div>
+                    </header>
+                    <div class="card-content" style="max-height: 75vh; overflow-y: auto;">
+                        <table class="table is-fullwidth is-narrow">
+                            <thead>
+                                <tr>
+                                    <th>{ "Time" }</th>
+                                    <th>{ "Level" }</th>
+                                    <th>{ "Subsystem" }</th>
+                                    <th>{ "Message" }</th>
+                                </tr>
+                            </thead>
+                            <tbody> {
+                                records.map(|record| html! {
+                                    <tr>
+                                        <td class="is-family-monospace">{ &record.timestamp }</td>
+                                        <td>{ level_tag(record.level) }</td>
+                                        <td class="is-family-monospace">{ &record.subsystem }</td>
+                                        <td class="is-family-monospace">{ &record.message }</td>
+                                    </tr>
+                                }).collect::<Html>()
+                            } </tbody>
+                        </table>
+                    </div>
+                </div>
+            </div>
+        }
+    }
+}
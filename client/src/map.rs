@@ -0,0 +1,174 @@
+use std::rc::Rc;
+use shared::map::{Bounds, Kind, Snapshot, Status};
+use yew::prelude::*;
+
+/* the size of the robots and the margin around the arena in meters */
+const ROBOT_RADIUS: f32 = 0.075;
+const MARGIN: f32 = 0.25;
+
+pub struct Panel {
+    props: Props,
+}
+
+#[derive(Clone, Properties)]
+pub struct Props {
+    pub snapshot: Rc<Option<Snapshot>>,
+}
+
+fn status_color(status: Status) -> &'static str {
+    match status {
+        Status::Connected => "#48c774",
+        Status::PartiallyConnected => "#ffdd57",
+        Status::Disconnected => "#f14668",
+    }
+}
+
+fn kind_icon(kind: Kind) -> &'static str {
+    match kind {
+        Kind::BuilderBot => "mdi-crop-square",
+        Kind::Drone => "mdi-quadcopter",
+        Kind::PiPuck => "mdi-circle-slice-8",
+    }
+}
+
+/* use the configured bounds or, if there are none, the area around the tracked robots */
+fn view_bounds(snapshot: &Snapshot) -> Bounds {
+    if let Some(bounds) = snapshot.bounds {
+        return bounds;
+    }
+    snapshot.robots.iter()
+        .filter_map(|robot| robot.pose.map(|(position, _)| position))
+        .fold(Bounds { min: [-1.0, -1.0], max: [1.0, 1.0] }, |bounds, [x, y]| Bounds {
+            min: [bounds.min[0].min(x), bounds.min[1].min(y)],
+            max: [bounds.max[0].max(x), bounds.max[1].max(y)],
+        })
+}
+
+impl Component for Panel {
+    type Message = ();
+    type Properties = Props;
+
+    fn create(props: Props, _: ComponentLink<Self>) -> Self {
+        Panel { props }
+    }
+
+    fn update(&mut self, _: Self::Message) -> ShouldRender {
+        false
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        self.props = props;
+        true
+    }
+
+    fn view(&self) -> Html {
+        let snapshot = match self.props.snapshot.as_ref() {
+            Some(snapshot) => snapshot,
+            None => return html! {
+                <div class="column is-full">
+                    <div class="notification">{ "Waiting for the first map update" }</div>
+                </div>
+            },
+        };
+        let bounds = view_bounds(snapshot);
+        let width = bounds.max[0] - bounds.min[0] + 2.0 * MARGIN;
+        let height = bounds.max[1] - bounds.min[1] + 2.0 * MARGIN;
+        /* the y axis of the SVG points down, so the arena is flipped vertically */
+        let to_svg = |[x, y]: [f32; 2]| [x - bounds.min[0] + MARGIN, bounds.max[1] - y + MARGIN];
+        let view_box = format!("0 0 {} {}", width, height);
+        let untracked = snapshot.robots.iter()
+            .filter(|robot| robot.pose.is_none())
+            .collect::<Vec<_>>();
+        html! {
+            <div class="column is-full">
+                <div class="card">
+                    <header class="card-header">
+                        <p class="card-header-title">{ "Arena" }</p>
+                    </header>
+                    <div class="card-content">
+                        <svg viewBox=view_box style="width: 100%; max-height: 75vh;">
+                            <rect x=MARGIN.to_string()
+                                  y=MARGIN.to_string()
+                                  width=(width - 2.0 * MARGIN).to_string()
+                                  height=(height - 2.0 * MARGIN).to_string()
+                                  fill="#f5f5f5"
+                                  stroke="#4a4a4a"
+                                  stroke-width="0.01" /> {
+                            /* the trails are drawn first so that they do not cover the robots */
+                            snapshot.robots.iter()
+                                .filter(|robot| robot.trail.len() > 1)
+                                .map(|robot| {
+                                    let points = robot.trail.iter()
+                                        .map(|position| {
+                                            let [x, y] = to_svg(*position);
+                                            format!("{},{}", x, y)
+                                        })
+                                        .collect::<Vec<_>>()
+                                        .join(" ");
+                                    html! {
+                                        <polyline points=points
+                                                  fill="none"
+                                                  stroke=status_color(robot.status)
+                                                  stroke-opacity="0.6"
+                                                  stroke-width="0.015" />
+                                    }
+                                })
+                                .collect::<Html>()
+                        } {
+                            snapshot.robots.iter()
+                                .filter_map(|robot| robot.pose.map(|pose| (robot, pose)))
+                                .map(|(robot, (position, heading))| {
+                                    let [x, y] = to_svg(position);
+                                    /* the heading is counterclockwise in the arena, i.e., clockwise in the SVG */
+                                    let [hx, hy] = [x + 2.0 * ROBOT_RADIUS * heading.cos(), y - 2.0 * ROBOT_RADIUS * heading.sin()];
+                                    html! {
+                                        <g>
+                                            <circle cx=x.to_string()
+                                                    cy=y.to_string()
+                                                    r=ROBOT_RADIUS.to_string()
+                                                    fill=status_color(robot.status)
+                                                    stroke="#4a4a4a"
+                                                    stroke-width="0.01" />
+                                            <line x1=x.to_string()
+                                                  y1=y.to_string()
+                                                  x2=hx.to_string()
+                                                  y2=hy.to_string()
+                                                  stroke="#4a4a4a"
+                                                  stroke-width="0.02" />
+                                            <text x=x.to_string()
+                                                  y=(y + 2.5 * ROBOT_RADIUS).to_string()
+                                                  font-size="0.08"
+                                                  text-anchor="middle">{ &robot.id }</text>
+                                        </g>
+                                    }
+                                })
+                                .collect::<Html>()
+                        } </svg>
+                    </div>
+                    {
+                        if untracked.is_empty() {
+                            html! {}
+                        }
+                        else {
+                            html! {
+                                <div class="card-content">
+                                    <p class="has-text-weight-bold">{ "Not tracked" }</p>
+                                    <div class="tags"> {
+                                        untracked.iter().map(|robot| html! {
+                                            <span class="tag" style=format!("background-color: {}", status_color(robot.status))>
+                                                <span class="icon">
+                                                    <i class=classes!("mdi", kind_icon(robot.kind))></i>
+                                                </span>
+                                                <span>{ &robot.id }</span>
+                                            </span>
+                                        }).collect::<Html>()
+                                    } </div>
+                                </div>
+                            }
+                        }
+                    }
+                </div>
+            </div>
+        }
+    }
+}
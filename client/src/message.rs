@@ -0,0 +1,316 @@
+use std::{collections::VecDeque, rc::Rc};
+use shared::{BackEndRequest, router::{Decoded, Message, Request}};
+use yew::prelude::*;
+
+pub struct Panel {
+    link: ComponentLink<Self>,
+    props: Props,
+    query: String,
+    /* the journal on the supervisor, the speed, and the comma-separated senders of a replay */
+    journal: String,
+    speed: String,
+    senders: String,
+    replay_result: Option<Result<(), String>>,
+}
+
+#[derive(Clone, Properties)]
+pub struct Props {
+    /* the most recent message of each sender for each schema, sorted by schema and sender */
+    pub decoded: Rc<Vec<Decoded>>,
+    /* the messages of the router that were received while the router was inspected, oldest first */
+    pub messages: Rc<VecDeque<Message>>,
+    pub inspecting: bool,
+    pub parent: ComponentLink<crate::UserInterface>,
+}
+
+pub enum Msg {
+    SetQuery(String),
+    SetJournal(String),
+    SetSpeed(String),
+    SetSenders(String),
+    Replay,
+    StopReplay,
+    SetReplayResult(Result<(), String>),
+}
+
+/* a message matches the query if its sender, recipient, or preview contains each of the words of the query */
+fn matches(message: &Message, query: &str) -> bool {
+    let fields = [message.from.as_str(), message.to.as_str(), message.preview.as_str()];
+    query.split_whitespace().all(|word| {
+        let word = word.to_lowercase();
+        fields.iter().any(|field| field.to_lowercase().contains(&word))
+    })
+}
+
+/* the messages as comma-separated values with a header */
+fn export<'a>(messages: impl Iterator<Item = &'a Message>) -> String {
+    let quote = |field: &str| format!("\"{}\"", field.replace('"', "\"\""));
+    let mut csv = String::from("timestamp,from,to,size,preview\n");
+    for message in messages {
+        csv.push_str(&format!("{},{},{},{},{}\n",
+            message.timestamp, quote(&message.from), quote(&message.to), message.size, quote(&message.preview)));
+    }
+    csv
+}
+
+impl Component for Panel {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(props: Props, link: ComponentLink<Self>) -> Self {
+        Panel {
+            link,
+            props,
+            query: String::new(),
+            journal: String::new(),
+            speed: String::from("1.0"),
+            senders: String::new(),
+            replay_result: None,
+        }
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            Msg::SetQuery(query) => self.query = query,
+            Msg::SetJournal(journal) => self.journal = journal,
+            Msg::SetSpeed(speed) => self.speed = speed,
+            Msg::SetSenders(senders) => self.senders = senders,
+            Msg::Replay => match self.speed.trim().parse::<f64>() {
+                Ok(speed) => {
+                    let senders = self.senders.split(',')
+                        .map(str::trim)
+                        .filter(|sender| !sender.is_empty())
+                        .map(String::from)
+                        .collect();
+                    let request = Request::Replay { journal: self.journal.trim().to_owned(), speed, senders };
+                    self.send(request);
+                },
+                Err(_) => self.replay_result = Some(Err(format!("{} is not a valid speed", self.speed))),
+            },
+            Msg::StopReplay => self.send(Request::StopReplay),
+            Msg::SetReplayResult(result) => self.replay_result = Some(result),
+        }
+        true
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        self.props = props;
+        true
+    }
+
+    fn view(&self) -> Html {
+        if self.props.decoded.is_empty() {
+            return html! {
+                <>
+                    { self.render_inspector() }
+                    { self.render_replay() }
+                    <div class="column is-full">
+                        <div class="notification">
+                            { "No messages that match a schema of the message router have been received" }
+                        </div>
+                    </div>
+                </>
+            };
+        }
+        let mut schemas = self.props.decoded.iter()
+            .map(|decoded| decoded.schema.as_str())
+            .collect::<Vec<_>>();
+        schemas.dedup();
+        html! {
+            <>
+                { self.render_inspector() }
+                { self.render_replay() }
+                { schemas.into_iter().map(|schema| self.render_schema(schema)).collect::<Html>() }
+            </>
+        }
+    }
+}
+
+impl Panel {
+    fn send(&mut self, request: Request) {
+        self.replay_result = None;
+        let callback = Some(self.link.callback(Msg::SetReplayResult));
+        self.props.parent.send_message(crate::Msg::SendRequest(BackEndRequest::RouterRequest(request), callback));
+    }
+
+    /* replay the messages recorded in a journal to reproduce a problem that was caused by them */
+    fn render_replay(&self) -> Html {
+        let journal_oninput = self.link.callback(|data: InputData| Msg::SetJournal(data.value));
+        let speed_oninput = self.link.callback(|data: InputData| Msg::SetSpeed(data.value));
+        let senders_oninput = self.link.callback(|data: InputData| Msg::SetSenders(data.value));
+        let replay_onclick = self.link.callback(|_| Msg::Replay);
+        let stop_onclick = self.link.callback(|_| Msg::StopReplay);
+        html! {
+            <div class="column is-full">
+                <div class="card">
+                    <header class="card-header">
+                        <p class="card-header-title">{ "Replay" }</p>
+                    </header>
+                    <div class="card-content">
+                        <div class="field is-grouped">
+                            <div class="control is-expanded">
+                                <input class="input is-small"
+                                       type="text"
+                                       placeholder="Journal, e.g., 20210801-120000.pkl"
+                                       value=self.journal.clone()
+                                       oninput=journal_oninput />
+                            </div>
+                            <div class="control">
+                                <input class="input is-small"
+                                       type="text"
+                                       placeholder="Speed"
+                                       title="Speed"
+                                       value=self.speed.clone()
+                                       oninput=speed_oninput />
+                            </div>
+                            <div class="control is-expanded">
+                                <input class="input is-small"
+                                       type="text"
+                                       placeholder="Senders, e.g., drone1, pipuck2 (all by default)"
+                                       value=self.senders.clone()
+                                       oninput=senders_oninput />
+                            </div>
+                            <div class="control">
+                                <button class="button is-small is-info" onclick=replay_onclick>{ "Replay" }</button>
+                            </div>
+                            <div class="control">
+                                <button class="button is-small" onclick=stop_onclick>{ "Stop" }</button>
+                            </div>
+                        </div>
+                        {
+                            match &self.replay_result {
+                                Some(Err(error)) => html! {
+                                    <p class="help is-danger">{ error }</p>
+                                },
+                                _ => html! {},
+                            }
+                        }
+                    </div>
+                </div>
+            </div>
+        }
+    }
+
+    /* the messages forwarded by the router, which are only sent by the supervisor while inspecting */
+    fn render_inspector(&self) -> Html {
+        let query_oninput = self.link.callback(|data: InputData| Msg::SetQuery(data.value));
+        let inspecting = self.props.inspecting;
+        let inspect_onclick = self.props.parent.callback(move |_| crate::Msg::SetInspecting(!inspecting));
+        let clear_onclick = self.props.parent.callback(|_| crate::Msg::ClearRouterMessages);
+        let (inspect_class, inspect_label) = match inspecting {
+            true => ("button is-small is-warning", "Pause"),
+            false => ("button is-small is-info", "Inspect"),
+        };
+        let messages = self.props.messages.iter()
+            .filter(|message| matches(message, &self.query))
+            .collect::<Vec<_>>();
+        let csv = export(messages.iter().copied());
+        let href = format!("data:text/csv;charset=utf-8,{}", String::from(js_sys::encode_uri_component(&csv)));
+        html! {
+            <div class="column is-full">
+                <div class="card">
+                    <header class="card-header">
+                        <p class="card-header-title">{ "Router traffic" }</p>
+                        <div class="card-header-icon">
+                            <div class="field is-grouped">
+                                <div class="control">
+                                    <input class="input is-small"
+                                           type="search"
+                                           placeholder="Filter"
+                                           value=self.query.clone()
+                                           oninput=query_oninput />
+                                </div>
+                                <div class="control">
+                                    <button class=inspect_class onclick=inspect_onclick>{ inspect_label }</button>
+                                </div>
+                                <div class="control">
+                                    <button class="button is-small" onclick=clear_onclick>{ "Clear" }</button>
+                                </div>
+                                <div class="control">
+                                    <a class="button is-small" href=href download="router-messages.csv">{ "Export" }</a>
+                                </div>
+                            </div>
+                        </div>
+                    </header>
+                    <div class="card-content" style="max-height: 50vh; overflow-y: auto;">
+                        <table class="table is-fullwidth is-narrow">
+                            <thead>
+                                <tr>
+                                    <th>{ "Time" }</th>
+                                    <th>{ "From" }</th>
+                                    <th>{ "To" }</th>
+                                    <th>{ "Size" }</th>
+                                    <th>{ "Content" }</th>
+                                </tr>
+                            </thead>
+                            <tbody> {
+                                /* the most recent messages are shown first */
+                                messages.iter().rev().map(|message| html! {
+                                    <tr>
+                                        <td class="is-family-monospace">{ &message.timestamp }</td>
+                                        <td>{ &message.from }</td>
+                                        <td>{ &message.to }</td>
+                                        <td>{ format!("{} B", message.size) }</td>
+                                        <td class="is-family-monospace" style="word-break: break-all;">{ &message.preview }</td>
+                                    </tr>
+                                }).collect::<Html>()
+                            } </tbody>
+                        </table>
+                        <p class="has-text-grey">{ "The most recent 1000 messages are kept while the router is inspected." }</p>
+                    </div>
+                </div>
+            </div>
+        }
+    }
+
+    /* a table for each schema with a row for each sender and a column for each field */
+    fn render_schema(&self, schema: &str) -> Html {
+        let messages = self.props.decoded.iter()
+            .filter(|decoded| decoded.schema == schema)
+            .collect::<Vec<_>>();
+        let labels = messages.first()
+            .map_or_else(Vec::new, |decoded| decoded.fields.iter().map(|field| field.label.clone()).collect());
+        html! {
+            <div class="column is-full">
+                <div class="card">
+                    <header class="card-header">
+                        <p class="card-header-title">{ schema }</p>
+                    </header>
+                    <div class="card-content" style="overflow-x: auto;">
+                        <table class="table is-fullwidth is-narrow">
+                            <thead>
+                                <tr>
+                                    <th>{ "From" }</th>
+                                    <th>{ "To" }</th>
+                                    { labels.iter().map(|label| html! { <th>{ label }</th> }).collect::<Html>() }
+                                </tr>
+                            </thead>
+                            <tbody> {
+                                messages.iter().map(|decoded| html! {
+                                    <tr>
+                                        <td>{ &decoded.from }</td>
+                                        <td>{ &decoded.to }</td>
+                                        {
+                                            decoded.fields.iter().map(|field| match (&field.value, field.valid) {
+                                                (Some(value), true) => html! {
+                                                    <td class="is-family-monospace">{ value }</td>
+                                                },
+                                                /* a value of the wrong type is shown but highlighted */
+                                                (Some(value), false) => html! {
+                                                    <td class="is-family-monospace has-text-danger" title="Unexpected type">{ value }</td>
+                                                },
+                                                (None, _) => html! {
+                                                    <td class="has-text-grey-light">{ "missing" }</td>
+                                                },
+                                            }).collect::<Html>()
+                                        }
+                                    </tr>
+                                }).collect::<Html>()
+                            } </tbody>
+                        </table>
+                    </div>
+                </div>
+            </div>
+        }
+    }
+}
@@ -0,0 +1,168 @@
+use shared::{BackEndRequest, telemetry::{Metric, Series, Window}};
+use yew::prelude::*;
+
+/* the windows that can be selected in minutes */
+const WINDOWS: [u32; 3] = [5, 15, 30];
+/* the size of a chart in the units of its view box */
+const WIDTH: f64 = 600.0;
+const HEIGHT: f64 = 100.0;
+
+/* the label, unit, and the range of the vertical axis of each metric */
+fn axis(metric: Metric) -> (&'static str, &'static str, f64, f64) {
+    match metric {
+        Metric::FernbedienungSignal => ("Fernbedienung signal", "dBm", -100.0, -20.0),
+        Metric::XbeeSignal => ("Xbee signal", "%", 0.0, 100.0),
+        Metric::Battery => ("Battery", "%", 0.0, 100.0),
+        Metric::Temperature => ("CPU temperature", "°C", 20.0, 100.0),
+    }
+}
+
+pub struct Viewer {
+    link: ComponentLink<Self>,
+    props: Props,
+    minutes: u32,
+    error: Option<String>,
+}
+
+#[derive(Clone, Properties)]
+pub struct Props {
+    pub robot: String,
+    /* the most recent window that was received for this robot */
+    pub window: Option<Window>,
+    pub parent: ComponentLink<crate::UserInterface>,
+    pub onclose: Callback<()>,
+}
+
+pub enum Msg {
+    SetWindow(u32),
+    Refresh,
+    Refreshed(Result<(), String>),
+}
+
+impl Component for Viewer {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(props: Props, link: ComponentLink<Self>) -> Self {
+        link.send_message(Msg::Refresh);
+        Viewer { link, props, minutes: WINDOWS[0], error: None }
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            Msg::SetWindow(minutes) => {
+                self.minutes = minutes;
+                self.link.send_message(Msg::Refresh);
+                true
+            },
+            Msg::Refresh => {
+                let callback = Some(self.link.callback(Msg::Refreshed));
+                let request = BackEndRequest::TelemetryRequest(self.props.robot.clone(), self.minutes);
+                self.props.parent.send_message(crate::Msg::SendRequest(request, callback));
+                false
+            },
+            Msg::Refreshed(result) => {
+                self.error = result.err();
+                true
+            },
+        }
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        self.props = props;
+        true
+    }
+
+    fn view(&self) -> Html {
+        let close_onclick = self.props.onclose.reform(|_| ());
+        let refresh_onclick = self.link.callback(|_| Msg::Refresh);
+        html! {
+            <div class="modal is-active">
+                <div class="modal-background" onclick=close_onclick.clone() />
+                <div class="modal-card" style="width: 720px;">
+                    <header class="modal-card-head">
+                        <p class="modal-card-title">{ format!("Telemetry of {}", self.props.robot) }</p>
+                    </header>
+                    <section class="modal-card-body">
+                        <div class="buttons has-addons"> {
+                            WINDOWS.iter().map(|&minutes| {
+                                let class = match minutes == self.minutes {
+                                    true => "button is-info is-selected",
+                                    false => "button",
+                                };
+                                let onclick = self.link.callback(move |_| Msg::SetWindow(minutes));
+                                html! {
+                                    <button class=class onclick=onclick>{ format!("{} min", minutes) }</button>
+                                }
+                            }).collect::<Html>()
+                        } </div>
+                        {
+                            match &self.error {
+                                Some(error) => html! {
+                                    <div class="notification is-danger">{ error }</div>
+                                },
+                                None => html! {},
+                            }
+                        }
+                        {
+                            match &self.props.window {
+                                Some(window) if !window.series.is_empty() => window.series.iter()
+                                    .map(|series| render_chart(window, series))
+                                    .collect::<Html>(),
+                                Some(_) => html! {
+                                    <div class="notification">{ "No values have been received from this robot" }</div>
+                                },
+                                None => html! {},
+                            }
+                        }
+                    </section>
+                    <footer class="modal-card-foot">
+                        <button class="button is-info" onclick=refresh_onclick>{ "Refresh" }</button>
+                        <button class="button" onclick=close_onclick>{ "Close" }</button>
+                    </footer>
+                </div>
+            </div>
+        }
+    }
+}
+
+/* a line for each run of values, so that a disconnection shows up as a gap in the chart */
+fn render_chart(window: &Window, series: &Series) -> Html {
+    let (label, unit, min, max) = axis(series.metric);
+    let duration = (window.end - window.start).max(1.0);
+    let to_point = |time: f64, value: i32| {
+        let x = (time - window.start) / duration * WIDTH;
+        let y = HEIGHT - (value as f64 - min).max(0.0).min(max - min) / (max - min) * HEIGHT;
+        format!("{:.1},{:.1}", x, y)
+    };
+    let runs = series.samples
+        .split(|(_, value)| value.is_none())
+        .filter(|run| !run.is_empty())
+        .map(|run| run.iter()
+            .filter_map(|&(time, value)| value.map(|value| to_point(time, value)))
+            .collect::<Vec<_>>()
+            .join(" "))
+        .collect::<Vec<_>>();
+    let latest = match series.samples.last() {
+        Some((_, Some(value))) => format!("{} {}", value, unit),
+        Some((_, None)) => String::from("disconnected"),
+        None => String::from("no values"),
+    };
+    html! {
+        <div class="mb-4">
+            <p class="has-text-weight-semibold">{ format!("{} ({})", label, latest) }</p>
+            <svg viewBox=format!("0 0 {} {}", WIDTH, HEIGHT)
+                 preserveAspectRatio="none"
+                 style="width: 100%; height: 100px; background: #f5f5f5;"> {
+                runs.into_iter().map(|points| html! {
+                    <polyline points=points
+                              fill="none"
+                              stroke="#3273dc"
+                              stroke-width="1.5"
+                              vector-effect="non-scaling-stroke" />
+                }).collect::<Html>()
+            } </svg>
+            <p class="is-size-7 has-text-grey">{ format!("{} to {} {}", min, max, unit) }</p>
+        </div>
+    }
+}
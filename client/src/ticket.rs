@@ -0,0 +1,297 @@
+use std::{collections::HashMap, rc::Rc};
+use shared::{BackEndRequest, ticket::{Request, Ticket}, usage::Usage};
+use uuid::Uuid;
+use yew::prelude::*;
+
+pub struct Panel {
+    link: ComponentLink<Self>,
+    props: Props,
+    robot: Option<String>,
+    description: String,
+    resolutions: HashMap<Uuid, String>,
+    error: Result<(), String>,
+}
+
+#[derive(Clone, Properties)]
+pub struct Props {
+    pub tickets: Rc<Vec<Ticket>>,
+    pub usage: Rc<Vec<Usage>>,
+    pub robots: Rc<Vec<String>>,
+    pub parent: ComponentLink<crate::UserInterface>,
+}
+
+pub enum Msg {
+    SetError(Result<(), String>),
+    SetRobot(String),
+    SetDescription(String),
+    SetResolution(Uuid, String),
+    OpenTicket,
+    CloseTicket(Uuid),
+}
+
+impl Component for Panel {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(props: Props, link: ComponentLink<Self>) -> Self {
+        Panel {
+            robot: props.robots.first().cloned(),
+            props,
+            link,
+            description: Default::default(),
+            resolutions: Default::default(),
+            error: Ok(()),
+        }
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            Msg::SetError(error) => {
+                self.error = error;
+                true
+            },
+            Msg::SetRobot(robot) => {
+                self.robot = Some(robot);
+                false
+            },
+            Msg::SetDescription(description) => {
+                self.description = description;
+                false
+            },
+            Msg::SetResolution(id, resolution) => {
+                self.resolutions.insert(id, resolution);
+                false
+            },
+            Msg::OpenTicket => match self.robot.clone() {
+                Some(robot) => {
+                    let callback = Some(self.link.callback(|result| Msg::SetError(result)));
+                    let request = BackEndRequest::TicketRequest(Request::Open {
+                        robot,
+                        description: std::mem::take(&mut self.description),
+                    });
+                    self.props.parent.send_message(crate::Msg::SendRequest(request, callback));
+                    true
+                },
+                None => {
+                    self.error = Err(String::from("A robot must be selected"));
+                    true
+                }
+            },
+            Msg::CloseTicket(id) => {
+                let callback = Some(self.link.callback(|result| Msg::SetError(result)));
+                let request = BackEndRequest::TicketRequest(Request::Close {
+                    id,
+                    resolution: self.resolutions.remove(&id).unwrap_or_default(),
+                });
+                self.props.parent.send_message(crate::Msg::SendRequest(request, callback));
+                true
+            },
+        }
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        if self.robot.is_none() {
+            self.robot = props.robots.first().cloned();
+        }
+        self.props = props;
+        true
+    }
+
+    fn view(&self) -> Html {
+        html! {
+            <>
+                <div class="column is-full-mobile is-full-tablet is-full-desktop is-half-widescreen is-one-third-fullhd">
+                    { self.render_open_card() }
+                </div>
+                <div class="column is-full-mobile is-full-tablet is-full-desktop is-half-widescreen is-one-third-fullhd">
+                    { self.render_usage_card() }
+                </div>
+                {
+                    self.props.tickets.iter().rev().map(|ticket| html! {
+                        <div class="column is-full-mobile is-full-tablet is-full-desktop is-half-widescreen is-one-third-fullhd">
+                            { self.render_ticket(ticket) }
+                        </div>
+                    }).collect::<Html>()
+                }
+                { self.render_error_modal() }
+            </>
+        }
+    }
+}
+
+impl Panel {
+    fn render_open_card(&self) -> Html {
+        let robot_onchange = self.link.batch_callback(|data| match data {
+            ChangeData::Select(select) => Some(Msg::SetRobot(select.value())),
+            _ => None,
+        });
+        let description_oninput = self.link.callback(|data: InputData| Msg::SetDescription(data.value));
+        let open_onclick = self.link.callback(|_| Msg::OpenTicket);
+        html! {
+            <div class="card">
+                <header class="card-header">
+                    <nav class="card-header-title is-shadowless has-background-white-ter level is-mobile">
+                        <div class="level-left">
+                            <p class="level-item subtitle is-size-4">{ "Open Ticket" }</p>
+                        </div>
+                    </nav>
+                </header>
+                <div class="card-content">
+                    <div class="field">
+                        <div class="control">
+                            <div class="select">
+                                <select onchange=robot_onchange> {
+                                    self.props.robots.iter().map(|robot| html! {
+                                        <option selected=self.robot.as_ref() == Some(robot)>{ robot }</option>
+                                    }).collect::<Html>()
+                                } </select>
+                            </div>
+                        </div>
+                    </div>
+                    <div class="field">
+                        <div class="control">
+                            <textarea class="textarea"
+                                      placeholder="Describe the fault"
+                                      value=self.description.clone()
+                                      oninput=description_oninput />
+                        </div>
+                    </div>
+                </div>
+                <footer class="card-footer">
+                    <a class="card-footer-item" onclick=open_onclick>{ "Open ticket" }</a>
+                </footer>
+            </div>
+        }
+    }
+
+    fn render_usage_card(&self) -> Html {
+        /* durations are shown in hours and minutes */
+        let duration = |seconds: u64| format!("{}h {:02}m", seconds / 3600, (seconds % 3600) / 60);
+        html! {
+            <div class="card">
+                <header class="card-header">
+                    <nav class="card-header-title is-shadowless has-background-white-ter level is-mobile">
+                        <div class="level-left">
+                            <p class="level-item subtitle is-size-4">{ "Usage" }</p>
+                        </div>
+                    </nav>
+                </header>
+                <div class="card-content">
+                    <table class="table is-fullwidth is-narrow">
+                        <thead>
+                            <tr>
+                                <th>{ "Robot" }</th>
+                                <th>{ "Runs" }</th>
+                                <th>{ "Experiment time" }</th>
+                                <th>{ "Flight time" }</th>
+                                <th>{ "Battery cycles" }</th>
+                            </tr>
+                        </thead>
+                        <tbody> {
+                            self.props.usage.iter().map(|usage| html! {
+                                <tr>
+                                    <td>{ &usage.robot }</td>
+                                    <td>{ usage.runs }</td>
+                                    <td>{ duration(usage.experiment_seconds) }</td>
+                                    <td>{ duration(usage.flight_seconds) }</td>
+                                    <td>{ format!("{:.1}", usage.battery_cycles()) }</td>
+                                </tr>
+                            }).collect::<Html>()
+                        } </tbody>
+                    </table>
+                </div>
+            </div>
+        }
+    }
+
+    fn render_ticket(&self, ticket: &Ticket) -> Html {
+        let id = ticket.id;
+        html! {
+            <div class="card">
+                <header class="card-header">
+                    <nav class="card-header-title is-shadowless has-background-white-ter level is-mobile">
+                        <div class="level-left">
+                            <p class="level-item subtitle is-size-4">{ &ticket.robot }</p>
+                        </div>
+                        <div class="level-right"> {
+                            match ticket.is_open() {
+                                true => html! { <span class="level-item tag is-danger">{ "Open" }</span> },
+                                false => html! { <span class="level-item tag is-success">{ "Closed" }</span> },
+                            }
+                        } </div>
+                    </nav>
+                </header>
+                <div class="card-content">
+                    <div class="content">
+                        <p class="has-text-grey">{ format!("Opened {}", ticket.opened) }</p>
+                        <p>{ &ticket.description }</p>
+                        <details>
+                            <summary>{ format!("Telemetry ({} entries)", ticket.telemetry.len()) }</summary>
+                            <pre class="is-family-monospace"> {
+                                ticket.telemetry.join("\n")
+                            } </pre>
+                        </details>
+                        {
+                            match &ticket.resolution {
+                                Some(resolution) => html! {
+                                    <>
+                                        <p class="has-text-grey">{ format!("Closed {}", resolution.closed) }</p>
+                                        <p>{ &resolution.description }</p>
+                                    </>
+                                },
+                                None => {
+                                    let resolution_oninput = self.link
+                                        .callback(move |data: InputData| Msg::SetResolution(id, data.value));
+                                    html! {
+                                        <div class="field">
+                                            <div class="control">
+                                                <input class="input"
+                                                       type="text"
+                                                       placeholder="Resolution"
+                                                       oninput=resolution_oninput />
+                                            </div>
+                                        </div>
+                                    }
+                                }
+                            }
+                        }
+                    </div>
+                </div>
+                {
+                    match ticket.is_open() {
+                        true => html! {
+                            <footer class="card-footer">
+                                <a class="card-footer-item"
+                                   onclick=self.link.callback(move |_| Msg::CloseTicket(id))>{ "Close ticket" }</a>
+                            </footer>
+                        },
+                        false => html! {},
+                    }
+                }
+            </div>
+        }
+    }
+
+    fn render_error_modal(&self) -> Html {
+        if let Err(error) = self.error.as_ref() {
+            let clear_error_onclick = self.link.callback(|_| Msg::SetError(Ok(())));
+            html! {
+                <div class="modal is-active">
+                    <div class="modal-background" onclick=clear_error_onclick />
+                    <div class="modal-card">
+                    <header class="modal-card-head">
+                      <p class="modal-card-title"> { "Error processing request" } </p>
+                    </header>
+                    <section class="modal-card-body">
+                      { error }
+                    </section>
+                    <footer class="modal-card-foot" />
+                  </div>
+                </div>
+            }
+        }
+        else {
+            html! {}
+        }
+    }
+}
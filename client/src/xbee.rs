@@ -0,0 +1,157 @@
+use std::{cell::RefCell, collections::{BTreeMap, HashMap}, rc::Rc};
+use shared::{BackEndRequest, drone::Request};
+use yew::prelude::*;
+
+use crate::drone;
+
+/* the signal strengths (in dBm) above which a link is considered good or marginal */
+const RSSI_GOOD: i32 = -60;
+const RSSI_MARGINAL: i32 = -75;
+
+pub struct Panel {
+    link: ComponentLink<Self>,
+    props: Props,
+    error: Option<String>,
+}
+
+#[derive(Clone, Properties)]
+pub struct Props {
+    pub parent: ComponentLink<crate::UserInterface>,
+    pub drones: HashMap<String, Rc<RefCell<drone::Instance>>>,
+}
+
+pub enum Msg {
+    Discover,
+    SetError(Result<(), String>),
+}
+
+fn rssi_cell(rssi: Option<Option<i32>>) -> Html {
+    let (class, label) = match rssi {
+        Some(Some(rssi)) if rssi >= RSSI_GOOD => ("has-background-success-light", rssi.to_string()),
+        Some(Some(rssi)) if rssi >= RSSI_MARGINAL => ("has-background-warning-light", rssi.to_string()),
+        Some(Some(rssi)) => ("has-background-danger-light", rssi.to_string()),
+        /* the node responded but the firmware does not report the signal strength */
+        Some(None) => ("has-background-info-light", String::from("?")),
+        None => ("has-text-grey-light", String::from("-")),
+    };
+    html! {
+        <td class=classes!("has-text-centered", "is-family-monospace", class)>{ label }</td>
+    }
+}
+
+impl Component for Panel {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(props: Props, link: ComponentLink<Self>) -> Self {
+        Panel { link, props, error: None }
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            Msg::Discover => {
+                self.error = None;
+                for (id, drone) in self.props.drones.iter() {
+                    if drone.borrow().is_xbee_connected() {
+                        let request = BackEndRequest::DroneRequest(id.clone(), Request::XbeeDiscover);
+                        let callback = self.link.callback(Msg::SetError);
+                        self.props.parent.send_message(crate::Msg::SendRequest(request, Some(callback)));
+                    }
+                }
+                true
+            },
+            /* keep the first error since the other drones may still respond successfully */
+            Msg::SetError(result) => match result {
+                Err(error) if self.error.is_none() => {
+                    self.error = Some(error);
+                    true
+                },
+                _ => false,
+            },
+        }
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        self.props = props;
+        true
+    }
+
+    fn view(&self) -> Html {
+        let drones = self.props.drones.values()
+            .map(|drone| drone.borrow())
+            .collect::<Vec<_>>();
+        /* the columns are the Xbees of all drones and any other node that was discovered, indexed
+           by their MAC addresses */
+        let mut nodes = drones.iter()
+            .map(|drone| (drone.descriptor.xbee_macaddr.to_string(), drone.descriptor.id.clone()))
+            .collect::<BTreeMap<_, _>>();
+        for node in drones.iter().filter_map(|drone| drone.xbee_nodes.as_ref()).flatten() {
+            nodes.entry(node.macaddr.to_string()).or_insert_with(|| match node.identifier.is_empty() {
+                true => node.macaddr.to_string(),
+                false => node.identifier.clone(),
+            });
+        }
+        let mut sources = drones.iter()
+            .filter(|drone| drone.xbee_nodes.is_some())
+            .collect::<Vec<_>>();
+        sources.sort_by(|lhs, rhs| lhs.descriptor.id.cmp(&rhs.descriptor.id));
+        let onclick = self.link.callback(|_| Msg::Discover);
+        html! {
+            <div class="column is-full">
+                <div class="card">
+                    <header class="card-header">
+                        <p class="card-header-title">{ "Xbee network" }</p>
+                        <div class="card-header-icon">
+                            <button class="button is-small" onclick=onclick>{ "Discover nodes" }</button>
+                        </div>
+                    </header>
+                    <div class="card-content" style="overflow-x: auto;">
+                        {
+                            match &self.error {
+                                Some(error) => html! {
+                                    <div class="notification is-danger">{ error }</div>
+                                },
+                                None => html! {},
+                            }
+                        }
+                        {
+                            match sources.is_empty() {
+                                true => html! {
+                                    <p class="has-text-grey">{ "Discover the nodes to show the signal strength (in dBm) between the Xbees of the connected drones." }</p>
+                                },
+                                false => html! {
+                                    <table class="table is-narrow is-bordered">
+                                        <thead>
+                                            <tr>
+                                                <th>{ "Discovered by" }</th>
+                                                { nodes.values().map(|label| html! { <th>{ label }</th> }).collect::<Html>() }
+                                            </tr>
+                                        </thead>
+                                        <tbody> {
+                                            sources.iter().map(|drone| {
+                                                let discovered = drone.xbee_nodes.iter()
+                                                    .flatten()
+                                                    .map(|node| (node.macaddr.to_string(), node.rssi))
+                                                    .collect::<HashMap<_, _>>();
+                                                html! {
+                                                    <tr>
+                                                        <th>{ &drone.descriptor.id }</th>
+                                                        {
+                                                            nodes.keys()
+                                                                .map(|macaddr| rssi_cell(discovered.get(macaddr).cloned()))
+                                                                .collect::<Html>()
+                                                        }
+                                                    </tr>
+                                                }
+                                            }).collect::<Html>()
+                                        } </tbody>
+                                    </table>
+                                },
+                            }
+                        }
+                    </div>
+                </div>
+            </div>
+        }
+    }
+}
@@ -0,0 +1,25 @@
+use serde::{Serialize, Deserialize};
+
+/* the metadata of a robot, e.g., to track its hardware, which is kept across restarts */
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub struct Asset {
+    pub robot: String,
+    /* the serial numbers of the robot and its components, e.g., of the frame and the Pixhawk */
+    pub serial_numbers: String,
+    pub hardware_revision: String,
+    /* the date of the most recent maintenance, e.g., 2021-07-31 */
+    pub last_maintenance: String,
+    pub known_issues: String,
+    pub notes: String,
+}
+
+impl Asset {
+    /* an asset without any metadata is not kept */
+    pub fn is_empty(&self) -> bool {
+        self.serial_numbers.is_empty() &&
+        self.hardware_revision.is_empty() &&
+        self.last_maintenance.is_empty() &&
+        self.known_issues.is_empty() &&
+        self.notes.is_empty()
+    }
+}
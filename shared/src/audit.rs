@@ -0,0 +1,16 @@
+use serde::{Serialize, Deserialize};
+
+/* an action that an operator requested through the user interface and its outcome */
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Entry {
+    pub timestamp: String,
+    /* the client that requested the action, i.e., its address */
+    pub operator: String,
+    /* the arena in which the action was requested */
+    pub arena: String,
+    /* the robot that the action concerns, if any */
+    pub robot: Option<String>,
+    pub action: String,
+    /* the reason why the action failed, if it failed */
+    pub error: Option<String>,
+}
@@ -8,6 +8,8 @@ pub struct Descriptor {
     pub duovero_macaddr: macaddr::MacAddr6,
     pub optitrack_id: Option<i32>,
     pub apriltag_id: Option<u8>,
+    #[serde(default)]
+    pub cameras: Vec<crate::camera::Configuration>,
 }
 
 impl Display for Descriptor {
@@ -26,7 +28,15 @@ pub enum Update {
     FernbedienungConnected(Ipv4Addr),
     FernbedienungDisconnected,
     FernbedienungSignal(i32),
+    /* the temperature of the robot's computer in degrees Celsius */
+    Temperature(i32),
     Bash(String),
+    /* the number of bytes of a file that have been uploaded to the robot */
+    Upload {
+        filename: String,
+        sent: u64,
+        total: u64,
+    },
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -35,6 +45,8 @@ pub enum Request {
     BashTerminalStop,
     BashTerminalRun(String),
     CameraStreamEnable(bool),
+    /* enable or disable the stream of a single camera by its device */
+    CameraDeviceStreamEnable(String, bool),
     Identify,
     DuoVeroHalt,
     DuoVeroReboot,
@@ -0,0 +1,16 @@
+use serde::{Serialize, Deserialize};
+
+/* a camera on a robot that is streamed with mjpg-streamer */
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub struct Configuration {
+    pub device: String,
+    pub width: u16,
+    pub height: u16,
+    /* the number of frames per second */
+    pub frame_rate: u16,
+    /* the port on which mjpg-streamer serves the frames */
+    pub port: u16,
+    /* the resolution of single images that are captured, e.g., for calibration */
+    pub still_width: u16,
+    pub still_height: u16,
+}
@@ -0,0 +1,24 @@
+use serde::{Serialize, Deserialize};
+
+/* which client, if any, is allowed to send actions to the robots and the arena */
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum Owner {
+    Nobody,
+    /* the client that receives this update */
+    This,
+    /* another client, described by its address */
+    Other(String),
+}
+
+impl Default for Owner {
+    fn default() -> Self {
+        Owner::Nobody
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum Request {
+    /* take control, even if it is held by another client */
+    TakeOver,
+    Release,
+}
@@ -8,6 +8,119 @@ pub struct Descriptor {
     pub xbee_macaddr: macaddr::MacAddr6,
     pub upcore_macaddr: macaddr::MacAddr6,
     pub optitrack_id: Option<i32>,
+    /* the identifier that is set on the Xbee's DIO0 to DIO3 pins */
+    pub xbee_identifier: Option<u8>,
+    #[serde(default)]
+    pub cameras: Vec<crate::camera::Configuration>,
+    #[serde(default)]
+    pub profile: Profile,
+}
+
+/* the hardware of a drone, which is shared by the drones of the same revision, the cameras of a
+   profile are copied into the descriptors of its drones */
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub struct Profile {
+    /* the name of the profile in the configuration, empty for the default profile */
+    pub name: String,
+    pub battery: Battery,
+    pub xbee_pins: XbeePins,
+    pub pixhawk: Pixhawk,
+    pub mavlink: MavlinkTransport,
+    /* the poses of the drone's rigid body that are sent to the Pixhawk, if any */
+    #[serde(default)]
+    pub pose_forwarding: Option<PoseForwarding>,
+    /* how the drone draws attention to itself when it is identified */
+    #[serde(default)]
+    pub identify: Identify,
+}
+
+/* a drone is identified by a command on the Up Core, e.g., to blink its LEDs, and by a tune on the
+   Pixhawk's buzzer, the ARGoS identify controller is run if no command is given */
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub struct Identify {
+    pub command: Option<String>,
+    /* the tune in the QBasic format of PX4, e.g., "MFT240L8 O4aO5dc" */
+    pub tune: Option<String>,
+}
+
+/* the poses from the tracking system are sent to the Pixhawk as external position estimates, so that
+   it can hold its position indoors without onboard localization */
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub struct PoseForwarding {
+    pub message: PoseMessage,
+    /* the maximum number of poses that are sent per second */
+    pub rate: u16,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub enum PoseMessage {
+    /* VISION_POSITION_ESTIMATE, i.e., the position and the attitude as Euler angles */
+    VisionPositionEstimate,
+    /* ATT_POS_MOCAP, i.e., the position and the attitude as a quaternion */
+    AttPosMocap,
+}
+
+/* how the supervisor is connected to the Pixhawk */
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub enum MavlinkTransport {
+    /* the serial communication service of the Xbee */
+    Xbee,
+    /* a UDP port on the Up Core that is forwarded to the Pixhawk, e.g., by mavlink-router */
+    UpCore(u16),
+}
+
+impl Default for MavlinkTransport {
+    fn default() -> Self {
+        MavlinkTransport::Xbee
+    }
+}
+
+/* the battery level is interpolated between the voltages of an empty and a full cell */
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub struct Battery {
+    pub cells: u8,
+    pub empty_mv: u16,
+    pub full_mv: u16,
+}
+
+impl Default for Battery {
+    fn default() -> Self {
+        Self { cells: 3, empty_mv: 3500, full_mv: 4050 }
+    }
+}
+
+/* the MAVLink system and component identifiers of the Pixhawk's autopilot */
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub struct Pixhawk {
+    pub system_id: u8,
+    pub component_id: u8,
+}
+
+impl Default for Pixhawk {
+    fn default() -> Self {
+        Self { system_id: 1, component_id: 1 }
+    }
+}
+
+/* the Xbee pins that are wired to the drone's hardware, where 0 to 12 are DIO0 to DIO12 */
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub struct XbeePins {
+    pub autonomous_mode: u8,
+    pub upcore_power: u8,
+    pub pixhawk_power: u8,
+    /* the pins that encode the identifier, starting with the least significant bit */
+    pub identifier: [u8; 4],
+}
+
+impl Default for XbeePins {
+    fn default() -> Self {
+        Self {
+            autonomous_mode: 4,
+            upcore_power: 11,
+            pixhawk_power: 12,
+            identifier: [0, 1, 2, 3],
+        }
+    }
 }
 
 impl Display for Descriptor {
@@ -16,6 +129,49 @@ impl Display for Descriptor {
     }
 }
 
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub enum Frame {
+    /* north, east, and altitude in meters relative to the home position */
+    Local,
+    /* latitude and longitude in degrees and altitude in meters relative to the home position */
+    Global,
+}
+
+/* how the flying drones are brought down as an alternative to cutting their power */
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub enum Landing {
+    /* land at the current position */
+    Land,
+    /* return to the home position and land there */
+    ReturnToLaunch,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum MissionItem {
+    Takeoff {
+        altitude: f32,
+    },
+    Waypoint {
+        frame: Frame,
+        x: f64,
+        y: f64,
+        altitude: f32,
+        /* time in seconds to hold at the waypoint */
+        hold: f32,
+    },
+    Land,
+}
+
+/* an Xbee that responded to a node discovery */
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct XbeeNode {
+    pub macaddr: macaddr::MacAddr6,
+    /* the node identifier (NI) */
+    pub identifier: String,
+    /* the signal strength in dBm, if reported by the firmware */
+    pub rssi: Option<i32>,
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub enum Update {
     Battery(i32),
@@ -26,15 +182,70 @@ pub enum Update {
     FernbedienungConnected(Ipv4Addr),
     FernbedienungDisconnected,
     FernbedienungSignal(i32),
+    /* the temperature of the robot's computer in degrees Celsius */
+    Temperature(i32),
     XbeeConnected(Ipv4Addr),
     XbeeDisconnected,
     XbeeSignal(i32),
+    XbeeIdentifier(u8),
+    XbeeFirmwareVersion(u32),
+    XbeeParameter {
+        parameter: String,
+        value: Vec<u8>,
+    },
+    /* the nodes found by the most recent node discovery */
+    XbeeNodes(Vec<XbeeNode>),
     Mavlink(String),
+    /* the percentage of MAVLink messages from the Pixhawk that were lost on the serial link */
+    MavlinkLoss(f32),
+    /* whether the Pixhawk is armed, sent when this changes */
+    Armed(bool),
     Bash(String),
+    /* the number of bytes of a file that have been uploaded to the robot */
+    Upload {
+        filename: String,
+        sent: u64,
+        total: u64,
+    },
     PowerState {
         pixhawk: bool,
         upcore: bool,
     },
+    PixhawkFirmware(FirmwareUpload),
+    /* the logs on the SD card of the Pixhawk */
+    PixhawkLogs(Vec<PixhawkLog>),
+    PixhawkLogDownload(LogDownload),
+    /* a new line in the kernel messages of the Up Core that reports a fault, e.g., an oops */
+    KernelFault(String),
+}
+
+/* a flight log (ULog) on the SD card of the Pixhawk */
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PixhawkLog {
+    pub id: u16,
+    /* seconds since the epoch, zero if the Pixhawk did not know the time */
+    pub time_utc: u32,
+    /* bytes */
+    pub size: u32,
+}
+
+/* the state of a download of a log from the Pixhawk */
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum LogDownload {
+    /* the identifier of the log, the number of bytes received, and the size of the log */
+    Progress(u16, u32, u32),
+    /* the identifier of the log and the file it was written to */
+    Complete(u16, String),
+    Failed(u16, String),
+}
+
+/* the state of an upload of PX4 firmware to the Pixhawk */
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum FirmwareUpload {
+    /* the stage of the uploader (e.g., erase, program, or verify) and its progress in percent */
+    Progress(String, f32),
+    Complete,
+    Failed(String),
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -43,6 +254,8 @@ pub enum Request {
     BashTerminalStop,
     BashTerminalRun(String),
     CameraStreamEnable(bool),
+    /* enable or disable the stream of a single camera by its device */
+    CameraDeviceStreamEnable(String, bool),
     Identify,
     PixhawkPowerEnable(bool),
     MavlinkTerminalStart,
@@ -51,5 +264,18 @@ pub enum Request {
     UpCorePowerEnable(bool),
     UpCoreHalt,
     UpCoreReboot,
+    XbeeParameterGet(String),
+    XbeeParameterSet(String, Vec<u8>),
+    XbeeParameterWrite,
+    XbeeDiscover,
+    MissionUpload(Vec<MissionItem>),
+    MissionStart,
+    /* fly to a target position in the coordinates of the tracking system, None stops the flight */
+    SupervisedFlight(Option<[f32; 3]>),
+    /* upload a PX4 firmware file (*.px4) to the Pixhawk */
+    PixhawkFirmwareUpload(Vec<u8>),
+    PixhawkLogList,
+    /* download a log from the Pixhawk and store it alongside the journals */
+    PixhawkLogDownload(u16),
 }
 
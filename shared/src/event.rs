@@ -0,0 +1,20 @@
+use serde::{Serialize, Deserialize};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+/* something that happened in the arena that the user should be aware of */
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Event {
+    pub timestamp: String,
+    pub severity: Severity,
+    /* the robot that the event concerns, if any */
+    pub robot: Option<String>,
+    /* the part of the supervisor that reported the event, e.g., the network */
+    pub subsystem: String,
+    pub message: String,
+}
@@ -7,8 +7,23 @@ pub enum Request {
         builderbot_software: software::Software,
         drone_software: software::Software,
         pipuck_software: software::Software,
+        /* start the experiment even if the readiness check fails */
+        force: bool,
+        /* only start the experiment on the members of this group */
+        group: Option<String>,
     },
     Stop,
+    /* check that the rigid bodies of the robots are being tracked */
+    CheckRigidBodies,
+    /* spin the ground robots one at a time to propose optitrack_id assignments */
+    IdentifyRigidBodies,
+    /* check whether the arena is ready to start an experiment with the given software */
+    CheckReadiness {
+        builderbot_software: software::Software,
+        drone_software: software::Software,
+        pipuck_software: software::Software,
+        group: Option<String>,
+    },
 }
 
 #[derive(Debug, Deserialize, Clone, Copy, Serialize)]
@@ -17,7 +32,35 @@ pub enum State {
     Active,
 }
 
+/* a subject of the readiness check (e.g., a robot or the journal) and the checks that it failed */
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Check {
+    pub subject: String,
+    pub failures: Vec<String>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct Readiness(pub Vec<Check>);
+
+impl Readiness {
+    pub fn is_ready(&self) -> bool {
+        self.0.iter().all(|check| check.failures.is_empty())
+    }
+
+    /* a summary of the failed checks */
+    pub fn failures(&self) -> String {
+        self.0.iter()
+            .filter(|check| !check.failures.is_empty())
+            .map(|check| format!("{}: {}", check.subject, check.failures.join(", ")))
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub enum Update {
     State(State),
+    /* the proposed rigid body for each robot, if one could be identified */
+    RigidBodyProposals(Vec<(String, Option<i32>)>),
+    Readiness(Readiness),
 }
\ No newline at end of file
@@ -0,0 +1,17 @@
+use serde::{Serialize, Deserialize};
+
+/* a named set of robots, e.g., the leaders of a swarm, that can be targeted as a whole */
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Group {
+    pub name: String,
+    /* the identifiers of the robots in the group */
+    pub members: Vec<String>,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub enum Request {
+    Halt,
+    Reboot,
+    /* stop the experiment on the members of the group only */
+    StopExperiment,
+}
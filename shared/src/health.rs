@@ -0,0 +1,21 @@
+use serde::{Serialize, Deserialize};
+
+/* how the score of a robot has changed over the last minute */
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum Trend {
+    Improving,
+    Steady,
+    Declining,
+}
+
+/* the health of a robot, which combines its links, battery, temperature, recent errors, and the
+   visibility of its rigid body into a single score */
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Health {
+    pub robot: String,
+    /* between 0 (unusable) and 100 (no issues) */
+    pub score: u8,
+    pub trend: Trend,
+    /* the issues that lowered the score */
+    pub issues: Vec<String>,
+}
@@ -1,10 +1,24 @@
 use serde::{Serialize, Deserialize};
 use uuid::Uuid;
 
+pub mod asset;
+pub mod audit;
 pub mod builderbot;
+pub mod camera;
+pub mod control;
 pub mod drone;
+pub mod event;
+pub mod group;
+pub mod health;
+pub mod logging;
 pub mod pipuck;
+pub mod router;
 pub mod experiment;
+pub mod map;
+pub mod snapshot;
+pub mod telemetry;
+pub mod ticket;
+pub mod usage;
 
 pub mod tracking_system {
     use serde::{Serialize, Deserialize};
@@ -16,9 +30,30 @@ pub mod tracking_system {
     }
 }
 
+/* how much data a client wants to receive, e.g., a phone connected over a VPN */
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum UpdateProfile {
+    Desktop,
+    Tablet,
+    Phone,
+}
+
+impl Default for UpdateProfile {
+    fn default() -> Self {
+        UpdateProfile::Desktop
+    }
+}
+
+/* the version of the protocol between the user interface and the supervisor, which must be incremented
+   whenever DownMessage, UpMessage, or any of the types that they contain change */
+pub const PROTOCOL_VERSION: u32 = 11;
+
 // backend to frontend
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub enum DownMessage {
+    /* the first message on each connection with the protocol version of the supervisor, this must remain
+       the first variant so that any version of the user interface can decode it */
+    Hello(u32),
     Request(Uuid, FrontEndRequest),
     Response(Uuid, Result<(), String>), // response to a up message
 }
@@ -33,11 +68,40 @@ pub enum FrontEndRequest {
     UpdatePiPuck(String, pipuck::Update),
     UpdateExperiment(experiment::Update),
     UpdateTrackingSystem(Vec<tracking_system::Update>),
+    UpdateTickets(Vec<ticket::Ticket>),
+    UpdateUsage(Vec<usage::Usage>),
+    /* the metadata of each robot that has any */
+    UpdateAssets(Vec<asset::Asset>),
+    UpdateMap(map::Snapshot),
+    UpdateControl(control::Owner),
+    UpdateGroups(Vec<group::Group>),
+    /* the names of the arenas that are managed by the supervisor */
+    UpdateArenas(Vec<String>),
+    /* the identifiers of the robots that are quarantined */
+    UpdateQuarantined(Vec<String>),
+    /* the health of each robot in the arena */
+    UpdateHealth(Vec<health::Health>),
+    /* the recent values of the metrics of a robot, in response to a telemetry request */
+    UpdateTelemetry(telemetry::Window),
+    AddEvent(event::Event),
+    /* a message between the robots that matched one of the registered schemas */
+    AddDecodedMessage(router::Decoded),
+    /* a message forwarded by the router while the client inspects the router */
+    AddRouterMessage(router::Message),
+    AddLogRecord(logging::Record),
+    AddAuditEntry(audit::Entry),
+    /* whether the supervisor is in rehearsal mode */
+    UpdateRehearsal(bool),
+    /* the supervisor is shutting down */
+    Shutdown,
 }
 
 // frontend to backend
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub enum UpMessage {
+    /* the first message on each connection with the protocol version of the user interface, this must
+       remain the first variant so that any version of the supervisor can decode it */
+    Hello(u32),
     Request(Uuid, BackEndRequest),
     Response(Uuid, Result<(), String>), // response to a down message
 }
@@ -47,6 +111,29 @@ pub enum BackEndRequest {
     BuilderBotRequest(String, builderbot::Request),
     DroneRequest(String, drone::Request),
     PiPuckRequest(String, pipuck::Request),
+    PiPuckPowerRequest(Vec<String>, pipuck::Power),
+    /* drive the Pi-Pucks back to their docks */
+    RecallPiPucksRequest,
+    /* land all drones that are flying */
+    LandAllRequest(drone::Landing),
     ExperimentRequest(experiment::Request),
+    /* a request for all members of the named group */
+    GroupRequest(String, group::Request),
+    /* quarantine a robot (true) or release it from quarantine (false) */
+    QuarantineRequest(String, bool),
+    /* run the maintenance script on all connected robots */
+    RunMaintenance,
+    TicketRequest(ticket::Request),
+    /* replace the metadata of a robot */
+    AssetRequest(asset::Asset),
+    /* enable or disable rehearsal mode, in which destructive actions are validated but not executed */
+    SetRehearsal(bool),
+    SetUpdateProfile(UpdateProfile),
+    /* start (true) or stop (false) sending the messages forwarded by the router to this client */
+    InspectRouter(bool),
+    /* the recent values of the metrics of a robot over the given number of minutes */
+    TelemetryRequest(String, u32),
+    RouterRequest(router::Request),
+    ControlRequest(control::Request),
 }
 
@@ -0,0 +1,21 @@
+use serde::{Serialize, Deserialize};
+
+/* the levels are ordered by severity so that records can be filtered by a minimum level */
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize)]
+pub enum Level {
+    Trace,
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+/* a message that was written to the log of the supervisor */
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Record {
+    pub timestamp: String,
+    pub level: Level,
+    /* the module that wrote the record without the name of the crate, e.g., robot::drone::task */
+    pub subsystem: String,
+    pub message: String,
+}
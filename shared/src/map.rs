@@ -0,0 +1,41 @@
+use serde::{Serialize, Deserialize};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum Kind {
+    BuilderBot,
+    Drone,
+    PiPuck,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum Status {
+    /* every link to the robot is up */
+    Connected,
+    /* only some of the links to the robot are up, e.g., the Xbee of a drone */
+    PartiallyConnected,
+    Disconnected,
+}
+
+/* the area covered by the tracking system in meters */
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub struct Bounds {
+    pub min: [f32; 2],
+    pub max: [f32; 2],
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Robot {
+    pub id: String,
+    pub kind: Kind,
+    pub status: Status,
+    /* the position in the x-y plane and the heading around the z axis, if the robot is tracked */
+    pub pose: Option<([f32; 2], f32)>,
+    /* the recent positions in the x-y plane, oldest first */
+    pub trail: Vec<[f32; 2]>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Snapshot {
+    pub bounds: Option<Bounds>,
+    pub robots: Vec<Robot>,
+}
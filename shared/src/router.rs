@@ -0,0 +1,75 @@
+use serde::{Serialize, Deserialize};
+
+/* the type that a field of a message is expected to have */
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum FieldType {
+    String,
+    Number,
+    Boolean,
+    Vector2,
+    Vector3,
+    Quaternion,
+    Table,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Field {
+    /* the path to the field in the Lua table, nested tables are separated by dots, e.g., poseT.positionV3 */
+    pub key: String,
+    pub label: String,
+    pub field_type: FieldType,
+}
+
+/* the fields of the messages between the robots that match the sender and recipient, which may
+   end with * to match any suffix, e.g., drone* */
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Schema {
+    pub name: String,
+    pub from: String,
+    pub to: String,
+    pub fields: Vec<Field>,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct DecodedField {
+    pub label: String,
+    /* the value in a human-readable form, none if the message does not have this field */
+    pub value: Option<String>,
+    /* whether the value has the expected type */
+    pub valid: bool,
+}
+
+/* a message that matched a schema */
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Decoded {
+    pub schema: String,
+    pub from: String,
+    pub to: String,
+    pub fields: Vec<DecodedField>,
+}
+
+/* a message forwarded by the router as shown by the inspector of the user interface */
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Message {
+    /* the time at which the message was forwarded, e.g., 12:34:56.789 */
+    pub timestamp: String,
+    pub from: String,
+    pub to: String,
+    /* the size of the encoded message in bytes */
+    pub size: usize,
+    /* the content of the message in a human-readable form, which is shortened if it is long */
+    pub preview: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub enum Request {
+    /* send the messages recorded in a journal to the robots that are connected to the router, where
+       the journal is a path on the supervisor, the speed scales the time between the messages, and
+       only the messages from the given senders are sent (all messages if there are no senders) */
+    Replay {
+        journal: String,
+        speed: f64,
+        senders: Vec<String>,
+    },
+    StopReplay,
+}
@@ -0,0 +1,56 @@
+use std::net::Ipv4Addr;
+use serde::{Serialize, Deserialize};
+
+use crate::{builderbot, drone, experiment, pipuck};
+
+/* a link to a robot, e.g., the Xbee of a drone, the address is None while it is disconnected */
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Link {
+    pub name: String,
+    pub addr: Option<Ipv4Addr>,
+    pub signal: Option<i32>,
+}
+
+/* the state of a robot as tracked by its task */
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct Robot {
+    pub links: Vec<Link>,
+    pub battery: Option<i32>,
+    /* the files and their MD5 checksums (in hexadecimal) of the software of the most recent experiment */
+    pub software: Option<Vec<(String, String)>>,
+}
+
+impl Robot {
+    pub fn new(links: &[&str]) -> Self {
+        Self {
+            links: links.iter()
+                .map(|name| Link { name: String::from(*name), addr: None, signal: None })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    pub fn link(&mut self, name: &str) -> Option<&mut Link> {
+        self.links.iter_mut().find(|link| link.name == name)
+    }
+
+    pub fn set_software(&mut self, software: &experiment::software::Software) {
+        let checksums = software.checksums()
+            .into_iter()
+            .map(|(filename, digest)| (filename, format!("{:x}", digest)))
+            .collect();
+        self.software = Some(checksums);
+    }
+}
+
+/* the state of the arena and all of its robots, so that a new client can be brought up to date with
+   a single request */
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Snapshot {
+    pub builderbots: Vec<(builderbot::Descriptor, Robot)>,
+    pub drones: Vec<(drone::Descriptor, Robot)>,
+    pub pipucks: Vec<(pipuck::Descriptor, Robot)>,
+    /* the identifiers of the robots that are quarantined */
+    pub quarantined: Vec<String>,
+    pub experiment: experiment::State,
+}
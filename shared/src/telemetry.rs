@@ -0,0 +1,32 @@
+use serde::{Serialize, Deserialize};
+
+/* a quantity that is recorded for each robot over time */
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub enum Metric {
+    /* the signal of the Fernbedienung in dBm */
+    FernbedienungSignal,
+    /* the link margin of the Xbee in percent */
+    XbeeSignal,
+    /* the battery level in percent */
+    Battery,
+    /* the CPU temperature in degrees Celsius */
+    Temperature,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Series {
+    pub metric: Metric,
+    /* the time in seconds since the epoch and the value, which is none while the link that
+       reports the metric is disconnected */
+    pub samples: Vec<(f64, Option<i32>)>,
+}
+
+/* the recent values of the metrics of a robot */
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Window {
+    pub robot: String,
+    /* the start and end of the window in seconds since the epoch */
+    pub start: f64,
+    pub end: f64,
+    pub series: Vec<Series>,
+}
@@ -0,0 +1,36 @@
+use serde::{Serialize, Deserialize};
+use uuid::Uuid;
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Ticket {
+    pub id: Uuid,
+    pub robot: String,
+    pub description: String,
+    pub opened: String,
+    pub telemetry: Vec<String>,
+    pub resolution: Option<Resolution>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Resolution {
+    pub closed: String,
+    pub description: String,
+}
+
+impl Ticket {
+    pub fn is_open(&self) -> bool {
+        self.resolution.is_none()
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum Request {
+    Open {
+        robot: String,
+        description: String,
+    },
+    Close {
+        id: Uuid,
+        resolution: String,
+    },
+}
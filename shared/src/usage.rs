@@ -0,0 +1,22 @@
+use serde::{Serialize, Deserialize};
+
+/* the usage of a robot, which is kept across restarts so that maintenance can be scheduled */
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct Usage {
+    pub robot: String,
+    /* the number of experiments that the robot took part in */
+    pub runs: u32,
+    /* the time in seconds that the robot spent in experiments */
+    pub experiment_seconds: u64,
+    /* the time in seconds that the Pixhawk of a drone was armed */
+    pub flight_seconds: u64,
+    /* the sum of all drops of the battery level in percent */
+    pub discharged: u64,
+}
+
+impl Usage {
+    /* the number of full discharges that the drops of the battery level add up to */
+    pub fn battery_cycles(&self) -> f32 {
+        self.discharged as f32 / 100.0
+    }
+}
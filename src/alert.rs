@@ -0,0 +1,197 @@
+use anyhow::Context;
+use futures::StreamExt;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor, transport::smtp::authentication::Credentials};
+use serde_json::json;
+use shared::{event::Severity, experiment::State};
+use std::collections::HashSet;
+use tokio::sync::{broadcast::error::RecvError, mpsc, oneshot};
+
+use crate::{arena, subscription, webui};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Rule {
+    /* a device of a robot disconnected during an experiment */
+    RobotLost,
+    /* the battery level of a robot fell below the threshold */
+    BatteryCritical,
+    ExperimentFinished,
+    /* the journal could not be written, e.g., because the disk is full */
+    JournalFailure,
+}
+
+impl Rule {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Rule::RobotLost => "robot_lost",
+            Rule::BatteryCritical => "battery_critical",
+            Rule::ExperimentFinished => "experiment_finished",
+            Rule::JournalFailure => "journal_failure",
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum Sink {
+    /* the alert is posted as a JSON object to the URL */
+    Webhook(reqwest::Url),
+    /* the alert is posted to an incoming webhook of Slack */
+    Slack(reqwest::Url),
+    Email {
+        server: String,
+        credentials: Option<(String, String)>,
+        from: String,
+        to: String,
+    },
+}
+
+#[derive(Debug)]
+pub struct Configuration {
+    pub rules: HashSet<Rule>,
+    /* the battery level in percent below which a battery is critical */
+    pub battery_threshold: i32,
+    pub sinks: Vec<Sink>,
+}
+
+/* the updates of the robots that are relevant to the rules */
+enum RobotUpdate {
+    Disconnected(&'static str),
+    Battery(i32),
+}
+
+/* send an alert to all sinks in the background so that a slow sink does not delay the other alerts */
+fn alert(sinks: &[Sink], rule: Rule, robot: Option<&str>, message: String) {
+    log::info!("Alert ({}): {}", rule.name(), message);
+    for sink in sinks {
+        let sink = sink.clone();
+        let robot = robot.map(str::to_owned);
+        let message = message.clone();
+        tokio::spawn(async move {
+            if let Err(error) = send(&sink, rule, robot, &message).await {
+                log::warn!("Could not send alert: {:#}", error);
+            }
+        });
+    }
+}
+
+async fn send(sink: &Sink, rule: Rule, robot: Option<String>, message: &str) -> anyhow::Result<()> {
+    match sink {
+        Sink::Webhook(url) => {
+            let body = json!({ "rule": rule.name(), "robot": robot, "message": message });
+            reqwest::Client::new().post(url.clone()).json(&body).send().await
+                .and_then(reqwest::Response::error_for_status)
+                .context(format!("Could not post alert to {}", url))?;
+        },
+        Sink::Slack(url) => {
+            let body = json!({ "text": format!("Supervisor: {}", message) });
+            reqwest::Client::new().post(url.clone()).json(&body).send().await
+                .and_then(reqwest::Response::error_for_status)
+                .context("Could not post alert to Slack")?;
+        },
+        Sink::Email { server, credentials, from, to } => {
+            let email = Message::builder()
+                .from(from.parse().context(format!("Invalid sender {}", from))?)
+                .to(to.parse().context(format!("Invalid recipient {}", to))?)
+                .subject(format!("Supervisor: {}", rule.name().replace('_', " ")))
+                .body(message.to_owned())
+                .context("Could not create email")?;
+            let mut transport = AsyncSmtpTransport::<Tokio1Executor>::relay(server)
+                .context(format!("Could not connect to {}", server))?;
+            if let Some((username, password)) = credentials {
+                transport = transport.credentials(Credentials::new(username.clone(), password.clone()));
+            }
+            transport.build().send(email).await
+                .context(format!("Could not send email to {}", to))?;
+        },
+    }
+    Ok(())
+}
+
+/// This function watches the robots, the experiment, and the arena events and sends an alert to the
+/// configured sinks when one of the configured rules applies, e.g., so that someone is notified when
+/// something goes wrong during an unattended experiment. If alerts have not been configured, this
+/// function never completes.
+pub async fn new(
+    config: Option<Configuration>,
+    arena_tx: mpsc::Sender<arena::Action>,
+) -> anyhow::Result<()> {
+    let config = match config {
+        Some(config) => config,
+        /* alerts are disabled, never complete */
+        None => return futures::future::pending().await,
+    };
+    let mut builderbot_updates = webui::subscribe_builderbot_updates(&arena_tx).await?;
+    let mut drone_updates = webui::subscribe_drone_updates(&arena_tx).await?;
+    let mut pipuck_updates = webui::subscribe_pipuck_updates(&arena_tx).await?;
+    let (callback_tx, callback_rx) = oneshot::channel();
+    arena_tx.send(arena::Action::SubscribeExperimentState(callback_tx)).await
+        .map_err(|_| anyhow::anyhow!("Could not communicate with arena"))?;
+    let mut experiment_updates = callback_rx.await
+        .map(subscription::updates)
+        .map_err(|_| anyhow::anyhow!("Could not subscribe to experiment updates"))?;
+    let (callback_tx, callback_rx) = oneshot::channel();
+    arena_tx.send(arena::Action::SubscribeEvents(callback_tx)).await
+        .map_err(|_| anyhow::anyhow!("Could not communicate with arena"))?;
+    let mut events_rx = callback_rx.await
+        .map_err(|_| anyhow::anyhow!("Could not subscribe to arena events"))?;
+    let mut experiment_state = State::Standby;
+    /* the robots whose battery is critical, these are not reported again until they are charged */
+    let mut critical: HashSet<String> = HashSet::new();
+    let enabled = |rule: Rule| config.rules.contains(&rule);
+    loop {
+        let (robot, update) = tokio::select! {
+            Some((desc, Ok(update))) = builderbot_updates.next() => match update {
+                shared::builderbot::Update::FernbedienungDisconnected => (desc.id.clone(), RobotUpdate::Disconnected("Fernbedienung")),
+                shared::builderbot::Update::Battery(level) => (desc.id.clone(), RobotUpdate::Battery(level)),
+                _ => continue,
+            },
+            Some((desc, Ok(update))) = drone_updates.next() => match update {
+                shared::drone::Update::FernbedienungDisconnected => (desc.id.clone(), RobotUpdate::Disconnected("Fernbedienung")),
+                shared::drone::Update::XbeeDisconnected => (desc.id.clone(), RobotUpdate::Disconnected("Xbee")),
+                shared::drone::Update::Battery(level) => (desc.id.clone(), RobotUpdate::Battery(level)),
+                _ => continue,
+            },
+            Some((desc, Ok(update))) = pipuck_updates.next() => match update {
+                shared::pipuck::Update::FernbedienungDisconnected => (desc.id.clone(), RobotUpdate::Disconnected("Fernbedienung")),
+                shared::pipuck::Update::Battery(level) => (desc.id.clone(), RobotUpdate::Battery(level)),
+                _ => continue,
+            },
+            Some(Ok(state)) = experiment_updates.next() => {
+                let finished = matches!(experiment_state, State::Active) && !matches!(state, State::Active);
+                if finished && enabled(Rule::ExperimentFinished) {
+                    alert(&config.sinks, Rule::ExperimentFinished, None, String::from("Experiment finished"));
+                }
+                experiment_state = state;
+                continue;
+            },
+            event = events_rx.recv() => match event {
+                Ok(event) => {
+                    if event.subsystem == "journal" && event.severity == Severity::Error && enabled(Rule::JournalFailure) {
+                        alert(&config.sinks, Rule::JournalFailure, None, event.message);
+                    }
+                    continue;
+                },
+                Err(RecvError::Lagged(count)) => {
+                    log::warn!("Alerts missed {} arena events", count);
+                    continue;
+                },
+                Err(RecvError::Closed) => break,
+            },
+        };
+        match update {
+            RobotUpdate::Disconnected(device) => if matches!(experiment_state, State::Active) && enabled(Rule::RobotLost) {
+                let message = format!("{} of {} disconnected during the experiment", device, robot);
+                alert(&config.sinks, Rule::RobotLost, Some(&robot), message);
+            },
+            RobotUpdate::Battery(level) if level < config.battery_threshold => {
+                if critical.insert(robot.clone()) && enabled(Rule::BatteryCritical) {
+                    let message = format!("Battery of {} is at {}%", robot, level);
+                    alert(&config.sinks, Rule::BatteryCritical, Some(&robot), message);
+                }
+            },
+            RobotUpdate::Battery(_) => {
+                critical.remove(&robot);
+            },
+        }
+    }
+    Ok(())
+}
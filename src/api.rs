@@ -0,0 +1,114 @@
+use anyhow::Context;
+use futures::{SinkExt, StreamExt};
+use serde::Serialize;
+use std::collections::HashMap;
+use shared::{builderbot, drone, experiment, pipuck, snapshot::Snapshot, tracking_system};
+use tokio::sync::{mpsc, oneshot};
+use tokio_stream::wrappers::BroadcastStream;
+
+use crate::{arena, subscription, tracking, trajectory, webui};
+
+/* messages for third-party dashboards, encoded as JSON so that they can be used without the shared crate */
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Message {
+    /* the first message on each connection */
+    Snapshot { snapshot: Snapshot },
+    BuilderBot { id: String, update: builderbot::Update },
+    Drone { id: String, update: drone::Update },
+    PiPuck { id: String, update: pipuck::Update },
+    Experiment { state: experiment::State },
+    Tracking { frame: Vec<tracking_system::Update> },
+}
+
+/* camera frames are not republished since they would be very large when encoded as JSON */
+fn is_camera_update(message: &Message) -> bool {
+    matches!(message,
+        Message::BuilderBot { update: builderbot::Update::Camera { .. }, .. } |
+        Message::Drone { update: drone::Update::Camera { .. }, .. } |
+        Message::PiPuck { update: pipuck::Update::Camera { .. }, .. })
+}
+
+pub async fn snapshot(arena_tx: &mpsc::Sender<arena::Action>) -> anyhow::Result<Snapshot> {
+    let (callback_tx, callback_rx) = oneshot::channel();
+    arena_tx.send(arena::Action::GetStateSnapshot(callback_tx)).await
+        .map_err(|_| anyhow::anyhow!("Could not communicate with arena"))?;
+    callback_rx.await
+        .map_err(|_| anyhow::anyhow!("Could not get snapshot from arena"))
+}
+
+pub async fn trajectories(arena_tx: &mpsc::Sender<arena::Action>) -> anyhow::Result<HashMap<String, Vec<trajectory::Sample>>> {
+    let (callback_tx, callback_rx) = oneshot::channel();
+    arena_tx.send(arena::Action::GetTrajectories(callback_tx)).await
+        .map_err(|_| anyhow::anyhow!("Could not communicate with arena"))?;
+    callback_rx.await
+        .map_err(|_| anyhow::anyhow!("Could not get trajectories from arena"))
+}
+
+pub async fn handle_client(
+    ws: warp::ws::WebSocket,
+    arena_tx: mpsc::Sender<arena::Action>,
+    tracking_tx: mpsc::Sender<tracking::Action>
+) {
+    if let Err(error) = stream(ws, arena_tx, tracking_tx).await {
+        log::warn!("API client disconnected: {}", error);
+    }
+}
+
+async fn stream(
+    ws: warp::ws::WebSocket,
+    arena_tx: mpsc::Sender<arena::Action>,
+    tracking_tx: mpsc::Sender<tracking::Action>
+) -> anyhow::Result<()> {
+    let mut builderbot_updates = webui::subscribe_builderbot_updates(&arena_tx).await?;
+    let mut drone_updates = webui::subscribe_drone_updates(&arena_tx).await?;
+    let mut pipuck_updates = webui::subscribe_pipuck_updates(&arena_tx).await?;
+    let (callback_tx, callback_rx) = oneshot::channel();
+    arena_tx.send(arena::Action::SubscribeExperimentState(callback_tx)).await
+        .map_err(|_| anyhow::anyhow!("Could not communicate with arena"))?;
+    let mut experiment_updates = callback_rx.await
+        .map(subscription::updates)
+        .map_err(|_| anyhow::anyhow!("Could not subscribe to experiment updates"))?;
+    let (callback_tx, callback_rx) = oneshot::channel();
+    tracking_tx.send(tracking::Action::Subscribe(callback_tx)).await
+        .map_err(|_| anyhow::anyhow!("Could not communicate with tracking system"))?;
+    let mut tracking_updates = callback_rx.await
+        .map(BroadcastStream::new)
+        .map_err(|_| anyhow::anyhow!("Could not subscribe to tracking system updates"))?;
+    let (mut websocket_tx, mut websocket_rx) = ws.split();
+    /* the snapshot is taken after subscribing so that no update is missed in between */
+    let message = Message::Snapshot { snapshot: snapshot(&arena_tx).await? };
+    let encoded = serde_json::to_string(&message)
+        .context("Could not serialize API message")?;
+    websocket_tx.send(warp::ws::Message::text(encoded)).await
+        .context("Could not send message to API client")?;
+    loop {
+        /* updates that were missed because the client was too slow are skipped */
+        let message = tokio::select! {
+            /* the API is read only, only watch for the client closing the connection */
+            message = websocket_rx.next() => match message {
+                Some(Ok(message)) if !message.is_close() => continue,
+                _ => break,
+            },
+            Some((desc, Ok(update))) = builderbot_updates.next() =>
+                Message::BuilderBot { id: desc.id.clone(), update },
+            Some((desc, Ok(update))) = drone_updates.next() =>
+                Message::Drone { id: desc.id.clone(), update },
+            Some((desc, Ok(update))) = pipuck_updates.next() =>
+                Message::PiPuck { id: desc.id.clone(), update },
+            Some(Ok(state)) = experiment_updates.next() =>
+                Message::Experiment { state },
+            Some(Ok(frame)) = tracking_updates.next() =>
+                Message::Tracking { frame },
+            else => break,
+        };
+        if is_camera_update(&message) {
+            continue;
+        }
+        let encoded = serde_json::to_string(&message)
+            .context("Could not serialize API message")?;
+        websocket_tx.send(warp::ws::Message::text(encoded)).await
+            .context("Could not send message to API client")?;
+    }
+    Ok(())
+}
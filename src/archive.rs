@@ -0,0 +1,125 @@
+use anyhow::Context;
+use bytes::Bytes;
+use std::{path::{Path, PathBuf}, sync::Arc, time::Duration};
+use tokio::sync::mpsc;
+
+#[derive(Debug)]
+pub enum Destination {
+    /* a local directory, e.g., a mounted network file system */
+    Directory(PathBuf),
+    /* a WebDAV collection */
+    WebDav(reqwest::Url),
+}
+
+#[derive(Debug)]
+pub struct Configuration {
+    pub destination: Destination,
+    pub retries: u32,
+    /* remove the local copy once it has been archived */
+    pub prune: bool,
+    /* only remove the local copy if fewer bytes than this are free on its file system */
+    pub min_free: Option<u64>,
+}
+
+pub enum Action {
+    Archive(PathBuf),
+}
+
+async fn upload(destination: &Destination, filename: &str, contents: Bytes) -> anyhow::Result<()> {
+    let digest = md5::compute(&contents);
+    let uploaded = match destination {
+        Destination::Directory(directory) => {
+            let path = directory.join(filename);
+            tokio::fs::write(&path, &contents).await
+                .context(format!("Could not write {:?}", path))?;
+            tokio::fs::read(&path).await
+                .context(format!("Could not read back {:?}", path))?
+        },
+        Destination::WebDav(url) => {
+            let url = url.join(filename)
+                .context(format!("Could not create URL for {}", filename))?;
+            let client = reqwest::Client::new();
+            client.put(url.clone()).body(contents).send().await
+                .and_then(|response| response.error_for_status())
+                .context(format!("Could not upload to {}", url))?;
+            client.get(url.clone()).send().await
+                .and_then(|response| response.error_for_status())
+                .context(format!("Could not download from {}", url))?
+                .bytes().await
+                .context(format!("Could not download from {}", url))?
+                .to_vec()
+        }
+    };
+    /* verify the integrity of the archived copy */
+    match md5::compute(&uploaded) == digest {
+        true => Ok(()),
+        false => Err(anyhow::anyhow!("Archived copy of {} does not match the original", filename)),
+    }
+}
+
+async fn archive(config: &Configuration, path: &Path) -> anyhow::Result<()> {
+    let filename = path.file_name()
+        .and_then(|filename| filename.to_str())
+        .ok_or(anyhow::anyhow!("Could not get the file name of {:?}", path))?;
+    let contents = tokio::fs::read(path).await
+        .map(Bytes::from)
+        .context(format!("Could not read {:?}", path))?;
+    let mut attempt = 0;
+    loop {
+        attempt += 1;
+        match upload(&config.destination, filename, contents.clone()).await {
+            Ok(_) => break,
+            Err(error) if attempt <= config.retries => {
+                log::warn!("Attempt {} to archive {:?} failed: {}", attempt, path, error);
+                /* back off before trying again */
+                tokio::time::sleep(Duration::from_secs(1 << attempt.min(6))).await;
+            },
+            Err(error) => return Err(error),
+        }
+    }
+    log::info!("Archived {:?} to {:?}", path, config.destination);
+    let prune = match config.min_free {
+        Some(min_free) if config.prune => free_space(path).await? < min_free,
+        _ => config.prune,
+    };
+    if prune {
+        tokio::fs::remove_file(path).await
+            .context(format!("Could not remove {:?}", path))?;
+    }
+    Ok(())
+}
+
+/* the number of bytes that are available on the file system of a file according to df */
+async fn free_space(path: &Path) -> anyhow::Result<u64> {
+    let output = tokio::process::Command::new("df").arg("-P").arg("-k").arg(path)
+        .output().await
+        .context("Could not run df")?;
+    /* the available space in kilobytes is the fourth column of the second line */
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .nth(1)
+        .and_then(|line| line.split_whitespace().nth(3))
+        .and_then(|available| available.parse::<u64>().ok())
+        .map(|available| available * 1024)
+        .ok_or(anyhow::anyhow!("Could not get the free space of the file system of {:?}", path))
+}
+
+pub async fn new(
+    config: Option<Configuration>,
+    mut requests_rx: mpsc::Receiver<Action>
+) -> anyhow::Result<()> {
+    let config = config.map(Arc::new);
+    while let Some(action) = requests_rx.recv().await {
+        match action {
+            Action::Archive(path) => if let Some(config) = config.clone() {
+                /* archive in the background so that retries do not delay other requests */
+                tokio::spawn(async move {
+                    if let Err(error) = archive(&config, &path).await {
+                        log::error!("Could not archive {:?}: {}", path, error);
+                    }
+                });
+            }
+        }
+    }
+    Ok(())
+}
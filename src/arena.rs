@@ -1,15 +1,35 @@
 
 use anyhow::Context;
-use futures::{StreamExt, TryStreamExt, stream::FuturesUnordered};
+use futures::{FutureExt, StreamExt, TryStreamExt, stream::FuturesUnordered};
 use log;
 use std::sync::Arc;
 use std::collections::HashMap;
-use tokio::sync::{mpsc, oneshot};
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+use tokio::{sync::{broadcast, mpsc, oneshot, watch}, task::JoinError};
 
-use crate::robot::{builderbot, drone, pipuck};
+use crate::robot::{builderbot, drone, pipuck, plugin, FernbedienungAction, RestartPolicy, SupervisedFlight, XbeeAction};
+use crate::argos;
+use crate::dock;
+use crate::identify;
 use crate::journal;
+use crate::recall;
+use crate::router::{self, LuaType};
+use crate::subscription::Subscription;
+use crate::telemetry;
+use crate::timeouts;
+use crate::tracking;
+use crate::trajectory;
 use crate::network::{xbee, fernbedienung};
-use shared::experiment::software::Software;
+use shared::{event::{Event, Severity}, health::Health};
+use shared::experiment::{Check, Readiness, State, software::Software};
+use shared::group::Group;
+use shared::snapshot::{self, Snapshot};
+
+/* how often the router is asked which controllers have connected before sending the go signal */
+const GO_SIGNAL_POLL_INTERVAL: Duration = Duration::from_millis(100);
 
 pub enum Action {
     /* BuilderBot actions */
@@ -18,111 +38,822 @@ pub enum Action {
     /* Drone actions */
     ForwardDroneAction(String, drone::Action),
     GetDroneDescriptors(oneshot::Sender<Vec<Arc<drone::Descriptor>>>),
+    /* stream setpoints derived from the tracking system to a drone */
+    SetDroneSupervisedFlight {
+        id: String,
+        target: Option<[f32; 3]>,
+        callback: oneshot::Sender<anyhow::Result<()>>,
+    },
+    /* land all drones that are flying, the callback is answered once the commands have been accepted
+       and the completion of each landing is reported as an event */
+    LandAll {
+        landing: shared::drone::Landing,
+        callback: oneshot::Sender<anyhow::Result<()>>,
+    },
     /* Pi-Puck actions */
     ForwardPiPuckAction(String, pipuck::Action),
     GetPiPuckDescriptors(oneshot::Sender<Vec<Arc<pipuck::Descriptor>>>),
+    /* halt or reboot several Pi-Pucks at once */
+    ExecutePiPuckPowerAction {
+        ids: Vec<String>,
+        power: shared::pipuck::Power,
+        callback: oneshot::Sender<anyhow::Result<()>>,
+    },
+    /* drive the Pi-Pucks back to their docks, the callback is answered once the Pi-Pucks have been
+       recalled and the outcome for each Pi-Puck is reported as an event */
+    RecallPiPucks {
+        callback: oneshot::Sender<anyhow::Result<()>>,
+    },
+    /* subscribe to the updates of the robots of types that are not built into the supervisor */
+    SubscribePluginRobots(oneshot::Sender<Vec<(String, broadcast::Receiver<serde_json::Value>)>>),
     /* Arena actions */
     AddXbee(xbee::Device, macaddr::MacAddr6),
     AddFernbedienung(fernbedienung::Device, macaddr::MacAddr6),
+    /* exclude a robot from the arena (or include it again) without removing its descriptor */
+    SetQuarantined {
+        id: String,
+        quarantined: bool,
+        callback: oneshot::Sender<anyhow::Result<()>>,
+    },
+    /* subscribe to changes of the robots that are quarantined */
+    SubscribeQuarantined(oneshot::Sender<watch::Receiver<Vec<String>>>),
+    /* subscribe to the health of the robots, which is assessed by the health task */
+    SubscribeHealth(oneshot::Sender<watch::Receiver<Vec<Health>>>),
+    /* the state of the arena and all of its robots */
+    GetStateSnapshot(oneshot::Sender<Snapshot>),
+    /* the recent poses of each robot with a rigid body, oldest first */
+    GetTrajectories(oneshot::Sender<HashMap<String, Vec<trajectory::Sample>>>),
+    /* subscribe to the messages forwarded by the router */
+    SubscribeRouter(oneshot::Sender<broadcast::Receiver<(SocketAddr, Arc<LuaType>)>>),
+    /* the schemas for decoding the messages forwarded by the router */
+    GetSchemas(oneshot::Sender<Arc<router::schema::Registry>>),
+    /* send the messages recorded in a journal to the robots that are connected to the router */
+    ReplayRouterMessages {
+        journal: PathBuf,
+        speed: f64,
+        senders: Vec<String>,
+        callback: oneshot::Sender<anyhow::Result<usize>>,
+    },
+    StopRouterReplay(oneshot::Sender<anyhow::Result<()>>),
+    /* the recent values of the metrics of a robot, e.g., its signal strength */
+    GetTelemetry {
+        robot: String,
+        window: Duration,
+        callback: oneshot::Sender<anyhow::Result<shared::telemetry::Window>>,
+    },
     /* Experiment actions */
     StartExperiment {
         callback: oneshot::Sender<anyhow::Result<()>>,
         builderbot_software: Software,
         drone_software: Software,
         pipuck_software: Software,
+        /* skip the readiness check */
+        force: bool,
+        /* only start the experiment on the members of this group */
+        group: Option<String>,
     },
     StopExperiment {
         callback: oneshot::Sender<anyhow::Result<()>>,
     },
+    CheckRigidBodies {
+        callback: oneshot::Sender<anyhow::Result<()>>,
+    },
+    IdentifyRigidBodies {
+        callback: oneshot::Sender<anyhow::Result<Vec<(String, Option<i32>)>>>,
+    },
+    CheckReadiness {
+        callback: oneshot::Sender<Readiness>,
+        builderbot_software: Software,
+        drone_software: Software,
+        pipuck_software: Software,
+        group: Option<String>,
+    },
+    /* Group actions */
+    GetGroups(oneshot::Sender<Vec<Group>>),
+    ExecuteGroupRequest {
+        group: String,
+        request: shared::group::Request,
+        callback: oneshot::Sender<anyhow::Result<()>>,
+    },
+    /* Maintenance actions, the callback is answered once the script has been sent to the robots */
+    RunMaintenance {
+        callback: oneshot::Sender<anyhow::Result<()>>,
+    },
+    /* subscribe to changes of the experiment state */
+    SubscribeExperimentState(oneshot::Sender<Subscription<State>>),
+    /* the robots that take part in the current (or most recent) experiment */
+    GetParticipants(oneshot::Sender<Vec<String>>),
+    /* subscribe to the events reported by the arena */
+    SubscribeEvents(oneshot::Sender<broadcast::Receiver<Event>>),
+    /* report an event on behalf of another component, e.g., an error in the output of ARGoS */
+    ReportEvent {
+        severity: Severity,
+        robot: Option<String>,
+        subsystem: String,
+        message: String,
+    },
+    /* record an action that an operator requested through the user interface in the journal */
+    RecordAuditEntry(shared::audit::Entry),
+    /* Control actions */
+    AcquireControl {
+        client: Controller,
+        /* take control even if another client holds it */
+        takeover: bool,
+        callback: oneshot::Sender<anyhow::Result<()>>,
+    },
+    ReleaseControl(Uuid),
+    SubscribeControl(oneshot::Sender<watch::Receiver<Option<Controller>>>),
+}
+
+/* a script that is run on the Up Cores of all connected robots, e.g., to upgrade their packages */
+#[derive(Clone, Debug)]
+pub struct MaintenanceConfiguration {
+    pub script: PathBuf,
+    /* the number of robots on which the script runs at the same time */
+    pub concurrency: usize,
+    pub timeout: Duration,
+}
+
+/* the devices of a quarantined robot are kept by the arena instead of being associated with the
+   robot, dropping them returns their addresses to the network task which then probes them again */
+#[derive(Default)]
+struct QuarantinedDevices {
+    xbees: Vec<xbee::Device>,
+    fernbedienungs: Vec<fernbedienung::Device>,
+}
+
+/* a client of the user interface that can hold control of the arena */
+#[derive(Clone, Debug)]
+pub struct Controller {
+    pub id: Uuid,
+    pub name: String,
+}
+
+/* how experiments are started and what happens if ARGoS fails during an experiment */
+#[derive(Clone, Debug, Default)]
+pub struct ExperimentConfiguration {
+    pub restart_policy: RestartPolicy,
+    /* the delay between starting one robot and the next */
+    pub stagger: Duration,
+    /* if set, send a go signal through the router once all controllers have connected */
+    pub go_signal_timeout: Option<Duration>,
+    /* if set, the readiness check fails for robots whose health is below this score */
+    pub min_health: Option<u8>,
+}
+
+/* the other tasks of an arena that the arena communicates with */
+pub struct Services {
+    pub journal_tx: mpsc::Sender<journal::Action>,
+    pub tracking_tx: mpsc::Sender<tracking::Action>,
+    pub router_tx: mpsc::Sender<router::Action>,
+    pub argos_tx: mpsc::Sender<argos::Action>,
+    pub dock_tx: mpsc::Sender<dock::Action>,
+    pub trajectory_tx: mpsc::Sender<trajectory::Action>,
+    pub telemetry_tx: mpsc::Sender<telemetry::Action>,
+    pub health_rx: watch::Receiver<Vec<Health>>,
+    /* whether destructive actions are only validated and logged instead of being sent to the robots */
+    pub rehearsal_rx: watch::Receiver<bool>,
+}
+
+/* the robots of an arena and how they are managed */
+pub struct Robots {
+    pub groups: Vec<Group>,
+    /* the identifiers of the robots that are simulated by the local instance of ARGoS */
+    pub argos_robots: Vec<String>,
+    /* the number of updates that a subscriber to a robot can fall behind before it misses updates */
+    pub update_capacity: usize,
+    /* the timeouts and retries of the connections to the robots */
+    pub timeouts: timeouts::Configuration,
+    pub builderbots: Vec<builderbot::Descriptor>,
+    pub drones: Vec<drone::Descriptor>,
+    pub pipucks: Vec<pipuck::Descriptor>,
+    pub plugin_robots: Vec<Box<dyn plugin::Robot>>,
 }
 
 pub async fn new(
     mut arena_action_rx: mpsc::Receiver<Action>,
-    journal_action_tx: mpsc::Sender<journal::Action>,
-    builderbots: Vec<builderbot::Descriptor>,
-    drones: Vec<drone::Descriptor>,
-    pipucks: Vec<pipuck::Descriptor>
+    services: Services,
+    experiment_config: ExperimentConfiguration,
+    maintenance_config: Option<MaintenanceConfiguration>,
+    robots: Robots,
 ) {
-    let builderbots: HashMap<Arc<builderbot::Descriptor>, builderbot::Instance> = builderbots
+    let Services {
+        journal_tx: journal_action_tx,
+        tracking_tx,
+        router_tx,
+        argos_tx,
+        dock_tx,
+        trajectory_tx,
+        telemetry_tx,
+        health_rx,
+        rehearsal_rx,
+    } = services;
+    let Robots { groups, argos_robots, update_capacity, timeouts, builderbots, drones, pipucks, plugin_robots } = robots;
+    let mut builderbots: HashMap<Arc<builderbot::Descriptor>, builderbot::Instance> = builderbots
         .into_iter()
-        .map(|descriptor| (Arc::new(descriptor), builderbot::Instance::default()))
+        .map(|descriptor| {
+            let instance = builderbot::Instance::new(descriptor.cameras.clone(), update_capacity);
+            (Arc::new(descriptor), instance)
+        })
         .collect();
-    let drones: HashMap<Arc<drone::Descriptor>, drone::Instance> = drones
+    let mut drones: HashMap<Arc<drone::Descriptor>, drone::Instance> = drones
         .into_iter()
-        .map(|descriptor| (Arc::new(descriptor), drone::Instance::default()))
+        .map(|descriptor| {
+            let instance = drone::Instance::new(descriptor.cameras.clone(), descriptor.profile.clone(),
+                rigid_body(descriptor.optitrack_id, &tracking_tx), timeouts, update_capacity);
+            (Arc::new(descriptor), instance)
+        })
         .collect();
-    let pipucks: HashMap<Arc<pipuck::Descriptor>, pipuck::Instance> = pipucks
+    let mut pipucks: HashMap<Arc<pipuck::Descriptor>, pipuck::Instance> = pipucks
         .into_iter()
-        .map(|descriptor| (Arc::new(descriptor), pipuck::Instance::default()))
+        .map(|descriptor| {
+            let instance = pipuck::Instance::new(descriptor.cameras.clone(), update_capacity);
+            (Arc::new(descriptor), instance)
+        })
         .collect();
-    while let Some(action) = arena_action_rx.recv().await {
+    let rigid_bodies = builderbots.keys()
+        .filter_map(|desc| desc.optitrack_id.map(|optitrack_id| (desc.id.clone(), optitrack_id)))
+        .chain(drones.keys()
+            .filter_map(|desc| desc.optitrack_id.map(|optitrack_id| (desc.id.clone(), optitrack_id))))
+        .chain(pipucks.keys()
+            .filter_map(|desc| desc.optitrack_id.map(|optitrack_id| (desc.id.clone(), optitrack_id))))
+        .collect::<Vec<_>>();
+    /* events are reported to the log, the journal, and the clients of the web interface */
+    let (events_tx, _) = broadcast::channel(32);
+    /* check the rigid bodies once the tracking system has had time to stream some data */
+    let startup_tracking_tx = tracking_tx.clone();
+    let startup_rigid_bodies = rigid_bodies.clone();
+    let startup_events_tx = events_tx.clone();
+    let startup_journal_action_tx = journal_action_tx.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_secs(5)).await;
+        let (severity, message) = match check_rigid_bodies(&startup_tracking_tx, &startup_rigid_bodies).await {
+            Ok(message) => (Severity::Info, message),
+            Err(error) => (Severity::Warning, error.to_string()),
+        };
+        report(&startup_events_tx, &startup_journal_action_tx, severity, None, "tracking", message).await;
+    });
+    /* only one client may send actions to the robots and the arena at a time */
+    let (controller_tx, controller_rx) = watch::channel(None::<Controller>);
+    let mut experiment_state = State::Standby;
+    let (experiment_state_tx, _) = broadcast::channel(8);
+    let mut participants: Vec<String> = Vec::new();
+    /* robots that are excluded from the arena, e.g., a drone with a broken arm */
+    let mut quarantine: HashMap<String, QuarantinedDevices> = HashMap::new();
+    let (quarantined_tx, quarantined_rx) = watch::channel(Vec::<String>::new());
+    loop {
+        let action = tokio::select! {
+            action = arena_action_rx.recv() => match action {
+                Some(action) => action,
+                None => break,
+            },
+            /* restart the tasks of drones and Pi-Pucks that have failed, the devices of the failed task
+               are returned to the network task, which then adds them to the arena again */
+            Some((descriptor, result)) = drones.iter_mut()
+                .map(|(descriptor, instance)| instance.terminated()
+                    .map(move |result| (descriptor.clone(), result)))
+                .collect::<FuturesUnordered<_>>()
+                .into_future()
+                .map(|(terminated, _)| terminated) => {
+                report_task_failure(&descriptor.id, result, experiment_state, &events_tx, &journal_action_tx).await;
+                let instance = drone::Instance::new(descriptor.cameras.clone(), descriptor.profile.clone(),
+                    rigid_body(descriptor.optitrack_id, &tracking_tx), timeouts, update_capacity);
+                drones.insert(descriptor, instance);
+                continue;
+            },
+            Some((descriptor, result)) = pipucks.iter_mut()
+                .map(|(descriptor, instance)| instance.terminated()
+                    .map(move |result| (descriptor.clone(), result)))
+                .collect::<FuturesUnordered<_>>()
+                .into_future()
+                .map(|(terminated, _)| terminated) => {
+                report_task_failure(&descriptor.id, result, experiment_state, &events_tx, &journal_action_tx).await;
+                let instance = pipuck::Instance::new(descriptor.cameras.clone(), update_capacity);
+                pipucks.insert(descriptor, instance);
+                continue;
+            },
+        };
+        /* rehearsal mode is enforced here so that it also applies to the script and the maintenance,
+           experiments are rehearsed after their readiness check below */
+        if *rehearsal_rx.borrow() && is_destructive(&action) {
+            let validation = match &action {
+                Action::ForwardBuilderBotAction(id, _) if !builderbots.keys().any(|desc| &desc.id == id) =>
+                    Err(anyhow::anyhow!("Could not find BuilderBot with identifier {}", id)),
+                Action::ForwardDroneAction(id, _) if !drones.keys().any(|desc| &desc.id == id) =>
+                    Err(anyhow::anyhow!("Could not find drone with identifier {}", id)),
+                Action::ForwardPiPuckAction(id, _) if !pipucks.keys().any(|desc| &desc.id == id) =>
+                    Err(anyhow::anyhow!("Could not find Pi-Puck with identifier {}", id)),
+                Action::ExecutePiPuckPowerAction { ids, .. } =>
+                    match ids.iter().find(|&id| !pipucks.keys().any(|desc| &desc.id == id)) {
+                        Some(id) => Err(anyhow::anyhow!("Could not find Pi-Puck with identifier {}", id)),
+                        None => Ok(()),
+                    },
+                Action::ExecuteGroupRequest { group, .. } => find_members(&groups, Some(group.as_str())).map(drop),
+                _ => Ok(()),
+            };
+            let (severity, message) = match &validation {
+                Ok(_) => (Severity::Info, format!("Rehearsed \"{}\", nothing was sent to the robots", describe(&action))),
+                Err(error) => (Severity::Warning, format!("Rehearsal of \"{}\" failed: {}", describe(&action), error)),
+            };
+            rehearse(action, validation);
+            report(&events_tx, &journal_action_tx, severity, None, "rehearsal", message).await;
+            continue;
+        }
         match action {
             Action::AddXbee(device, macaddr) => {
+                if let Some(devices) = drones.keys()
+                    .find(|desc| desc.xbee_macaddr == macaddr)
+                    .and_then(|desc| quarantine.get_mut(&desc.id)) {
+                    devices.xbees.push(device);
+                    continue;
+                }
                 match &associate_xbee_device(macaddr, &drones)[..] {
                     [instance] => {
                         let request = drone::Action::AssociateXbee(device);
                         let _ = instance.action_tx.send(request).await;
                     },
-                    [_, _, ..] => log::error!("Xbee {} is associated with multiple drones", macaddr),
-                    [] => log::warn!("Xbee {} is not associated with any drone", macaddr),
+                    [_, _, ..] => report(&events_tx, &journal_action_tx, Severity::Error, None, "network",
+                        format!("Xbee {} is associated with multiple drones", macaddr)).await,
+                    [] => match plugin_robots.iter().find(|robot| robot.claims_xbee(macaddr)) {
+                        Some(robot) => robot.associate_xbee(device).await,
+                        None => report(&events_tx, &journal_action_tx, Severity::Warning, None, "network",
+                            format!("Xbee {} is not associated with any robot", macaddr)).await,
+                    },
                 }
             },
             Action::AddFernbedienung(device, macaddr) => {
+                let id = drones.keys()
+                    .find(|desc| desc.upcore_macaddr == macaddr)
+                    .map(|desc| &desc.id)
+                    .or_else(|| pipucks.keys()
+                        .find(|desc| desc.rpi_macaddr == macaddr)
+                        .map(|desc| &desc.id))
+                    .or_else(|| builderbots.keys()
+                        .find(|desc| desc.duovero_macaddr == macaddr)
+                        .map(|desc| &desc.id));
+                if let Some(devices) = id.and_then(|id| quarantine.get_mut(id)) {
+                    devices.fernbedienungs.push(device);
+                    continue;
+                }
                 /* first: attempt to associate fernbedienung with a drone */
                 match &associate_fernbedienung_device_with_drone(macaddr, &drones)[..] {
                     [instance] => {
                         let request = drone::Action::AssociateFernbedienung(device);
                         let _ = instance.action_tx.send(request).await;
                     },
-                    [_, _, ..] => log::error!("Fernbedienung {} is associated with multiple drones", macaddr),
+                    [_, _, ..] => report(&events_tx, &journal_action_tx, Severity::Error, None, "network",
+                        format!("Fernbedienung {} is associated with multiple drones", macaddr)).await,
                     /* second: attempt to associate fernbedienung with a Pi-Puck */
                     [] => match &associate_fernbedienung_device_with_pipuck(macaddr, &pipucks)[..] {
                         [instance] => {
                             let request = pipuck::Action::AssociateFernbedienung(device);
                             let _ = instance.action_tx.send(request).await;
                         },
-                        [_, _, ..] => log::error!("Fernbedienung {} is associated with multiple Pi-Pucks", macaddr),
+                        [_, _, ..] => report(&events_tx, &journal_action_tx, Severity::Error, None, "network",
+                            format!("Fernbedienung {} is associated with multiple Pi-Pucks", macaddr)).await,
                         /* third: attempt to associate fernbedienung with a BuilderBot */
                         [] => match &associate_fernbedienung_device_with_builderbot(macaddr, &builderbots)[..] {
                             [instance] => {
                                 let request = builderbot::Action::AssociateFernbedienung(device);
                                 let _ = instance.action_tx.send(request).await;
                             },
-                            [_, _, ..] => log::error!("Fernbedienung {} is associated with multiple BuilderBots", macaddr),
-                            [] => log::warn!("Fernbedienung {} is not associated with any robot", macaddr),
+                            [_, _, ..] => report(&events_tx, &journal_action_tx, Severity::Error, None, "network",
+                                format!("Fernbedienung {} is associated with multiple BuilderBots", macaddr)).await,
+                            /* finally: attempt to associate fernbedienung with a robot of another type */
+                            [] => match plugin_robots.iter().find(|robot| robot.claims_fernbedienung(macaddr)) {
+                                Some(robot) => robot.associate_fernbedienung(device).await,
+                                None => report(&events_tx, &journal_action_tx, Severity::Warning, None, "network",
+                                    format!("Fernbedienung {} is not associated with any robot", macaddr)).await,
+                            },
                         },
                     }
                 }
             },
             /* Arena requests */
-            Action::StartExperiment { callback, builderbot_software, drone_software, pipuck_software } => {
+            Action::StartExperiment { callback, builderbot_software, drone_software, pipuck_software, force, group } => {
+                let members = match find_members(&groups, group.as_deref()) {
+                    Ok(members) => members,
+                    Err(error) => {
+                        let _ = callback.send(Err(error).context("Could not start experiment"));
+                        continue;
+                    }
+                };
+                /* quarantined robots do not take part in experiments */
+                let selected = |id: &str| !quarantine.contains_key(id) &&
+                    members.is_none_or(|members| members.iter().any(|member| member == id));
+                let selection = Selection {
+                    builderbots: builderbots.iter().filter(|(desc, _)| selected(&desc.id)).collect(),
+                    builderbot_software: &builderbot_software,
+                    drones: drones.iter().filter(|(desc, _)| selected(&desc.id)).collect(),
+                    drone_software: &drone_software,
+                    pipucks: pipucks.iter().filter(|(desc, _)| selected(&desc.id)).collect(),
+                    pipuck_software: &pipuck_software,
+                };
+                let selected_plugin_robots = plugin_robots.iter()
+                    .filter(|robot| selected(robot.id()))
+                    .map(|robot| robot.as_ref())
+                    .collect::<Vec<&dyn plugin::Robot>>();
+                let selected_argos_robots = argos_robots.iter()
+                    .filter(|id| selected(id))
+                    .cloned()
+                    .collect::<Vec<_>>();
+                /* do not start an experiment unless the arena is ready or the start is forced */
+                if !force {
+                    let selected_rigid_bodies = rigid_bodies.iter()
+                        .filter(|(id, _)| selected(id))
+                        .cloned()
+                        .collect::<Vec<_>>();
+                    let readiness = check_readiness(&selection,
+                        &selected_rigid_bodies, &tracking_tx, &journal_action_tx, &dock_tx,
+                        &health_rx.borrow().clone(), experiment_config.min_health).await;
+                    if !readiness.is_ready() {
+                        let error = anyhow::anyhow!("Arena is not ready: {}", readiness.failures());
+                        if *rehearsal_rx.borrow() {
+                            report(&events_tx, &journal_action_tx, Severity::Warning, None, "rehearsal",
+                                format!("Rehearsal of \"Start experiment\" failed: {}", error)).await;
+                        }
+                        let _ = callback.send(Err(error).context("Could not start experiment"));
+                        continue;
+                    }
+                }
+                if *rehearsal_rx.borrow() {
+                    report(&events_tx, &journal_action_tx, Severity::Info, None, "rehearsal",
+                        String::from("Rehearsed \"Start experiment\", nothing was sent to the robots")).await;
+                    let _ = callback.send(Ok(()));
+                    continue;
+                }
+                let selected_ids = selection.builderbots.iter().map(|(desc, _)| desc.id.clone())
+                    .chain(selection.drones.iter().map(|(desc, _)| desc.id.clone()))
+                    .chain(selection.pipucks.iter().map(|(desc, _)| desc.id.clone()))
+                    .chain(selected_plugin_robots.iter().map(|robot| robot.id().to_owned()))
+                    .collect::<Vec<_>>();
                 let start_result = start_experiment(
-                    &builderbots,
-                    &builderbot_software,
-                    &drones,
-                    &drone_software,
-                    &pipucks,
-                    &pipuck_software,
-                    &journal_action_tx).await;
+                    &selection,
+                    &selected_plugin_robots,
+                    &selected_argos_robots,
+                    &argos_tx,
+                    &journal_action_tx,
+                    &router_tx,
+                    &experiment_config).await;
                 let result = match start_result {
-                    Ok(_) => Ok(()),
-                    Err(start_error) => match stop_experiment(&builderbots, &drones, &pipucks, &journal_action_tx).await {
+                    Ok(_) => {
+                        participants = selected_ids;
+                        experiment_state = State::Active;
+                        let _ = experiment_state_tx.send(experiment_state);
+                        Ok(())
+                    },
+                    Err(start_error) => match stop_experiment(&builderbots, &drones, &pipucks, &plugin_robots, &argos_tx, &journal_action_tx).await {
                         Ok(_) => Err(start_error),
                         Err(stop_error) => Err(stop_error).context(start_error),
                     }
                 };
+                let (severity, message) = match (&result, &group) {
+                    (Ok(_), Some(group)) => (Severity::Info, format!("Experiment started on group {}", group)),
+                    (Ok(_), None) => (Severity::Info, String::from("Experiment started")),
+                    (Err(error), _) => (Severity::Error, format!("Could not start experiment: {:#}", error)),
+                };
+                report(&events_tx, &journal_action_tx, severity, None, "experiment", message).await;
                 let _ = callback.send(result);
             },
+            Action::CheckReadiness { callback, builderbot_software, drone_software, pipuck_software, group } => {
+                let members = match find_members(&groups, group.as_deref()) {
+                    Ok(members) => members,
+                    Err(error) => {
+                        let check = Check { subject: String::from("Group"), failures: vec![error.to_string()] };
+                        let _ = callback.send(Readiness(vec![check]));
+                        continue;
+                    }
+                };
+                let selected = |id: &str| !quarantine.contains_key(id) &&
+                    members.is_none_or(|members| members.iter().any(|member| member == id));
+                let selected_rigid_bodies = rigid_bodies.iter()
+                    .filter(|(id, _)| selected(id))
+                    .cloned()
+                    .collect::<Vec<_>>();
+                let selection = Selection {
+                    builderbots: builderbots.iter().filter(|(desc, _)| selected(&desc.id)).collect(),
+                    builderbot_software: &builderbot_software,
+                    drones: drones.iter().filter(|(desc, _)| selected(&desc.id)).collect(),
+                    drone_software: &drone_software,
+                    pipucks: pipucks.iter().filter(|(desc, _)| selected(&desc.id)).collect(),
+                    pipuck_software: &pipuck_software,
+                };
+                let readiness = check_readiness(&selection,
+                    &selected_rigid_bodies, &tracking_tx, &journal_action_tx, &dock_tx,
+                    &health_rx.borrow().clone(), experiment_config.min_health).await;
+                let _ = callback.send(readiness);
+            },
+            Action::SetQuarantined { id, quarantined, callback } => {
+                if !builderbots.keys().any(|desc| desc.id == id) &&
+                   !drones.keys().any(|desc| desc.id == id) &&
+                   !pipucks.keys().any(|desc| desc.id == id) {
+                    let _ = callback.send(Err(anyhow::anyhow!("Could not find robot with identifier {}", id)));
+                    continue;
+                }
+                match quarantined {
+                    true if !quarantine.contains_key(&id) => {
+                        quarantine.insert(id.clone(), QuarantinedDevices::default());
+                        /* restart the task of the robot so that its devices are released, these are then
+                           probed again by the network task and kept by the arena */
+                        if let Some((desc, instance)) = builderbots.iter_mut().find(|(desc, _)| desc.id == id) {
+                            *instance = builderbot::Instance::new(desc.cameras.clone(), update_capacity);
+                        }
+                        if let Some((desc, instance)) = drones.iter_mut().find(|(desc, _)| desc.id == id) {
+                            *instance = drone::Instance::new(desc.cameras.clone(), desc.profile.clone(),
+                                rigid_body(desc.optitrack_id, &tracking_tx), timeouts, update_capacity);
+                        }
+                        if let Some((desc, instance)) = pipucks.iter_mut().find(|(desc, _)| desc.id == id) {
+                            *instance = pipuck::Instance::new(desc.cameras.clone(), update_capacity);
+                        }
+                        report(&events_tx, &journal_action_tx, Severity::Warning, Some(id.as_str()), "arena",
+                            format!("{} has been quarantined", id)).await;
+                        if let State::Active = experiment_state {
+                            report(&events_tx, &journal_action_tx, Severity::Warning, Some(id.as_str()), "experiment",
+                                format!("{} is no longer taking part in the experiment", id)).await;
+                        }
+                    },
+                    /* dropping the devices makes them available to the network task again */
+                    false if quarantine.remove(&id).is_some() => {
+                        report(&events_tx, &journal_action_tx, Severity::Info, Some(id.as_str()), "arena",
+                            format!("{} has been released from quarantine", id)).await;
+                    },
+                    _ => {}
+                }
+                let mut quarantined = quarantine.keys().cloned().collect::<Vec<_>>();
+                quarantined.sort();
+                let _ = quarantined_tx.send(quarantined);
+                let _ = callback.send(Ok(()));
+            },
+            Action::SubscribeQuarantined(callback) => {
+                let _ = callback.send(quarantined_rx.clone());
+            },
+            Action::SubscribeHealth(callback) => {
+                let _ = callback.send(health_rx.clone());
+            },
+            Action::GetTrajectories(callback) => {
+                /* the trajectory task answers the callback directly */
+                let _ = trajectory_tx.send(trajectory::Action::Get(callback)).await;
+            },
+            Action::SubscribeRouter(callback) => {
+                /* the router answers the callback directly */
+                let _ = router_tx.send(router::Action::Subscribe(callback)).await;
+            },
+            Action::GetSchemas(callback) => {
+                let _ = router_tx.send(router::Action::GetSchemas(callback)).await;
+            },
+            Action::ReplayRouterMessages { journal, speed, senders, callback } => {
+                let _ = router_tx.send(router::Action::Replay { journal, speed, senders, callback }).await;
+            },
+            Action::StopRouterReplay(callback) => {
+                let _ = router_tx.send(router::Action::StopReplay(callback)).await;
+            },
+            Action::GetTelemetry { robot, window, callback } => {
+                /* the telemetry task answers the callback directly */
+                let _ = telemetry_tx.send(telemetry::Action::Get { robot, window, callback }).await;
+            },
+            Action::GetStateSnapshot(callback) => {
+                let mut builderbot_txs = builderbots.iter()
+                    .map(|(desc, instance)| (desc.as_ref().clone(), instance.action_tx.clone()))
+                    .collect::<Vec<_>>();
+                builderbot_txs.sort_by(|(lhs, _), (rhs, _)| lhs.id.cmp(&rhs.id));
+                let mut drone_txs = drones.iter()
+                    .map(|(desc, instance)| (desc.as_ref().clone(), instance.action_tx.clone()))
+                    .collect::<Vec<_>>();
+                drone_txs.sort_by(|(lhs, _), (rhs, _)| lhs.id.cmp(&rhs.id));
+                let mut pipuck_txs = pipucks.iter()
+                    .map(|(desc, instance)| (desc.as_ref().clone(), instance.action_tx.clone()))
+                    .collect::<Vec<_>>();
+                pipuck_txs.sort_by(|(lhs, _), (rhs, _)| lhs.id.cmp(&rhs.id));
+                let quarantined = quarantined_rx.borrow().clone();
+                let experiment = experiment_state;
+                /* do not block the arena while the robots respond */
+                tokio::spawn(async move {
+                    let snapshot = Snapshot {
+                        builderbots: robot_states(builderbot_txs, builderbot::Action::GetState).await,
+                        drones: robot_states(drone_txs, drone::Action::GetState).await,
+                        pipucks: robot_states(pipuck_txs, pipuck::Action::GetState).await,
+                        quarantined,
+                        experiment,
+                    };
+                    let _ = callback.send(snapshot);
+                });
+            },
+            Action::GetGroups(callback) => {
+                let _ = callback.send(groups.clone());
+            },
+            Action::ExecuteGroupRequest { group, request, callback } => {
+                let members = match find_members(&groups, Some(group.as_str())) {
+                    Ok(members) => members.unwrap_or_default(),
+                    Err(error) => {
+                        let _ = callback.send(Err(error));
+                        continue;
+                    }
+                };
+                let is_member = |id: &str| !quarantine.contains_key(id) && members.iter().any(|member| member == id);
+                let builderbot_txs = builderbots.iter()
+                    .filter(|(desc, _)| is_member(&desc.id))
+                    .map(|(desc, instance)| (desc.id.clone(), instance.action_tx.clone()))
+                    .collect::<Vec<_>>();
+                let drone_txs = drones.iter()
+                    .filter(|(desc, _)| is_member(&desc.id))
+                    .map(|(desc, instance)| (desc.id.clone(), instance.action_tx.clone()))
+                    .collect::<Vec<_>>();
+                let pipuck_txs = pipucks.iter()
+                    .filter(|(desc, _)| is_member(&desc.id))
+                    .map(|(desc, instance)| (desc.id.clone(), instance.action_tx.clone()))
+                    .collect::<Vec<_>>();
+                report(&events_tx, &journal_action_tx, Severity::Info, None, "arena",
+                    format!("Executing {:?} on group {}", request, group)).await;
+                /* halting and rebooting take a while, do not block the arena */
+                tokio::spawn(async move {
+                    let result = execute_group_request(builderbot_txs, drone_txs, pipuck_txs, request).await
+                        .context(format!("Could not execute {:?} on group {}", request, group));
+                    let _ = callback.send(result);
+                });
+            },
+            Action::RunMaintenance { callback } => {
+                let config = match (maintenance_config.as_ref(), experiment_state) {
+                    (None, _) => Err(anyhow::anyhow!("Maintenance has not been configured")),
+                    (_, State::Active) => Err(anyhow::anyhow!("Maintenance cannot be run during an experiment")),
+                    (Some(config), _) => Ok(config.clone()),
+                };
+                let script = match config {
+                    Ok(config) => tokio::fs::read(&config.script).await
+                        .context(format!("Could not read {:?}", config.script))
+                        .map(|script| (config, script)),
+                    Err(error) => Err(error),
+                };
+                let (config, script) = match script {
+                    Ok(script) => script,
+                    Err(error) => {
+                        let _ = callback.send(Err(error).context("Could not run maintenance"));
+                        continue;
+                    }
+                };
+                /* quarantined robots are not connected and are skipped */
+                let mut scripts = Vec::new();
+                for (desc, instance) in builderbots.iter().filter(|(desc, _)| !quarantine.contains_key(&desc.id)) {
+                    let (callback_tx, callback_rx) = oneshot::channel();
+                    let (output_tx, output_rx) = oneshot::channel();
+                    let action = FernbedienungAction::RunScript(script.clone(), config.timeout, output_tx);
+                    let action = builderbot::Action::ExecuteFernbedienungAction(callback_tx, action);
+                    let action_tx = instance.action_tx.clone();
+                    let send = async move { action_tx.send(action).await.is_ok() };
+                    scripts.push((desc.id.clone(), run_maintenance_script(send, callback_rx, output_rx).boxed()));
+                }
+                for (desc, instance) in drones.iter().filter(|(desc, _)| !quarantine.contains_key(&desc.id)) {
+                    let (callback_tx, callback_rx) = oneshot::channel();
+                    let (output_tx, output_rx) = oneshot::channel();
+                    let action = FernbedienungAction::RunScript(script.clone(), config.timeout, output_tx);
+                    let action = drone::Action::ExecuteFernbedienungAction(callback_tx, action);
+                    let action_tx = instance.action_tx.clone();
+                    let send = async move { action_tx.send(action).await.is_ok() };
+                    scripts.push((desc.id.clone(), run_maintenance_script(send, callback_rx, output_rx).boxed()));
+                }
+                for (desc, instance) in pipucks.iter().filter(|(desc, _)| !quarantine.contains_key(&desc.id)) {
+                    let (callback_tx, callback_rx) = oneshot::channel();
+                    let (output_tx, output_rx) = oneshot::channel();
+                    let action = FernbedienungAction::RunScript(script.clone(), config.timeout, output_tx);
+                    let action = pipuck::Action::ExecuteFernbedienungAction(callback_tx, action);
+                    let action_tx = instance.action_tx.clone();
+                    let send = async move { action_tx.send(action).await.is_ok() };
+                    scripts.push((desc.id.clone(), run_maintenance_script(send, callback_rx, output_rx).boxed()));
+                }
+                report(&events_tx, &journal_action_tx, Severity::Info, None, "maintenance",
+                    format!("Running {:?} on {} robot(s)", config.script, scripts.len())).await;
+                let events_tx = events_tx.clone();
+                let journal_action_tx = journal_action_tx.clone();
+                /* updating the robots takes a while, so the results are reported as events instead */
+                let _ = callback.send(Ok(()));
+                /* build each future up front so that it owns everything it needs to report its result */
+                let tasks = scripts.into_iter().map(|(id, script)| {
+                    let events_tx = events_tx.clone();
+                    let journal_action_tx = journal_action_tx.clone();
+                    async move {
+                        match script.await {
+                            Ok(output) => {
+                                log::debug!("Output of maintenance script on {}:\n{}", id, output);
+                                report(&events_tx, &journal_action_tx, Severity::Info, Some(&id), "maintenance",
+                                    String::from("Maintenance script completed")).await;
+                                None
+                            },
+                            Err(error) => {
+                                report(&events_tx, &journal_action_tx, Severity::Error, Some(&id), "maintenance",
+                                    format!("Maintenance script failed: {:#}", error)).await;
+                                Some(id)
+                            }
+                        }
+                    }
+                }).collect::<Vec<_>>();
+                tokio::spawn(async move {
+                    let failures = futures::stream::iter(tasks)
+                        .buffer_unordered(config.concurrency)
+                        .filter_map(futures::future::ready)
+                        .collect::<Vec<String>>().await;
+                    let (severity, message) = match failures.is_empty() {
+                        true => (Severity::Info, String::from("Maintenance completed on all robots")),
+                        false => (Severity::Error, format!("Maintenance failed on {}", failures.join(", "))),
+                    };
+                    report(&events_tx, &journal_action_tx, severity, None, "maintenance", message).await;
+                });
+            },
             Action::StopExperiment { callback } => {
-                let result = stop_experiment(&builderbots, &drones, &pipucks, &journal_action_tx).await;
-                let _ = callback.send(result.context("Could not stop experiment"));
+                let result = stop_experiment(&builderbots, &drones, &pipucks, &plugin_robots, &argos_tx, &journal_action_tx).await;
+                if result.is_ok() {
+                    experiment_state = State::Standby;
+                    let _ = experiment_state_tx.send(experiment_state);
+                }
+                let result = result.context("Could not stop experiment");
+                let (severity, message) = match &result {
+                    Ok(_) => (Severity::Info, String::from("Experiment stopped")),
+                    Err(error) => (Severity::Error, format!("{:#}", error)),
+                };
+                report(&events_tx, &journal_action_tx, severity, None, "experiment", message).await;
+                let _ = callback.send(result);
+            },
+            Action::AcquireControl { client, takeover, callback } => {
+                let result = match controller_rx.borrow().as_ref() {
+                    Some(controller) if controller.id != client.id && !takeover =>
+                        Err(anyhow::anyhow!("{} has control of the arena", controller.name)),
+                    _ => Ok(()),
+                };
+                if result.is_ok() {
+                    let changed = controller_rx.borrow().as_ref()
+                        .is_none_or(|controller| controller.id != client.id);
+                    if changed {
+                        report(&events_tx, &journal_action_tx, Severity::Info, None, "control",
+                            format!("{} has taken control of the arena", client.name)).await;
+                        let _ = controller_tx.send(Some(client));
+                    }
+                }
+                let _ = callback.send(result);
+            },
+            Action::ReleaseControl(id) => {
+                let holds_control = controller_rx.borrow().as_ref()
+                    .is_some_and(|controller| controller.id == id);
+                if holds_control {
+                    let _ = controller_tx.send(None);
+                }
+            },
+            Action::SubscribeControl(callback) => {
+                let _ = callback.send(controller_rx.clone());
+            },
+            Action::SubscribeExperimentState(callback) => {
+                let _ = callback.send((vec![experiment_state], experiment_state_tx.subscribe()));
+            },
+            Action::GetParticipants(callback) => {
+                let _ = callback.send(participants.clone());
+            },
+            Action::SubscribePluginRobots(callback) => {
+                let receivers = plugin_robots.iter()
+                    .map(|robot| (robot.id().to_owned(), robot.subscribe()))
+                    .collect();
+                let _ = callback.send(receivers);
+            },
+            Action::SubscribeEvents(callback) => {
+                let _ = callback.send(events_tx.subscribe());
+            },
+            Action::RecordAuditEntry(entry) => {
+                if journal_action_tx.send(journal::Action::Record(journal::Event::Audit(entry))).await.is_err() {
+                    log::error!("Could not record audit entry in journal");
+                }
+            },
+            Action::ReportEvent { severity, robot, subsystem, message } => {
+                report(&events_tx, &journal_action_tx, severity, robot.as_deref(), &subsystem, message).await;
+            },
+            Action::CheckRigidBodies { callback } => {
+                let result = match check_rigid_bodies(&tracking_tx, &rigid_bodies).await {
+                    Ok(message) => {
+                        report(&events_tx, &journal_action_tx, Severity::Info, None, "tracking", message).await;
+                        Ok(())
+                    },
+                    Err(error) => Err(error),
+                };
+                let _ = callback.send(result);
+            },
+            Action::IdentifyRigidBodies { callback } => {
+                /* drones are not moved since this would require them to take off */
+                let mut robots = builderbots.iter()
+                    .map(|(desc, instance)|
+                        (desc.id.clone(), identify::Robot::BuilderBot(instance.action_tx.clone())))
+                    .chain(pipucks.iter()
+                        .map(|(desc, instance)|
+                            (desc.id.clone(), identify::Robot::PiPuck(instance.action_tx.clone()))))
+                    .collect::<Vec<_>>();
+                robots.sort_by(|(a, _), (b, _)| a.cmp(b));
+                /* identification takes several seconds per robot, do not block the arena */
+                let tracking_tx = tracking_tx.clone();
+                tokio::spawn(async move {
+                    let _ = callback.send(identify::rigid_bodies(robots, tracking_tx).await);
+                });
             },
             Action::ForwardBuilderBotAction(id, request) => {
                 match builderbots.iter().find(|&(desc, _)| desc.id == id) {
                     Some((_, instance)) => {
                         let _ = instance.action_tx.send(request).await;
                     }
-                    None => log::warn!("Could not find BuilderBot with identifier {}", id),
+                    None => report(&events_tx, &journal_action_tx, Severity::Warning, Some(id.as_str()), "arena",
+                        format!("Could not find BuilderBot with identifier {}", id)).await,
                 }
             }
             Action::GetBuilderBotDescriptors(callback) => {
@@ -133,28 +864,647 @@ pub async fn new(
                     Some((_, instance)) => {
                         let _ = instance.action_tx.send(request).await;
                     }
-                    None => log::warn!("Could not find drone with identifier {}", id),
+                    None => report(&events_tx, &journal_action_tx, Severity::Warning, Some(id.as_str()), "arena",
+                        format!("Could not find drone with identifier {}", id)).await,
                 }
             }
             Action::GetDroneDescriptors(callback) => {
                 let _ = callback.send(drones.keys().cloned().collect::<Vec<_>>());
             },
+            Action::SetDroneSupervisedFlight { id, target, callback } => {
+                match drones.iter().find(|&(desc, _)| desc.id == id) {
+                    Some((desc, instance)) => match target {
+                        Some(target) => match desc.optitrack_id {
+                            Some(optitrack_id) => match subscribe_rigid_body(&tracking_tx, optitrack_id).await {
+                                Ok(poses) => {
+                                    let action = XbeeAction::SetSupervisedFlight(Some(SupervisedFlight { poses, target }));
+                                    let _ = instance.action_tx.send(drone::Action::ExecuteXbeeAction(callback, action)).await;
+                                },
+                                Err(error) => {
+                                    let _ = callback.send(Err(error));
+                                }
+                            },
+                            None => {
+                                let _ = callback.send(Err(anyhow::anyhow!("{} does not have a rigid body", id)));
+                            }
+                        },
+                        None => {
+                            let action = XbeeAction::SetSupervisedFlight(None);
+                            let _ = instance.action_tx.send(drone::Action::ExecuteXbeeAction(callback, action)).await;
+                        }
+                    },
+                    None => {
+                        let _ = callback.send(Err(anyhow::anyhow!("Could not find drone with identifier {}", id)));
+                    }
+                }
+            },
+            Action::LandAll { landing, callback } => {
+                /* quarantined drones are not connected and are skipped */
+                let commands = drones.iter()
+                    .filter(|(desc, _)| !quarantine.contains_key(&desc.id))
+                    .map(|(desc, instance)| {
+                        let (callback_tx, callback_rx) = oneshot::channel();
+                        let (landed_tx, landed_rx) = oneshot::channel();
+                        let action = drone::Action::ExecuteXbeeAction(callback_tx, XbeeAction::Land(landing, landed_tx));
+                        let action_tx = instance.action_tx.clone();
+                        let id = desc.id.clone();
+                        async move {
+                            let result = match action_tx.send(action).await {
+                                Ok(_) => callback_rx.await
+                                    .map_err(|_| anyhow::anyhow!("No response from drone"))
+                                    .and_then(|result| result),
+                                Err(_) => Err(anyhow::anyhow!("Could not send action to drone")),
+                            };
+                            (id, result.map(|_| landed_rx))
+                        }
+                    })
+                    .collect::<FuturesUnordered<_>>();
+                let events_tx = events_tx.clone();
+                let journal_action_tx = journal_action_tx.clone();
+                /* landing takes a while, do not block the arena */
+                tokio::spawn(async move {
+                    let mut failures = Vec::new();
+                    let mut landings = FuturesUnordered::new();
+                    for (id, result) in commands.collect::<Vec<_>>().await {
+                        match result {
+                            Ok(landed_rx) => landings.push(landed_rx.map(move |result| (id, result))),
+                            Err(error) => failures.push(format!("{}: {:#}", id, error)),
+                        }
+                    }
+                    let verb = match landing {
+                        shared::drone::Landing::Land => "land",
+                        shared::drone::Landing::ReturnToLaunch => "return to launch",
+                    };
+                    let result = match failures.is_empty() {
+                        true => Ok(()),
+                        false => Err(anyhow::anyhow!("Could not {} all drones: {}", verb, failures.join("; "))),
+                    };
+                    let _ = callback.send(result);
+                    let mut landed = 0;
+                    while let Some((id, result)) = landings.next().await {
+                        match result {
+                            Ok(Ok(true)) => {
+                                landed += 1;
+                                report(&events_tx, &journal_action_tx, Severity::Info, Some(&id), "arena",
+                                    String::from("Landed")).await;
+                            },
+                            /* the drone was not flying */
+                            Ok(Ok(false)) => {},
+                            Ok(Err(error)) => report(&events_tx, &journal_action_tx, Severity::Error, Some(&id), "arena",
+                                format!("Could not {}: {:#}", verb, error)).await,
+                            Err(_) => report(&events_tx, &journal_action_tx, Severity::Error, Some(&id), "arena",
+                                format!("Could not {}: no response from drone", verb)).await,
+                        }
+                    }
+                    if landed > 0 {
+                        report(&events_tx, &journal_action_tx, Severity::Info, None, "arena",
+                            format!("{} drone(s) landed", landed)).await;
+                    }
+                });
+            },
             /* Pi-Puck requests */
             Action::ForwardPiPuckAction(id, request) => {
                 match pipucks.iter().find(|&(desc, _)| desc.id == id) {
                     Some((_, instance)) => {
                         let _ = instance.action_tx.send(request).await;
                     }
-                    None => log::warn!("Could not find drone with identifier {}", id),
+                    None => report(&events_tx, &journal_action_tx, Severity::Warning, Some(id.as_str()), "arena",
+                        format!("Could not find Pi-Puck with identifier {}", id)).await,
                 }
             },
             Action::GetPiPuckDescriptors(callback) => {
                 let _ = callback.send(pipucks.keys().cloned().collect::<Vec<_>>());
+            },
+            Action::ExecutePiPuckPowerAction { ids, power, callback } => {
+                let mut action_txs = Vec::new();
+                let mut missing = Vec::new();
+                for id in ids {
+                    match pipucks.iter().find(|&(desc, _)| desc.id == id) {
+                        Some((_, instance)) => action_txs.push((id, instance.action_tx.clone())),
+                        None => missing.push(id),
+                    }
+                }
+                if !missing.is_empty() {
+                    let error = anyhow::anyhow!("Could not find Pi-Puck(s) with identifier(s) {}", missing.join(", "));
+                    let _ = callback.send(Err(error));
+                    continue;
+                }
+                /* halting and rebooting take a while, do not block the arena */
+                tokio::spawn(async move {
+                    let _ = callback.send(execute_pipuck_power_action(action_txs, power).await);
+                });
+            }
+            Action::RecallPiPucks { callback } => {
+                if let State::Active = experiment_state {
+                    let _ = callback.send(Err(anyhow::anyhow!("Pi-Pucks cannot be recalled during an experiment")));
+                    continue;
+                }
+                let (callback_tx, callback_rx) = oneshot::channel();
+                let positions = match dock_tx.send(dock::Action::GetPositions(callback_tx)).await {
+                    Ok(_) => callback_rx.await.unwrap_or_default(),
+                    Err(_) => HashMap::new(),
+                };
+                /* quarantined Pi-Pucks are not connected and are skipped */
+                let mut failures = Vec::new();
+                let mut recalls = pipucks.keys()
+                    .filter(|desc| !quarantine.contains_key(&desc.id))
+                    .filter_map(|desc| positions.get(&desc.id).map(|position| (desc, *position)))
+                    .filter_map(|(desc, position)| match desc.optitrack_id {
+                        Some(optitrack_id) => {
+                            let id = desc.id.clone();
+                            let recall = recall::pipuck(id.clone(), optitrack_id, position,
+                                tracking_tx.clone(), router_tx.clone());
+                            Some(recall.map(move |result| (id, result)))
+                        },
+                        None => {
+                            failures.push(format!("{}: no rigid body is assigned", desc.id));
+                            None
+                        }
+                    })
+                    .collect::<FuturesUnordered<_>>();
+                if recalls.is_empty() && failures.is_empty() {
+                    let _ = callback.send(Err(anyhow::anyhow!("No Pi-Puck has a docking position")));
+                    continue;
+                }
+                let events_tx = events_tx.clone();
+                let journal_action_tx = journal_action_tx.clone();
+                /* driving back to the docks takes a while, do not block the arena */
+                tokio::spawn(async move {
+                    while let Some((id, result)) = recalls.next().await {
+                        match result {
+                            Ok(_) => report(&events_tx, &journal_action_tx, Severity::Info, Some(&id), "recall",
+                                String::from("Reached dock")).await,
+                            Err(error) => {
+                                report(&events_tx, &journal_action_tx, Severity::Warning, Some(&id), "recall",
+                                    format!("Could not reach dock: {:#}", error)).await;
+                                failures.push(format!("{}: {:#}", id, error));
+                            }
+                        }
+                    }
+                    let result = match failures.is_empty() {
+                        true => Ok(()),
+                        false => {
+                            failures.sort();
+                            Err(anyhow::anyhow!("Could not recall all Pi-Pucks: {}", failures.join("; ")))
+                        }
+                    };
+                    let _ = callback.send(result);
+                });
             }
         }
     }
 }
 
+/* whether an action powers off, reboots, or reprograms a robot */
+fn is_destructive(action: &Action) -> bool {
+    match action {
+        Action::ForwardBuilderBotAction(_, builderbot::Action::ExecuteFernbedienungAction(_, action)) |
+        Action::ForwardPiPuckAction(_, pipuck::Action::ExecuteFernbedienungAction(_, action)) =>
+            matches!(action, FernbedienungAction::Halt | FernbedienungAction::Reboot),
+        Action::ForwardDroneAction(_, drone::Action::ExecuteFernbedienungAction(_, action)) =>
+            matches!(action, FernbedienungAction::Halt | FernbedienungAction::Reboot |
+                FernbedienungAction::UploadPixhawkFirmware(_)),
+        Action::ForwardDroneAction(_, drone::Action::ExecuteXbeeAction(_, action)) =>
+            matches!(action, XbeeAction::SetUpCorePower(_) | XbeeAction::SetPixhawkPower(_)),
+        Action::ExecuteGroupRequest { request, .. } =>
+            matches!(request, shared::group::Request::Halt | shared::group::Request::Reboot),
+        Action::ExecutePiPuckPowerAction { .. } |
+        Action::RunMaintenance { .. } => true,
+        _ => false,
+    }
+}
+
+/* a description of a destructive action for the events */
+fn describe(action: &Action) -> String {
+    match action {
+        Action::ForwardBuilderBotAction(id, builderbot::Action::ExecuteFernbedienungAction(_, action)) |
+        Action::ForwardDroneAction(id, drone::Action::ExecuteFernbedienungAction(_, action)) |
+        Action::ForwardPiPuckAction(id, pipuck::Action::ExecuteFernbedienungAction(_, action)) =>
+            format!("{:?} on {}", action, id),
+        Action::ForwardDroneAction(id, drone::Action::ExecuteXbeeAction(_, action)) =>
+            format!("{:?} on {}", action, id),
+        Action::ExecuteGroupRequest { group, request, .. } =>
+            format!("{:?} on group {}", request, group),
+        Action::ExecutePiPuckPowerAction { ids, power, .. } =>
+            format!("{:?} on {}", power, ids.join(", ")),
+        Action::RunMaintenance { .. } =>
+            String::from("Run maintenance script"),
+        _ => String::from("action"),
+    }
+}
+
+/* answer a destructive action with the outcome of its validation without executing it */
+fn rehearse(action: Action, validation: anyhow::Result<()>) {
+    let callback = match action {
+        Action::ForwardBuilderBotAction(_, builderbot::Action::ExecuteFernbedienungAction(callback, _)) |
+        Action::ForwardDroneAction(_, drone::Action::ExecuteFernbedienungAction(callback, _)) |
+        Action::ForwardPiPuckAction(_, pipuck::Action::ExecuteFernbedienungAction(callback, _)) |
+        Action::ForwardDroneAction(_, drone::Action::ExecuteXbeeAction(callback, _)) |
+        Action::ExecuteGroupRequest { callback, .. } |
+        Action::ExecutePiPuckPowerAction { callback, .. } |
+        Action::RunMaintenance { callback } => callback,
+        _ => return,
+    };
+    let _ = callback.send(validation);
+}
+
+/* report an event to the log, the journal (if one is open), and the clients of the web interface */
+async fn report(
+    events_tx: &broadcast::Sender<Event>,
+    journal_action_tx: &mpsc::Sender<journal::Action>,
+    severity: Severity,
+    robot: Option<&str>,
+    subsystem: &str,
+    message: String
+) {
+    match severity {
+        Severity::Info => log::info!("{}", message),
+        Severity::Warning => log::warn!("{}", message),
+        Severity::Error => log::error!("{}", message),
+    }
+    let event = Event {
+        timestamp: chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+        severity,
+        robot: robot.map(String::from),
+        subsystem: String::from(subsystem),
+        message,
+    };
+    /* there may not be any clients connected */
+    let _ = events_tx.send(event.clone());
+    if journal_action_tx.send(journal::Action::Record(journal::Event::Arena(event))).await.is_err() {
+        log::error!("Could not communicate with journal");
+    }
+}
+
+/* report why the task of a robot terminated */
+async fn report_task_failure(
+    id: &str,
+    result: Result<(), JoinError>,
+    experiment_state: State,
+    events_tx: &broadcast::Sender<Event>,
+    journal_action_tx: &mpsc::Sender<journal::Action>
+) {
+    let reason = match result {
+        Ok(_) => String::from("task completed unexpectedly"),
+        Err(error) => match error.try_into_panic() {
+            Ok(panic) => panic.downcast_ref::<&str>()
+                .map(|message| message.to_string())
+                .or_else(|| panic.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| String::from("task panicked")),
+            Err(error) => error.to_string(),
+        }
+    };
+    report(events_tx, journal_action_tx, Severity::Error, Some(id), "arena",
+        format!("Restarting the task of {}: {}", id, reason)).await;
+    if let State::Active = experiment_state {
+        report(events_tx, journal_action_tx, Severity::Warning, Some(id), "experiment",
+            format!("{} is no longer taking part in the experiment", id)).await;
+    }
+}
+
+/* find the members of a group, no group means that all robots are selected */
+fn find_members<'g>(groups: &'g [Group], group: Option<&str>) -> anyhow::Result<Option<&'g [String]>> {
+    match group {
+        Some(name) => groups.iter()
+            .find(|group| group.name == name)
+            .map(|group| Some(group.members.as_slice()))
+            .ok_or(anyhow::anyhow!("Could not find group {}", name)),
+        None => Ok(None),
+    }
+}
+
+async fn execute_group_request(
+    builderbot_txs: Vec<(String, builderbot::Sender)>,
+    drone_txs: Vec<(String, drone::Sender)>,
+    pipuck_txs: Vec<(String, pipuck::Sender)>,
+    request: shared::group::Request,
+) -> anyhow::Result<()> {
+    /* stopping the experiment does not involve fernbedienung and there is no response */
+    let fernbedienung_action = || match request {
+        shared::group::Request::Halt => Some(FernbedienungAction::Halt),
+        shared::group::Request::Reboot => Some(FernbedienungAction::Reboot),
+        shared::group::Request::StopExperiment => None,
+    };
+    let builderbot_requests = builderbot_txs.into_iter()
+        .map(|(id, action_tx)| async move {
+            let result = match fernbedienung_action() {
+                Some(action) => {
+                    let (callback_tx, callback_rx) = oneshot::channel();
+                    match action_tx.send(builderbot::Action::ExecuteFernbedienungAction(callback_tx, action)).await {
+                        Ok(_) => callback_rx.await
+                            .map_err(|_| anyhow::anyhow!("No response from BuilderBot"))
+                            .and_then(|result| result),
+                        Err(_) => Err(anyhow::anyhow!("Could not send action to BuilderBot")),
+                    }
+                },
+                None => action_tx.send(builderbot::Action::StopExperiment).await
+                    .map_err(|_| anyhow::anyhow!("Could not send action to BuilderBot")),
+            };
+            result.map_err(|error| format!("{}: {}", id, error))
+        })
+        .collect::<FuturesUnordered<_>>()
+        .filter_map(|result| async move { result.err() })
+        .collect::<Vec<_>>();
+    let drone_requests = drone_txs.into_iter()
+        .map(|(id, action_tx)| async move {
+            let result = match fernbedienung_action() {
+                Some(action) => {
+                    let (callback_tx, callback_rx) = oneshot::channel();
+                    match action_tx.send(drone::Action::ExecuteFernbedienungAction(callback_tx, action)).await {
+                        Ok(_) => callback_rx.await
+                            .map_err(|_| anyhow::anyhow!("No response from drone"))
+                            .and_then(|result| result),
+                        Err(_) => Err(anyhow::anyhow!("Could not send action to drone")),
+                    }
+                },
+                None => action_tx.send(drone::Action::StopExperiment).await
+                    .map_err(|_| anyhow::anyhow!("Could not send action to drone")),
+            };
+            result.map_err(|error| format!("{}: {}", id, error))
+        })
+        .collect::<FuturesUnordered<_>>()
+        .filter_map(|result| async move { result.err() })
+        .collect::<Vec<_>>();
+    let pipuck_requests = pipuck_txs.into_iter()
+        .map(|(id, action_tx)| async move {
+            let result = match fernbedienung_action() {
+                Some(action) => {
+                    let (callback_tx, callback_rx) = oneshot::channel();
+                    match action_tx.send(pipuck::Action::ExecuteFernbedienungAction(callback_tx, action)).await {
+                        Ok(_) => callback_rx.await
+                            .map_err(|_| anyhow::anyhow!("No response from Pi-Puck"))
+                            .and_then(|result| result),
+                        Err(_) => Err(anyhow::anyhow!("Could not send action to Pi-Puck")),
+                    }
+                },
+                None => action_tx.send(pipuck::Action::StopExperiment).await
+                    .map_err(|_| anyhow::anyhow!("Could not send action to Pi-Puck")),
+            };
+            result.map_err(|error| format!("{}: {}", id, error))
+        })
+        .collect::<FuturesUnordered<_>>()
+        .filter_map(|result| async move { result.err() })
+        .collect::<Vec<_>>();
+    let (builderbot_failures, drone_failures, pipuck_failures) =
+        tokio::join!(builderbot_requests, drone_requests, pipuck_requests);
+    let failures = builderbot_failures.into_iter()
+        .chain(drone_failures)
+        .chain(pipuck_failures)
+        .collect::<Vec<_>>();
+    match failures.is_empty() {
+        true => Ok(()),
+        false => Err(anyhow::anyhow!(failures.join("; "))),
+    }
+}
+
+/* the action is sent to the robot by the given future which returns false if the robot's task has stopped */
+async fn run_maintenance_script(
+    send: impl std::future::Future<Output = bool>,
+    callback_rx: oneshot::Receiver<anyhow::Result<()>>,
+    output_rx: oneshot::Receiver<String>,
+) -> anyhow::Result<String> {
+    if !send.await {
+        anyhow::bail!("Could not send action to robot");
+    }
+    callback_rx.await
+        .map_err(|_| anyhow::anyhow!("No response from robot"))??;
+    output_rx.await
+        .map_err(|_| anyhow::anyhow!("No output from robot"))
+}
+
+async fn execute_pipuck_power_action(
+    action_txs: Vec<(String, pipuck::Sender)>,
+    power: shared::pipuck::Power,
+) -> anyhow::Result<()> {
+    let failures = action_txs.into_iter()
+        .map(|(id, action_tx)| async move {
+            let (callback_tx, callback_rx) = oneshot::channel();
+            let action = match power {
+                shared::pipuck::Power::Halt => FernbedienungAction::Halt,
+                shared::pipuck::Power::Reboot => FernbedienungAction::Reboot,
+            };
+            let result = match action_tx.send(pipuck::Action::ExecuteFernbedienungAction(callback_tx, action)).await {
+                Ok(_) => callback_rx.await
+                    .map_err(|_| anyhow::anyhow!("No response from Pi-Puck"))
+                    .and_then(|result| result),
+                Err(_) => Err(anyhow::anyhow!("Could not send action to Pi-Puck")),
+            };
+            result.map_err(|error| format!("{}: {}", id, error))
+        })
+        .collect::<FuturesUnordered<_>>()
+        .filter_map(|result| async move { result.err() })
+        .collect::<Vec<_>>().await;
+    let verb = match power {
+        shared::pipuck::Power::Halt => "halt",
+        shared::pipuck::Power::Reboot => "reboot",
+    };
+    match failures.is_empty() {
+        true => Ok(()),
+        false => Err(anyhow::anyhow!("Could not {} all Pi-Pucks: {}", verb, failures.join("; "))),
+    }
+}
+
+/* the rigid body of a drone and the tracking system, from which its poses are forwarded to the Pixhawk */
+fn rigid_body(
+    optitrack_id: Option<i32>,
+    tracking_tx: &mpsc::Sender<tracking::Action>
+) -> Option<(i32, mpsc::Sender<tracking::Action>)> {
+    optitrack_id.map(|optitrack_id| (optitrack_id, tracking_tx.clone()))
+}
+
+async fn subscribe_rigid_body(
+    tracking_tx: &mpsc::Sender<tracking::Action>,
+    optitrack_id: i32
+) -> anyhow::Result<broadcast::Receiver<shared::tracking_system::Update>> {
+    let (callback_tx, callback_rx) = oneshot::channel();
+    tracking_tx.send(tracking::Action::SubscribeRigidBody(optitrack_id, callback_tx)).await
+        .map_err(|_| anyhow::anyhow!("Could not communicate with tracking system"))?;
+    callback_rx.await
+        .map_err(|_| anyhow::anyhow!("Could not subscribe to rigid body {}", optitrack_id))
+}
+
+/* query the state of each robot, a robot whose task does not respond is reported without any state */
+async fn robot_states<D, A>(
+    robots: Vec<(D, mpsc::Sender<A>)>,
+    action: fn(oneshot::Sender<snapshot::Robot>) -> A
+) -> Vec<(D, snapshot::Robot)> {
+    let mut states = Vec::with_capacity(robots.len());
+    for (desc, action_tx) in robots {
+        let (callback_tx, callback_rx) = oneshot::channel();
+        let state = match action_tx.send(action(callback_tx)).await {
+            Ok(_) => callback_rx.await.unwrap_or_default(),
+            Err(_) => snapshot::Robot::default(),
+        };
+        states.push((desc, state));
+    }
+    states
+}
+
+/* run the pre-flight checklist of a drone, the callback receives the failed checks */
+async fn preflight_check(instance: &drone::Instance) -> Vec<String> {
+    let (callback_tx, callback_rx) = oneshot::channel();
+    match instance.action_tx.send(drone::Action::PreflightCheck(callback_tx)).await {
+        Ok(_) => callback_rx.await
+            .unwrap_or_else(|_| vec![String::from("no response from drone")]),
+        Err(_) => vec![String::from("could not send action to drone")],
+    }
+}
+
+/* the robots that take part in an experiment, i.e., all robots or the members of a group, and the
+   software for each type of robot */
+struct Selection<'a> {
+    builderbots: Vec<(&'a Arc<builderbot::Descriptor>, &'a builderbot::Instance)>,
+    builderbot_software: &'a Software,
+    drones: Vec<(&'a Arc<drone::Descriptor>, &'a drone::Instance)>,
+    drone_software: &'a Software,
+    pipucks: Vec<(&'a Arc<pipuck::Descriptor>, &'a pipuck::Instance)>,
+    pipuck_software: &'a Software,
+}
+
+/* check the software, the journal, the tracking system, and the robots, reporting every failed check */
+async fn check_readiness(
+    selection: &Selection<'_>,
+    rigid_bodies: &[(String, i32)],
+    tracking_tx: &mpsc::Sender<tracking::Action>,
+    journal_tx: &mpsc::Sender<journal::Action>,
+    dock_tx: &mpsc::Sender<dock::Action>,
+    health: &[Health],
+    min_health: Option<u8>,
+) -> Readiness {
+    let &Selection { ref builderbots, builderbot_software, ref drones, drone_software, ref pipucks, pipuck_software } = selection;
+    let mut checks = Vec::new();
+    /* software is only required for the types of robots in the arena */
+    let software = [
+        ("BuilderBot software", builderbots.len(), builderbot_software),
+        ("Drone software", drones.len(), drone_software),
+        ("Pi-Puck software", pipucks.len(), pipuck_software),
+    ];
+    for &(subject, _, software) in software.iter().filter(|(_, count, _)| *count > 0) {
+        let failures = software.check_config().err()
+            .map(|error| error.to_string())
+            .into_iter()
+            .collect();
+        checks.push(Check { subject: subject.to_owned(), failures });
+    }
+    /* journal */
+    let (callback_tx, callback_rx) = oneshot::channel();
+    let journal_result = match journal_tx.send(journal::Action::CheckAvailable(callback_tx)).await {
+        Ok(_) => callback_rx.await
+            .unwrap_or_else(|_| Err(anyhow::anyhow!("No response from journal"))),
+        Err(_) => Err(anyhow::anyhow!("Could not communicate with journal")),
+    };
+    checks.push(Check {
+        subject: String::from("Journal"),
+        failures: journal_result.err().map(|error| error.to_string()).into_iter().collect(),
+    });
+    /* tracking system */
+    let (callback_tx, callback_rx) = oneshot::channel();
+    let tracked = match tracking_tx.send(tracking::Action::GetRigidBodies(callback_tx)).await {
+        Ok(_) => callback_rx.await.unwrap_or_default(),
+        Err(_) => Vec::new(),
+    };
+    checks.push(Check {
+        subject: String::from("Tracking system"),
+        failures: check_rigid_bodies(tracking_tx, rigid_bodies).await.err()
+            .map(|error| error.to_string())
+            .into_iter()
+            .collect(),
+    });
+    /* charging docks, the status of a Pi-Puck that is not assigned to a dock is not known */
+    let (callback_tx, callback_rx) = oneshot::channel();
+    let docks = match dock_tx.send(dock::Action::GetStatus(callback_tx)).await {
+        Ok(_) => callback_rx.await.unwrap_or_default(),
+        Err(_) => HashMap::new(),
+    };
+    let dock_failures = |id: &str| match docks.get(id) {
+        Some((dock, dock::Status::Docked { .. })) => vec![format!("still on dock {}", dock)],
+        Some((dock, dock::Status::Unknown)) => vec![format!("status on dock {} is unknown", dock)],
+        _ => Vec::new(),
+    };
+    /* robots */
+    let rigid_body_failures = |optitrack_id: Option<i32>| match optitrack_id {
+        Some(optitrack_id) if !tracked.contains(&optitrack_id) =>
+            vec![format!("rigid body {} is not visible", optitrack_id)],
+        _ => Vec::new(),
+    };
+    let mut robots = builderbots.iter()
+        .map(|(desc, _)| Check { subject: desc.id.clone(), failures: rigid_body_failures(desc.optitrack_id) })
+        .chain(pipucks.iter()
+            .map(|(desc, _)| {
+                let mut failures = rigid_body_failures(desc.optitrack_id);
+                failures.extend(dock_failures(&desc.id));
+                Check { subject: desc.id.clone(), failures }
+            }))
+        .collect::<Vec<_>>();
+    /* drones also run their pre-flight checklist and must have a rigid body */
+    for (desc, instance) in drones.iter() {
+        let mut failures = preflight_check(instance).await;
+        match desc.optitrack_id {
+            Some(optitrack_id) => failures.extend(rigid_body_failures(Some(optitrack_id))),
+            None => failures.push(String::from("no rigid body is assigned")),
+        }
+        robots.push(Check { subject: desc.id.clone(), failures });
+    }
+    /* the health of a robot is only checked if a minimum has been configured */
+    if let Some(min_health) = min_health {
+        for check in robots.iter_mut() {
+            if let Some(health) = health.iter().find(|health| health.robot == check.subject) {
+                if health.score < min_health {
+                    check.failures.push(format!("health {} is below {}", health.score, min_health));
+                }
+            }
+        }
+    }
+    robots.sort_by(|a, b| a.subject.cmp(&b.subject));
+    checks.extend(robots);
+    Readiness(checks)
+}
+
+/* check that each rigid body of a robot is being tracked and assigned to a single robot, the result
+   also lists the tracked rigid bodies that are not assigned to a robot */
+async fn check_rigid_bodies(
+    tracking_tx: &mpsc::Sender<tracking::Action>,
+    rigid_bodies: &[(String, i32)],
+) -> anyhow::Result<String> {
+    let (callback_tx, callback_rx) = oneshot::channel();
+    tracking_tx.send(tracking::Action::GetRigidBodies(callback_tx)).await
+        .map_err(|_| anyhow::anyhow!("Could not communicate with tracking system"))?;
+    let tracked = callback_rx.await
+        .map_err(|_| anyhow::anyhow!("No response from tracking system"))?;
+    let mut assignments: HashMap<i32, Vec<&str>> = HashMap::new();
+    for (robot, optitrack_id) in rigid_bodies {
+        assignments.entry(*optitrack_id).or_default().push(robot);
+    }
+    let mut problems = Vec::new();
+    for (optitrack_id, robots) in assignments.iter() {
+        if robots.len() > 1 {
+            problems.push(format!("rigid body {} is assigned to {}", optitrack_id, robots.join(", ")));
+        }
+        if !tracked.contains(optitrack_id) {
+            problems.push(format!("rigid body {} for {} is not being tracked", optitrack_id, robots.join(", ")));
+        }
+    }
+    /* rigid bodies that do not belong to a robot are not a problem, e.g., obstacles */
+    let mut unassigned = tracked.iter()
+        .filter(|optitrack_id| !assignments.contains_key(optitrack_id))
+        .collect::<Vec<_>>();
+    unassigned.sort();
+    let unassigned = match unassigned.is_empty() {
+        true => String::new(),
+        false => format!(" (rigid bodies {} are not assigned to a robot)", unassigned.iter()
+            .map(|optitrack_id| optitrack_id.to_string())
+            .collect::<Vec<_>>()
+            .join(", ")),
+    };
+    match problems.is_empty() {
+        true => Ok(format!("All rigid bodies are being tracked{}", unassigned)),
+        false => {
+            problems.sort();
+            Err(anyhow::anyhow!("Rigid body check failed: {}{}", problems.join("; "), unassigned))
+        }
+    }
+}
+
 fn associate_xbee_device(
     macaddr: macaddr::MacAddr6,
     drones: &HashMap<Arc<drone::Descriptor>, drone::Instance>,
@@ -171,9 +1521,9 @@ fn associate_xbee_device(
 
 fn associate_fernbedienung_device_with_builderbot(
     macaddr: macaddr::MacAddr6,
-    pipucks: &HashMap<Arc<builderbot::Descriptor>, builderbot::Instance>,
+    builderbots: &HashMap<Arc<builderbot::Descriptor>, builderbot::Instance>,
 ) -> Vec<&builderbot::Instance> {
-    pipucks.into_iter().filter_map(|(desc, instance)| {
+    builderbots.iter().filter_map(|(desc, instance)| {
         if desc.duovero_macaddr == macaddr {
             Some(instance)
         }
@@ -215,9 +1565,16 @@ async fn stop_experiment(
     builderbots: &HashMap<Arc<builderbot::Descriptor>, builderbot::Instance>,
     drones: &HashMap<Arc<drone::Descriptor>, drone::Instance>,
     pipucks: &HashMap<Arc<pipuck::Descriptor>, pipuck::Instance>,
+    plugin_robots: &[Box<dyn plugin::Robot>],
+    argos_tx: &mpsc::Sender<argos::Action>,
     journal_action_tx: &mpsc::Sender<journal::Action>
 ) -> anyhow::Result<()> {
     let _ = journal_action_tx.send(journal::Action::Stop).await;
+    let _ = argos_tx.send(argos::Action::Stop).await;
+    plugin_robots.iter()
+        .map(|robot| robot.stop_experiment())
+        .collect::<FuturesUnordered<_>>()
+        .collect::<Vec<_>>().await;
     let builderbot_requests = builderbots
         .iter()
         .map(|(desc, instance)| async move {
@@ -271,15 +1628,55 @@ async fn stop_experiment(
     }
 }
 
+/* wait for the controllers to register themselves with the router (by sending a message) and then send
+   {fromS = "supervisor", toS = <id>, goB = true} to each robot so that they start at the same time */
+async fn send_go_signal(
+    router_tx: &mpsc::Sender<router::Action>,
+    ids: Vec<String>,
+    timeout: Duration
+) -> anyhow::Result<()> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        let (callback_tx, callback_rx) = oneshot::channel();
+        router_tx.send(router::Action::GetConnected(callback_tx)).await
+            .map_err(|_| anyhow::anyhow!("Could not communicate with router"))?;
+        let connected = callback_rx.await
+            .map_err(|_| anyhow::anyhow!("No response from router"))?;
+        let waiting = ids.iter()
+            .filter(|id| !connected.contains(id))
+            .map(String::as_str)
+            .collect::<Vec<_>>();
+        if waiting.is_empty() {
+            break;
+        }
+        if Instant::now() >= deadline {
+            anyhow::bail!("Controllers did not connect to the router: {}", waiting.join(", "));
+        }
+        tokio::time::sleep(GO_SIGNAL_POLL_INTERVAL).await;
+    }
+    for id in ids {
+        let table = vec![
+            (LuaType::String(String::from("fromS")), LuaType::String(String::from("supervisor"))),
+            (LuaType::String(String::from("toS")), LuaType::String(id.clone())),
+            (LuaType::String(String::from("goB")), LuaType::Boolean(true)),
+        ];
+        router_tx.send(router::Action::Send { to: id, table }).await
+            .map_err(|_| anyhow::anyhow!("Could not communicate with router"))?;
+    }
+    Ok(())
+}
+
+/* start an experiment on the selected robots */
 async fn start_experiment(
-    builderbots: &HashMap<Arc<builderbot::Descriptor>, builderbot::Instance>,
-    builderbot_software: &Software,
-    drones: &HashMap<Arc<drone::Descriptor>, drone::Instance>,
-    drone_software: &Software,
-    pipucks: &HashMap<Arc<pipuck::Descriptor>, pipuck::Instance>,
-    pipuck_software: &Software,
-    journal_requests_tx: &mpsc::Sender<journal::Action>
+    selection: &Selection<'_>,
+    plugin_robots: &[&dyn plugin::Robot],
+    argos_robots: &[String],
+    argos_tx: &mpsc::Sender<argos::Action>,
+    journal_requests_tx: &mpsc::Sender<journal::Action>,
+    router_tx: &mpsc::Sender<router::Action>,
+    config: &ExperimentConfiguration,
 ) -> anyhow::Result<()> {
+    let &Selection { ref builderbots, builderbot_software, ref drones, drone_software, ref pipucks, pipuck_software } = selection;
     /* check software validity before starting */
     if builderbots.len() > 0 {
         builderbot_software.check_config()?;
@@ -299,16 +1696,16 @@ async fn start_experiment(
         .map_err(|_| anyhow::anyhow!("No response from journal"))??;
     /* send all descriptors */
     let builderbot_descriptors = builderbots
-        .keys()
-        .map(|desc| builderbot::Descriptor::clone(desc))
+        .iter()
+        .map(|(desc, _)| builderbot::Descriptor::clone(desc))
         .collect::<Vec<_>>();
     let drone_descriptors = drones
-        .keys()
-        .map(|desc| drone::Descriptor::clone(desc))
+        .iter()
+        .map(|(desc, _)| drone::Descriptor::clone(desc))
         .collect::<Vec<_>>();
     let pipuck_descriptors = pipucks
-        .keys()
-        .map(|desc| pipuck::Descriptor::clone(desc))
+        .iter()
+        .map(|(desc, _)| pipuck::Descriptor::clone(desc))
         .collect::<Vec<_>>();
     let descriptor_event = journal::Event::Descriptors(builderbot_descriptors, drone_descriptors, pipuck_descriptors);
     journal_requests_tx.send(journal::Action::Record(descriptor_event)).await
@@ -321,7 +1718,8 @@ async fn start_experiment(
                 callback_tx, 
                 desc.id.clone(),
                 builderbot_software.clone(),
-                journal_requests_tx.clone()
+                journal_requests_tx.clone(),
+                config.restart_policy
             );
             async move {
                 instance.action_tx.send(action).await
@@ -340,7 +1738,8 @@ async fn start_experiment(
                 callback_tx,
                 desc.id.clone(),
                 pipuck_software.clone(),
-                journal_requests_tx.clone()
+                journal_requests_tx.clone(),
+                config.restart_policy
             );
             async move {
                 instance.action_tx.send(action).await
@@ -359,7 +1758,8 @@ async fn start_experiment(
                 callback_tx, 
                 desc.id.clone(),
                 drone_software.clone(),
-                journal_requests_tx.clone()
+                journal_requests_tx.clone(),
+                config.restart_policy
             );
             async move {
                 instance.action_tx.send(action).await
@@ -370,12 +1770,37 @@ async fn start_experiment(
         })
         .collect::<FuturesUnordered<_>>()
         .try_collect::<Vec<_>>().await?;
+    /* set up the experiment on the robots of other types */
+    plugin_robots.iter()
+        .map(|robot| robot.setup_experiment(journal_requests_tx.clone(), config.restart_policy)
+            .map(move |result| result.context(format!("Could not set up experiment on {}", robot.id()))))
+        .collect::<FuturesUnordered<_>>()
+        .try_collect::<Vec<_>>().await?;
+    /* start the simulation if any of the simulated robots take part in the experiment */
+    if !argos_robots.is_empty() {
+        let (callback_tx, callback_rx) = oneshot::channel();
+        let action = argos::Action::Start {
+            builderbot_software: builderbot_software.clone(),
+            drone_software: drone_software.clone(),
+            pipuck_software: pipuck_software.clone(),
+            journal: journal_requests_tx.clone(),
+            callback: callback_tx,
+        };
+        argos_tx.send(action).await
+            .map_err(|_| anyhow::anyhow!("Could not send action to ARGoS simulation"))?;
+        callback_rx.await
+            .map_err(|_| anyhow::anyhow!("No response from ARGoS simulation"))?
+            .context("Could not start ARGoS simulation")?;
+    }
     /* start the pipucks */
     pipucks.iter()
-        .map(|(_, instance)| {
+        .enumerate()
+        .map(|(index, (_, instance))| {
             let (callback_tx, callback_rx) = oneshot::channel();
             let action = pipuck::Action::StartExperiment(callback_tx);
+            let delay = config.stagger * index as u32;
             async move {
+                tokio::time::sleep(delay).await;
                 instance.action_tx.send(action).await
                     .map_err(|_| anyhow::anyhow!("Could not send action to Pi-Puck"))?;
                 callback_rx.await
@@ -386,10 +1811,13 @@ async fn start_experiment(
         .try_collect::<Vec<_>>().await?;
     /* start the builderbots */
     builderbots.iter()
-        .map(|(_, instance)| {
+        .enumerate()
+        .map(|(index, (_, instance))| {
             let (callback_tx, callback_rx) = oneshot::channel();
             let action = builderbot::Action::StartExperiment(callback_tx);
+            let delay = config.stagger * index as u32;
             async move {
+                tokio::time::sleep(delay).await;
                 instance.action_tx.send(action).await
                     .map_err(|_| anyhow::anyhow!("Could not send action to BuilderBot"))?;
                 callback_rx.await
@@ -400,10 +1828,13 @@ async fn start_experiment(
         .try_collect::<Vec<_>>().await?;
     /* start the drones */
     drones.iter()
-        .map(|(_, instance)| {
+        .enumerate()
+        .map(|(index, (_, instance))| {
             let (callback_tx, callback_rx) = oneshot::channel();
             let action = drone::Action::StartExperiment(callback_tx);
+            let delay = config.stagger * index as u32;
             async move {
+                tokio::time::sleep(delay).await;
                 instance.action_tx.send(action).await
                     .map_err(|_| anyhow::anyhow!("Could not send action to drone"))?;
                 callback_rx.await
@@ -412,5 +1843,21 @@ async fn start_experiment(
         })
         .collect::<FuturesUnordered<_>>()
         .try_collect::<Vec<_>>().await?;
+    /* start the robots of other types */
+    plugin_robots.iter()
+        .map(|robot| robot.start_experiment()
+            .map(move |result| result.context(format!("Could not start experiment on {}", robot.id()))))
+        .collect::<FuturesUnordered<_>>()
+        .try_collect::<Vec<_>>().await?;
+    /* send the go signal once the controllers on all robots are running */
+    if let Some(timeout) = config.go_signal_timeout {
+        let ids = builderbots.iter().map(|(desc, _)| desc.id.clone())
+            .chain(drones.iter().map(|(desc, _)| desc.id.clone()))
+            .chain(pipucks.iter().map(|(desc, _)| desc.id.clone()))
+            .chain(plugin_robots.iter().map(|robot| robot.id().to_owned()))
+            .chain(argos_robots.iter().cloned())
+            .collect::<Vec<_>>();
+        send_go_signal(router_tx, ids, timeout).await?;
+    }
     Ok(())
 }
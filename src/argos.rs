@@ -0,0 +1,159 @@
+use anyhow::Context;
+use bytes::BytesMut;
+use futures::TryStreamExt;
+use shared::experiment::software::Software;
+use std::{net::SocketAddr, path::PathBuf, process::Stdio};
+use tokio::{process::Command, sync::{mpsc, oneshot}, task::JoinHandle};
+use tokio_util::codec::{BytesCodec, FramedRead};
+
+use crate::journal::{self, ARGoS, Event};
+
+/* the name under which the output of the simulation is recorded in the journal */
+const JOURNAL_ID: &str = "argos";
+
+/* the type of robot whose software provides the controllers for the simulated robots */
+#[derive(Clone, Copy, Debug)]
+pub enum SoftwareSource {
+    BuilderBot,
+    Drone,
+    PiPuck,
+}
+
+#[derive(Debug)]
+pub struct Configuration {
+    pub executable: PathBuf,
+    /* the ARGoS configuration file that describes the arena and the simulated robots */
+    pub config: PathBuf,
+    pub software: Option<SoftwareSource>,
+    /* the identifiers of the simulated robots, these connect to the router under these names */
+    pub robots: Vec<String>,
+}
+
+pub enum Action {
+    Start {
+        builderbot_software: Software,
+        drone_software: Software,
+        pipuck_software: Software,
+        journal: mpsc::Sender<journal::Action>,
+        callback: oneshot::Sender<anyhow::Result<()>>,
+    },
+    Stop,
+}
+
+/// This function runs a local instance of ARGoS during experiments so that some of the robots are
+/// simulated. The instance is started in a temporary directory with the software of the configured
+/// type of robot and the configured ARGoS configuration file, and is connected to the message router
+/// at the given socket. If the simulation has not been configured, starting it does nothing.
+pub async fn new(
+    config: Option<Configuration>,
+    router_socket: SocketAddr,
+    mut action_rx: mpsc::Receiver<Action>
+) {
+    /* the stop signal and the task of the running instance */
+    let mut instance: Option<(oneshot::Sender<()>, JoinHandle<()>)> = None;
+    while let Some(action) = action_rx.recv().await {
+        /* stop the running instance before a new one is started */
+        if let Some((stop_tx, task)) = instance.take() {
+            let _ = stop_tx.send(());
+            let _ = task.await;
+        }
+        if let Action::Start { builderbot_software, drone_software, pipuck_software, journal, callback } = action {
+            let config = match &config {
+                Some(config) => config,
+                None => {
+                    let _ = callback.send(Ok(()));
+                    continue;
+                }
+            };
+            let software = match config.software {
+                Some(SoftwareSource::BuilderBot) => builderbot_software,
+                Some(SoftwareSource::Drone) => drone_software,
+                Some(SoftwareSource::PiPuck) => pipuck_software,
+                None => Software::default(),
+            };
+            let (stop_tx, stop_rx) = oneshot::channel();
+            match start(config, software, router_socket, journal, stop_rx).await {
+                Ok(task) => {
+                    instance = Some((stop_tx, task));
+                    let _ = callback.send(Ok(()));
+                },
+                Err(error) => {
+                    let _ = callback.send(Err(error));
+                }
+            }
+        }
+    }
+}
+
+/* write the software and the configuration file to a temporary directory and run ARGoS in that directory
+   until it exits or until it is stopped, its output is forwarded to the journal */
+async fn start(
+    config: &Configuration,
+    software: Software,
+    router_socket: SocketAddr,
+    journal: mpsc::Sender<journal::Action>,
+    stop_rx: oneshot::Receiver<()>,
+) -> anyhow::Result<JoinHandle<()>> {
+    let path = std::env::temp_dir().join(format!("supervisor-argos-{}", uuid::Uuid::new_v4()));
+    tokio::fs::create_dir_all(&path).await
+        .context(format!("Could not create {:?}", path))?;
+    /* the configuration file of the software is for the real robots and is replaced */
+    for (filename, contents) in software.0.iter().filter(|(filename, _)| !filename.ends_with(".argos")) {
+        tokio::fs::write(path.join(filename), contents).await
+            .context(format!("Could not write {}", filename))?;
+    }
+    let config_filename = config.config.file_name()
+        .ok_or(anyhow::anyhow!("Invalid ARGoS configuration file {:?}", config.config))?;
+    tokio::fs::copy(&config.config, path.join(config_filename)).await
+        .context(format!("Could not copy {:?}", config.config))?;
+    let mut child = Command::new(&config.executable)
+        .arg("--config").arg(config_filename)
+        .arg("--router").arg(router_socket.to_string())
+        .current_dir(&path)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .context(format!("Could not start {:?}", config.executable))?;
+    let stdout = child.stdout.take()
+        .ok_or(anyhow::anyhow!("Could not capture standard output of ARGoS"))?;
+    let stderr = child.stderr.take()
+        .ok_or(anyhow::anyhow!("Could not capture standard error of ARGoS"))?;
+    let forward = |output: fn(BytesMut) -> ARGoS| {
+        let journal = journal.clone();
+        move |chunk: BytesMut| {
+            let journal = journal.clone();
+            async move {
+                let event = Event::ARGoS(JOURNAL_ID.to_owned(), output(chunk));
+                let _ = journal.send(journal::Action::Record(event)).await;
+                Ok::<_, std::io::Error>(())
+            }
+        }
+    };
+    let forward_stdout = FramedRead::new(stdout, BytesCodec::new())
+        .try_for_each(forward(ARGoS::StandardOutput));
+    let forward_stderr = FramedRead::new(stderr, BytesCodec::new())
+        .try_for_each(forward(ARGoS::StandardError));
+    log::info!("Started ARGoS simulation in {:?}", path);
+    Ok(tokio::spawn(async move {
+        let run = async {
+            tokio::select! {
+                result = child.wait() => match result {
+                    Ok(status) if status.success() => log::info!("ARGoS simulation completed"),
+                    Ok(status) => log::warn!("ARGoS simulation exited with {}", status),
+                    Err(error) => log::warn!("Could not wait for ARGoS simulation: {}", error),
+                },
+                _ = stop_rx => match child.kill().await {
+                    Ok(_) => log::info!("Stopped ARGoS simulation"),
+                    Err(error) => log::warn!("Could not stop ARGoS simulation: {}", error),
+                },
+            }
+        };
+        /* the output is forwarded until ARGoS exits and closes its standard output and error */
+        let _ = tokio::join!(run, forward_stdout, forward_stderr);
+        if let Err(error) = tokio::fs::remove_dir_all(&path).await {
+            log::warn!("Could not remove {:?}: {}", path, error);
+        }
+    }))
+}
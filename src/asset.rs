@@ -0,0 +1,82 @@
+use anyhow::Context;
+use chrono::NaiveDate;
+use std::{collections::HashMap, path::PathBuf};
+use tokio::sync::{broadcast, mpsc, oneshot};
+
+use shared::asset::Asset;
+use crate::subscription::Subscription;
+
+pub enum Action {
+    Set {
+        callback: oneshot::Sender<anyhow::Result<()>>,
+        asset: Asset,
+    },
+    Subscribe(oneshot::Sender<Subscription<Vec<Asset>>>),
+}
+
+fn load(path: &PathBuf) -> anyhow::Result<HashMap<String, Asset>> {
+    match std::fs::read_to_string(path) {
+        Ok(contents) => serde_json::from_str::<Vec<Asset>>(&contents)
+            .map(|assets| assets.into_iter().map(|asset| (asset.robot.clone(), asset)).collect())
+            .context(format!("Could not parse assets in {:?}", path)),
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(HashMap::new()),
+        Err(error) => Err(error).context(format!("Could not read assets from {:?}", path)),
+    }
+}
+
+fn save(path: &PathBuf, assets: &[Asset]) -> anyhow::Result<()> {
+    let contents = serde_json::to_string_pretty(assets)
+        .context("Could not serialize assets")?;
+    std::fs::write(path, contents)
+        .context(format!("Could not write assets to {:?}", path))
+}
+
+fn sorted(assets: &HashMap<String, Asset>) -> Vec<Asset> {
+    let mut assets = assets.values().cloned().collect::<Vec<_>>();
+    assets.sort_by(|a, b| a.robot.cmp(&b.robot));
+    assets
+}
+
+fn validate(asset: &Asset) -> anyhow::Result<()> {
+    if asset.robot.is_empty() {
+        anyhow::bail!("The metadata of a robot must name the robot");
+    }
+    if !asset.last_maintenance.is_empty() {
+        NaiveDate::parse_from_str(&asset.last_maintenance, "%Y-%m-%d")
+            .context(format!("Could not parse \"{}\" as a date (YYYY-MM-DD)", asset.last_maintenance))?;
+    }
+    Ok(())
+}
+
+/// This function keeps the metadata of each robot, e.g., its serial numbers, hardware revision, and known
+/// issues, which is edited from the user interface. The metadata is written to a JSON file whenever it
+/// changes, so that it is kept across restarts, and robots whose metadata is empty are left out.
+pub async fn new(
+    path: PathBuf,
+    mut requests_rx: mpsc::Receiver<Action>
+) -> anyhow::Result<()> {
+    let mut assets = load(&path)?;
+    let (updates_tx, _) = broadcast::channel(8);
+    while let Some(request) = requests_rx.recv().await {
+        match request {
+            Action::Set { callback, asset } => {
+                if let Err(error) = validate(&asset) {
+                    let _ = callback.send(Err(error));
+                    continue;
+                }
+                log::info!("Updated the metadata of {}", asset.robot);
+                match asset.is_empty() {
+                    true => assets.remove(&asset.robot),
+                    false => assets.insert(asset.robot.clone(), asset),
+                };
+                let assets = sorted(&assets);
+                let _ = callback.send(save(&path, &assets));
+                let _ = updates_tx.send(assets);
+            },
+            Action::Subscribe(callback) => {
+                let _ = callback.send((vec![sorted(&assets)], updates_tx.subscribe()));
+            },
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,76 @@
+use anyhow::Context;
+use std::{collections::VecDeque, fs::OpenOptions, io::Write, path::PathBuf};
+use tokio::sync::{broadcast, mpsc, oneshot};
+
+use shared::audit::Entry;
+use crate::subscription::Subscription;
+
+/* the number of recent entries that are sent to a client when it subscribes */
+const MAX_ENTRIES: usize = 1000;
+
+pub enum Action {
+    Record(Entry),
+    Subscribe(oneshot::Sender<Subscription<Entry>>),
+}
+
+/* the most recent entries of the audit trail, which is a file with one JSON object per line */
+fn load(path: &PathBuf) -> anyhow::Result<VecDeque<Entry>> {
+    let contents = match std::fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(VecDeque::new()),
+        Err(error) => return Err(error).context(format!("Could not read audit trail from {:?}", path)),
+    };
+    let mut entries = VecDeque::with_capacity(MAX_ENTRIES);
+    for (index, line) in contents.lines().enumerate().filter(|(_, line)| !line.is_empty()) {
+        match serde_json::from_str::<Entry>(line) {
+            Ok(entry) => {
+                if entries.len() == MAX_ENTRIES {
+                    entries.pop_front();
+                }
+                entries.push_back(entry);
+            },
+            Err(error) => log::warn!("Could not parse line {} of audit trail {:?}: {}", index + 1, path, error),
+        }
+    }
+    Ok(entries)
+}
+
+fn append(path: &PathBuf, entry: &Entry) -> anyhow::Result<()> {
+    let mut line = serde_json::to_string(entry)
+        .context("Could not serialize audit entry")?;
+    line.push('\n');
+    OpenOptions::new().create(true).append(true).open(path)
+        .and_then(|mut file| file.write_all(line.as_bytes()))
+        .context(format!("Could not write audit entry to {:?}", path))
+}
+
+/// This function keeps the audit trail, i.e., the actions that operators requested through the user
+/// interface of any arena, who requested them, and whether they succeeded. The entries are appended
+/// to a file so that the audit trail is kept across restarts and the most recent entries are sent to
+/// each client of the user interface so that they can be searched.
+pub async fn new(
+    path: PathBuf,
+    mut requests_rx: mpsc::Receiver<Action>
+) -> anyhow::Result<()> {
+    let mut entries = load(&path)?;
+    let (updates_tx, _) = broadcast::channel(16);
+    while let Some(request) = requests_rx.recv().await {
+        match request {
+            Action::Record(entry) => {
+                /* a failure to write the file must not stop the audit trail in the user interface */
+                if let Err(error) = append(&path, &entry) {
+                    log::error!("{:#}", error);
+                }
+                if entries.len() == MAX_ENTRIES {
+                    entries.pop_front();
+                }
+                entries.push_back(entry.clone());
+                let _ = updates_tx.send(entry);
+            },
+            Action::Subscribe(callback) => {
+                let _ = callback.send((entries.iter().cloned().collect(), updates_tx.subscribe()));
+            },
+        }
+    }
+    Ok(())
+}
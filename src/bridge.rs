@@ -0,0 +1,77 @@
+use futures::{StreamExt, TryFutureExt};
+use std::{collections::HashMap, time::Duration};
+use tokio::sync::{mpsc, oneshot};
+use tokio_stream::{StreamMap, wrappers::{BroadcastStream, errors::BroadcastStreamRecvError}};
+use shared::tracking_system::Update;
+
+use crate::{router::{self, LuaType}, tracking};
+
+#[derive(Debug)]
+pub struct Configuration {
+    /* the rate in Hz at which poses are sent to the robots */
+    pub rate: f64,
+}
+
+fn pose_table(id: &str, update: &Update) -> Vec<(LuaType, LuaType)> {
+    let [x, y, z] = update.position;
+    let [w, i, j, k] = update.orientation;
+    let pose = vec![
+        (LuaType::String(String::from("positionV3")),
+         LuaType::Vector3(x as f64, y as f64, z as f64)),
+        (LuaType::String(String::from("orientationQ")),
+         LuaType::Quaternion(w as f64, i as f64, j as f64, k as f64)),
+    ];
+    vec![
+        (LuaType::String(String::from("fromS")), LuaType::String(String::from("supervisor"))),
+        (LuaType::String(String::from("toS")), LuaType::String(id.to_owned())),
+        (LuaType::String(String::from("poseT")), LuaType::Table(pose)),
+    ]
+}
+
+pub async fn new(
+    config: Option<Configuration>,
+    robots: Vec<(String, i32)>,
+    tracking_tx: mpsc::Sender<tracking::Action>,
+    router_tx: mpsc::Sender<router::Action>
+) -> anyhow::Result<()> {
+    let config = match config {
+        Some(config) => config,
+        /* the bridge is disabled, never complete */
+        None => return futures::future::pending().await,
+    };
+    /* subscribe to the rigid body of each robot */
+    let mut poses = StreamMap::new();
+    for (id, optitrack_id) in robots {
+        let (callback_tx, callback_rx) = oneshot::channel();
+        let action = tracking::Action::SubscribeRigidBody(optitrack_id, callback_tx);
+        let receiver = tracking_tx.send(action)
+            .map_err(|_| anyhow::anyhow!("Could not communicate with tracking system"))
+            .and_then(|_| callback_rx
+                .map_err(|_| anyhow::anyhow!("Could not subscribe to rigid body {}", optitrack_id)))
+            .await?;
+        poses.insert(id, BroadcastStream::new(receiver));
+    }
+    /* only forward the most recent pose of each robot */
+    let mut latest = HashMap::new();
+    let mut interval = tokio::time::interval(Duration::from_secs_f64(1.0 / config.rate));
+    loop {
+        tokio::select! {
+            Some((id, update)) = poses.next() => match update {
+                Ok(update) => {
+                    latest.insert(id, update);
+                },
+                Err(BroadcastStreamRecvError::Lagged(count)) => {
+                    log::warn!("Bridge missed {} poses for {}", count, id);
+                }
+            },
+            _ = interval.tick() => {
+                for (id, update) in latest.drain() {
+                    let action = router::Action::Send { table: pose_table(&id, &update), to: id };
+                    if router_tx.send(action).await.is_err() {
+                        anyhow::bail!("Could not communicate with router");
+                    }
+                }
+            }
+        }
+    }
+}
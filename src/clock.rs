@@ -0,0 +1,192 @@
+use futures::StreamExt;
+use shared::{event::Severity, experiment::State};
+use std::{collections::{HashMap, VecDeque}, time::Duration};
+use tokio::sync::{broadcast::error::RecvError, mpsc, oneshot};
+
+use crate::{arena, journal, router::{self, LuaType}, subscription};
+
+/* the number of recent ticks for which a reply from a robot is accepted */
+const PENDING_TICKS: usize = 16;
+
+#[derive(Debug)]
+pub struct Configuration {
+    /* the time between two ticks */
+    pub interval: Duration,
+}
+
+/* the best estimate of the offset of the clock of a robot so far */
+struct Estimate {
+    /* the time of the robot minus the time of the supervisor in seconds */
+    offset: f64,
+    /* the round-trip time of the tick from which the offset was estimated in seconds */
+    round_trip: f64,
+}
+
+fn timestamp() -> f64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0.0, |duration| duration.as_secs_f64())
+}
+
+fn field<'a>(table: &'a [(LuaType, LuaType)], name: &str) -> Option<&'a LuaType> {
+    table.iter()
+        .find(|(key, _)| matches!(key, LuaType::String(key) if key == name))
+        .map(|(_, value)| value)
+}
+
+/* the robot, tick, and time of the robot in a reply, i.e., {fromS = <id>, clockT = {tickN = <tick>, timeN = <time>}} */
+fn reply(message: &LuaType) -> Option<(String, u64, f64)> {
+    let table = match message {
+        LuaType::Table(table) => table,
+        _ => return None,
+    };
+    let robot = match field(table, "fromS")? {
+        LuaType::String(robot) => robot.clone(),
+        _ => return None,
+    };
+    let clock = match field(table, "clockT")? {
+        LuaType::Table(clock) => clock,
+        _ => return None,
+    };
+    match (field(clock, "tickN")?, field(clock, "timeN")?) {
+        (LuaType::Number(tick), LuaType::Number(time)) if *tick >= 0.0 => Some((robot, *tick as u64, *time)),
+        _ => None,
+    }
+}
+
+fn tick_table(id: &str, start: f64, tick: u64, time: f64) -> Vec<(LuaType, LuaType)> {
+    let clock = vec![
+        (LuaType::String(String::from("startN")), LuaType::Number(start)),
+        (LuaType::String(String::from("tickN")), LuaType::Number(tick as f64)),
+        (LuaType::String(String::from("timeN")), LuaType::Number(time)),
+    ];
+    vec![
+        (LuaType::String(String::from("fromS")), LuaType::String(String::from("supervisor"))),
+        (LuaType::String(String::from("toS")), LuaType::String(id.to_owned())),
+        (LuaType::String(String::from("clockT")), LuaType::Table(clock)),
+    ]
+}
+
+async fn report(arena_tx: &mpsc::Sender<arena::Action>, robot: Option<String>, severity: Severity, message: String) -> anyhow::Result<()> {
+    let action = arena::Action::ReportEvent {
+        severity,
+        robot,
+        subsystem: String::from("clock"),
+        message,
+    };
+    arena_tx.send(action).await
+        .map_err(|_| anyhow::anyhow!("Could not communicate with arena"))
+}
+
+/// This function distributes a common experiment clock through the message router. When an experiment
+/// starts, the start time is recorded and every robot that is connected to the router, including the
+/// robots simulated by ARGoS, is sent a tick at the configured interval, which contains the start time,
+/// the number of the tick, and the time at which it was sent, all in seconds since the epoch. Robots
+/// that reply with the number of the tick and their own time allow the offset of their clocks to be
+/// estimated from the round trip. Each estimate that improves on the previous estimate for a robot is
+/// recorded in the journal. If no clock has been configured, this function never completes.
+pub async fn new(
+    config: Option<Configuration>,
+    arena_tx: mpsc::Sender<arena::Action>,
+    router_tx: mpsc::Sender<router::Action>,
+    journal_tx: mpsc::Sender<journal::Action>,
+) -> anyhow::Result<()> {
+    let config = match config {
+        Some(config) => config,
+        /* clock distribution is disabled, never complete */
+        None => return futures::future::pending().await,
+    };
+    let (callback_tx, callback_rx) = oneshot::channel();
+    arena_tx.send(arena::Action::SubscribeExperimentState(callback_tx)).await
+        .map_err(|_| anyhow::anyhow!("Could not communicate with arena"))?;
+    let mut experiment_updates = callback_rx.await
+        .map(subscription::updates)
+        .map_err(|_| anyhow::anyhow!("Could not subscribe to experiment updates"))?;
+    let (callback_tx, callback_rx) = oneshot::channel();
+    router_tx.send(router::Action::Subscribe(callback_tx)).await
+        .map_err(|_| anyhow::anyhow!("Could not communicate with router"))?;
+    let mut messages = callback_rx.await
+        .map_err(|_| anyhow::anyhow!("Could not subscribe to router"))?;
+    /* the start time of the current experiment, if any */
+    let mut start: Option<f64> = None;
+    let mut tick: u64 = 0;
+    /* the time at which each of the recent ticks was sent */
+    let mut pending: VecDeque<(u64, f64)> = VecDeque::with_capacity(PENDING_TICKS);
+    let mut estimates: HashMap<String, Estimate> = HashMap::new();
+    let mut interval = tokio::time::interval(config.interval);
+    loop {
+        tokio::select! {
+            Some(Ok(state)) = experiment_updates.next() => match (state, start) {
+                (State::Active, None) => {
+                    let now = timestamp();
+                    start = Some(now);
+                    tick = 0;
+                    pending.clear();
+                    estimates.clear();
+                    /* the first tick is sent immediately */
+                    interval = tokio::time::interval(config.interval);
+                    report(&arena_tx, None, Severity::Info,
+                        format!("Experiment clock started at {:.3} s since the epoch", now)).await?;
+                },
+                (State::Active, Some(_)) => {},
+                (_, Some(_)) => {
+                    start = None;
+                    let mut robots = estimates.iter().collect::<Vec<_>>();
+                    robots.sort_by_key(|(lhs, _)| *lhs);
+                    for (robot, estimate) in robots {
+                        report(&arena_tx, Some(robot.clone()), Severity::Info,
+                            format!("Clock offset of {:+.1} ms (round trip of {:.1} ms)",
+                                estimate.offset * 1000.0, estimate.round_trip * 1000.0)).await?;
+                    }
+                },
+                (_, None) => {},
+            },
+            _ = interval.tick(), if start.is_some() => {
+                let start = start.unwrap_or_default();
+                let (callback_tx, callback_rx) = oneshot::channel();
+                router_tx.send(router::Action::GetConnected(callback_tx)).await
+                    .map_err(|_| anyhow::anyhow!("Could not communicate with router"))?;
+                let connected = callback_rx.await
+                    .map_err(|_| anyhow::anyhow!("No response from router"))?;
+                let now = timestamp();
+                for id in connected {
+                    let table = tick_table(&id, start, tick, now);
+                    router_tx.send(router::Action::Send { to: id, table }).await
+                        .map_err(|_| anyhow::anyhow!("Could not communicate with router"))?;
+                }
+                if pending.len() == PENDING_TICKS {
+                    pending.pop_front();
+                }
+                pending.push_back((tick, now));
+                tick += 1;
+            },
+            message = messages.recv() => match message {
+                Ok((_, message)) => {
+                    let received = timestamp();
+                    let (robot, tick, time) = match reply(&message) {
+                        Some(reply) => reply,
+                        None => continue,
+                    };
+                    let sent = match pending.iter().find(|(pending, _)| *pending == tick) {
+                        Some((_, sent)) => *sent,
+                        /* replies to old ticks or ticks of a previous experiment are ignored */
+                        None => continue,
+                    };
+                    /* assuming that the tick takes as long to arrive as the reply, the time of the
+                       robot corresponds to the middle of the round trip */
+                    let round_trip = received - sent;
+                    let offset = time - (sent + received) / 2.0;
+                    /* the estimate from the shortest round trip is the most accurate */
+                    if estimates.get(&robot).is_none_or(|estimate| round_trip < estimate.round_trip) {
+                        estimates.insert(robot.clone(), Estimate { offset, round_trip });
+                        let event = journal::Event::ClockOffset(robot, offset, round_trip);
+                        let _ = journal_tx.send(journal::Action::Record(event)).await;
+                    }
+                },
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => break,
+            }
+        }
+    }
+    Ok(())
+}
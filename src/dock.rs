@@ -0,0 +1,190 @@
+use anyhow::Context;
+use futures::{FutureExt, StreamExt, stream::FuturesUnordered};
+use serde::Deserialize;
+use std::{collections::HashMap, net::SocketAddr, time::Duration};
+use tokio::{io::{AsyncBufReadExt, AsyncWriteExt, BufReader}, net::TcpStream, sync::{mpsc, oneshot}};
+
+use shared::event::Severity;
+use crate::{arena, timeouts};
+
+#[derive(Clone, Copy, Debug)]
+pub enum Protocol {
+    /* GET http://<addr>/status responds with a JSON array of slots */
+    Http,
+    /* after sending a line with STATUS, the dock responds with a line for each slot, i.e.,
+       <slot> <docked> <charging>, where docked and charging are 0 or 1, and closes the connection */
+    Tcp,
+}
+
+#[derive(Debug)]
+pub struct Slot {
+    pub pipuck: String,
+    /* the position (x, y) to which the Pi-Puck is driven when it is recalled */
+    pub position: Option<[f32; 2]>,
+}
+
+#[derive(Debug)]
+pub struct Dock {
+    pub id: String,
+    pub addr: SocketAddr,
+    pub protocol: Protocol,
+    /* the Pi-Puck that is assigned to each slot of the dock */
+    pub slots: HashMap<u8, Slot>,
+}
+
+#[derive(Debug)]
+pub struct Configuration {
+    pub docks: Vec<Dock>,
+    /* how often the status of the docks is requested */
+    pub interval: Duration,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Status {
+    /* the dock could not be reached or did not report the slot */
+    Unknown,
+    Undocked,
+    Docked { charging: bool },
+}
+
+pub enum Action {
+    /* the dock and the status of each Pi-Puck that has been assigned to a slot */
+    GetStatus(oneshot::Sender<HashMap<String, (String, Status)>>),
+    /* the position of the slot of each Pi-Puck for which a position has been configured */
+    GetPositions(oneshot::Sender<HashMap<String, [f32; 2]>>),
+}
+
+#[derive(Deserialize)]
+struct SlotStatus {
+    slot: u8,
+    docked: bool,
+    charging: bool,
+}
+
+async fn request_http(addr: SocketAddr) -> anyhow::Result<Vec<SlotStatus>> {
+    let url = format!("http://{}/status", addr);
+    reqwest::Client::new().get(&url).send().await
+        .and_then(reqwest::Response::error_for_status)
+        .context(format!("Could not request {}", url))?
+        .json::<Vec<SlotStatus>>().await
+        .context(format!("Could not parse response from {}", url))
+}
+
+async fn request_tcp(addr: SocketAddr) -> anyhow::Result<Vec<SlotStatus>> {
+    let request = async {
+        let mut stream = TcpStream::connect(addr).await?;
+        stream.write_all(b"STATUS\n").await?;
+        let mut lines = BufReader::new(stream).lines();
+        let mut slots = Vec::new();
+        while let Some(line) = lines.next_line().await? {
+            let fields = line.split_whitespace().collect::<Vec<_>>();
+            match fields.as_slice() {
+                [] => continue,
+                [slot, docked, charging] => slots.push(SlotStatus {
+                    slot: slot.parse().context("Could not parse slot")?,
+                    docked: *docked == "1",
+                    charging: *charging == "1",
+                }),
+                _ => anyhow::bail!("Could not parse line \"{}\"", line),
+            }
+        }
+        Ok(slots)
+    };
+    request.await
+        .context(format!("Could not request status from {}", addr))
+}
+
+/* the status of each Pi-Puck in the slots of a dock, the status is unknown if the dock did not respond
+   within the timeout of the policy after all retries */
+async fn poll(dock: &Dock, policy: &timeouts::Policy) -> Vec<(String, Status)> {
+    let result = policy.run(|| match dock.protocol {
+        Protocol::Http => request_http(dock.addr).left_future(),
+        Protocol::Tcp => request_tcp(dock.addr).right_future(),
+    }).await;
+    let slots = match result {
+        Ok(slots) => slots,
+        Err(error) => {
+            log::warn!("Could not poll dock {}: {:#}", dock.id, error);
+            Vec::new()
+        }
+    };
+    dock.slots.iter()
+        .map(|(index, Slot { pipuck, .. })| {
+            let status = slots.iter()
+                .find(|slot| slot.slot == *index)
+                .map_or(Status::Unknown, |slot| match slot.docked {
+                    true => Status::Docked { charging: slot.charging },
+                    false => Status::Undocked,
+                });
+            (pipuck.clone(), status)
+        })
+        .collect()
+}
+
+/// This function polls the charging docks of the Pi-Pucks and keeps track of which Pi-Pucks are docked
+/// and charging. Changes are reported as events of the arena and the arena asks for the status of the
+/// docks as part of its readiness check, so that an experiment is not started with Pi-Pucks that are
+/// still on a dock. If no docks have been configured, the status of every Pi-Puck is empty.
+pub async fn new(
+    config: Option<Configuration>,
+    policy: timeouts::Policy,
+    arena_tx: mpsc::Sender<arena::Action>,
+    mut requests_rx: mpsc::Receiver<Action>
+) -> anyhow::Result<()> {
+    let (docks, interval) = match config {
+        Some(config) => (config.docks, config.interval),
+        None => (Vec::new(), Duration::from_secs(60)),
+    };
+    let mut status: HashMap<String, (String, Status)> = docks.iter()
+        .flat_map(|dock| dock.slots.values().map(move |slot| (slot.pipuck.clone(), (dock.id.clone(), Status::Unknown))))
+        .collect();
+    let mut interval = tokio::time::interval(interval);
+    loop {
+        tokio::select! {
+            _ = interval.tick(), if !docks.is_empty() => {
+                let mut polls = docks.iter()
+                    .map(|dock| async move { (dock, poll(dock, &policy).await) })
+                    .collect::<FuturesUnordered<_>>();
+                while let Some((dock, updates)) = polls.next().await {
+                    for (pipuck, update) in updates {
+                        let previous = status.insert(pipuck.clone(), (dock.id.clone(), update.clone()));
+                        if previous.is_some_and(|(_, previous)| previous == update) {
+                            continue;
+                        }
+                        let message = match update {
+                            Status::Unknown => format!("Status on dock {} is unknown", dock.id),
+                            Status::Undocked => format!("Left dock {}", dock.id),
+                            Status::Docked { charging: true } => format!("Charging on dock {}", dock.id),
+                            Status::Docked { charging: false } => format!("Docked on dock {}", dock.id),
+                        };
+                        /* the event is sent in the background since the arena may be waiting for the
+                           status of the docks */
+                        let arena_tx = arena_tx.clone();
+                        tokio::spawn(async move {
+                            let _ = arena_tx.send(arena::Action::ReportEvent {
+                                severity: Severity::Info,
+                                robot: Some(pipuck),
+                                subsystem: String::from("dock"),
+                                message,
+                            }).await;
+                        });
+                    }
+                }
+            },
+            request = requests_rx.recv() => match request {
+                Some(Action::GetStatus(callback)) => {
+                    let _ = callback.send(status.clone());
+                },
+                Some(Action::GetPositions(callback)) => {
+                    let positions = docks.iter()
+                        .flat_map(|dock| dock.slots.values())
+                        .filter_map(|slot| slot.position.map(|position| (slot.pipuck.clone(), position)))
+                        .collect();
+                    let _ = callback.send(positions);
+                },
+                None => break,
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,224 @@
+use anyhow::Context;
+use bytes::BytesMut;
+use serde::Deserialize;
+use std::{collections::HashMap, fs::File, io::{BufRead, BufReader}, path::Path};
+use shared::{audit, builderbot, drone, pipuck, event, tracking_system};
+
+/* the journal entries as they are written by the journal task, the messages from the router are
+   kept as generic pickle values since their structure depends on the controllers of the robots */
+#[derive(Deserialize)]
+pub struct Entry {
+    pub timestamp: i64,
+    pub event: Event,
+}
+
+#[derive(Deserialize)]
+pub enum Event {
+    ARGoS(String, ARGoS),
+    /* the run is missing in the journals that were recorded before the runs were tagged */
+    Message(String, serde_pickle::Value, #[serde(default)] Option<String>),
+    TrackingSystem(Vec<tracking_system::Update>),
+    Descriptors(Vec<builderbot::Descriptor>, Vec<drone::Descriptor>, Vec<pipuck::Descriptor>),
+    Arena(event::Event),
+    ClockOffset(String, f64, f64),
+    Audit(audit::Entry),
+}
+
+#[derive(Deserialize)]
+pub enum ARGoS {
+    StandardOutput(BytesMut),
+    StandardError(BytesMut),
+}
+
+struct Writers {
+    robots: csv::Writer<File>,
+    poses: csv::Writer<File>,
+    argos: csv::Writer<File>,
+    messages: csv::Writer<File>,
+    events: csv::Writer<File>,
+    clock: csv::Writer<File>,
+    audit: csv::Writer<File>,
+}
+
+impl Writers {
+    fn new(output: &Path, stem: &str) -> anyhow::Result<Self> {
+        let writer = |name: &str, header: &[&str]| -> anyhow::Result<csv::Writer<File>> {
+            let path = output.join(format!("{}-{}.csv", stem, name));
+            let mut writer = csv::Writer::from_path(&path)
+                .context(format!("Could not create {:?}", path))?;
+            writer.write_record(header)?;
+            Ok(writer)
+        };
+        Ok(Self {
+            robots: writer("robots", &["robot", "kind", "optitrack_id"])?,
+            poses: writer("poses", &["timestamp", "optitrack_id", "x", "y", "z", "qw", "qx", "qy", "qz"])?,
+            argos: writer("argos", &["timestamp", "robot", "stream", "line"])?,
+            messages: writer("messages", &["timestamp", "run", "socket", "message"])?,
+            events: writer("events", &["timestamp", "severity", "robot", "subsystem", "message"])?,
+            clock: writer("clock", &["timestamp", "robot", "offset", "round_trip"])?,
+            audit: writer("audit", &["timestamp", "time", "operator", "arena", "robot", "action", "error"])?,
+        })
+    }
+
+    fn flush(&mut self) -> anyhow::Result<()> {
+        self.robots.flush()?;
+        self.poses.flush()?;
+        self.argos.flush()?;
+        self.messages.flush()?;
+        self.events.flush()?;
+        self.clock.flush()?;
+        self.audit.flush()?;
+        Ok(())
+    }
+}
+
+/* ARGoS output arrives in chunks that do not necessarily end with a complete line */
+pub fn split_lines(buffer: &mut Vec<u8>, chunk: &[u8]) -> Vec<String> {
+    buffer.extend_from_slice(chunk);
+    let mut lines = Vec::new();
+    while let Some(index) = buffer.iter().position(|byte| *byte == b'\n') {
+        let line = buffer.drain(..=index).collect::<Vec<_>>();
+        lines.push(String::from_utf8_lossy(&line[..line.len() - 1]).into_owned());
+    }
+    lines
+}
+
+/* the name of a journal without its extensions, e.g., .pkl.gz, and its extensions */
+fn split_name(journal: &Path) -> anyhow::Result<(&str, &str)> {
+    let name = journal.file_name()
+        .and_then(|name| name.to_str())
+        .ok_or(anyhow::anyhow!("Could not get the file name of {:?}", journal))?;
+    Ok(match name.find('.') {
+        Some(index) => (&name[..index], &name[index..]),
+        None => (name, ""),
+    })
+}
+
+/* open a journal for reading, decompressing it if necessary */
+fn open(journal: &Path) -> anyhow::Result<Box<dyn BufRead>> {
+    let file = File::open(journal)
+        .context(format!("Could not open journal {:?}", journal))?;
+    let (_, extension) = split_name(journal)?;
+    Ok(match extension {
+        ".pkl.gz" => Box::new(BufReader::new(flate2::read::GzDecoder::new(file))),
+        ".pkl.zst" => Box::new(BufReader::new(zstd::Decoder::new(file)?)),
+        _ => Box::new(BufReader::new(file)),
+    })
+}
+
+/* read the entries of a journal one after another, the closure is called for each entry */
+pub fn read(journal: &Path, mut entry: impl FnMut(Entry) -> anyhow::Result<()>) -> anyhow::Result<usize> {
+    let mut reader = open(journal)?;
+    let mut entries = 0;
+    while !reader.fill_buf()?.is_empty() {
+        let mut deserializer = serde_pickle::Deserializer::new(&mut reader, false);
+        let next = Entry::deserialize(&mut deserializer)
+            .context(format!("Could not read entry {} of journal {:?}", entries, journal))?;
+        entries += 1;
+        entry(next)?;
+    }
+    Ok(entries)
+}
+
+/* a pickle value as JSON, the sequences that the vectors and tables of a message were recorded as
+   become arrays and the keys of a dictionary become strings */
+fn json(value: serde_pickle::Value) -> serde_json::Value {
+    use serde_pickle::Value;
+    match value {
+        Value::None => serde_json::Value::Null,
+        Value::Bool(value) => serde_json::Value::Bool(value),
+        Value::I64(value) => serde_json::Value::from(value),
+        /* integers that do not fit into 64 bits are kept exact as strings */
+        Value::Int(value) => serde_json::Value::String(value.to_string()),
+        Value::F64(value) => serde_json::Number::from_f64(value)
+            .map_or(serde_json::Value::Null, serde_json::Value::Number),
+        Value::Bytes(value) => serde_json::Value::String(String::from_utf8_lossy(&value).into_owned()),
+        Value::String(value) => serde_json::Value::String(value),
+        Value::List(values) | Value::Tuple(values) => values.into_iter()
+            .map(json)
+            .collect(),
+        Value::Set(values) | Value::FrozenSet(values) => values.into_iter()
+            .map(|value| json(value.into_value()))
+            .collect(),
+        Value::Dict(entries) => serde_json::Value::Object(entries.into_iter()
+            .map(|(key, value)| {
+                let key = match json(key.into_value()) {
+                    serde_json::Value::String(key) => key,
+                    key => key.to_string(),
+                };
+                (key, json(value))
+            })
+            .collect()),
+    }
+}
+
+/* convert a journal into one CSV file per type of event */
+pub fn journal(journal: &Path, output: &Path) -> anyhow::Result<()> {
+    let (stem, _) = split_name(journal)?;
+    let mut writers = Writers::new(output, stem)?;
+    /* incomplete lines of output, indexed by robot and stream */
+    let mut partial_lines: HashMap<(String, &str), Vec<u8>> = HashMap::new();
+    let entries = read(journal, |Entry { timestamp, event }| {
+        let timestamp = timestamp.to_string();
+        match event {
+            Event::ARGoS(robot, output) => {
+                let (stream, chunk) = match output {
+                    ARGoS::StandardOutput(chunk) => ("stdout", chunk),
+                    ARGoS::StandardError(chunk) => ("stderr", chunk),
+                };
+                let buffer = partial_lines.entry((robot.clone(), stream)).or_default();
+                for line in split_lines(buffer, &chunk) {
+                    writers.argos.write_record([timestamp.as_str(), robot.as_str(), stream, line.as_str()])?;
+                }
+            },
+            Event::Message(socket, message, run) => {
+                let message = json(message).to_string();
+                let run = run.unwrap_or_default();
+                writers.messages.write_record([timestamp.as_str(), run.as_str(), socket.as_str(), message.as_str()])?;
+            },
+            Event::TrackingSystem(updates) => for update in updates {
+                let [x, y, z] = update.position;
+                let [qw, qx, qy, qz] = update.orientation;
+                writers.poses.write_record(&[timestamp.clone(), update.id.to_string(),
+                    x.to_string(), y.to_string(), z.to_string(),
+                    qw.to_string(), qx.to_string(), qy.to_string(), qz.to_string()])?;
+            },
+            Event::Descriptors(builderbots, drones, pipucks) => {
+                let robots = builderbots.into_iter()
+                    .map(|desc| (desc.id, "BuilderBot", desc.optitrack_id))
+                    .chain(drones.into_iter()
+                        .map(|desc| (desc.id, "Drone", desc.optitrack_id)))
+                    .chain(pipucks.into_iter()
+                        .map(|desc| (desc.id, "Pi-Puck", desc.optitrack_id)));
+                for (id, kind, optitrack_id) in robots {
+                    let optitrack_id = optitrack_id.map_or_else(String::new, |id| id.to_string());
+                    writers.robots.write_record([id.as_str(), kind, optitrack_id.as_str()])?;
+                }
+            },
+            Event::Arena(event) => {
+                let severity = format!("{:?}", event.severity);
+                writers.events.write_record([timestamp.as_str(),
+                    severity.as_str(),
+                    event.robot.as_deref().unwrap_or(""),
+                    event.subsystem.as_str(),
+                    event.message.as_str()])?;
+            },
+            Event::ClockOffset(robot, offset, round_trip) => {
+                writers.clock.write_record(&[timestamp, robot, offset.to_string(), round_trip.to_string()])?;
+            },
+            Event::Audit(entry) => {
+                writers.audit.write_record([timestamp.as_str(),
+                    entry.timestamp.as_str(),
+                    entry.operator.as_str(),
+                    entry.arena.as_str(),
+                    entry.robot.as_deref().unwrap_or(""),
+                    entry.action.as_str(),
+                    entry.error.as_deref().unwrap_or("")])?;
+            },
+        }
+        Ok(())
+    })?;
+    writers.flush()?;
+    log::info!("Exported {} entries from {:?} to {:?}", entries, journal, output);
+    Ok(())
+}
@@ -0,0 +1,390 @@
+use anyhow::Context;
+use bytes::Bytes;
+use futures::{SinkExt, StreamExt, stream::FuturesUnordered};
+use serde::{Deserialize, Serialize};
+use shared::{FrontEndRequest, event::{Event, Severity}, experiment::State, group::Group, tracking_system};
+use std::{collections::HashMap, mem::discriminant, net::SocketAddr, sync::Arc, time::Duration};
+use tokio::{net::{TcpListener, TcpStream}, sync::{broadcast, mpsc, oneshot, watch}};
+use tokio_stream::wrappers::BroadcastStream;
+use tokio_util::codec::{Framed, LengthDelimitedCodec};
+use uuid::Uuid;
+
+use crate::{arena, robot::{builderbot, drone, pipuck}, subscription::Subscription, tracking, webui};
+
+/* the delay before connecting to the primary instance again after the connection has been lost */
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+/* the minimum time between two updates from the tracking system that are sent to the primary instance */
+const TRACKING_INTERVAL: Duration = Duration::from_millis(100);
+/* the number of updates from a member that can be waiting to be handled by its arena */
+const MEMBER_CAPACITY: usize = 64;
+
+#[derive(Debug)]
+pub enum Configuration {
+    /* aggregate the robots and events of the members with the given names */
+    Primary {
+        socket: SocketAddr,
+        members: Vec<String>,
+    },
+    /* send the robots and events of this instance to the primary instance */
+    Member {
+        primary: SocketAddr,
+        name: String,
+    },
+}
+
+/* the messages from a member to the primary instance, the updates are the same as those sent to the
+   clients of the web interface */
+#[derive(Debug, Deserialize, Serialize)]
+enum Message {
+    Hello(String),
+    Update(FrontEndRequest),
+}
+
+/* a member as seen by the primary instance, which provides a read-only arena for the web interface */
+pub struct Member {
+    name: String,
+    arena_rx: mpsc::Receiver<arena::Action>,
+    tracking_rx: mpsc::Receiver<tracking::Action>,
+}
+
+impl Member {
+    pub fn new(name: String) -> (Member, webui::Arena) {
+        let (arena_tx, arena_rx) = mpsc::channel(8);
+        let (tracking_tx, tracking_rx) = mpsc::channel(8);
+        let arena = webui::Arena { name: name.clone(), arena_tx, tracking_tx };
+        (Member { name, arena_rx, tracking_rx }, arena)
+    }
+}
+
+/// This function connects several supervisors, e.g., one per room or subnet. In the primary instance,
+/// the robots, events, and tracking system of each member are provided as a read-only arena that can
+/// be selected in the web interface. A member sends these to the primary instance and connects again
+/// if the connection is lost. If federation has not been configured, this function never completes.
+pub async fn new(
+    config: Option<Configuration>,
+    members: Vec<Member>,
+    arena_tx: mpsc::Sender<arena::Action>,
+    tracking_tx: mpsc::Sender<tracking::Action>,
+    capacity: usize,
+) -> anyhow::Result<()> {
+    match config {
+        Some(Configuration::Primary { socket, .. }) => primary(socket, members, capacity).await,
+        Some(Configuration::Member { primary, name }) => loop {
+            if let Err(error) = member(primary, &name, &arena_tx, &tracking_tx).await {
+                log::warn!("Connection to primary supervisor {} lost: {:#}", primary, error);
+            }
+            tokio::time::sleep(RECONNECT_DELAY).await;
+        },
+        /* federation is disabled, never complete */
+        None => futures::future::pending().await,
+    }
+}
+
+async fn primary(socket: SocketAddr, members: Vec<Member>, capacity: usize) -> anyhow::Result<()> {
+    let listener = TcpListener::bind(socket).await
+        .context(format!("Could not bind federation socket {}", socket))?;
+    log::info!("Federation running on: {:?}", listener.local_addr());
+    let mut member_txs = HashMap::new();
+    let mut proxies = FuturesUnordered::new();
+    for member in members {
+        let (message_tx, message_rx) = mpsc::channel(MEMBER_CAPACITY);
+        member_txs.insert(member.name.clone(), message_tx);
+        proxies.push(proxy(member, message_rx, capacity));
+    }
+    let member_txs = Arc::new(member_txs);
+    loop {
+        tokio::select! {
+            result = listener.accept() => {
+                let (stream, addr) = result
+                    .context("Could not accept connection from member")?;
+                let member_txs = member_txs.clone();
+                tokio::spawn(async move {
+                    if let Err(error) = receive(stream, &member_txs).await {
+                        log::warn!("Connection to member {} lost: {:#}", addr, error);
+                    }
+                });
+            },
+            Some(_) = proxies.next() => {},
+        }
+    }
+}
+
+/* forward the messages of a member to the proxy of its arena */
+async fn receive(
+    stream: TcpStream,
+    member_txs: &HashMap<String, mpsc::Sender<Message>>
+) -> anyhow::Result<()> {
+    let mut framed = Framed::new(stream, LengthDelimitedCodec::new());
+    let decode = |frame: bytes::BytesMut| bincode::deserialize::<Message>(&frame)
+        .context("Could not decode message from member");
+    let name = match framed.next().await {
+        Some(frame) => match decode(frame.context("Could not receive message from member")?)? {
+            Message::Hello(name) => name,
+            Message::Update(_) => anyhow::bail!("Member did not introduce itself"),
+        },
+        None => return Ok(()),
+    };
+    let member_tx = member_txs.get(&name)
+        .ok_or_else(|| anyhow::anyhow!("{} is not a member", name))?;
+    member_tx.send(Message::Hello(name.clone())).await
+        .map_err(|_| anyhow::anyhow!("Could not communicate with arena {}", name))?;
+    while let Some(frame) = framed.next().await {
+        let message = decode(frame.context("Could not receive message from member")?)?;
+        member_tx.send(message).await
+            .map_err(|_| anyhow::anyhow!("Could not communicate with arena {}", name))?;
+    }
+    let message = Message::Update(FrontEndRequest::AddEvent(event(Severity::Warning, format!("{} disconnected", name))));
+    let _ = member_tx.send(message).await;
+    Ok(())
+}
+
+fn event(severity: Severity, message: String) -> Event {
+    Event {
+        timestamp: chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+        severity,
+        robot: None,
+        subsystem: String::from("federation"),
+        message,
+    }
+}
+
+/* a robot of a member, the most recent update of each kind is kept in the order in which the updates
+   arrived, which describes the state of the robot to a new subscriber */
+struct Robot<D, U> {
+    descriptor: Arc<D>,
+    updates_tx: broadcast::Sender<U>,
+    state: Vec<U>,
+}
+
+impl<D, U: Clone> Robot<D, U> {
+    fn new(descriptor: D, capacity: usize) -> Self {
+        let (updates_tx, _) = broadcast::channel(capacity);
+        Robot { descriptor: Arc::new(descriptor), updates_tx, state: Vec::new() }
+    }
+
+    fn update(&mut self, update: U) {
+        let kind = discriminant(&update);
+        self.state.retain(|previous| discriminant(previous) != kind);
+        self.state.push(update.clone());
+        let _ = self.updates_tx.send(update);
+    }
+
+    fn subscribe(&self) -> Subscription<U> {
+        (self.state.clone(), self.updates_tx.subscribe())
+    }
+}
+
+/* answer the requests of the web interface for the arena of a member. Control of the arena can not
+   be acquired, so that the requests that would change the arena are never sent. The remaining
+   requests are dropped, which their senders report as no response */
+async fn proxy(member: Member, mut message_rx: mpsc::Receiver<Message>, capacity: usize) {
+    let Member { name, mut arena_rx, mut tracking_rx } = member;
+    let mut builderbots: HashMap<String, Robot<builderbot::Descriptor, builderbot::Update>> = HashMap::new();
+    let mut drones: HashMap<String, Robot<drone::Descriptor, drone::Update>> = HashMap::new();
+    let mut pipucks: HashMap<String, Robot<pipuck::Descriptor, pipuck::Update>> = HashMap::new();
+    let mut groups: Vec<Group> = Vec::new();
+    let (events_tx, _) = broadcast::channel(capacity);
+    let (tracking_tx, _) = broadcast::channel::<Vec<tracking_system::Update>>(capacity);
+    let (quarantined_tx, quarantined_rx) = watch::channel(Vec::new());
+    let (experiment_tx, _) = broadcast::channel::<State>(capacity);
+    let controller = arena::Controller {
+        id: Uuid::nil(),
+        name: format!("the supervisor of {}", name),
+    };
+    let (_control_tx, control_rx) = watch::channel(Some(controller));
+    loop {
+        tokio::select! {
+            Some(message) = message_rx.recv() => match message {
+                Message::Hello(_) => {
+                    let _ = events_tx.send(event(Severity::Info, format!("{} connected", name)));
+                },
+                Message::Update(update) => match update {
+                    FrontEndRequest::AddBuilderBot(desc) => {
+                        builderbots.entry(desc.id.clone()).or_insert_with(|| Robot::new(desc, capacity));
+                    },
+                    FrontEndRequest::UpdateBuilderBot(id, update) => if let Some(robot) = builderbots.get_mut(&id) {
+                        robot.update(update);
+                    },
+                    FrontEndRequest::AddDrone(desc) => {
+                        drones.entry(desc.id.clone()).or_insert_with(|| Robot::new(desc, capacity));
+                    },
+                    FrontEndRequest::UpdateDrone(id, update) => if let Some(robot) = drones.get_mut(&id) {
+                        robot.update(update);
+                    },
+                    FrontEndRequest::AddPiPuck(desc) => {
+                        pipucks.entry(desc.id.clone()).or_insert_with(|| Robot::new(desc, capacity));
+                    },
+                    FrontEndRequest::UpdatePiPuck(id, update) => if let Some(robot) = pipucks.get_mut(&id) {
+                        robot.update(update);
+                    },
+                    FrontEndRequest::UpdateTrackingSystem(updates) => {
+                        let _ = tracking_tx.send(updates);
+                    },
+                    FrontEndRequest::UpdateGroups(update) => groups = update,
+                    FrontEndRequest::UpdateQuarantined(quarantined) => {
+                        let _ = quarantined_tx.send(quarantined);
+                    },
+                    FrontEndRequest::AddEvent(event) => {
+                        let _ = events_tx.send(event);
+                    },
+                    _ => {},
+                },
+            },
+            Some(action) = arena_rx.recv() => match action {
+                arena::Action::GetBuilderBotDescriptors(callback) => {
+                    let _ = callback.send(builderbots.values().map(|robot| robot.descriptor.clone()).collect());
+                },
+                arena::Action::GetDroneDescriptors(callback) => {
+                    let _ = callback.send(drones.values().map(|robot| robot.descriptor.clone()).collect());
+                },
+                arena::Action::GetPiPuckDescriptors(callback) => {
+                    let _ = callback.send(pipucks.values().map(|robot| robot.descriptor.clone()).collect());
+                },
+                /* the camera frames are not forwarded by the members */
+                arena::Action::ForwardBuilderBotAction(id, action) => match (builderbots.get(&id), action) {
+                    (Some(robot), builderbot::Action::Subscribe(callback)) => {
+                        let _ = callback.send(robot.subscribe());
+                    },
+                    (Some(_), builderbot::Action::SubscribeFrames(callback)) => {
+                        let _ = callback.send(HashMap::new());
+                    },
+                    _ => {},
+                },
+                arena::Action::ForwardDroneAction(id, action) => match (drones.get(&id), action) {
+                    (Some(robot), drone::Action::Subscribe(callback)) => {
+                        let _ = callback.send(robot.subscribe());
+                    },
+                    (Some(_), drone::Action::SubscribeFrames(callback)) => {
+                        let _ = callback.send(HashMap::new());
+                    },
+                    _ => {},
+                },
+                arena::Action::ForwardPiPuckAction(id, action) => match (pipucks.get(&id), action) {
+                    (Some(robot), pipuck::Action::Subscribe(callback)) => {
+                        let _ = callback.send(robot.subscribe());
+                    },
+                    (Some(_), pipuck::Action::SubscribeFrames(callback)) => {
+                        let _ = callback.send(HashMap::new());
+                    },
+                    _ => {},
+                },
+                arena::Action::GetGroups(callback) => {
+                    let _ = callback.send(groups.clone());
+                },
+                arena::Action::SubscribeEvents(callback) => {
+                    let _ = callback.send(events_tx.subscribe());
+                },
+                arena::Action::SubscribeQuarantined(callback) => {
+                    let _ = callback.send(quarantined_rx.clone());
+                },
+                arena::Action::SubscribeControl(callback) => {
+                    let _ = callback.send(control_rx.clone());
+                },
+                arena::Action::SubscribeExperimentState(callback) => {
+                    let _ = callback.send((Vec::new(), experiment_tx.subscribe()));
+                },
+                arena::Action::AcquireControl { callback, .. } => {
+                    let _ = callback.send(Err(anyhow::anyhow!("{} can only be controlled from its own supervisor", name)));
+                },
+                _ => {},
+            },
+            Some(action) = tracking_rx.recv() => if let tracking::Action::Subscribe(callback) = action {
+                let _ = callback.send(tracking_tx.subscribe());
+            },
+            else => break,
+        }
+    }
+}
+
+/* send the robots, groups, events, and tracking system of this instance to the primary instance */
+async fn member(
+    primary: SocketAddr,
+    name: &str,
+    arena_tx: &mpsc::Sender<arena::Action>,
+    tracking_tx: &mpsc::Sender<tracking::Action>,
+) -> anyhow::Result<()> {
+    let stream = TcpStream::connect(primary).await
+        .context(format!("Could not connect to primary supervisor {}", primary))?;
+    log::info!("Connected to primary supervisor {}", primary);
+    let mut framed = Framed::new(stream, LengthDelimitedCodec::new());
+    let mut builderbot_updates = webui::subscribe_builderbot_updates(arena_tx).await?;
+    let mut drone_updates = webui::subscribe_drone_updates(arena_tx).await?;
+    let mut pipuck_updates = webui::subscribe_pipuck_updates(arena_tx).await?;
+    let (callback_tx, callback_rx) = oneshot::channel();
+    arena_tx.send(arena::Action::GetGroups(callback_tx)).await
+        .map_err(|_| anyhow::anyhow!("Could not communicate with arena"))?;
+    let groups = callback_rx.await
+        .map_err(|_| anyhow::anyhow!("Could not get groups"))?;
+    let (callback_tx, callback_rx) = oneshot::channel();
+    arena_tx.send(arena::Action::SubscribeQuarantined(callback_tx)).await
+        .map_err(|_| anyhow::anyhow!("Could not communicate with arena"))?;
+    let mut quarantined_rx = callback_rx.await
+        .map_err(|_| anyhow::anyhow!("Could not subscribe to quarantine updates"))?;
+    let (callback_tx, callback_rx) = oneshot::channel();
+    arena_tx.send(arena::Action::SubscribeEvents(callback_tx)).await
+        .map_err(|_| anyhow::anyhow!("Could not communicate with arena"))?;
+    let mut events = callback_rx.await
+        .map(BroadcastStream::new)
+        .map_err(|_| anyhow::anyhow!("Could not subscribe to arena events"))?;
+    let (callback_tx, callback_rx) = oneshot::channel();
+    tracking_tx.send(tracking::Action::Subscribe(callback_tx)).await
+        .map_err(|_| anyhow::anyhow!("Could not communicate with tracking system"))?;
+    let mut tracking_updates = callback_rx.await
+        .map(BroadcastStream::new)
+        .map_err(|_| anyhow::anyhow!("Could not subscribe to tracking system updates"))?;
+    /* introduce this instance and its robots, followed by their updates */
+    let mut messages = vec![
+        Message::Hello(name.to_owned()),
+        Message::Update(FrontEndRequest::UpdateGroups(groups)),
+        Message::Update(FrontEndRequest::UpdateQuarantined(quarantined_rx.borrow().clone())),
+    ];
+    messages.extend(builderbot_updates.keys()
+        .map(|desc| Message::Update(FrontEndRequest::AddBuilderBot(builderbot::Descriptor::clone(desc)))));
+    messages.extend(drone_updates.keys()
+        .map(|desc| Message::Update(FrontEndRequest::AddDrone(drone::Descriptor::clone(desc)))));
+    messages.extend(pipuck_updates.keys()
+        .map(|desc| Message::Update(FrontEndRequest::AddPiPuck(pipuck::Descriptor::clone(desc)))));
+    /* only the most recent update of the tracking system is sent in each interval */
+    let mut tracking_latest = None;
+    let mut tracking_interval = tokio::time::interval(TRACKING_INTERVAL);
+    loop {
+        for message in messages.drain(..) {
+            let encoded = bincode::serialize(&message)
+                .context("Could not encode message for primary supervisor")?;
+            framed.send(Bytes::from(encoded)).await
+                .context("Could not send message to primary supervisor")?;
+        }
+        let update = tokio::select! {
+            Some((desc, Ok(update))) = builderbot_updates.next() => match update {
+                builderbot::Update::Camera { .. } => continue,
+                update => FrontEndRequest::UpdateBuilderBot(desc.id.clone(), update),
+            },
+            Some((desc, Ok(update))) = drone_updates.next() => match update {
+                drone::Update::Camera { .. } => continue,
+                update => FrontEndRequest::UpdateDrone(desc.id.clone(), update),
+            },
+            Some((desc, Ok(update))) = pipuck_updates.next() => match update {
+                pipuck::Update::Camera { .. } => continue,
+                update => FrontEndRequest::UpdatePiPuck(desc.id.clone(), update),
+            },
+            Ok(_) = quarantined_rx.changed() =>
+                FrontEndRequest::UpdateQuarantined(quarantined_rx.borrow().clone()),
+            Some(Ok(event)) = events.next() => FrontEndRequest::AddEvent(event),
+            Some(Ok(updates)) = tracking_updates.next() => {
+                tracking_latest = Some(updates);
+                continue;
+            },
+            _ = tracking_interval.tick() => match tracking_latest.take() {
+                Some(updates) => FrontEndRequest::UpdateTrackingSystem(updates),
+                None => continue,
+            },
+            /* the primary instance does not send anything, this detects that the connection was closed */
+            frame = framed.next() => match frame {
+                Some(Ok(_)) => continue,
+                Some(Err(error)) => return Err(error).context("Could not receive from primary supervisor"),
+                None => anyhow::bail!("Connection closed by primary supervisor"),
+            },
+        };
+        messages.push(Message::Update(update));
+    }
+}
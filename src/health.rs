@@ -0,0 +1,248 @@
+use futures::StreamExt;
+use shared::{event::Severity, health::{Health, Trend}};
+use std::{collections::{HashMap, VecDeque}, time::{Duration, Instant}};
+use tokio::sync::{broadcast::error::RecvError, mpsc, oneshot, watch};
+
+use crate::{arena, tracking, webui, robot::{builderbot, drone, pipuck}};
+
+/* how often the health of the robots is assessed */
+const ASSESSMENT_INTERVAL: Duration = Duration::from_secs(5);
+/* the errors that were reported for a robot within this window lower its score */
+const ERROR_WINDOW: Duration = Duration::from_secs(600);
+/* the trend compares the current score with the score from this long ago */
+const TREND_WINDOW: Duration = Duration::from_secs(60);
+/* the smallest change in score that is taken as a trend */
+const TREND_THRESHOLD: i32 = 5;
+/* a rigid body that has not been tracked for this long is not visible */
+const VISIBILITY_TIMEOUT: Duration = Duration::from_secs(2);
+
+struct Link {
+    name: &'static str,
+    connected: bool,
+    /* the quality of the signal in percent */
+    quality: Option<i32>,
+}
+
+struct Robot {
+    optitrack_id: Option<i32>,
+    links: Vec<Link>,
+    battery: Option<i32>,
+    temperature: Option<i32>,
+    /* when the errors within the error window were reported */
+    errors: VecDeque<Instant>,
+    /* the scores within the trend window, oldest first */
+    scores: VecDeque<(Instant, u8)>,
+}
+
+impl Robot {
+    fn new(optitrack_id: Option<i32>, links: &[&'static str]) -> Self {
+        Robot {
+            optitrack_id,
+            links: links.iter()
+                .map(|&name| Link { name, connected: false, quality: None })
+                .collect(),
+            battery: None,
+            temperature: None,
+            errors: VecDeque::new(),
+            scores: VecDeque::new(),
+        }
+    }
+
+    fn set_connected(&mut self, name: &str, connected: bool) {
+        if let Some(link) = self.links.iter_mut().find(|link| link.name == name) {
+            link.connected = connected;
+            link.quality = None;
+        }
+    }
+
+    fn set_quality(&mut self, name: &str, quality: i32) {
+        if let Some(link) = self.links.iter_mut().find(|link| link.name == name) {
+            link.quality = Some(quality.clamp(0, 100));
+        }
+    }
+
+    /* the score starts at 100 and each issue subtracts a penalty */
+    fn assess(&mut self, visible: Option<bool>, now: Instant) -> (u8, Vec<String>) {
+        let mut penalty: usize = 0;
+        let mut issues = Vec::new();
+        for link in &self.links {
+            match (link.connected, link.quality) {
+                (false, _) => {
+                    penalty += 60;
+                    issues.push(format!("{} is disconnected", link.name));
+                },
+                (true, Some(quality)) if quality < 25 => {
+                    penalty += 25;
+                    issues.push(format!("{} signal is weak ({}%)", link.name, quality));
+                },
+                (true, Some(quality)) if quality < 50 => {
+                    penalty += 10;
+                    issues.push(format!("{} signal is fair ({}%)", link.name, quality));
+                },
+                _ => {},
+            }
+        }
+        match self.battery {
+            Some(battery) if battery < 25 => {
+                penalty += 30;
+                issues.push(format!("battery is low ({}%)", battery));
+            },
+            Some(battery) if battery < 50 => {
+                penalty += 10;
+                issues.push(format!("battery is below half ({}%)", battery));
+            },
+            _ => {},
+        }
+        match self.temperature {
+            Some(temperature) if temperature >= 80 => {
+                penalty += 30;
+                issues.push(format!("CPU is hot ({} °C)", temperature));
+            },
+            Some(temperature) if temperature >= 70 => {
+                penalty += 10;
+                issues.push(format!("CPU is warm ({} °C)", temperature));
+            },
+            _ => {},
+        }
+        while self.errors.front().is_some_and(|&error| now.duration_since(error) > ERROR_WINDOW) {
+            self.errors.pop_front();
+        }
+        if !self.errors.is_empty() {
+            penalty += 15 * self.errors.len().min(3);
+            issues.push(format!("{} error(s) in the last ten minutes", self.errors.len()));
+        }
+        if let (Some(optitrack_id), Some(false)) = (self.optitrack_id, visible) {
+            penalty += 20;
+            issues.push(format!("rigid body {} is not visible", optitrack_id));
+        }
+        ((100 - penalty.min(100)) as u8, issues)
+    }
+
+    fn trend(&mut self, score: u8, now: Instant) -> Trend {
+        while self.scores.front().is_some_and(|&(time, _)| now.duration_since(time) > TREND_WINDOW) {
+            self.scores.pop_front();
+        }
+        self.scores.push_back((now, score));
+        let change = self.scores.front()
+            .map_or(0, |&(_, previous)| score as i32 - previous as i32);
+        match change {
+            change if change >= TREND_THRESHOLD => Trend::Improving,
+            change if change <= -TREND_THRESHOLD => Trend::Declining,
+            _ => Trend::Steady,
+        }
+    }
+}
+
+/// This function assesses the health of each robot in the arena from the strength of its links, its
+/// battery level, the temperature of its computer, the errors that were recently reported for it
+/// (e.g., crashes of ARGoS or restarts of its task), and the visibility of its rigid body. The health
+/// is a score between 0 and 100 with a trend over the last minute, which is sent to the arena every
+/// few seconds so that it can be shown in the user interface and checked before an experiment.
+pub async fn new(
+    arena_tx: mpsc::Sender<arena::Action>,
+    tracking_tx: mpsc::Sender<tracking::Action>,
+    health_tx: watch::Sender<Vec<Health>>,
+) -> anyhow::Result<()> {
+    let mut builderbot_updates = webui::subscribe_builderbot_updates(&arena_tx).await?;
+    let mut drone_updates = webui::subscribe_drone_updates(&arena_tx).await?;
+    let mut pipuck_updates = webui::subscribe_pipuck_updates(&arena_tx).await?;
+    let (callback_tx, callback_rx) = oneshot::channel();
+    tracking_tx.send(tracking::Action::Subscribe(callback_tx)).await
+        .map_err(|_| anyhow::anyhow!("Could not communicate with tracking system"))?;
+    let mut frames = callback_rx.await
+        .map_err(|_| anyhow::anyhow!("Could not subscribe to tracking system"))?;
+    let (callback_tx, callback_rx) = oneshot::channel();
+    arena_tx.send(arena::Action::SubscribeEvents(callback_tx)).await
+        .map_err(|_| anyhow::anyhow!("Could not communicate with arena"))?;
+    let mut events_rx = callback_rx.await
+        .map_err(|_| anyhow::anyhow!("Could not subscribe to arena events"))?;
+    let mut robots: HashMap<String, Robot> = HashMap::new();
+    for desc in builderbot_updates.keys() {
+        robots.insert(desc.id.clone(), Robot::new(desc.optitrack_id, &["Fernbedienung"]));
+    }
+    for desc in drone_updates.keys() {
+        robots.insert(desc.id.clone(), Robot::new(desc.optitrack_id, &["Fernbedienung", "Xbee"]));
+    }
+    for desc in pipuck_updates.keys() {
+        robots.insert(desc.id.clone(), Robot::new(desc.optitrack_id, &["Fernbedienung"]));
+    }
+    /* when each rigid body was last tracked */
+    let mut last_tracked: HashMap<i32, Instant> = HashMap::new();
+    let mut interval = tokio::time::interval(ASSESSMENT_INTERVAL);
+    loop {
+        tokio::select! {
+            Some((desc, Ok(update))) = builderbot_updates.next() => if let Some(robot) = robots.get_mut(&desc.id) {
+                match update {
+                    builderbot::Update::FernbedienungConnected(_) => robot.set_connected("Fernbedienung", true),
+                    builderbot::Update::FernbedienungDisconnected => robot.set_connected("Fernbedienung", false),
+                    /* the signal is reported in dBm, the user interface shows it as dBm + 90 percent */
+                    builderbot::Update::FernbedienungSignal(signal) => robot.set_quality("Fernbedienung", signal + 90),
+                    builderbot::Update::Battery(battery) => robot.battery = Some(battery),
+                    builderbot::Update::Temperature(temperature) => robot.temperature = Some(temperature),
+                    _ => {},
+                }
+            },
+            Some((desc, Ok(update))) = drone_updates.next() => if let Some(robot) = robots.get_mut(&desc.id) {
+                match update {
+                    drone::Update::FernbedienungConnected(_) => robot.set_connected("Fernbedienung", true),
+                    drone::Update::FernbedienungDisconnected => robot.set_connected("Fernbedienung", false),
+                    drone::Update::FernbedienungSignal(signal) => robot.set_quality("Fernbedienung", signal + 90),
+                    drone::Update::XbeeConnected(_) => robot.set_connected("Xbee", true),
+                    drone::Update::XbeeDisconnected => robot.set_connected("Xbee", false),
+                    /* the link margin of the Xbee is already reported in percent */
+                    drone::Update::XbeeSignal(signal) => robot.set_quality("Xbee", signal),
+                    drone::Update::Battery(battery) => robot.battery = Some(battery),
+                    drone::Update::Temperature(temperature) => robot.temperature = Some(temperature),
+                    _ => {},
+                }
+            },
+            Some((desc, Ok(update))) = pipuck_updates.next() => if let Some(robot) = robots.get_mut(&desc.id) {
+                match update {
+                    pipuck::Update::FernbedienungConnected(_) => robot.set_connected("Fernbedienung", true),
+                    pipuck::Update::FernbedienungDisconnected => robot.set_connected("Fernbedienung", false),
+                    pipuck::Update::FernbedienungSignal(signal) => robot.set_quality("Fernbedienung", signal + 90),
+                    pipuck::Update::Battery(battery) => robot.battery = Some(battery),
+                    pipuck::Update::Temperature(temperature) => robot.temperature = Some(temperature),
+                    _ => {},
+                }
+            },
+            frame = frames.recv() => match frame {
+                Ok(frame) => {
+                    let now = Instant::now();
+                    for update in frame {
+                        last_tracked.insert(update.id, now);
+                    }
+                },
+                Err(RecvError::Lagged(_)) => {},
+                Err(RecvError::Closed) => break,
+            },
+            event = events_rx.recv() => match event {
+                Ok(event) => if event.severity == Severity::Error {
+                    if let Some(robot) = event.robot.as_ref().and_then(|robot| robots.get_mut(robot)) {
+                        robot.errors.push_back(Instant::now());
+                    }
+                },
+                Err(RecvError::Lagged(count)) => log::warn!("Health assessment missed {} arena events", count),
+                Err(RecvError::Closed) => break,
+            },
+            _ = interval.tick() => {
+                let now = Instant::now();
+                let mut health = robots.iter_mut()
+                    .map(|(id, robot)| {
+                        let visible = robot.optitrack_id.map(|optitrack_id| last_tracked.get(&optitrack_id)
+                            .is_some_and(|&time| now.duration_since(time) < VISIBILITY_TIMEOUT));
+                        let (score, issues) = robot.assess(visible, now);
+                        let trend = robot.trend(score, now);
+                        Health { robot: id.clone(), score, trend, issues }
+                    })
+                    .collect::<Vec<_>>();
+                health.sort_by(|a, b| a.robot.cmp(&b.robot));
+                /* the arena keeps a receiver, so this only fails once the arena has stopped */
+                if health_tx.send(health).is_err() {
+                    break;
+                }
+            }
+        }
+    }
+    Ok(())
+}
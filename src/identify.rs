@@ -0,0 +1,105 @@
+use std::time::Duration;
+use tokio::sync::{broadcast::error::RecvError, mpsc, oneshot};
+use shared::tracking_system::Update;
+
+use crate::{robot::{self, builderbot, pipuck}, tracking};
+
+/* how long to observe the rigid bodies after a robot has been told to spin */
+const OBSERVATION_WINDOW: Duration = Duration::from_secs(8);
+/* the minimum rotation (in radians) for a rigid body to be considered as spinning */
+const ROTATION_THRESHOLD: f32 = std::f32::consts::FRAC_PI_4;
+
+/* robots that can safely be moved to identify their rigid bodies */
+pub enum Robot {
+    BuilderBot(builderbot::Sender),
+    PiPuck(pipuck::Sender),
+}
+
+impl Robot {
+    async fn spin(&self) -> anyhow::Result<()> {
+        let (callback_tx, callback_rx) = oneshot::channel();
+        let action = robot::FernbedienungAction::Spin;
+        match self {
+            Robot::BuilderBot(action_tx) => action_tx
+                .send(builderbot::Action::ExecuteFernbedienungAction(callback_tx, action)).await
+                .map_err(|_| anyhow::anyhow!("Could not communicate with BuilderBot"))?,
+            Robot::PiPuck(action_tx) => action_tx
+                .send(pipuck::Action::ExecuteFernbedienungAction(callback_tx, action)).await
+                .map_err(|_| anyhow::anyhow!("Could not communicate with Pi-Puck"))?,
+        }
+        callback_rx.await
+            .map_err(|_| anyhow::anyhow!("No response from robot"))?
+    }
+}
+
+/* the angle between two orientations given as quaternions (w, x, y, z) */
+fn angle_between(a: &[f32; 4], b: &[f32; 4]) -> f32 {
+    let dot = a.iter().zip(b.iter()).map(|(a, b)| a * b).sum::<f32>();
+    2.0 * dot.abs().min(1.0).acos()
+}
+
+/* spin a robot and return the rigid body that rotated the most during the observation window */
+async fn identify(
+    robot: &Robot,
+    tracking_tx: &mpsc::Sender<tracking::Action>
+) -> anyhow::Result<Option<i32>> {
+    let (callback_tx, callback_rx) = oneshot::channel();
+    tracking_tx.send(tracking::Action::Subscribe(callback_tx)).await
+        .map_err(|_| anyhow::anyhow!("Could not communicate with tracking system"))?;
+    let mut frames = callback_rx.await
+        .map_err(|_| anyhow::anyhow!("No response from tracking system"))?;
+    robot.spin().await?;
+    /* track the largest rotation of each rigid body from its initial orientation */
+    let mut initial: Vec<Update> = Vec::new();
+    let mut rotations: Vec<(i32, f32)> = Vec::new();
+    let deadline = tokio::time::sleep(OBSERVATION_WINDOW);
+    tokio::pin!(deadline);
+    loop {
+        tokio::select! {
+            _ = &mut deadline => break,
+            frame = frames.recv() => match frame {
+                Ok(frame) => for update in frame {
+                    match initial.iter().find(|body| body.id == update.id) {
+                        Some(body) => {
+                            let angle = angle_between(&body.orientation, &update.orientation);
+                            match rotations.iter_mut().find(|(id, _)| *id == update.id) {
+                                Some((_, rotation)) => *rotation = rotation.max(angle),
+                                None => rotations.push((update.id, angle)),
+                            }
+                        },
+                        None => initial.push(update),
+                    }
+                },
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => anyhow::bail!("Tracking system is not available"),
+            }
+        }
+    }
+    /* only propose a rigid body if exactly one body rotated significantly */
+    let mut spinning = rotations.into_iter()
+        .filter(|(_, rotation)| *rotation > ROTATION_THRESHOLD);
+    match (spinning.next(), spinning.next()) {
+        (Some((id, _)), None) => Ok(Some(id)),
+        _ => Ok(None),
+    }
+}
+
+/* identify the rigid body of each robot by spinning the robots one at a time */
+pub async fn rigid_bodies(
+    robots: Vec<(String, Robot)>,
+    tracking_tx: mpsc::Sender<tracking::Action>
+) -> anyhow::Result<Vec<(String, Option<i32>)>> {
+    let mut proposals = Vec::new();
+    for (id, robot) in robots {
+        log::info!("Identifying the rigid body of {}", id);
+        let proposal = match identify(&robot, &tracking_tx).await {
+            Ok(proposal) => proposal,
+            Err(error) => {
+                log::warn!("Could not identify the rigid body of {}: {}", id, error);
+                None
+            }
+        };
+        proposals.push((id, proposal));
+    }
+    Ok(proposals)
+}
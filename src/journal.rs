@@ -1,32 +1,116 @@
 use anyhow::{Result, Context};
+use ansi_parser::{AnsiParser, Output};
 use futures::{Stream, StreamExt, TryFutureExt, TryStreamExt};
 use shared::{builderbot, drone, pipuck};
 use tokio_stream::wrappers::BroadcastStream;
 use tokio_stream::wrappers::errors::BroadcastStreamRecvError;
 use std::net::SocketAddr;
+use std::sync::Arc;
+use uuid::Uuid;
 use std::fs::File;
-use std::io::BufWriter;
+use std::io::{self, BufWriter, Write};
+use std::path::PathBuf;
 use bytes::BytesMut;
 use serde::Serialize;
-use tokio::sync::{mpsc, oneshot};
+use tokio::{io::AsyncWriteExt, net::TcpStream, sync::{mpsc, oneshot}};
 use chrono::{DateTime, Local};
-use shared::tracking_system;
+use shared::{event::Severity, tracking_system};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
 
 
-use crate::{optitrack, router};
+use crate::{arena, archive, export, router, tracking};
+
+/* the number of entries that can be waiting to be sent to the sink */
+const SINK_CAPACITY: usize = 1024;
 
 pub enum Action {
     Start(oneshot::Sender<anyhow::Result<()>>),
     Stop,
+    /* stop recording and flush the journal, the callback is called once this has completed */
+    Close(oneshot::Sender<()>),
     Record(Event),
+    /* the number of entries written to journals since the supervisor started */
+    GetEntriesWritten(oneshot::Sender<u64>),
+    /* check that a new journal can be started */
+    CheckAvailable(oneshot::Sender<anyhow::Result<()>>),
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum Compression {
+    Gzip,
+    Zstd,
+}
+
+#[derive(Debug, Default)]
+pub struct Configuration {
+    /* a server to which the entries are streamed in addition to the local file */
+    pub sink: Option<SocketAddr>,
+    /* compress the local file while it is being written */
+    pub compression: Option<Compression>,
+    /* prepended to the file names of the journals, e.g., the name of the arena */
+    pub prefix: Option<String>,
+    /* the maximum number of frames from the tracking system that are recorded per second, every
+       frame is recorded by default */
+    pub rate: Option<f64>,
+}
+
+/* the local file of the journal, the compressed formats must be finished to write their trailers */
+enum Writer {
+    Plain(BufWriter<File>),
+    Gzip(flate2::write::GzEncoder<BufWriter<File>>),
+    Zstd(zstd::Encoder<'static, BufWriter<File>>),
+}
+
+impl Writer {
+    fn new(file: File, compression: Option<Compression>) -> io::Result<Self> {
+        let writer = BufWriter::new(file);
+        Ok(match compression {
+            None => Writer::Plain(writer),
+            Some(Compression::Gzip) =>
+                Writer::Gzip(flate2::write::GzEncoder::new(writer, flate2::Compression::default())),
+            Some(Compression::Zstd) => Writer::Zstd(zstd::Encoder::new(writer, 0)?),
+        })
+    }
+
+    fn finish(self) -> io::Result<()> {
+        match self {
+            Writer::Plain(mut writer) => writer.flush(),
+            Writer::Gzip(encoder) => encoder.finish()?.flush(),
+            Writer::Zstd(encoder) => encoder.finish()?.flush(),
+        }
+    }
+}
+
+impl Write for Writer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Writer::Plain(writer) => writer.write(buf),
+            Writer::Gzip(encoder) => encoder.write(buf),
+            Writer::Zstd(encoder) => encoder.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Writer::Plain(writer) => writer.flush(),
+            Writer::Gzip(encoder) => encoder.flush(),
+            Writer::Zstd(encoder) => encoder.flush(),
+        }
+    }
 }
 
 #[derive(Debug, Serialize)]
 pub enum Event {
     ARGoS(String, ARGoS),
-    Message(SocketAddr, crate::router::LuaType),
+    /* the message, the socket of its sender, and the run of the experiment during which it was forwarded */
+    Message(SocketAddr, Arc<crate::router::LuaType>, Uuid),
     TrackingSystem(Vec<tracking_system::Update>),
-    Descriptors(Vec<builderbot::Descriptor>, Vec<drone::Descriptor>, Vec<pipuck::Descriptor>, )
+    Descriptors(Vec<builderbot::Descriptor>, Vec<drone::Descriptor>, Vec<pipuck::Descriptor>, ),
+    Arena(shared::event::Event),
+    /* the robot and the estimated offset of its clock and the round-trip time in seconds */
+    ClockOffset(String, f64, f64),
+    Audit(shared::audit::Entry),
 }
 
 #[derive(Debug, Serialize)]
@@ -35,6 +119,82 @@ pub enum ARGoS {
     StandardError(BytesMut),
 }
 
+/* the severity of a line of output from ARGoS, e.g., "[FATAL] ..." or an exception from Lua */
+fn argos_severity(line: &str) -> Option<Severity> {
+    if line.contains("[FATAL]") || line.contains("[ERROR]") || line.to_lowercase().contains("exception") {
+        Some(Severity::Error)
+    }
+    else if line.contains("[WARNING]") {
+        Some(Severity::Warning)
+    }
+    else {
+        None
+    }
+}
+
+/* checks the output of ARGoS for errors and warnings and reports them as arena events so that, e.g., a
+   controller that crashes on one robot is noticed during the experiment */
+#[derive(Default)]
+struct ARGoSMonitor {
+    /* incomplete lines of output, indexed by robot and stream */
+    partial_lines: HashMap<(String, bool), Vec<u8>>,
+    /* the last line that was reported for each robot, repeated lines are not reported again */
+    reported: HashMap<String, String>,
+}
+
+impl ARGoSMonitor {
+    fn check(&mut self, arena_tx: &mpsc::Sender<arena::Action>, robot: &str, output: &ARGoS) {
+        let (stderr, chunk) = match output {
+            ARGoS::StandardOutput(chunk) => (false, chunk),
+            ARGoS::StandardError(chunk) => (true, chunk),
+        };
+        let buffer = self.partial_lines.entry((robot.to_owned(), stderr)).or_default();
+        for line in export::split_lines(buffer, chunk) {
+            /* ARGoS colors its output using escape sequences */
+            let line = line.ansi_parse()
+                .fold(String::new(), |line, item| match item {
+                    Output::TextBlock(text) => line + text,
+                    Output::Escape(_) => line,
+                });
+            let line = line.trim();
+            if let Some(severity) = argos_severity(line) {
+                if self.reported.get(robot).is_some_and(|reported| reported == line) {
+                    continue;
+                }
+                self.reported.insert(robot.to_owned(), line.to_owned());
+                let action = arena::Action::ReportEvent {
+                    severity,
+                    robot: Some(robot.to_owned()),
+                    subsystem: String::from("argos"),
+                    message: format!("{}: {}", robot, line),
+                };
+                /* the arena also sends actions to the journal, do not wait to avoid a deadlock */
+                if arena_tx.try_send(action).is_err() {
+                    log::warn!("Could not report output from ARGoS on {} to arena", robot);
+                }
+            }
+        }
+    }
+}
+
+/* report a failure to write the journal, e.g., because the disk is full, as an arena event */
+fn report_write_failure(arena_tx: &mpsc::Sender<arena::Action>, reported: &mut bool, error: &anyhow::Error) {
+    if *reported {
+        return;
+    }
+    *reported = true;
+    let action = arena::Action::ReportEvent {
+        severity: Severity::Error,
+        robot: None,
+        subsystem: String::from("journal"),
+        message: format!("Could not write to journal: {}", error),
+    };
+    /* the arena also sends actions to the journal, do not wait to avoid a deadlock */
+    if arena_tx.try_send(action).is_err() {
+        log::warn!("Could not report journal failure to arena");
+    }
+}
+
 #[derive(Debug, Serialize)]
 struct Entry {
     timestamp: i64,
@@ -52,19 +212,28 @@ struct Entry {
 
 // the design flaw is most certainly the arena actor -- there is actually little that this actor does
 // other than create an additional layer of complexity
-pub async fn new(mut requests_rx: mpsc::Receiver<Action>,
-                 optitrack_tx: mpsc::Sender<optitrack::Action>,
-                 router_tx: mpsc::Sender<router::Action>) -> Result<()> {
+pub async fn new(config: Configuration,
+                 mut requests_rx: mpsc::Receiver<Action>,
+                 tracking_tx: mpsc::Sender<tracking::Action>,
+                 router_tx: mpsc::Sender<router::Action>,
+                 archive_tx: mpsc::Sender<archive::Action>,
+                 arena_tx: mpsc::Sender<arena::Action>) -> Result<()> {
     
-    let optitrack_stream = futures::stream::pending().left_stream();
-    tokio::pin!(optitrack_stream);
+    let tracking_stream = futures::stream::pending().left_stream();
+    tokio::pin!(tracking_stream);
     let router_stream = futures::stream::pending().left_stream();
     tokio::pin!(router_stream);
-    let mut journal: Option<(DateTime<Local>, BufWriter<_>)> = None;
+    let mut journal: Option<(DateTime<Local>, Writer)> = None;
+    let mut journal_path: Option<PathBuf> = None;
+    let mut entries_written: u64 = 0;
+    let mut sink_tx: Option<mpsc::Sender<Vec<u8>>> = None;
+    let mut argos_monitor = ARGoSMonitor::default();
+    /* only the first failure to write each journal is reported to the arena */
+    let mut write_failure_reported = false;
 
     loop {
         tokio::select! {
-            Some(update) = optitrack_stream.next() => match update {
+            Some(update) = tracking_stream.next() => match update {
                 Ok(event) => {
                     let (start, writer) = journal.as_mut().unwrap();
                     let entry = Entry {
@@ -73,12 +242,17 @@ pub async fn new(mut requests_rx: mpsc::Receiver<Action>,
                             .num_milliseconds(),
                         event
                     };
-                    if let Err(error) = serde_pickle::ser::to_writer(writer, &entry, true) {
-                        log::error!("Error writing entry {:?} to journal: {}", entry, error);
+                    match write_entry(writer, &mut sink_tx, &entry) {
+                        Ok(_) => entries_written += 1,
+                        Err(error) => {
+                            log::error!("Error writing entry {:?} to journal: {}", entry, error);
+                            report_write_failure(&arena_tx, &mut write_failure_reported, &error);
+                        },
                     }
                 }
-                Err(error) => {
-                    log::error!("Error writing entries to journal: {}", error);
+                /* the missed frames cannot be recovered, the gap is recorded in the log instead */
+                Err(BroadcastStreamRecvError::Lagged(count)) => {
+                    log::warn!("Journal missed {} tracking system updates", count);
                 }
             },
             Some(update) = router_stream.next() => match update {
@@ -89,12 +263,16 @@ pub async fn new(mut requests_rx: mpsc::Receiver<Action>,
                             .num_milliseconds(),
                         event
                     };
-                    if let Err(error) = serde_pickle::ser::to_writer(writer, &entry, true) {
-                        log::error!("Error writing entry {:?} to journal: {}", entry, error);
+                    match write_entry(writer, &mut sink_tx, &entry) {
+                        Ok(_) => entries_written += 1,
+                        Err(error) => {
+                            log::error!("Error writing entry {:?} to journal: {}", entry, error);
+                            report_write_failure(&arena_tx, &mut write_failure_reported, &error);
+                        },
                     }
                 }
-                Err(error) => {
-                    log::error!("Error writing entries to journal: {}", error);
+                Err(BroadcastStreamRecvError::Lagged(count)) => {
+                    log::warn!("Journal missed {} router messages", count);
                 }
             },
             request = requests_rx.recv() => match request {
@@ -102,16 +280,43 @@ pub async fn new(mut requests_rx: mpsc::Receiver<Action>,
                 Some(action) => match action {
                     Action::Start(callback) => {
                         let now = Local::now();
-                        let log_filename = now.format("%Y%m%d-%H%M%S.pkl").to_string();
-                        let file_result = File::create(log_filename)
+                        let extension = match config.compression {
+                            None => "pkl",
+                            Some(Compression::Gzip) => "pkl.gz",
+                            Some(Compression::Zstd) => "pkl.zst",
+                        };
+                        let log_filename = match &config.prefix {
+                            Some(prefix) => format!("{}-{}.{}", prefix, now.format("%Y%m%d-%H%M%S"), extension),
+                            None => format!("{}.{}", now.format("%Y%m%d-%H%M%S"), extension),
+                        };
+                        let file_result = File::create(&log_filename)
+                            .and_then(|file| Writer::new(file, config.compression))
                             .context("Could not create file for journal");
-                        let router_result = router(&router_tx).await;
-                        let optitrack_result = optitrack(&optitrack_tx).await;
-                        match (file_result, router_result, optitrack_result) {
-                            (Ok(file), Ok(router), Ok(optitrack)) => {
-                                journal = Some((now, BufWriter::new(file)));
+                        /* the messages of each run are tagged so that they can be told apart once the
+                           journals of several experiments have been collected, e.g., by a sink */
+                        let run = Uuid::new_v4();
+                        let router_result = router(&router_tx, run).await;
+                        let tracking_result = tracking(&tracking_tx, config.rate).await;
+                        match (file_result, router_result, tracking_result) {
+                            (Ok(writer), Ok(router), Ok(tracking)) => {
+                                journal = Some((now, writer));
+                                argos_monitor = ARGoSMonitor::default();
+                                write_failure_reported = false;
+                                /* the sink runs in the background so that a slow connection does not delay the journal */
+                                if let Some(addr) = config.sink {
+                                    let (tx, rx) = mpsc::channel(SINK_CAPACITY);
+                                    sink_tx = Some(tx);
+                                    let name = log_filename.clone();
+                                    tokio::spawn(async move {
+                                        if let Err(error) = sink(addr, name, rx).await {
+                                            log::error!("Journal sink {} failed: {}", addr, error);
+                                        }
+                                    });
+                                }
+                                log::info!("Recording run {} in {}", run, log_filename);
+                                journal_path = Some(PathBuf::from(log_filename));
                                 router_stream.set(router.right_stream());
-                                optitrack_stream.set(optitrack.right_stream());
+                                tracking_stream.set(tracking.right_stream());
                                 let _ = callback.send(Ok(()));
                             },
                             (Err(error), _, _) | (_, Err(error), _) | (_, _, Err(error)) => {
@@ -119,22 +324,62 @@ pub async fn new(mut requests_rx: mpsc::Receiver<Action>,
                             }
                         }
                     },
-                    Action::Stop => {
-                        optitrack_stream.set(futures::stream::pending().left_stream());
+                    action @ (Action::Stop | Action::Close(_)) => {
+                        tracking_stream.set(futures::stream::pending().left_stream());
                         router_stream.set(futures::stream::pending().left_stream());
-                        journal = None;
+                        /* the sink sends the remaining entries and then closes its connection */
+                        sink_tx = None;
+                        if let Some((_, writer)) = journal.take() {
+                            if let Err(error) = writer.finish() {
+                                log::error!("Could not flush journal: {}", error);
+                            }
+                        }
+                        /* archive the journal now that the experiment has finished */
+                        if let Some(path) = journal_path.take() {
+                            if archive_tx.send(archive::Action::Archive(path)).await.is_err() {
+                                log::error!("Could not communicate with archive");
+                            }
+                        }
+                        if let Action::Close(callback) = action {
+                            let _ = callback.send(());
+                        }
                     },
+                    /* events are only recorded while a journal is open */
                     Action::Record(event) => {
-                        let (start, writer) = journal.as_mut().unwrap();
-                        let entry = Entry {
-                            timestamp: Local::now()
-                                .signed_duration_since(*start)
-                                .num_milliseconds(),
-                            event
-                        };
-                        if let Err(error) = serde_pickle::ser::to_writer(writer, &entry, true) {
-                            log::error!("Error writing entry {:?} to journal: {}", entry, error);
+                        if let Event::ARGoS(robot, output) = &event {
+                            argos_monitor.check(&arena_tx, robot, output);
                         }
+                        if let Some((start, writer)) = journal.as_mut() {
+                            let entry = Entry {
+                                timestamp: Local::now()
+                                    .signed_duration_since(*start)
+                                    .num_milliseconds(),
+                                event
+                            };
+                            match write_entry(writer, &mut sink_tx, &entry) {
+                                Ok(_) => entries_written += 1,
+                                Err(error) => {
+                                    log::error!("Error writing entry {:?} to journal: {}", entry, error);
+                                    report_write_failure(&arena_tx, &mut write_failure_reported, &error);
+                                },
+                            }
+                        }
+                    },
+                    Action::GetEntriesWritten(callback) => {
+                        let _ = callback.send(entries_written);
+                    },
+                    Action::CheckAvailable(callback) => {
+                        let result = match journal {
+                            Some(_) => Err(anyhow::anyhow!("A journal is already being recorded")),
+                            None => std::env::current_dir()
+                                .and_then(|directory| directory.metadata())
+                                .context("Could not access the journal directory")
+                                .and_then(|metadata| match metadata.permissions().readonly() {
+                                    true => Err(anyhow::anyhow!("The journal directory is read only")),
+                                    false => Ok(()),
+                                }),
+                        };
+                        let _ = callback.send(result);
                     }
                 }
             }
@@ -143,8 +388,46 @@ pub async fn new(mut requests_rx: mpsc::Receiver<Action>,
     Ok(())
 }
 
+/* write an entry to the journal and, if there is one, to the sink */
+fn write_entry(
+    writer: &mut Writer,
+    sink_tx: &mut Option<mpsc::Sender<Vec<u8>>>,
+    entry: &Entry
+) -> Result<()> {
+    let encoded = serde_pickle::ser::to_vec(entry, true)?;
+    writer.write_all(&encoded)?;
+    if let Some(tx) = sink_tx.as_ref() {
+        /* entries are dropped rather than blocking the journal, since each entry is a separate
+           pickle, the entries that do arrive can still be read */
+        match tx.try_send(encoded) {
+            Ok(_) => {},
+            Err(mpsc::error::TrySendError::Full(_)) =>
+                log::warn!("Journal sink is not keeping up, dropped entry"),
+            Err(mpsc::error::TrySendError::Closed(_)) => {
+                log::warn!("Journal sink disconnected, entries are only written to the local file");
+                *sink_tx = None;
+            }
+        }
+    }
+    Ok(())
+}
+
+/* stream the entries of a journal to a server, the name of the journal is sent on the first line
+   followed by the entries as they are written to the local file */
+async fn sink(addr: SocketAddr, name: String, mut entries_rx: mpsc::Receiver<Vec<u8>>) -> Result<()> {
+    let mut stream = TcpStream::connect(addr).await
+        .context("Could not connect")?;
+    stream.write_all(format!("{}\n", name).as_bytes()).await?;
+    while let Some(entry) = entries_rx.recv().await {
+        stream.write_all(&entry).await?;
+    }
+    stream.shutdown().await?;
+    Ok(())
+}
+
 async fn router(
-    router_tx: &mpsc::Sender<router::Action>
+    router_tx: &mpsc::Sender<router::Action>,
+    run: Uuid,
 ) -> anyhow::Result<impl Stream<Item = Result<Event, BroadcastStreamRecvError>>> {
     let (callback_tx, callback_rx) = oneshot::channel();
     let router_updates = router_tx.send(router::Action::Subscribe(callback_tx))
@@ -153,19 +436,33 @@ async fn router(
             .map_err(|_| anyhow::anyhow!("Could not subscribe to router updates")));
     router_updates.await
         .map(|updates| BroadcastStream::new(updates)
-            .map_ok(|(socket, message)| Event::Message(socket, message)))
+            .map_ok(move |(socket, message)| Event::Message(socket, message, run)))
 }
 
-async fn optitrack(
-    optitrack_tx: &mpsc::Sender<optitrack::Action>
+async fn tracking(
+    tracking_tx: &mpsc::Sender<tracking::Action>,
+    rate: Option<f64>,
 ) -> anyhow::Result<impl Stream<Item = Result<Event, BroadcastStreamRecvError>>> {
+    let interval = rate.map(|rate| Duration::from_secs_f64(1.0 / rate));
+    /* when the last frame was recorded */
+    let mut recorded: Option<Instant> = None;
     let (callback_tx, callback_rx) = oneshot::channel();
-    let optitrack_updates = optitrack_tx.send(optitrack::Action::Subscribe(callback_tx))
+    let tracking_updates = tracking_tx.send(tracking::Action::Subscribe(callback_tx))
         .map_err(|_| anyhow::anyhow!("Could not subscribe to tracking system updates"))
         .and_then(move |_| callback_rx
             .map_err(|_| anyhow::anyhow!("Could not subscribe to tracking system updates")));
-    optitrack_updates.await
+    tracking_updates.await
         .map(|updates| BroadcastStream::new(updates)
+            .filter(move |update| {
+                let record = match (update, interval, recorded) {
+                    (Ok(_), Some(interval), Some(last)) => last.elapsed() >= interval,
+                    _ => true,
+                };
+                if record && update.is_ok() {
+                    recorded = Some(Instant::now());
+                }
+                futures::future::ready(record)
+            })
             .map_ok(Event::TrackingSystem))
 }
 
@@ -0,0 +1,37 @@
+use futures::StreamExt;
+use shared::event::Severity;
+use tokio::sync::mpsc;
+
+use crate::{arena, webui};
+
+async fn report(arena_tx: &mpsc::Sender<arena::Action>, robot: String, message: String) -> anyhow::Result<()> {
+    let action = arena::Action::ReportEvent {
+        severity: Severity::Warning,
+        robot: Some(robot),
+        subsystem: String::from("kernel"),
+        message,
+    };
+    arena_tx.send(action).await
+        .map_err(|_| anyhow::anyhow!("Could not communicate with arena"))
+}
+
+/// This function watches the faults that the drones find in the kernel messages of their Up Cores,
+/// e.g., an oops, a kernel panic, or the out-of-memory killer, and reports each of them as a warning
+/// of the arena, which is also recorded in the journal, so that a misbehaving Up Core is noticed
+/// before its drone is flown.
+pub async fn new(
+    arena_tx: mpsc::Sender<arena::Action>,
+) -> anyhow::Result<()> {
+    let mut drone_updates = webui::subscribe_drone_updates(&arena_tx).await?;
+    if drone_updates.is_empty() {
+        /* there are no drones to watch, never complete */
+        return futures::future::pending().await;
+    }
+    while let Some((desc, update)) = drone_updates.next().await {
+        if let Ok(shared::drone::Update::KernelFault(line)) = update {
+            let message = format!("Kernel fault on the Up Core of {}: {}", desc.id, line);
+            report(&arena_tx, desc.id.clone(), message).await?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,60 @@
+use shared::logging::{Level, Record};
+use tokio::sync::broadcast;
+
+/* the number of records that a client of the web interface can fall behind before it misses records */
+const RECORDS_CAPACITY: usize = 256;
+
+/* writes the log to the terminal like env_logger and also broadcasts each record that is written, so
+   that the log can be shown in the web interface */
+pub struct Logger {
+    inner: env_logger::Logger,
+    records_tx: broadcast::Sender<Record>,
+}
+
+/* install the logger and return the sender of the records, which can be subscribed to */
+pub fn init(inner: env_logger::Logger) -> broadcast::Sender<Record> {
+    let (records_tx, _) = broadcast::channel(RECORDS_CAPACITY);
+    log::set_max_level(inner.filter());
+    let logger = Logger { inner, records_tx: records_tx.clone() };
+    if log::set_boxed_logger(Box::new(logger)).is_err() {
+        eprintln!("Could not install logger, a logger has already been installed");
+    }
+    records_tx
+}
+
+impl log::Log for Logger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        self.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &log::Record) {
+        if !self.inner.matches(record) {
+            return;
+        }
+        self.inner.log(record);
+        /* records are only formatted if a client of the web interface is subscribed */
+        if self.records_tx.receiver_count() > 0 {
+            let level = match record.level() {
+                log::Level::Trace => Level::Trace,
+                log::Level::Debug => Level::Debug,
+                log::Level::Info => Level::Info,
+                log::Level::Warn => Level::Warn,
+                log::Level::Error => Level::Error,
+            };
+            let target = record.target();
+            let subsystem = target.strip_prefix("supervisor::")
+                .unwrap_or(target)
+                .to_owned();
+            let _ = self.records_tx.send(Record {
+                timestamp: chrono::Local::now().format("%Y-%m-%d %H:%M:%S%.3f").to_string(),
+                level,
+                subsystem,
+                message: record.args().to_string(),
+            });
+        }
+    }
+
+    fn flush(&self) {
+        self.inner.flush();
+    }
+}
@@ -1,144 +1,937 @@
-use std::{net::{Ipv4Addr, SocketAddr}, path::{Path, PathBuf}};
+use std::{collections::HashMap, convert::TryFrom, future::Future, net::{Ipv4Addr, SocketAddr}, path::{Path, PathBuf}, time::Duration};
 use ipnet::Ipv4Net;
 use structopt::StructOpt;
 use anyhow::Context;
-use tokio::sync::mpsc;
+use futures::FutureExt;
+use tokio::{signal::unix::{signal, SignalKind}, sync::{broadcast, mpsc, oneshot, watch}};
+use sd_notify::NotifyState;
+use shared::camera;
 
+mod alert;
+mod api;
+mod archive;
+mod asset;
+mod audit;
 mod arena;
+mod argos;
+mod bridge;
+mod clock;
+mod dock;
+mod export;
+mod federation;
+mod health;
+mod identify;
+mod kernel;
 mod robot;
+mod ros;
 mod network;
 mod webui;
-mod optitrack;
+mod timeouts;
+mod tracking;
+mod trajectory;
 mod journal;
+mod logging;
+mod recall;
+mod recorder;
+mod metrics;
+mod mqtt;
 mod router;
+mod script;
+mod separation;
+mod simulation;
+mod subscription;
+mod telemetry;
+mod ticket;
+mod usage;
+mod watchdog;
 
 #[derive(Debug, StructOpt)]
 #[structopt(name = "supervisor", about = "A supervisor for experiments with swarms of robots")]
 struct Options {
     #[structopt(short = "c", long = "configuration")]
-    config: PathBuf,
+    config: Option<PathBuf>,
+    #[structopt(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Debug, StructOpt)]
+enum Command {
+    #[structopt(about = "Convert a journal into one CSV file per type of event")]
+    Export {
+        journal: PathBuf,
+        #[structopt(short = "o", long = "output", default_value = ".",
+                    help = "The directory in which the CSV files are created")]
+        output: PathBuf,
+    },
 }
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    /* initialize the logger */
+    /* initialize the logger, the records that it writes are also shown in the web interface */
     let environment = env_logger::Env::default().default_filter_or("supervisor=info");
-    env_logger::Builder::from_env(environment).format_timestamp_millis().init();
+    let log_records_tx = logging::init(env_logger::Builder::from_env(environment).format_timestamp_millis().build());
     /* parse the configuration file */
     let options = Options::from_args();
+    if let Some(Command::Export { journal, output }) = options.command {
+        return export::journal(&journal, &output)
+            .context(format!("Could not export journal {:?}", journal));
+    }
+    let config_path = options.config
+        .ok_or(anyhow::anyhow!("A configuration file must be provided"))?;
+    let mut config = parse_config(&config_path)
+        .context(format!("Could not parse configuration file {:?}", config_path))?;
+    /* run the supervisor until it quits, restarting it when the configuration is reloaded */
+    let mut open_browser = true;
+    while let Exit::Reload(reloaded) = run(config, &config_path, open_browser, &log_records_tx).await? {
+        config = *reloaded;
+        open_browser = false;
+    }
+    Ok(())
+}
+
+enum Exit {
+    Quit,
+    Reload(Box<Configuration>),
+}
+
+async fn run(
+    config: Configuration,
+    config_path: &Path,
+    open_browser: bool,
+    log_records_tx: &broadcast::Sender<shared::logging::Record>
+) -> anyhow::Result<Exit> {
     let Configuration {
-        optitrack_config,
-        router_socket,
+        bridge_config,
+        ros_config,
+        mqtt_config,
+        alert_config,
+        separation_config,
+        trajectory_config,
+        clock_config,
+        federation_config,
+        archive_config,
+        recording_config,
         webui_socket,
-        robot_network,
-        builderbots,
-        drones,
-        pipucks,
-    } = parse_config(&options.config)
-            .context(format!("Could not parse configuration file {:?}", options.config))?;
-    /* channels for task communication */
-    let (journal_requests_tx, journal_requests_rx) = mpsc::channel(8);
-    let (arena_requests_tx, arena_requests_rx) = mpsc::channel(8);
-    let (optitrack_requests_tx, optitrack_requests_rx) = mpsc::channel(8);
-    let (router_requests_tx, router_requests_rx) = mpsc::channel(8);
-    /* create journal task */
-    let journal_task =
-        journal::new(journal_requests_rx,
-                     optitrack_requests_tx.clone(),
-                     router_requests_tx);
-    /* create arena task */
-    let arena_task =
-        arena::new(arena_requests_rx,
-                   journal_requests_tx,
-                   builderbots,
-                   drones,
-                   pipucks);
-    /* create network task */
-    let network_task = network::new(robot_network, arena_requests_tx.clone());
-    /* create message router task */
-    let router_socket = router_socket
-        .ok_or(anyhow::anyhow!("A socket for the message router must be provided"))?;
-    let router_task = router::new(router_socket, router_requests_rx);
-    /* create optitrack task */
-    let optitrack_config = optitrack_config
-        .ok_or(anyhow::anyhow!("Optitrack configuration must be specified"))?;
-    let optitrack_task = optitrack::new(optitrack_config, optitrack_requests_rx);
+        webui_rate,
+        map_config,
+        tickets_path,
+        usage_path,
+        audit_path,
+        assets_path,
+        rehearsal,
+        shutdown_timeout,
+        watchdog_config,
+        experiment_config,
+        maintenance_config,
+        argos_config,
+        script_config,
+        bandwidth_limits,
+        timeouts_config,
+        capacities,
+        arena_config,
+        additional_arenas,
+    } = config;
+    /* channels for the services that are shared by all arenas */
+    let (ticket_requests_tx, ticket_requests_rx) = mpsc::channel(8);
+    let (usage_requests_tx, usage_requests_rx) = mpsc::channel(8);
+    let (audit_requests_tx, audit_requests_rx) = mpsc::channel(8);
+    let (asset_requests_tx, asset_requests_rx) = mpsc::channel(8);
+    let (archive_requests_tx, archive_requests_rx) = mpsc::channel(8);
+    let (metrics_requests_tx, metrics_requests_rx) = mpsc::channel(8);
+    let (shutdown_tx, shutdown_rx) = watch::channel(false);
+    /* rehearsal mode applies to all arenas and is kept when the web interface is restarted */
+    if rehearsal {
+        log::warn!("Starting in rehearsal mode, destructive actions will not be sent to the robots");
+    }
+    let (rehearsal_tx, rehearsal_rx) = watch::channel(rehearsal);
+    let rehearsal_tx = std::sync::Arc::new(rehearsal_tx);
+    /* the rigid bodies of the primary arena, which are used by the bridges */
+    let bridge_robots = rigid_bodies(&arena_config);
+    /* create the tasks of the arenas, the primary arena is always the first arena */
+    let arena_services = ArenaServices {
+        experiment_config: experiment_config.clone(),
+        maintenance_config: maintenance_config.clone(),
+        separation_config,
+        trajectory_config,
+        capacities,
+        bandwidth_limits: bandwidth_limits.clone(),
+        timeouts_config,
+        archive_tx: archive_requests_tx.clone(),
+        rehearsal_rx: rehearsal_rx.clone(),
+        watchdog_config: watchdog_config.as_ref(),
+    };
+    let (primary_arena, router_requests_tx, journal_requests_tx, arena_task) =
+        create_arena(arena_config, argos_config, arena_services.clone())?;
+    let arena_requests_tx = primary_arena.arena_tx.clone();
+    let tracking_requests_tx = primary_arena.tracking_tx.clone();
+    let mut arenas = vec![primary_arena];
+    let mut journals = vec![journal_requests_tx.clone()];
+    let mut arena_tasks = Vec::new();
+    for config in additional_arenas {
+        let (arena, _, journal_tx, task) = create_arena(config, None, arena_services.clone())?;
+        arenas.push(arena);
+        journals.push(journal_tx);
+        arena_tasks.push(Box::pin(task));
+    }
+    let additional_arenas_task = async move {
+        match arena_tasks.is_empty() {
+            true => futures::future::pending().await,
+            false => futures::future::select_all(arena_tasks).await.0,
+        }
+    };
+    /* create archive task */
+    let archive_task = archive::new(archive_config, archive_requests_rx);
+    /* the following services are global and only use the primary arena, see create_arena */
+    /* create recorder task */
+    let recorder_task = recorder::new(recording_config, arena_requests_tx.clone());
+    /* create metrics task */
+    let metrics_task =
+        metrics::new(metrics_requests_rx,
+                     arena_requests_tx.clone(),
+                     router_requests_tx.clone(),
+                     journal_requests_tx.clone());
+    /* create alert task */
+    let alert_task = alert::new(alert_config, arena_requests_tx.clone());
+    /* create kernel task */
+    let kernel_task = kernel::new(arena_requests_tx.clone());
+    /* create clock task */
+    let clock_task =
+        clock::new(clock_config,
+                   arena_requests_tx.clone(),
+                   router_requests_tx.clone(),
+                   journal_requests_tx.clone());
+    /* create federation task, the members of a federation are added to the arenas */
+    let federation_members = match &federation_config {
+        Some(federation::Configuration::Primary { members, .. }) => members.iter()
+            .map(|name| {
+                let (member, arena) = federation::Member::new(name.clone());
+                arenas.push(arena);
+                member
+            })
+            .collect(),
+        _ => Vec::new(),
+    };
+    let federation_task =
+        federation::new(federation_config,
+                        federation_members,
+                        arena_requests_tx.clone(),
+                        tracking_requests_tx.clone(),
+                        capacities.robots);
+    /* create MQTT task */
+    let mqtt_task = mqtt::new(mqtt_config, arena_requests_tx.clone());
+    /* create bridge task */
+    let bridge_task =
+        bridge::new(bridge_config,
+                    bridge_robots.clone(),
+                    tracking_requests_tx.clone(),
+                    router_requests_tx.clone());
+    /* create ROS bridge task, which publishes the poses of the same rigid bodies as the bridge */
+    let ros_task =
+        ros::new(ros_config,
+                 bridge_robots,
+                 arena_requests_tx.clone(),
+                 tracking_requests_tx.clone());
+    /* create shutdown task, the members of a federation have no journal and are stopped by their own
+       supervisors, they follow the local arenas and are therefore left out by zip */
+    let shutdown_task = shutdown(config_path,
+                                 shutdown_timeout,
+                                 arenas.iter().map(|arena| arena.arena_tx.clone()).zip(journals).collect(),
+                                 shutdown_tx);
+    /* create script task */
+    let script_task =
+        script::new(script_config,
+                    arena_requests_tx.clone(),
+                    router_requests_tx,
+                    tracking_requests_tx.clone());
+    /* create ticket task */
+    let ticket_task = ticket::new(tickets_path, arena_requests_tx.clone(), ticket_requests_rx);
+    /* create usage task */
+    let usage_task = usage::new(usage_path, arena_requests_tx.clone(), usage_requests_rx);
+    /* create audit task */
+    let audit_task = audit::new(audit_path, audit_requests_rx);
+    /* create asset task */
+    let asset_task = asset::new(assets_path, asset_requests_rx);
     /* create the backend task */
     let webui_socket = webui_socket
         .ok_or(anyhow::anyhow!("A socket for the web interface must be provided"))?;
-    let webui_task = webui::new(webui_socket, arena_requests_tx.clone(), optitrack_requests_tx.clone());
-
-    /* listen for the ctrl-c shutdown signal */
-    let sigint_task = tokio::signal::ctrl_c();
+    let webui_task = {
+        let log_records_tx = log_records_tx.clone();
+        watchdog::restartable("WebUI", watchdog_config.as_ref(), move ||
+            webui::new(webui_socket,
+                       map_config.clone(),
+                       Duration::from_secs_f64(1.0 / webui_rate),
+                       arenas.clone(),
+                       webui::Services {
+                           ticket_tx: ticket_requests_tx.clone(),
+                           usage_tx: usage_requests_tx.clone(),
+                           audit_tx: audit_requests_tx.clone(),
+                           asset_tx: asset_requests_tx.clone(),
+                           metrics_tx: metrics_requests_tx.clone(),
+                           log_records_tx: log_records_tx.clone(),
+                           rehearsal_tx: rehearsal_tx.clone(),
+                           rehearsal_rx: rehearsal_rx.clone(),
+                           shutdown_rx: shutdown_rx.clone(),
+                       }))
+    };
     /* pin the futures so that they can be polled via &mut */
     tokio::pin!(arena_task);
-    tokio::pin!(journal_task);
-    tokio::pin!(network_task);
     tokio::pin!(webui_task);
-    tokio::pin!(sigint_task);
-    tokio::pin!(router_task);
-    tokio::pin!(optitrack_task);
+    tokio::pin!(shutdown_task);
+    tokio::pin!(ticket_task);
+    tokio::pin!(usage_task);
+    tokio::pin!(audit_task);
+    tokio::pin!(asset_task);
+    tokio::pin!(bridge_task);
+    tokio::pin!(archive_task);
+    tokio::pin!(metrics_task);
+    tokio::pin!(recorder_task);
+    tokio::pin!(script_task);
+    tokio::pin!(ros_task);
+    tokio::pin!(mqtt_task);
+    tokio::pin!(alert_task);
+    tokio::pin!(kernel_task);
+    tokio::pin!(clock_task);
+    tokio::pin!(federation_task);
+    tokio::pin!(additional_arenas_task);
     /* no point in implementing automatic browser opening */
     /* https://bugzilla.mozilla.org/show_bug.cgi?id=1512438 */
     let server_addr = format!("http://{}/", webui_socket);
-    if let Err(_) = webbrowser::open(&server_addr) {
+    if open_browser && webbrowser::open(&server_addr).is_err() {
         log::warn!("Could not start browser");
         log::info!("Please open this URL manually: {}", server_addr);
     };
-    
+    /* notify systemd (if present) that the supervisor is ready */
+    let _ = sd_notify::notify(false, &[NotifyState::Ready]);
+
     tokio::select! {
-        result = &mut optitrack_task => match result {
-            Ok(_) => log::info!("Optitrack task completed"),
-            Err(error) => log::warn!("Optitrack task aborted: {}", error)
+        /* the core tasks, which include the tasks of the arenas, only complete if they failed and could
+           not be restarted */
+        error = &mut arena_task => log::error!("{:#}", error),
+        error = &mut webui_task => log::error!("{:#}", error),
+        error = &mut additional_arenas_task => log::error!("{:#}", error),
+        result = &mut bridge_task => match result {
+            Ok(_) => log::info!("Bridge task completed"),
+            Err(error) => log::warn!("Bridge task aborted: {}", error)
+        },
+        result = &mut archive_task => match result {
+            Ok(_) => log::info!("Archive task completed"),
+            Err(error) => log::warn!("Archive task aborted: {}", error)
+        },
+        result = &mut metrics_task => match result {
+            Ok(_) => log::info!("Metrics task completed"),
+            Err(error) => log::warn!("Metrics task aborted: {}", error)
+        },
+        result = &mut recorder_task => match result {
+            Ok(_) => log::info!("Recorder task completed"),
+            Err(error) => log::warn!("Recorder task aborted: {}", error)
+        },
+        result = &mut ticket_task => match result {
+            Ok(_) => log::info!("Ticket task completed"),
+            Err(error) => log::warn!("Ticket task aborted: {}", error)
+        },
+        result = &mut usage_task => match result {
+            Ok(_) => log::info!("Usage task completed"),
+            Err(error) => log::warn!("Usage task aborted: {}", error)
+        },
+        result = &mut audit_task => match result {
+            Ok(_) => log::info!("Audit task completed"),
+            Err(error) => log::warn!("Audit task aborted: {}", error)
         },
-        _ = &mut arena_task => log::info!("Arena task completed"),
-        result = &mut journal_task => match result {
-            Ok(_) => log::info!("Journal task completed"),
-            Err(error) => log::warn!("Journal task aborted: {}", error)
+        result = &mut asset_task => match result {
+            Ok(_) => log::info!("Asset task completed"),
+            Err(error) => log::warn!("Asset task aborted: {}", error)
         },
-        _ = &mut network_task => log::info!("Network task completed"),
-        result = &mut router_task => match result {
-            Ok(_) => log::info!("Router task completed"),
-            Err(error) => log::warn!("Router task aborted: {}", error)
+        result = &mut alert_task => match result {
+            Ok(_) => log::info!("Alert task completed"),
+            Err(error) => log::warn!("Alert task aborted: {}", error)
         },
-        _ = &mut webui_task => log::info!("WebUI task completed"),
-        _ = &mut sigint_task => {
-            /* TODO: is it safe to do this? should messages be broadcast to robots */
-            /* what happens if ARGoS is running on the robots, does breaking the
-            connection to fernbedienung kill ARGoS? How does the Pixhawk respond? */
+        result = &mut kernel_task => match result {
+            Ok(_) => log::info!("Kernel task completed"),
+            Err(error) => log::warn!("Kernel task aborted: {}", error)
+        },
+        result = &mut clock_task => match result {
+            Ok(_) => log::info!("Clock task completed"),
+            Err(error) => log::warn!("Clock task aborted: {}", error)
+        },
+        result = &mut federation_task => match result {
+            Ok(_) => log::info!("Federation task completed"),
+            Err(error) => log::warn!("Federation task aborted: {}", error)
+        },
+        result = &mut mqtt_task => match result {
+            Ok(_) => log::info!("MQTT task completed"),
+            Err(error) => log::warn!("MQTT task aborted: {}", error)
+        },
+        result = &mut ros_task => match result {
+            Ok(_) => log::info!("ROS bridge task completed"),
+            Err(error) => log::warn!("ROS bridge task aborted: {}", error)
+        },
+        result = &mut script_task => match result {
+            Ok(_) => log::info!("Script task completed"),
+            Err(error) => log::warn!("Script task aborted: {}", error)
+        },
+        /* the other tasks keep running while the shutdown sequence is in progress */
+        result = &mut shutdown_task => match result {
+            Ok(exit) => {
+                log::info!("Shutdown completed");
+                return Ok(exit);
+            },
+            Err(error) => log::warn!("Forcing shutdown: {}", error)
+        }
+    }
+
+    Ok(Exit::Quit)
+}
+
+/* wait for ctrl-c or SIGTERM (quit) or SIGHUP (reload) and then stop the experiment on all robots
+   of all arenas, which also terminates ARGoS and stops the journals, notify the clients of the web
+   interface, and close the journals */
+async fn shutdown(
+    config_path: &Path,
+    timeout: Duration,
+    arenas: Vec<(mpsc::Sender<arena::Action>, mpsc::Sender<journal::Action>)>,
+    shutdown_tx: watch::Sender<bool>
+) -> anyhow::Result<Exit> {
+    let mut terminate = signal(SignalKind::terminate())
+        .context("Could not listen for the SIGTERM signal")?;
+    let mut hangup = signal(SignalKind::hangup())
+        .context("Could not listen for the SIGHUP signal")?;
+    let exit = loop {
+        tokio::select! {
+            result = tokio::signal::ctrl_c() => {
+                result.context("Could not listen for the ctrl-c signal")?;
+                break Exit::Quit;
+            },
+            _ = terminate.recv() => break Exit::Quit,
+            /* keep running with the current configuration if the new one is invalid */
+            _ = hangup.recv() => match parse_config(config_path) {
+                Ok(config) => break Exit::Reload(Box::new(config)),
+                Err(error) => log::error!("Could not reload configuration file {:?}: {}",
+                    config_path, error),
+            }
+        }
+    };
+    match exit {
+        Exit::Quit => {
             log::info!("Shutting down");
+            let _ = sd_notify::notify(false, &[NotifyState::Stopping]);
+        },
+        Exit::Reload(_) => {
+            log::info!("Reloading configuration file {:?}", config_path);
+            let _ = sd_notify::notify(false, &[NotifyState::Reloading]);
         }
     }
+    let _ = shutdown_tx.send(true);
+    /* the arenas are stopped concurrently */
+    let sequence = futures::future::try_join_all(arenas.iter()
+        .map(|(arena_tx, journal_tx)| stop_arena(arena_tx, journal_tx)));
+    /* a second ctrl-c or SIGTERM signal or the timeout force the supervisor to quit */
+    tokio::select! {
+        result = tokio::time::timeout(timeout, sequence) => result
+            .map_err(|_| anyhow::anyhow!("Timeout after {:?}", timeout))
+            .and_then(|result| result)
+            .map(|_| exit),
+        _ = tokio::signal::ctrl_c() => Err(anyhow::anyhow!("Interrupted")),
+        _ = terminate.recv() => Err(anyhow::anyhow!("Terminated")),
+    }
+}
 
-    Ok(())
+/* stop the experiment on all robots of an arena and close its journal */
+async fn stop_arena(
+    arena_tx: &mpsc::Sender<arena::Action>,
+    journal_tx: &mpsc::Sender<journal::Action>
+) -> anyhow::Result<()> {
+    let (callback_tx, callback_rx) = oneshot::channel();
+    arena_tx.send(arena::Action::StopExperiment { callback: callback_tx }).await
+        .map_err(|_| anyhow::anyhow!("Could not communicate with arena"))?;
+    /* close the journal even if the experiment could not be stopped on all robots */
+    match callback_rx.await {
+        Ok(Ok(_)) => log::info!("Stopped experiment on all robots"),
+        Ok(Err(error)) => log::warn!("{}", error),
+        Err(_) => log::warn!("No response from arena"),
+    }
+    let (callback_tx, callback_rx) = oneshot::channel();
+    journal_tx.send(journal::Action::Close(callback_tx)).await
+        .map_err(|_| anyhow::anyhow!("Could not communicate with journal"))?;
+    callback_rx.await
+        .map_err(|_| anyhow::anyhow!("No response from journal"))
+}
+
+/* the identifier and the rigid body of each robot of an arena that has an optitrack_id */
+fn rigid_bodies(config: &ArenaConfiguration) -> Vec<(String, i32)> {
+    config.builderbots.iter()
+        .filter_map(|desc| desc.optitrack_id.map(|optitrack_id| (desc.id.clone(), optitrack_id)))
+        .chain(config.drones.iter()
+            .filter_map(|desc| desc.optitrack_id.map(|optitrack_id| (desc.id.clone(), optitrack_id))))
+        .chain(config.pipucks.iter()
+            .filter_map(|desc| desc.optitrack_id.map(|optitrack_id| (desc.id.clone(), optitrack_id))))
+        .collect()
+}
+
+/* the settings and services with which each arena is created, the settings are the same for all arenas
+   and the services, i.e., the archive, rehearsal mode, and the watchdog, are shared by all arenas */
+#[derive(Clone)]
+struct ArenaServices<'a> {
+    experiment_config: arena::ExperimentConfiguration,
+    maintenance_config: Option<arena::MaintenanceConfiguration>,
+    separation_config: Option<separation::Configuration>,
+    trajectory_config: trajectory::Configuration,
+    capacities: Capacities,
+    bandwidth_limits: network::throttle::Limits,
+    timeouts_config: timeouts::Configuration,
+    archive_tx: mpsc::Sender<archive::Action>,
+    rehearsal_rx: watch::Receiver<bool>,
+    watchdog_config: Option<&'a watchdog::Configuration>,
+}
+
+/* create the tasks of an arena, i.e., its robots, network, router, tracking system, journal, dock,
+   trajectories, health, telemetry, and separation monitor. The primary arena and the additional
+   arenas are created alike, except that only the primary arena runs ARGoS. The web interface, the
+   archive, the tickets, the usage, the audit trail, the assets, and the configuration of experiments
+   are shared by all arenas, while the other services, i.e., the clock, the bridge, the ROS bridge, the
+   script, the recorder, the alerts, the kernel messages, the metrics, MQTT, and the federation, are
+   global and only use the primary arena. The returned task only completes if one of the tasks of the
+   arena failed */
+fn create_arena<'a>(
+    config: ArenaConfiguration,
+    argos_config: Option<argos::Configuration>,
+    services: ArenaServices<'a>,
+) -> anyhow::Result<(webui::Arena, mpsc::Sender<router::Action>, mpsc::Sender<journal::Action>, impl Future<Output = anyhow::Error> + 'a)> {
+    let rigid_bodies = rigid_bodies(&config);
+    let ArenaServices {
+        experiment_config,
+        maintenance_config,
+        separation_config,
+        trajectory_config,
+        capacities,
+        bandwidth_limits,
+        timeouts_config,
+        archive_tx,
+        rehearsal_rx,
+        watchdog_config,
+    } = services;
+    let ArenaConfiguration {
+        name,
+        tracking_config,
+        router_config,
+        journal_config,
+        robot_network,
+        probe_parallelism,
+        device_cache,
+        simulated,
+        groups,
+        builderbots,
+        drones,
+        pipucks,
+        plugin_robots,
+        dock_config,
+    } = config;
+    /* channels for task communication */
+    let (journal_requests_tx, journal_requests_rx) = mpsc::channel(8);
+    let (arena_requests_tx, arena_requests_rx) = mpsc::channel(8);
+    let (tracking_requests_tx, tracking_requests_rx) = mpsc::channel(8);
+    let (router_requests_tx, router_requests_rx) = mpsc::channel(8);
+    let (argos_requests_tx, argos_requests_rx) = mpsc::channel(8);
+    let (dock_requests_tx, dock_requests_rx) = mpsc::channel(8);
+    let (trajectory_requests_tx, trajectory_requests_rx) = mpsc::channel(8);
+    let (telemetry_requests_tx, telemetry_requests_rx) = mpsc::channel(8);
+    /* create journal task */
+    let journal_task = watchdog::watch("Journal",
+        journal::new(journal_config,
+                     journal_requests_rx,
+                     tracking_requests_tx.clone(),
+                     router_requests_tx.clone(),
+                     archive_tx,
+                     arena_requests_tx.clone()));
+    /* create network task, the devices of the robots are simulated if there is no hardware */
+    let network_task = {
+        let builderbots = builderbots.clone();
+        let drones = drones.clone();
+        let pipucks = pipucks.clone();
+        let arena_requests_tx = arena_requests_tx.clone();
+        watchdog::restartable("Network", watchdog_config, move || match simulated {
+            true => simulation::new(builderbots.clone(), drones.clone(), pipucks.clone(),
+                bandwidth_limits.clone(), arena_requests_tx.clone()).left_future(),
+            false => network::new(robot_network, probe_parallelism, device_cache.clone(),
+                bandwidth_limits.clone(), timeouts_config, arena_requests_tx.clone()).right_future(),
+        })
+    };
+    /* create message router task */
+    let router_config = router_config
+        .ok_or(anyhow::anyhow!("A socket for the message router of arena {} must be provided", name))?;
+    let router_socket = router_config.socket;
+    let router_task = watchdog::watch("Router",
+        router::new(router_config, capacities.router, arena_requests_tx.clone(), router_requests_rx));
+    /* create ARGoS task, the local instance connects to the router over the loopback interface if the
+       router listens on all interfaces. The arena stops ARGoS with every experiment, which does nothing
+       without a configuration */
+    let mut argos_router_socket = router_socket;
+    if argos_router_socket.ip().is_unspecified() {
+        argos_router_socket.set_ip(Ipv4Addr::LOCALHOST.into());
+    }
+    let argos_robots = argos_config.as_ref()
+        .map_or_else(Vec::new, |config| config.robots.clone());
+    let argos_task = watchdog::watch("ARGoS", argos::new(argos_config, argos_router_socket, argos_requests_rx));
+    /* create dock task */
+    let dock_task = watchdog::watch("Dock",
+        dock::new(dock_config, timeouts_config.dock, arena_requests_tx.clone(), dock_requests_rx));
+    /* create trajectory task, which keeps the recent poses of the rigid bodies */
+    let trajectory_task = watchdog::watch("Trajectory",
+        trajectory::new(trajectory_config, rigid_bodies, tracking_requests_tx.clone(), trajectory_requests_rx));
+    /* create health task, which assesses the robots for the arena */
+    let (health_tx, health_rx) = watch::channel(Vec::new());
+    let health_task = watchdog::watch("Health",
+        health::new(arena_requests_tx.clone(), tracking_requests_tx.clone(), health_tx));
+    /* create telemetry task, which keeps the recent signal, battery, and temperature of the robots */
+    let telemetry_task = watchdog::watch("Telemetry",
+        telemetry::new(arena_requests_tx.clone(), telemetry_requests_rx));
+    /* create separation task, the drones of each arena are monitored separately since they are
+       tracked by different systems */
+    let separation_drones = drones.iter()
+        .filter_map(|desc| desc.optitrack_id.map(|optitrack_id| (desc.id.clone(), optitrack_id)))
+        .collect();
+    let separation_task = watchdog::watch("Separation",
+        separation::new(separation_config, separation_drones, arena_requests_tx.clone(), tracking_requests_tx.clone()));
+    /* create arena task */
+    let arena_task = watchdog::watch("Arena",
+        arena::new(arena_requests_rx,
+                   arena::Services {
+                       journal_tx: journal_requests_tx.clone(),
+                       tracking_tx: tracking_requests_tx.clone(),
+                       router_tx: router_requests_tx.clone(),
+                       argos_tx: argos_requests_tx,
+                       dock_tx: dock_requests_tx,
+                       trajectory_tx: trajectory_requests_tx,
+                       telemetry_tx: telemetry_requests_tx,
+                       health_rx,
+                       rehearsal_rx,
+                   },
+                   experiment_config,
+                   maintenance_config,
+                   arena::Robots {
+                       groups,
+                       argos_robots,
+                       update_capacity: capacities.robots,
+                       timeouts: timeouts_config,
+                       builderbots,
+                       drones,
+                       pipucks,
+                       plugin_robots,
+                   }));
+    /* create tracking system task */
+    let tracking_config = tracking_config
+        .ok_or(anyhow::anyhow!("Either an optitrack or a vicon configuration must be specified for arena {}", name))?;
+    let tracking_task = watchdog::watch("Tracking system",
+        tracking::new(tracking_config, capacities.tracking, tracking_requests_rx));
+    let arena = webui::Arena {
+        name: name.clone(),
+        arena_tx: arena_requests_tx,
+        tracking_tx: tracking_requests_tx,
+    };
+    let task = async move {
+        let error = tokio::select! {
+            error = arena_task => error,
+            error = journal_task => error,
+            error = network_task => error,
+            error = router_task => error,
+            error = tracking_task => error,
+            error = argos_task => error,
+            error = dock_task => error,
+            error = trajectory_task => error,
+            error = health_task => error,
+            error = telemetry_task => error,
+            error = separation_task => error,
+        };
+        error.context(format!("Arena {} failed", name))
+    };
+    Ok((arena, router_requests_tx, journal_requests_tx, task))
 }
 
 #[derive(Debug)]
 struct Configuration {
-    optitrack_config: Option<optitrack::Configuration>,
-    router_socket: Option<SocketAddr>,
+    bridge_config: Option<bridge::Configuration>,
+    ros_config: Option<ros::Configuration>,
+    mqtt_config: Option<mqtt::Configuration>,
+    alert_config: Option<alert::Configuration>,
+    separation_config: Option<separation::Configuration>,
+    trajectory_config: trajectory::Configuration,
+    clock_config: Option<clock::Configuration>,
+    federation_config: Option<federation::Configuration>,
+    archive_config: Option<archive::Configuration>,
+    recording_config: Option<recorder::Configuration>,
     webui_socket: Option<SocketAddr>,
+    /* the maximum number of camera frames, signal strengths, and poses sent to a client per second */
+    webui_rate: f64,
+    map_config: webui::MapConfiguration,
+    tickets_path: PathBuf,
+    usage_path: PathBuf,
+    audit_path: PathBuf,
+    assets_path: PathBuf,
+    /* whether the supervisor starts in rehearsal mode */
+    rehearsal: bool,
+    shutdown_timeout: Duration,
+    watchdog_config: Option<watchdog::Configuration>,
+    experiment_config: arena::ExperimentConfiguration,
+    maintenance_config: Option<arena::MaintenanceConfiguration>,
+    argos_config: Option<argos::Configuration>,
+    script_config: Option<script::Configuration>,
+    bandwidth_limits: network::throttle::Limits,
+    timeouts_config: timeouts::Configuration,
+    capacities: Capacities,
+    arena_config: ArenaConfiguration,
+    additional_arenas: Vec<ArenaConfiguration>,
+}
+
+/* the capacities of the broadcast channels, i.e., the number of updates that a subscriber can fall
+   behind before it misses updates */
+#[derive(Clone, Copy, Debug)]
+struct Capacities {
+    robots: usize,
+    router: usize,
+    tracking: usize,
+}
+
+/* the frame rate of a camera if the attribute "frame_rate" is not specified */
+const DEFAULT_CAMERA_FRAME_RATE: u16 = 5;
+
+/* the cameras of each type of robot if they are not specified in the configuration */
+fn default_cameras(robot: &str) -> Vec<camera::Configuration> {
+    let camera = |device: String, width, height, port| camera::Configuration {
+        device, width, height, frame_rate: DEFAULT_CAMERA_FRAME_RATE, port, still_width: width, still_height: height
+    };
+    match robot {
+        "drone" => (0..4)
+            .map(|index| camera(format!("/dev/camera{}", index), 1024, 768, 8000 + index))
+            .collect(),
+        "pipuck" => vec![camera("/dev/camera0".to_owned(), 640, 480, 8000)],
+        _ => Vec::new(),
+    }
+}
+
+/* parse the <camera> nodes inside of a <cameras> node */
+fn parse_cameras(cameras: roxmltree::Node) -> anyhow::Result<Vec<camera::Configuration>> {
+    let cameras = cameras.children()
+        .filter(|node| node.tag_name().name() == "camera")
+        .map(|node| -> anyhow::Result<camera::Configuration> {
+            let attribute = |name: &str| node.attribute(name)
+                .ok_or(anyhow::anyhow!("Could not find attribute \"{}\" in <camera>", name));
+            let parse = |name: &str| attribute(name)?.parse::<u16>()
+                .context(format!("Could not parse attribute \"{}\" in <camera>", name));
+            let frame_rate = match node.attribute("frame_rate") {
+                Some(_) => parse("frame_rate")?,
+                None => DEFAULT_CAMERA_FRAME_RATE,
+            };
+            if frame_rate == 0 {
+                anyhow::bail!("Attribute \"frame_rate\" in <camera> must be greater than zero");
+            }
+            let (width, height) = (parse("width")?, parse("height")?);
+            /* still images are captured at the resolution of the stream unless specified otherwise */
+            let (still_width, still_height) = match (node.attribute("still_width"), node.attribute("still_height")) {
+                (None, None) => (width, height),
+                _ => (parse("still_width")?, parse("still_height")?),
+            };
+            Ok(camera::Configuration {
+                device: attribute("device")?.to_owned(),
+                width,
+                height,
+                frame_rate,
+                port: parse("port")?,
+                still_width,
+                still_height,
+            })
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    /* mjpg-streamer can only stream each device on one port */
+    for (index, camera) in cameras.iter().enumerate() {
+        for other in &cameras[..index] {
+            if camera.device == other.device {
+                anyhow::bail!("Camera {} is specified more than once in <cameras>", camera.device);
+            }
+            if camera.port == other.port {
+                anyhow::bail!("Port {} is used by more than one camera in <cameras>", camera.port);
+            }
+        }
+    }
+    Ok(cameras)
+}
+
+/* pins that are not given keep the wiring of the original drone */
+fn parse_xbee_pins(pins: roxmltree::Node) -> anyhow::Result<shared::drone::XbeePins> {
+    let parse = |value: &str| -> anyhow::Result<u8> {
+        let pin = value.trim().strip_prefix("DIO").unwrap_or(value.trim()).parse::<u8>()
+            .context(format!("Could not parse pin \"{}\" in <xbee_pins>", value))?;
+        /* DIO6 and DIO7 are the flow control pins of the UART */
+        match pin {
+            6 | 7 => Err(anyhow::anyhow!("Pin \"{}\" in <xbee_pins> is reserved for the UART", value)),
+            pin if pin > 12 => Err(anyhow::anyhow!("Pin \"{}\" in <xbee_pins> does not exist", value)),
+            pin => Ok(pin),
+        }
+    };
+    let defaults = shared::drone::XbeePins::default();
+    let pin = |name: &str, default: u8| pins.attribute(name)
+        .map(parse)
+        .unwrap_or(Ok(default))
+        .context(format!("Could not parse attribute \"{}\" in <xbee_pins>", name));
+    let identifier = match pins.attribute("identifier") {
+        Some(identifier) => <[u8; 4]>::try_from(identifier.split_whitespace()
+                .map(parse)
+                .collect::<anyhow::Result<Vec<_>>>()?)
+            .map_err(|_| anyhow::anyhow!("Attribute \"identifier\" in <xbee_pins> must list four pins"))?,
+        None => defaults.identifier,
+    };
+    let xbee_pins = shared::drone::XbeePins {
+        autonomous_mode: pin("autonomous_mode", defaults.autonomous_mode)?,
+        upcore_power: pin("upcore_power", defaults.upcore_power)?,
+        pixhawk_power: pin("pixhawk_power", defaults.pixhawk_power)?,
+        identifier,
+    };
+    let mut assigned = vec![xbee_pins.autonomous_mode, xbee_pins.upcore_power, xbee_pins.pixhawk_power];
+    assigned.extend_from_slice(&xbee_pins.identifier);
+    for (index, pin) in assigned.iter().enumerate() {
+        if assigned[..index].contains(pin) {
+            anyhow::bail!("Pin DIO{} is assigned more than once in <xbee_pins>", pin);
+        }
+    }
+    Ok(xbee_pins)
+}
+
+fn parse_mavlink(mavlink: roxmltree::Node) -> anyhow::Result<shared::drone::MavlinkTransport> {
+    match mavlink.attribute("transport") {
+        Some("xbee") | None => Ok(shared::drone::MavlinkTransport::Xbee),
+        Some("udp") => mavlink.attribute("port")
+            .ok_or(anyhow::anyhow!("Could not find attribute \"port\" in <mavlink>"))?
+            .parse()
+            .map(shared::drone::MavlinkTransport::UpCore)
+            .context("Could not parse attribute \"port\" in <mavlink>"),
+        Some(transport) => Err(anyhow::anyhow!("Unknown transport \"{}\" in <mavlink>", transport)),
+    }
+}
+
+fn parse_pose_forwarding(node: roxmltree::Node) -> anyhow::Result<shared::drone::PoseForwarding> {
+    let message = match node.attribute("message") {
+        Some("vision_position_estimate") | None => shared::drone::PoseMessage::VisionPositionEstimate,
+        Some("att_pos_mocap") => shared::drone::PoseMessage::AttPosMocap,
+        Some(message) => anyhow::bail!("Unknown message \"{}\" in <pose_forwarding>", message),
+    };
+    let rate = node.attribute("rate")
+        .map(|rate| rate.parse::<u16>()
+            .context("Could not parse attribute \"rate\" in <pose_forwarding>"))
+        .unwrap_or(Ok(30))?;
+    if rate == 0 {
+        anyhow::bail!("Attribute \"rate\" in <pose_forwarding> must be greater than zero");
+    }
+    Ok(shared::drone::PoseForwarding { message, rate })
+}
+
+fn parse_identify(node: roxmltree::Node) -> anyhow::Result<shared::drone::Identify> {
+    let identify = shared::drone::Identify {
+        command: node.attribute("command").map(str::to_owned),
+        tune: node.attribute("tune").map(str::to_owned),
+    };
+    if identify.command.as_ref().is_some_and(|command| command.trim().is_empty()) {
+        anyhow::bail!("Attribute \"command\" in <identify> must not be empty");
+    }
+    if let Some(tune) = &identify.tune {
+        /* the tune must fit into a single PLAY_TUNE message */
+        if tune.is_empty() || tune.len() > 30 || !tune.is_ascii() {
+            anyhow::bail!("Attribute \"tune\" in <identify> must have between 1 and 30 ASCII characters");
+        }
+    }
+    Ok(identify)
+}
+
+/* parses an optional attribute, the fields of a profile have different types */
+fn attribute<T>(node: roxmltree::Node, name: &str) -> anyhow::Result<Option<T>>
+    where T: std::str::FromStr, T::Err: std::error::Error + Send + Sync + 'static {
+    node.attribute(name)
+        .map(|value| value.parse::<T>()
+            .context(format!("Could not parse attribute \"{}\" in <{}>", name, node.tag_name().name())))
+        .transpose()
+}
+
+/* the hardware of a drone revision, the cameras default to the cameras of all drones */
+fn parse_profile(
+    profile: roxmltree::Node,
+    default_cameras: &[camera::Configuration],
+) -> anyhow::Result<(shared::drone::Profile, Vec<camera::Configuration>)> {
+    let name = profile.attribute("name")
+        .filter(|name| !name.is_empty())
+        .ok_or(anyhow::anyhow!("Could not find attribute \"name\" in <profile>"))?
+        .to_owned();
+    let child = |name: &str| profile.children().find(|node| node.tag_name().name() == name);
+    let defaults = shared::drone::Profile::default();
+    let battery = match child("battery") {
+        Some(node) => shared::drone::Battery {
+            cells: attribute(node, "cells")?.unwrap_or(defaults.battery.cells),
+            empty_mv: attribute(node, "empty_mv")?.unwrap_or(defaults.battery.empty_mv),
+            full_mv: attribute(node, "full_mv")?.unwrap_or(defaults.battery.full_mv),
+        },
+        None => defaults.battery,
+    };
+    if battery.cells == 0 {
+        anyhow::bail!("Attribute \"cells\" in <battery> must be greater than zero");
+    }
+    if battery.full_mv <= battery.empty_mv {
+        anyhow::bail!("Attribute \"full_mv\" in <battery> must be greater than \"empty_mv\"");
+    }
+    let pixhawk = match child("pixhawk") {
+        Some(node) => shared::drone::Pixhawk {
+            system_id: attribute(node, "system_id")?.unwrap_or(defaults.pixhawk.system_id),
+            component_id: attribute(node, "component_id")?.unwrap_or(defaults.pixhawk.component_id),
+        },
+        None => defaults.pixhawk,
+    };
+    let xbee_pins = child("xbee_pins")
+        .map(parse_xbee_pins)
+        .transpose()?
+        .unwrap_or_default();
+    let mavlink = child("mavlink")
+        .map(parse_mavlink)
+        .transpose()?
+        .unwrap_or_default();
+    let pose_forwarding = child("pose_forwarding")
+        .map(parse_pose_forwarding)
+        .transpose()?;
+    let identify = child("identify")
+        .map(parse_identify)
+        .transpose()?
+        .unwrap_or_default();
+    let cameras = child("cameras")
+        .map(parse_cameras)
+        .unwrap_or_else(|| Ok(default_cameras.to_vec()))?;
+    Ok((shared::drone::Profile { name, battery, xbee_pins, pixhawk, mavlink, pose_forwarding, identify }, cameras))
+}
+
+/* the settings and the robots of an arena, the settings of the primary arena are given by <supervisor>
+   and those of an additional arena by its <arena> node */
+#[derive(Debug)]
+struct ArenaConfiguration {
+    name: String,
+    tracking_config: Option<tracking::Configuration>,
+    router_config: Option<router::Configuration>,
+    journal_config: journal::Configuration,
     robot_network: Ipv4Net,
+    probe_parallelism: usize,
+    device_cache: Option<PathBuf>,
+    simulated: bool,
+    groups: Vec<shared::group::Group>,
     builderbots: Vec<robot::builderbot::Descriptor>,
     drones: Vec<robot::drone::Descriptor>,
     pipucks: Vec<robot::pipuck::Descriptor>,
+    plugin_robots: Vec<Box<dyn robot::plugin::Robot>>,
+    dock_config: Option<dock::Configuration>,
 }
 
-fn parse_config(config: &Path) -> anyhow::Result<Configuration> {
-    let config = std::fs::read_to_string(config)?;
-    let tree = roxmltree::Document::parse(&config)?;
-    let configuration = tree
-        .descendants()
-        .find(|node| node.tag_name().name() == "configuration")
-        .ok_or(anyhow::anyhow!("Could not find node <configuration>"))?;
-    let supervisor = configuration
-        .descendants()
-        .find(|node| node.tag_name().name() == "supervisor")
-        .ok_or(anyhow::anyhow!("Could not find node <supervisor>"))?;
-    let optitrack_config = supervisor
+fn parse_arena(
+    name: &str,
+    primary: bool,
+    settings: roxmltree::Node,
+    robots: roxmltree::Node,
+    argos_robots: &[String]
+) -> anyhow::Result<ArenaConfiguration> {
+    let optitrack_config = settings
         .descendants()
         .find(|node| node.tag_name().name() == "optitrack")
-        .map(|node| -> anyhow::Result<optitrack::Configuration> {
+        .map(|node| -> anyhow::Result<tracking::optitrack::Configuration> {
             let version = node
                 .attribute("version")
                 .ok_or(anyhow::anyhow!("Could not find attribute \"version\" in <optitrack>"))?
@@ -157,45 +950,264 @@ fn parse_config(config: &Path) -> anyhow::Result<Configuration> {
                 .context("Could not parse attribute \"bind_port\" in <optitrack>")?;
             let multicast_addr = node
                 .attribute("multicast_addr")
-                .ok_or(anyhow::anyhow!("Could not find attribute \"multicast_addr\" in <optitrack>"))?
-                .parse::<Ipv4Addr>()
-                .context("Could not parse attribute \"multicast_addr\" in <optitrack>")?;
+                .map(|addr| addr
+                    .parse::<Ipv4Addr>()
+                    .context("Could not parse attribute \"multicast_addr\" in <optitrack>"))
+                .transpose()?;
             let iface_addr = node
                 .attribute("iface_addr")
                 .map(|addr| addr
                     .parse::<Ipv4Addr>()
                     .context("Could not parse attribute \"iface_addr\" in <optitrack>"))
                 .unwrap_or(Ok(Ipv4Addr::UNSPECIFIED))?;
-            Ok(optitrack::Configuration { version, bind_addr, bind_port, multicast_addr, iface_addr })
+            let server_addr = node
+                .attribute("server_addr")
+                .map(|addr| addr
+                    .parse::<Ipv4Addr>()
+                    .context("Could not parse attribute \"server_addr\" in <optitrack>"))
+                .transpose()?;
+            let command_port = node
+                .attribute("command_port")
+                .map(|port| port
+                    .parse::<u16>()
+                    .context("Could not parse attribute \"command_port\" in <optitrack>"))
+                .unwrap_or(Ok(1510))?;
+            if multicast_addr.is_none() && server_addr.is_none() {
+                anyhow::bail!("Either attribute \"multicast_addr\" or \"server_addr\" must be provided in <optitrack>");
+            }
+            Ok(tracking::optitrack::Configuration {
+                version,
+                bind_addr,
+                bind_port,
+                multicast_addr,
+                iface_addr,
+                server_addr,
+                command_port
+            })
         })
         .transpose()?;
-    let webui_socket = supervisor
+    let vicon_config = settings
         .descendants()
-        .find(|node| node.tag_name().name() == "webui")
-        .map(|node| node
-            .attribute("socket")
-            .ok_or(anyhow::anyhow!("Could not find attribute \"socket\" in <webui>"))?
-            .parse::<SocketAddr>()
-            .context("Could not parse attribute \"socket\" in <webui>"))
+        .find(|node| node.tag_name().name() == "vicon")
+        .map(|node| -> anyhow::Result<tracking::vicon::Configuration> {
+            let bind_addr = node
+                .attribute("bind_addr")
+                .map(|addr| addr
+                    .parse::<Ipv4Addr>()
+                    .context("Could not parse attribute \"bind_addr\" in <vicon>"))
+                .unwrap_or(Ok(Ipv4Addr::UNSPECIFIED))?;
+            let bind_port = node
+                .attribute("bind_port")
+                .map(|port| port
+                    .parse::<u16>()
+                    .context("Could not parse attribute \"bind_port\" in <vicon>"))
+                .unwrap_or(Ok(51001))?;
+            let objects = node
+                .children()
+                .filter(|node| node.tag_name().name() == "object")
+                .map(|node| anyhow::Result::<_>::Ok((
+                    node.attribute("name")
+                        .ok_or(anyhow::anyhow!("Could not find attribute \"name\" for <object>"))?
+                        .to_owned(),
+                    node.attribute("id")
+                        .ok_or(anyhow::anyhow!("Could not find attribute \"id\" for <object>"))?
+                        .parse::<i32>()
+                        .context("Could not parse attribute \"id\" for <object>")?
+                )))
+                .collect::<Result<_, _>>()?;
+            Ok(tracking::vicon::Configuration {
+                bind_addr,
+                bind_port,
+                objects,
+            })
+        })
         .transpose()?;
-    let router_socket = supervisor
+    let tracking_config = match (optitrack_config, vicon_config) {
+        (Some(_), Some(_)) => anyhow::bail!("Only one of <optitrack> and <vicon> can be specified"),
+        (Some(config), None) => Some(tracking::Configuration::Optitrack(config)),
+        (None, Some(config)) => Some(tracking::Configuration::Vicon(config)),
+        (None, None) => None,
+    };
+    let router_config = settings
         .descendants()
         .find(|node| node.tag_name().name() == "router")
-        .map(|node| node
-            .attribute("socket")
-            .ok_or(anyhow::anyhow!("Could not find attribute \"socket\" in <router>"))?
-            .parse::<SocketAddr>()
-            .context("Could not parse attribute \"socket\" in <router>"))
+        .map(|node| -> anyhow::Result<router::Configuration> {
+            let socket = node
+                .attribute("socket")
+                .ok_or(anyhow::anyhow!("Could not find attribute \"socket\" in <router>"))?
+                .parse::<SocketAddr>()
+                .context("Could not parse attribute \"socket\" in <router>")?;
+            let tls = match (node.attribute("tls_certificate"), node.attribute("tls_key")) {
+                (Some(certificate), Some(key)) => Some(router::TlsConfiguration {
+                    certificate: PathBuf::from(certificate),
+                    key: PathBuf::from(key),
+                }),
+                (None, None) => None,
+                _ => anyhow::bail!("Attributes \"tls_certificate\" and \"tls_key\" in <router> must be provided together"),
+            };
+            let schemas = node.children()
+                .filter(|node| node.tag_name().name() == "schema")
+                .map(parse_schema)
+                .collect::<anyhow::Result<Vec<_>>>()?;
+            let tokens = node.children()
+                .filter(|node| node.tag_name().name() == "peer")
+                .map(|node| -> anyhow::Result<(String, String)> {
+                    let id = node.attribute("id")
+                        .ok_or(anyhow::anyhow!("Could not find attribute \"id\" in <peer>"))?;
+                    let token = node.attribute("token")
+                        .ok_or(anyhow::anyhow!("Could not find attribute \"token\" of peer {}", id))?;
+                    Ok((id.to_owned(), token.to_owned()))
+                })
+                .collect::<anyhow::Result<HashMap<_, _>>>()?;
+            let unauthenticated = match node.attribute("unauthenticated") {
+                None | Some("reject") => router::Unauthenticated::Reject,
+                Some("quarantine") => router::Unauthenticated::Quarantine,
+                Some(other) => anyhow::bail!("Unknown value \"{}\" of attribute \"unauthenticated\" in <router>", other),
+            };
+            /* peers are only authenticated if tokens have been configured */
+            let authentication = match tokens.is_empty() {
+                true => None,
+                false => Some(router::Authentication { tokens, unauthenticated }),
+            };
+            let capacity = match node.attribute("queue") {
+                Some(capacity) => capacity.parse::<usize>()
+                    .ok()
+                    .filter(|capacity| *capacity > 0)
+                    .ok_or(anyhow::anyhow!("Attribute \"queue\" in <router> must be a positive integer"))?,
+                None => 32,
+            };
+            let overflow = match node.attribute("overflow") {
+                None | Some("wait") => router::queue::Overflow::Wait,
+                Some("drop_oldest") => router::queue::Overflow::DropOldest,
+                Some(other) => anyhow::bail!("Unknown value \"{}\" of attribute \"overflow\" in <router>", other),
+            };
+            /* peers are evicted after 10 seconds by default, zero disables eviction */
+            let evict_after = match node.attribute("evict_after") {
+                Some(evict_after) => evict_after.parse::<f64>()
+                    .ok()
+                    .filter(|evict_after| evict_after.is_finite() && *evict_after >= 0.0)
+                    .ok_or(anyhow::anyhow!("Could not parse attribute \"evict_after\" in <router>"))?,
+                None => 10.0,
+            };
+            let queue = router::QueueConfiguration {
+                capacity,
+                overflow,
+                evict_after: Some(evict_after)
+                    .filter(|evict_after| *evict_after > 0.0)
+                    .map(Duration::from_secs_f64),
+            };
+            /* messages are limited to 1 MiB by default */
+            let max_message_size = match node.attribute("max_message_size") {
+                Some(max_message_size) => max_message_size.parse::<usize>()
+                    .ok()
+                    .filter(|max_message_size| *max_message_size > 0)
+                    .ok_or(anyhow::anyhow!("Attribute \"max_message_size\" in <router> must be a positive integer"))?,
+                None => 1 << 20,
+            };
+            let rate = node.attribute("rate")
+                .map(|rate| rate.parse::<f64>()
+                    .ok()
+                    .filter(|rate| rate.is_finite() && *rate >= 1.0)
+                    .ok_or(anyhow::anyhow!("Attribute \"rate\" in <router> must be at least one message per second")))
+                .transpose()?;
+            let limits = router::Limits { max_message_size, rate };
+            let require_experiment = match node.attribute("require_experiment") {
+                None | Some("false") => false,
+                Some("true") => true,
+                Some(other) => anyhow::bail!("Attribute \"require_experiment\" in <router> must be \"true\" or \"false\", not \"{}\"", other),
+            };
+            Ok(router::Configuration { socket, tls, schemas, authentication, queue, limits, require_experiment })
+        })
         .transpose()?;
-    let robots = configuration
+    let mut journal_config = settings
         .descendants()
-        .find(|node| node.tag_name().name() == "robots")
-        .ok_or(anyhow::anyhow!("Could not find node \"robots\" in <configuration>"))?;
+        .find(|node| node.tag_name().name() == "journal")
+        .map(|node| -> anyhow::Result<journal::Configuration> {
+            let sink = node
+                .attribute("sink")
+                .map(|sink| sink
+                    .parse::<SocketAddr>()
+                    .context("Could not parse attribute \"sink\" in <journal>"))
+                .transpose()?;
+            let compression = match node.attribute("compression") {
+                None | Some("none") => None,
+                Some("gzip") => Some(journal::Compression::Gzip),
+                Some("zstd") => Some(journal::Compression::Zstd),
+                Some(_) => anyhow::bail!("Attribute \"compression\" in <journal> must be one of \"none\", \"gzip\", or \"zstd\""),
+            };
+            let rate = node
+                .attribute("rate")
+                .map(|rate| rate
+                    .parse::<f64>()
+                    .context("Could not parse attribute \"rate\" in <journal>")
+                    .and_then(|rate| match rate > 0.0 {
+                        true => Ok(rate),
+                        false => Err(anyhow::anyhow!("Attribute \"rate\" in <journal> must be greater than zero")),
+                    }))
+                .transpose()?;
+            Ok(journal::Configuration { sink, compression, prefix: None, rate })
+        })
+        .transpose()?
+        .unwrap_or_default();
+    /* the journals of the additional arenas are named after the arena */
+    if !primary {
+        journal_config.prefix = Some(name.to_owned());
+    }
     let robot_network = robots
         .attribute("network")
         .ok_or(anyhow::anyhow!("Could not find attribute \"network\" in <robots>"))?
         .parse::<Ipv4Net>()
         .context("Could not parse attribute \"network\" in <robots>")?;
+    let probe_parallelism = robots
+        .attribute("probes")
+        .map(|probes| probes
+            .parse::<usize>()
+            .context("Could not parse attribute \"probes\" in <robots>"))
+        .unwrap_or(Ok(network::DEFAULT_PROBE_PARALLELISM))?;
+    if probe_parallelism == 0 {
+        anyhow::bail!("Attribute \"probes\" in <robots> must be greater than zero");
+    }
+    /* an empty path disables the device cache */
+    let device_cache = match robots.attribute("cache") {
+        Some("") => None,
+        Some(path) => Some(PathBuf::from(path)),
+        None if primary => Some(PathBuf::from("devices.json")),
+        None => Some(PathBuf::from(format!("devices-{}.json", name))),
+    };
+    let simulated = robots
+        .attribute("simulated")
+        .map(|simulated| simulated
+            .parse::<bool>()
+            .context("Could not parse attribute \"simulated\" in <robots>"))
+        .unwrap_or(Ok(false))?;
+    /* the cameras of each type of robot, these can be overridden for individual robots */
+    let robot_cameras = |robot: &str| robots
+        .children()
+        .find(|node| node.tag_name().name() == "cameras" && node.attribute("robot") == Some(robot))
+        .map(parse_cameras)
+        .unwrap_or_else(|| Ok(default_cameras(robot)))
+        .context(format!("Could not parse cameras for <{}>", robot));
+    let builderbot_cameras = robot_cameras("builderbot")?;
+    let drone_cameras = robot_cameras("drone")?;
+    let pipuck_cameras = robot_cameras("pipuck")?;
+    /* the hardware profiles of the drones, indexed by name */
+    let mut profiles = HashMap::new();
+    for node in robots.children()
+        .filter(|node| node.tag_name().name() == "profiles")
+        .flat_map(|node| node.children())
+        .filter(|node| node.tag_name().name() == "profile") {
+        let (profile, cameras) = parse_profile(node, &drone_cameras)
+            .context("Could not parse <profile>")?;
+        if profiles.contains_key(&profile.name) {
+            anyhow::bail!("Profile {} is defined more than once in <profiles>", profile.name);
+        }
+        profiles.insert(profile.name.clone(), (profile, cameras));
+    }
+    let cameras = |node: roxmltree::Node, default: &Vec<camera::Configuration>| node
+        .children()
+        .find(|node| node.tag_name().name() == "cameras")
+        .map(parse_cameras)
+        .unwrap_or_else(|| Ok(default.clone()));
     let builderbots = robots
         .descendants()
         .filter(|node| node.tag_name().name() == "builderbot")
@@ -215,28 +1227,71 @@ fn parse_config(config: &Path) -> anyhow::Result<Configuration> {
                 .map(|value| value.parse())
                 .transpose()
                 .context("Could not parse attribute \"apriltag_id\" for <builderbot>")?,
+            cameras: cameras(node, &builderbot_cameras)
+                .context("Could not parse cameras for <builderbot>")?,
         }))
         .collect::<Result<Vec<_>, _>>()?;
     let drones = robots
         .descendants()
         .filter(|node| node.tag_name().name() == "drone")
-        .map(|node| anyhow::Result::<_>::Ok(robot::drone::Descriptor {
-            id: node.attribute("id")
-                .ok_or(anyhow::anyhow!("Could not find attribute \"id\" for <drone>"))?
-                .to_owned(),
-            xbee_macaddr: node.attribute("xbee_macaddr")
-                .ok_or(anyhow::anyhow!("Could not find attribute \"xbee_macaddr\" for <drone>"))?
-                .parse()
-                .context("Could not parse attribute \"xbee_macaddr\" for <drone>")?,
-            upcore_macaddr: node.attribute("upcore_macaddr")
-                .ok_or(anyhow::anyhow!("Could not find attribute \"upcore_macaddr\" for <drone>"))?
-                .parse()
-                .context("Could not parse attribute \"upcore_macaddr\" for <drone>")?,                
-            optitrack_id: node.attribute("optitrack_id")
+        .map(|node| {
+            let (mut profile, profile_cameras) = match node.attribute("profile") {
+                Some(name) => profiles.get(name)
+                    .cloned()
+                    .ok_or(anyhow::anyhow!("Could not find profile {} for <drone>", name))?,
+                None => (Default::default(), drone_cameras.clone()),
+            };
+            /* the Xbee pins, MAVLink transport, pose forwarding, and identification that are given for a
+               drone override those of its profile */
+            if let Some(xbee_pins) = node.children().find(|node| node.tag_name().name() == "xbee_pins") {
+                profile.xbee_pins = parse_xbee_pins(xbee_pins)
+                    .context("Could not parse Xbee pins for <drone>")?;
+            }
+            if let Some(mavlink) = node.children().find(|node| node.tag_name().name() == "mavlink") {
+                profile.mavlink = parse_mavlink(mavlink)
+                    .context("Could not parse MAVLink transport for <drone>")?;
+            }
+            if let Some(pose_forwarding) = node.children().find(|node| node.tag_name().name() == "pose_forwarding") {
+                profile.pose_forwarding = Some(parse_pose_forwarding(pose_forwarding)
+                    .context("Could not parse pose forwarding for <drone>")?);
+            }
+            if let Some(identify) = node.children().find(|node| node.tag_name().name() == "identify") {
+                profile.identify = parse_identify(identify)
+                    .context("Could not parse identification for <drone>")?;
+            }
+            let optitrack_id = node.attribute("optitrack_id")
                 .map(|value| value.parse())
                 .transpose()
-                .context("Could not parse attribute \"optitrack_id\" for <drone>")?,
-        }))
+                .context("Could not parse attribute \"optitrack_id\" for <drone>")?;
+            if profile.pose_forwarding.is_some() && optitrack_id.is_none() {
+                anyhow::bail!("Pose forwarding requires attribute \"optitrack_id\" for <drone>");
+            }
+            anyhow::Result::<_>::Ok(robot::drone::Descriptor {
+                id: node.attribute("id")
+                    .ok_or(anyhow::anyhow!("Could not find attribute \"id\" for <drone>"))?
+                    .to_owned(),
+                xbee_macaddr: node.attribute("xbee_macaddr")
+                    .ok_or(anyhow::anyhow!("Could not find attribute \"xbee_macaddr\" for <drone>"))?
+                    .parse()
+                    .context("Could not parse attribute \"xbee_macaddr\" for <drone>")?,
+                upcore_macaddr: node.attribute("upcore_macaddr")
+                    .ok_or(anyhow::anyhow!("Could not find attribute \"upcore_macaddr\" for <drone>"))?
+                    .parse()
+                    .context("Could not parse attribute \"upcore_macaddr\" for <drone>")?,                
+                optitrack_id,
+                xbee_identifier: node.attribute("xbee_identifier")
+                    .map(|value| value.parse::<u8>()
+                        .context("Could not parse attribute \"xbee_identifier\" for <drone>")
+                        .and_then(|identifier| match identifier < 16 {
+                            true => Ok(identifier),
+                            false => Err(anyhow::anyhow!("Attribute \"xbee_identifier\" for <drone> must be less than 16")),
+                        }))
+                    .transpose()?,
+                cameras: cameras(node, &profile_cameras)
+                    .context("Could not parse cameras for <drone>")?,
+                profile,
+            })
+        })
         .collect::<Result<Vec<_>, _>>()?;
     let pipucks = robots
         .descendants()
@@ -257,15 +1312,883 @@ fn parse_config(config: &Path) -> anyhow::Result<Configuration> {
                 .map(|value| value.parse())
                 .transpose()
                 .context("Could not parse attribute \"apriltag_id\" for <pipuck>")?,
+            cameras: cameras(node, &pipuck_cameras)
+                .context("Could not parse cameras for <pipuck>")?,
         }))
         .collect::<Result<Vec<_>, _>>()?;
-    Ok(Configuration { 
-        optitrack_config,
-        router_socket,
-        webui_socket,
+    /* robots of the types that are not built into the supervisor */
+    let plugin_robots = robots
+        .children()
+        .filter_map(|node| robot::plugin::robot_type(node.tag_name().name())
+            .map(|robot_type| robot_type.parse(node)
+                .context(format!("Could not parse <{}>", robot_type.name()))))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    for robot in &plugin_robots {
+        let exists = builderbots.iter().any(|desc| desc.id == robot.id()) ||
+            drones.iter().any(|desc| desc.id == robot.id()) ||
+            pipucks.iter().any(|desc| desc.id == robot.id()) ||
+            plugin_robots.iter().filter(|other| other.id() == robot.id()).count() > 1;
+        if exists {
+            anyhow::bail!("Robot {} is defined more than once", robot.id());
+        }
+    }
+    /* the robots simulated by ARGoS must not have the same identifiers as the real robots */
+    for id in argos_robots {
+        let exists = builderbots.iter().any(|desc| &desc.id == id) ||
+            drones.iter().any(|desc| &desc.id == id) ||
+            pipucks.iter().any(|desc| &desc.id == id) ||
+            plugin_robots.iter().any(|robot| robot.id() == id);
+        if exists {
+            anyhow::bail!("Simulated robot {} has the same identifier as a real robot", id);
+        }
+    }
+    /* groups of robots that can be targeted as a whole */
+    let mut groups: Vec<shared::group::Group> = Vec::new();
+    for node in robots.children().filter(|node| node.tag_name().name() == "group") {
+        let name = node.attribute("name")
+            .ok_or(anyhow::anyhow!("Could not find attribute \"name\" for <group>"))?;
+        if groups.iter().any(|group| group.name == name) {
+            anyhow::bail!("Group {} is defined more than once", name);
+        }
+        let members = node.attribute("members")
+            .ok_or(anyhow::anyhow!("Could not find attribute \"members\" for <group>"))?
+            .split(',')
+            .map(str::trim)
+            .filter(|member| !member.is_empty())
+            .map(String::from)
+            .collect::<Vec<_>>();
+        for member in &members {
+            let exists = builderbots.iter().any(|desc| &desc.id == member) ||
+                drones.iter().any(|desc| &desc.id == member) ||
+                pipucks.iter().any(|desc| &desc.id == member) ||
+                plugin_robots.iter().any(|robot| robot.id() == member) ||
+                argos_robots.contains(member);
+            if !exists {
+                anyhow::bail!("Member {} of group {} is not a robot", member, name);
+            }
+        }
+        groups.push(shared::group::Group { name: name.to_owned(), members });
+    }
+    /* the charging docks of the Pi-Pucks */
+    let dock_config = settings
+        .descendants()
+        .find(|node| node.tag_name().name() == "docks")
+        .map(|node| -> anyhow::Result<dock::Configuration> {
+            let interval = node
+                .attribute("interval")
+                .map(|interval| interval
+                    .parse::<f32>()
+                    .context("Could not parse attribute \"interval\" in <docks>"))
+                .unwrap_or(Ok(5.0))?;
+            if interval.is_nan() || interval <= 0.0 {
+                anyhow::bail!("Attribute \"interval\" in <docks> must be greater than zero");
+            }
+            let mut docks: Vec<dock::Dock> = Vec::new();
+            for node in node.children().filter(|node| node.tag_name().name() == "dock") {
+                let id = node.attribute("id")
+                    .ok_or(anyhow::anyhow!("Could not find attribute \"id\" for <dock>"))?;
+                if docks.iter().any(|dock| dock.id == id) {
+                    anyhow::bail!("Dock {} is defined more than once", id);
+                }
+                let addr = node.attribute("addr")
+                    .ok_or(anyhow::anyhow!("Could not find attribute \"addr\" for <dock>"))?
+                    .parse::<SocketAddr>()
+                    .context("Could not parse attribute \"addr\" for <dock>")?;
+                let protocol = match node.attribute("protocol") {
+                    None | Some("http") => dock::Protocol::Http,
+                    Some("tcp") => dock::Protocol::Tcp,
+                    Some(_) => anyhow::bail!("Attribute \"protocol\" for <dock> must be either \"http\" or \"tcp\""),
+                };
+                let mut slots = HashMap::new();
+                for node in node.children().filter(|node| node.tag_name().name() == "slot") {
+                    let index = node.attribute("index")
+                        .ok_or(anyhow::anyhow!("Could not find attribute \"index\" for <slot>"))?
+                        .parse::<u8>()
+                        .context("Could not parse attribute \"index\" for <slot>")?;
+                    let pipuck = node.attribute("pipuck")
+                        .ok_or(anyhow::anyhow!("Could not find attribute \"pipuck\" for <slot>"))?;
+                    if !pipucks.iter().any(|desc| desc.id == pipuck) {
+                        anyhow::bail!("Slot {} of dock {} is assigned to {}, which is not a Pi-Puck", index, id, pipuck);
+                    }
+                    if docks.iter().flat_map(|dock| dock.slots.values()).chain(slots.values()).any(|slot| slot.pipuck == pipuck) {
+                        anyhow::bail!("Pi-Puck {} is assigned to more than one slot", pipuck);
+                    }
+                    let position = node.attribute("position")
+                        .map(|position| -> anyhow::Result<[f32; 2]> {
+                            let coordinates = position.split(',')
+                                .map(|coordinate| coordinate.trim().parse::<f32>())
+                                .collect::<Result<Vec<_>, _>>()?;
+                            match coordinates.as_slice() {
+                                &[x, y] => Ok([x, y]),
+                                _ => anyhow::bail!("Expected two coordinates"),
+                            }
+                        })
+                        .transpose()
+                        .context("Could not parse attribute \"position\" for <slot>")?;
+                    let slot = dock::Slot { pipuck: pipuck.to_owned(), position };
+                    if slots.insert(index, slot).is_some() {
+                        anyhow::bail!("Slot {} of dock {} is defined more than once", index, id);
+                    }
+                }
+                docks.push(dock::Dock { id: id.to_owned(), addr, protocol, slots });
+            }
+            Ok(dock::Configuration { docks, interval: Duration::from_secs_f32(interval) })
+        })
+        .transpose()?;
+    /* the tracking system is also simulated unless one has been configured */
+    let tracking_config = match (tracking_config, simulated) {
+        (None, true) => Some(tracking::Configuration::Simulated(tracking::simulated::Configuration {
+            ground: builderbots.iter().filter_map(|desc| desc.optitrack_id)
+                .chain(pipucks.iter().filter_map(|desc| desc.optitrack_id))
+                .collect(),
+            aerial: drones.iter().filter_map(|desc| desc.optitrack_id).collect(),
+        })),
+        (tracking_config, _) => tracking_config,
+    };
+    Ok(ArenaConfiguration {
+        name: name.to_owned(),
+        tracking_config,
+        router_config,
+        journal_config,
         robot_network,
+        probe_parallelism,
+        device_cache,
+        simulated,
+        groups,
         builderbots,
-        pipucks,
         drones,
+        pipucks,
+        plugin_robots,
+        dock_config,
+    })
+}
+
+fn parse_schema(node: roxmltree::Node) -> anyhow::Result<shared::router::Schema> {
+    use shared::router::{Field, FieldType};
+    let name = node.attribute("name")
+        .ok_or(anyhow::anyhow!("Could not find attribute \"name\" in <schema>"))?;
+    let fields = node.children()
+        .filter(|node| node.tag_name().name() == "field")
+        .map(|node| -> anyhow::Result<Field> {
+            let key = node.attribute("key")
+                .ok_or(anyhow::anyhow!("Could not find attribute \"key\" in <field> of schema {}", name))?;
+            let field_type = match node.attribute("type") {
+                Some("string") => FieldType::String,
+                Some("number") => FieldType::Number,
+                Some("boolean") => FieldType::Boolean,
+                Some("vector2") => FieldType::Vector2,
+                Some("vector3") => FieldType::Vector3,
+                Some("quaternion") => FieldType::Quaternion,
+                Some("table") => FieldType::Table,
+                Some(other) => anyhow::bail!("Unknown type \"{}\" of field {} in schema {}", other, key, name),
+                None => anyhow::bail!("Could not find attribute \"type\" of field {} in schema {}", key, name),
+            };
+            Ok(Field {
+                key: key.to_owned(),
+                label: node.attribute("label").unwrap_or(key).to_owned(),
+                field_type,
+            })
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    Ok(shared::router::Schema {
+        name: name.to_owned(),
+        from: node.attribute("from").unwrap_or("*").to_owned(),
+        to: node.attribute("to").unwrap_or("*").to_owned(),
+        fields,
+    })
+}
+
+fn parse_config(config: &Path) -> anyhow::Result<Configuration> {
+    /* the metadata of the robots is kept next to the configuration file by default */
+    let default_assets_path = config.with_file_name("assets.json");
+    let config = std::fs::read_to_string(config)?;
+    let tree = roxmltree::Document::parse(&config)?;
+    let configuration = tree
+        .descendants()
+        .find(|node| node.tag_name().name() == "configuration")
+        .ok_or(anyhow::anyhow!("Could not find node <configuration>"))?;
+    let supervisor = configuration
+        .descendants()
+        .find(|node| node.tag_name().name() == "supervisor")
+        .ok_or(anyhow::anyhow!("Could not find node <supervisor>"))?;
+    let webui_socket = supervisor
+        .descendants()
+        .find(|node| node.tag_name().name() == "webui")
+        .map(|node| node
+            .attribute("socket")
+            .ok_or(anyhow::anyhow!("Could not find attribute \"socket\" in <webui>"))?
+            .parse::<SocketAddr>()
+            .context("Could not parse attribute \"socket\" in <webui>"))
+        .transpose()?;
+    /* the camera frames, signal strengths, and poses are sent to each client at 5 Hz by default */
+    let webui_rate = supervisor
+        .descendants()
+        .find(|node| node.tag_name().name() == "webui")
+        .and_then(|node| node.attribute("rate"))
+        .map(|rate| rate
+            .parse::<f64>()
+            .context("Could not parse attribute \"rate\" in <webui>"))
+        .unwrap_or(Ok(5.0))?;
+    if webui_rate.is_nan() || webui_rate <= 0.0 {
+        anyhow::bail!("Attribute \"rate\" in <webui> must be greater than zero");
+    }
+    let bridge_config = supervisor
+        .descendants()
+        .find(|node| node.tag_name().name() == "bridge")
+        .map(|node| -> anyhow::Result<bridge::Configuration> {
+            let rate = node
+                .attribute("rate")
+                .ok_or(anyhow::anyhow!("Could not find attribute \"rate\" in <bridge>"))?
+                .parse::<f64>()
+                .context("Could not parse attribute \"rate\" in <bridge>")?;
+            if rate.is_nan() || rate <= 0.0 {
+                anyhow::bail!("Attribute \"rate\" in <bridge> must be greater than zero");
+            }
+            Ok(bridge::Configuration { rate })
+        })
+        .transpose()?;
+    let ros_config = supervisor
+        .descendants()
+        .find(|node| node.tag_name().name() == "ros")
+        .map(|node| -> anyhow::Result<ros::Configuration> {
+            let url = node
+                .attribute("url")
+                .ok_or(anyhow::anyhow!("Could not find attribute \"url\" in <ros>"))?
+                .to_owned();
+            let prefix = node
+                .attribute("prefix")
+                .unwrap_or("/supervisor")
+                .trim_end_matches('/')
+                .to_owned();
+            let rate = node
+                .attribute("rate")
+                .map(|rate| rate
+                    .parse::<f64>()
+                    .context("Could not parse attribute \"rate\" in <ros>"))
+                .unwrap_or(Ok(10.0))?;
+            if rate.is_nan() || rate <= 0.0 {
+                anyhow::bail!("Attribute \"rate\" in <ros> must be greater than zero");
+            }
+            Ok(ros::Configuration { url, prefix, rate })
+        })
+        .transpose()?;
+    let mqtt_config = supervisor
+        .descendants()
+        .find(|node| node.tag_name().name() == "mqtt")
+        .map(|node| -> anyhow::Result<mqtt::Configuration> {
+            let broker = node
+                .attribute("broker")
+                .ok_or(anyhow::anyhow!("Could not find attribute \"broker\" in <mqtt>"))?;
+            /* the port is optional since most brokers use the default port */
+            let (host, port) = match broker.rsplit_once(':') {
+                Some((host, port)) => (host, port
+                    .parse::<u16>()
+                    .context("Could not parse port of attribute \"broker\" in <mqtt>")?),
+                None => (broker, 1883),
+            };
+            let prefix = node
+                .attribute("prefix")
+                .unwrap_or("supervisor")
+                .trim_end_matches('/')
+                .to_owned();
+            let client_id = node
+                .attribute("client_id")
+                .unwrap_or("supervisor")
+                .to_owned();
+            Ok(mqtt::Configuration { host: host.to_owned(), port, client_id, prefix })
+        })
+        .transpose()?;
+    let separation_config = supervisor
+        .descendants()
+        .find(|node| node.tag_name().name() == "separation")
+        .map(|node| -> anyhow::Result<separation::Configuration> {
+            let distance = node
+                .attribute("distance")
+                .ok_or(anyhow::anyhow!("Could not find attribute \"distance\" in <separation>"))?
+                .parse::<f32>()
+                .context("Could not parse attribute \"distance\" in <separation>")?;
+            if distance.is_nan() || distance <= 0.0 {
+                anyhow::bail!("Attribute \"distance\" in <separation> must be greater than zero");
+            }
+            let stop = node
+                .attribute("stop")
+                .map(|stop| stop
+                    .parse::<bool>()
+                    .context("Could not parse attribute \"stop\" in <separation>"))
+                .unwrap_or(Ok(false))?;
+            Ok(separation::Configuration { distance, stop })
+        })
+        .transpose()?;
+    let trajectory_config = supervisor
+        .descendants()
+        .find(|node| node.tag_name().name() == "trajectories")
+        .map(|node| -> anyhow::Result<trajectory::Configuration> {
+            let default = trajectory::Configuration::default();
+            let length = node
+                .attribute("length")
+                .map(|length| length
+                    .parse::<usize>()
+                    .context("Could not parse attribute \"length\" in <trajectories>"))
+                .unwrap_or(Ok(default.length))?;
+            if length == 0 {
+                anyhow::bail!("Attribute \"length\" in <trajectories> must be greater than zero");
+            }
+            let rate = node
+                .attribute("rate")
+                .map(|rate| rate
+                    .parse::<f64>()
+                    .context("Could not parse attribute \"rate\" in <trajectories>"))
+                .unwrap_or(Ok(default.rate))?;
+            if !(rate > 0.0 && rate.is_finite()) {
+                anyhow::bail!("Attribute \"rate\" in <trajectories> must be greater than zero");
+            }
+            Ok(trajectory::Configuration { length, rate })
+        })
+        .transpose()?
+        .unwrap_or_default();
+    let clock_config = supervisor
+        .descendants()
+        .find(|node| node.tag_name().name() == "clock")
+        .map(|node| -> anyhow::Result<clock::Configuration> {
+            let interval = node
+                .attribute("interval")
+                .map(|interval| interval
+                    .parse::<f64>()
+                    .context("Could not parse attribute \"interval\" in <clock>"))
+                .unwrap_or(Ok(1.0))?;
+            if !(interval > 0.0 && interval.is_finite()) {
+                anyhow::bail!("Attribute \"interval\" in <clock> must be greater than zero");
+            }
+            Ok(clock::Configuration { interval: Duration::from_secs_f64(interval) })
+        })
+        .transpose()?;
+    let alert_config = supervisor
+        .descendants()
+        .find(|node| node.tag_name().name() == "alerts")
+        .map(|node| -> anyhow::Result<alert::Configuration> {
+            use alert::Rule;
+            /* all rules apply unless they are listed explicitly */
+            let rules = match node.attribute("rules") {
+                Some(rules) => rules
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|rule| !rule.is_empty())
+                    .map(|rule| match rule {
+                        "robot_lost" => Ok(Rule::RobotLost),
+                        "battery_critical" => Ok(Rule::BatteryCritical),
+                        "experiment_finished" => Ok(Rule::ExperimentFinished),
+                        "journal_failure" => Ok(Rule::JournalFailure),
+                        _ => Err(anyhow::anyhow!("Unknown rule {} in attribute \"rules\" in <alerts>", rule)),
+                    })
+                    .collect::<anyhow::Result<_>>()?,
+                None => vec![Rule::RobotLost, Rule::BatteryCritical, Rule::ExperimentFinished, Rule::JournalFailure]
+                    .into_iter()
+                    .collect(),
+            };
+            let battery_threshold = node
+                .attribute("battery")
+                .map(|battery| battery
+                    .parse::<i32>()
+                    .context("Could not parse attribute \"battery\" in <alerts>"))
+                .unwrap_or(Ok(20))?;
+            let url = |node: roxmltree::Node| node
+                .attribute("url")
+                .ok_or(anyhow::anyhow!("Could not find attribute \"url\" in <{}>", node.tag_name().name()))?
+                .parse::<reqwest::Url>()
+                .context(format!("Could not parse attribute \"url\" in <{}>", node.tag_name().name()));
+            let mut sinks = Vec::new();
+            for child in node.children().filter(roxmltree::Node::is_element) {
+                let sink = match child.tag_name().name() {
+                    "webhook" => alert::Sink::Webhook(url(child)?),
+                    "slack" => alert::Sink::Slack(url(child)?),
+                    "email" => {
+                        let attribute = |name: &str| child
+                            .attribute(name)
+                            .map(str::to_owned)
+                            .ok_or(anyhow::anyhow!("Could not find attribute \"{}\" in <email>", name));
+                        let credentials = match (child.attribute("username"), child.attribute("password")) {
+                            (Some(username), Some(password)) => Some((username.to_owned(), password.to_owned())),
+                            (None, None) => None,
+                            _ => anyhow::bail!("Attributes \"username\" and \"password\" in <email> must be used together"),
+                        };
+                        alert::Sink::Email {
+                            server: attribute("server")?,
+                            credentials,
+                            from: attribute("from")?,
+                            to: attribute("to")?,
+                        }
+                    },
+                    name => anyhow::bail!("Unknown sink <{}> in <alerts>", name),
+                };
+                sinks.push(sink);
+            }
+            Ok(alert::Configuration { rules, battery_threshold, sinks })
+        })
+        .transpose()?;
+    let archive_config = supervisor
+        .descendants()
+        .find(|node| node.tag_name().name() == "archive")
+        .map(|node| -> anyhow::Result<archive::Configuration> {
+            let destination = match (node.attribute("directory"), node.attribute("url")) {
+                (Some(directory), None) => archive::Destination::Directory(PathBuf::from(directory)),
+                (None, Some(url)) => archive::Destination::WebDav(url
+                    .parse::<reqwest::Url>()
+                    .context("Could not parse attribute \"url\" in <archive>")?),
+                _ => anyhow::bail!("Exactly one of the attributes \"directory\" or \"url\" must be provided in <archive>"),
+            };
+            let retries = node
+                .attribute("retries")
+                .map(|retries| retries
+                    .parse::<u32>()
+                    .context("Could not parse attribute \"retries\" in <archive>"))
+                .unwrap_or(Ok(3))?;
+            let prune = node
+                .attribute("prune")
+                .map(|prune| prune
+                    .parse::<bool>()
+                    .context("Could not parse attribute \"prune\" in <archive>"))
+                .unwrap_or(Ok(false))?;
+            /* the free space is given in megabytes */
+            let min_free = node
+                .attribute("min_free")
+                .map(|min_free| min_free
+                    .parse::<u64>()
+                    .map(|min_free| min_free * 1_000_000)
+                    .context("Could not parse attribute \"min_free\" in <archive>"))
+                .transpose()?;
+            Ok(archive::Configuration { destination, retries, prune, min_free })
+        })
+        .transpose()?;
+    let recording_config = supervisor
+        .descendants()
+        .find(|node| node.tag_name().name() == "recording")
+        .map(|node| node
+            .attribute("directory")
+            .ok_or(anyhow::anyhow!("Could not find attribute \"directory\" in <recording>"))
+            .map(|directory| recorder::Configuration { directory: PathBuf::from(directory) }))
+        .transpose()?;
+    let map_config = supervisor
+        .descendants()
+        .find(|node| node.tag_name().name() == "map")
+        .map(|node| -> anyhow::Result<webui::MapConfiguration> {
+            let rate = node
+                .attribute("rate")
+                .map(|rate| rate
+                    .parse::<f64>()
+                    .context("Could not parse attribute \"rate\" in <map>"))
+                .unwrap_or(Ok(2.0))?;
+            if rate.is_nan() || rate <= 0.0 {
+                anyhow::bail!("Attribute \"rate\" in <map> must be greater than zero");
+            }
+            let bounds = ["x_min", "y_min", "x_max", "y_max"].iter()
+                .map(|attribute| node
+                    .attribute(*attribute)
+                    .map(|value| value
+                        .parse::<f32>()
+                        .context(format!("Could not parse attribute \"{}\" in <map>", attribute)))
+                    .transpose())
+                .collect::<anyhow::Result<Vec<_>>>()?;
+            let bounds = match bounds[..] {
+                [Some(x_min), Some(y_min), Some(x_max), Some(y_max)] =>
+                    Some(shared::map::Bounds { min: [x_min, y_min], max: [x_max, y_max] }),
+                [None, None, None, None] => None,
+                _ => anyhow::bail!("Either all or none of the attributes \"x_min\", \"y_min\", \"x_max\", and \"y_max\" must be provided in <map>"),
+            };
+            Ok(webui::MapConfiguration { rate, bounds })
+        })
+        .transpose()?
+        .unwrap_or_default();
+    let tickets_path = supervisor
+        .descendants()
+        .find(|node| node.tag_name().name() == "tickets")
+        .map(|node| node
+            .attribute("path")
+            .ok_or(anyhow::anyhow!("Could not find attribute \"path\" in <tickets>"))
+            .map(PathBuf::from))
+        .unwrap_or(Ok(PathBuf::from("tickets.json")))?;
+    let usage_path = supervisor
+        .descendants()
+        .find(|node| node.tag_name().name() == "usage")
+        .map(|node| node
+            .attribute("path")
+            .ok_or(anyhow::anyhow!("Could not find attribute \"path\" in <usage>"))
+            .map(PathBuf::from))
+        .unwrap_or(Ok(PathBuf::from("usage.json")))?;
+    let audit_path = supervisor
+        .descendants()
+        .find(|node| node.tag_name().name() == "audit")
+        .map(|node| node
+            .attribute("path")
+            .ok_or(anyhow::anyhow!("Could not find attribute \"path\" in <audit>"))
+            .map(PathBuf::from))
+        .unwrap_or(Ok(PathBuf::from("audit.jsonl")))?;
+    let assets_path = supervisor
+        .descendants()
+        .find(|node| node.tag_name().name() == "assets")
+        .map(|node| node
+            .attribute("path")
+            .ok_or(anyhow::anyhow!("Could not find attribute \"path\" in <assets>"))
+            .map(PathBuf::from))
+        .unwrap_or(Ok(default_assets_path))?;
+    let rehearsal = supervisor
+        .descendants()
+        .find(|node| node.tag_name().name() == "rehearsal")
+        .map(|node| node
+            .attribute("enabled")
+            .map(|enabled| enabled
+                .parse::<bool>()
+                .context("Could not parse attribute \"enabled\" in <rehearsal>"))
+            .unwrap_or(Ok(true)))
+        .transpose()?
+        .unwrap_or(false);
+    let shutdown_timeout = supervisor
+        .descendants()
+        .find(|node| node.tag_name().name() == "shutdown")
+        .map(|node| node
+            .attribute("timeout")
+            .ok_or(anyhow::anyhow!("Could not find attribute \"timeout\" in <shutdown>"))?
+            .parse::<f64>()
+            .context("Could not parse attribute \"timeout\" in <shutdown>")
+            .and_then(|timeout| match timeout >= 0.0 && timeout.is_finite() {
+                true => Ok(Duration::from_secs_f64(timeout)),
+                false => Err(anyhow::anyhow!("Attribute \"timeout\" in <shutdown> must not be negative")),
+            }))
+        .unwrap_or(Ok(Duration::from_secs(10)))?;
+    let watchdog_config = supervisor
+        .descendants()
+        .find(|node| node.tag_name().name() == "watchdog")
+        .map(|node| -> anyhow::Result<watchdog::Configuration> {
+            let attempts = node
+                .attribute("attempts")
+                .map(|attempts| attempts
+                    .parse::<usize>()
+                    .context("Could not parse attribute \"attempts\" in <watchdog>"))
+                .unwrap_or(Ok(3))?;
+            let delay = node
+                .attribute("delay")
+                .map(|delay| delay
+                    .parse::<f64>()
+                    .context("Could not parse attribute \"delay\" in <watchdog>")
+                    .and_then(|delay| match delay >= 0.0 && delay.is_finite() {
+                        true => Ok(Duration::from_secs_f64(delay)),
+                        false => Err(anyhow::anyhow!("Attribute \"delay\" in <watchdog> must not be negative")),
+                    }))
+                .unwrap_or(Ok(Duration::from_secs(5)))?;
+            Ok(watchdog::Configuration { attempts, delay })
+        })
+        .transpose()?;
+    let channels = supervisor
+        .descendants()
+        .find(|node| node.tag_name().name() == "channels");
+    let capacity = |name: &str, default: usize| channels
+        .and_then(|node| node.attribute(name))
+        .map(|capacity| capacity
+            .parse::<usize>()
+            .context(format!("Could not parse attribute \"{}\" in <channels>", name))
+            .and_then(|capacity| match capacity > 0 {
+                true => Ok(capacity),
+                false => Err(anyhow::anyhow!("Attribute \"{}\" in <channels> must be greater than zero", name)),
+            }))
+        .unwrap_or(Ok(default));
+    let capacities = Capacities {
+        robots: capacity("robots", 16)?,
+        router: capacity("router", 32)?,
+        tracking: capacity("tracking", 32)?,
+    };
+    let experiment_config = supervisor
+        .descendants()
+        .find(|node| node.tag_name().name() == "experiment")
+        .map(|node| -> anyhow::Result<_> {
+            let restart_policy = match node.attribute("restart").unwrap_or("never") {
+                "never" => robot::RestartPolicy::Never,
+                "on-failure" => {
+                    let max_retries = node.attribute("max_retries")
+                        .map(|value| value.parse::<u32>())
+                        .transpose()
+                        .context("Could not parse attribute \"max_retries\" in <experiment>")?
+                        .unwrap_or(3);
+                    robot::RestartPolicy::OnFailure { max_retries }
+                },
+                other => anyhow::bail!("Attribute \"restart\" in <experiment> must be \"never\" or \"on-failure\", not \"{}\"", other),
+            };
+            let seconds = |name: &str| node.attribute(name)
+                .map(|value| value.parse::<f64>()
+                    .context(format!("Could not parse attribute \"{}\" in <experiment>", name))
+                    .and_then(|seconds| match seconds >= 0.0 && seconds.is_finite() {
+                        true => Ok(Duration::from_secs_f64(seconds)),
+                        false => Err(anyhow::anyhow!("Attribute \"{}\" in <experiment> must not be negative", name)),
+                    }))
+                .transpose();
+            let stagger = seconds("stagger")?.unwrap_or_default();
+            let go_signal_timeout = match node.attribute("go_signal") {
+                None | Some("false") => None,
+                Some("true") => Some(seconds("go_signal_timeout")?.unwrap_or(Duration::from_secs(30))),
+                Some(other) => anyhow::bail!("Attribute \"go_signal\" in <experiment> must be \"true\" or \"false\", not \"{}\"", other),
+            };
+            let min_health = node.attribute("min_health")
+                .map(|value| value.parse::<u8>()
+                    .context("Could not parse attribute \"min_health\" in <experiment>")
+                    .and_then(|min_health| match min_health <= 100 {
+                        true => Ok(min_health),
+                        false => Err(anyhow::anyhow!("Attribute \"min_health\" in <experiment> must not exceed 100")),
+                    }))
+                .transpose()?;
+            Ok(arena::ExperimentConfiguration { restart_policy, stagger, go_signal_timeout, min_health })
+        })
+        .transpose()?
+        .unwrap_or_default();
+    let maintenance_config = supervisor
+        .descendants()
+        .find(|node| node.tag_name().name() == "maintenance")
+        .map(|node| -> anyhow::Result<_> {
+            let script = node
+                .attribute("script")
+                .map(PathBuf::from)
+                .ok_or(anyhow::anyhow!("Could not find attribute \"script\" in <maintenance>"))?;
+            let concurrency = node
+                .attribute("concurrency")
+                .map(|concurrency| concurrency
+                    .parse::<usize>()
+                    .context("Could not parse attribute \"concurrency\" in <maintenance>"))
+                .unwrap_or(Ok(4))?;
+            if concurrency == 0 {
+                anyhow::bail!("Attribute \"concurrency\" in <maintenance> must be greater than zero");
+            }
+            let timeout = node
+                .attribute("timeout")
+                .map(|timeout| timeout
+                    .parse::<f64>()
+                    .context("Could not parse attribute \"timeout\" in <maintenance>")
+                    .and_then(|timeout| match timeout > 0.0 && timeout.is_finite() {
+                        true => Ok(Duration::from_secs_f64(timeout)),
+                        false => Err(anyhow::anyhow!("Attribute \"timeout\" in <maintenance> must be greater than zero")),
+                    }))
+                .unwrap_or(Ok(Duration::from_secs(600)))?;
+            Ok(arena::MaintenanceConfiguration { script, concurrency, timeout })
+        })
+        .transpose()?;
+    let argos_config = supervisor
+        .descendants()
+        .find(|node| node.tag_name().name() == "argos")
+        .map(|node| -> anyhow::Result<argos::Configuration> {
+            let config = node
+                .attribute("config")
+                .map(PathBuf::from)
+                .ok_or(anyhow::anyhow!("Could not find attribute \"config\" in <argos>"))?;
+            let executable = node
+                .attribute("executable")
+                .map_or_else(|| PathBuf::from("argos3"), PathBuf::from);
+            let software = node
+                .attribute("software")
+                .map(|software| match software {
+                    "builderbot" => Ok(argos::SoftwareSource::BuilderBot),
+                    "drone" => Ok(argos::SoftwareSource::Drone),
+                    "pipuck" => Ok(argos::SoftwareSource::PiPuck),
+                    _ => Err(anyhow::anyhow!("Could not parse attribute \"software\" in <argos>")),
+                })
+                .transpose()?;
+            let robots = node
+                .attribute("robots")
+                .ok_or(anyhow::anyhow!("Could not find attribute \"robots\" in <argos>"))?
+                .split(',')
+                .map(str::trim)
+                .filter(|robot| !robot.is_empty())
+                .map(String::from)
+                .collect::<Vec<_>>();
+            Ok(argos::Configuration { executable, config, software, robots })
+        })
+        .transpose()?;
+    let script_config = supervisor
+        .descendants()
+        .find(|node| node.tag_name().name() == "script")
+        .map(|node| -> anyhow::Result<script::Configuration> {
+            let path = node
+                .attribute("path")
+                .map(PathBuf::from)
+                .ok_or(anyhow::anyhow!("Could not find attribute \"path\" in <script>"))?;
+            Ok(script::Configuration { path })
+        })
+        .transpose()?;
+    let bandwidth_limits = supervisor
+        .descendants()
+        .find(|node| node.tag_name().name() == "bandwidth")
+        .map(|node| -> anyhow::Result<_> {
+            /* the limits are given in megabits per second */
+            let limiter = |name: &str| node.attribute(name)
+                .map(|value| value.parse::<f64>()
+                    .context(format!("Could not parse attribute \"{}\" in <bandwidth>", name))
+                    .and_then(|rate| match rate > 0.0 && rate.is_finite() {
+                        true => Ok(network::throttle::RateLimiter::new(rate * 1.0e6 / 8.0)),
+                        false => Err(anyhow::anyhow!("Attribute \"{}\" in <bandwidth> must be greater than zero", name)),
+                    }))
+                .transpose();
+            Ok(network::throttle::Limits { upload: limiter("upload")?, camera: limiter("camera")? })
+        })
+        .transpose()?
+        .unwrap_or_default();
+    let timeouts_config = supervisor
+        .descendants()
+        .find(|node| node.tag_name().name() == "timeouts")
+        .map(|node| -> anyhow::Result<_> {
+            /* durations are given in seconds */
+            let seconds = |node: roxmltree::Node, name: &str| node.attribute(name)
+                .map(|value| value.parse::<f64>()
+                    .context(format!("Could not parse attribute \"{}\" in <{}>", name, node.tag_name().name()))
+                    .and_then(|seconds| match seconds > 0.0 && seconds.is_finite() {
+                        true => Ok(Duration::from_secs_f64(seconds)),
+                        false => Err(anyhow::anyhow!("Attribute \"{}\" in <{}> must be greater than zero",
+                            name, node.tag_name().name())),
+                    }))
+                .transpose();
+            let retries = |node: roxmltree::Node| node.attribute("retries")
+                .map(|value| value.parse::<u32>()
+                    .context(format!("Could not parse attribute \"retries\" in <{}>", node.tag_name().name())))
+                .transpose();
+            let mut config = timeouts::Configuration::default();
+            /* the attributes of <timeouts> apply to all subsystems */
+            let scale = node
+                .attribute("scale")
+                .map(|scale| scale
+                    .parse::<f64>()
+                    .context("Could not parse attribute \"scale\" in <timeouts>"))
+                .unwrap_or(Ok(1.0))?;
+            if !(scale > 0.0 && scale.is_finite()) {
+                anyhow::bail!("Attribute \"scale\" in <timeouts> must be greater than zero");
+            }
+            let default_retries = retries(node)?;
+            let default_backoff = seconds(node, "backoff")?;
+            for (_, policy) in config.subsystems().iter_mut() {
+                policy.timeout = policy.timeout.mul_f64(scale);
+                if let Some(retries) = default_retries {
+                    policy.retries = retries;
+                }
+                if let Some(backoff) = default_backoff {
+                    policy.backoff = backoff;
+                }
+            }
+            /* each child node overrides the policy of a single subsystem */
+            for child in node.children().filter(|child| child.is_element()) {
+                let name = child.tag_name().name();
+                let mut subsystems = config.subsystems();
+                let policy = subsystems.iter_mut()
+                    .find(|(subsystem, _)| *subsystem == name)
+                    .map(|(_, policy)| policy)
+                    .ok_or(anyhow::anyhow!("Unknown subsystem <{}> in <timeouts>", name))?;
+                /* probes are not retried, since the addresses at which no device was found are probed again */
+                if let ("xbee" | "fernbedienung", Some(attribute)) = (name, ["retries", "backoff"].iter()
+                    .find(|attribute| child.attribute(**attribute).is_some())) {
+                    anyhow::bail!("Attribute \"{}\" is not supported in <{}>, since probes are not retried", attribute, name);
+                }
+                if let Some(timeout) = seconds(child, "timeout")? {
+                    policy.timeout = timeout;
+                }
+                if let Some(retries) = retries(child)? {
+                    policy.retries = retries;
+                }
+                if let Some(backoff) = seconds(child, "backoff")? {
+                    policy.backoff = backoff;
+                }
+            }
+            Ok(config)
+        })
+        .transpose()?
+        .unwrap_or_default();
+    let federation_config = supervisor
+        .descendants()
+        .find(|node| node.tag_name().name() == "federation")
+        .map(|node| -> anyhow::Result<federation::Configuration> {
+            /* a member connects to the primary instance, while the primary instance lists its members */
+            match node.attribute("primary") {
+                Some(primary) => {
+                    let primary = primary
+                        .parse::<SocketAddr>()
+                        .context("Could not parse attribute \"primary\" in <federation>")?;
+                    let name = node
+                        .attribute("name")
+                        .ok_or(anyhow::anyhow!("Could not find attribute \"name\" in <federation>"))?
+                        .to_owned();
+                    Ok(federation::Configuration::Member { primary, name })
+                },
+                None => {
+                    let socket = node
+                        .attribute("socket")
+                        .ok_or(anyhow::anyhow!("Could not find attribute \"socket\" or \"primary\" in <federation>"))?
+                        .parse::<SocketAddr>()
+                        .context("Could not parse attribute \"socket\" in <federation>")?;
+                    let members = node.children()
+                        .filter(|node| node.tag_name().name() == "member")
+                        .map(|node| node.attribute("name")
+                            .map(String::from)
+                            .ok_or(anyhow::anyhow!("Could not find attribute \"name\" in <member>")))
+                        .collect::<anyhow::Result<Vec<_>>>()?;
+                    Ok(federation::Configuration::Primary { socket, members })
+                }
+            }
+        })
+        .transpose()?;
+    /* the primary arena consists of the robots next to <supervisor> */
+    let robots = configuration
+        .descendants()
+        .find(|node| node.tag_name().name() == "robots" &&
+            !node.ancestors().any(|node| node.tag_name().name() == "arena"))
+        .ok_or(anyhow::anyhow!("Could not find node \"robots\" in <configuration>"))?;
+    let argos_robots = argos_config.as_ref()
+        .map_or(&[][..], |config| config.robots.as_slice());
+    let arena_name = supervisor.attribute("arena").unwrap_or("main");
+    let arena_config = parse_arena(arena_name, true, supervisor, robots, argos_robots)?;
+    /* each additional arena has its own robots, router, tracking system, and journal */
+    let mut additional_arenas: Vec<ArenaConfiguration> = Vec::new();
+    for node in configuration.children().filter(|node| node.tag_name().name() == "arena") {
+        let name = node.attribute("name")
+            .ok_or(anyhow::anyhow!("Could not find attribute \"name\" for <arena>"))?;
+        if name == arena_config.name || additional_arenas.iter().any(|arena| arena.name == name) {
+            anyhow::bail!("Arena {} is defined more than once", name);
+        }
+        let robots = node.children()
+            .find(|node| node.tag_name().name() == "robots")
+            .ok_or(anyhow::anyhow!("Could not find node \"robots\" in <arena>"))?;
+        let arena = parse_arena(name, false, node, robots, &[])
+            .context(format!("Could not parse arena {}", name))?;
+        additional_arenas.push(arena);
+    }
+    /* the members of a federation are shown as arenas in the web interface */
+    if let Some(federation::Configuration::Primary { members, .. }) = &federation_config {
+        for (index, name) in members.iter().enumerate() {
+            if name == &arena_config.name || additional_arenas.iter().any(|arena| &arena.name == name) ||
+                members[..index].contains(name) {
+                anyhow::bail!("Arena {} is defined more than once", name);
+            }
+        }
+    }
+    Ok(Configuration { 
+        bridge_config,
+        ros_config,
+        mqtt_config,
+        alert_config,
+        separation_config,
+        trajectory_config,
+        clock_config,
+        federation_config,
+        archive_config,
+        recording_config,
+        webui_socket,
+        webui_rate,
+        map_config,
+        tickets_path,
+        usage_path,
+        audit_path,
+        assets_path,
+        rehearsal,
+        shutdown_timeout,
+        watchdog_config,
+        experiment_config,
+        maintenance_config,
+        argos_config,
+        script_config,
+        bandwidth_limits,
+        timeouts_config,
+        capacities,
+        arena_config,
+        additional_arenas,
     })
 }
\ No newline at end of file
@@ -0,0 +1,200 @@
+use futures::StreamExt;
+use shared::experiment::State;
+use std::{collections::BTreeMap, fmt::Write};
+use tokio::sync::{mpsc, oneshot};
+
+use crate::{arena, journal, router, subscription, webui, robot::{builderbot, drone, pipuck}};
+
+pub enum Action {
+    /* render the current metrics in the Prometheus text format */
+    Render(oneshot::Sender<String>),
+}
+
+#[derive(Default)]
+struct Link {
+    connected: bool,
+    strength: Option<i32>,
+}
+
+struct Robot {
+    links: BTreeMap<&'static str, Link>,
+    battery: Option<i32>,
+}
+
+impl Robot {
+    fn new(links: &[&'static str]) -> Self {
+        Robot {
+            links: links.iter().map(|link| (*link, Link::default())).collect(),
+            battery: None,
+        }
+    }
+
+    fn link(&mut self, link: &'static str) -> &mut Link {
+        self.links.entry(link).or_default()
+    }
+}
+
+fn render(
+    robots: &BTreeMap<(&'static str, String), Robot>,
+    router_messages: u64,
+    router_peers: &[router::PeerStatus],
+    journal_entries: u64,
+    experiment_state: State
+) -> String {
+    let mut output = String::new();
+    /* writing to a string cannot fail */
+    let _ = writeln!(output, "# HELP supervisor_robot_connected Whether a link to a robot is up.");
+    let _ = writeln!(output, "# TYPE supervisor_robot_connected gauge");
+    for ((kind, id), robot) in robots {
+        for (link, state) in &robot.links {
+            let _ = writeln!(output, "supervisor_robot_connected{{kind=\"{}\",robot=\"{}\",link=\"{}\"}} {}",
+                kind, id, link, state.connected as u8);
+        }
+    }
+    let _ = writeln!(output, "# HELP supervisor_robot_link_strength The strength of a link to a robot.");
+    let _ = writeln!(output, "# TYPE supervisor_robot_link_strength gauge");
+    for ((kind, id), robot) in robots {
+        for (link, state) in &robot.links {
+            if let Some(strength) = state.strength {
+                let _ = writeln!(output, "supervisor_robot_link_strength{{kind=\"{}\",robot=\"{}\",link=\"{}\"}} {}",
+                    kind, id, link, strength);
+            }
+        }
+    }
+    let _ = writeln!(output, "# HELP supervisor_robot_battery The battery level of a robot.");
+    let _ = writeln!(output, "# TYPE supervisor_robot_battery gauge");
+    for ((kind, id), robot) in robots {
+        if let Some(battery) = robot.battery {
+            let _ = writeln!(output, "supervisor_robot_battery{{kind=\"{}\",robot=\"{}\"}} {}", kind, id, battery);
+        }
+    }
+    let _ = writeln!(output, "# HELP supervisor_router_messages_total Messages forwarded by the message router.");
+    let _ = writeln!(output, "# TYPE supervisor_router_messages_total counter");
+    let _ = writeln!(output, "supervisor_router_messages_total {}", router_messages);
+    let _ = writeln!(output, "# HELP supervisor_router_queue_depth Messages waiting to be sent to a peer of the message router.");
+    let _ = writeln!(output, "# TYPE supervisor_router_queue_depth gauge");
+    for peer in router_peers {
+        let _ = writeln!(output, "supervisor_router_queue_depth{{peer=\"{}\",robot=\"{}\"}} {}",
+            peer.addr, peer.robot.as_deref().unwrap_or(""), peer.depth);
+    }
+    let _ = writeln!(output, "# HELP supervisor_router_queue_dropped_total Messages discarded since the queue of a peer was full.");
+    let _ = writeln!(output, "# TYPE supervisor_router_queue_dropped_total counter");
+    for peer in router_peers {
+        let _ = writeln!(output, "supervisor_router_queue_dropped_total{{peer=\"{}\",robot=\"{}\"}} {}",
+            peer.addr, peer.robot.as_deref().unwrap_or(""), peer.dropped);
+    }
+    let _ = writeln!(output, "# HELP supervisor_router_rate_limited_total Messages from a peer discarded since it exceeded its rate.");
+    let _ = writeln!(output, "# TYPE supervisor_router_rate_limited_total counter");
+    for peer in router_peers {
+        let _ = writeln!(output, "supervisor_router_rate_limited_total{{peer=\"{}\",robot=\"{}\"}} {}",
+            peer.addr, peer.robot.as_deref().unwrap_or(""), peer.rate_limited);
+    }
+    let _ = writeln!(output, "# HELP supervisor_router_decode_failures_total Messages from a peer that could not be decoded.");
+    let _ = writeln!(output, "# TYPE supervisor_router_decode_failures_total counter");
+    for peer in router_peers {
+        let _ = writeln!(output, "supervisor_router_decode_failures_total{{peer=\"{}\",robot=\"{}\"}} {}",
+            peer.addr, peer.robot.as_deref().unwrap_or(""), peer.undecodable);
+    }
+    let _ = writeln!(output, "# HELP supervisor_journal_entries_total Entries written to experiment journals.");
+    let _ = writeln!(output, "# TYPE supervisor_journal_entries_total counter");
+    let _ = writeln!(output, "supervisor_journal_entries_total {}", journal_entries);
+    let _ = writeln!(output, "# HELP supervisor_experiment_active Whether an experiment is running.");
+    let _ = writeln!(output, "# TYPE supervisor_experiment_active gauge");
+    let _ = writeln!(output, "supervisor_experiment_active {}", matches!(experiment_state, State::Active) as u8);
+    output
+}
+
+pub async fn new(
+    mut requests_rx: mpsc::Receiver<Action>,
+    arena_tx: mpsc::Sender<arena::Action>,
+    router_tx: mpsc::Sender<router::Action>,
+    journal_tx: mpsc::Sender<journal::Action>
+) -> anyhow::Result<()> {
+    let mut builderbot_updates = webui::subscribe_builderbot_updates(&arena_tx).await?;
+    let mut drone_updates = webui::subscribe_drone_updates(&arena_tx).await?;
+    let mut pipuck_updates = webui::subscribe_pipuck_updates(&arena_tx).await?;
+    let (callback_tx, callback_rx) = oneshot::channel();
+    arena_tx.send(arena::Action::SubscribeExperimentState(callback_tx)).await
+        .map_err(|_| anyhow::anyhow!("Could not communicate with arena"))?;
+    let mut experiment_updates = callback_rx.await
+        .map(subscription::updates)
+        .map_err(|_| anyhow::anyhow!("Could not subscribe to experiment updates"))?;
+    /* robots are identified by their kind and identifier */
+    let mut robots = BTreeMap::new();
+    for desc in builderbot_updates.keys() {
+        robots.insert(("builderbot", desc.id.clone()), Robot::new(&["fernbedienung"]));
+    }
+    for desc in drone_updates.keys() {
+        robots.insert(("drone", desc.id.clone()), Robot::new(&["fernbedienung", "xbee"]));
+    }
+    for desc in pipuck_updates.keys() {
+        robots.insert(("pipuck", desc.id.clone()), Robot::new(&["fernbedienung"]));
+    }
+    let mut experiment_state = State::Standby;
+    loop {
+        tokio::select! {
+            Some((desc, update)) = builderbot_updates.next() => {
+                if let Some(robot) = robots.get_mut(&("builderbot", desc.id.clone())) {
+                    match update {
+                        Ok(builderbot::Update::Battery(battery)) => robot.battery = Some(battery),
+                        Ok(builderbot::Update::FernbedienungConnected(_)) => robot.link("fernbedienung").connected = true,
+                        Ok(builderbot::Update::FernbedienungDisconnected) => *robot.link("fernbedienung") = Link::default(),
+                        Ok(builderbot::Update::FernbedienungSignal(strength)) => robot.link("fernbedienung").strength = Some(strength),
+                        _ => {}
+                    }
+                }
+            },
+            Some((desc, update)) = drone_updates.next() => {
+                if let Some(robot) = robots.get_mut(&("drone", desc.id.clone())) {
+                    match update {
+                        Ok(drone::Update::Battery(battery)) => robot.battery = Some(battery),
+                        Ok(drone::Update::FernbedienungConnected(_)) => robot.link("fernbedienung").connected = true,
+                        Ok(drone::Update::FernbedienungDisconnected) => *robot.link("fernbedienung") = Link::default(),
+                        Ok(drone::Update::FernbedienungSignal(strength)) => robot.link("fernbedienung").strength = Some(strength),
+                        Ok(drone::Update::XbeeConnected(_)) => robot.link("xbee").connected = true,
+                        Ok(drone::Update::XbeeDisconnected) => *robot.link("xbee") = Link::default(),
+                        Ok(drone::Update::XbeeSignal(strength)) => robot.link("xbee").strength = Some(strength),
+                        _ => {}
+                    }
+                }
+            },
+            Some((desc, update)) = pipuck_updates.next() => {
+                if let Some(robot) = robots.get_mut(&("pipuck", desc.id.clone())) {
+                    match update {
+                        Ok(pipuck::Update::Battery(battery)) => robot.battery = Some(battery),
+                        Ok(pipuck::Update::FernbedienungConnected(_)) => robot.link("fernbedienung").connected = true,
+                        Ok(pipuck::Update::FernbedienungDisconnected) => *robot.link("fernbedienung") = Link::default(),
+                        Ok(pipuck::Update::FernbedienungSignal(strength)) => robot.link("fernbedienung").strength = Some(strength),
+                        _ => {}
+                    }
+                }
+            },
+            Some(update) = experiment_updates.next() => if let Ok(state) = update {
+                experiment_state = state;
+            },
+            request = requests_rx.recv() => match request {
+                Some(Action::Render(callback)) => {
+                    let (entries_tx, entries_rx) = oneshot::channel();
+                    let journal_entries = match journal_tx.send(journal::Action::GetEntriesWritten(entries_tx)).await {
+                        Ok(_) => entries_rx.await.unwrap_or_default(),
+                        Err(_) => 0,
+                    };
+                    /* the router counts the messages itself so that they are not decoded for the metrics */
+                    let (forwarded_tx, forwarded_rx) = oneshot::channel();
+                    let router_messages = match router_tx.send(router::Action::GetForwarded(forwarded_tx)).await {
+                        Ok(_) => forwarded_rx.await.unwrap_or_default(),
+                        Err(_) => 0,
+                    };
+                    let (peers_tx, peers_rx) = oneshot::channel();
+                    let router_peers = match router_tx.send(router::Action::GetPeers(peers_tx)).await {
+                        Ok(_) => peers_rx.await.unwrap_or_default(),
+                        Err(_) => Vec::new(),
+                    };
+                    let _ = callback.send(render(&robots, router_messages, &router_peers, journal_entries, experiment_state));
+                },
+                None => break,
+            }
+        }
+    }
+    Ok(())
+}
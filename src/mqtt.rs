@@ -0,0 +1,107 @@
+use futures::StreamExt;
+use rumqttc::{AsyncClient, MqttOptions, QoS};
+use serde::Serialize;
+use std::time::Duration;
+use tokio::sync::{broadcast::error::RecvError, mpsc, oneshot};
+use tokio_stream::{StreamMap, wrappers::{BroadcastStream, errors::BroadcastStreamRecvError}};
+
+use crate::{arena, subscription, webui};
+
+/* the delay before polling the connection to the broker again after an error */
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+/* the number of messages that can be waiting to be sent to the broker */
+const REQUEST_CAPACITY: usize = 64;
+
+#[derive(Debug)]
+pub struct Configuration {
+    pub host: String,
+    pub port: u16,
+    pub client_id: String,
+    /* the prefix of all topics, e.g., supervisor */
+    pub prefix: String,
+}
+
+/* queue a message for the broker, messages are dropped if the connection can not keep up */
+fn publish<T: Serialize>(client: &AsyncClient, topic: String, retain: bool, payload: &T) {
+    match serde_json::to_vec(payload) {
+        Ok(payload) => if let Err(error) = client.try_publish(&topic, QoS::AtMostOnce, retain, payload) {
+            log::warn!("Could not publish to {}: {}", topic, error);
+        },
+        Err(error) => log::warn!("Could not serialize message for {}: {}", topic, error),
+    }
+}
+
+/// This function publishes the updates of each robot, the state of the experiment, and the arena events
+/// to an MQTT broker. The updates of a robot, except for its camera frames, are published as JSON to
+/// `<prefix>/<type>/<id>`, where the type of the robots that are not built into the supervisor is
+/// `plugin`. The state of the experiment is retained under `<prefix>/experiment` and the events are
+/// published to `<prefix>/events`. If no broker has been configured, this function never completes.
+pub async fn new(
+    config: Option<Configuration>,
+    arena_tx: mpsc::Sender<arena::Action>,
+) -> anyhow::Result<()> {
+    let config = match config {
+        Some(config) => config,
+        /* publishing is disabled, never complete */
+        None => return futures::future::pending().await,
+    };
+    let options = MqttOptions::new(config.client_id.clone(), config.host.clone(), config.port);
+    let (client, mut eventloop) = AsyncClient::new(options, REQUEST_CAPACITY);
+    let mut builderbot_updates = webui::subscribe_builderbot_updates(&arena_tx).await?;
+    let mut drone_updates = webui::subscribe_drone_updates(&arena_tx).await?;
+    let mut pipuck_updates = webui::subscribe_pipuck_updates(&arena_tx).await?;
+    let (callback_tx, callback_rx) = oneshot::channel();
+    arena_tx.send(arena::Action::SubscribePluginRobots(callback_tx)).await
+        .map_err(|_| anyhow::anyhow!("Could not communicate with arena"))?;
+    let mut plugin_updates = callback_rx.await
+        .map_err(|_| anyhow::anyhow!("Could not subscribe to plugin robot updates"))?
+        .into_iter()
+        .map(|(id, receiver)| (id, BroadcastStream::new(receiver)))
+        .collect::<StreamMap<_, _>>();
+    let (callback_tx, callback_rx) = oneshot::channel();
+    arena_tx.send(arena::Action::SubscribeExperimentState(callback_tx)).await
+        .map_err(|_| anyhow::anyhow!("Could not communicate with arena"))?;
+    let mut experiment_updates = callback_rx.await
+        .map(subscription::updates)
+        .map_err(|_| anyhow::anyhow!("Could not subscribe to experiment updates"))?;
+    let (callback_tx, callback_rx) = oneshot::channel();
+    arena_tx.send(arena::Action::SubscribeEvents(callback_tx)).await
+        .map_err(|_| anyhow::anyhow!("Could not communicate with arena"))?;
+    let mut events_rx = callback_rx.await
+        .map_err(|_| anyhow::anyhow!("Could not subscribe to arena events"))?;
+    let topic = |kind: &str, id: &str| format!("{}/{}/{}", config.prefix, kind, id);
+    loop {
+        tokio::select! {
+            /* the event loop must be polled for the messages to be sent */
+            event = eventloop.poll() => if let Err(error) = event {
+                log::warn!("Could not communicate with MQTT broker {}:{}: {}", config.host, config.port, error);
+                tokio::time::sleep(RECONNECT_DELAY).await;
+            },
+            Some((desc, Ok(update))) = builderbot_updates.next() => match update {
+                shared::builderbot::Update::Camera { .. } => continue,
+                update => publish(&client, topic("builderbot", &desc.id), false, &update),
+            },
+            Some((desc, Ok(update))) = drone_updates.next() => match update {
+                shared::drone::Update::Camera { .. } => continue,
+                update => publish(&client, topic("drone", &desc.id), false, &update),
+            },
+            Some((desc, Ok(update))) = pipuck_updates.next() => match update {
+                shared::pipuck::Update::Camera { .. } => continue,
+                update => publish(&client, topic("pipuck", &desc.id), false, &update),
+            },
+            Some((id, update)) = plugin_updates.next() => match update {
+                Ok(update) => publish(&client, topic("plugin", &id), false, &update),
+                Err(BroadcastStreamRecvError::Lagged(count)) =>
+                    log::warn!("MQTT publisher missed {} updates from {}", count, id),
+            },
+            Some(Ok(state)) = experiment_updates.next() =>
+                publish(&client, format!("{}/experiment", config.prefix), true, &state),
+            event = events_rx.recv() => match event {
+                Ok(event) => publish(&client, format!("{}/events", config.prefix), false, &event),
+                Err(RecvError::Lagged(count)) => log::warn!("MQTT publisher missed {} arena events", count),
+                Err(RecvError::Closed) => break,
+            },
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,66 @@
+use anyhow::Context;
+use macaddr::MacAddr6;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, net::Ipv4Addr, path::PathBuf};
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub enum Kind {
+    Xbee,
+    Fernbedienung,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct Entry {
+    mac_addr: MacAddr6,
+    addr: Ipv4Addr,
+    kind: Kind,
+}
+
+/* the last known address of each device, indexed by its MAC address, so that these addresses can be
+   probed first after the supervisor has been restarted */
+pub struct Cache {
+    path: PathBuf,
+    entries: HashMap<MacAddr6, (Ipv4Addr, Kind)>,
+}
+
+impl Cache {
+    /* an unreadable cache is reported and replaced */
+    pub fn load(path: PathBuf) -> Self {
+        let entries = match std::fs::read_to_string(&path) {
+            Ok(contents) => serde_json::from_str::<Vec<Entry>>(&contents)
+                .map(|entries| entries.into_iter()
+                    .map(|entry| (entry.mac_addr, (entry.addr, entry.kind)))
+                    .collect())
+                .context(format!("Could not parse device cache in {:?}", path)),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(HashMap::new()),
+            Err(error) => Err(error).context(format!("Could not read device cache from {:?}", path)),
+        };
+        let entries = entries.unwrap_or_else(|error| {
+            log::warn!("{:#}", error);
+            HashMap::new()
+        });
+        Self { path, entries }
+    }
+
+    pub fn addresses(&self) -> impl Iterator<Item = (Ipv4Addr, Kind)> + '_ {
+        self.entries.values().copied()
+    }
+
+    /* the cache is only written if the address of a device has changed */
+    pub fn update(&mut self, mac_addr: MacAddr6, addr: Ipv4Addr, kind: Kind) -> anyhow::Result<()> {
+        if self.entries.get(&mac_addr) == Some(&(addr, kind)) {
+            return Ok(());
+        }
+        /* the address may have been assigned to another device */
+        self.entries.retain(|_, (cached_addr, _)| *cached_addr != addr);
+        self.entries.insert(mac_addr, (addr, kind));
+        let mut entries = self.entries.iter()
+            .map(|(&mac_addr, &(addr, kind))| Entry { mac_addr, addr, kind })
+            .collect::<Vec<_>>();
+        entries.sort_by_key(|entry| entry.addr);
+        let contents = serde_json::to_string_pretty(&entries)
+            .context("Could not serialize device cache")?;
+        std::fs::write(&self.path, contents)
+            .context(format!("Could not write device cache to {:?}", self.path))
+    }
+}
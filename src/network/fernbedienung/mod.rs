@@ -1,21 +1,24 @@
 use std::fmt::Debug;
 use std::net::Ipv4Addr;
 use std::path::PathBuf;
-use std::collections::HashMap;
+use std::time::Duration;
+use std::collections::{HashMap, HashSet};
 
 use bytes::BytesMut;
 use macaddr::MacAddr6;
 use regex::Regex;
 use once_cell::sync::Lazy;
 
-use futures::{self, FutureExt, StreamExt, stream::FuturesUnordered};
+use futures::{self, FutureExt, StreamExt, future::BoxFuture, stream::FuturesUnordered};
 use tokio::{net::TcpStream, sync::{mpsc, oneshot}};
 use tokio_stream::wrappers::ReceiverStream;
 use tokio_serde::{SymmetricallyFramed, formats::SymmetricalJson};
 use tokio_util::codec::{FramedRead, FramedWrite, LengthDelimitedCodec};
 use uuid::Uuid;
 
-mod protocol;
+use super::throttle;
+
+pub mod protocol;
 pub use protocol::{Upload, process::Process};
 
 static REGEX_LINK_STRENGTH: Lazy<Regex> = Lazy::new(|| {
@@ -44,6 +47,22 @@ pub enum Error {
 
 pub type Result<T> = std::result::Result<T, Error>;
 
+/* the size of the chunks in which files are uploaded with version 2 of the protocol */
+const TRANSFER_CHUNK_SIZE: usize = 64 * 1024;
+/* the number of chunks that can be sent before they are acknowledged by the remote */
+const TRANSFER_WINDOW: usize = 4;
+/* the number of responses that are buffered for each request before the responses are no longer read */
+const STATUS_CAPACITY: usize = 32;
+/* how often and at which interval the service is reconnected to after the connection was lost during an upload */
+const RECONNECT_ATTEMPTS: usize = 15;
+const RECONNECT_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Clone, Copy, Debug)]
+enum Version {
+    V1,
+    V2,
+}
+
 type RemoteResponses = SymmetricallyFramed<
     FramedRead<tokio::io::ReadHalf<TcpStream>, LengthDelimitedCodec>,
     protocol::Response,
@@ -56,6 +75,7 @@ pub type RemoteRequests = SymmetricallyFramed<
 
 pub struct Device {
     pub addr: Ipv4Addr,
+    pub limits: throttle::Limits,
     request_tx: mpsc::Sender<Request>,
     return_addr_tx: Option<oneshot::Sender<Ipv4Addr>>,
 }
@@ -91,149 +111,294 @@ enum Request {
     },
     Upload {
         upload: protocol::Upload,
+        progress_tx: Option<mpsc::Sender<(u64, u64)>>,
         result_tx: oneshot::Sender<Result<()>>
     },
 }
 
 impl Device {
-    pub async fn new(addr: Ipv4Addr, return_addr_tx: oneshot::Sender<Ipv4Addr>) -> Result<Self> {
+    pub async fn new(
+        addr: Ipv4Addr,
+        limits: throttle::Limits,
+        return_addr_tx: oneshot::Sender<Ipv4Addr>
+    ) -> Result<Self> {
         let (local_request_tx, mut local_request_rx) = mpsc::channel(8);
+        let upload_limiter = limits.upload.clone();
         tokio::spawn(async move {
-            let stream = match TcpStream::connect((addr, 17653)).await {
-                Ok(stream) => stream,
-                Err(_) => return,
+            /* prefer version 2 of the protocol and fall back to version 1 for older installations */
+            let (mut stream, version) = match TcpStream::connect((addr, protocol::PORT_V2)).await {
+                Ok(stream) => (stream, Version::V2),
+                Err(_) => match TcpStream::connect((addr, protocol::PORT_V1)).await {
+                    Ok(stream) => (stream, Version::V1),
+                    Err(_) => return,
+                }
             };
-            /* requests and responses from remote */
-            let (read, write) = tokio::io::split(stream);
-            let remote_requests: RemoteRequests = SymmetricallyFramed::new(
-                FramedWrite::new(write, LengthDelimitedCodec::new()),
-                SymmetricalJson::<protocol::Request>::default(),
-            );
-            let mut remote_responses: RemoteResponses = SymmetricallyFramed::new(
-                FramedRead::new(read, LengthDelimitedCodec::new()),
-                SymmetricalJson::<protocol::Response>::default(),
-            );
-            /* create an mpsc channel to share for remote_requests */
-            let (remote_requests_tx, remote_requests_rx) = mpsc::channel(32);           
-            let mut forward_remote_requests = ReceiverStream::new(remote_requests_rx)
-                .map(|request| Ok(request))
-                .forward(remote_requests);
-            /* collections for tracking state */
-            let mut status_txs: HashMap<Uuid, mpsc::Sender<protocol::ResponseKind>> = Default::default();
-            let mut tasks: FuturesUnordered<_> = Default::default();
-            /* event loop */
+            let mut interrupted = Vec::new();
             loop {
-                tokio::select! {
-                    Some(response) = remote_responses.next() => match response {
-                        Ok(protocol::Response(uuid, response)) => {
-                            if let Some(uuid) = uuid {
-                                if let Some(status_tx) = status_txs.get(&uuid) {
-                                    let _ = status_tx.send(response).await;
+                log::debug!("Connected to Fernbedienung@{} using protocol {:?}", addr, version);
+                interrupted = match Device::serve(stream, version, &upload_limiter, &mut local_request_rx, interrupted).await {
+                    Some(interrupted) if !interrupted.is_empty() => interrupted,
+                    /* the device was dropped or the connection was lost while no upload was in progress */
+                    _ => break,
+                };
+                /* the service keeps the data of an interrupted upload, reconnect and resume the uploads,
+                   the uploads fail if the connection can not be re-established */
+                log::warn!("Lost connection to Fernbedienung@{} during {} upload(s)", addr, interrupted.len());
+                stream = match Device::reconnect(addr).await {
+                    Some(stream) => stream,
+                    None => break,
+                };
+            }
+        });
+        Ok(Device { request_tx: local_request_tx, addr, limits, return_addr_tx: Some(return_addr_tx) })
+    }
+
+    /* connect to the service again using version 2 of the protocol, which is the only version that can
+       resume uploads */
+    async fn reconnect(addr: Ipv4Addr) -> Option<TcpStream> {
+        for _ in 0..RECONNECT_ATTEMPTS {
+            tokio::time::sleep(RECONNECT_INTERVAL).await;
+            if let Ok(stream) = TcpStream::connect((addr, protocol::PORT_V2)).await {
+                return Some(stream);
+            }
+        }
+        None
+    }
+
+    /* forward the local requests to the service and its responses to the tasks of the requests until the
+       device is dropped, in which case None is returned, or until the connection is lost, in which case
+       the uploads that were interrupted are returned. The requests in resumed are started first */
+    async fn serve(stream: TcpStream,
+                   version: Version,
+                   upload_limiter: &Option<throttle::RateLimiter>,
+                   local_request_rx: &mut mpsc::Receiver<Request>,
+                   resumed: Vec<Request>) -> Option<Vec<Request>> {
+        /* requests and responses from remote */
+        let (read, write) = tokio::io::split(stream);
+        let remote_requests: RemoteRequests = SymmetricallyFramed::new(
+            FramedWrite::new(write, LengthDelimitedCodec::new()),
+            SymmetricalJson::<protocol::Request>::default(),
+        );
+        let mut remote_responses: RemoteResponses = SymmetricallyFramed::new(
+            FramedRead::new(read, LengthDelimitedCodec::new()),
+            SymmetricalJson::<protocol::Response>::default(),
+        );
+        /* create an mpsc channel to share for remote_requests */
+        let (remote_requests_tx, remote_requests_rx) = mpsc::channel(32);
+        let mut forward_remote_requests = ReceiverStream::new(remote_requests_rx)
+            .map(Ok)
+            .forward(remote_requests);
+        /* collections for tracking state, the responses are buffered for each request so that a
+           slow consumer, e.g., of the standard output of a process, does not immediately block the others */
+        let mut status_txs: HashMap<Uuid, mpsc::Sender<protocol::ResponseKind>> = Default::default();
+        let mut tasks: FuturesUnordered<_> = Default::default();
+        /* the tasks of the uploads that can be resumed if the connection is lost */
+        let mut transfers: HashSet<Uuid> = Default::default();
+        /* a response that is waiting for space in the buffer of its request, no further responses are read
+           until it has been delivered while the tasks keep running so that they can empty their buffers */
+        let mut delivery: Option<BoxFuture<'static, ()>> = None;
+        for request in resumed {
+            tasks.push(Device::start(request, version, upload_limiter, &mut status_txs, &mut transfers, &remote_requests_tx));
+        }
+        /* event loop */
+        loop {
+            tokio::select! {
+                response = remote_responses.next(), if delivery.is_none() => match response {
+                    Some(Ok(protocol::Response(uuid, response))) => {
+                        if let Some(uuid) = uuid {
+                            if let Some(status_tx) = status_txs.get(&uuid) {
+                                if let Err(mpsc::error::TrySendError::Full(response)) = status_tx.try_send(response) {
+                                    let status_tx = status_tx.clone();
+                                    delivery = Some(async move {
+                                        let _ = status_tx.send(response).await;
+                                    }.boxed());
                                 }
                             }
-                            else {
-                                log::warn!("Received message without identifier: {:?}", response);
-                            }
-                        },
-                        Err(error) => {
-                            log::warn!("Could not deserialize response from remote: {}", error);
+                        }
+                        else {
+                            log::warn!("Received message without identifier: {:?}", response);
                         }
                     },
-                    request = local_request_rx.recv() => match request {
-                        Some(request) => {
-                            let task = match request {
-                                Request::Halt { result_tx } => {
-                                    let uuid = Uuid::new_v4();
-                                    let request = protocol::RequestKind::Halt;
-                                    let (halt_status_tx, mut halt_status_rx) = mpsc::channel(8);
-                                    status_txs.insert(uuid, halt_status_tx);
-                                    let request_result = remote_requests_tx
-                                        .send(protocol::Request(uuid, request)).await;
-                                    async move {
-                                        let result = match request_result {
-                                            Ok(_) => match halt_status_rx.recv().await {
-                                                Some(protocol::ResponseKind::Ok) => Ok(()),
-                                                _ => Err(Error::ResponseError),
-                                            }
-                                            _ => Err(Error::RequestError),
-                                        };
-                                        let _ = result_tx.send(result);
-                                        uuid
-                                    }.boxed()
-                                }
-                                Request::Reboot { result_tx } => {
-                                    let uuid = Uuid::new_v4();
-                                    let request = protocol::RequestKind::Reboot;
-                                    let (reboot_status_tx, mut reboot_status_rx) = mpsc::channel(8);
-                                    status_txs.insert(uuid, reboot_status_tx);
-                                    let request_result = remote_requests_tx
-                                        .send(protocol::Request(uuid, request)).await;
-                                    async move {
-                                        let result = match request_result {
-                                            Ok(_) => match reboot_status_rx.recv().await {
-                                                Some(protocol::ResponseKind::Ok) => Ok(()),
-                                                _ => Err(Error::ResponseError),
-                                            }
-                                            _ => Err(Error::RequestError),
-                                        };
-                                        let _ = result_tx.send(result);
-                                        uuid
-                                    }.boxed()
-                                }
-                                Request::Upload { upload, result_tx } => {
-                                    let uuid = Uuid::new_v4();
-                                    let request = protocol::RequestKind::Upload(upload);
-                                    /* subscribe to updates */
-                                    let (upload_status_tx, mut upload_status_rx) = mpsc::channel(8);
-                                    status_txs.insert(uuid, upload_status_tx);
-                                    /* send the request */
-                                    let request_result = remote_requests_tx
-                                        .send(protocol::Request(uuid, request)).await;
-                                    /* process responses */
-                                    async move {
-                                        let result = match request_result {
-                                            Ok(_) => match upload_status_rx.recv().await {
-                                                Some(protocol::ResponseKind::Ok) => Ok(()),
-                                                _ => Err(Error::ResponseError),
-                                            }
-                                            _ => Err(Error::RequestError),
-                                        };
-                                        let _ = result_tx.send(result);
-                                        uuid
-                                    }.boxed()
-                                },
-                                Request::Run { process, terminate_rx, stdin_rx, stdout_tx, stderr_tx, result_tx } => {
-                                    let uuid = Uuid::new_v4();
-                                    let request = protocol::RequestKind::Process(protocol::process::Request::Run(process));
-                                    /* subscribe to updates */
-                                    let (run_status_tx, run_status_rx) = mpsc::channel(8);
-                                    status_txs.insert(uuid, run_status_tx);
-                                    /* send the request */
-                                    match remote_requests_tx.send(protocol::Request(uuid, request)).await {
-                                        Ok(_) => {
-                                            let remote_requests_tx = remote_requests_tx.clone();
-                                            Device::handle_run_request(uuid, run_status_rx, remote_requests_tx,
-                                                terminate_rx, stdin_rx, stdout_tx, stderr_tx, result_tx).left_future()
-                                        }
-                                        _ => async move {
-                                            let _ = result_tx.send(Err(Error::RequestError));
-                                            uuid
-                                        }.right_future()
-                                    }.boxed()
-                                },
-                            };
-                            tasks.push(task);
-                        },
-                        None => break,
-                    },
-                    Some(uuid) = tasks.next() => {
-                        status_txs.remove(&uuid);
+                    Some(Err(error)) => {
+                        log::warn!("Could not deserialize response from remote: {}", error);
                     },
-                    _ = &mut forward_remote_requests => {}
+                    None => break,
+                },
+                request = local_request_rx.recv() => match request {
+                    Some(request) => tasks.push(Device::start(request, version, upload_limiter,
+                        &mut status_txs, &mut transfers, &remote_requests_tx)),
+                    None => return None,
+                },
+                Some((uuid, _)) = tasks.next() => {
+                    status_txs.remove(&uuid);
+                    transfers.remove(&uuid);
+                },
+                _ = async { delivery.as_mut().unwrap().await }, if delivery.is_some() => {
+                    delivery = None;
+                },
+                _ = &mut forward_remote_requests => break,
+            }
+        }
+        /* the connection has been lost, closing the outgoing requests before the responses lets the uploads
+           tell the loss of the connection apart from an error of the service */
+        drop(forward_remote_requests);
+        drop(status_txs);
+        drop(delivery);
+        let mut interrupted = Vec::new();
+        while !transfers.is_empty() {
+            match tasks.next().await {
+                Some((uuid, upload)) => {
+                    transfers.remove(&uuid);
+                    interrupted.extend(upload);
+                },
+                None => break,
+            }
+        }
+        /* let the other tasks deliver the responses that were received before the connection was lost */
+        while let Some(Some(_)) = tasks.next().now_or_never() {}
+        Some(interrupted)
+    }
+
+    /* start the task of a request, the task returns the identifier of the request and, if the request is an
+       upload that was interrupted by the loss of the connection, the upload so that it can be resumed */
+    fn start(request: Request,
+             version: Version,
+             upload_limiter: &Option<throttle::RateLimiter>,
+             status_txs: &mut HashMap<Uuid, mpsc::Sender<protocol::ResponseKind>>,
+             transfers: &mut HashSet<Uuid>,
+             remote_requests_tx: &mpsc::Sender<protocol::Request>) -> BoxFuture<'static, (Uuid, Option<Request>)> {
+        /* subscribe to updates, the request itself is sent by its task so that the event
+           loop keeps forwarding requests and responses while the outgoing channel is full */
+        let uuid = Uuid::new_v4();
+        let (status_tx, status_rx) = mpsc::channel(STATUS_CAPACITY);
+        status_txs.insert(uuid, status_tx);
+        let remote_requests_tx = remote_requests_tx.clone();
+        match request {
+            Request::Halt { result_tx } => {
+                let request = protocol::RequestKind::Halt;
+                Device::handle_request(uuid, status_rx, remote_requests_tx, request, result_tx)
+                    .map(|uuid| (uuid, None)).boxed()
+            },
+            Request::Reboot { result_tx } => {
+                let request = protocol::RequestKind::Reboot;
+                Device::handle_request(uuid, status_rx, remote_requests_tx, request, result_tx)
+                    .map(|uuid| (uuid, None)).boxed()
+            },
+            Request::Upload { upload, progress_tx, result_tx } => match version {
+                Version::V1 => {
+                    let upload_limiter = upload_limiter.clone();
+                    async move {
+                        if let Some(upload_limiter) = upload_limiter {
+                            upload_limiter.acquire(upload.contents.len()).await;
+                        }
+                        let request = protocol::RequestKind::Upload(upload);
+                        (Device::handle_request(uuid, status_rx, remote_requests_tx, request, result_tx).await, None)
+                    }.boxed()
+                },
+                Version::V2 => {
+                    transfers.insert(uuid);
+                    Device::handle_transfer_request(uuid, status_rx, remote_requests_tx,
+                        upload, upload_limiter.clone(), progress_tx, result_tx).boxed()
+                },
+            },
+            Request::Run { process, terminate_rx, stdin_rx, stdout_tx, stderr_tx, result_tx } => {
+                let request = protocol::RequestKind::Process(protocol::process::Request::Run(process));
+                async move {
+                    match remote_requests_tx.send(protocol::Request(uuid, request)).await {
+                        Ok(_) => (Device::handle_run_request(uuid, status_rx, remote_requests_tx,
+                            terminate_rx, stdin_rx, stdout_tx, stderr_tx, result_tx).await, None),
+                        Err(_) => {
+                            let _ = result_tx.send(Err(Error::RequestError));
+                            (uuid, None)
+                        }
+                    }
+                }.boxed()
+            },
+        }
+    }
+
+    /* send a request and wait for the remote to acknowledge it */
+    async fn handle_request(uuid: Uuid,
+                            mut status_rx: mpsc::Receiver<protocol::ResponseKind>,
+                            remote_requests_tx: mpsc::Sender<protocol::Request>,
+                            request: protocol::RequestKind,
+                            result_tx: oneshot::Sender<Result<()>>) -> Uuid {
+        let result = match remote_requests_tx.send(protocol::Request(uuid, request)).await {
+            Ok(_) => match status_rx.recv().await {
+                Some(protocol::ResponseKind::Ok) => Ok(()),
+                Some(protocol::ResponseKind::Error(error)) => Err(Error::RemoteError(error)),
+                _ => Err(Error::ResponseError),
+            }
+            _ => Err(Error::RequestError),
+        };
+        let _ = result_tx.send(result);
+        uuid
+    }
+
+    /* send a file in chunks, at most TRANSFER_WINDOW chunks are sent before they are acknowledged so that
+       the chunks are interleaved with the requests of other tasks. The progress is reported as the number
+       of bytes acknowledged and the total number of bytes */
+    async fn handle_transfer_request(uuid: Uuid,
+                                     mut status_rx: mpsc::Receiver<protocol::ResponseKind>,
+                                     remote_requests_tx: mpsc::Sender<protocol::Request>,
+                                     upload: protocol::Upload,
+                                     upload_limiter: Option<throttle::RateLimiter>,
+                                     progress_tx: Option<mpsc::Sender<(u64, u64)>>,
+                                     result_tx: oneshot::Sender<Result<()>>) -> (Uuid, Option<Request>) {
+        let length = upload.contents.len() as u64;
+        let checksum = format!("{:x}", md5::compute(&upload.contents));
+        let report_progress = |received: u64| if let Some(progress_tx) = &progress_tx {
+            /* progress is not essential, drop it if the receiver does not keep up */
+            let _ = progress_tx.try_send((received, length));
+        };
+        let send = |request| {
+            let request = protocol::Request(uuid, protocol::RequestKind::Transfer(request));
+            remote_requests_tx.send(request)
+        };
+        let result = async {
+            let (filename, path) = (upload.filename.clone(), upload.path.clone());
+            send(protocol::transfer::Request::Start { filename, path, length, checksum }).await
+                .map_err(|_| Error::RequestError)?;
+            /* the remote responds with the number of bytes that it already has */
+            let mut received = match status_rx.recv().await {
+                Some(protocol::ResponseKind::Resume(offset)) if offset <= length => offset,
+                Some(protocol::ResponseKind::Error(error)) => return Err(Error::RemoteError(error)),
+                _ => return Err(Error::ResponseError),
+            };
+            report_progress(received);
+            let mut sent = received;
+            while received < length {
+                while sent < length && sent - received < (TRANSFER_WINDOW * TRANSFER_CHUNK_SIZE) as u64 {
+                    let end = std::cmp::min(sent + TRANSFER_CHUNK_SIZE as u64, length);
+                    let chunk = BytesMut::from(&upload.contents[sent as usize..end as usize]);
+                    if let Some(upload_limiter) = &upload_limiter {
+                        upload_limiter.acquire(chunk.len()).await;
+                    }
+                    send(protocol::transfer::Request::Chunk(chunk)).await
+                        .map_err(|_| Error::RequestError)?;
+                    sent = end;
                 }
+                received = match status_rx.recv().await {
+                    Some(protocol::ResponseKind::Received(count)) if count <= sent => count,
+                    Some(protocol::ResponseKind::Error(error)) => return Err(Error::RemoteError(error)),
+                    _ => return Err(Error::ResponseError),
+                };
+                report_progress(received);
             }
-        });
-        Ok(Device { request_tx: local_request_tx, addr, return_addr_tx: Some(return_addr_tx) })
+            send(protocol::transfer::Request::Finish).await
+                .map_err(|_| Error::RequestError)?;
+            match status_rx.recv().await {
+                Some(protocol::ResponseKind::Ok) => Ok(()),
+                Some(protocol::ResponseKind::Error(error)) => Err(Error::RemoteError(error)),
+                _ => Err(Error::ResponseError),
+            }
+        }.await;
+        /* the requests can no longer be sent once the connection has been lost */
+        if result.is_err() && remote_requests_tx.is_closed() {
+            return (uuid, Some(Request::Upload { upload, progress_tx, result_tx }));
+        }
+        let _ = result_tx.send(result);
+        (uuid, None)
     }
 
     async fn handle_run_request(uuid: Uuid,
@@ -270,6 +435,11 @@ impl Device {
                 },
                 Some(response) = run_status_rx.recv() => match response {
                     protocol::ResponseKind::Ok => {},
+                    /* the progress of a chunked upload does not apply to a process, there is nothing
+                       to acknowledge or resume */
+                    protocol::ResponseKind::Resume(_) | protocol::ResponseKind::Received(_) => {
+                        log::warn!("Ignored transfer response for process {}: {:?}", uuid, response);
+                    },
                     protocol::ResponseKind::Error(error) => {
                         let status = Err(Error::RemoteError(error));
                         let _ = exit_status_tx.send(status);
@@ -308,13 +478,25 @@ impl Device {
         path: P,
         filename: F,
         contents: C
+    ) -> Result<()> where P: Into<PathBuf>, F: Into<PathBuf>, C: Into<Vec<u8>> {
+        self.upload_with_progress(path, filename, contents, None).await
+    }
+
+    /* the progress is sent as the number of bytes received by the remote and the total number of bytes,
+       using version 1 of the protocol, the progress is not reported */
+    pub async fn upload_with_progress<P, F, C>(
+        &self,
+        path: P,
+        filename: F,
+        contents: C,
+        progress_tx: impl Into<Option<mpsc::Sender<(u64, u64)>>>,
     ) -> Result<()> where P: Into<PathBuf>, F: Into<PathBuf>, C: Into<Vec<u8>> {
         let upload = protocol::Upload {
             path: path.into(), filename: filename.into(), contents: contents.into(),
         };
         let (result_tx, result_rx) = oneshot::channel();
         self.request_tx
-            .send(Request::Upload { upload, result_tx }).await
+            .send(Request::Upload { upload, progress_tx: progress_tx.into(), result_tx }).await
             .map_err(|_| Error::RequestError)?;
         result_rx.await.map_err(|_| Error::ResponseError).and_then(|result| result)
     }
@@ -388,22 +570,22 @@ impl Device {
     //     Ok(hostname.trim().to_owned())
     // }
 
-    // pub async fn kernel_messages(&self) -> Result<String> {
-    //     let process = protocol::process::Process {
-    //         target: "dmesg".into(),
-    //         working_dir: None,
-    //         args: vec![],
-    //     };
-    //     let (stdout_tx, stdout_rx) = mpsc::channel(8);
-    //     let stdout_stream = ReceiverStream::new(stdout_rx);
-    //     let (_, stdout) = tokio::try_join!(
-    //         self.run(process, None, None, stdout_tx, None),
-    //         stdout_stream.concat().map(Result::Ok)
-    //     )?;
-    //     let messages = std::str::from_utf8(stdout.as_ref())
-    //         .map_err(|_| Error::DecodeError)?;
-    //     Ok(messages.trim().to_owned())
-    // }
+    pub async fn kernel_messages(&self) -> Result<String> {
+        let process = protocol::process::Process {
+            target: "dmesg".into(),
+            working_dir: None,
+            args: vec![],
+        };
+        let (stdout_tx, stdout_rx) = mpsc::channel(8);
+        let stdout_stream = ReceiverStream::new(stdout_rx);
+        let (_, stdout) = tokio::try_join!(
+            self.run(process, None, None, stdout_tx, None),
+            stdout_stream.concat().map(Result::Ok)
+        )?;
+        /* drivers may log bytes that are not valid UTF-8 */
+        let messages = String::from_utf8_lossy(stdout.as_ref());
+        Ok(messages.trim().to_owned())
+    }
 
     pub async fn link_strength(&self) -> Result<i32> {
         let process = protocol::process::Process {
@@ -429,6 +611,26 @@ impl Device {
             .and_then(|strength| strength.parse().map_err(|_| Error::DecodeError))
     }
 
+    /* the temperature of the first thermal zone in degrees Celsius */
+    pub async fn cpu_temperature(&self) -> Result<i32> {
+        let process = protocol::process::Process {
+            target: "cat".into(),
+            working_dir: None,
+            args: vec!["/sys/class/thermal/thermal_zone0/temp".to_owned()],
+        };
+        let (stdout_tx, stdout_rx) = mpsc::channel(8);
+        let stdout_stream = ReceiverStream::new(stdout_rx);
+        let (_, stdout) = tokio::try_join!(
+            self.run(process, None, None, stdout_tx, None),
+            stdout_stream.concat().map(Result::Ok)
+        )?;
+        /* the temperature is given in millidegrees */
+        std::str::from_utf8(stdout.as_ref())
+            .map_err(|_| Error::DecodeError)
+            .and_then(|temperature| temperature.trim().parse::<i32>().map_err(|_| Error::DecodeError))
+            .map(|temperature| temperature / 1000)
+    }
+
     pub async fn mac(&self) -> Result<MacAddr6> {
         let process = protocol::process::Process {
             target: "iw".into(),
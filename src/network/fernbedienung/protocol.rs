@@ -3,6 +3,10 @@ use bytes::BytesMut;
 use std::path::PathBuf;
 use uuid::Uuid;
 
+/* the service listens on one port per version of the protocol */
+pub const PORT_V1: u16 = 17653;
+pub const PORT_V2: u16 = 17654;
+
 fn bytesmut_serialize<S: Serializer>(bytes: &BytesMut, serializer: S) -> Result<S::Ok, S::Error> {
     serializer.serialize_str(&base64::encode(bytes))
 }
@@ -21,55 +25,86 @@ pub mod process {
     use serde::{Deserialize, Serialize};
     
 
-    #[derive(Debug, Serialize)]
+    #[derive(Debug, Deserialize, Serialize)]
     pub struct Process {
         pub target: PathBuf,
         pub working_dir: Option<PathBuf>,
         pub args: Vec<String>,
     }
 
-    #[derive(Debug, Serialize)]
+    #[derive(Debug, Deserialize, Serialize)]
     pub enum Request {
         Run(Process),
-        #[serde(serialize_with = "super::bytesmut_serialize")]
+        #[serde(serialize_with = "super::bytesmut_serialize", deserialize_with = "super::bytesmut_deserialize")]
         StandardInput(BytesMut),
         Terminate,
     }
 
-    #[derive(Debug, Deserialize)]
+    #[derive(Debug, Deserialize, Serialize)]
     pub enum Response {
         Terminated(bool),
-        #[serde(deserialize_with = "super::bytesmut_deserialize")]
+        #[serde(serialize_with = "super::bytesmut_serialize", deserialize_with = "super::bytesmut_deserialize")]
         StandardOutput(BytesMut),
-        #[serde(deserialize_with = "super::bytesmut_deserialize")]
+        #[serde(serialize_with = "super::bytesmut_serialize", deserialize_with = "super::bytesmut_deserialize")]
         StandardError(BytesMut),
     }
 }
 
-#[derive(Debug, Serialize)]
+/* version 2 of the protocol transfers files in chunks so that a large upload does not delay the
+   other requests and the standard input of the processes that share the connection */
+pub mod transfer {
+    use std::path::PathBuf;
+    use bytes::BytesMut;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, Deserialize, Serialize)]
+    pub enum Request {
+        /* the remote keeps the data of an interrupted transfer, which is resumed if a transfer with
+           the same checksum is started again */
+        Start {
+            filename: PathBuf,
+            path: PathBuf,
+            length: u64,
+            checksum: String,
+        },
+        #[serde(serialize_with = "super::bytesmut_serialize", deserialize_with = "super::bytesmut_deserialize")]
+        Chunk(BytesMut),
+        Finish,
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
 pub struct Upload {
     pub filename: PathBuf,
     pub path: PathBuf,
     pub contents: Vec<u8>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Deserialize, Serialize)]
 pub enum RequestKind {
     Halt,
     Reboot,
     Upload(Upload),
     Process(process::Request),
+    /* version 2 only */
+    Transfer(transfer::Request),
 }
 
-#[derive(Debug, Serialize)]
+/* the identifier of a request is repeated in all of its responses, which allows several processes
+   and transfers to share one connection */
+#[derive(Debug, Deserialize, Serialize)]
 pub struct Request(pub Uuid, pub RequestKind);
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 pub enum ResponseKind {
     Ok,
     Error(String),
     Process(process::Response),
+    /* version 2 only, the number of bytes of a transfer that the remote already has */
+    Resume(u64),
+    /* version 2 only, acknowledges the chunks of a transfer up to the given number of bytes */
+    Received(u64),
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 pub struct Response(pub Option<Uuid>, pub ResponseKind);
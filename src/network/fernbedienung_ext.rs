@@ -1,10 +1,17 @@
-use std::{pin::Pin, task::{Context, Poll}};
+use std::{pin::Pin, task::{Context, Poll}, time::Duration};
+use anyhow::Context as _;
 use bytes::Bytes;
-use futures::{Stream, TryFutureExt};
-use tokio::sync::oneshot;
+use futures::{FutureExt, Stream, StreamExt, TryFutureExt};
+use shared::camera;
+use tokio::sync::{mpsc, oneshot};
+use tokio_stream::wrappers::ReceiverStream;
 
 use super::fernbedienung;
 
+/* how often the temperature of a robot is read */
+const TEMPERATURE_INTERVAL: Duration = Duration::from_secs(5);
+const TEMPERATURE_TIMEOUT: Duration = Duration::from_secs(1);
+
 // TO READ: https://carllerche.com/2021/06/17/six-ways-to-make-async-rust-easier/
 // TO READ: https://rust-lang.github.io/wg-async-foundations/vision.html
 
@@ -28,30 +35,40 @@ impl<S> PinnedDrop for MjpegStreamerStream<'_, S> {
 impl MjpegStreamerStream<'_, ()> {
     pub fn new<'dev>(
         device: &'dev fernbedienung::Device,
-        camera: &str,
-        width: u16,
-        height: u16,
-        port: u16
+        camera: &camera::Configuration,
     ) -> impl Stream<Item = reqwest::Result<Bytes>> + 'dev {
         let mjpg_streamer = fernbedienung::Process {
             target: "mjpg_streamer".into(),
             working_dir: None,
             args: vec![
                 "-i".to_owned(),
-                format!("input_uvc.so -d {} -r {}x{} -n", camera, width, height),
+                format!("input_uvc.so -d {} -r {}x{} -f {} -n",
+                    camera.device, camera.width, camera.height, camera.frame_rate),
                 "-o".to_owned(),
-                format!("output_http.so -p {} -l {}", port, device.addr)
+                format!("output_http.so -p {} -l {}", camera.port, device.addr)
             ],
         };
         let (terminate_tx, terminate_rx) = oneshot::channel::<()>();
         let mjpg_streamer = device.run(mjpg_streamer, Some(terminate_rx), None, None, None);
-        let source = format!("http://{}:{}/?action=snapshot", device.addr, port);
+        let source = format!("http://{}:{}/?action=snapshot", device.addr, camera.port);
+        /* request snapshots at the frame rate of the camera */
+        let period = Duration::from_secs_f32(1.0 / camera.frame_rate.max(1) as f32);
+        let camera_limiter = device.limits.camera.clone();
         MjpegStreamerStream {
             device, terminate_tx: Some(terminate_tx), stream: async_stream::stream! {
                 tokio::pin!(mjpg_streamer);
+                let mut interval = tokio::time::interval(period);
                 loop {
+                    tokio::select! {
+                        _ = interval.tick() => {},
+                        _ = &mut mjpg_streamer => break,
+                    }
                     tokio::select! {
                         item = reqwest::get(&source).and_then(|response| response.bytes()) => {
+                            /* the next snapshot is delayed until the bandwidth of this one is available */
+                            if let (Some(camera_limiter), Ok(frame)) = (&camera_limiter, &item) {
+                                camera_limiter.acquire(frame.len()).await;
+                            }
                             yield item;
                         }
                         _ = &mut mjpg_streamer => break,
@@ -62,6 +79,112 @@ impl MjpegStreamerStream<'_, ()> {
     }
 }
 
+/* capture a single JPEG image from a camera at the resolution for still images, the camera must not
+   be streaming since the device can only be opened once */
+pub async fn capture_image(
+    device: &fernbedienung::Device,
+    camera: &camera::Configuration
+) -> fernbedienung::Result<Bytes> {
+    let v4l2_ctl = fernbedienung::Process {
+        target: "v4l2-ctl".into(),
+        working_dir: None,
+        args: vec![
+            format!("--device={}", camera.device),
+            format!("--set-fmt-video=width={},height={},pixelformat=MJPG", camera.still_width, camera.still_height),
+            "--stream-mmap".to_owned(),
+            /* skip the first frames while the exposure of the camera settles */
+            "--stream-skip=5".to_owned(),
+            "--stream-count=1".to_owned(),
+            "--stream-to=-".to_owned(),
+        ],
+    };
+    let (stdout_tx, stdout_rx) = mpsc::channel(8);
+    let stdout_stream = ReceiverStream::new(stdout_rx);
+    let (_, image) = tokio::try_join!(
+        device.run(v4l2_ctl, None, None, stdout_tx, None),
+        stdout_stream.concat().map(Result::Ok)
+    )?;
+    match image.is_empty() {
+        true => Err(fernbedienung::Error::DecodeError),
+        false => Ok(image.freeze()),
+    }
+}
+
+/* upload a file and pass its progress to a closure, e.g., to report it as an update of the robot */
+pub async fn upload_with_progress(
+    device: &fernbedienung::Device,
+    path: &str,
+    filename: &str,
+    contents: Vec<u8>,
+    mut progress: impl FnMut(u64, u64),
+) -> fernbedienung::Result<()> {
+    let (progress_tx, progress_rx) = mpsc::channel(8);
+    let upload = device.upload_with_progress(path, filename, contents, progress_tx);
+    let progress = ReceiverStream::new(progress_rx)
+        .for_each(|(received, total)| {
+            progress(received, total);
+            futures::future::ready(())
+        });
+    let (result, _) = tokio::join!(upload, progress);
+    result
+}
+
+/* poll the temperature of the robot's computer, nothing is yielded if it does not have a thermal sensor */
+pub fn temperature_stream<'dev>(
+    device: &'dev fernbedienung::Device
+) -> impl Stream<Item = i32> + 'dev {
+    async_stream::stream! {
+        loop {
+            if let Ok(Ok(temperature)) = tokio::time::timeout(TEMPERATURE_TIMEOUT, device.cpu_temperature()).await {
+                yield temperature;
+            }
+            tokio::time::sleep(TEMPERATURE_INTERVAL).await;
+        }
+    }
+}
+
+/* upload a script to a temporary directory and run it with sh, e.g., to update the software of a robot,
+   the standard output and standard error of the script are combined and returned */
+pub async fn run_script(
+    device: &fernbedienung::Device,
+    script: Vec<u8>,
+    timeout: Duration,
+) -> anyhow::Result<String> {
+    let path = device.create_temp_dir().await
+        .context("Could not create temporary directory")?;
+    device.upload(&path, "script.sh", script).await
+        .context("Could not upload script")?;
+    let process = fernbedienung::Process {
+        target: "sh".into(),
+        working_dir: Some(path.into()),
+        args: vec!["script.sh".to_owned()],
+    };
+    let (output_tx, output_rx) = mpsc::channel(8);
+    let (terminate_tx, terminate_rx) = oneshot::channel();
+    let run = async {
+        let script = device.run(process, terminate_rx, None, output_tx.clone(), output_tx);
+        tokio::pin!(script);
+        match tokio::time::timeout(timeout, &mut script).await {
+            Ok(result) => result.context("Script terminated with an error"),
+            Err(_) => {
+                let _ = terminate_tx.send(());
+                let _ = script.await;
+                Err(anyhow::anyhow!("Script did not complete within {} seconds", timeout.as_secs()))
+            }
+        }
+    };
+    /* the output must be consumed while the script is running */
+    let (result, output) = tokio::join!(run, ReceiverStream::new(output_rx).concat());
+    let output = String::from_utf8_lossy(&output).into_owned();
+    match result {
+        Ok(_) => Ok(output),
+        Err(error) => match output.lines().rev().find(|line| !line.trim().is_empty()) {
+            Some(line) => Err(error.context(format!("Last output: {}", line.trim()))),
+            None => Err(error),
+        }
+    }
+}
+
 impl<S: futures::Stream> Stream for MjpegStreamerStream<'_, S>  {
     type Item = S::Item;
     fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
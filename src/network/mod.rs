@@ -1,76 +1,153 @@
 
 use macaddr::MacAddr6;
-use std::{net::Ipv4Addr, time::Duration};
+use std::{collections::{HashSet, VecDeque}, net::Ipv4Addr, path::PathBuf, time::Duration};
 use ipnet::Ipv4Net;
 
 use tokio::sync::{mpsc, oneshot};
 use tokio_stream::StreamExt;
-use futures::stream::FuturesUnordered;
+use futures::{FutureExt, future::BoxFuture, stream::FuturesUnordered};
 
+pub mod cache;
 pub mod xbee;
 pub mod fernbedienung;
 pub mod fernbedienung_ext;
+pub mod throttle;
 
-use crate::arena;
+use crate::{arena, timeouts};
 
-/// This function represents the main task of the network module. It takes a network and a channel for
-/// making requests to the arena. IP addresses belonging to this network are repeated probed for an
-/// xbee or for the fernbedienung service until they are associated
-pub async fn new(network: Ipv4Net, arena_request_tx: mpsc::Sender<arena::Action>) {
-    /* probe for xbees on all addresses */
-    let (mut xbee_returned_addrs, mut probe_xbee_queue) : (FuturesUnordered<_>, FuturesUnordered<_>) = network
-        .hosts()
-        .map(|addr| {
-            let (return_addr_tx, return_addr_rx) = oneshot::channel();
-            (return_addr_rx, probe_xbee(return_addr_tx, addr))
-        }).unzip();
-    /* empty collections for the fernbedienung tasks */
-    let mut fernbedienung_returned_addrs : FuturesUnordered<oneshot::Receiver<Ipv4Addr>> = Default::default();
-    let mut probe_fernbedienung_queue: FuturesUnordered<_> = Default::default();
+/* the number of probes that are in flight at the same time if it is not configured */
+pub const DEFAULT_PROBE_PARALLELISM: usize = 64;
+/* each probe is delayed by a random duration up to this value so that the probes are spread out */
+const PROBE_JITTER: Duration = Duration::from_millis(100);
+
+/* the next probe for an address, an address is probed for an xbee and for the fernbedienung service in turn */
+enum Probe {
+    Xbee(Ipv4Addr),
+    Fernbedienung(Ipv4Addr),
+}
+
+enum Probed {
+    Xbee(anyhow::Result<(MacAddr6, xbee::Device)>),
+    Fernbedienung(anyhow::Result<(MacAddr6, fernbedienung::Device)>),
+}
+
+/// This function represents the main task of the network module. It takes a network, the number of probes
+/// that can be in flight at the same time, the path of the device cache, the bandwidth limits for the
+/// connections to the fernbedienung service, the timeouts of the probes, and a channel for making requests
+/// to the arena. IP addresses belonging to this network are repeated probed for an xbee or for the
+/// fernbedienung service until they are associated
+pub async fn new(
+    network: Ipv4Net,
+    parallelism: usize,
+    cache_path: Option<PathBuf>,
+    limits: throttle::Limits,
+    timeouts: timeouts::Configuration,
+    arena_request_tx: mpsc::Sender<arena::Action>
+) {
+    let mut cache = cache_path.map(cache::Cache::load);
+    /* probe the addresses in the cache first, using the kind of device that was found there */
+    let mut queue: VecDeque<Probe> = VecDeque::new();
+    let mut cached_addrs = HashSet::new();
+    if let Some(cache) = cache.as_ref() {
+        for (addr, kind) in cache.addresses() {
+            if network.contains(&addr) && cached_addrs.insert(addr) {
+                queue.push_back(match kind {
+                    cache::Kind::Xbee => Probe::Xbee(addr),
+                    cache::Kind::Fernbedienung => Probe::Fernbedienung(addr),
+                });
+            }
+        }
+    }
+    /* probe for xbees on all other addresses */
+    queue.extend(network.hosts()
+        .filter(|addr| !cached_addrs.contains(addr))
+        .map(Probe::Xbee));
+    /* the probes in flight and the addresses that will be returned by the devices */
+    let mut probes: FuturesUnordered<BoxFuture<'static, Probed>> = Default::default();
+    let mut returned_addrs: FuturesUnordered<BoxFuture<'static, Option<Probe>>> = Default::default();
     /* main task loop */
     loop {
+        while probes.len() < parallelism {
+            match queue.pop_front() {
+                Some(probe) => {
+                    let (returned_addr, probe) = start_probe(probe, &limits, &timeouts);
+                    returned_addrs.push(returned_addr);
+                    probes.push(probe);
+                },
+                None => break,
+            }
+        }
         tokio::select!{
-            Some(result) = probe_xbee_queue.next() => {
-                if let Ok((mac_addr, device)) = result {
+            Some(result) = probes.next() => match result {
+                Probed::Xbee(Ok((mac_addr, device))) => {
+                    update_cache(cache.as_mut(), mac_addr, device.addr, cache::Kind::Xbee);
                     let _ = arena_request_tx.send(arena::Action::AddXbee(device, mac_addr)).await;
-                }
-            },
-            Some(result) = xbee_returned_addrs.next() => match result {
-                Ok(addr) => {
-                    let (return_addr_tx, return_addr_rx) = oneshot::channel();
-                    fernbedienung_returned_addrs.push(return_addr_rx);
-                    probe_fernbedienung_queue.push(probe_fernbedienung(return_addr_tx, addr));
                 },
-                Err(_) => {
-                    log::error!("xbee::Device did not return its IP address");
-                }
-            },
-            Some(result) = probe_fernbedienung_queue.next() => {
-                if let Ok((mac_addr, device)) = result {
+                Probed::Fernbedienung(Ok((mac_addr, device))) => {
+                    update_cache(cache.as_mut(), mac_addr, device.addr, cache::Kind::Fernbedienung);
                     let _ = arena_request_tx.send(arena::Action::AddFernbedienung(device, mac_addr)).await;
-                }
-            },
-            Some(result) = fernbedienung_returned_addrs.next() => match result {
-                Ok(addr) => {
-                    let (return_addr_tx, return_addr_rx) = oneshot::channel();
-                    xbee_returned_addrs.push(return_addr_rx);
-                    probe_xbee_queue.push(probe_xbee(return_addr_tx, addr));
                 },
-                Err(_) => {
-                    log::error!("fernbedienung::Device did not return its IP address");
-                }
+                _ => {},
+            },
+            Some(result) = returned_addrs.next() => match result {
+                Some(probe) => queue.push_back(probe),
+                None => log::error!("Device did not return its IP address"),
             },
             else => break
         }
     }
 }
 
+fn update_cache(cache: Option<&mut cache::Cache>, mac_addr: MacAddr6, addr: Ipv4Addr, kind: cache::Kind) {
+    if let Some(cache) = cache {
+        if let Err(error) = cache.update(mac_addr, addr, kind) {
+            log::warn!("{:#}", error);
+        }
+    }
+}
+
+/* returns a future that resolves to the next probe once the device has returned its address and
+   the probe itself */
+fn start_probe(
+    probe: Probe,
+    limits: &throttle::Limits,
+    timeouts: &timeouts::Configuration
+) -> (BoxFuture<'static, Option<Probe>>, BoxFuture<'static, Probed>) {
+    let (return_addr_tx, return_addr_rx) = oneshot::channel();
+    let jitter = PROBE_JITTER.mul_f64(rand::random::<f64>());
+    match probe {
+        Probe::Xbee(addr) => {
+            let timeout = timeouts.xbee.timeout;
+            let returned_addr = return_addr_rx
+                .map(|result| result.ok().map(Probe::Fernbedienung));
+            let probe = async move {
+                tokio::time::sleep(jitter).await;
+                Probed::Xbee(probe_xbee(return_addr_tx, addr, timeout).await)
+            };
+            (returned_addr.boxed(), probe.boxed())
+        },
+        Probe::Fernbedienung(addr) => {
+            let limits = limits.clone();
+            let timeout = timeouts.fernbedienung.timeout;
+            let returned_addr = return_addr_rx
+                .map(|result| result.ok().map(Probe::Xbee));
+            let probe = async move {
+                tokio::time::sleep(jitter).await;
+                Probed::Fernbedienung(probe_fernbedienung(return_addr_tx, addr, limits, timeout).await)
+            };
+            (returned_addr.boxed(), probe.boxed())
+        },
+    }
+}
+
 /// This function attempts to associate an xbee device with a given Ipv4Addr. The function starts the async 
-/// xbee::Device function `new` inside of a tokio::timeout which attempts the connection.
+/// xbee::Device function `new` inside of a tokio::timeout which attempts the connection. A probe is not
+/// retried, since the addresses at which no device was found are probed again.
 async fn probe_xbee(return_addr_tx: oneshot::Sender<Ipv4Addr>,
-                    addr: Ipv4Addr) -> anyhow::Result<(MacAddr6, xbee::Device)> {
-    /* assume address is an xbee and attempt to connect for 500 ms */
-    tokio::time::timeout(Duration::from_millis(500), async {
+                    addr: Ipv4Addr,
+                    timeout: Duration) -> anyhow::Result<(MacAddr6, xbee::Device)> {
+    /* assume address is an xbee and attempt to connect until the timeout */
+    tokio::time::timeout(timeout, async {
         let device = xbee::Device::new(addr, return_addr_tx).await?;
         let mac_addr = device.mac().await?;
         Ok((mac_addr, device))
@@ -81,10 +158,12 @@ async fn probe_xbee(return_addr_tx: oneshot::Sender<Ipv4Addr>,
 /// function starts the async fernbedienung::Device function `new` inside of a tokio::timeout which attempts
 /// the connection.
 async fn probe_fernbedienung(return_addr_tx: oneshot::Sender<Ipv4Addr>,
-                             addr: Ipv4Addr) -> anyhow::Result<(MacAddr6, fernbedienung::Device)> {
-    /* assume there is a fernbedienung instance running on `addr` and attempt to connect to it for 500 ms */
-    tokio::time::timeout(Duration::from_millis(500), async {
-        let device = fernbedienung::Device::new(addr, return_addr_tx).await?;
+                             addr: Ipv4Addr,
+                             limits: throttle::Limits,
+                             timeout: Duration) -> anyhow::Result<(MacAddr6, fernbedienung::Device)> {
+    /* assume there is a fernbedienung instance running on `addr` and attempt to connect to it until the timeout */
+    tokio::time::timeout(timeout, async {
+        let device = fernbedienung::Device::new(addr, limits, return_addr_tx).await?;
         let mac_addr = device.mac().await?;
         Ok((mac_addr, device))
     }).await?
@@ -0,0 +1,64 @@
+use std::{sync::{Arc, Mutex}, time::{Duration, Instant}};
+
+/* the rates at which data is transferred to and from the robots, the limiters are shared by all
+   connections since the robots share the same wireless network */
+#[derive(Clone, Debug, Default)]
+pub struct Limits {
+    pub upload: Option<RateLimiter>,
+    pub camera: Option<RateLimiter>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+/* a token bucket that holds at most one second worth of data, a transfer that is larger than the
+   available tokens is allowed to proceed once the tokens that it has borrowed have been refilled */
+#[derive(Clone, Debug)]
+pub struct RateLimiter {
+    /* bytes per second */
+    rate: f64,
+    bucket: Arc<Mutex<Bucket>>,
+}
+
+impl RateLimiter {
+    pub fn new(rate: f64) -> Self {
+        let bucket = Bucket { tokens: rate, updated: Instant::now() };
+        Self { rate, bucket: Arc::new(Mutex::new(bucket)) }
+    }
+
+    /* take the tokens without waiting, false is returned and no tokens are taken if there are not enough */
+    pub fn try_acquire(&self, amount: usize) -> bool {
+        let mut bucket = self.bucket.lock().unwrap();
+        let now = Instant::now();
+        let refill = now.duration_since(bucket.updated).as_secs_f64() * self.rate;
+        bucket.tokens = (bucket.tokens + refill).min(self.rate);
+        bucket.updated = now;
+        match bucket.tokens >= amount as f64 {
+            true => {
+                bucket.tokens -= amount as f64;
+                true
+            },
+            false => false,
+        }
+    }
+
+    pub async fn acquire(&self, bytes: usize) {
+        let delay = {
+            let mut bucket = self.bucket.lock().unwrap();
+            let now = Instant::now();
+            let refill = now.duration_since(bucket.updated).as_secs_f64() * self.rate;
+            bucket.tokens = (bucket.tokens + refill).min(self.rate) - bytes as f64;
+            bucket.updated = now;
+            match bucket.tokens < 0.0 {
+                true => Duration::from_secs_f64(-bucket.tokens / self.rate),
+                false => Duration::from_secs(0),
+            }
+        };
+        if delay > Duration::from_secs(0) {
+            tokio::time::sleep(delay).await;
+        }
+    }
+}
@@ -18,6 +18,9 @@ const SAMPLE_CMD_RESP_LEN: usize = 4;
 
 const MAX_RETRIES: usize = 3;
 
+/* the time to wait for responses in addition to the node discovery timeout (NT) */
+const DISCOVERY_MARGIN: Duration = Duration::from_secs(1);
+
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
     #[error(transparent)]
@@ -146,6 +149,45 @@ enum Request {
     SetParameter([u8; 2], BytesMut, bool),
     GetParameter([u8; 2], oneshot::Sender<Result<BytesMut>>),
     ApplyChanges,
+    /* each node that responds to a node discovery (ND) is forwarded until the deadline */
+    Discover(mpsc::UnboundedSender<BytesMut>, Instant),
+}
+
+/* a node that responded to a node discovery */
+#[derive(Debug, Clone)]
+pub struct Node {
+    pub mac: MacAddr6,
+    pub identifier: String,
+    /* the signal strength of the response in -dBm, only reported by some firmware versions */
+    pub rssi: Option<u8>,
+}
+
+impl TryFrom<BytesMut> for Node {
+    type Error = Error;
+
+    /* MY (2), SH (4), SL (4), NI (null terminated), parent (2), device type (1), status (1),
+       profile (2), manufacturer (2), followed by an optional RSSI (1) */
+    fn try_from(mut value: BytesMut) -> Result<Self> {
+        if value.len() < 10 {
+            return Err(Error::DecodeError);
+        }
+        value.advance(2);
+        /* the upper 16 bits of SH are not part of the MAC address */
+        let serial = value.split_to(8);
+        let mac = <[u8; 6]>::try_from(&serial[2..])
+            .map(MacAddr6::from)
+            .map_err(|_| Error::DecodeError)?;
+        let terminator = value.iter()
+            .position(|&byte| byte == 0)
+            .ok_or(Error::DecodeError)?;
+        let identifier = String::from_utf8_lossy(&value.split_to(terminator)).into_owned();
+        value.advance(1);
+        let rssi = match value.len() {
+            9 => value.last().cloned(),
+            _ => None,
+        };
+        Ok(Node { mac, identifier, rssi })
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -242,6 +284,7 @@ impl Device {
             let socket_addr = SocketAddr::new(addr.into(), 0xBEE);
             let mut framed = UdpFramed::new(socket, Codec);
             let mut remote_requests: HashMap<u8, RemoteRequest> = HashMap::new();
+            let mut discoveries: HashMap<u8, (Instant, mpsc::UnboundedSender<BytesMut>)> = HashMap::new();
             let maintain_remote_requests_task = tokio::time::sleep(Duration::from_millis(100));
             tokio::pin!(maintain_remote_requests_task);
             loop {
@@ -251,6 +294,9 @@ impl Device {
                         remote_requests.retain(|_, (_, callback, _, _)| {
                             callback.as_ref().map_or(false, |callback| !callback.is_closed())
                         });
+                        discoveries.retain(|_, (deadline, nodes_tx)| {
+                            *deadline > Instant::now() && !nodes_tx.is_closed()
+                        });
                         /* iterate over remote requests */
                         for (_, (timestamp, callback, command, retries)) in remote_requests.iter_mut() {
                             if timestamp.elapsed().as_millis() > 300 {
@@ -274,6 +320,12 @@ impl Device {
                                 if let Some((_, Some(callback), _, _)) = remote_requests.remove(&frame_id) {
                                     let _ = callback.send(Ok(data));
                                 }
+                                /* the final response to a node discovery is empty */
+                                else if let Some((_, nodes_tx)) = discoveries.get(&frame_id) {
+                                    if !data.is_empty() {
+                                        let _ = nodes_tx.send(data);
+                                    }
+                                }
                             }
                         }
                         Err(error) => if let Error::RemoteError{frame_id, status} = error {
@@ -302,10 +354,25 @@ impl Device {
                                 };
                                 let _ = framed.send((command, socket_addr)).await;
                             },
+                            Request::Discover(nodes_tx, deadline) => {
+                                /* node discoveries are not retried since they have more than one response */
+                                let unused_id = (1..u8::MAX).into_iter()
+                                    .find(|id| !remote_requests.contains_key(id) && !discoveries.contains_key(id));
+                                if let Some(unused_id) = unused_id {
+                                    let command = Command {
+                                        frame_id: unused_id,
+                                        queue: false,
+                                        at_command: [b'N', b'D'],
+                                        data: None
+                                    };
+                                    discoveries.insert(unused_id, (deadline, nodes_tx));
+                                    let _ = framed.send((command, socket_addr)).await;
+                                }
+                            },
                             Request::GetParameter(parameter, callback) => {
                                 /* find an unused key */
                                 let unused_id = (1..u8::MAX).into_iter()
-                                    .find(|id| !remote_requests.contains_key(id));
+                                    .find(|id| !remote_requests.contains_key(id) && !discoveries.contains_key(id));
                                 if let Some(unused_id) = unused_id {
                                     let command = Command {
                                         frame_id: unused_id,
@@ -366,6 +433,26 @@ impl Device {
         value.first().cloned().map(|state| state as i32).ok_or(Error::DecodeError)
     }
 
+    /* discover the other nodes on the network, this takes as long as the node discovery timeout (NT) */
+    pub async fn discover(&self) -> Result<Vec<Node>> {
+        let timeout = self.get_parameter([b'N', b'T']).await?
+            .iter()
+            .fold(0, |timeout, &byte| (timeout << 8) | byte as u64);
+        /* NT is in units of 100 ms */
+        let deadline = Instant::now() + Duration::from_millis(timeout * 100) + DISCOVERY_MARGIN;
+        let (nodes_tx, mut nodes_rx) = mpsc::unbounded_channel();
+        self.request_tx.send(Request::Discover(nodes_tx, deadline)).await
+            .map_err(|_| Error::RequestFailed)?;
+        let mut nodes = Vec::new();
+        while let Some(node) = nodes_rx.recv().await {
+            match Node::try_from(node) {
+                Ok(node) => nodes.push(node),
+                Err(error) => log::warn!("Could not decode node discovery response: {}", error),
+            }
+        }
+        Ok(nodes)
+    }
+
     pub async fn pin_states(&self) -> Result<HashMap<Pin, bool>> {
         let (response_tx, response_rx) = oneshot::channel();
         let request = Request::GetParameter([b'I',b'S'], response_tx);
@@ -496,6 +583,33 @@ impl Device {
         }
     }
 
+    pub async fn get_parameter(&self, parameter: [u8; 2]) -> Result<BytesMut> {
+        let (response_tx, response_rx) = oneshot::channel();
+        let request = Request::GetParameter(parameter, response_tx);
+        self.request_tx.send(request).await.map_err(|_| Error::RequestFailed)?;
+        response_rx.await.map_err(|_| Error::NoResponse)?
+    }
+
+    /* note: write-only parameters such as the encryption key (KY) can not be read back */
+    pub async fn set_parameter(&self, parameter: [u8; 2], value: BytesMut) -> Result<()> {
+        self.request_tx.send(Request::SetParameter(parameter, value, false)).await
+            .map_err(|_| Error::RequestFailed)
+    }
+
+    /* write the current parameters to non-volatile memory so that they persist after a reset */
+    pub async fn write_parameters(&self) -> Result<()> {
+        self.request_tx.send(Request::SetParameter([b'W', b'R'], BytesMut::new(), false)).await
+            .map_err(|_| Error::RequestFailed)
+    }
+
+    pub async fn firmware_version(&self) -> Result<u32> {
+        let value = self.get_parameter([b'V', b'R']).await?;
+        match value.len() {
+            1..=4 => Ok(value.iter().fold(0, |version, &byte| (version << 8) | byte as u32)),
+            _ => Err(Error::DecodeError)
+        }
+    }
+
     pub async fn set_baud_rate(&self, baud_rate: u32) -> Result<()> {
         self.request_tx.send(Request::SetParameter(
             [b'B', b'D'],
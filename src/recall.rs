@@ -0,0 +1,107 @@
+use std::time::Duration;
+use tokio::sync::{broadcast::error::RecvError, mpsc, oneshot};
+use shared::tracking_system::Update;
+
+use crate::{router::{self, LuaType}, tracking};
+
+/* how often the pose and the target are sent to the controller of a Pi-Puck */
+const INTERVAL: Duration = Duration::from_millis(200);
+/* a Pi-Puck has reached its dock once it is within this distance (in meters) */
+const TOLERANCE: f32 = 0.03;
+/* a Pi-Puck that has not come closer to its dock by this distance (in meters) within the stall
+   timeout is considered to be stuck */
+const PROGRESS: f32 = 0.05;
+const STALL_TIMEOUT: Duration = Duration::from_secs(20);
+/* the time that a Pi-Puck has to reach its dock */
+const TIMEOUT: Duration = Duration::from_secs(120);
+
+fn recall_table(id: &str, recall: Vec<(LuaType, LuaType)>) -> Vec<(LuaType, LuaType)> {
+    vec![
+        (LuaType::String(String::from("fromS")), LuaType::String(String::from("supervisor"))),
+        (LuaType::String(String::from("toS")), LuaType::String(id.to_owned())),
+        (LuaType::String(String::from("recallT")), LuaType::Table(recall)),
+    ]
+}
+
+/* the current pose of the Pi-Puck and the position of its dock */
+fn drive(id: &str, update: &Update, target: [f32; 2]) -> router::Action {
+    let [x, y, z] = update.position;
+    let [w, i, j, k] = update.orientation;
+    let recall = vec![
+        (LuaType::String(String::from("positionV3")),
+         LuaType::Vector3(x as f64, y as f64, z as f64)),
+        (LuaType::String(String::from("orientationQ")),
+         LuaType::Quaternion(w as f64, i as f64, j as f64, k as f64)),
+        (LuaType::String(String::from("targetV3")),
+         LuaType::Vector3(target[0] as f64, target[1] as f64, z as f64)),
+    ];
+    router::Action::Send { table: recall_table(id, recall), to: id.to_owned() }
+}
+
+fn stop(id: &str) -> router::Action {
+    let recall = vec![(LuaType::String(String::from("stopB")), LuaType::Boolean(true))];
+    router::Action::Send { table: recall_table(id, recall), to: id.to_owned() }
+}
+
+/// This function drives a Pi-Puck back to the position of its dock. The controller on the Pi-Puck is
+/// sent the pose of its rigid body and the position of its dock over the message router and is
+/// expected to drive towards the dock. The Pi-Puck is told to stop once it has reached the dock, if it
+/// stops making progress, or if it does not reach the dock in time.
+pub async fn pipuck(
+    id: String,
+    optitrack_id: i32,
+    target: [f32; 2],
+    tracking_tx: mpsc::Sender<tracking::Action>,
+    router_tx: mpsc::Sender<router::Action>,
+) -> anyhow::Result<()> {
+    let (callback_tx, callback_rx) = oneshot::channel();
+    tracking_tx.send(tracking::Action::SubscribeRigidBody(optitrack_id, callback_tx)).await
+        .map_err(|_| anyhow::anyhow!("Could not communicate with tracking system"))?;
+    let mut poses = callback_rx.await
+        .map_err(|_| anyhow::anyhow!("Could not subscribe to rigid body {}", optitrack_id))?;
+    let mut latest: Option<Update> = None;
+    /* the closest that the Pi-Puck has come to its dock and when it came this close */
+    let mut closest: Option<(f32, tokio::time::Instant)> = None;
+    let started = tokio::time::Instant::now();
+    let mut interval = tokio::time::interval(INTERVAL);
+    let timeout = tokio::time::sleep(TIMEOUT);
+    tokio::pin!(timeout);
+    let result = loop {
+        tokio::select! {
+            pose = poses.recv() => match pose {
+                Ok(update) => latest = Some(update),
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => break Err(anyhow::anyhow!("Tracking system is not available")),
+            },
+            _ = interval.tick() => {
+                let now = tokio::time::Instant::now();
+                let update = match latest.as_ref() {
+                    Some(update) => update,
+                    /* wait for the first pose */
+                    None if now.duration_since(started) < STALL_TIMEOUT => continue,
+                    None => break Err(anyhow::anyhow!("Rigid body {} is not visible", optitrack_id)),
+                };
+                let distance = (update.position[0] - target[0]).hypot(update.position[1] - target[1]);
+                if distance < TOLERANCE {
+                    break Ok(());
+                }
+                match closest {
+                    Some((closest_distance, since)) if closest_distance - distance < PROGRESS => {
+                        if now.duration_since(since) > STALL_TIMEOUT {
+                            break Err(anyhow::anyhow!("No progress towards the dock for {:?} ({:.2} m away)",
+                                STALL_TIMEOUT, distance));
+                        }
+                    },
+                    _ => closest = Some((distance, now)),
+                }
+                if router_tx.send(drive(&id, update, target)).await.is_err() {
+                    break Err(anyhow::anyhow!("Could not communicate with router"));
+                }
+            },
+            _ = &mut timeout => break Err(anyhow::anyhow!("Did not reach the dock within {:?}", TIMEOUT)),
+        }
+    };
+    /* stop the Pi-Puck in any case */
+    let _ = router_tx.send(stop(&id)).await;
+    result
+}
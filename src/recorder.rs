@@ -0,0 +1,162 @@
+use anyhow::Context;
+use bytes::Bytes;
+use chrono::{DateTime, Local};
+use futures::StreamExt;
+use shared::experiment::State;
+use std::{collections::HashMap, path::{Path, PathBuf}, sync::{Arc, atomic::{AtomicU64, Ordering}}};
+use tokio::sync::{mpsc, oneshot};
+use tokio_stream::{StreamMap, wrappers::ReceiverStream};
+
+use crate::{arena, subscription, robot::{self, drone, pipuck, FernbedienungAction}};
+
+#[derive(Debug)]
+pub struct Configuration {
+    /* the directory in which the frames of each experiment are recorded */
+    pub directory: PathBuf,
+}
+
+/* each frame from the mjpg-streamer is a complete JPEG image, the frames are stored under
+   <experiment>/<robot>/<camera>/<milliseconds since the start of the experiment>.jpg */
+async fn write_frame(
+    experiment: &Path,
+    start: DateTime<Local>,
+    robot: &str,
+    camera: &str,
+    frame: Bytes
+) -> anyhow::Result<()> {
+    let camera = Path::new(camera).file_name()
+        .and_then(|camera| camera.to_str())
+        .unwrap_or(camera);
+    let directory = experiment.join(robot).join(camera);
+    tokio::fs::create_dir_all(&directory).await
+        .context(format!("Could not create {:?}", directory))?;
+    let timestamp = Local::now().signed_duration_since(start).num_milliseconds();
+    let path = directory.join(format!("{:08}.jpg", timestamp));
+    tokio::fs::write(&path, frame).await
+        .context(format!("Could not write {:?}", path))
+}
+
+/* the frames are written to disk, which can be slower than the cameras, so the recorder can buffer
+   a few seconds of frames from each robot before they are dropped */
+const FRAME_QUEUE_CAPACITY: usize = 64;
+
+/* the camera streams of all robots are enabled when an experiment starts so that there are frames
+   to record, they are left enabled when the experiment stops */
+async fn enable_cameras(
+    arena_tx: &mpsc::Sender<arena::Action>,
+    drones: &[String],
+    pipucks: &[String],
+) {
+    for id in drones {
+        let (callback_tx, _) = oneshot::channel();
+        let action = drone::Action::ExecuteFernbedienungAction(callback_tx, FernbedienungAction::SetCameraStream(true));
+        let _ = arena_tx.send(arena::Action::ForwardDroneAction(id.clone(), action)).await;
+    }
+    for id in pipucks {
+        let (callback_tx, _) = oneshot::channel();
+        let action = pipuck::Action::ExecuteFernbedienungAction(callback_tx, FernbedienungAction::SetCameraStream(true));
+        let _ = arena_tx.send(arena::Action::ForwardPiPuckAction(id.clone(), action)).await;
+    }
+}
+
+/* unlike the web interface, which only shows the most recent frame, the recorder receives every frame
+   via a bounded queue for each robot */
+async fn subscribe_frames(
+    arena_tx: &mpsc::Sender<arena::Action>,
+    drones: &[String],
+    pipucks: &[String],
+) -> anyhow::Result<(StreamMap<String, ReceiverStream<robot::QueuedFrame>>, HashMap<String, Arc<AtomicU64>>)> {
+    let mut frames = StreamMap::new();
+    let mut overruns = HashMap::new();
+    for id in drones {
+        let (queue, queue_rx) = robot::frame_queue(FRAME_QUEUE_CAPACITY);
+        overruns.insert(id.clone(), queue.overruns.clone());
+        arena_tx.send(arena::Action::ForwardDroneAction(id.clone(), drone::Action::SubscribeFrameQueue(queue))).await
+            .map_err(|_| anyhow::anyhow!("Could not communicate with arena"))?;
+        frames.insert(id.clone(), ReceiverStream::new(queue_rx));
+    }
+    for id in pipucks {
+        let (queue, queue_rx) = robot::frame_queue(FRAME_QUEUE_CAPACITY);
+        overruns.insert(id.clone(), queue.overruns.clone());
+        arena_tx.send(arena::Action::ForwardPiPuckAction(id.clone(), pipuck::Action::SubscribeFrameQueue(queue))).await
+            .map_err(|_| anyhow::anyhow!("Could not communicate with arena"))?;
+        frames.insert(id.clone(), ReceiverStream::new(queue_rx));
+    }
+    Ok((frames, overruns))
+}
+
+pub async fn new(
+    config: Option<Configuration>,
+    arena_tx: mpsc::Sender<arena::Action>
+) -> anyhow::Result<()> {
+    let config = match config {
+        Some(config) => config,
+        /* recording is disabled, never complete */
+        None => return futures::future::pending().await,
+    };
+    let (callback_tx, callback_rx) = oneshot::channel();
+    arena_tx.send(arena::Action::GetDroneDescriptors(callback_tx)).await
+        .map_err(|_| anyhow::anyhow!("Could not communicate with arena"))?;
+    let drones = callback_rx.await
+        .context("Could not get drone descriptors")?
+        .into_iter()
+        .map(|desc| desc.id.clone())
+        .collect::<Vec<_>>();
+    let (callback_tx, callback_rx) = oneshot::channel();
+    arena_tx.send(arena::Action::GetPiPuckDescriptors(callback_tx)).await
+        .map_err(|_| anyhow::anyhow!("Could not communicate with arena"))?;
+    let pipucks = callback_rx.await
+        .context("Could not get Pi-Puck descriptors")?
+        .into_iter()
+        .map(|desc| desc.id.clone())
+        .collect::<Vec<_>>();
+    let (mut frames, overruns) = subscribe_frames(&arena_tx, &drones, &pipucks).await?;
+    let (callback_tx, callback_rx) = oneshot::channel();
+    arena_tx.send(arena::Action::SubscribeExperimentState(callback_tx)).await
+        .map_err(|_| anyhow::anyhow!("Could not communicate with arena"))?;
+    let mut experiment_updates = callback_rx.await
+        .map(subscription::updates)
+        .map_err(|_| anyhow::anyhow!("Could not subscribe to experiment updates"))?;
+    /* the directory and start time of the experiment that is being recorded */
+    let mut recording: Option<(PathBuf, DateTime<Local>)> = None;
+    loop {
+        let (robot, camera, frame) = tokio::select! {
+            Some((robot, (camera, frame))) = frames.next() => match frame.as_ref() {
+                Ok(frame) => (robot, camera, frame.clone()),
+                Err(_) => continue,
+            },
+            Some(update) = experiment_updates.next() => {
+                match update {
+                    Ok(State::Active) if recording.is_none() => {
+                        let start = Local::now();
+                        let directory = config.directory.join(start.format("%Y%m%d-%H%M%S").to_string());
+                        log::info!("Recording camera frames to {:?}", directory);
+                        recording = Some((directory, start));
+                        /* only the frames that are dropped during a recording are reported */
+                        for overruns in overruns.values() {
+                            overruns.store(0, Ordering::Relaxed);
+                        }
+                        enable_cameras(&arena_tx, &drones, &pipucks).await;
+                    },
+                    Ok(State::Standby) => if let Some((directory, _)) = recording.take() {
+                        log::info!("Stopped recording camera frames to {:?}", directory);
+                    },
+                    _ => {}
+                }
+                continue;
+            },
+            else => break,
+        };
+        if let Some((directory, start)) = recording.as_ref() {
+            let skipped = overruns.get(&robot)
+                .map_or(0, |overruns| overruns.swap(0, Ordering::Relaxed));
+            if skipped > 0 {
+                log::warn!("Could not record {} frames from {} since the recorder did not keep up", skipped, robot);
+            }
+            if let Err(error) = write_frame(directory, *start, &robot, &camera, frame).await {
+                log::warn!("Could not record frame from {} of {}: {}", camera, robot, error);
+            }
+        }
+    }
+    Ok(())
+}
@@ -11,13 +11,22 @@ pub struct Instance {
     _task: JoinHandle<()>
 }
 
-impl Default for Instance {
-    fn default() -> Self {
+impl Instance {
+    /* cameras are the cameras on the robot that can be streamed, capacity is the number of updates
+       that a subscriber can fall behind before it misses updates */
+    pub fn new(cameras: Vec<shared::camera::Configuration>, capacity: usize) -> Self {
         let (action_tx, action_rx) = mpsc::channel(8);
-        let _task = tokio::spawn(task::new(action_rx));
+        let _task = tokio::spawn(task::new(action_rx, cameras, capacity));
         Self { 
             action_tx,
             _task
         }
     }
+}
+
+/* stop the task when the instance is dropped, e.g., when the configuration is reloaded */
+impl Drop for Instance {
+    fn drop(&mut self) {
+        self._task.abort();
+    }
 }
\ No newline at end of file
@@ -1,14 +1,16 @@
-use std::{net::SocketAddr, time::Duration};
+use std::{net::SocketAddr, sync::Arc, time::Duration};
 use anyhow::Context;
 use bytes::BytesMut;
 use tokio::{net::UdpSocket, sync::{broadcast, mpsc, oneshot}};
-use futures::{FutureExt, Stream, StreamExt, TryStreamExt};
+use futures::{FutureExt, SinkExt, Stream, StreamExt, TryStreamExt};
 use tokio_stream::{self, wrappers::ReceiverStream};
 use tokio_util::sync::PollSender;
 
-use crate::network::{fernbedienung, fernbedienung_ext::MjpegStreamerStream};
-use crate::robot::{FernbedienungAction, TerminalAction};
+use crate::network::{fernbedienung, fernbedienung_ext::{self, MjpegStreamerStream}};
+use crate::robot::{FernbedienungAction, FrameReceivers, FrameSenders, RestartPolicy, TerminalAction};
 use crate::journal;
+use crate::subscription::Subscription;
+use shared::{camera, snapshot};
 
 pub use shared::{
     builderbot::{Descriptor, Update},
@@ -19,16 +21,27 @@ const IDENTIFY_BUILDERBOT_ARGOS: (&'static str, &'static [u8]) =
     ("identify_builderbot.argos", include_bytes!("identify_builderbot.argos"));
 const IDENTIFY_BUILDERBOT_LUA: (&'static str, &'static [u8]) = 
     ("identify_builderbot.lua", include_bytes!("identify_builderbot.lua"));
+const SPIN_BUILDERBOT_ARGOS: (&str, &[u8]) = 
+    ("spin_builderbot.argos", include_bytes!("spin_builderbot.argos"));
+const SPIN_BUILDERBOT_LUA: (&str, &[u8]) = 
+    ("spin_builderbot.lua", include_bytes!("spin_builderbot.lua"));
 
-const BUILDERBOT_CAMERAS_CONFIG: &[(&str, u16, u16, u16)] = &[];
+/* how long to wait for a stream to release a camera before capturing an image */
+const CAMERA_RELEASE_DELAY: Duration = Duration::from_millis(500);
+const CAPTURE_IMAGE_TIMEOUT: Duration = Duration::from_secs(10);
+const KERNEL_MESSAGES_TIMEOUT: Duration = Duration::from_secs(5);
 
 #[derive(Debug)]
 pub enum Action {
     AssociateFernbedienung(fernbedienung::Device),
     ExecuteFernbedienungAction(oneshot::Sender<anyhow::Result<()>>, FernbedienungAction),
-    Subscribe(oneshot::Sender<broadcast::Receiver<Update>>),
+    Subscribe(oneshot::Sender<Subscription<Update>>),
+    /* the frames of the cameras are not sent with the updates since only the most recent frame is kept */
+    SubscribeFrames(oneshot::Sender<FrameReceivers>),
+    /* the connections, battery level, and software of the robot */
+    GetState(oneshot::Sender<snapshot::Robot>),
     // its good to keep this one seperate since start exp need to interact with xbee and fernbedienung
-    SetupExperiment(oneshot::Sender<anyhow::Result<()>>, String, Software, mpsc::Sender<journal::Action>),
+    SetupExperiment(oneshot::Sender<anyhow::Result<()>>, String, Software, mpsc::Sender<journal::Action>, RestartPolicy),
     StartExperiment(oneshot::Sender<anyhow::Result<()>>),
     StopExperiment,
 }
@@ -130,6 +143,7 @@ async fn bash(
 }
 
 async fn argos(device: &fernbedienung::Device,
+    updates_tx: broadcast::Sender<Update>,
     callback: oneshot::Sender<anyhow::Result<()>>,
     software: Software,
     id: impl Into<Option<String>>,
@@ -137,6 +151,7 @@ async fn argos(device: &fernbedienung::Device,
     journal: impl Into<Option<mpsc::Sender<journal::Action>>>,
     wait_rx: impl Into<Option<oneshot::Receiver<()>>>,
     stop_rx: oneshot::Receiver<()>,
+    restart_policy: RestartPolicy,
 ) {
     let id = id.into();
     let router_socket = router_socket.into();
@@ -162,7 +177,11 @@ async fn argos(device: &fernbedienung::Device,
     };
     /* upload the control software */
     for (filename, contents) in software.0.iter() {
-        match device.upload(&path, filename, contents.clone()).await {
+        let upload = fernbedienung_ext::upload_with_progress(device, &path, filename, contents.clone(),
+            |sent, total| {
+                let _ = updates_tx.send(Update::Upload { filename: filename.clone(), sent, total });
+            });
+        match upload.await {
             Ok(_) => continue,
             Err(error) => {
                 let result = Err(error).context("Could not upload software");
@@ -194,54 +213,78 @@ async fn argos(device: &fernbedienung::Device,
     let mut args = vec!["--config".to_owned(), config.to_owned()];
     args.extend(router_socket.into_iter().flat_map(|socket| vec!["--router".to_owned(), socket.to_string()]));
     args.extend(id.iter().flat_map(|id| vec!["--id".to_owned(), id.clone()]));
-    let process = fernbedienung::Process {
-        target: "argos3".into(),
-        working_dir: Some(path.into()),
-        args,
-    };
-    let (stdout_tx, mut forward_stdout, stderr_tx, mut forward_stderr) = match (journal, id) {
-        (Some(journal), Some(id)) => {
-            use journal::{ARGoS, Event, Action};
-            let (stdout_tx, stdout_rx) = mpsc::channel(8);
-            let (stderr_tx, stderr_rx) = mpsc::channel(8);
-            let stdout_stream = ReceiverStream::new(stdout_rx);
-            let stderr_stream = ReceiverStream::new(stderr_rx);
-            let journal_sink = PollSender::new(journal.clone());
-            let stdout_robot_id = id.clone();
-            let forward_stdout = stdout_stream.map(move |data: BytesMut| 
-                Ok(Action::Record(Event::ARGoS(stdout_robot_id.clone(), ARGoS::StandardOutput(data)))))
-                    .forward(journal_sink).right_future();
-            let journal_sink = PollSender::new(journal);
-            let forward_stderr = stderr_stream.map(move |data: BytesMut| 
-                Ok(Action::Record(Event::ARGoS(id.clone(), ARGoS::StandardError(data)))))
-                    .forward(journal_sink).right_future();
-            (Some(stdout_tx), forward_stdout, Some(stderr_tx), forward_stderr)
-        },
-        (_, _) => {
-            (None, futures::future::pending().left_future(),
-             None, futures::future::pending().left_future())
-        }
-    };
-    let (terminate_tx, terminate_rx) = oneshot::channel();      
-    let argos = device.run(process, terminate_rx, None, stdout_tx, stderr_tx);
-    tokio::pin!(argos);
+    let mut restarts = 0;
     loop {
-        tokio::select! {
-            _ = &mut forward_stdout => {
-                /* disable while we wait for the other futures to finish */
-                forward_stdout = futures::future::pending().left_future();
+        let process = fernbedienung::Process {
+            target: "argos3".into(),
+            working_dir: Some(path.clone().into()),
+            args: args.clone(),
+        };
+        let (stdout_tx, mut forward_stdout, stderr_tx, mut forward_stderr) = match (journal.as_ref(), id.as_ref()) {
+            (Some(journal), Some(id)) => {
+                use journal::{ARGoS, Event, Action};
+                let (stdout_tx, stdout_rx) = mpsc::channel(8);
+                let (stderr_tx, stderr_rx) = mpsc::channel(8);
+                let stdout_stream = ReceiverStream::new(stdout_rx);
+                let stderr_stream = ReceiverStream::new(stderr_rx);
+                let journal_sink = PollSender::new(journal.clone()).sink_map_err(|_| ());
+                let stdout_robot_id = id.clone();
+                let forward_stdout = stdout_stream.map(move |data: BytesMut| 
+                    Ok(Action::Record(Event::ARGoS(stdout_robot_id.clone(), ARGoS::StandardOutput(data)))))
+                        .forward(journal_sink).right_future();
+                let journal_sink = PollSender::new(journal.clone()).sink_map_err(|_| ());
+                let stderr_robot_id = id.clone();
+                let forward_stderr = stderr_stream.map(move |data: BytesMut| 
+                    Ok(Action::Record(Event::ARGoS(stderr_robot_id.clone(), ARGoS::StandardError(data)))))
+                        .forward(journal_sink).right_future();
+                (Some(stdout_tx), forward_stdout, Some(stderr_tx), forward_stderr)
             },
-            _ = &mut forward_stderr => {
-                /* disable while we wait for the other futures to finish */
-                forward_stderr = futures::future::pending().left_future();
-            },
-            /* local shutdown */
-            _ = &mut stop_rx => {
-                let _ = terminate_tx.send(());
-                break;
+            (_, _) => {
+                (None, futures::future::pending().left_future(),
+                 None, futures::future::pending().left_future())
             }
-            /* argos finished */
-            _ = &mut argos => break,
+        };
+        let (terminate_tx, terminate_rx) = oneshot::channel();      
+        let argos = device.run(process, terminate_rx, None, stdout_tx, stderr_tx);
+        tokio::pin!(argos);
+        let result = loop {
+            tokio::select! {
+                _ = &mut forward_stdout => {
+                    /* disable while we wait for the other futures to finish */
+                    forward_stdout = futures::future::pending().left_future();
+                },
+                _ = &mut forward_stderr => {
+                    /* disable while we wait for the other futures to finish */
+                    forward_stderr = futures::future::pending().left_future();
+                },
+                /* local shutdown */
+                _ = &mut stop_rx => {
+                    let _ = terminate_tx.send(());
+                    return;
+                }
+                /* argos finished */
+                result = &mut argos => break result,
+            }
+        };
+        /* restart ARGoS with the same arguments if it exited with an error and the policy allows it */
+        match (result, restart_policy) {
+            (Err(error), RestartPolicy::OnFailure { max_retries }) if restarts < max_retries => {
+                restarts += 1;
+                let robot = id.as_deref().unwrap_or("robot");
+                let message = format!("Restarting ARGoS on {} ({} of {}): {}", robot, restarts, max_retries, error);
+                log::warn!("{}", message);
+                if let Some(journal) = journal.as_ref() {
+                    let event = shared::event::Event {
+                        timestamp: chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+                        severity: shared::event::Severity::Warning,
+                        robot: id.clone(),
+                        subsystem: String::from("argos"),
+                        message,
+                    };
+                    let _ = journal.send(journal::Action::Record(journal::Event::Arena(event))).await;
+                }
+            },
+            _ => break,
         }
     }
 }
@@ -249,7 +292,9 @@ async fn argos(device: &fernbedienung::Device,
 async fn fernbedienung(
     device: fernbedienung::Device,
     mut rx: mpsc::Receiver<(oneshot::Sender<anyhow::Result<()>>, FernbedienungAction)>,
-    updates_tx: broadcast::Sender<Update>
+    updates_tx: broadcast::Sender<Update>,
+    cameras: Vec<camera::Configuration>,
+    frame_txs: FrameSenders,
 ) {
     /* ARGos task */
     let argos_task = futures::future::pending().left_future();
@@ -260,12 +305,20 @@ async fn fernbedienung(
     let (mut bash_tx, bash_rx) = mpsc::channel(8);
     let bash_task = bash(&device, bash_rx, updates_tx.clone());
     tokio::pin!(bash_task);
+    /* maintenance task */
+    let maintenance_task = futures::future::pending().left_future();
+    let mut maintenance_callbacks = Option::default();
+    tokio::pin!(maintenance_task);
     /* link strength stream */
     let link_strength_stream = fernbedienung_link_strength_stream(&device)
         .map_ok(Update::FernbedienungSignal);
     let link_strength_stream_throttled =
         tokio_stream::StreamExt::throttle(link_strength_stream, Duration::from_millis(1000));
     tokio::pin!(link_strength_stream_throttled);
+    /* temperature stream */
+    let temperature_stream = fernbedienung_ext::temperature_stream(&device)
+        .map(Update::Temperature);
+    tokio::pin!(temperature_stream);
     /* camera stream */
     let mut cameras_stream: tokio_stream::StreamMap<String, _> =
         tokio_stream::StreamMap::new();
@@ -274,7 +327,9 @@ async fn fernbedienung(
         tokio::select! {
             Some((camera, result)) = cameras_stream.next() => {
                 let result: reqwest::Result<bytes::Bytes> = result;
-                let update = Update::Camera { camera, result: result.map_err(|e| e.to_string()) };
+                frame_txs.send(&camera, Some(Arc::new(result.map_err(|e| e.to_string()))));
+            },
+            Some(update) = temperature_stream.next() => {
                 let _ = updates_tx.send(update);
             },
             Some(response) = link_strength_stream_throttled.next() => match response {
@@ -290,15 +345,66 @@ async fn fernbedienung(
                 Some((callback, action)) => match action {
                     FernbedienungAction::SetCameraStream(enable) => {
                         cameras_stream.clear();
+                        frame_txs.clear();
                         if enable {
-                            for &(camera, width, height, port) in BUILDERBOT_CAMERAS_CONFIG {
-                                let stream = MjpegStreamerStream::new(&device, camera, width, height, port);
-                                let stream = tokio_stream::StreamExt::throttle(stream, Duration::from_millis(200));
-                                cameras_stream.insert(camera.to_owned(), Box::pin(stream));
+                            for camera in &cameras {
+                                let stream = MjpegStreamerStream::new(&device, camera);
+                                cameras_stream.insert(camera.device.clone(), Box::pin(stream));
                             }
                         }
                         let _ = callback.send(Ok(()));
                     },
+                    FernbedienungAction::SetCameraDeviceStream(camera, enable) => {
+                        let result = match cameras.iter().find(|config| config.device == camera) {
+                            Some(config) => {
+                                if enable {
+                                    let stream = MjpegStreamerStream::new(&device, config);
+                                    cameras_stream.insert(camera, Box::pin(stream));
+                                }
+                                else {
+                                    cameras_stream.remove(&camera);
+                                    frame_txs.send(&camera, None);
+                                }
+                                Ok(())
+                            },
+                            None => Err(anyhow::anyhow!("Camera {} is not configured", camera)),
+                        };
+                        let _ = callback.send(result);
+                    },
+                    FernbedienungAction::GetKernelMessages(messages_tx) => {
+                        let result = tokio::time::timeout(KERNEL_MESSAGES_TIMEOUT, device.kernel_messages()).await
+                            .context("Timeout while retrieving kernel messages")
+                            .and_then(|result| result.context("Could not retrieve kernel messages"))
+                            .map(|messages| {
+                                let _ = messages_tx.send(messages);
+                            });
+                        let _ = callback.send(result);
+                    },
+                    FernbedienungAction::CaptureImage(camera, image_tx) => {
+                        let result = match cameras.iter().find(|config| config.device == camera) {
+                            Some(config) => {
+                                /* stop the stream while the image is captured so that the device is available */
+                                let streaming = cameras_stream.remove(&camera).is_some();
+                                if streaming {
+                                    tokio::time::sleep(CAMERA_RELEASE_DELAY).await;
+                                }
+                                let capture = fernbedienung_ext::capture_image(&device, config);
+                                let result = tokio::time::timeout(CAPTURE_IMAGE_TIMEOUT, capture).await
+                                    .context("Timeout while capturing image")
+                                    .and_then(|result| result.context("Could not capture image"))
+                                    .map(|image| {
+                                        let _ = image_tx.send(image);
+                                    });
+                                if streaming {
+                                    let stream = MjpegStreamerStream::new(&device, config);
+                                    cameras_stream.insert(camera, Box::pin(stream));
+                                }
+                                result
+                            },
+                            None => Err(anyhow::anyhow!("Camera {} is not configured", camera)),
+                        };
+                        let _ = callback.send(result);
+                    },
                     FernbedienungAction::Halt => {
                         let result = device.halt().await
                             .context("Could not halt DuoVero");
@@ -320,7 +426,10 @@ async fn fernbedienung(
                             anyhow::anyhow!("Could not send {:?} to Bash terminal: channel is {}", action, reason);
                         let _ = callback.send(Err(error));
                     },
-                    FernbedienungAction::SetupExperiment(id, software, journal) => match argos_stop_tx.as_ref() {
+                    FernbedienungAction::SetupExperiment(..) if maintenance_callbacks.is_some() => {
+                        let _ = callback.send(Err(anyhow::anyhow!("Maintenance script is running")));
+                    },
+                    FernbedienungAction::SetupExperiment(id, software, journal, restart_policy) => match argos_stop_tx.as_ref() {
                         Some(_) => {
                             let _ = callback.send(Err(anyhow::anyhow!("ARGoS is already setup or running")));
                         }
@@ -343,13 +452,15 @@ async fn fernbedienung(
                                     let (stop_tx, stop_rx) = oneshot::channel();
                                     let task = argos(
                                         &device,
+                                        updates_tx.clone(),
                                         callback,
                                         software,
                                         id,
                                         local_addr,
                                         journal,
                                         start_rx,
-                                        stop_rx);
+                                        stop_rx,
+                                        restart_policy);
                                     argos_task.set(task.left_future().right_future());
                                     argos_start_tx = Some(start_tx);
                                     argos_stop_tx = Some(stop_tx);
@@ -375,15 +486,21 @@ async fn fernbedienung(
                             let _ = callback.send(Err(anyhow::anyhow!("Experiment has not been set up")));
                         }
                     },
-                    FernbedienungAction::Identify => match argos_stop_tx.as_ref() {
+                    FernbedienungAction::Identify | FernbedienungAction::Spin => match argos_stop_tx.as_ref() {
                         Some(_) => {
                             let _ = callback.send(Err(anyhow::anyhow!("ARGoS is already running")));
                         }
                         None => {
-                            let software = Software(vec![
-                                (IDENTIFY_BUILDERBOT_ARGOS.0.to_owned(), IDENTIFY_BUILDERBOT_ARGOS.1.to_vec()),
-                                (IDENTIFY_BUILDERBOT_LUA.0.to_owned(), IDENTIFY_BUILDERBOT_LUA.1.to_vec())
-                            ]);
+                            let software = match action {
+                                FernbedienungAction::Spin => Software(vec![
+                                    (SPIN_BUILDERBOT_ARGOS.0.to_owned(), SPIN_BUILDERBOT_ARGOS.1.to_vec()),
+                                    (SPIN_BUILDERBOT_LUA.0.to_owned(), SPIN_BUILDERBOT_LUA.1.to_vec())
+                                ]),
+                                _ => Software(vec![
+                                    (IDENTIFY_BUILDERBOT_ARGOS.0.to_owned(), IDENTIFY_BUILDERBOT_ARGOS.1.to_vec()),
+                                    (IDENTIFY_BUILDERBOT_LUA.0.to_owned(), IDENTIFY_BUILDERBOT_LUA.1.to_vec())
+                                ]),
+                            };
                             match software.check_config() {
                                 Err(error) => {
                                     let _ = callback.send(Err(error).context("Identify software error"));
@@ -392,13 +509,27 @@ async fn fernbedienung(
                                     let (start_tx, start_rx) = oneshot::channel();
                                     start_tx.send(()).unwrap();
                                     let (stop_tx, stop_rx) = oneshot::channel();
-                                    let task = argos(&device, callback, software, None, None, None, start_rx, stop_rx);
+                                    let task = argos(&device, updates_tx.clone(), callback, software, None, None, None, start_rx, stop_rx, RestartPolicy::Never);
                                     argos_task.set(task.right_future().right_future());
                                     argos_stop_tx = Some(stop_tx);
                                 }
                             }
                         }
                     },
+                    FernbedienungAction::UploadPixhawkFirmware(_) => {
+                        let _ = callback.send(Err(anyhow::anyhow!("BuilderBots do not have a Pixhawk")));
+                    },
+                    FernbedienungAction::RunScript(script, timeout, output_tx) => match maintenance_callbacks {
+                        Some(_) => {
+                            let _ = callback.send(Err(anyhow::anyhow!("Maintenance script is already running")));
+                        },
+                        None => {
+                            /* the callback is answered once the script completes */
+                            let task = fernbedienung_ext::run_script(&device, script, timeout);
+                            maintenance_task.set(task.right_future());
+                            maintenance_callbacks = Some((callback, output_tx));
+                        }
+                    },
                 },
                 None => break,
             },
@@ -414,29 +545,86 @@ async fn fernbedienung(
                 argos_start_tx = None;
                 argos_stop_tx = None;
             },
+            result = &mut maintenance_task => {
+                if let Some((callback, output_tx)) = maintenance_callbacks.take() {
+                    let result = result.map(|output| {
+                        let _ = output_tx.send(output);
+                    });
+                    let _ = callback.send(result);
+                }
+                maintenance_task.set(futures::future::pending().left_future());
+            },
         }
     }
 }
 
-pub async fn new(mut action_rx: Receiver) {
+/* keep track of the state of the robot from the updates that are sent to the subscribers */
+fn update_state(state: &mut snapshot::Robot, update: Update) {
+    match update {
+        Update::Battery(battery) => state.battery = Some(battery),
+        Update::FernbedienungConnected(addr) => if let Some(link) = state.link("fernbedienung") {
+            link.addr = Some(addr);
+        },
+        Update::FernbedienungDisconnected => if let Some(link) = state.link("fernbedienung") {
+            link.addr = None;
+            link.signal = None;
+        },
+        Update::FernbedienungSignal(signal) => if let Some(link) = state.link("fernbedienung") {
+            link.signal = Some(signal);
+        },
+        _ => {}
+    }
+}
+
+/* apply the updates that have been sent since the state was last updated */
+fn sync_state(state: &mut snapshot::Robot, state_rx: &mut broadcast::Receiver<Update>) {
+    loop {
+        match state_rx.try_recv() {
+            Ok(update) => update_state(state, update),
+            Err(broadcast::error::TryRecvError::Lagged(_)) => continue,
+            Err(_) => break,
+        }
+    }
+}
+
+/* the updates that bring a new subscriber up to date with the state of the robot */
+fn initial_updates(state: &snapshot::Robot) -> Vec<Update> {
+    let mut updates = Vec::new();
+    for link in &state.links {
+        if let ("fernbedienung", Some(addr)) = (link.name.as_str(), link.addr) {
+            updates.push(Update::FernbedienungConnected(addr));
+            updates.extend(link.signal.map(Update::FernbedienungSignal));
+        }
+    }
+    updates.extend(state.battery.map(Update::Battery));
+    updates
+}
+
+pub async fn new(mut action_rx: Receiver, cameras: Vec<camera::Configuration>, capacity: usize) {
     /* fernbedienung task state */
     let fernbedienung_task = futures::future::pending().left_future();
     let mut fernbedienung_tx = Option::default();
-    let mut fernbedienung_addr = Option::default();
     tokio::pin!(fernbedienung_task);
     /* updates_tx is for sending changes in state to subscribers (e.g., the webui) */
-    let (updates_tx, _) = broadcast::channel(16);
+    let (updates_tx, _) = broadcast::channel(capacity);
+    /* the state of the robot for snapshots of the arena */
+    let mut state = snapshot::Robot::new(&["fernbedienung"]);
+    let mut state_rx = updates_tx.subscribe();
+    /* the most recent frame of each camera, the receivers are kept so that the frames can be sent
+       while nobody is subscribed */
+    let (frame_txs, frame_rxs) = crate::robot::frame_channels(&cameras);
     
     // TODO: for a clean shutdown we may want to consider the case where updates_tx hangs up
     loop {
         tokio::select! {
+            /* updates that were missed while the task was busy are skipped */
+            Ok(update) = state_rx.recv() => update_state(&mut state, update),
             Some(action) = action_rx.recv() => match action {
                 Action::AssociateFernbedienung(device) => {
                     let (tx, rx) = mpsc::channel(8);
                     fernbedienung_tx = Some(tx);
-                    fernbedienung_addr = Some(device.addr);
                     let _ = updates_tx.send(Update::FernbedienungConnected(device.addr));
-                    let task = tokio::spawn(fernbedienung(device, rx, updates_tx.clone()));
+                    let task = tokio::spawn(fernbedienung(device, rx, updates_tx.clone(), cameras.clone(), frame_txs.clone()));
                     fernbedienung_task.set(task.right_future());
                 },
                 Action::ExecuteFernbedienungAction(callback, action) => match fernbedienung_tx.as_ref() {
@@ -450,18 +638,21 @@ pub async fn new(mut action_rx: Receiver) {
                         let _ = callback.send(Err(error));
                     }
                 },
+                Action::GetState(callback) => {
+                    sync_state(&mut state, &mut state_rx);
+                    let _ = callback.send(state.clone());
+                },
                 Action::Subscribe(callback) => {
-                    /* note that upon subscribing all updates should be sent to ensure
-                       that new clients are in sync */
-                    if let Ok(_) = callback.send(updates_tx.subscribe()) {
-                        if let Some(addr) = fernbedienung_addr {
-                            let _ = updates_tx.send(Update::FernbedienungConnected(addr));
-                        }
-                    }
+                    sync_state(&mut state, &mut state_rx);
+                    let _ = callback.send((initial_updates(&state), updates_tx.subscribe()));
+                },
+                Action::SubscribeFrames(callback) => {
+                    let _ = callback.send(frame_rxs.clone());
                 },
-                Action::SetupExperiment(callback, id, software, journal) => match fernbedienung_tx.as_ref() {
+                Action::SetupExperiment(callback, id, software, journal, restart_policy) => match fernbedienung_tx.as_ref() {
                     Some(tx) => {
-                        let action = FernbedienungAction::SetupExperiment(id, software, journal);
+                        state.set_software(&software);
+                        let action = FernbedienungAction::SetupExperiment(id, software, journal, restart_policy);
                         if let Err(mpsc::error::SendError((callback, _))) = tx.send((callback, action)).await {
                             let _ = callback.send(Err(anyhow::anyhow!("Could not communicate with Fernbedienung task")));
                         }
@@ -503,9 +694,9 @@ pub async fn new(mut action_rx: Receiver) {
             },
             _ = &mut fernbedienung_task => {
                 fernbedienung_tx = None;
-                fernbedienung_addr = None;
                 fernbedienung_task.set(futures::future::pending().left_future());
                 let _ = updates_tx.send(Update::FernbedienungDisconnected);
+                frame_txs.clear();
             },
         }
     }
@@ -1,7 +1,7 @@
-use tokio::{self, sync::mpsc, task::JoinHandle};
+use tokio::{self, sync::mpsc, task::{JoinError, JoinHandle}};
 
 mod task;
-mod codec;
+pub mod codec;
 
 pub use task::{
     Action, Receiver, Sender, Update, Descriptor
@@ -9,16 +9,38 @@ pub use task::{
 
 pub struct Instance {
     pub action_tx: Sender,
-    _task: JoinHandle<()>
+    task: JoinHandle<()>
 }
 
-impl Default for Instance {
-    fn default() -> Self {
+impl Instance {
+    /* cameras are the cameras on the robot that can be streamed, profile describes the rest of its
+       hardware, rigid_body is the identifier of its rigid body and the tracking system that its poses
+       are forwarded from, timeouts limit the time spent connecting to the robot, and capacity is the
+       number of updates that a subscriber can fall behind before it misses updates */
+    pub fn new(
+        cameras: Vec<shared::camera::Configuration>,
+        profile: shared::drone::Profile,
+        rigid_body: Option<(i32, mpsc::Sender<crate::tracking::Action>)>,
+        timeouts: crate::timeouts::Configuration,
+        capacity: usize
+    ) -> Self {
         let (action_tx, action_rx) = mpsc::channel(8);
-        let _task = tokio::spawn(task::new(action_rx));
+        let task = tokio::spawn(task::new(action_rx, cameras, profile, rigid_body, timeouts, capacity));
         Self { 
             action_tx,
-            _task
+            task
         }
     }
+
+    /* wait for the task to terminate, e.g., due to a panic */
+    pub async fn terminated(&mut self) -> Result<(), JoinError> {
+        (&mut self.task).await
+    }
+}
+
+/* stop the task when the instance is dropped, e.g., when the configuration is reloaded */
+impl Drop for Instance {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
 }
\ No newline at end of file
@@ -1,21 +1,24 @@
-use std::{collections::HashMap, net::SocketAddr, sync::atomic::{AtomicU8, Ordering}, time::Duration};
+use std::{collections::{HashMap, HashSet}, convert::TryFrom, net::{Ipv4Addr, SocketAddr}, sync::Arc, time::{Duration, Instant}};
 use anyhow::Context;
 use ansi_parser::{Output, AnsiParser};
 use bytes::BytesMut;
-use mavlink::{MavHeader, common::{self, MavMessage, SerialControlDev, SerialControlFlag}, error::MessageReadError};
-use tokio::{net::{TcpStream, UdpSocket}, sync::{broadcast, mpsc, oneshot}};
+use mavlink::{MavHeader, common::{self, MavCmd, MavMessage, SerialControlDev, SerialControlFlag}, error::MessageReadError};
+use tokio::{net::{TcpStream, UdpSocket}, sync::{broadcast, mpsc, oneshot, watch}};
 use futures::{FutureExt, Sink, SinkExt, Stream, StreamExt, TryStreamExt};
 use tokio_stream::{self, wrappers::ReceiverStream};
-use tokio_util::{codec::Framed, sync::PollSender};
+use tokio_util::{codec::Framed, sync::PollSender, udp::UdpFramed};
 
-use crate::network::{fernbedienung, fernbedienung_ext::MjpegStreamerStream, xbee};
-use crate::robot::{FernbedienungAction, XbeeAction, TerminalAction};
-use crate::journal;
+use crate::network::{fernbedienung, fernbedienung_ext::{self, MjpegStreamerStream}, xbee};
+use crate::robot::{FernbedienungAction, FrameQueue, FrameReceivers, FrameSenders, RestartPolicy, SupervisedFlight, XbeeAction, TerminalAction};
+use crate::{journal, timeouts, tracking};
+use crate::subscription::Subscription;
+use shared::{camera, snapshot};
 use super::codec;
 
 pub use shared::{
-    drone::{Descriptor, Update},
-    experiment::software::Software
+    drone::{Battery, Descriptor, FirmwareUpload, Frame, Landing, LogDownload, MavlinkTransport, MissionItem, Pixhawk, PixhawkLog, PoseForwarding, PoseMessage, Profile, Update, XbeeNode, XbeePins},
+    experiment::software::Software,
+    tracking_system,
 };
 
 const IDENTIFY_DRONE_ARGOS: (&'static str, &'static [u8]) = 
@@ -23,86 +26,498 @@ const IDENTIFY_DRONE_ARGOS: (&'static str, &'static [u8]) =
 const IDENTIFY_DRONE_LUA: (&'static str, &'static [u8]) = 
     ("identify_drone.lua", include_bytes!("identify_drone.lua"));
 
-const DRONE_BATT_FULL_MV: f32 = 4050.0;
-const DRONE_BATT_EMPTY_MV: f32 = 3500.0;
-const DRONE_BATT_NUM_CELLS: f32 = 3.0;
-const DRONE_CAMERAS_CONFIG: &[(&str, u16, u16, u16)] = &[
-    ("/dev/camera0", 1024, 768, 8000),
-    ("/dev/camera1", 1024, 768, 8001),
-    ("/dev/camera2", 1024, 768, 8002),
-    ("/dev/camera3", 1024, 768, 8003),
-];
-
+/* the minimum battery level (in percent) for a drone to pass the pre-flight check */
+const PREFLIGHT_MIN_BATTERY: i32 = 50;
+/* the maximum age of the last heartbeat from the Pixhawk for a drone to pass the pre-flight check */
+const PREFLIGHT_HEARTBEAT_TIMEOUT: Duration = Duration::from_secs(3);
 const PIXHAWK_PORT: &'static str = "/dev/ttyS1:921600";
+/* the MAVLink system and component identifiers of the supervisor, i.e., a ground control station */
+const SUPERVISOR_SYSTEM_ID: u8 = 255;
+const SUPERVISOR_COMPONENT_ID: u8 = 190;
+/* how often the fraction of MAVLink messages that were lost on the serial link is reported */
+const MAVLINK_LOSS_INTERVAL: Duration = Duration::from_secs(5);
+/* how long to wait for the Pixhawk to respond during the mission protocol or to a command */
+const PIXHAWK_RESPONSE_TIMEOUT: Duration = Duration::from_secs(2);
+/* the Pixhawk leaves offboard mode if it does not receive setpoints at 2 Hz or more */
+const SUPERVISED_FLIGHT_SETPOINT_INTERVAL: Duration = Duration::from_millis(50);
+/* how often the progress of a log download is reported and checked for stalls */
+const LOG_DOWNLOAD_INTERVAL: Duration = Duration::from_secs(1);
+/* how many times the remainder of a log is requested again before the download is abandoned */
+const LOG_DOWNLOAD_RETRIES: u8 = 5;
+/* how long to wait for a drone to land and disarm after the Pixhawk has accepted a land command */
+const LANDING_TIMEOUT: Duration = Duration::from_secs(120);
+
+/* how long the identify command of a profile may run on the Up Core */
+const IDENTIFY_COMMAND_TIMEOUT: Duration = Duration::from_secs(30);
+/* how long to wait for the UP Core to halt before its power is cut anyway */
+const UPCORE_HALT_TIMEOUT: Duration = Duration::from_secs(30);
+/* how long to wait after the Fernbedienung connection has dropped before cutting the power */
+const UPCORE_HALT_GRACE_PERIOD: Duration = Duration::from_secs(5);
 
-const XBEE_DEFAULT_PIN_CONFIG: &[(xbee::Pin, xbee::PinMode)] = &[
+const XBEE_UART_PIN_CONFIG: &[(xbee::Pin, xbee::PinMode)] = &[
     /* UART pins: TX: DOUT, RTS: DIO6, RX: DIN, CTS: DIO7 */
     /* UART enabled without hardware flow control */
     (xbee::Pin::DOUT, xbee::PinMode::Alternate),
     (xbee::Pin::DIO6, xbee::PinMode::Disable),
     (xbee::Pin::DIO7, xbee::PinMode::Disable),
     (xbee::Pin::DIN,  xbee::PinMode::Alternate),
-    /* Input pins for reading an identifer */
-    (xbee::Pin::DIO0, xbee::PinMode::Input),
-    (xbee::Pin::DIO1, xbee::PinMode::Input),
-    (xbee::Pin::DIO2, xbee::PinMode::Input),
-    (xbee::Pin::DIO3, xbee::PinMode::Input),
-    /* Output pins for controlling power and mux */
-    (xbee::Pin::DIO4, xbee::PinMode::OutputDefaultLow),
-    (xbee::Pin::DIO11, xbee::PinMode::OutputDefaultLow),
-    (xbee::Pin::DIO12, xbee::PinMode::OutputDefaultLow),
 ];
 
+/* the Xbee pins that are wired to the drone's hardware, these differ between hardware revisions */
+#[derive(Debug)]
+struct PinMap {
+    autonomous_mode: xbee::Pin,
+    upcore_power: xbee::Pin,
+    pixhawk_power: xbee::Pin,
+    identifier: [xbee::Pin; 4],
+}
+
+impl TryFrom<&XbeePins> for PinMap {
+    type Error = anyhow::Error;
+
+    fn try_from(pins: &XbeePins) -> anyhow::Result<Self> {
+        let pin = |index: u8| xbee::Pin::try_from(index as usize)
+            .map_err(|_| anyhow::anyhow!("DIO{} is not a valid Xbee pin", index));
+        Ok(Self {
+            autonomous_mode: pin(pins.autonomous_mode)?,
+            upcore_power: pin(pins.upcore_power)?,
+            pixhawk_power: pin(pins.pixhawk_power)?,
+            identifier: [
+                pin(pins.identifier[0])?, pin(pins.identifier[1])?,
+                pin(pins.identifier[2])?, pin(pins.identifier[3])?
+            ],
+        })
+    }
+}
+
+impl PinMap {
+    fn config(&self) -> Vec<(xbee::Pin, xbee::PinMode)> {
+        XBEE_UART_PIN_CONFIG.iter()
+            .cloned()
+            /* input pins for reading an identifer */
+            .chain(self.identifier.iter().map(|&pin| (pin, xbee::PinMode::Input)))
+            /* output pins for controlling power and mux */
+            .chain([self.autonomous_mode, self.upcore_power, self.pixhawk_power].iter()
+                .map(|&pin| (pin, xbee::PinMode::OutputDefaultLow)))
+            .collect()
+    }
+}
+
+/* parameters (in addition to the configured pins) that the supervisor relies on and that must not be
+   changed from the web interface: the serial communication service, baud rate, and output pins */
+const XBEE_RESERVED_PARAMETERS: &[[u8; 2]] = &[
+    [b'I', b'P'], [b'B', b'D'], [b'O', b'M'], [b'I', b'O'],
+];
+
+/* how long to wait for a stream to release a camera before capturing an image */
+const CAMERA_RELEASE_DELAY: Duration = Duration::from_millis(500);
+const CAPTURE_IMAGE_TIMEOUT: Duration = Duration::from_secs(10);
+const KERNEL_MESSAGES_TIMEOUT: Duration = Duration::from_secs(5);
+/* how often the kernel messages of the Up Core are checked for faults */
+const KERNEL_MESSAGES_INTERVAL: Duration = Duration::from_secs(10);
+/* the kernel messages that report a fault, e.g., an oops, a panic, or the out-of-memory killer */
+const KERNEL_FAULT_PATTERNS: &[&str] = &[
+    "Oops", "Kernel panic", "BUG:", "general protection fault", "Out of memory", "invoked oom-killer",
+];
+/* the PX4 uploader that is installed on the Up Core, it reboots the Pixhawk into its bootloader */
+const PX4_UPLOADER: &str = "px_uploader.py";
+const PX4_BOOTLOADER_BAUD_RATE: u32 = 115200;
+
 #[derive(Debug)]
 pub enum Action {
     AssociateFernbedienung(fernbedienung::Device),
     AssociateXbee(xbee::Device),
     ExecuteXbeeAction(oneshot::Sender<anyhow::Result<()>>, XbeeAction),
     ExecuteFernbedienungAction(oneshot::Sender<anyhow::Result<()>>, FernbedienungAction),
-    Subscribe(oneshot::Sender<broadcast::Receiver<Update>>),
+    Subscribe(oneshot::Sender<Subscription<Update>>),
+    /* the frames of the cameras are not sent with the updates since only the most recent frame is kept */
+    SubscribeFrames(oneshot::Sender<FrameReceivers>),
+    /* every frame of the cameras is sent to the queue, e.g., to record them */
+    SubscribeFrameQueue(FrameQueue),
+    /* the connections, battery level, and software of the robot */
+    GetState(oneshot::Sender<snapshot::Robot>),
     // its good to keep this one seperate since start exp need to interact with xbee and fernbedienung
-    SetupExperiment(oneshot::Sender<anyhow::Result<()>>, String, Software, mpsc::Sender<journal::Action>),
+    SetupExperiment(oneshot::Sender<anyhow::Result<()>>, String, Software, mpsc::Sender<journal::Action>, RestartPolicy),
     StartExperiment(oneshot::Sender<anyhow::Result<()>>),
     StopExperiment,
+    /* check that the drone is ready to fly, the callback receives the failed checks */
+    PreflightCheck(oneshot::Sender<Vec<String>>),
 }
 
 pub type Sender = mpsc::Sender<Action>;
 pub type Receiver = mpsc::Receiver<Action>;
 
 async fn mavlink<'dev>(
-    device: &'dev xbee::Device
-) -> anyhow::Result<impl Stream<Item = Result<(MavHeader, MavMessage), MessageReadError>> + Sink<MavMessage> + 'dev> {
+    device: &'dev xbee::Device,
+    policy: &timeouts::Policy
+) -> anyhow::Result<impl Stream<Item = Result<(MavHeader, MavMessage), MessageReadError>> + Sink<MavMessage, Error = anyhow::Error> + 'dev> {
     /* set the baud rate to match the baud rate of the Pixhawk */
     device.set_baud_rate(921600).await
         .context("Could not set serial baud rate")?;
     /* set the serial communication service to TCP mode */
     device.set_scs_mode(true).await
         .context("Could not enable serial communication service")?;
-    /* try to connect */
-    let connection = TcpStream::connect((device.addr, 9750))
-        .map(|result| result
-            .context("Could not connect to serial communication service"));
-    let connection = tokio::time::timeout(Duration::from_secs(1), connection)
-        .map(|result| result
-            .context("Timeout while connecting to serial communication service")
-            .and_then(|result| result)).await?;
+    /* try to connect, retrying as configured since the connection is often slow to establish on a
+       congested network */
+    let connection = policy.run(|| TcpStream::connect((device.addr, 9750))
+        .map(|result| result.map_err(anyhow::Error::from))).await
+        .context("Could not connect to serial communication service")?;
     let framed = Framed::new(connection, codec::MavMessageCodec::<MavMessage>::new());
     /* automatically add headers to outbound mavlink messages */
-    let mavlink_sequence = AtomicU8::new(0);
+    let mut header = mavlink_headers();
     let framed = framed.with(move |message| {
-        let sequence = mavlink_sequence.fetch_add(1, Ordering::Relaxed);
-        async move {
-            let header = MavHeader {
-                system_id: 255,
-                component_id: 0,
-                sequence
-            };
-            anyhow::Result::<_>::Ok((header, message))
-        }
+        futures::future::ready(anyhow::Result::<_>::Ok((header(), message)))
     });
     Ok(framed)
 }
 
+/* connect to a UDP port on the Up Core that is forwarded to the Pixhawk, messages are only sent and
+   received while the Up Core is connected, i.e., while its address is known */
+async fn mavlink_udp(
+    port: u16,
+    upcore_addr: watch::Receiver<Option<Ipv4Addr>>,
+) -> anyhow::Result<impl Stream<Item = Result<(MavHeader, MavMessage), MessageReadError>> + Sink<MavMessage, Error = anyhow::Error>> {
+    let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).await
+        .context("Could not bind MAVLink socket")?;
+    let framed = UdpFramed::new(socket, codec::MavMessageCodec::<MavMessage>::new());
+    let mut header = mavlink_headers();
+    let sink_upcore_addr = upcore_addr.clone();
+    let framed = framed
+        .with(move |message| {
+            let target = sink_upcore_addr.borrow()
+                .map(|addr| SocketAddr::new(addr.into(), port))
+                .ok_or(anyhow::anyhow!("Up Core is not connected"));
+            futures::future::ready(target.map(|target| ((header(), message), target)))
+        })
+        .filter_map(move |frame| {
+            let source = upcore_addr.borrow()
+                .map(|addr| SocketAddr::new(addr.into(), port));
+            futures::future::ready(match frame {
+                Ok((message, addr)) => match Some(addr) == source {
+                    true => Some(Ok(message)),
+                    false => None,
+                },
+                Err(error) => Some(Err(error)),
+            })
+        });
+    Ok(framed)
+}
+
+/* the headers of outbound messages, the sequence number is incremented for each message so that
+   the receiver can detect lost messages */
+fn mavlink_headers() -> impl FnMut() -> MavHeader {
+    let mut sequence: u8 = 0;
+    move || {
+        let header = MavHeader {
+            system_id: SUPERVISOR_SYSTEM_ID,
+            component_id: SUPERVISOR_COMPONENT_ID,
+            sequence,
+        };
+        sequence = sequence.wrapping_add(1);
+        header
+    }
+}
+
+/* the sequence numbers of the messages from each MAVLink component, these are used to estimate
+   the fraction of messages that are lost on the serial link */
+#[derive(Default)]
+struct MavlinkSequences {
+    last: HashMap<(u8, u8), u8>,
+    received: u32,
+    lost: u32,
+}
+
+impl MavlinkSequences {
+    fn update(&mut self, header: &MavHeader) {
+        let source = (header.system_id, header.component_id);
+        if let Some(last) = self.last.insert(source, header.sequence) {
+            /* a large gap is more likely to be caused by the component restarting */
+            let lost = header.sequence.wrapping_sub(last.wrapping_add(1));
+            if lost < u8::MAX / 2 {
+                self.lost += lost as u32;
+            }
+        }
+        self.received += 1;
+    }
+
+    /* the percentage of messages that were lost since the last call */
+    fn take_loss(&mut self) -> Option<f32> {
+        let (received, lost) = (self.received, self.lost);
+        self.received = 0;
+        self.lost = 0;
+        match received {
+            0 => None,
+            _ => Some(lost as f32 * 100.0 / (received + lost) as f32),
+        }
+    }
+}
+
+/* wait for a response from the Pixhawk, ignoring any messages that are rejected by the filter or that
+   were sent by other MAVLink components (e.g., a companion computer) */
+async fn mavlink_response<T, F, R>(stream: &mut T, pixhawk: Pixhawk, mut filter: F) -> anyhow::Result<R>
+    where T: Stream<Item = Result<(MavHeader, MavMessage), MessageReadError>> + Unpin,
+          F: FnMut(MavMessage) -> Option<R> {
+    let response = async {
+        while let Some(message) = stream.next().await {
+            if let Ok((header, message)) = message {
+                if header.system_id != pixhawk.system_id || header.component_id != pixhawk.component_id {
+                    continue;
+                }
+                if let Some(response) = filter(message) {
+                    return Ok(response);
+                }
+            }
+        }
+        Err(anyhow::anyhow!("MAVLink connection closed"))
+    };
+    tokio::time::timeout(PIXHAWK_RESPONSE_TIMEOUT, response).await
+        .context("Timeout while waiting for a response from the Pixhawk")?
+}
+
+async fn mavlink_command<S, T>(
+    sink: &mut S,
+    stream: &mut T,
+    pixhawk: Pixhawk,
+    command: MavCmd,
+    [param1, param2, param3, param4, param5, param6, param7]: [f32; 7]
+) -> anyhow::Result<()>
+    where S: Sink<MavMessage> + Unpin,
+          T: Stream<Item = Result<(MavHeader, MavMessage), MessageReadError>> + Unpin {
+    let message = MavMessage::COMMAND_LONG(common::COMMAND_LONG_DATA {
+        param1, param2, param3, param4, param5, param6, param7,
+        command,
+        target_system: pixhawk.system_id,
+        target_component: pixhawk.component_id,
+        confirmation: 0,
+    });
+    sink.send(message).await
+        .map_err(|_| anyhow::anyhow!("Could not send {:?} to the Pixhawk", command))?;
+    let result = mavlink_response(stream, pixhawk, |message| match message {
+        MavMessage::COMMAND_ACK(data) if data.command == command => Some(data.result),
+        _ => None
+    }).await?;
+    match result {
+        common::MavResult::MAV_RESULT_ACCEPTED => Ok(()),
+        result => Err(anyhow::anyhow!("Pixhawk did not accept {:?}: {:?}", command, result)),
+    }
+}
+
+/* the tracking system is assumed to use an east, north, up frame, while the Pixhawk uses
+   a north, east, down frame */
+fn ned_position([x, y, z]: [f32; 3]) -> [f32; 3] {
+    [y, x, -z]
+}
+
+/* the roll, pitch, and yaw of the body (front, right, down) in the north, east, down frame */
+fn ned_attitude([w, x, y, z]: [f32; 4]) -> [f32; 3] {
+    let roll = (2.0 * (w * x + y * z)).atan2(1.0 - 2.0 * (x * x + y * y));
+    let pitch = (2.0 * (w * y - z * x)).clamp(-1.0, 1.0).asin();
+    let yaw = (2.0 * (w * z + x * y)).atan2(1.0 - 2.0 * (y * y + z * z));
+    [roll, -pitch, std::f32::consts::FRAC_PI_2 - yaw]
+}
+
+fn vision_position_estimate(timestamp: Duration, pose: &tracking_system::Update) -> MavMessage {
+    let [x, y, z] = ned_position(pose.position);
+    let [roll, pitch, yaw] = ned_attitude(pose.orientation);
+    MavMessage::VISION_POSITION_ESTIMATE(common::VISION_POSITION_ESTIMATE_DATA {
+        usec: timestamp.as_micros() as u64,
+        x, y, z,
+        roll, pitch, yaw,
+    })
+}
+
+fn att_pos_mocap(timestamp: Duration, pose: &tracking_system::Update) -> MavMessage {
+    let [x, y, z] = ned_position(pose.position);
+    /* the quaternion of the attitude in the north, east, down frame */
+    let [roll, pitch, yaw] = ned_attitude(pose.orientation);
+    let (sr, cr) = (roll * 0.5).sin_cos();
+    let (sp, cp) = (pitch * 0.5).sin_cos();
+    let (sy, cy) = (yaw * 0.5).sin_cos();
+    let q = [
+        cr * cp * cy + sr * sp * sy,
+        sr * cp * cy - cr * sp * sy,
+        cr * sp * cy + sr * cp * sy,
+        cr * cp * sy - sr * sp * cy,
+    ];
+    MavMessage::ATT_POS_MOCAP(common::ATT_POS_MOCAP_DATA {
+        time_usec: timestamp.as_micros() as u64,
+        q,
+        x, y, z,
+    })
+}
+
+fn external_pose(message: PoseMessage, timestamp: Duration, pose: &tracking_system::Update) -> MavMessage {
+    match message {
+        PoseMessage::VisionPositionEstimate => vision_position_estimate(timestamp, pose),
+        PoseMessage::AttPosMocap => att_pos_mocap(timestamp, pose),
+    }
+}
+
+/* subscribe to the poses of a rigid body for forwarding them to the Pixhawk */
+async fn subscribe_poses(
+    (optitrack_id, tracking_tx): &(i32, mpsc::Sender<tracking::Action>)
+) -> anyhow::Result<broadcast::Receiver<tracking_system::Update>> {
+    let (callback_tx, callback_rx) = oneshot::channel();
+    tracking_tx.send(tracking::Action::SubscribeRigidBody(*optitrack_id, callback_tx)).await
+        .map_err(|_| anyhow::anyhow!("Could not communicate with tracking system"))?;
+    callback_rx.await
+        .map_err(|_| anyhow::anyhow!("Could not subscribe to rigid body {}", optitrack_id))
+}
+
+fn position_target(pixhawk: Pixhawk, timestamp: Duration, target: [f32; 3]) -> MavMessage {
+    let [x, y, z] = ned_position(target);
+    /* only the position of the setpoint is used */
+    let type_mask =
+        common::PositionTargetTypemask::POSITION_TARGET_TYPEMASK_VX_IGNORE |
+        common::PositionTargetTypemask::POSITION_TARGET_TYPEMASK_VY_IGNORE |
+        common::PositionTargetTypemask::POSITION_TARGET_TYPEMASK_VZ_IGNORE |
+        common::PositionTargetTypemask::POSITION_TARGET_TYPEMASK_AX_IGNORE |
+        common::PositionTargetTypemask::POSITION_TARGET_TYPEMASK_AY_IGNORE |
+        common::PositionTargetTypemask::POSITION_TARGET_TYPEMASK_AZ_IGNORE |
+        common::PositionTargetTypemask::POSITION_TARGET_TYPEMASK_YAW_IGNORE |
+        common::PositionTargetTypemask::POSITION_TARGET_TYPEMASK_YAW_RATE_IGNORE;
+    MavMessage::SET_POSITION_TARGET_LOCAL_NED(common::SET_POSITION_TARGET_LOCAL_NED_DATA {
+        time_boot_ms: timestamp.as_millis() as u32,
+        x, y, z,
+        target_system: pixhawk.system_id,
+        target_component: pixhawk.component_id,
+        coordinate_frame: common::MavFrame::MAV_FRAME_LOCAL_NED,
+        type_mask,
+        ..Default::default()
+    })
+}
+
+fn mission_item(pixhawk: Pixhawk, seq: u16, item: &MissionItem) -> common::MISSION_ITEM_INT_DATA {
+    let (command, frame, x, y, z, param1) = match *item {
+        /* takeoff and landing are relative to the current position */
+        MissionItem::Takeoff { altitude } =>
+            (MavCmd::MAV_CMD_NAV_TAKEOFF, common::MavFrame::MAV_FRAME_GLOBAL_RELATIVE_ALT_INT, 0, 0, altitude, 0.0),
+        MissionItem::Land =>
+            (MavCmd::MAV_CMD_NAV_LAND, common::MavFrame::MAV_FRAME_GLOBAL_RELATIVE_ALT_INT, 0, 0, 0.0, 0.0),
+        /* local positions are encoded in units of 0.1 mm and the z axis of the NED frame points down */
+        MissionItem::Waypoint { frame: Frame::Local, x, y, altitude, hold } =>
+            (MavCmd::MAV_CMD_NAV_WAYPOINT, common::MavFrame::MAV_FRAME_LOCAL_NED,
+             (x * 1e4) as i32, (y * 1e4) as i32, -altitude, hold),
+        /* global positions are encoded in units of 1e-7 degrees */
+        MissionItem::Waypoint { frame: Frame::Global, x, y, altitude, hold } =>
+            (MavCmd::MAV_CMD_NAV_WAYPOINT, common::MavFrame::MAV_FRAME_GLOBAL_RELATIVE_ALT_INT,
+             (x * 1e7) as i32, (y * 1e7) as i32, altitude, hold),
+    };
+    common::MISSION_ITEM_INT_DATA {
+        param1,
+        param2: 0.0,
+        param3: 0.0,
+        param4: f32::NAN,
+        x,
+        y,
+        z,
+        seq,
+        command,
+        target_system: pixhawk.system_id,
+        target_component: pixhawk.component_id,
+        frame,
+        current: 0,
+        autocontinue: 1,
+    }
+}
+
+/* upload a mission to the Pixhawk using the MAVLink mission protocol */
+async fn upload_mission<S, T>(sink: &mut S, stream: &mut T, pixhawk: Pixhawk, items: &[MissionItem]) -> anyhow::Result<()>
+    where S: Sink<MavMessage> + Unpin,
+          T: Stream<Item = Result<(MavHeader, MavMessage), MessageReadError>> + Unpin {
+    let count = u16::try_from(items.len())
+        .context("Mission contains too many items")?;
+    let message = MavMessage::MISSION_COUNT(common::MISSION_COUNT_DATA {
+        count,
+        target_system: pixhawk.system_id,
+        target_component: pixhawk.component_id,
+    });
+    sink.send(message).await
+        .map_err(|_| anyhow::anyhow!("Could not send mission count to the Pixhawk"))?;
+    /* the Pixhawk requests each item in turn and acknowledges the mission once it has all items */
+    loop {
+        let response = mavlink_response(stream, pixhawk, |message| match message {
+            MavMessage::MISSION_REQUEST_INT(data) => Some(Ok(data.seq)),
+            MavMessage::MISSION_REQUEST(data) => Some(Ok(data.seq)),
+            MavMessage::MISSION_ACK(data) => Some(Err(data.mavtype)),
+            _ => None
+        }).await?;
+        match response {
+            Ok(seq) => {
+                let item = items.get(seq as usize)
+                    .ok_or(anyhow::anyhow!("Pixhawk requested unknown mission item {}", seq))?;
+                let message = MavMessage::MISSION_ITEM_INT(mission_item(pixhawk, seq, item));
+                sink.send(message).await
+                    .map_err(|_| anyhow::anyhow!("Could not send mission item {} to the Pixhawk", seq))?;
+            },
+            Err(common::MavMissionResult::MAV_MISSION_ACCEPTED) => break Ok(()),
+            Err(result) => break Err(anyhow::anyhow!("Pixhawk rejected mission: {:?}", result)),
+        }
+    }
+}
+
+/* list the flight logs on the Pixhawk, the Pixhawk responds with one entry per log (or a single
+   entry with no logs if the SD card is empty) */
+async fn list_logs<S, T>(sink: &mut S, stream: &mut T, pixhawk: Pixhawk) -> anyhow::Result<Vec<PixhawkLog>>
+    where S: Sink<MavMessage> + Unpin,
+          T: Stream<Item = Result<(MavHeader, MavMessage), MessageReadError>> + Unpin {
+    let message = MavMessage::LOG_REQUEST_LIST(common::LOG_REQUEST_LIST_DATA {
+        start: 0,
+        end: u16::MAX,
+        target_system: pixhawk.system_id,
+        target_component: pixhawk.component_id,
+    });
+    sink.send(message).await
+        .map_err(|_| anyhow::anyhow!("Could not request the list of logs from the Pixhawk"))?;
+    let mut logs = Vec::new();
+    loop {
+        let entry = mavlink_response(stream, pixhawk, |message| match message {
+            MavMessage::LOG_ENTRY(data) => Some(data),
+            _ => None
+        }).await?;
+        if entry.num_logs != 0 && logs.iter().all(|log: &PixhawkLog| log.id != entry.id) {
+            logs.push(PixhawkLog { id: entry.id, time_utc: entry.time_utc, size: entry.size });
+        }
+        if logs.len() >= entry.num_logs as usize {
+            break Ok(logs);
+        }
+    }
+}
+
+/* a log that is being downloaded from the Pixhawk, the data arrives in small chunks that are only
+   accepted in order so that a lost chunk can be recovered by requesting the remainder of the log */
+struct LogTransfer {
+    id: u16,
+    size: u32,
+    path: std::path::PathBuf,
+    data: Vec<u8>,
+    updated: Instant,
+    retries: u8,
+}
+
+impl LogTransfer {
+    fn request(&self, pixhawk: Pixhawk) -> MavMessage {
+        MavMessage::LOG_REQUEST_DATA(common::LOG_REQUEST_DATA_DATA {
+            id: self.id,
+            ofs: self.data.len() as u32,
+            count: self.size - self.data.len() as u32,
+            target_system: pixhawk.system_id,
+            target_component: pixhawk.component_id,
+        })
+    }
+
+    fn is_complete(&self) -> bool {
+        self.data.len() as u32 >= self.size
+    }
+}
+
+fn log_request_end(pixhawk: Pixhawk) -> MavMessage {
+    MavMessage::LOG_REQUEST_END(common::LOG_REQUEST_END_DATA {
+        target_system: pixhawk.system_id,
+        target_component: pixhawk.component_id,
+    })
+}
+
 fn xbee_pin_states_stream<'dev>(
     device: &'dev xbee::Device
 ) -> impl Stream<Item = anyhow::Result<HashMap<xbee::Pin, bool>>> + 'dev {
@@ -152,12 +567,65 @@ fn xbee_link_margin_stream<'dev>(
 async fn xbee(
     device: xbee::Device,
     mut rx: mpsc::Receiver<(oneshot::Sender<anyhow::Result<()>>, XbeeAction)>,
-    updates_tx: broadcast::Sender<Update>
+    updates_tx: broadcast::Sender<Update>,
+    profile: Profile,
+    upcore_addr: watch::Receiver<Option<Ipv4Addr>>,
+    rigid_body: Option<(i32, mpsc::Sender<tracking::Action>)>,
+    mavlink_policy: timeouts::Policy,
 ) -> anyhow::Result<()> {
+    let Profile { battery: Battery { cells, empty_mv, full_mv }, pixhawk, pose_forwarding, .. } = profile;
+    let pins = PinMap::try_from(&profile.xbee_pins)
+        .context("Could not map Xbee pins")?;
+    let pin_config = pins.config();
     /* autonomous mode: this variable tracks whether or not we are in autonomous mode */
     let mut autonomous_mode = false;
+    /* pre-flight check: the most recent battery level and heartbeat from the Pixhawk */
+    let mut battery = Option::<i32>::None;
+    let mut last_heartbeat = Option::<Instant>::None;
+    /* landing: whether the Pixhawk reported that it is armed and the sender that is answered once it
+       has landed and disarmed */
+    let mut armed = false;
+    let mut landing: Option<oneshot::Sender<anyhow::Result<bool>>> = None;
+    let landing_timeout = futures::future::pending().left_future();
+    tokio::pin!(landing_timeout);
+    /* supervised flight: the poses of the drone's rigid body are forwarded to the Pixhawk as
+       vision position estimates alongside the setpoints */
+    let mut supervised_flight: Option<SupervisedFlight> = None;
+    let mut supervised_flight_pose: Option<tracking_system::Update> = None;
+    let mut setpoint_interval = tokio::time::interval(SUPERVISED_FLIGHT_SETPOINT_INTERVAL);
+    let start = Instant::now();
+    /* pose forwarding: the poses of the drone's rigid body are sent to the Pixhawk at the configured
+       rate, each pose is only sent once so that the Pixhawk can detect a loss of tracking */
+    let mut forwarded_poses = match (pose_forwarding, rigid_body.as_ref()) {
+        (Some(_), Some(rigid_body)) => match subscribe_poses(rigid_body).await {
+            Ok(poses) => Some(poses),
+            Err(error) => {
+                log::warn!("Could not forward poses to drone with Xbee {}: {:#}", device.addr, error);
+                None
+            }
+        },
+        _ => None,
+    };
+    let mut forwarded_pose: Option<tracking_system::Update> = None;
+    let (pose_message, pose_rate) = pose_forwarding
+        .map_or((PoseMessage::VisionPositionEstimate, 1), |PoseForwarding { message, rate }| (message, rate));
+    let mut pose_forwarding_interval = tokio::time::interval(Duration::from_secs_f64(1.0 / pose_rate as f64));
+    /* node discovery: this takes several seconds and is therefore run alongside the other streams */
+    let discovery = futures::future::pending::<xbee::Result<Vec<xbee::Node>>>().left_future();
+    let mut discovery_callback: Option<oneshot::Sender<anyhow::Result<()>>> = None;
+    tokio::pin!(discovery);
+    /* log download: the sizes of the logs from the most recent listing and the current transfer */
+    let mut log_sizes: HashMap<u16, u32> = HashMap::new();
+    let mut log_transfer: Option<LogTransfer> = None;
+    let mut log_transfer_interval = tokio::time::interval(LOG_DOWNLOAD_INTERVAL);
     /* mavlink sink and stream */
-    let (mut mavlink_sink, mut mavlink_stream) = mavlink(&device).await
+    let mavlink = match profile.mavlink {
+        MavlinkTransport::Xbee => mavlink(&device, &mavlink_policy).await
+            .map(futures::future::Either::Left),
+        MavlinkTransport::UpCore(port) => mavlink_udp(port, upcore_addr).await
+            .map(futures::future::Either::Right),
+    };
+    let (mut mavlink_sink, mut mavlink_stream) = mavlink
         .context("Could not connect to MAVLink")?
         .split();
     /* link margin stream */
@@ -175,27 +643,33 @@ async fn xbee(
     if let Some(Ok(pin_states)) = pin_states_stream_throttled.next().await {
         /* initialise autonomous mode based on current pin states */
         autonomous_mode =
-            pin_states.get(&xbee::Pin::DIO4).cloned().unwrap_or_default();
+            pin_states.get(&pins.autonomous_mode).cloned().unwrap_or_default();
         let upcore_power =
-            pin_states.get(&xbee::Pin::DIO11).cloned().unwrap_or_default();
+            pin_states.get(&pins.upcore_power).cloned().unwrap_or_default();
         let pixhawk_power =
-            pin_states.get(&xbee::Pin::DIO12).cloned().unwrap_or_default();
-        let pin_modes = XBEE_DEFAULT_PIN_CONFIG.iter()
+            pin_states.get(&pins.pixhawk_power).cloned().unwrap_or_default();
+        let pin_modes = pin_config.iter()
             .filter(|&(pin, _)| match pin {
                 /* if a pin is already set to true, then it should be removed from
                    the default pin configuration */
-                xbee::Pin::DIO4 => !autonomous_mode,
-                xbee::Pin::DIO11 => !upcore_power,
-                xbee::Pin::DIO12 => !pixhawk_power,
+                pin if *pin == pins.autonomous_mode => !autonomous_mode,
+                pin if *pin == pins.upcore_power => !upcore_power,
+                pin if *pin == pins.pixhawk_power => !pixhawk_power,
                 _ => true,
             });
         device.set_pin_modes(pin_modes).await
             .context("Could not set Xbee pin modes")?;
     }
     else {
-        device.set_pin_modes(XBEE_DEFAULT_PIN_CONFIG.into_iter()).await
+        device.set_pin_modes(pin_config.iter()).await
             .context("Could not set Xbee pin modes")?;
     }
+    match device.firmware_version().await {
+        Ok(version) => {
+            let _ = updates_tx.send(Update::XbeeFirmwareVersion(version));
+        },
+        Err(error) => log::warn!("Could not read Xbee firmware version: {}", error),
+    }
     /* mavlink heartbeat stream */
     let mavlink_heartbeat_stream = futures::stream::iter(std::iter::repeat(
         MavMessage::HEARTBEAT(common::HEARTBEAT_DATA {
@@ -210,6 +684,11 @@ async fn xbee(
     let mavlink_heartbeat_stream_throttled =
         tokio_stream::StreamExt::throttle(mavlink_heartbeat_stream, Duration::from_millis(500));
     tokio::pin!(mavlink_heartbeat_stream_throttled);
+    /* only the messages from the Pixhawk are handled, the other components that share the serial link
+       (e.g., a second autopilot or a companion computer) are only reported once */
+    let mut mavlink_sequences = MavlinkSequences::default();
+    let mut mavlink_components = HashSet::new();
+    let mut mavlink_loss_interval = tokio::time::interval(MAVLINK_LOSS_INTERVAL);
     /* poll all streams, sinks, channels, and futures */
     loop {
         tokio::select! {
@@ -219,32 +698,166 @@ async fn xbee(
                     let _ = mavlink_sink.send(heartbeat).await;
                 }
             },
-            Some(Ok((_header, body))) = mavlink_stream.next() => match body {
-                MavMessage::BATTERY_STATUS(data) => {
-                    let mut battery_reading = data.voltages[0] as f32;
-                    battery_reading /= DRONE_BATT_NUM_CELLS;
-                    battery_reading -= DRONE_BATT_EMPTY_MV;
-                    battery_reading /= DRONE_BATT_FULL_MV - DRONE_BATT_EMPTY_MV;
-                    let battery_reading = (battery_reading.max(0.0).min(1.0) * 100.0) as i32;
-                    let _ = updates_tx.send(Update::Battery(battery_reading));
-                },
-                MavMessage::SERIAL_CONTROL(common::SERIAL_CONTROL_DATA { data, count, .. }) => {
-                    let data = match std::str::from_utf8(&data[..count as usize]) {
-                        Ok(data) => data,
-                        Err(error) => {
-                            std::str::from_utf8(&data[..error.valid_up_to()]).unwrap()
+            _ = mavlink_loss_interval.tick() => {
+                if let Some(loss) = mavlink_sequences.take_loss() {
+                    let _ = updates_tx.send(Update::MavlinkLoss(loss));
+                }
+            },
+            Some(Ok((header, body))) = mavlink_stream.next() => {
+                mavlink_sequences.update(&header);
+                if header.system_id != pixhawk.system_id || header.component_id != pixhawk.component_id {
+                    if mavlink_components.insert((header.system_id, header.component_id)) {
+                        log::info!("Ignoring MAVLink messages from system {} component {} on Xbee {}",
+                            header.system_id, header.component_id, device.addr);
+                    }
+                    continue;
+                }
+                match body {
+                    MavMessage::BATTERY_STATUS(data) => {
+                        let mut battery_reading = data.voltages[0] as f32;
+                        battery_reading /= cells as f32;
+                        battery_reading -= empty_mv as f32;
+                        battery_reading /= (full_mv - empty_mv) as f32;
+                        let battery_reading = (battery_reading.clamp(0.0, 1.0) * 100.0) as i32;
+                        battery = Some(battery_reading);
+                        let _ = updates_tx.send(Update::Battery(battery_reading));
+                    },
+                    MavMessage::HEARTBEAT(data) => {
+                        last_heartbeat = Some(Instant::now());
+                        let previous = std::mem::replace(&mut armed,
+                            data.base_mode.contains(common::MavModeFlag::MAV_MODE_FLAG_SAFETY_ARMED));
+                        if armed != previous {
+                            let _ = updates_tx.send(Update::Armed(armed));
                         }
-                    };
-                    let parsed: String = data
-                        .ansi_parse()
-                        .fold(String::new(), |output, item| match item {
-                            Output::TextBlock(text) => format!("{}{}", output, text),
-                            Output::Escape(_) => output,
-                        });
-                    let _  = updates_tx.send(Update::Mavlink(parsed));
-                },
-                /* ignore other MAVLink messages */
-                _ => {}
+                        /* PX4 disarms automatically once it has landed */
+                        if !armed {
+                            if let Some(landed) = landing.take() {
+                                landing_timeout.set(futures::future::pending().left_future());
+                                let _ = landed.send(Ok(true));
+                            }
+                        }
+                    },
+                    MavMessage::SERIAL_CONTROL(common::SERIAL_CONTROL_DATA { data, count, .. }) => {
+                        let data = match std::str::from_utf8(&data[..count as usize]) {
+                            Ok(data) => data,
+                            Err(error) => {
+                                std::str::from_utf8(&data[..error.valid_up_to()]).unwrap()
+                            }
+                        };
+                        let parsed: String = data
+                            .ansi_parse()
+                            .fold(String::new(), |output, item| match item {
+                                Output::TextBlock(text) => format!("{}{}", output, text),
+                                Output::Escape(_) => output,
+                            });
+                        let _  = updates_tx.send(Update::Mavlink(parsed));
+                    },
+                    MavMessage::LOG_DATA(data) => if let Some(transfer) = log_transfer.as_mut() {
+                        if data.id == transfer.id && data.ofs as usize == transfer.data.len() {
+                            let count = (data.count as usize).min(data.data.len());
+                            transfer.data.extend_from_slice(&data.data[..count]);
+                            transfer.updated = Instant::now();
+                            transfer.retries = 0;
+                            /* the Pixhawk sends an empty chunk once it reaches the end of the log */
+                            if count == 0 || transfer.is_complete() {
+                                let transfer = log_transfer.take().unwrap();
+                                let _ = mavlink_sink.send(log_request_end(pixhawk)).await;
+                                let update = match tokio::fs::write(&transfer.path, &transfer.data).await {
+                                    Ok(_) => {
+                                        log::info!("Downloaded log {} from drone with Xbee {} to {:?}",
+                                            transfer.id, device.addr, transfer.path);
+                                        LogDownload::Complete(transfer.id, transfer.path.to_string_lossy().into_owned())
+                                    },
+                                    Err(error) => LogDownload::Failed(transfer.id,
+                                        format!("Could not write {:?}: {}", transfer.path, error)),
+                                };
+                                let _ = updates_tx.send(Update::PixhawkLogDownload(update));
+                            }
+                        }
+                    },
+                    /* ignore other MAVLink messages */
+                    _ => {}
+                }
+            },
+            pose = async { supervised_flight.as_mut().unwrap().poses.recv().await },
+                if supervised_flight.is_some() => match pose {
+                Ok(pose) => supervised_flight_pose = Some(pose),
+                Err(broadcast::error::RecvError::Lagged(_)) => {},
+                Err(broadcast::error::RecvError::Closed) => {
+                    log::warn!("Stopping supervised flight: tracking system is not available");
+                    supervised_flight = None;
+                }
+            },
+            _ = setpoint_interval.tick(), if supervised_flight.is_some() => {
+                if let Some(SupervisedFlight { target, .. }) = supervised_flight.as_ref() {
+                    /* only forward each pose once so that the Pixhawk can detect a loss of tracking, the
+                       poses are not sent twice if they are already forwarded */
+                    if let Some(pose) = supervised_flight_pose.take() {
+                        if forwarded_poses.is_none() {
+                            let _ = mavlink_sink.send(vision_position_estimate(start.elapsed(), &pose)).await;
+                        }
+                    }
+                    let _ = mavlink_sink.send(position_target(pixhawk, start.elapsed(), *target)).await;
+                }
+            },
+            pose = async { forwarded_poses.as_mut().unwrap().recv().await },
+                if forwarded_poses.is_some() => match pose {
+                Ok(pose) => forwarded_pose = Some(pose),
+                Err(broadcast::error::RecvError::Lagged(_)) => {},
+                Err(broadcast::error::RecvError::Closed) => {
+                    log::warn!("Stopping pose forwarding: tracking system is not available");
+                    forwarded_poses = None;
+                }
+            },
+            _ = pose_forwarding_interval.tick(), if forwarded_poses.is_some() => {
+                if let Some(pose) = forwarded_pose.take() {
+                    let _ = mavlink_sink.send(external_pose(pose_message, start.elapsed(), &pose)).await;
+                }
+            },
+            _ = log_transfer_interval.tick(), if log_transfer.is_some() => {
+                if let Some(transfer) = log_transfer.as_mut() {
+                    /* request the remainder of the log if the Pixhawk has stopped sending data */
+                    if transfer.updated.elapsed() > PIXHAWK_RESPONSE_TIMEOUT {
+                        transfer.retries += 1;
+                        transfer.updated = Instant::now();
+                        if transfer.retries > LOG_DOWNLOAD_RETRIES {
+                            let update = LogDownload::Failed(transfer.id,
+                                String::from("Timeout while waiting for data from the Pixhawk"));
+                            let _ = updates_tx.send(Update::PixhawkLogDownload(update));
+                            log_transfer = None;
+                            let _ = mavlink_sink.send(log_request_end(pixhawk)).await;
+                            continue;
+                        }
+                        let _ = mavlink_sink.send(transfer.request(pixhawk)).await;
+                    }
+                    let update = LogDownload::Progress(transfer.id, transfer.data.len() as u32, transfer.size);
+                    let _ = updates_tx.send(Update::PixhawkLogDownload(update));
+                }
+            },
+            _ = &mut landing_timeout => {
+                landing_timeout.set(futures::future::pending().left_future());
+                if let Some(landed) = landing.take() {
+                    let error = anyhow::anyhow!("Drone did not land within {} seconds", LANDING_TIMEOUT.as_secs());
+                    let _ = landed.send(Err(error));
+                }
+            },
+            result = &mut discovery => {
+                discovery.set(futures::future::pending().left_future());
+                let result = result
+                    .context("Could not discover Xbee nodes")
+                    .map(|nodes: Vec<xbee::Node>| {
+                        let nodes = nodes.into_iter()
+                            .map(|node| XbeeNode {
+                                macaddr: node.mac,
+                                identifier: node.identifier,
+                                rssi: node.rssi.map(|rssi| -(rssi as i32)),
+                            })
+                            .collect();
+                        let _ = updates_tx.send(Update::XbeeNodes(nodes));
+                    });
+                if let Some(callback) = discovery_callback.take() {
+                    let _ = callback.send(result);
+                }
             },
             Some(response) = link_margin_stream_throttled.next() => {
                 let update = Update::XbeeSignal(response?);
@@ -252,36 +865,222 @@ async fn xbee(
             },
             Some(response) = pin_states_stream_throttled.next() => {
                 let response = response?;
-                let upcore = response.get(&xbee::Pin::DIO11);
-                let pixhawk = response.get(&xbee::Pin::DIO12);
+                let upcore = response.get(&pins.upcore_power);
+                let pixhawk = response.get(&pins.pixhawk_power);
                 match (upcore, pixhawk) {
                     (Some(&upcore), Some(&pixhawk)) => {
                         let _ = updates_tx.send(Update::PowerState { upcore, pixhawk });
                     },
                     _ => log::warn!("Could not update power state")
                 }
+                /* four pins encode the identifier of the drone, the first is the least significant bit */
+                let identifier = pins.identifier.iter()
+                    .enumerate()
+                    .map(|(bit, pin)| response.get(pin).map(|&state| (state as u8) << bit))
+                    .sum::<Option<u8>>();
+                match identifier {
+                    Some(identifier) => {
+                        let _ = updates_tx.send(Update::XbeeIdentifier(identifier));
+                    },
+                    None => log::warn!("Could not update Xbee identifier")
+                }
             },
             recv = rx.recv() => match recv {
                 Some((callback, action)) => match action {
                     XbeeAction::SetAutonomousMode(enable) => {
-                        let result = device.write_outputs(&[(xbee::Pin::DIO4, enable)]).await
+                        let result = device.write_outputs(&[(pins.autonomous_mode, enable)]).await
                             .context("Could not configure autonomous mode");
                         /* if successful update the state of the autonomous mode variable */
                         if result.is_ok() {
                             autonomous_mode = enable;
+                            /* the drone is no longer controlled by the supervisor */
+                            if enable {
+                                supervised_flight = None;
+                            }
                         }
                         let _ = callback.send(result);
                     }
                     XbeeAction::SetUpCorePower(enable) => {
-                        let result = device.write_outputs(&[(xbee::Pin::DIO11, enable)]).await
+                        let result = device.write_outputs(&[(pins.upcore_power, enable)]).await
                             .context("Could not configure Up Core power");
                         let _ = callback.send(result);
                     },
                     XbeeAction::SetPixhawkPower(enable) => {
-                        let result = device.write_outputs(&[(xbee::Pin::DIO12, enable)]).await
+                        let result = device.write_outputs(&[(pins.pixhawk_power, enable)]).await
                             .context("Could not configure Pixhawk power");
                         let _ = callback.send(result);
                     },
+                    XbeeAction::GetParameter(parameter) => {
+                        let result = device.get_parameter(parameter).await
+                            .context(format!("Could not read Xbee parameter {}", String::from_utf8_lossy(&parameter)))
+                            .map(|value| {
+                                let parameter = String::from_utf8_lossy(&parameter).into_owned();
+                                let _ = updates_tx.send(Update::XbeeParameter { parameter, value: value.to_vec() });
+                            });
+                        let _ = callback.send(result);
+                    },
+                    XbeeAction::SetParameter(parameter, value) => {
+                        let reserved = XBEE_RESERVED_PARAMETERS.contains(&parameter) ||
+                            pin_config.iter().any(|&(pin, _)| <[u8; 2]>::from(pin) == parameter);
+                        let result = match reserved {
+                            true => Err(anyhow::anyhow!("Xbee parameter {} is reserved by the supervisor",
+                                String::from_utf8_lossy(&parameter))),
+                            false => device.set_parameter(parameter, BytesMut::from(&value[..])).await
+                                .context(format!("Could not set Xbee parameter {}", String::from_utf8_lossy(&parameter))),
+                        };
+                        let _ = callback.send(result);
+                    },
+                    XbeeAction::DiscoverNodes => match discovery_callback {
+                        Some(_) => {
+                            let error = anyhow::anyhow!("Xbee node discovery is already in progress");
+                            let _ = callback.send(Err(error));
+                        },
+                        None => {
+                            discovery.set(device.discover().right_future());
+                            discovery_callback = Some(callback);
+                        }
+                    },
+                    XbeeAction::ListLogs | XbeeAction::DownloadLog(..) if log_transfer.is_some() => {
+                        let error = anyhow::anyhow!("A log is already being downloaded from the Pixhawk");
+                        let _ = callback.send(Err(error));
+                    },
+                    XbeeAction::ListLogs => {
+                        let result = list_logs(&mut mavlink_sink, &mut mavlink_stream, pixhawk).await
+                            .context("Could not list logs")
+                            .map(|logs| {
+                                log_sizes = logs.iter().map(|log| (log.id, log.size)).collect();
+                                let _ = updates_tx.send(Update::PixhawkLogs(logs));
+                            });
+                        let _ = callback.send(result);
+                    },
+                    XbeeAction::DownloadLog(id, path) => match log_sizes.get(&id) {
+                        Some(&size) => {
+                            let transfer = LogTransfer {
+                                id, size, path, data: Vec::with_capacity(size as usize),
+                                updated: Instant::now(), retries: 0,
+                            };
+                            let result = mavlink_sink.send(transfer.request(pixhawk)).await
+                                .map_err(|_| anyhow::anyhow!("Could not request log {} from the Pixhawk", id));
+                            if result.is_ok() {
+                                log_transfer = Some(transfer);
+                            }
+                            let _ = callback.send(result);
+                        },
+                        None => {
+                            let error = anyhow::anyhow!("Log {} was not in the most recent list of logs", id);
+                            let _ = callback.send(Err(error));
+                        }
+                    },
+                    XbeeAction::WriteParameters => {
+                        let result = device.write_parameters().await
+                            .context("Could not write Xbee parameters");
+                        let _ = callback.send(result);
+                    },
+                    XbeeAction::UploadMission(_) | XbeeAction::StartMission if autonomous_mode => {
+                        let error = anyhow::anyhow!("Missions are not available in autonomous mode");
+                        let _ = callback.send(Err(error));
+                    },
+                    XbeeAction::PreflightCheck => {
+                        let mut failures = Vec::new();
+                        match battery {
+                            Some(battery) if battery < PREFLIGHT_MIN_BATTERY =>
+                                failures.push(format!("battery at {}% is below {}%", battery, PREFLIGHT_MIN_BATTERY)),
+                            Some(_) => {},
+                            None => failures.push(String::from("battery level is unknown")),
+                        }
+                        let heartbeat_present = last_heartbeat
+                            .is_some_and(|instant| instant.elapsed() < PREFLIGHT_HEARTBEAT_TIMEOUT);
+                        if !heartbeat_present {
+                            failures.push(String::from("no heartbeat from the Pixhawk"));
+                        }
+                        let result = match failures.is_empty() {
+                            true => Ok(()),
+                            false => Err(anyhow::anyhow!("{}", failures.join(", "))),
+                        };
+                        let _ = callback.send(result);
+                    },
+                    XbeeAction::SetSupervisedFlight(Some(_)) if autonomous_mode => {
+                        let error = anyhow::anyhow!("Supervised flight is not available in autonomous mode");
+                        let _ = callback.send(Err(error));
+                    },
+                    XbeeAction::SetSupervisedFlight(flight) => {
+                        supervised_flight = flight;
+                        supervised_flight_pose = None;
+                        let _ = callback.send(Ok(()));
+                    },
+                    XbeeAction::Land(mode, landed) => {
+                        let heartbeat_present = last_heartbeat
+                            .is_some_and(|instant| instant.elapsed() < PREFLIGHT_HEARTBEAT_TIMEOUT);
+                        if !heartbeat_present {
+                            let _ = callback.send(Err(anyhow::anyhow!("No heartbeat from the Pixhawk")));
+                            continue;
+                        }
+                        if !armed {
+                            let _ = callback.send(Ok(()));
+                            let _ = landed.send(Ok(false));
+                            continue;
+                        }
+                        let result = async {
+                            /* take the serial link back from the Up Core so that the command reaches the Pixhawk */
+                            if autonomous_mode {
+                                device.write_outputs(&[(pins.autonomous_mode, false)]).await
+                                    .context("Could not disable autonomous mode")?;
+                                autonomous_mode = false;
+                            }
+                            /* the setpoints of a supervised flight would keep the drone in offboard mode */
+                            supervised_flight = None;
+                            match mode {
+                                /* NaN for the yaw and the position means the current yaw and position */
+                                Landing::Land => mavlink_command(&mut mavlink_sink, &mut mavlink_stream, pixhawk,
+                                    MavCmd::MAV_CMD_NAV_LAND, [0.0, 0.0, 0.0, f32::NAN, f32::NAN, f32::NAN, f32::NAN]).await,
+                                Landing::ReturnToLaunch => mavlink_command(&mut mavlink_sink, &mut mavlink_stream, pixhawk,
+                                    MavCmd::MAV_CMD_NAV_RETURN_TO_LAUNCH, [0.0; 7]).await,
+                            }
+                        }.await.context("Could not land drone");
+                        if result.is_ok() {
+                            if let Some(previous) = landing.replace(landed) {
+                                let _ = previous.send(Err(anyhow::anyhow!("Superseded by another land command")));
+                            }
+                            landing_timeout.set(tokio::time::sleep(LANDING_TIMEOUT).right_future());
+                        }
+                        let _ = callback.send(result);
+                    },
+                    XbeeAction::PlayTune(tune) => {
+                        /* the tune is padded with zeros to the 30 characters of the message */
+                        let mut characters = ['\0'; 30];
+                        let result = match tune.chars().count() {
+                            length if length > characters.len() =>
+                                Err(anyhow::anyhow!("Tune has {} characters, at most {} are supported", length, characters.len())),
+                            _ => {
+                                for (character, slot) in tune.chars().zip(characters.iter_mut()) {
+                                    *slot = character;
+                                }
+                                let message = MavMessage::PLAY_TUNE(common::PLAY_TUNE_DATA {
+                                    target_system: pixhawk.system_id,
+                                    target_component: pixhawk.component_id,
+                                    tune: characters,
+                                });
+                                mavlink_sink.send(message).await
+                                    .map_err(|_| anyhow::anyhow!("Could not play tune on the Pixhawk"))
+                            }
+                        };
+                        let _ = callback.send(result);
+                    },
+                    XbeeAction::UploadMission(items) => {
+                        let result = upload_mission(&mut mavlink_sink, &mut mavlink_stream, pixhawk, &items).await
+                            .context("Could not upload mission");
+                        let _ = callback.send(result);
+                    },
+                    XbeeAction::StartMission => {
+                        /* arm the drone and start the mission from its first item */
+                        let result = async {
+                            mavlink_command(&mut mavlink_sink, &mut mavlink_stream, pixhawk,
+                                MavCmd::MAV_CMD_COMPONENT_ARM_DISARM, [1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0]).await?;
+                            mavlink_command(&mut mavlink_sink, &mut mavlink_stream, pixhawk,
+                                MavCmd::MAV_CMD_MISSION_START, [0.0; 7]).await
+                        }.await.context("Could not start mission");
+                        let _ = callback.send(result);
+                    },
                     XbeeAction::Mavlink(action) => {
                         match autonomous_mode {
                             true => {
@@ -337,6 +1136,40 @@ async fn xbee(
     }
 }
 
+/* check the kernel messages of the Up Core periodically and yield each new line that reports a fault,
+   the messages that were logged before the first check are also checked */
+fn kernel_faults_stream<'dev>(
+    device: &'dev fernbedienung::Device
+) -> impl Stream<Item = String> + 'dev {
+    async_stream::stream! {
+        let mut last_line = Option::<String>::None;
+        loop {
+            let messages = tokio::time::timeout(KERNEL_MESSAGES_TIMEOUT, device.kernel_messages()).await
+                .context("Timeout while retrieving kernel messages")
+                .and_then(|result| result.context("Could not retrieve kernel messages"));
+            match messages {
+                Ok(messages) => {
+                    let lines = messages.lines().collect::<Vec<_>>();
+                    /* all lines are new if the last line of the previous check has left the buffer */
+                    let start = last_line.as_ref()
+                        .and_then(|last_line| lines.iter().rposition(|line| *line == last_line.as_str()))
+                        .map_or(0, |position| position + 1);
+                    for line in &lines[start..] {
+                        if KERNEL_FAULT_PATTERNS.iter().any(|pattern| line.contains(pattern)) {
+                            yield line.trim().to_owned();
+                        }
+                    }
+                    if let Some(line) = lines.last() {
+                        last_line = Some(line.to_string());
+                    }
+                },
+                Err(error) => log::debug!("{:#}", error),
+            }
+            tokio::time::sleep(KERNEL_MESSAGES_INTERVAL).await;
+        }
+    }
+}
+
 fn fernbedienung_link_strength_stream<'dev>(
     device: &'dev fernbedienung::Device
 ) -> impl Stream<Item = anyhow::Result<i32>> + 'dev {
@@ -395,19 +1228,31 @@ async fn bash(
                     process.set(device.run(bash, terminate_rx, stdin_rx, stdout_tx, stderr_tx).right_future());
                     let _ = callback.send(Ok(()));
                 },
-                TerminalAction::Run(mut command) => if let Some(tx) = stdin.as_ref() {
-                    command.push_str("\r");
-                    let result = tx.send(BytesMut::from(command.as_bytes())).await
-                        .map_err(|_| {
-                            /* remove the "\r" before including the command in the error message */
-                            command.pop();
-                            anyhow::anyhow!("Could not send \"{}\" to Bash terminal", command)
-                        });
-                    let _ = callback.send(result);
+                TerminalAction::Run(mut command) => match stdin.as_ref() {
+                    Some(tx) => {
+                        command.push('\r');
+                        let result = tx.send(BytesMut::from(command.as_bytes())).await
+                            .map_err(|_| {
+                                /* remove the "\r" before including the command in the error message */
+                                command.pop();
+                                anyhow::anyhow!("Could not send \"{}\" to Bash terminal", command)
+                            });
+                        let _ = callback.send(result);
+                    },
+                    /* respond instead of dropping the callback so that the client gets a meaningful error */
+                    None => {
+                        let _ = callback.send(Err(anyhow::anyhow!("Bash terminal has not been started")));
+                    }
                 },
-                TerminalAction::Stop => if let Some(tx) = terminate.take() {
-                    let _ = tx.send(());
-                    let _ = callback.send(Ok(()));
+                TerminalAction::Stop => match terminate.take() {
+                    Some(tx) => {
+                        let _ = tx.send(());
+                        let _ = callback.send(Ok(()));
+                    },
+                    /* the terminal may have already terminated, e.g., after running exit */
+                    None => {
+                        let _ = callback.send(Ok(()));
+                    }
                 }
             },
             result = &mut process => {
@@ -430,7 +1275,74 @@ async fn bash(
     }
 }
 
+/* a PX4 firmware file is a JSON object which contains the compressed image and the board it is built for */
+fn check_px4_firmware(firmware: &[u8]) -> anyhow::Result<()> {
+    let firmware: serde_json::Value = serde_json::from_slice(firmware)
+        .context("Firmware is not a PX4 firmware file")?;
+    for field in ["board_id", "image", "image_size"].iter() {
+        if firmware.get(field).is_none() {
+            anyhow::bail!("Firmware does not contain the field \"{}\"", field);
+        }
+    }
+    Ok(())
+}
+
+/* parse the progress of the uploader from lines such as "Program: [=====     ] 25.0%" */
+fn parse_px4_uploader_progress(line: &str) -> Option<(String, f32)> {
+    let (stage, rest) = line.split_once(':')?;
+    let (_, progress) = rest.rsplit_once(']')?;
+    let progress = progress.trim().strip_suffix('%')?.parse().ok()?;
+    Some((stage.trim().to_owned(), progress))
+}
+
+/* upload PX4 firmware to the Pixhawk using the uploader on the Up Core, the uploader verifies the
+   firmware after it has been programmed and only exits successfully if the verification passed */
+async fn pixhawk_upload_firmware(
+    device: &fernbedienung::Device,
+    firmware: Vec<u8>,
+    updates_tx: broadcast::Sender<Update>,
+) -> anyhow::Result<()> {
+    let path = device.create_temp_dir().await
+        .context("Could not create temporary directory")?;
+    device.upload(&path, "firmware.px4", firmware).await
+        .context("Could not upload firmware to Up Core")?;
+    let (port, baud_rate) = PIXHAWK_PORT.split_once(':')
+        .unwrap_or((PIXHAWK_PORT, "57600"));
+    let process = fernbedienung::Process {
+        target: PX4_UPLOADER.into(),
+        working_dir: Some(path.into()),
+        args: vec![
+            "--port".to_owned(), port.to_owned(),
+            "--baud-bootloader".to_owned(), PX4_BOOTLOADER_BAUD_RATE.to_string(),
+            "--baud-flightstack".to_owned(), baud_rate.to_owned(),
+            "firmware.px4".to_owned(),
+        ],
+    };
+    let (stdout_tx, stdout_rx) = mpsc::channel(8);
+    let uploader = device.run(process, None, None, stdout_tx, None);
+    tokio::pin!(uploader);
+    let mut stdout = ReceiverStream::new(stdout_rx);
+    /* the uploader redraws its progress bars using carriage returns */
+    let mut buffer = String::new();
+    loop {
+        tokio::select! {
+            Some(data) = stdout.next() => {
+                buffer.push_str(&String::from_utf8_lossy(&data));
+                while let Some(index) = buffer.find(['\r', '\n']) {
+                    let line = buffer.drain(..=index).collect::<String>();
+                    if let Some((stage, progress)) = parse_px4_uploader_progress(&line) {
+                        let _ = updates_tx.send(Update::PixhawkFirmware(FirmwareUpload::Progress(stage, progress)));
+                    }
+                }
+            },
+            result = &mut uploader => return result
+                .context("Could not program and verify the firmware"),
+        }
+    }
+}
+
 async fn argos(device: &fernbedienung::Device,
+    updates_tx: broadcast::Sender<Update>,
     callback: oneshot::Sender<anyhow::Result<()>>,
     software: Software,
     id: impl Into<Option<String>>,
@@ -439,6 +1351,7 @@ async fn argos(device: &fernbedienung::Device,
     journal: impl Into<Option<mpsc::Sender<journal::Action>>>,
     wait_rx: impl Into<Option<oneshot::Receiver<()>>>,
     stop_rx: oneshot::Receiver<()>,
+    restart_policy: RestartPolicy,
 ) {
     let id = id.into();
     let router_socket = router_socket.into();
@@ -465,7 +1378,11 @@ async fn argos(device: &fernbedienung::Device,
     };
     /* upload the control software */
     for (filename, contents) in software.0.iter() {
-        match device.upload(&path, filename, contents.clone()).await {
+        let upload = fernbedienung_ext::upload_with_progress(device, &path, filename, contents.clone(),
+            |sent, total| {
+                let _ = updates_tx.send(Update::Upload { filename: filename.clone(), sent, total });
+            });
+        match upload.await {
             Ok(_) => continue,
             Err(error) => {
                 let result = Err(error).context("Could not upload software");
@@ -498,54 +1415,78 @@ async fn argos(device: &fernbedienung::Device,
     args.extend(router_socket.into_iter().flat_map(|socket| vec!["--router".to_owned(), socket.to_string()]));
     args.extend(id.iter().flat_map(|id| vec!["--id".to_owned(), id.clone()]));
     args.extend(pixhawk_port.into_iter().flat_map(|port| vec!["--pixhawk".to_owned(), port]));
-    let process = fernbedienung::Process {
-        target: "argos3".into(),
-        working_dir: Some(path.into()),
-        args,
-    };
-    let (stdout_tx, mut forward_stdout, stderr_tx, mut forward_stderr) = match (journal, id) {
-        (Some(journal), Some(id)) => {
-            use journal::{ARGoS, Event, Action};
-            let (stdout_tx, stdout_rx) = mpsc::channel(8);
-            let (stderr_tx, stderr_rx) = mpsc::channel(8);
-            let stdout_stream = ReceiverStream::new(stdout_rx);
-            let stderr_stream = ReceiverStream::new(stderr_rx);
-            let journal_sink = PollSender::new(journal.clone());
-            let stdout_robot_id = id.clone();
-            let forward_stdout = stdout_stream.map(move |data: BytesMut| 
-                Ok(Action::Record(Event::ARGoS(stdout_robot_id.clone(), ARGoS::StandardOutput(data)))))
-                    .forward(journal_sink).right_future();
-            let journal_sink = PollSender::new(journal);
-            let forward_stderr = stderr_stream.map(move |data: BytesMut| 
-                Ok(Action::Record(Event::ARGoS(id.clone(), ARGoS::StandardError(data)))))
-                    .forward(journal_sink).right_future();
-            (Some(stdout_tx), forward_stdout, Some(stderr_tx), forward_stderr)
-        },
-        (_, _) => {
-            (None, futures::future::pending().left_future(),
-             None, futures::future::pending().left_future())
-        }
-    };
-    let (terminate_tx, terminate_rx) = oneshot::channel();      
-    let argos = device.run(process, terminate_rx, None, stdout_tx, stderr_tx);
-    tokio::pin!(argos);
+    let mut restarts = 0;
     loop {
-        tokio::select! {
-            _ = &mut forward_stdout => {
-                /* disable while we wait for the other futures to finish */
-                forward_stdout = futures::future::pending().left_future();
+        let process = fernbedienung::Process {
+            target: "argos3".into(),
+            working_dir: Some(path.clone().into()),
+            args: args.clone(),
+        };
+        let (stdout_tx, mut forward_stdout, stderr_tx, mut forward_stderr) = match (journal.as_ref(), id.as_ref()) {
+            (Some(journal), Some(id)) => {
+                use journal::{ARGoS, Event, Action};
+                let (stdout_tx, stdout_rx) = mpsc::channel(8);
+                let (stderr_tx, stderr_rx) = mpsc::channel(8);
+                let stdout_stream = ReceiverStream::new(stdout_rx);
+                let stderr_stream = ReceiverStream::new(stderr_rx);
+                let journal_sink = PollSender::new(journal.clone()).sink_map_err(|_| ());
+                let stdout_robot_id = id.clone();
+                let forward_stdout = stdout_stream.map(move |data: BytesMut| 
+                    Ok(Action::Record(Event::ARGoS(stdout_robot_id.clone(), ARGoS::StandardOutput(data)))))
+                        .forward(journal_sink).right_future();
+                let journal_sink = PollSender::new(journal.clone()).sink_map_err(|_| ());
+                let stderr_robot_id = id.clone();
+                let forward_stderr = stderr_stream.map(move |data: BytesMut| 
+                    Ok(Action::Record(Event::ARGoS(stderr_robot_id.clone(), ARGoS::StandardError(data)))))
+                        .forward(journal_sink).right_future();
+                (Some(stdout_tx), forward_stdout, Some(stderr_tx), forward_stderr)
             },
-            _ = &mut forward_stderr => {
-                /* disable while we wait for the other futures to finish */
-                forward_stderr = futures::future::pending().left_future();
-            },
-            /* local shutdown */
-            _ = &mut stop_rx => {
-                let _ = terminate_tx.send(());
-                break;
+            (_, _) => {
+                (None, futures::future::pending().left_future(),
+                 None, futures::future::pending().left_future())
+            }
+        };
+        let (terminate_tx, terminate_rx) = oneshot::channel();      
+        let argos = device.run(process, terminate_rx, None, stdout_tx, stderr_tx);
+        tokio::pin!(argos);
+        let result = loop {
+            tokio::select! {
+                _ = &mut forward_stdout => {
+                    /* disable while we wait for the other futures to finish */
+                    forward_stdout = futures::future::pending().left_future();
+                },
+                _ = &mut forward_stderr => {
+                    /* disable while we wait for the other futures to finish */
+                    forward_stderr = futures::future::pending().left_future();
+                },
+                /* local shutdown */
+                _ = &mut stop_rx => {
+                    let _ = terminate_tx.send(());
+                    return;
+                }
+                /* argos finished */
+                result = &mut argos => break result,
             }
-            /* argos finished */
-            _ = &mut argos => break,
+        };
+        /* restart ARGoS with the same arguments if it exited with an error and the policy allows it */
+        match (result, restart_policy) {
+            (Err(error), RestartPolicy::OnFailure { max_retries }) if restarts < max_retries => {
+                restarts += 1;
+                let robot = id.as_deref().unwrap_or("robot");
+                let message = format!("Restarting ARGoS on {} ({} of {}): {}", robot, restarts, max_retries, error);
+                log::warn!("{}", message);
+                if let Some(journal) = journal.as_ref() {
+                    let event = shared::event::Event {
+                        timestamp: chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+                        severity: shared::event::Severity::Warning,
+                        robot: id.clone(),
+                        subsystem: String::from("argos"),
+                        message,
+                    };
+                    let _ = journal.send(journal::Action::Record(journal::Event::Arena(event))).await;
+                }
+            },
+            _ => break,
         }
     }
 }
@@ -553,7 +1494,10 @@ async fn argos(device: &fernbedienung::Device,
 async fn fernbedienung(
     device: fernbedienung::Device,
     mut rx: mpsc::Receiver<(oneshot::Sender<anyhow::Result<()>>, FernbedienungAction)>,
-    updates_tx: broadcast::Sender<Update>
+    updates_tx: broadcast::Sender<Update>,
+    cameras: Vec<camera::Configuration>,
+    frame_txs: FrameSenders,
+    identify_command: Option<String>,
 ) {
     /* ARGos task */
     let argos_task = futures::future::pending().left_future();
@@ -564,12 +1508,31 @@ async fn fernbedienung(
     let (mut bash_tx, bash_rx) = mpsc::channel(8);
     let bash_task = bash(&device, bash_rx, updates_tx.clone());
     tokio::pin!(bash_task);
+    /* firmware upload task */
+    let firmware_task = futures::future::pending().left_future();
+    let mut firmware_uploading = false;
+    tokio::pin!(firmware_task);
+    /* identify task, which runs the identify command of the profile */
+    let identify_task = futures::future::pending().left_future();
+    let mut identify_callback = Option::default();
+    tokio::pin!(identify_task);
+    /* maintenance task */
+    let maintenance_task = futures::future::pending().left_future();
+    let mut maintenance_callbacks = Option::default();
+    tokio::pin!(maintenance_task);
     /* link strength stream */
     let link_strength_stream = fernbedienung_link_strength_stream(&device)
         .map_ok(Update::FernbedienungSignal);
     let link_strength_stream_throttled =
         tokio_stream::StreamExt::throttle(link_strength_stream, Duration::from_millis(1000));
     tokio::pin!(link_strength_stream_throttled);
+    /* temperature stream */
+    let temperature_stream = fernbedienung_ext::temperature_stream(&device)
+        .map(Update::Temperature);
+    tokio::pin!(temperature_stream);
+    /* kernel faults stream */
+    let kernel_faults_stream = kernel_faults_stream(&device);
+    tokio::pin!(kernel_faults_stream);
     /* camera stream */
     let mut cameras_stream: tokio_stream::StreamMap<String, _> =
         tokio_stream::StreamMap::new();
@@ -578,7 +1541,12 @@ async fn fernbedienung(
         tokio::select! {
             Some((camera, result)) = cameras_stream.next() => {
                 let result: reqwest::Result<bytes::Bytes> = result;
-                let update = Update::Camera { camera, result: result.map_err(|e| e.to_string()) };
+                frame_txs.send(&camera, Some(Arc::new(result.map_err(|e| e.to_string()))));
+            },
+            Some(fault) = kernel_faults_stream.next() => {
+                let _ = updates_tx.send(Update::KernelFault(fault));
+            },
+            Some(update) = temperature_stream.next() => {
                 let _ = updates_tx.send(update);
             },
             Some(response) = link_strength_stream_throttled.next() => match response {
@@ -594,15 +1562,66 @@ async fn fernbedienung(
                 Some((callback, action)) => match action {
                     FernbedienungAction::SetCameraStream(enable) => {
                         cameras_stream.clear();
+                        frame_txs.clear();
                         if enable {
-                            for &(camera, width, height, port) in DRONE_CAMERAS_CONFIG {
-                                let stream = MjpegStreamerStream::new(&device, camera, width, height, port);
-                                let stream = tokio_stream::StreamExt::throttle(stream, Duration::from_millis(200));
-                                cameras_stream.insert(camera.to_owned(), Box::pin(stream));
+                            for camera in &cameras {
+                                let stream = MjpegStreamerStream::new(&device, camera);
+                                cameras_stream.insert(camera.device.clone(), Box::pin(stream));
                             }
                         }
                         let _ = callback.send(Ok(()));
                     },
+                    FernbedienungAction::SetCameraDeviceStream(camera, enable) => {
+                        let result = match cameras.iter().find(|config| config.device == camera) {
+                            Some(config) => {
+                                if enable {
+                                    let stream = MjpegStreamerStream::new(&device, config);
+                                    cameras_stream.insert(camera, Box::pin(stream));
+                                }
+                                else {
+                                    cameras_stream.remove(&camera);
+                                    frame_txs.send(&camera, None);
+                                }
+                                Ok(())
+                            },
+                            None => Err(anyhow::anyhow!("Camera {} is not configured", camera)),
+                        };
+                        let _ = callback.send(result);
+                    },
+                    FernbedienungAction::GetKernelMessages(messages_tx) => {
+                        let result = tokio::time::timeout(KERNEL_MESSAGES_TIMEOUT, device.kernel_messages()).await
+                            .context("Timeout while retrieving kernel messages")
+                            .and_then(|result| result.context("Could not retrieve kernel messages"))
+                            .map(|messages| {
+                                let _ = messages_tx.send(messages);
+                            });
+                        let _ = callback.send(result);
+                    },
+                    FernbedienungAction::CaptureImage(camera, image_tx) => {
+                        let result = match cameras.iter().find(|config| config.device == camera) {
+                            Some(config) => {
+                                /* stop the stream while the image is captured so that the device is available */
+                                let streaming = cameras_stream.remove(&camera).is_some();
+                                if streaming {
+                                    tokio::time::sleep(CAMERA_RELEASE_DELAY).await;
+                                }
+                                let capture = fernbedienung_ext::capture_image(&device, config);
+                                let result = tokio::time::timeout(CAPTURE_IMAGE_TIMEOUT, capture).await
+                                    .context("Timeout while capturing image")
+                                    .and_then(|result| result.context("Could not capture image"))
+                                    .map(|image| {
+                                        let _ = image_tx.send(image);
+                                    });
+                                if streaming {
+                                    let stream = MjpegStreamerStream::new(&device, config);
+                                    cameras_stream.insert(camera, Box::pin(stream));
+                                }
+                                result
+                            },
+                            None => Err(anyhow::anyhow!("Camera {} is not configured", camera)),
+                        };
+                        let _ = callback.send(result);
+                    },
                     FernbedienungAction::Halt => {
                         let result = device.halt().await
                             .context("Could not halt Up Core");
@@ -624,7 +1643,13 @@ async fn fernbedienung(
                             anyhow::anyhow!("Could not send {:?} to Bash terminal: channel is {}", action, reason);
                         let _ = callback.send(Err(error));
                     },
-                    FernbedienungAction::SetupExperiment(id, software, journal) => match argos_stop_tx.as_ref() {
+                    FernbedienungAction::SetupExperiment(..) if maintenance_callbacks.is_some() => {
+                        let _ = callback.send(Err(anyhow::anyhow!("Maintenance script is running")));
+                    },
+                    FernbedienungAction::SetupExperiment(..) if firmware_uploading => {
+                        let _ = callback.send(Err(anyhow::anyhow!("Firmware is being uploaded to the Pixhawk")));
+                    },
+                    FernbedienungAction::SetupExperiment(id, software, journal, restart_policy) => match argos_stop_tx.as_ref() {
                         Some(_) => {
                             let _ = callback.send(Err(anyhow::anyhow!("ARGoS is already setup or running")));
                         }
@@ -647,6 +1672,7 @@ async fn fernbedienung(
                                     let (stop_tx, stop_rx) = oneshot::channel();
                                     let task = argos(
                                         &device,
+                                        updates_tx.clone(),
                                         callback,
                                         software,
                                         id,
@@ -654,7 +1680,8 @@ async fn fernbedienung(
                                         PIXHAWK_PORT.to_owned(),
                                         journal,
                                         start_rx,
-                                        stop_rx);
+                                        stop_rx,
+                                        restart_policy);
                                     argos_task.set(task.left_future().right_future());
                                     argos_start_tx = Some(start_tx);
                                     argos_stop_tx = Some(stop_tx);
@@ -680,6 +1707,17 @@ async fn fernbedienung(
                             let _ = callback.send(Ok(()));
                         }
                     },
+                    FernbedienungAction::Identify if identify_command.is_some() => match identify_callback {
+                        Some(_) => {
+                            let _ = callback.send(Err(anyhow::anyhow!("Drone is already being identified")));
+                        },
+                        None => {
+                            let script = identify_command.clone().unwrap().into_bytes();
+                            let task = fernbedienung_ext::run_script(&device, script, IDENTIFY_COMMAND_TIMEOUT);
+                            identify_task.set(task.right_future());
+                            identify_callback = Some(callback);
+                        }
+                    },
                     FernbedienungAction::Identify => match argos_stop_tx.as_ref() {
                         Some(_) => {
                             let _ = callback.send(Err(anyhow::anyhow!("ARGoS is already running")));
@@ -697,13 +1735,40 @@ async fn fernbedienung(
                                     let (start_tx, start_rx) = oneshot::channel();
                                     start_tx.send(()).unwrap();
                                     let (stop_tx, stop_rx) = oneshot::channel();
-                                    let task = argos(&device, callback, software, None, None, None, None, start_rx, stop_rx);
+                                    let task = argos(&device, updates_tx.clone(), callback, software, None, None, None, None, start_rx, stop_rx, RestartPolicy::Never);
                                     argos_task.set(task.right_future().right_future());
                                     argos_stop_tx = Some(stop_tx);
                                 }
                             }
                         }
                     },
+                    FernbedienungAction::Spin => {
+                        let _ = callback.send(Err(anyhow::anyhow!("Drones cannot be identified by motion")));
+                    },
+                    FernbedienungAction::UploadPixhawkFirmware(firmware) => {
+                        let result = match (firmware_uploading, argos_stop_tx.as_ref()) {
+                            (true, _) => Err(anyhow::anyhow!("Firmware is already being uploaded")),
+                            (_, Some(_)) => Err(anyhow::anyhow!("ARGoS is using the Pixhawk")),
+                            (false, None) => check_px4_firmware(&firmware).map(|_| {
+                                /* the upload takes a while, its progress is reported as updates */
+                                let task = pixhawk_upload_firmware(&device, firmware, updates_tx.clone());
+                                firmware_task.set(task.right_future());
+                                firmware_uploading = true;
+                            }),
+                        };
+                        let _ = callback.send(result);
+                    },
+                    FernbedienungAction::RunScript(script, timeout, output_tx) => match maintenance_callbacks {
+                        Some(_) => {
+                            let _ = callback.send(Err(anyhow::anyhow!("Maintenance script is already running")));
+                        },
+                        None => {
+                            /* the callback is answered once the script completes */
+                            let task = fernbedienung_ext::run_script(&device, script, timeout);
+                            maintenance_task.set(task.right_future());
+                            maintenance_callbacks = Some((callback, output_tx));
+                        }
+                    },
                 },
                 None => break,
             },
@@ -719,42 +1784,172 @@ async fn fernbedienung(
                 argos_start_tx = None;
                 argos_stop_tx = None;
             },
+            result = &mut identify_task => {
+                if let Some(callback) = identify_callback.take() {
+                    let _ = callback.send(result.map(|_| ()).context("Could not run identify command"));
+                }
+                identify_task.set(futures::future::pending().left_future());
+            },
+            result = &mut maintenance_task => {
+                if let Some((callback, output_tx)) = maintenance_callbacks.take() {
+                    let result = result.map(|output| {
+                        let _ = output_tx.send(output);
+                    });
+                    let _ = callback.send(result);
+                }
+                maintenance_task.set(futures::future::pending().left_future());
+            },
+            result = &mut firmware_task => {
+                let update = match result {
+                    Ok(_) => FirmwareUpload::Complete,
+                    Err(error) => FirmwareUpload::Failed(format!("{:#}", error)),
+                };
+                let _ = updates_tx.send(Update::PixhawkFirmware(update));
+                firmware_task.set(futures::future::pending().left_future());
+                firmware_uploading = false;
+            },
+        }
+    }
+}
+
+/* keep track of the state of the robot from the updates that are sent to the subscribers */
+fn update_state(state: &mut snapshot::Robot, update: Update) {
+    match update {
+        Update::Battery(battery) => state.battery = Some(battery),
+        Update::FernbedienungConnected(addr) => if let Some(link) = state.link("fernbedienung") {
+            link.addr = Some(addr);
+        },
+        Update::FernbedienungDisconnected => if let Some(link) = state.link("fernbedienung") {
+            link.addr = None;
+            link.signal = None;
+        },
+        Update::FernbedienungSignal(signal) => if let Some(link) = state.link("fernbedienung") {
+            link.signal = Some(signal);
+        },
+        Update::XbeeConnected(addr) => if let Some(link) = state.link("xbee") {
+            link.addr = Some(addr);
+        },
+        Update::XbeeDisconnected => if let Some(link) = state.link("xbee") {
+            link.addr = None;
+            link.signal = None;
+        },
+        Update::XbeeSignal(signal) => if let Some(link) = state.link("xbee") {
+            link.signal = Some(signal);
+        },
+        _ => {}
+    }
+}
+
+/* apply the updates that have been sent since the state was last updated */
+fn sync_state(state: &mut snapshot::Robot, state_rx: &mut broadcast::Receiver<Update>) {
+    loop {
+        match state_rx.try_recv() {
+            Ok(update) => update_state(state, update),
+            Err(broadcast::error::TryRecvError::Lagged(_)) => continue,
+            Err(_) => break,
         }
     }
 }
 
-pub async fn new(mut action_rx: Receiver) {
+/* the updates that bring a new subscriber up to date with the state of the robot */
+fn initial_updates(state: &snapshot::Robot) -> Vec<Update> {
+    let mut updates = Vec::new();
+    for link in &state.links {
+        let addr = match link.addr {
+            Some(addr) => addr,
+            None => continue,
+        };
+        match link.name.as_str() {
+            "fernbedienung" => {
+                updates.push(Update::FernbedienungConnected(addr));
+                updates.extend(link.signal.map(Update::FernbedienungSignal));
+            },
+            "xbee" => {
+                updates.push(Update::XbeeConnected(addr));
+                updates.extend(link.signal.map(Update::XbeeSignal));
+            },
+            _ => {}
+        }
+    }
+    updates.extend(state.battery.map(Update::Battery));
+    updates
+}
+
+pub async fn new(
+    mut action_rx: Receiver,
+    cameras: Vec<camera::Configuration>,
+    profile: Profile,
+    rigid_body: Option<(i32, mpsc::Sender<tracking::Action>)>,
+    timeouts: timeouts::Configuration,
+    capacity: usize
+) {
     /* fernbedienung task state */
     let fernbedienung_task = futures::future::pending().left_future();
     let mut fernbedienung_tx = Option::default();
-    let mut fernbedienung_addr = Option::default();
     tokio::pin!(fernbedienung_task);
+    /* the address of the Up Core for the MAVLink connection over UDP */
+    let (upcore_addr_tx, upcore_addr_rx) = watch::channel(None);
     /* xbee task state */
     let xbee_task = futures::future::pending().left_future();
     let mut xbee_tx = Option::default();
-    let mut xbee_addr = Option::default();
     tokio::pin!(xbee_task);
+    /* safe shutdown state, the power to the UP Core is only cut once it has halted */
+    let mut upcore_power_off_callback: Option<oneshot::Sender<anyhow::Result<()>>> = None;
+    let upcore_halt = futures::future::pending().left_future();
+    tokio::pin!(upcore_halt);
+    let upcore_power_off = futures::future::pending().left_future();
+    tokio::pin!(upcore_power_off);
     /* updates_tx is for sending changes in state to subscribers (e.g., the webui) */
-    let (updates_tx, _) = broadcast::channel(16);
+    let (updates_tx, _) = broadcast::channel(capacity);
+    /* the state of the robot for snapshots of the arena */
+    let mut state = snapshot::Robot::new(&["fernbedienung", "xbee"]);
+    let mut state_rx = updates_tx.subscribe();
+    /* the most recent frame of each camera, the receivers are kept so that the frames can be sent
+       while nobody is subscribed */
+    let (frame_txs, frame_rxs) = crate::robot::frame_channels(&cameras);
     loop {
         tokio::select! {
+            /* updates that were missed while the task was busy are skipped */
+            Ok(update) = state_rx.recv() => update_state(&mut state, update),
             Some(action) = action_rx.recv() => match action {
                 Action::AssociateFernbedienung(device) => {
                     let (tx, rx) = mpsc::channel(8);
                     fernbedienung_tx = Some(tx);
-                    fernbedienung_addr = Some(device.addr);
+                    let _ = upcore_addr_tx.send(Some(device.addr));
                     let _ = updates_tx.send(Update::FernbedienungConnected(device.addr));
-                    let task = tokio::spawn(fernbedienung(device, rx, updates_tx.clone()));
+                    let task = tokio::spawn(fernbedienung(device, rx, updates_tx.clone(), cameras.clone(), frame_txs.clone(),
+                        profile.identify.command.clone()));
                     fernbedienung_task.set(task.right_future());
                 },
                 Action::AssociateXbee(device) => {
                     let (tx, rx) = mpsc::channel(8);
                     xbee_tx = Some(tx);
-                    xbee_addr = Some(device.addr);
                     let _ = updates_tx.send(Update::XbeeConnected(device.addr));
-                    let task = tokio::spawn(xbee(device, rx, updates_tx.clone()));
+                    let task = tokio::spawn(xbee(device, rx, updates_tx.clone(), profile.clone(), upcore_addr_rx.clone(), rigid_body.clone(), timeouts.mavlink));
                     xbee_task.set(task.right_future());
                 },
+                Action::ExecuteXbeeAction(callback, XbeeAction::SetUpCorePower(false))
+                    if upcore_power_off_callback.is_some() => {
+                    let _ = callback.send(Err(anyhow::anyhow!("UP Core is already shutting down")));
+                },
+                /* halt the UP Core via Fernbedienung before cutting its power */
+                Action::ExecuteXbeeAction(callback, XbeeAction::SetUpCorePower(false))
+                    if xbee_tx.is_some() && fernbedienung_tx.is_some() => {
+                    let (halt_callback_tx, halt_callback_rx) = oneshot::channel();
+                    let halt = (halt_callback_tx, FernbedienungAction::Halt);
+                    match fernbedienung_tx.as_ref().unwrap().send(halt).await {
+                        Ok(_) => {
+                            log::info!("Halting UP Core before cutting its power");
+                            upcore_power_off_callback = Some(callback);
+                            upcore_halt.set(halt_callback_rx.right_future());
+                            upcore_power_off.set(tokio::time::sleep(UPCORE_HALT_TIMEOUT).right_future());
+                        },
+                        Err(_) => {
+                            let error = anyhow::anyhow!("Could not communicate with Fernbedienung task");
+                            let _ = callback.send(Err(error).context("Could not halt UP Core"));
+                        }
+                    }
+                },
                 Action::ExecuteXbeeAction(callback, action) => match xbee_tx.as_ref() {
                     Some(tx) => {
                         if let Err(mpsc::error::SendError((callback, _))) = tx.send((callback, action)).await {
@@ -766,6 +1961,52 @@ pub async fn new(mut action_rx: Receiver) {
                         let _ = callback.send(Err(error));
                     }
                 },
+                /* play the tune of the profile on the Pixhawk alongside the identification on the Up Core,
+                   the drone has been identified if either of them succeeds */
+                Action::ExecuteFernbedienungAction(callback, FernbedienungAction::Identify)
+                    if profile.identify.tune.is_some() && xbee_tx.is_some() => {
+                    let tune = profile.identify.tune.clone().unwrap();
+                    let xbee_tx = xbee_tx.clone().unwrap();
+                    let fernbedienung_tx = fernbedienung_tx.clone();
+                    tokio::spawn(async move {
+                        let play_tune = async {
+                            let (callback_tx, callback_rx) = oneshot::channel();
+                            if xbee_tx.send((callback_tx, XbeeAction::PlayTune(tune))).await.is_err() {
+                                return Err(anyhow::anyhow!("Could not communicate with Xbee task"));
+                            }
+                            callback_rx.await
+                                .map_err(|_| anyhow::anyhow!("Xbee task did not respond"))
+                                .and_then(|result| result)
+                        };
+                        let identify = async {
+                            let fernbedienung_tx = match fernbedienung_tx {
+                                Some(fernbedienung_tx) => fernbedienung_tx,
+                                None => return Err(anyhow::anyhow!("Fernbedienung is not connected")),
+                            };
+                            let (callback_tx, callback_rx) = oneshot::channel();
+                            if fernbedienung_tx.send((callback_tx, FernbedienungAction::Identify)).await.is_err() {
+                                return Err(anyhow::anyhow!("Could not communicate with Fernbedienung task"));
+                            }
+                            callback_rx.await
+                                .map_err(|_| anyhow::anyhow!("Fernbedienung task did not respond"))
+                                .and_then(|result| result)
+                        };
+                        let result = match tokio::join!(play_tune, identify) {
+                            (Err(tune_error), Err(identify_error)) =>
+                                Err(anyhow::anyhow!("Could not identify drone: {:#}; {:#}", tune_error, identify_error)),
+                            (Err(error), Ok(_)) => {
+                                log::warn!("Could not play identify tune: {:#}", error);
+                                Ok(())
+                            },
+                            (Ok(_), Err(error)) => {
+                                log::warn!("Could not identify drone on the Up Core: {:#}", error);
+                                Ok(())
+                            },
+                            (Ok(_), Ok(_)) => Ok(()),
+                        };
+                        let _ = callback.send(result);
+                    });
+                },
                 Action::ExecuteFernbedienungAction(callback, action) => match fernbedienung_tx.as_ref() {
                     Some(tx) => {
                         if let Err(mpsc::error::SendError((callback, _))) = tx.send((callback, action)).await {
@@ -777,21 +2018,24 @@ pub async fn new(mut action_rx: Receiver) {
                         let _ = callback.send(Err(error));
                     }
                 },
+                Action::GetState(callback) => {
+                    sync_state(&mut state, &mut state_rx);
+                    let _ = callback.send(state.clone());
+                },
                 Action::Subscribe(callback) => {
-                    /* note that upon subscribing all updates should be sent to ensure
-                       that new clients are in sync */
-                    if let Ok(_) = callback.send(updates_tx.subscribe()) {
-                        if let Some(addr) = xbee_addr {
-                            let _ = updates_tx.send(Update::XbeeConnected(addr));
-                        }
-                        if let Some(addr) = fernbedienung_addr {
-                            let _ = updates_tx.send(Update::FernbedienungConnected(addr));
-                        }
-                    }
+                    sync_state(&mut state, &mut state_rx);
+                    let _ = callback.send((initial_updates(&state), updates_tx.subscribe()));
+                },
+                Action::SubscribeFrames(callback) => {
+                    let _ = callback.send(frame_rxs.clone());
                 },
-                Action::SetupExperiment(callback, id, software, journal) => match fernbedienung_tx.as_ref() {
+                Action::SubscribeFrameQueue(queue) => {
+                    frame_txs.subscribe(queue);
+                },
+                Action::SetupExperiment(callback, id, software, journal, restart_policy) => match fernbedienung_tx.as_ref() {
                     Some(tx) => {
-                        let action = FernbedienungAction::SetupExperiment(id, software, journal);
+                        state.set_software(&software);
+                        let action = FernbedienungAction::SetupExperiment(id, software, journal, restart_policy);
                         if let Err(mpsc::error::SendError((callback, _))) = tx.send((callback, action)).await {
                             let _ = callback.send(Err(anyhow::anyhow!("Could not communicate with Fernbedienung task")));
                         }
@@ -822,6 +2066,27 @@ pub async fn new(mut action_rx: Receiver) {
                     };
                     let _ = callback.send(result.await.context("Could not start experiment"));
                 },
+                Action::PreflightCheck(callback) => {
+                    let mut failures = Vec::new();
+                    match xbee_tx.as_ref() {
+                        Some(xbee_tx) => {
+                            let (xbee_callback_tx, xbee_callback_rx) = oneshot::channel();
+                            let result = match xbee_tx.send((xbee_callback_tx, XbeeAction::PreflightCheck)).await {
+                                Ok(_) => xbee_callback_rx.await
+                                    .unwrap_or_else(|_| Err(anyhow::anyhow!("Xbee did not respond"))),
+                                Err(_) => Err(anyhow::anyhow!("Could not communicate with Xbee task")),
+                            };
+                            if let Err(error) = result {
+                                failures.push(error.to_string());
+                            }
+                        },
+                        None => failures.push(String::from("Xbee is not connected")),
+                    }
+                    if fernbedienung_tx.is_none() {
+                        failures.push(String::from("Fernbedienung is not connected"));
+                    }
+                    let _ = callback.send(failures);
+                },
                 Action::StopExperiment => {
                     let terminate_argos = async {
                         let fernbedienung_tx = fernbedienung_tx.as_ref()
@@ -859,13 +2124,45 @@ pub async fn new(mut action_rx: Receiver) {
             },
             _ = &mut fernbedienung_task => {
                 fernbedienung_tx = None;
-                fernbedienung_addr = None;
+                let _ = upcore_addr_tx.send(None);
                 fernbedienung_task.set(futures::future::pending().left_future());
                 let _ = updates_tx.send(Update::FernbedienungDisconnected);
+                frame_txs.clear();
+                /* the UP Core is halting, give it some time to finish before cutting the power */
+                if upcore_power_off_callback.is_some() {
+                    upcore_power_off.set(tokio::time::sleep(UPCORE_HALT_GRACE_PERIOD).right_future());
+                }
+            },
+            result = &mut upcore_halt => {
+                upcore_halt.set(futures::future::pending().left_future());
+                /* a halt that fails before the connection has dropped means that the UP Core is still running */
+                if let Ok(Err(error)) = result {
+                    upcore_power_off.set(futures::future::pending().left_future());
+                    if let Some(callback) = upcore_power_off_callback.take() {
+                        let _ = callback.send(Err(error).context("Could not halt UP Core, power was not cut"));
+                    }
+                }
+            },
+            _ = &mut upcore_power_off => {
+                upcore_halt.set(futures::future::pending().left_future());
+                upcore_power_off.set(futures::future::pending().left_future());
+                if let Some(callback) = upcore_power_off_callback.take() {
+                    match xbee_tx.as_ref() {
+                        Some(tx) => {
+                            let action = XbeeAction::SetUpCorePower(false);
+                            if let Err(mpsc::error::SendError((callback, _))) = tx.send((callback, action)).await {
+                                let _ = callback.send(Err(anyhow::anyhow!("Could not communicate with Xbee task")));
+                            }
+                        },
+                        None => {
+                            let error = anyhow::anyhow!("Could not cut UP Core power: Xbee is not connected.");
+                            let _ = callback.send(Err(error));
+                        }
+                    }
+                }
             },
             join_result = &mut xbee_task => {
                 xbee_tx = None;
-                xbee_addr = None;
                 xbee_task.set(futures::future::pending().left_future());
                 let _ = updates_tx.send(Update::XbeeDisconnected);
                 match join_result {
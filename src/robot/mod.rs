@@ -1,10 +1,107 @@
 pub mod builderbot;
 pub mod drone;
 pub mod pipuck;
+pub mod plugin;
 
-use shared::experiment::software::Software;
-use tokio::sync::mpsc;
-use crate::journal;
+use shared::{camera, experiment::software::Software};
+use bytes::Bytes;
+use futures::StreamExt;
+use std::{collections::HashMap, sync::{Arc, Mutex, atomic::{AtomicU64, Ordering}}, time::Duration};
+use tokio::sync::{broadcast, mpsc, oneshot, watch};
+use tokio_stream::wrappers::WatchStream;
+use crate::{journal, subscription::Updates};
+
+/* the most recent frame from a camera, which is shared by the subscribers instead of being copied into
+   each of them. Only the most recent frame is kept, so a subscriber that falls behind (e.g., a slow
+   client of the web interface) skips the frames that it missed instead of buffering them */
+pub type Frame = Option<Arc<Result<Bytes, String>>>;
+
+/* a frame and the device of the camera that captured it */
+pub type QueuedFrame = (String, Arc<Result<Bytes, String>>);
+
+/* a bounded queue that receives every frame from the cameras of a robot rather than only the most
+   recent one (e.g., for the recorder). A frame that does not fit into the queue is dropped and
+   counted as an overrun so that the subscriber can report it */
+#[derive(Clone, Debug)]
+pub struct FrameQueue {
+    pub tx: mpsc::Sender<QueuedFrame>,
+    pub overruns: Arc<AtomicU64>,
+}
+
+pub fn frame_queue(capacity: usize) -> (FrameQueue, mpsc::Receiver<QueuedFrame>) {
+    let (tx, rx) = mpsc::channel(capacity);
+    (FrameQueue { tx, overruns: Arc::new(AtomicU64::new(0)) }, rx)
+}
+
+/* the frames of the cameras of a robot, indexed by device, and the queues that receive every frame */
+#[derive(Clone)]
+pub struct FrameSenders {
+    latest: Arc<HashMap<String, watch::Sender<Frame>>>,
+    queues: Arc<Mutex<Vec<FrameQueue>>>,
+}
+
+impl FrameSenders {
+    pub fn subscribe(&self, queue: FrameQueue) {
+        self.queues.lock().unwrap().push(queue);
+    }
+
+    /* None indicates that the camera is no longer streaming, the queues that have been closed by
+       their subscribers are removed */
+    pub fn send(&self, camera: &str, frame: Frame) {
+        if let Some(frame_tx) = self.latest.get(camera) {
+            let _ = frame_tx.send(frame.clone());
+        }
+        if let Some(frame) = frame {
+            self.queues.lock().unwrap().retain(|queue| {
+                match queue.tx.try_send((camera.to_owned(), frame.clone())) {
+                    Ok(_) => true,
+                    Err(mpsc::error::TrySendError::Full(_)) => {
+                        queue.overruns.fetch_add(1, Ordering::Relaxed);
+                        true
+                    },
+                    Err(mpsc::error::TrySendError::Closed(_)) => false,
+                }
+            });
+        }
+    }
+
+    pub fn clear(&self) {
+        for frame_tx in self.latest.values() {
+            let _ = frame_tx.send(None);
+        }
+    }
+}
+
+pub type FrameReceivers = HashMap<String, watch::Receiver<Frame>>;
+
+pub fn frame_channels(cameras: &[camera::Configuration]) -> (FrameSenders, FrameReceivers) {
+    let (frame_txs, frame_rxs): (HashMap<_, _>, HashMap<_, _>) = cameras.iter()
+        .map(|camera| {
+            let (frame_tx, frame_rx) = watch::channel(None);
+            ((camera.device.clone(), frame_tx), (camera.device.clone(), frame_rx))
+        })
+        .unzip();
+    let frame_txs = FrameSenders {
+        latest: Arc::new(frame_txs),
+        queues: Default::default(),
+    };
+    (frame_txs, frame_rxs)
+}
+
+/* the frames of the cameras as a stream of updates, starting with the current frame of each camera */
+pub fn camera_updates<U, F>(frame_rxs: FrameReceivers, update: F) -> Updates<U>
+    where U: Send + 'static,
+          F: Fn(String, Result<Bytes, String>) -> U + Clone + Send + 'static {
+    let streams = frame_rxs.into_iter()
+        .map(|(camera, frame_rx)| {
+            let update = update.clone();
+            WatchStream::new(frame_rx)
+                .filter_map(move |frame| futures::future::ready(frame
+                    .map(|frame| Ok(update(camera.clone(), frame.as_ref().clone())))))
+                .boxed()
+        });
+    futures::stream::select_all(streams).boxed()
+}
 
 #[derive(Debug)]
 pub enum FernbedienungAction {
@@ -12,10 +109,32 @@ pub enum FernbedienungAction {
     Reboot,
     Bash(TerminalAction),
     SetCameraStream(bool),
-    SetupExperiment(String, Software, mpsc::Sender<journal::Action>),
+    /* enable or disable the stream of a single camera, e.g., /dev/camera0 */
+    SetCameraDeviceStream(String, bool),
+    /* capture a single image from a camera, e.g., for calibration */
+    CaptureImage(String, oneshot::Sender<Bytes>),
+    /* retrieve the kernel messages (dmesg) of the robot's computer */
+    GetKernelMessages(oneshot::Sender<String>),
+    SetupExperiment(String, Software, mpsc::Sender<journal::Action>, RestartPolicy),
     StartExperiment,
     StopExperiment,
     Identify,
+    /* rotate on the spot so that the robot's rigid body can be identified */
+    Spin,
+    /* upload PX4 firmware to the Pixhawk via the Up Core, the progress is reported as updates */
+    UploadPixhawkFirmware(Vec<u8>),
+    /* run a maintenance script, e.g., to update the software, the output is sent once the script completes */
+    RunScript(Vec<u8>, Duration, oneshot::Sender<String>),
+}
+
+/* what to do if ARGoS exits with an error during an experiment */
+#[derive(Clone, Copy, Debug, Default)]
+pub enum RestartPolicy {
+    #[default]
+    Never,
+    OnFailure {
+        max_retries: u32,
+    },
 }
 
 #[derive(Debug)]
@@ -24,6 +143,34 @@ pub enum XbeeAction {
     SetUpCorePower(bool),
     SetPixhawkPower(bool),
     Mavlink(TerminalAction),
+    GetParameter([u8; 2]),
+    SetParameter([u8; 2], Vec<u8>),
+    WriteParameters,
+    UploadMission(Vec<shared::drone::MissionItem>),
+    StartMission,
+    /* check the battery level and the heartbeat of the Pixhawk */
+    PreflightCheck,
+    /* stream setpoints to the Pixhawk, None stops the supervised flight */
+    SetSupervisedFlight(Option<SupervisedFlight>),
+    /* list the other Xbees on the network and the signal strength of their responses */
+    DiscoverNodes,
+    /* list the flight logs on the SD card of the Pixhawk */
+    ListLogs,
+    /* download a flight log from the Pixhawk to the given path */
+    DownloadLog(u16, std::path::PathBuf),
+    /* the callback is answered once the Pixhawk has accepted the command, the sender is answered once
+       the drone has landed and disarmed, with false if the drone was not flying */
+    Land(shared::drone::Landing, oneshot::Sender<anyhow::Result<bool>>),
+    /* play a tune on the buzzer of the Pixhawk, e.g., to identify the drone */
+    PlayTune(String),
+}
+
+/* fly a drone to a target position using the poses of its rigid body as feedback */
+#[derive(Debug)]
+pub struct SupervisedFlight {
+    pub poses: broadcast::Receiver<shared::tracking_system::Update>,
+    /* the target position in the coordinates of the tracking system */
+    pub target: [f32; 3],
 }
 
 #[derive(Debug)]
@@ -31,4 +178,42 @@ pub enum TerminalAction {
     Start,
     Run(String),
     Stop,
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(value: u8) -> Frame {
+        Some(Arc::new(Ok(Bytes::from(vec![value]))))
+    }
+
+    #[test]
+    fn frame_queue_receives_every_frame_and_counts_overruns() {
+        let (frame_txs, frame_rxs) = frame_channels(&[]);
+        assert!(frame_rxs.is_empty());
+        let (queue, mut queue_rx) = frame_queue(2);
+        frame_txs.subscribe(queue.clone());
+        for value in 0..3 {
+            frame_txs.send("/dev/camera0", frame(value));
+        }
+        /* the end of a stream is not queued */
+        frame_txs.send("/dev/camera0", None);
+        for value in 0..2 {
+            let (camera, frame) = queue_rx.try_recv().unwrap();
+            assert_eq!(camera, "/dev/camera0");
+            assert_eq!(frame.as_ref().as_ref().unwrap(), &Bytes::from(vec![value]));
+        }
+        assert!(queue_rx.try_recv().is_err());
+        assert_eq!(queue.overruns.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn closed_frame_queues_are_removed() {
+        let (frame_txs, _) = frame_channels(&[]);
+        let (queue, queue_rx) = frame_queue(1);
+        frame_txs.subscribe(queue);
+        drop(queue_rx);
+        frame_txs.send("/dev/camera0", frame(0));
+        assert!(frame_txs.queues.lock().unwrap().is_empty());
+    }
+}
@@ -1,4 +1,4 @@
-use tokio::{self, sync::mpsc, task::JoinHandle};
+use tokio::{self, sync::mpsc, task::{JoinError, JoinHandle}};
 
 mod task;
 
@@ -8,16 +8,30 @@ pub use task::{
 
 pub struct Instance {
     pub action_tx: Sender,
-    _task: JoinHandle<()>
+    task: JoinHandle<()>
 }
 
-impl Default for Instance {
-    fn default() -> Self {
+impl Instance {
+    /* cameras are the cameras on the robot that can be streamed, capacity is the number of updates
+       that a subscriber can fall behind before it misses updates */
+    pub fn new(cameras: Vec<shared::camera::Configuration>, capacity: usize) -> Self {
         let (action_tx, action_rx) = mpsc::channel(8);
-        let _task = tokio::spawn(task::new(action_rx));
+        let task = tokio::spawn(task::new(action_rx, cameras, capacity));
         Self { 
             action_tx,
-            _task
+            task
         }
     }
+
+    /* wait for the task to terminate, e.g., due to a panic */
+    pub async fn terminated(&mut self) -> Result<(), JoinError> {
+        (&mut self.task).await
+    }
+}
+
+/* stop the task when the instance is dropped, e.g., when the configuration is reloaded */
+impl Drop for Instance {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
 }
\ No newline at end of file
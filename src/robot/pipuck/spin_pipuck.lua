@@ -0,0 +1,14 @@
+-- rotate on the spot so that the supervisor can identify the rigid body of this robot
+function init()
+   robot.differential_drive.set_target_velocity(-0.05, 0.05)
+end
+
+function step()
+end
+
+function reset()
+end
+
+function destroy()
+   robot.differential_drive.set_target_velocity(0, 0)
+end
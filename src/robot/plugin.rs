@@ -0,0 +1,46 @@
+use futures::future::BoxFuture;
+use macaddr::MacAddr6;
+use tokio::sync::{broadcast, mpsc};
+
+use crate::journal;
+use crate::network::{fernbedienung, xbee};
+use super::RestartPolicy;
+
+/* a robot of a type that is not built into the supervisor. The arena offers the devices found by the
+   network task to these robots after the built-in robots, and includes them in experiments after the
+   built-in robots. Like the built-in robots, an implementation would typically forward these requests
+   to its own task */
+pub trait Robot: std::fmt::Debug + Send + Sync {
+    fn id(&self) -> &str;
+    /* whether a device with the given MAC address belongs to this robot */
+    fn claims_fernbedienung(&self, macaddr: MacAddr6) -> bool;
+    fn claims_xbee(&self, macaddr: MacAddr6) -> bool;
+    fn associate_fernbedienung(&self, device: fernbedienung::Device) -> BoxFuture<'_, ()>;
+    fn associate_xbee(&self, device: xbee::Device) -> BoxFuture<'_, ()>;
+    /* prepare the experiment, e.g., upload the software, so that it can be started without delay */
+    fn setup_experiment(
+        &self,
+        journal: mpsc::Sender<journal::Action>,
+        restart_policy: RestartPolicy
+    ) -> BoxFuture<'_, anyhow::Result<()>>;
+    fn start_experiment(&self) -> BoxFuture<'_, anyhow::Result<()>>;
+    fn stop_experiment(&self) -> BoxFuture<'_, ()>;
+    /* the updates of the robot, e.g., its battery level, in a format chosen by the implementation */
+    fn subscribe(&self) -> broadcast::Receiver<serde_json::Value>;
+}
+
+/* a type of robot that is configured by the nodes underneath <robots> with the given name */
+pub trait RobotType: Sync {
+    fn name(&self) -> &'static str;
+    fn parse(&self, node: roxmltree::Node) -> anyhow::Result<Box<dyn Robot>>;
+}
+
+/* the types of robots that are not built into the supervisor. A new type of robot is added by
+   implementing the traits above in its own module and adding an instance of its RobotType here */
+static ROBOT_TYPES: &[&dyn RobotType] = &[];
+
+pub fn robot_type(name: &str) -> Option<&'static dyn RobotType> {
+    ROBOT_TYPES.iter()
+        .find(|robot_type| robot_type.name() == name)
+        .copied()
+}
@@ -0,0 +1,199 @@
+use anyhow::Context;
+use futures::{SinkExt, StreamExt, TryFutureExt};
+use serde_json::json;
+use shared::tracking_system::Update;
+use std::{collections::{HashMap, HashSet}, time::{Duration, SystemTime, UNIX_EPOCH}};
+use tokio::sync::{mpsc, oneshot};
+use tokio_stream::{StreamMap, wrappers::{BroadcastStream, errors::BroadcastStreamRecvError}};
+use tokio_tungstenite::tungstenite::Message;
+
+use crate::{arena, tracking, webui};
+
+/* the delay before connecting to rosbridge again after the connection has been lost */
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+#[derive(Debug)]
+pub struct Configuration {
+    /* the WebSocket of the rosbridge server, e.g., ws://127.0.0.1:9090 */
+    pub url: String,
+    /* the namespace of the topics, e.g., /supervisor */
+    pub prefix: String,
+    /* the rate in Hz at which poses are published */
+    pub rate: f64,
+}
+
+/// This function publishes the connection state, the battery level, and the pose of each robot as
+/// ROS 2 topics via a rosbridge server and subscribes to an emergency stop topic, which stops the
+/// experiment on all robots. The bridge connects to the server again if the connection is lost. If
+/// no server has been configured, this function never completes.
+pub async fn new(
+    config: Option<Configuration>,
+    robots: Vec<(String, i32)>,
+    arena_tx: mpsc::Sender<arena::Action>,
+    tracking_tx: mpsc::Sender<tracking::Action>,
+) -> anyhow::Result<()> {
+    let config = match config {
+        Some(config) => config,
+        /* the bridge is disabled, never complete */
+        None => return futures::future::pending().await,
+    };
+    loop {
+        if let Err(error) = bridge(&config, &robots, &arena_tx, &tracking_tx).await {
+            log::warn!("ROS bridge disconnected: {:#}", error);
+        }
+        tokio::time::sleep(RECONNECT_DELAY).await;
+    }
+}
+
+/* the topics are advertised when they are first published */
+struct Publisher {
+    prefix: String,
+    advertised: HashSet<String>,
+    messages: Vec<Message>,
+}
+
+impl Publisher {
+    fn publish(&mut self, robot: &str, topic: &str, message_type: &str, message: serde_json::Value) {
+        let topic = format!("{}/{}/{}", self.prefix, robot, topic);
+        if self.advertised.insert(topic.clone()) {
+            let advertise = json!({ "op": "advertise", "topic": topic, "type": message_type });
+            self.messages.push(Message::Text(advertise.to_string()));
+        }
+        let publish = json!({ "op": "publish", "topic": topic, "msg": message });
+        self.messages.push(Message::Text(publish.to_string()));
+    }
+}
+
+fn stamp() -> serde_json::Value {
+    let time = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+    json!({ "sec": time.as_secs(), "nanosec": time.subsec_nanos() })
+}
+
+async fn bridge(
+    config: &Configuration,
+    robots: &[(String, i32)],
+    arena_tx: &mpsc::Sender<arena::Action>,
+    tracking_tx: &mpsc::Sender<tracking::Action>,
+) -> anyhow::Result<()> {
+    let (websocket, _) = tokio_tungstenite::connect_async(config.url.as_str()).await
+        .context(format!("Could not connect to rosbridge at {}", config.url))?;
+    log::info!("ROS bridge connected to {}", config.url);
+    let (mut websocket_tx, mut websocket_rx) = websocket.split();
+    let estop = format!("{}/estop", config.prefix);
+    let subscribe = json!({ "op": "subscribe", "topic": estop, "type": "std_msgs/msg/Bool" });
+    websocket_tx.send(Message::Text(subscribe.to_string())).await
+        .context("Could not subscribe to emergency stop topic")?;
+    /* subscribe to the robots and to their rigid bodies */
+    let mut builderbot_updates = webui::subscribe_builderbot_updates(arena_tx).await?;
+    let mut drone_updates = webui::subscribe_drone_updates(arena_tx).await?;
+    let mut pipuck_updates = webui::subscribe_pipuck_updates(arena_tx).await?;
+    let mut poses = StreamMap::new();
+    for (id, optitrack_id) in robots {
+        let (callback_tx, callback_rx) = oneshot::channel();
+        let action = tracking::Action::SubscribeRigidBody(*optitrack_id, callback_tx);
+        let receiver = tracking_tx.send(action)
+            .map_err(|_| anyhow::anyhow!("Could not communicate with tracking system"))
+            .and_then(|_| callback_rx
+                .map_err(|_| anyhow::anyhow!("Could not subscribe to rigid body {}", optitrack_id)))
+            .await?;
+        poses.insert(id.clone(), BroadcastStream::new(receiver));
+    }
+    /* only publish the most recent pose of each robot */
+    let mut latest: HashMap<String, Update> = HashMap::new();
+    let mut interval = tokio::time::interval(Duration::from_secs_f64(1.0 / config.rate));
+    let mut publisher = Publisher {
+        prefix: config.prefix.clone(),
+        advertised: HashSet::new(),
+        messages: Vec::new(),
+    };
+    let connected = |connected: bool| json!({ "data": connected });
+    let battery = |level: i32| json!({ "percentage": level as f32 / 100.0, "present": true });
+    loop {
+        tokio::select! {
+            Some((desc, update)) = builderbot_updates.next() => match update {
+                Ok(shared::builderbot::Update::FernbedienungConnected(_)) =>
+                    publisher.publish(&desc.id, "fernbedienung", "std_msgs/msg/Bool", connected(true)),
+                Ok(shared::builderbot::Update::FernbedienungDisconnected) =>
+                    publisher.publish(&desc.id, "fernbedienung", "std_msgs/msg/Bool", connected(false)),
+                Ok(shared::builderbot::Update::Battery(level)) =>
+                    publisher.publish(&desc.id, "battery", "sensor_msgs/msg/BatteryState", battery(level)),
+                _ => continue,
+            },
+            Some((desc, update)) = drone_updates.next() => match update {
+                Ok(shared::drone::Update::FernbedienungConnected(_)) =>
+                    publisher.publish(&desc.id, "fernbedienung", "std_msgs/msg/Bool", connected(true)),
+                Ok(shared::drone::Update::FernbedienungDisconnected) =>
+                    publisher.publish(&desc.id, "fernbedienung", "std_msgs/msg/Bool", connected(false)),
+                Ok(shared::drone::Update::XbeeConnected(_)) =>
+                    publisher.publish(&desc.id, "xbee", "std_msgs/msg/Bool", connected(true)),
+                Ok(shared::drone::Update::XbeeDisconnected) =>
+                    publisher.publish(&desc.id, "xbee", "std_msgs/msg/Bool", connected(false)),
+                Ok(shared::drone::Update::Battery(level)) =>
+                    publisher.publish(&desc.id, "battery", "sensor_msgs/msg/BatteryState", battery(level)),
+                _ => continue,
+            },
+            Some((desc, update)) = pipuck_updates.next() => match update {
+                Ok(shared::pipuck::Update::FernbedienungConnected(_)) =>
+                    publisher.publish(&desc.id, "fernbedienung", "std_msgs/msg/Bool", connected(true)),
+                Ok(shared::pipuck::Update::FernbedienungDisconnected) =>
+                    publisher.publish(&desc.id, "fernbedienung", "std_msgs/msg/Bool", connected(false)),
+                Ok(shared::pipuck::Update::Battery(level)) =>
+                    publisher.publish(&desc.id, "battery", "sensor_msgs/msg/BatteryState", battery(level)),
+                _ => continue,
+            },
+            Some((id, update)) = poses.next() => match update {
+                Ok(update) => {
+                    latest.insert(id, update);
+                },
+                Err(BroadcastStreamRecvError::Lagged(count)) => {
+                    log::warn!("ROS bridge missed {} poses for {}", count, id);
+                }
+            },
+            _ = interval.tick() => {
+                for (id, update) in latest.drain() {
+                    let [x, y, z] = update.position;
+                    let [w, i, j, k] = update.orientation;
+                    let pose = json!({
+                        "header": { "stamp": stamp(), "frame_id": "world" },
+                        "pose": {
+                            "position": { "x": x, "y": y, "z": z },
+                            "orientation": { "x": i, "y": j, "z": k, "w": w },
+                        },
+                    });
+                    publisher.publish(&id, "pose", "geometry_msgs/msg/PoseStamped", pose);
+                }
+            },
+            message = websocket_rx.next() => match message {
+                Some(Ok(Message::Text(text))) => {
+                    let message: serde_json::Value = match serde_json::from_str(&text) {
+                        Ok(message) => message,
+                        Err(error) => {
+                            log::warn!("Could not parse message from rosbridge: {}", error);
+                            continue;
+                        }
+                    };
+                    let stop = message["op"] == "publish" && message["topic"] == estop.as_str() &&
+                        message["msg"]["data"] == true;
+                    if stop {
+                        log::warn!("Emergency stop requested via ROS");
+                        let (callback_tx, callback_rx) = oneshot::channel();
+                        arena_tx.send(arena::Action::StopExperiment { callback: callback_tx }).await
+                            .map_err(|_| anyhow::anyhow!("Could not communicate with arena"))?;
+                        match callback_rx.await {
+                            Ok(Ok(_)) => log::info!("Stopped experiment on all robots"),
+                            Ok(Err(error)) => log::warn!("{}", error),
+                            Err(_) => log::warn!("No response from arena"),
+                        }
+                    }
+                },
+                Some(Ok(Message::Close(_))) | None => anyhow::bail!("Connection closed by rosbridge"),
+                Some(Ok(_)) => {},
+                Some(Err(error)) => return Err(error).context("Could not receive message from rosbridge"),
+            },
+        }
+        for message in publisher.messages.drain(..) {
+            websocket_tx.send(message).await
+                .context("Could not publish to rosbridge")?;
+        }
+    }
+}
@@ -0,0 +1,1156 @@
+use anyhow::{Context, Result};
+use bytes::{BytesMut, Bytes, BufMut, Buf};
+use std::{io::{self, BufReader}, collections::{HashMap, HashSet}, fs::File, future::Future, path::PathBuf, pin::Pin, sync::{Arc, atomic::{AtomicU64, Ordering}}, net::SocketAddr, task::Poll, time::Duration};
+use log;
+use serde::Serialize;
+
+use tokio::{io::{AsyncRead, AsyncWrite}, net::TcpListener, sync::{Mutex, broadcast, mpsc, oneshot}, task::{JoinError, JoinHandle}};
+use tokio_rustls::{TlsAcceptor, rustls};
+use tokio_util::codec::{Decoder, Encoder, Framed};
+use futures::{FutureExt, SinkExt, Stream, StreamExt};
+use shared::{event::Severity, experiment::State};
+
+use crate::{arena, network::throttle::RateLimiter, subscription};
+
+use std::mem::size_of;
+
+pub mod queue;
+pub mod replay;
+pub mod schema;
+#[cfg(test)]
+mod tests;
+
+use queue::{Overflow, Queue};
+
+const LUA_TNIL: i8 = 0;
+const LUA_TBOOLEAN: i8 = 1;
+//const LUA_TLIGHTUSERDATA: i8 = 2;
+const LUA_TNUMBER: i8 = 3;
+const LUA_TSTRING: i8 = 4;
+const LUA_TTABLE: i8 = 5;
+//const LUA_TFUNCTION: i8 = 6;
+const LUA_TUSERDATA: i8 = 7;
+//const LUA_TTHREAD: i8 = 8;
+const LUA_TUSERDATA_VECTOR2: u8 = 1;
+const LUA_TUSERDATA_VECTOR3: u8 = 2;
+const LUA_TUSERDATA_QUATERNION: u8 = 3;
+const MAX_MANTISSA: f64 = 9223372036854775806.0;
+/* how long a peer has to send its credentials after connecting */
+const AUTHENTICATION_TIMEOUT: Duration = Duration::from_secs(5);
+/* how often the queue of each peer is checked for discarded messages and whether the peer is still reading */
+const QUEUE_MONITOR_INTERVAL: Duration = Duration::from_secs(1);
+/* the reserved recipient of the log messages of the controllers, which are reported as events instead of
+   being forwarded, e.g., {fromS = "drone1", toS = "LOGINFO", levelS = "warning", messageS = "Lost block"} */
+const LOG_RECIPIENT: &str = "LOGINFO";
+/* the fields of a log message that are not shown as its details */
+const LOG_FIELDS: [&str; 4] = ["fromS", "toS", "levelS", "messageS"];
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum LuaType {
+    String(String),
+    Number(f64),
+    Boolean(bool),
+    Vector2(f64, f64),
+    Vector3(f64, f64, f64),
+    Quaternion(f64, f64, f64, f64),
+    Table(Vec<(LuaType, LuaType)>),
+}
+
+fn decode_lua_usertype(buf: &mut impl Buf) -> Result<LuaType> {
+    if buf.has_remaining() {
+        match buf.get_u8() {
+            LUA_TUSERDATA_VECTOR2 => decode_lua_vector2(buf)
+                .context("Could not decode vector2"),
+            LUA_TUSERDATA_VECTOR3 => decode_lua_vector3(buf)
+                .context("Could not decode vector3"),
+            LUA_TUSERDATA_QUATERNION => decode_lua_quaternion(buf)
+                .context("Could not decode quaternion"),
+            _ => Err(anyhow::anyhow!("Could not decode Lua user type"))
+        }
+    }
+    else {
+        Err(anyhow::anyhow!("Could not decode Lua user type"))
+    }
+}
+
+fn decode_lua_vector2(buf: &mut impl Buf) -> Result<LuaType> {
+    let x = decode_lua_number(buf)
+        .context("Could not decode X")?;
+    let y = decode_lua_number(buf)
+        .context("Could not decode Y")?;
+    match (x, y) {
+        (LuaType::Number(x),
+         LuaType::Number(y)) => Ok(LuaType::Vector2(x, y)),
+        _ => Err(anyhow::anyhow!("Either X or Y was not a Lua number"))
+    }
+}
+
+fn decode_lua_vector3(buf: &mut impl Buf) -> Result<LuaType> {
+    let x = decode_lua_number(buf)
+        .context("Could not decode X")?;
+    let y = decode_lua_number(buf)
+        .context("Could not decode Y")?;
+    let z = decode_lua_number(buf)
+        .context("Could not decode Z")?;
+    match (x, y, z) {
+        (LuaType::Number(x),
+         LuaType::Number(y),
+         LuaType::Number(z)) => Ok(LuaType::Vector3(x, y, z)),
+        _ => Err(anyhow::anyhow!("Either X, Y, or Z was not a Lua number"))
+    }
+}
+
+fn decode_lua_quaternion(buf: &mut impl Buf) -> Result<LuaType> {
+    let w = decode_lua_number(buf)
+        .context("Could not decode W")?;
+    let x = decode_lua_number(buf)
+        .context("Could not decode X")?;
+    let y = decode_lua_number(buf)
+        .context("Could not decode Y")?;
+    let z = decode_lua_number(buf)
+        .context("Could not decode Z")?;
+    match (w, x, y, z) {
+        (LuaType::Number(w),
+         LuaType::Number(x),
+         LuaType::Number(y),
+         LuaType::Number(z)) => Ok(LuaType::Quaternion(w, x, y, z)),
+         _ => Err(anyhow::anyhow!("Either W, X, Y, or Z was not a Lua number"))
+    }
+}
+
+fn decode_lua_number(buf: &mut impl Buf) -> Result<LuaType> {
+    /* handle Carlo's unusual double encoding */
+    if buf.remaining() > size_of::<u64>() + size_of::<u32>() {
+        let mantissa = buf.get_i64();
+        let exponent = buf.get_i32();
+        if mantissa == 0 {
+            Ok(LuaType::Number(0.0))
+        }
+        else {
+            let significand = ((mantissa.abs() - 1i64) as f64 / MAX_MANTISSA) / 2.0 + 0.5;
+            let value = significand * 2.0f64.powi(exponent);
+            if mantissa < 0 {
+                Ok(LuaType::Number(-value))
+            }
+            else {
+                Ok(LuaType::Number(value))
+            }
+        }
+    }
+    else {
+        Err(anyhow::anyhow!("Could not decode Lua number"))
+    }
+}
+
+fn decode_lua_string(buf: &mut impl Buf) -> Result<LuaType> {
+    /* extract C string */
+    let mut data = Vec::new();
+    loop {
+        anyhow::ensure!(buf.has_remaining(), "Could not find the end of Lua string");
+        match buf.get_u8() {
+            0 => break,
+            byte => data.push(byte),
+        }
+    }
+    String::from_utf8(data)
+        .map_err(|_| anyhow::anyhow!("Could not decode Lua string"))
+        .map(|content| LuaType::String(content))
+}
+
+fn decode_lua_boolean(buf: &mut impl Buf) -> Result<LuaType> {
+    if buf.has_remaining() {
+        match buf.get_i8() {
+            0 => Ok(LuaType::Boolean(false)),
+            _ => Ok(LuaType::Boolean(true)),
+        }
+    }
+    else {
+        Err(anyhow::anyhow!("Could not decode Lua boolean"))
+    }
+}
+
+fn decode_lua_table(buf: &mut impl Buf) -> Result<LuaType> {
+    let mut table = Vec::new();
+    while buf.has_remaining() {
+        /* parse the key */
+        let key = match buf.get_i8() {
+            LUA_TBOOLEAN => decode_lua_boolean(buf),
+            LUA_TNUMBER => decode_lua_number(buf),
+            LUA_TSTRING => decode_lua_string(buf),
+            LUA_TUSERDATA => decode_lua_usertype(buf),
+            LUA_TTABLE => decode_lua_table(buf),
+            LUA_TNIL => break,
+            _ => Err(anyhow::anyhow!("Could not decode key")),
+        }?;
+        if buf.has_remaining() {
+            /* parse the value */
+            let value_type = buf.get_i8();
+            let value = decode_lua_value(buf, value_type)?;
+            table.push((key, value));
+        }
+        else {
+            anyhow::bail!("Could not decode value");
+        }
+    }
+    Ok(LuaType::Table(table))
+}
+
+fn decode_lua_value(buf: &mut impl Buf, lua_type: i8) -> Result<LuaType> {
+    match lua_type {
+        LUA_TBOOLEAN => decode_lua_boolean(buf),
+        LUA_TNUMBER => decode_lua_number(buf),
+        LUA_TSTRING => decode_lua_string(buf),
+        LUA_TUSERDATA => decode_lua_usertype(buf),
+        LUA_TTABLE => decode_lua_table(buf),
+        _ => Err(anyhow::anyhow!("Could not decode value")),
+    }
+}
+
+fn encode_lua_number(value: f64, buf: &mut impl BufMut) {
+    /* inverse of Carlo's unusual double encoding */
+    if value == 0.0 {
+        buf.put_i64(0);
+        buf.put_i32(0);
+        return;
+    }
+    /* normalize the significand to the range [0.5, 1.0) */
+    let mut exponent = value.abs().log2().floor() as i32 + 1;
+    let mut significand = value.abs() / 2.0f64.powi(exponent);
+    if significand >= 1.0 {
+        significand /= 2.0;
+        exponent += 1;
+    }
+    else if significand < 0.5 {
+        significand *= 2.0;
+        exponent -= 1;
+    }
+    let mantissa = ((significand - 0.5) * 2.0 * MAX_MANTISSA) as i64 + 1;
+    buf.put_i64(if value < 0.0 { -mantissa } else { mantissa });
+    buf.put_i32(exponent);
+}
+
+fn encode_lua_value(value: &LuaType, buf: &mut impl BufMut) {
+    match value {
+        LuaType::String(content) => {
+            buf.put_i8(LUA_TSTRING);
+            buf.put_slice(content.as_bytes());
+            buf.put_u8(0);
+        },
+        LuaType::Number(value) => {
+            buf.put_i8(LUA_TNUMBER);
+            encode_lua_number(*value, buf);
+        },
+        LuaType::Boolean(value) => {
+            buf.put_i8(LUA_TBOOLEAN);
+            buf.put_i8(*value as i8);
+        },
+        LuaType::Vector2(x, y) => {
+            buf.put_i8(LUA_TUSERDATA);
+            buf.put_u8(LUA_TUSERDATA_VECTOR2);
+            for value in [x, y].iter() {
+                encode_lua_number(**value, buf);
+            }
+        },
+        LuaType::Vector3(x, y, z) => {
+            buf.put_i8(LUA_TUSERDATA);
+            buf.put_u8(LUA_TUSERDATA_VECTOR3);
+            for value in [x, y, z].iter() {
+                encode_lua_number(**value, buf);
+            }
+        },
+        LuaType::Quaternion(w, x, y, z) => {
+            buf.put_i8(LUA_TUSERDATA);
+            buf.put_u8(LUA_TUSERDATA_QUATERNION);
+            for value in [w, x, y, z].iter() {
+                encode_lua_number(**value, buf);
+            }
+        },
+        LuaType::Table(table) => {
+            buf.put_i8(LUA_TTABLE);
+            encode_lua_table(table, buf);
+        },
+    }
+}
+
+fn encode_lua_table(table: &[(LuaType, LuaType)], buf: &mut impl BufMut) {
+    for (key, value) in table {
+        encode_lua_value(key, buf);
+        encode_lua_value(value, buf);
+    }
+    /* a nil key terminates the table */
+    buf.put_i8(LUA_TNIL);
+}
+
+/* the size of a message as it is sent over the network */
+pub fn encoded_len(message: &LuaType) -> usize {
+    let mut buf = BytesMut::new();
+    match message {
+        LuaType::Table(table) => encode_lua_table(table, &mut buf),
+        value => encode_lua_value(value, &mut buf),
+    }
+    buf.len()
+}
+
+fn read_lua_table_with_string_key_and_string_value(table: &LuaType, key: String) -> String {
+    match table {
+        LuaType::Table(table_vec) => {
+            for x in table_vec{
+                match &x.0 {
+                    LuaType::String(key_string) => {
+                        if key_string.eq(&key) {
+                            match &x.1 {
+                                LuaType::String(value_string) => {
+                                    return String::from(value_string)
+                                }
+                                _ => {
+                                    return String::from("nil")
+                                },
+                            }
+                        }
+                    }
+                    _ => {},
+                }
+            }
+            String::from("nil")
+        }
+        _ => {String::from("nil")},
+    }
+}
+
+/* skip over a value of the given type without keeping it, a value that can not be skipped could not
+   have been decoded either */
+fn skip_lua_value(buf: &mut impl Buf, lua_type: i8) -> Result<()> {
+    match lua_type {
+        LUA_TBOOLEAN => decode_lua_boolean(buf).map(drop),
+        LUA_TNUMBER => decode_lua_number(buf).map(drop),
+        LUA_TUSERDATA => decode_lua_usertype(buf).map(drop),
+        /* the content of a string must be valid UTF-8 */
+        LUA_TSTRING => decode_lua_string(buf).map(drop),
+        LUA_TTABLE => {
+            while buf.has_remaining() {
+                match buf.get_i8() {
+                    LUA_TNIL => break,
+                    key_type => skip_lua_value(buf, key_type).context("Could not decode key")?,
+                }
+                anyhow::ensure!(buf.has_remaining(), "Could not decode value");
+                let value_type = buf.get_i8();
+                skip_lua_value(buf, value_type).context("Could not decode value")?;
+            }
+            Ok(())
+        },
+        _ => Err(anyhow::anyhow!("Could not decode Lua type {}", lua_type)),
+    }
+}
+
+/* the fields of a message that determine to which peers it is forwarded */
+#[derive(Debug, PartialEq)]
+struct Routing {
+    from_s: String,
+    to_s: String,
+    topic_s: String,
+    subscribed: Option<HashSet<String>>,
+}
+
+impl Default for Routing {
+    fn default() -> Self {
+        Routing {
+            from_s: String::from("nil"),
+            to_s: String::from("nil"),
+            topic_s: String::from("nil"),
+            subscribed: None,
+        }
+    }
+}
+
+impl Routing {
+    fn new(table: &LuaType) -> Self {
+        Routing {
+            from_s: read_lua_table_with_string_key_and_string_value(table, String::from("fromS")),
+            to_s: read_lua_table_with_string_key_and_string_value(table, String::from("toS")),
+            topic_s: read_lua_table_with_string_key_and_string_value(table, String::from("topicS")),
+            subscribed: read_subscribed_topics(table),
+        }
+    }
+}
+
+/* read the routing fields of a message while skipping over the other fields, which avoids decoding
+   the whole table when nobody is subscribed to the decoded messages */
+fn read_routing(buf: &mut impl Buf) -> Result<Routing> {
+    let (mut from_s, mut to_s, mut topic_s, mut subscribed) = (None, None, None, None);
+    while buf.has_remaining() {
+        let key = match buf.get_i8() {
+            LUA_TNIL => break,
+            LUA_TSTRING => match decode_lua_string(buf)? {
+                LuaType::String(key) => Some(key),
+                _ => None,
+            },
+            key_type => {
+                skip_lua_value(buf, key_type).context("Could not decode key")?;
+                None
+            },
+        };
+        anyhow::ensure!(buf.has_remaining(), "Could not decode value");
+        let value_type = buf.get_i8();
+        /* as with a decoded table, only the first occurrence of a key is used, whatever its type */
+        let field = match key.as_deref() {
+            Some("fromS") => Some(&mut from_s),
+            Some("toS") => Some(&mut to_s),
+            Some("topicS") => Some(&mut topic_s),
+            Some("subscribeT") => Some(&mut subscribed),
+            _ => None,
+        };
+        match field.filter(|field| field.is_none()) {
+            Some(field) => *field = Some(decode_lua_value(buf, value_type)?),
+            None => skip_lua_value(buf, value_type).context("Could not decode value")?,
+        }
+    }
+    let string = |value: Option<LuaType>| match value {
+        Some(LuaType::String(value)) => value,
+        _ => String::from("nil"),
+    };
+    Ok(Routing {
+        from_s: string(from_s),
+        to_s: string(to_s),
+        topic_s: string(topic_s),
+        subscribed: subscribed.as_ref().and_then(topic_set),
+    })
+}
+
+#[derive(Debug)]
+struct ByteArrayCodec {
+    len: Option<usize>,
+    /* the largest message that is accepted, the length prefix is otherwise trusted to buffer the message */
+    max_len: usize,
+}
+
+impl ByteArrayCodec {
+    fn new(max_len: usize) -> Self {
+        ByteArrayCodec { len: None, max_len }
+    }
+}
+
+impl Decoder for ByteArrayCodec {
+    type Item = Bytes;
+    type Error = io::Error;
+
+    fn decode(&mut self, buf: &mut BytesMut) -> Result<Option<Bytes>, io::Error> {
+        loop {
+            if let Some(len) = self.len {
+                if buf.len() >= len {
+                    self.len = None;
+                    return Ok(Some(buf.split_to(len).freeze()));
+                }
+                else {
+                    break;
+                }
+            }
+            else {
+                if buf.len() >= 4 {
+                    let len = buf.get_u32() as usize;
+                    if len > self.max_len {
+                        return Err(io::Error::new(io::ErrorKind::InvalidData,
+                            format!("Message of {} bytes exceeds the limit of {} bytes", len, self.max_len)));
+                    }
+                    buf.reserve(len);
+                    self.len = Some(len);
+                }
+                else {
+                    break;
+                }
+            }
+        }
+        Ok(None)
+    }
+}
+
+impl Encoder<Bytes> for ByteArrayCodec {
+    type Error = io::Error;
+
+    fn encode(&mut self, data: Bytes, buf: &mut BytesMut) -> Result<(), io::Error> {
+        buf.reserve(data.len() + size_of::<u32>());
+        buf.put_u32(data.len() as u32);
+        buf.put(data);
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub struct Configuration {
+    pub socket: SocketAddr,
+    pub tls: Option<TlsConfiguration>,
+    /* the schemas of the messages between the robots, which are only used to show the messages */
+    pub schemas: Vec<shared::router::Schema>,
+    /* require peers to authenticate themselves before their messages are forwarded */
+    pub authentication: Option<Authentication>,
+    pub queue: QueueConfiguration,
+    pub limits: Limits,
+    /* only accept connections while an experiment is running */
+    pub require_experiment: bool,
+}
+
+/* the limits on the messages from each peer */
+#[derive(Clone, Copy, Debug)]
+pub struct Limits {
+    /* the size of the largest message in bytes, a peer that sends a larger message is disconnected */
+    pub max_message_size: usize,
+    /* the number of messages per second, with bursts of up to one second, the excess messages are discarded */
+    pub rate: Option<f64>,
+}
+
+/* the messages waiting to be sent to each peer */
+#[derive(Clone, Copy, Debug)]
+pub struct QueueConfiguration {
+    pub capacity: usize,
+    pub overflow: Overflow,
+    /* disconnect peers that have not read any of their queued messages for this long */
+    pub evict_after: Option<Duration>,
+}
+
+#[derive(Debug)]
+pub struct PeerStatus {
+    pub addr: SocketAddr,
+    pub robot: Option<String>,
+    /* the number of messages waiting to be sent */
+    pub depth: usize,
+    /* the number of messages that were discarded since the queue was full */
+    pub dropped: u64,
+    /* the number of messages from the peer that were discarded since it exceeded its rate */
+    pub rate_limited: u64,
+    /* the number of messages from the peer that could not be decoded as Lua tables */
+    pub undecodable: u64,
+}
+
+#[derive(Debug)]
+pub struct Authentication {
+    /* the pre-shared token of each robot */
+    pub tokens: HashMap<String, String>,
+    pub unauthenticated: Unauthenticated,
+}
+
+/* what happens to a peer that does not authenticate itself */
+#[derive(Clone, Copy, Debug)]
+pub enum Unauthenticated {
+    /* close the connection */
+    Reject,
+    /* keep the connection open but neither forward its messages nor send it messages */
+    Quarantine,
+}
+
+#[derive(Debug)]
+pub struct TlsConfiguration {
+    pub certificate: PathBuf,
+    pub key: PathBuf,
+}
+
+fn tls_acceptor(config: &TlsConfiguration) -> Result<TlsAcceptor> {
+    use rustls::internal::pemfile::{certs, pkcs8_private_keys, rsa_private_keys};
+    let certificate = File::open(&config.certificate)
+        .context(format!("Could not open certificate {:?}", config.certificate))?;
+    let certificates = certs(&mut BufReader::new(certificate))
+        .map_err(|_| anyhow::anyhow!("Could not parse certificate {:?}", config.certificate))?;
+    /* try PKCS8 first and then fall back to RSA */
+    let key = File::open(&config.key)
+        .context(format!("Could not open key {:?}", config.key))?;
+    let mut keys = pkcs8_private_keys(&mut BufReader::new(key))
+        .map_err(|_| anyhow::anyhow!("Could not parse key {:?}", config.key))?;
+    if keys.is_empty() {
+        let key = File::open(&config.key)
+            .context(format!("Could not open key {:?}", config.key))?;
+        keys = rsa_private_keys(&mut BufReader::new(key))
+            .map_err(|_| anyhow::anyhow!("Could not parse key {:?}", config.key))?;
+    }
+    let key = keys.into_iter().next()
+        .ok_or(anyhow::anyhow!("Could not find a private key in {:?}", config.key))?;
+    let mut server_config = rustls::ServerConfig::new(rustls::NoClientAuth::new());
+    server_config.set_single_cert(certificates, key)
+        .context("Could not configure TLS")?;
+    Ok(TlsAcceptor::from(Arc::new(server_config)))
+}
+
+/* a connected peer, the messages that it receives and the counters of the messages that it sent */
+struct Peer {
+    queue: Queue,
+    rate_limited: AtomicU64,
+    undecodable: AtomicU64,
+}
+
+type Peers = Arc<Mutex<HashMap<SocketAddr, Arc<Peer>>>>;
+type NameAddressIndex = Arc<Mutex<HashMap<String, SocketAddr>>>;
+/* the topics that each peer has subscribed to */
+type Topics = Arc<Mutex<HashMap<SocketAddr, HashSet<String>>>>;
+
+/* the state of the router that is shared by the handlers of all peers */
+#[derive(Clone)]
+struct Shared {
+    peers: Peers,
+    name_address_index: NameAddressIndex,
+    topics: Topics,
+    authentication: Arc<Option<Authentication>>,
+    /* the number of messages received from the peers that were forwarded */
+    forwarded: Arc<AtomicU64>,
+    arena_tx: mpsc::Sender<arena::Action>,
+    updates_tx: broadcast::Sender<(SocketAddr, Arc<LuaType>)>,
+}
+
+/* a spawned task that is aborted when it is dropped, so that the tasks of the peers and of a replay do
+   not outlive the router, e.g., when the configuration is reloaded */
+struct Task<T>(JoinHandle<T>);
+
+impl<T> Drop for Task<T> {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+impl<T> Future for Task<T> {
+    type Output = std::result::Result<T, JoinError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut std::task::Context<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut self.0).poll(cx)
+    }
+}
+
+/* the topics in the subscribeT table of a message, e.g., subscribeT = {"formation", "blocks"}, if the
+   message has such a table */
+fn read_subscribed_topics(table: &LuaType) -> Option<HashSet<String>> {
+    schema::field(table, "subscribeT").and_then(topic_set)
+}
+
+/* the names in a table of topics, e.g., {"formation", "blocks"} */
+fn topic_set(topics: &LuaType) -> Option<HashSet<String>> {
+    match topics {
+        LuaType::Table(topics) => Some(topics.iter()
+            .filter_map(|(_, topic)| match topic {
+                LuaType::String(topic) => Some(topic.clone()),
+                _ => None,
+            })
+            .collect()),
+        _ => None,
+    }
+}
+
+/* send a message that was published to a topic to the robots that subscribed to that topic, otherwise
+   send the message to the robot that registered itself under the name to_s or, if there is no such robot,
+   to all robots except for the sender */
+async fn forward(peers: &Peers,
+                 name_address_index: &NameAddressIndex,
+                 topics: &Topics,
+                 addr: Option<SocketAddr>,
+                 topic: Option<&str>,
+                 to_s: &str,
+                 message: Bytes) {
+    /* the queues are collected first so that the peers are not locked while waiting for a slow peer */
+    let targets = match topic {
+        Some(topic) => {
+            let subscribers = topics.lock().await.iter()
+                .filter(|(peer_addr, subscribed)| Some(**peer_addr) != addr && subscribed.contains(topic))
+                .map(|(peer_addr, _)| *peer_addr)
+                .collect::<Vec<_>>();
+            let peers = peers.lock().await;
+            subscribers.iter()
+                .filter_map(|subscriber| peers.get(subscriber).cloned())
+                .collect::<Vec<_>>()
+        },
+        None => {
+            // check if to_s is in address index, send only to to_s, otherwise send to everyone
+            let target_addr = name_address_index.lock().await.get(to_s).cloned()
+                .filter(|target_addr| Some(*target_addr) != addr);
+            let peers = peers.lock().await;
+            match target_addr.and_then(|target_addr| peers.get(&target_addr)) {
+                Some(peer) => vec![peer.clone()],
+                // send to to_s failed, send to everyone
+                None if to_s != LOG_RECIPIENT => peers.iter()
+                    /* do not send messages to the sending robot */
+                    .filter(|(peer_addr, _)| Some(**peer_addr) != addr)
+                    .map(|(_, peer)| peer.clone())
+                    .collect(),
+                None => Vec::new(),
+            }
+        },
+    };
+    for peer in targets {
+        peer.queue.push(message.clone()).await;
+    }
+}
+
+/* send the messages at the times at which they were recorded divided by the speed */
+async fn replay(messages: Vec<(Duration, LuaType)>,
+                speed: f64,
+                peers: Peers,
+                name_address_index: NameAddressIndex,
+                topics: Topics) {
+    let start = tokio::time::Instant::now();
+    let count = messages.len();
+    for (offset, message) in messages {
+        tokio::time::sleep_until(start + offset.div_f64(speed)).await;
+        /* the recorded subscriptions do not change the subscriptions of the connected robots */
+        if read_subscribed_topics(&message).is_some() {
+            continue;
+        }
+        if let LuaType::Table(table) = &message {
+            let from_s = read_lua_table_with_string_key_and_string_value(&message, String::from("fromS"));
+            let to_s = read_lua_table_with_string_key_and_string_value(&message, String::from("toS"));
+            let topic_s = read_lua_table_with_string_key_and_string_value(&message, String::from("topicS"));
+            let topic = Some(topic_s.as_str()).filter(|topic| *topic != "nil");
+            /* a robot that is still connected does not receive its own messages */
+            let addr = name_address_index.lock().await.get(&from_s).cloned();
+            let mut encoded = BytesMut::new();
+            encode_lua_table(table, &mut encoded);
+            forward(&peers, &name_address_index, &topics, addr, topic, &to_s, encoded.freeze()).await;
+        }
+    }
+    log::info!("Replayed {} messages", count);
+}
+
+/* the first message from a peer must contain the id of its robot and the token of that robot, i.e.,
+   {fromS = <id>, tokenS = <token>}, the robot's id is returned if the token is correct */
+async fn authenticate<S>(stream: &mut S, authentication: &Authentication) -> Result<String>
+    where S: Stream<Item = Result<Bytes, io::Error>> + Unpin {
+    let mut message = tokio::time::timeout(AUTHENTICATION_TIMEOUT, stream.next()).await
+        .map_err(|_| anyhow::anyhow!("No credentials were sent within {:?}", AUTHENTICATION_TIMEOUT))?
+        .ok_or(anyhow::anyhow!("Connection was closed before credentials were sent"))?
+        .context("Could not receive credentials")?;
+    let credentials = decode_lua_table(&mut message)
+        .context("Could not decode credentials")?;
+    let id = read_lua_table_with_string_key_and_string_value(&credentials, String::from("fromS"));
+    let token = match schema::field(&credentials, "tokenS") {
+        Some(LuaType::String(token)) => token,
+        _ => anyhow::bail!("The first message from {} did not contain a token", id),
+    };
+    match authentication.tokens.get(&id) {
+        Some(expected) if expected == token => Ok(id),
+        Some(_) => Err(anyhow::anyhow!("Incorrect token for {}", id)),
+        None => Err(anyhow::anyhow!("No token has been configured for {}", id)),
+    }
+}
+
+fn report_event(arena_tx: &mpsc::Sender<arena::Action>,
+                severity: Severity,
+                robot: Option<String>,
+                subsystem: &str,
+                message: String) {
+    let action = arena::Action::ReportEvent {
+        severity,
+        robot,
+        subsystem: String::from(subsystem),
+        message,
+    };
+    /* the arena also sends actions to the router, do not wait to avoid a deadlock */
+    if arena_tx.try_send(action).is_err() {
+        log::warn!("Could not report {} event to arena", subsystem);
+    }
+}
+
+/* report a problem with a peer as an event of the arena */
+fn report(arena_tx: &mpsc::Sender<arena::Action>, robot: Option<String>, message: String) {
+    report_event(arena_tx, Severity::Warning, robot, "router", message);
+}
+
+/* report a log message of a controller as an event of its robot, the severity is taken from levelS
+   (info by default) and the fields other than the message are appended to it */
+fn report_log(arena_tx: &mpsc::Sender<arena::Action>, robot: Option<String>, table: &LuaType) {
+    let field = |key| match schema::field(table, key) {
+        Some(LuaType::String(value)) => Some(value.as_str()),
+        _ => None,
+    };
+    let severity = match field("levelS").map(str::to_lowercase).as_deref() {
+        Some("warning") | Some("warn") => Severity::Warning,
+        Some("error") => Severity::Error,
+        _ => Severity::Info,
+    };
+    let details = match table {
+        LuaType::Table(entries) => entries.iter()
+            .filter(|(key, _)| !matches!(key, LuaType::String(key) if LOG_FIELDS.contains(&key.as_str())))
+            .cloned()
+            .collect(),
+        _ => Vec::new(),
+    };
+    let message = match (field("messageS"), details.is_empty()) {
+        (Some(message), true) => message.to_owned(),
+        (Some(message), false) => format!("{} {}", message, schema::format(&LuaType::Table(details))),
+        (None, _) => schema::format(&LuaType::Table(details)),
+    };
+    report_event(arena_tx, severity, robot, "controller", message);
+}
+
+async fn client_handler<S>(stream: S,
+                        addr: SocketAddr,
+                        shared: Shared,
+                        queue_config: QueueConfiguration,
+                        limits: Limits)
+    where S: AsyncRead + AsyncWrite + Unpin + Send + 'static {
+    let Shared { peers, name_address_index, topics, authentication, forwarded, arena_tx, updates_tx } = shared;
+    log::info!("{} connected to message router", addr);
+    /* set up a queue for the messages from other robot sockets */
+    let peer = Arc::new(Peer {
+        queue: Queue::new(queue_config.capacity, queue_config.overflow),
+        rate_limited: AtomicU64::new(0),
+        undecodable: AtomicU64::new(0),
+    });
+    let rate_limiter = limits.rate.map(RateLimiter::new);
+    /* wrap up socket in our ByteArrayCodec */
+    let codec = ByteArrayCodec::new(limits.max_message_size);
+    let (mut sink, mut stream) = Framed::new(stream, codec).split();
+    /* peers on the loopback interface, e.g., the local instance of ARGoS, are not authenticated */
+    let mut authenticated = None;
+    let mut quarantined = false;
+    match authentication.as_ref() {
+        Some(authentication) if !addr.ip().is_loopback() => match authenticate(&mut stream, authentication).await {
+            Ok(id) => {
+                log::info!("{} authenticated as {}", addr, id);
+                name_address_index.lock().await.insert(id.clone(), addr);
+                authenticated = Some(id);
+            },
+            Err(error) => match authentication.unauthenticated {
+                Unauthenticated::Reject => {
+                    log::warn!("Rejected {}: {}", addr, error);
+                    return;
+                },
+                Unauthenticated::Quarantine => {
+                    log::warn!("Quarantined {}: {}", addr, error);
+                    quarantined = true;
+                },
+            },
+        },
+        _ => {},
+    }
+    /* a quarantined peer does not receive messages from the other peers */
+    if !quarantined {
+        peers.lock().await.insert(addr, peer.clone());
+    }
+    /* the robot of this peer, which is used to attribute the events */
+    let mut robot = authenticated.clone();
+    /* the last name under which this peer was added to the index */
+    let mut registered = None;
+    /* send and receive messages concurrently */
+    let outgoing = peer.clone();
+    let mut forward_task = Task(tokio::spawn(async move {
+        while let Some(message) = outgoing.queue.pop().await {
+            sink.send(message).await?;
+        }
+        Ok::<_, io::Error>(())
+    }));
+    let mut monitor = tokio::time::interval(QUEUE_MONITOR_INTERVAL);
+    let mut dropped = 0;
+    loop {
+        tokio::select! {
+            Some(message) = stream.next() => match message {
+                /* the messages of a quarantined peer are discarded */
+                Ok(_) if quarantined => {},
+                /* the messages that exceed the rate of the peer are discarded */
+                Ok(_) if !rate_limiter.as_ref().is_none_or(|limiter| limiter.try_acquire(1)) => {
+                    if peer.rate_limited.fetch_add(1, Ordering::Relaxed) == 0 {
+                        report(&arena_tx, robot.clone(),
+                            format!("Discarding messages from {} since it exceeds its rate", addr));
+                    }
+                },
+                Ok(message) => {
+                    /* the whole table is only decoded if there are subscribers, e.g., the journal, otherwise
+                       only the fields that are needed to forward the message are read */
+                    let decoded = match updates_tx.receiver_count() {
+                        0 => read_routing(&mut &message[..]).map(|routing| (routing, None)),
+                        _ => decode_lua_table(&mut &message[..])
+                            .map(|decoded| (Routing::new(&decoded), Some(decoded))),
+                    };
+                    let (routing, decoded) = match decoded {
+                        Ok(decoded) => decoded,
+                        Err(error) => {
+                            /* report the first message that could not be decoded, the others are only counted */
+                            if peer.undecodable.fetch_add(1, Ordering::Relaxed) == 0 {
+                                report(&arena_tx, robot.clone(),
+                                    format!("Could not decode a message from {}: {:#}", addr, error));
+                            }
+                            (Routing::default(), None)
+                        }
+                    };
+                    let Routing { from_s, to_s, topic_s, subscribed } = routing;
+
+                    /* an authenticated peer can not send messages on behalf of other robots */
+                    if let Some(id) = authenticated.as_ref().filter(|id| **id != from_s) {
+                        log::warn!("Discarded message from {} ({}) that was sent as {}", addr, id, from_s);
+                        continue;
+                    }
+
+                    let decoded = decoded.map(Arc::new);
+                    if let Some(decoded) = decoded.as_ref() {
+                        let _ = updates_tx.send((addr, decoded.clone()));
+                    }
+
+                    /* add from_s to name_address_index, the index is only locked when the name changes */
+                    if from_s != "nil" && registered.as_ref() != Some(&from_s) {
+                        name_address_index.lock().await.entry(from_s.clone()).or_insert(addr);
+                        robot.get_or_insert_with(|| from_s.clone());
+                        registered = Some(from_s.clone());
+                    }
+
+                    /* log messages are reported as events of the sending robot and are not forwarded */
+                    if to_s == LOG_RECIPIENT {
+                        let table = decoded.or_else(|| decode_lua_table(&mut &message[..]).ok().map(Arc::new));
+                        if let Some(table) = table {
+                            let sender = Some(from_s).filter(|from_s| from_s != "nil").or_else(|| robot.clone());
+                            report_log(&arena_tx, sender, &table);
+                        }
+                        continue;
+                    }
+
+                    /* a subscription replaces the previous subscriptions of the robot and is not forwarded */
+                    if let Some(subscribed) = subscribed {
+                        log::info!("{} ({}) subscribed to the topics {:?}", addr, from_s, subscribed);
+                        topics.lock().await.insert(addr, subscribed);
+                        continue;
+                    }
+
+                    let topic = Some(topic_s.as_str()).filter(|topic| *topic != "nil");
+                    forwarded.fetch_add(1, Ordering::Relaxed);
+                    forward(&peers, &name_address_index, &topics, Some(addr), topic, &to_s, message).await;
+                },
+                Err(error) => {
+                    if error.kind() == io::ErrorKind::InvalidData {
+                        report(&arena_tx, robot.clone(), format!("Disconnected {}: {}", addr, error));
+                    }
+                    break;
+                }
+            },
+            _ = &mut forward_task => break,
+            _ = monitor.tick() => {
+                /* report when a peer starts to miss messages, but not every message that it misses */
+                let total = peer.queue.dropped();
+                if total > dropped && dropped == 0 {
+                    report(&arena_tx, robot.clone(),
+                        format!("Discarding messages for {} since it does not read them fast enough", addr));
+                }
+                dropped = total;
+                if let Some(evict_after) = queue_config.evict_after {
+                    if peer.queue.stalled() > evict_after {
+                        report(&arena_tx, robot.clone(),
+                            format!("Disconnected {} since it has not read its messages for {:?}", addr, evict_after));
+                        break;
+                    }
+                }
+            }
+        }
+    }
+    {
+        peers.lock().await.remove(&addr);
+        topics.lock().await.remove(&addr);
+    }
+    /* wake up any peer that is waiting to queue a message for this peer */
+    peer.queue.close();
+    drop(forward_task);
+    log::info!("{} disconnected from message router", addr);
+}
+
+pub enum Action {
+    /* the messages are shared by the subscribers and are only decoded while there are subscribers */
+    Subscribe(oneshot::Sender<broadcast::Receiver<(SocketAddr, Arc<LuaType>)>>),
+    /* send a Lua table to the robot that registered itself under the given name */
+    Send {
+        to: String,
+        table: Vec<(LuaType, LuaType)>,
+    },
+    /* the names of the robots that have registered themselves and are still connected */
+    GetConnected(oneshot::Sender<Vec<String>>),
+    /* the registered schemas for decoding the messages */
+    GetSchemas(oneshot::Sender<Arc<schema::Registry>>),
+    /* the queue and the counters of the discarded messages of each peer */
+    GetPeers(oneshot::Sender<Vec<PeerStatus>>),
+    /* the number of messages that have been forwarded */
+    GetForwarded(oneshot::Sender<u64>),
+    /* send the messages recorded in a journal to the connected robots, replacing any replay that is
+       in progress, the callback is answered with the number of messages once the journal has been read */
+    Replay {
+        journal: PathBuf,
+        speed: f64,
+        senders: Vec<String>,
+        callback: oneshot::Sender<anyhow::Result<usize>>,
+    },
+    StopReplay(oneshot::Sender<anyhow::Result<()>>),
+}
+
+pub async fn new(config: Configuration,
+                 capacity: usize,
+                 arena_tx: mpsc::Sender<arena::Action>,
+                 mut requests_rx: mpsc::Receiver<Action>) -> Result<()> {
+    /* load the certificate and key if TLS has been configured */
+    let acceptor = config.tls.as_ref()
+        .map(tls_acceptor)
+        .transpose()
+        .context("Could not set up TLS for the message router")?;
+    let listener = TcpListener::bind(config.socket).await
+        .context("Could not bind message router socket")?;
+    match acceptor {
+        Some(_) => log::info!("Message router running on: {:?} (TLS)", listener.local_addr()),
+        None => log::info!("Message router running on: {:?}", listener.local_addr()),
+    }
+    /* create an atomic map of all peers */
+    let peers = Peers::default();
+    /* create an index of robot name and address */
+    let name_address_index = NameAddressIndex::default();
+    /* create an index of the topics that each peer has subscribed to */
+    let topics = Topics::default();
+    let authentication = Arc::new(config.authentication);
+    /* update channel (for the journal) */
+    let (updates_tx, _) = broadcast::channel(capacity);
+    /* the number of messages received from the peers that were forwarded */
+    let forwarded = Arc::new(AtomicU64::new(0));
+    let schemas = Arc::new(schema::Registry::new(config.schemas));
+    let mut replay_task: Option<Task<()>> = None;
+    /* the handlers of the connected peers */
+    let mut handlers: Vec<Task<()>> = Vec::new();
+    /* the state of the experiment decides whether connections are accepted and when the queues are flushed */
+    let (callback_tx, callback_rx) = oneshot::channel();
+    arena_tx.send(arena::Action::SubscribeExperimentState(callback_tx)).await
+        .map_err(|_| anyhow::anyhow!("Could not communicate with arena"))?;
+    let mut experiment_updates = callback_rx.await
+        .map(subscription::updates)
+        .map_err(|_| anyhow::anyhow!("Could not subscribe to experiment updates"))?;
+    let mut experiment_state = State::Standby;
+    /* start the main loop */
+    loop {
+        tokio::select! {
+            Some(update) = experiment_updates.next() => if let Ok(state) = update {
+                /* messages that were queued during an experiment are not delivered after it has stopped */
+                if let (State::Active, State::Standby) = (experiment_state, state) {
+                    let flushed = peers.lock().await.values()
+                        .map(|peer| peer.queue.clear())
+                        .sum::<usize>();
+                    if flushed > 0 {
+                        log::info!("Discarded {} queued messages at the end of the experiment", flushed);
+                    }
+                }
+                experiment_state = state;
+            },
+            result = listener.accept() => match result {
+                Ok((_, addr)) if config.require_experiment && !matches!(experiment_state, State::Active) => {
+                    log::warn!("Rejected {} since no experiment is running", addr);
+                },
+                Ok((stream, addr)) => {
+                    /* forget the handlers of the peers that have disconnected */
+                    handlers.retain_mut(|handler| handler.now_or_never().is_none());
+                    let shared = Shared {
+                        peers: Arc::clone(&peers),
+                        name_address_index: Arc::clone(&name_address_index),
+                        topics: Arc::clone(&topics),
+                        authentication: Arc::clone(&authentication),
+                        forwarded: Arc::clone(&forwarded),
+                        arena_tx: arena_tx.clone(),
+                        updates_tx: updates_tx.clone(),
+                    };
+                    let queue_config = config.queue;
+                    let limits = config.limits;
+                    /* spawn a handler for the newly connected client */
+                    let handler = match acceptor.clone() {
+                        Some(acceptor) => {
+                            tokio::spawn(async move {
+                                match acceptor.accept(stream).await {
+                                    Ok(stream) =>
+                                        client_handler(stream, addr, shared, queue_config, limits).await,
+                                    Err(error) =>
+                                        log::warn!("TLS handshake with {} failed: {}", addr, error),
+                                }
+                            })
+                        },
+                        None => {
+                            tokio::spawn(client_handler(stream, addr, shared, queue_config, limits))
+                        }
+                    };
+                    handlers.push(Task(handler));
+                }
+                Err(err) => {
+                    log::error!("Error accepting incoming connection: {}", err);
+                }
+            },
+            request = requests_rx.recv() => match request {
+                Some(action) => match action {
+                    Action::Subscribe(callback) => {
+                        let _ = callback.send(updates_tx.subscribe());
+                    },
+                    Action::GetSchemas(callback) => {
+                        let _ = callback.send(schemas.clone());
+                    },
+                    Action::Replay { journal, speed, senders, callback } => {
+                        /* dropping the previous replay aborts it */
+                        replay_task = None;
+                        if !(speed.is_finite() && speed > 0.0) {
+                            let _ = callback.send(Err(anyhow::anyhow!("The speed of a replay must be positive")));
+                            continue;
+                        }
+                        let peers = Arc::clone(&peers);
+                        let name_address_index = Arc::clone(&name_address_index);
+                        let topics = Arc::clone(&topics);
+                        replay_task = Some(Task(tokio::spawn(async move {
+                            /* reading a journal blocks until the whole file has been decompressed and decoded */
+                            let loaded = tokio::task::spawn_blocking(move || replay::load(&journal, &senders)).await
+                                .map_err(|_| anyhow::anyhow!("Could not read journal"))
+                                .and_then(|result| result);
+                            match loaded {
+                                Ok(messages) => {
+                                    log::info!("Replaying {} messages", messages.len());
+                                    let _ = callback.send(Ok(messages.len()));
+                                    replay(messages, speed, peers, name_address_index, topics).await;
+                                },
+                                Err(error) => {
+                                    let _ = callback.send(Err(error));
+                                }
+                            }
+                        })));
+                    },
+                    Action::StopReplay(callback) => {
+                        let result = match replay_task.take() {
+                            Some(task) => {
+                                drop(task);
+                                Ok(())
+                            },
+                            None => Err(anyhow::anyhow!("No messages are being replayed")),
+                        };
+                        let _ = callback.send(result);
+                    },
+                    Action::GetPeers(callback) => {
+                        let peers = peers.lock().await;
+                        let name_address_index = name_address_index.lock().await;
+                        let statuses = peers.iter()
+                            .map(|(addr, peer)| PeerStatus {
+                                addr: *addr,
+                                robot: name_address_index.iter()
+                                    .find(|(_, registered)| *registered == addr)
+                                    .map(|(name, _)| name.clone()),
+                                depth: peer.queue.depth(),
+                                dropped: peer.queue.dropped(),
+                                rate_limited: peer.rate_limited.load(Ordering::Relaxed),
+                                undecodable: peer.undecodable.load(Ordering::Relaxed),
+                            })
+                            .collect();
+                        let _ = callback.send(statuses);
+                    },
+                    Action::GetForwarded(callback) => {
+                        let _ = callback.send(forwarded.load(Ordering::Relaxed));
+                    },
+                    Action::GetConnected(callback) => {
+                        let peers = peers.lock().await;
+                        let connected = name_address_index.lock().await.iter()
+                            .filter(|(_, addr)| peers.contains_key(addr))
+                            .map(|(name, _)| name.clone())
+                            .collect();
+                        let _ = callback.send(connected);
+                    },
+                    Action::Send { to, table } => {
+                        let target_addr = name_address_index.lock().await.get(&to).cloned();
+                        if let Some(target_addr) = target_addr {
+                            if let Some(peer) = peers.lock().await.get(&target_addr) {
+                                let mut message = BytesMut::new();
+                                encode_lua_table(&table, &mut message);
+                                /* do not block the router if the robot is not keeping up */
+                                if !peer.queue.try_push(message.freeze()) {
+                                    log::warn!("Could not send message to {}", to);
+                                }
+                            }
+                        }
+                    },
+                },
+                None => break,
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,248 @@
+use bytes::Bytes;
+use std::{collections::VecDeque, sync::{Mutex, MutexGuard}, time::{Duration, Instant}};
+use tokio::sync::{Notify, Semaphore, TryAcquireError};
+
+/* what happens to a message for a peer whose queue is full */
+#[derive(Clone, Copy, Debug)]
+pub enum Overflow {
+    /* wait until the peer has read a message, which also delays the sender */
+    Wait,
+    /* discard the oldest message in the queue */
+    DropOldest,
+}
+
+struct State {
+    messages: VecDeque<Bytes>,
+    /* when a message was last read or when a message was queued while the queue was empty */
+    progress: Instant,
+    dropped: u64,
+    closed: bool,
+}
+
+/* the messages that are waiting to be sent to a peer, the free space in the queue is tracked by a
+   semaphore so that the senders that are waiting are woken up once the queue is closed */
+pub struct Queue {
+    state: Mutex<State>,
+    space: Semaphore,
+    pushed: Notify,
+    overflow: Overflow,
+}
+
+impl Queue {
+    pub fn new(capacity: usize, overflow: Overflow) -> Self {
+        Queue {
+            state: Mutex::new(State {
+                messages: VecDeque::with_capacity(capacity),
+                progress: Instant::now(),
+                dropped: 0,
+                closed: false,
+            }),
+            space: Semaphore::new(capacity),
+            pushed: Notify::new(),
+            overflow,
+        }
+    }
+
+    fn enqueue(&self, mut state: MutexGuard<State>, message: Bytes) {
+        if state.messages.is_empty() {
+            state.progress = Instant::now();
+        }
+        state.messages.push_back(message);
+        drop(state);
+        self.pushed.notify_one();
+    }
+
+    /* queue a message without waiting, false is returned if the message could not be queued */
+    pub fn try_push(&self, message: Bytes) -> bool {
+        /* the permits are only released while the state is locked, so the queue can not be emptied
+           between failing to acquire a permit and discarding the oldest message */
+        let mut state = self.state.lock().unwrap();
+        match (self.space.try_acquire(), self.overflow) {
+            (Ok(permit), _) => permit.forget(),
+            (Err(TryAcquireError::NoPermits), Overflow::DropOldest) => match state.messages.pop_front() {
+                /* the new message takes the place, and therefore the permit, of the oldest message */
+                Some(_) => state.dropped += 1,
+                /* a queue without capacity */
+                None => return false,
+            },
+            (Err(_), _) => return false,
+        }
+        self.enqueue(state, message);
+        true
+    }
+
+    /* queue a message, false is returned if the queue has been closed */
+    pub async fn push(&self, message: Bytes) -> bool {
+        match self.overflow {
+            Overflow::Wait => match self.space.acquire().await {
+                Ok(permit) => {
+                    permit.forget();
+                    self.enqueue(self.state.lock().unwrap(), message);
+                    true
+                },
+                Err(_) => false,
+            },
+            Overflow::DropOldest => self.try_push(message),
+        }
+    }
+
+    /* the next message to send to the peer, none is returned once the queue has been closed */
+    pub async fn pop(&self) -> Option<Bytes> {
+        loop {
+            let pushed = self.pushed.notified();
+            {
+                let mut state = self.state.lock().unwrap();
+                if state.closed {
+                    return None;
+                }
+                if let Some(message) = state.messages.pop_front() {
+                    state.progress = Instant::now();
+                    /* the permit is released while the state is locked so that the free space
+                       always matches the number of queued messages */
+                    self.space.add_permits(1);
+                    return Some(message);
+                }
+            }
+            pushed.await;
+        }
+    }
+
+    /* discard the queued messages without counting them as dropped, the number of messages is returned */
+    pub fn clear(&self) -> usize {
+        let mut state = self.state.lock().unwrap();
+        let cleared = state.messages.len();
+        state.messages.clear();
+        state.progress = Instant::now();
+        self.space.add_permits(cleared);
+        cleared
+    }
+
+    pub fn close(&self) {
+        self.state.lock().unwrap().closed = true;
+        self.space.close();
+        self.pushed.notify_one();
+    }
+
+    pub fn depth(&self) -> usize {
+        self.state.lock().unwrap().messages.len()
+    }
+
+    /* the number of messages that were discarded since the queue was full */
+    pub fn dropped(&self) -> u64 {
+        self.state.lock().unwrap().dropped
+    }
+
+    /* how long the peer has not read any of the messages that are waiting in the queue */
+    pub fn stalled(&self) -> Duration {
+        let state = self.state.lock().unwrap();
+        match state.messages.is_empty() {
+            true => Duration::from_secs(0),
+            false => state.progress.elapsed(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    fn message(value: u8) -> Bytes {
+        Bytes::from(vec![value])
+    }
+
+    #[tokio::test]
+    async fn wait_delays_the_sender_until_a_message_is_read() {
+        let queue = Arc::new(Queue::new(1, Overflow::Wait));
+        assert!(queue.push(message(0)).await);
+        assert!(!queue.try_push(message(1)));
+        let mut push = tokio::spawn({
+            let queue = Arc::clone(&queue);
+            async move { queue.push(message(1)).await }
+        });
+        assert!(tokio::time::timeout(Duration::from_millis(50), &mut push).await.is_err());
+        assert_eq!(queue.pop().await, Some(message(0)));
+        assert!(push.await.unwrap());
+        assert_eq!(queue.pop().await, Some(message(1)));
+        assert_eq!(queue.dropped(), 0);
+    }
+
+    #[tokio::test]
+    async fn drop_oldest_discards_the_oldest_messages() {
+        let queue = Queue::new(2, Overflow::DropOldest);
+        for value in 0..4 {
+            assert!(queue.push(message(value)).await);
+        }
+        assert_eq!(queue.depth(), 2);
+        assert_eq!(queue.dropped(), 2);
+        assert_eq!(queue.pop().await, Some(message(2)));
+        assert_eq!(queue.pop().await, Some(message(3)));
+        /* the free space matches the capacity again once the queue has been read */
+        assert!(queue.try_push(message(4)));
+        assert!(queue.try_push(message(5)));
+        assert_eq!(queue.depth(), 2);
+        assert_eq!(queue.dropped(), 2);
+    }
+
+    #[tokio::test]
+    async fn drop_oldest_never_exceeds_the_capacity() {
+        let queue = Arc::new(Queue::new(4, Overflow::DropOldest));
+        let reader = tokio::spawn({
+            let queue = Arc::clone(&queue);
+            async move { while queue.pop().await.is_some() {} }
+        });
+        for value in 0..=255 {
+            assert!(queue.try_push(message(value)));
+            assert!(queue.depth() <= 4);
+        }
+        queue.close();
+        reader.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn close_wakes_the_waiting_senders_and_readers() {
+        let full = Arc::new(Queue::new(1, Overflow::Wait));
+        assert!(full.push(message(0)).await);
+        let push = tokio::spawn({
+            let full = Arc::clone(&full);
+            async move { full.push(message(1)).await }
+        });
+        let empty = Arc::new(Queue::new(1, Overflow::Wait));
+        let pop = tokio::spawn({
+            let empty = Arc::clone(&empty);
+            async move { empty.pop().await }
+        });
+        tokio::task::yield_now().await;
+        full.close();
+        empty.close();
+        assert!(!push.await.unwrap());
+        assert_eq!(pop.await.unwrap(), None);
+        assert_eq!(full.pop().await, None);
+        assert!(!full.try_push(message(2)));
+    }
+
+    #[tokio::test]
+    async fn stalled_is_the_time_since_the_peer_last_read_a_message() {
+        let queue = Queue::new(2, Overflow::Wait);
+        assert_eq!(queue.stalled(), Duration::from_secs(0));
+        assert!(queue.push(message(0)).await);
+        assert!(queue.push(message(1)).await);
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(queue.stalled() >= Duration::from_millis(20));
+        assert_eq!(queue.pop().await, Some(message(0)));
+        assert!(queue.stalled() < Duration::from_millis(20));
+        assert_eq!(queue.pop().await, Some(message(1)));
+        assert_eq!(queue.stalled(), Duration::from_secs(0));
+    }
+
+    #[tokio::test]
+    async fn clear_releases_the_space_without_counting_drops() {
+        let queue = Queue::new(2, Overflow::Wait);
+        assert!(queue.push(message(0)).await);
+        assert!(queue.push(message(1)).await);
+        assert_eq!(queue.clear(), 2);
+        assert!(queue.try_push(message(2)));
+        assert!(queue.try_push(message(3)));
+        assert_eq!(queue.dropped(), 0);
+    }
+}
@@ -0,0 +1,67 @@
+use anyhow::Context;
+use std::{path::Path, time::Duration};
+
+use crate::export::{self, Entry, Event};
+use super::{LuaType, schema};
+
+/* a value of a message as it was recorded in the journal, where vectors and quaternions were
+   recorded as sequences of numbers and tables as sequences of key-value pairs */
+fn lua_value(value: serde_pickle::Value) -> anyhow::Result<LuaType> {
+    use serde_pickle::Value;
+    match value {
+        Value::String(value) => Ok(LuaType::String(value)),
+        Value::F64(value) => Ok(LuaType::Number(value)),
+        Value::I64(value) => Ok(LuaType::Number(value as f64)),
+        Value::Bool(value) => Ok(LuaType::Boolean(value)),
+        Value::List(values) | Value::Tuple(values) => {
+            let numbers = values.iter()
+                .map(|value| match value {
+                    Value::F64(value) => Some(*value),
+                    Value::I64(value) => Some(*value as f64),
+                    _ => None,
+                })
+                .collect::<Option<Vec<_>>>();
+            match numbers.as_deref() {
+                Some(&[x, y]) => Ok(LuaType::Vector2(x, y)),
+                Some(&[x, y, z]) => Ok(LuaType::Vector3(x, y, z)),
+                Some(&[w, x, y, z]) => Ok(LuaType::Quaternion(w, x, y, z)),
+                _ => values.into_iter()
+                    .map(|entry| match entry {
+                        Value::List(mut pair) | Value::Tuple(mut pair) if pair.len() == 2 => {
+                            let value = lua_value(pair.remove(1))?;
+                            let key = lua_value(pair.remove(0))?;
+                            Ok((key, value))
+                        },
+                        _ => Err(anyhow::anyhow!("Could not decode table entry")),
+                    })
+                    .collect::<anyhow::Result<Vec<_>>>()
+                    .map(LuaType::Table),
+            }
+        },
+        _ => Err(anyhow::anyhow!("Could not decode value")),
+    }
+}
+
+/* the messages from the given senders (all senders if there are none) that were recorded in a
+   journal and the time at which each message was recorded relative to the first message */
+pub fn load(journal: &Path, senders: &[String]) -> anyhow::Result<Vec<(Duration, LuaType)>> {
+    let mut messages = Vec::new();
+    let mut first = None;
+    export::read(journal, |Entry { timestamp, event }| {
+        if let Event::Message(_, message, _) = event {
+            let message = lua_value(message)
+                .context(format!("Could not decode the message recorded at {} ms", timestamp))?;
+            let from = match schema::field(&message, "fromS") {
+                Some(LuaType::String(from)) => from.as_str(),
+                _ => "nil",
+            };
+            if senders.is_empty() || senders.iter().any(|sender| sender == from) {
+                let first = *first.get_or_insert(timestamp);
+                let offset = Duration::from_millis(timestamp.saturating_sub(first).max(0) as u64);
+                messages.push((offset, message));
+            }
+        }
+        Ok(())
+    })?;
+    Ok(messages)
+}
@@ -0,0 +1,100 @@
+use shared::router::{Decoded, DecodedField, FieldType, Schema};
+
+use super::LuaType;
+
+/* the schemas of the messages between the robots, which are used to show the messages with labeled
+   fields instead of nested Lua tables */
+#[derive(Debug, Default)]
+pub struct Registry {
+    schemas: Vec<Schema>,
+}
+
+/* a pattern matches the name exactly or, if it ends with *, any name that starts with the prefix */
+fn matches(pattern: &str, name: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(prefix) => name.starts_with(prefix),
+        None => pattern == name,
+    }
+}
+
+pub fn field<'a>(table: &'a LuaType, key: &str) -> Option<&'a LuaType> {
+    match table {
+        LuaType::Table(table) => table.iter()
+            .find(|(field, _)| matches!(field, LuaType::String(field) if field == key))
+            .map(|(_, value)| value),
+        _ => None,
+    }
+}
+
+fn has_type(value: &LuaType, field_type: FieldType) -> bool {
+    matches!((value, field_type),
+        (LuaType::String(_), FieldType::String) |
+        (LuaType::Number(_), FieldType::Number) |
+        (LuaType::Boolean(_), FieldType::Boolean) |
+        (LuaType::Vector2(..), FieldType::Vector2) |
+        (LuaType::Vector3(..), FieldType::Vector3) |
+        (LuaType::Quaternion(..), FieldType::Quaternion) |
+        (LuaType::Table(_), FieldType::Table))
+}
+
+/* a value in a human-readable form, e.g., (1.000, 2.000, 0.500) for a vector */
+pub fn format(value: &LuaType) -> String {
+    match value {
+        LuaType::String(value) => format!("{:?}", value),
+        LuaType::Number(value) => format!("{}", value),
+        LuaType::Boolean(value) => format!("{}", value),
+        LuaType::Vector2(x, y) => format!("({:.3}, {:.3})", x, y),
+        LuaType::Vector3(x, y, z) => format!("({:.3}, {:.3}, {:.3})", x, y, z),
+        LuaType::Quaternion(w, x, y, z) => format!("({:.3}, {:.3}, {:.3}, {:.3})", w, x, y, z),
+        LuaType::Table(table) => {
+            let entries = table.iter()
+                .map(|(key, value)| match key {
+                    LuaType::String(key) => format!("{} = {}", key, format(value)),
+                    key => format!("[{}] = {}", format(key), format(value)),
+                })
+                .collect::<Vec<_>>();
+            format!("{{{}}}", entries.join(", "))
+        },
+    }
+}
+
+impl Registry {
+    pub fn new(schemas: Vec<Schema>) -> Self {
+        Registry { schemas }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.schemas.is_empty()
+    }
+
+    /* decode a message with the first schema that matches its sender and recipient */
+    pub fn decode(&self, message: &LuaType) -> Option<Decoded> {
+        let from = match field(message, "fromS") {
+            Some(LuaType::String(from)) => from.as_str(),
+            _ => "nil",
+        };
+        let to = match field(message, "toS") {
+            Some(LuaType::String(to)) => to.as_str(),
+            _ => "nil",
+        };
+        let schema = self.schemas.iter()
+            .find(|schema| matches(&schema.from, from) && matches(&schema.to, to))?;
+        let fields = schema.fields.iter()
+            .map(|expected| {
+                let value = expected.key.split('.')
+                    .try_fold(message, |table, key| field(table, key));
+                DecodedField {
+                    label: expected.label.clone(),
+                    value: value.map(format),
+                    valid: value.is_some_and(|value| has_type(value, expected.field_type)),
+                }
+            })
+            .collect();
+        Some(Decoded {
+            schema: schema.name.clone(),
+            from: from.to_owned(),
+            to: to.to_owned(),
+            fields,
+        })
+    }
+}
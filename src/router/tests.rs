@@ -0,0 +1,220 @@
+use super::*;
+
+fn string(value: &str) -> LuaType {
+    LuaType::String(value.to_owned())
+}
+
+fn encode(table: &[(LuaType, LuaType)]) -> BytesMut {
+    let mut buf = BytesMut::new();
+    encode_lua_table(table, &mut buf);
+    buf
+}
+
+/* reading only the routing fields of a message must give the same result as decoding the whole message */
+fn assert_consistent(message: &[u8]) {
+    let decoded = decode_lua_table(&mut &message[..]).map(|table| Routing::new(&table));
+    let read = read_routing(&mut &message[..]);
+    match (decoded, read) {
+        (Ok(decoded), Ok(read)) => assert_eq!(decoded, read, "message {:?}", message),
+        (Err(_), Err(_)) => {},
+        (decoded, read) => panic!("message {:?} was decoded as {:?} but read as {:?}", message, decoded, read),
+    }
+}
+
+fn nested_message() -> Vec<(LuaType, LuaType)> {
+    vec![
+        (string("dataT"), LuaType::Table(vec![
+            (LuaType::Number(1.0), LuaType::Table(vec![(string("fromS"), string("inner"))])),
+            (string("poseV3"), LuaType::Vector3(1.0, -2.0, 0.5)),
+            (string("orientationQ"), LuaType::Quaternion(1.0, 0.0, 0.0, 0.0)),
+        ])),
+        (LuaType::Boolean(true), LuaType::Vector2(0.25, 4.0)),
+        (string("fromS"), string("drone1")),
+        (string("toS"), string("pipuck1")),
+        (string("subscribeT"), LuaType::Table(vec![
+            (LuaType::Number(1.0), string("formation")),
+            (LuaType::Number(2.0), string("blocks")),
+        ])),
+    ]
+}
+
+#[test]
+fn read_routing_reads_the_routing_fields() {
+    let message = encode(&[
+        (string("fromS"), string("drone1")),
+        (string("dataN"), LuaType::Number(1.5)),
+        (string("toS"), string("pipuck1")),
+        (string("topicS"), string("formation")),
+    ]);
+    let routing = read_routing(&mut &message[..]).unwrap();
+    assert_eq!(routing, Routing {
+        from_s: String::from("drone1"),
+        to_s: String::from("pipuck1"),
+        topic_s: String::from("formation"),
+        subscribed: None,
+    });
+    assert_consistent(&message);
+}
+
+#[test]
+fn read_routing_agrees_with_decoding_nested_tables() {
+    let message = encode(&nested_message());
+    let routing = read_routing(&mut &message[..]).unwrap();
+    assert_eq!(routing.from_s, "drone1");
+    assert_eq!(routing.subscribed, Some(["formation", "blocks"].iter().map(|topic| topic.to_string()).collect::<HashSet<_>>()));
+    assert_consistent(&message);
+}
+
+#[test]
+fn read_routing_agrees_with_decoding_repeated_and_mistyped_fields() {
+    /* only the first occurrence of a key is used, even if its value is not a string */
+    assert_consistent(&encode(&[
+        (string("fromS"), LuaType::Number(1.0)),
+        (string("fromS"), string("drone1")),
+        (string("toS"), string("pipuck1")),
+        (string("toS"), string("pipuck2")),
+        (string("subscribeT"), string("formation")),
+        (string("subscribeT"), LuaType::Table(vec![(LuaType::Number(1.0), string("blocks"))])),
+    ]));
+}
+
+#[test]
+fn read_routing_agrees_with_decoding_malformed_tables() {
+    let mut messages = Vec::new();
+    /* a key of an unknown type */
+    let mut message = BytesMut::new();
+    message.put_i8(6);
+    message.put_i8(LUA_TNIL);
+    messages.push(message);
+    /* a nil value */
+    let mut message = BytesMut::new();
+    message.put_i8(LUA_TSTRING);
+    message.put_slice(b"fromS\0");
+    message.put_i8(LUA_TNIL);
+    messages.push(message);
+    /* a string that is not valid UTF-8, once as a routing field and once as another field */
+    for key in ["fromS", "dataS"].iter() {
+        let mut message = BytesMut::new();
+        message.put_i8(LUA_TSTRING);
+        message.put_slice(key.as_bytes());
+        message.put_u8(0);
+        message.put_i8(LUA_TSTRING);
+        message.put_slice(&[0xff, 0xfe, 0]);
+        message.put_i8(LUA_TNIL);
+        messages.push(message);
+    }
+    /* a user type that is not known */
+    let mut message = BytesMut::new();
+    message.put_i8(LUA_TSTRING);
+    message.put_slice(b"dataV\0");
+    message.put_i8(LUA_TUSERDATA);
+    message.put_u8(42);
+    messages.push(message);
+    for message in messages {
+        assert!(read_routing(&mut &message[..]).is_err(), "message {:?}", message);
+        assert_consistent(&message);
+    }
+}
+
+#[test]
+fn read_routing_agrees_with_decoding_truncated_tables() {
+    let message = encode(&nested_message());
+    for len in 0..message.len() {
+        assert_consistent(&message[..len]);
+    }
+}
+
+#[test]
+fn strings_without_a_terminator_are_not_decoded() {
+    let message = b"\x04fromS\0\x04drone1";
+    assert!(read_routing(&mut &message[..]).is_err());
+    assert!(decode_lua_table(&mut &message[..]).is_err());
+    /* the same applies to a string that is skipped */
+    let message = b"\x04dataS\0\x04drone1";
+    assert!(read_routing(&mut &message[..]).is_err());
+    assert!(decode_lua_table(&mut &message[..]).is_err());
+}
+
+#[test]
+fn encoded_tables_are_decoded_to_the_same_table() {
+    let numbers = [0.0, 1.0, -1.0, 0.5, 0.1, -0.75, 3.0e-9, -2.5e-300, 1.0e300, -123456789.123, i64::MAX as f64];
+    let mut table = numbers.iter()
+        .map(|number| (LuaType::Number(*number), LuaType::Number(-number)))
+        .collect::<Vec<_>>();
+    table.push((string("messageS"), string("")));
+    table.push((string("nestedT"), LuaType::Table(nested_message())));
+    table.push((string("emptyT"), LuaType::Table(Vec::new())));
+    table.push((LuaType::Table(vec![(LuaType::Boolean(false), LuaType::Number(7.0))]), LuaType::Boolean(false)));
+    let message = encode(&table);
+    let decoded = decode_lua_table(&mut &message[..]).unwrap();
+    assert_eq!(decoded, LuaType::Table(table));
+}
+
+/* a typical message of a controller, the routing fields are followed by the data */
+fn controller_message() -> Bytes {
+    let data = (0..32)
+        .map(|index| (LuaType::Number(index as f64), LuaType::Number(index as f64 * 0.125)))
+        .collect();
+    encode(&[
+        (string("fromS"), string("drone1")),
+        (string("toS"), string("pipuck1")),
+        (string("poseV3"), LuaType::Vector3(1.0, -2.0, 0.5)),
+        (string("dataT"), LuaType::Table(data)),
+        (string("messageS"), string(&"x".repeat(512))),
+    ]).freeze()
+}
+
+fn rate(messages: usize, elapsed: std::time::Duration) -> f64 {
+    messages as f64 / elapsed.as_secs_f64()
+}
+
+/* measures the hot path of the router, run with
+   cargo test --release -- --ignored --nocapture router::tests::throughput */
+#[tokio::test]
+#[ignore]
+async fn throughput() {
+    const MESSAGES: usize = 100_000;
+    const PEERS: u16 = 16;
+    let message = controller_message();
+    /* reading a message before it is forwarded, which decodes the whole table only if there are subscribers */
+    let start = std::time::Instant::now();
+    for _ in 0..MESSAGES {
+        let table = decode_lua_table(&mut &message[..]).unwrap();
+        assert_eq!(Routing::new(&table).to_s, "pipuck1");
+    }
+    let decoded = rate(MESSAGES, start.elapsed());
+    let start = std::time::Instant::now();
+    for _ in 0..MESSAGES {
+        assert_eq!(read_routing(&mut &message[..]).unwrap().to_s, "pipuck1");
+    }
+    let read = rate(MESSAGES, start.elapsed());
+    /* broadcasting a message to the peers, which share the same buffer */
+    let peers: Peers = Default::default();
+    for port in 0..PEERS {
+        let peer = Peer {
+            queue: Queue::new(64, Overflow::DropOldest),
+            rate_limited: AtomicU64::new(0),
+            undecodable: AtomicU64::new(0),
+        };
+        peers.lock().await.insert(SocketAddr::from(([127, 0, 0, 1], port)), Arc::new(peer));
+    }
+    let (name_address_index, topics) = (Default::default(), Default::default());
+    let start = std::time::Instant::now();
+    for _ in 0..MESSAGES {
+        forward(&peers, &name_address_index, &topics, None, None, "nil", message.clone()).await;
+    }
+    let shared = rate(MESSAGES, start.elapsed());
+    /* the same broadcast with a copy of the message for each peer */
+    let start = std::time::Instant::now();
+    for _ in 0..MESSAGES {
+        for peer in peers.lock().await.values() {
+            peer.queue.push(Bytes::copy_from_slice(&message)).await;
+        }
+    }
+    let copied = rate(MESSAGES, start.elapsed());
+    println!("message of {} bytes, {} peers", message.len(), PEERS);
+    println!("decoding the whole table: {:.0} messages/s", decoded);
+    println!("reading the routing fields: {:.0} messages/s ({:.1}x)", read, read / decoded);
+    println!("broadcasting a shared buffer: {:.0} messages/s", shared);
+    println!("broadcasting a copy per peer: {:.0} messages/s ({:.1}x slower)", copied, shared / copied);
+}
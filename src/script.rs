@@ -0,0 +1,367 @@
+use anyhow::Context;
+use futures::{StreamExt, stream::FuturesUnordered};
+use rhai::{Array, CallFnOptions, Dynamic, Engine, Map, Scope};
+use shared::{event::Event, experiment::software::Software, tracking_system::Update};
+use std::{path::{Path, PathBuf}, time::Duration};
+use tokio::sync::{broadcast::error::RecvError, mpsc, oneshot};
+
+use crate::{arena, router::{self, LuaType}, tracking, webui, robot::{drone, XbeeAction}};
+
+#[derive(Debug)]
+pub struct Configuration {
+    /* the Rhai script that orchestrates the experiments */
+    pub path: PathBuf,
+}
+
+/* the requests that the script makes, these are queued by the functions that are registered with
+   the engine and are carried out once the script has returned */
+enum Command {
+    StartExperiment {
+        builderbot_software: Option<PathBuf>,
+        drone_software: Option<PathBuf>,
+        pipuck_software: Option<PathBuf>,
+        force: bool,
+        group: Option<String>,
+    },
+    StopExperiment,
+    Send {
+        to: String,
+        table: Vec<(LuaType, LuaType)>,
+    },
+    PowerOffDrone(String),
+    /* call a function of the script after a delay */
+    After(Duration, String),
+}
+
+/// This function runs a script that orchestrates experiments, e.g., an experiment with several phases.
+/// The script can define the functions `on_start()`, `on_event(event)`, `on_connected(robot, device)`,
+/// `on_disconnected(robot, device)`, `on_message(table)`, and `on_poses(poses)`, which are called
+/// when the script is loaded and when the corresponding updates are received. From these functions,
+/// the script can start and stop experiments, send messages to robots, power off drones, and call
+/// its own functions after a delay. The functions share their state in the object map `this`. If no
+/// script has been configured, this function never completes.
+pub async fn new(
+    config: Option<Configuration>,
+    arena_tx: mpsc::Sender<arena::Action>,
+    router_tx: mpsc::Sender<router::Action>,
+    tracking_tx: mpsc::Sender<tracking::Action>,
+) -> anyhow::Result<()> {
+    let config = match config {
+        Some(config) => config,
+        /* no script has been configured, never complete */
+        None => return futures::future::pending().await,
+    };
+    let (command_tx, mut command_rx) = mpsc::unbounded_channel();
+    let engine = engine(command_tx);
+    let ast = engine.compile_file(config.path.clone())
+        .map_err(|error| anyhow::anyhow!("Could not compile script {:?}: {}", config.path, error))?;
+    /* the statements at the top level of the script are only run once */
+    let mut scope = Scope::new();
+    engine.run_ast_with_scope(&mut scope, &ast)
+        .map_err(|error| anyhow::anyhow!("Could not run script {:?}: {}", config.path, error))?;
+    /* the functions of the script can not access its variables, instead they share the object map
+       `this` to keep track of, e.g., the phase of the experiment */
+    let mut this = Dynamic::from(Map::new());
+    /* only the functions that the script defines are called */
+    let defined = |name: &str| ast.iter_functions().any(|function| function.name == name);
+    let mut call = |name: &str, args: Vec<Dynamic>| if defined(name) {
+        let options = CallFnOptions::new().eval_ast(false).bind_this_ptr(&mut this);
+        if let Err(error) = engine.call_fn_with_options::<Dynamic>(options, &mut scope, &ast, name, args) {
+            log::warn!("Script function {} failed: {}", name, error);
+        }
+    };
+    /* subscribe to the updates to which the script can react */
+    let (callback_tx, callback_rx) = oneshot::channel();
+    arena_tx.send(arena::Action::SubscribeEvents(callback_tx)).await
+        .map_err(|_| anyhow::anyhow!("Could not communicate with arena"))?;
+    let mut events_rx = callback_rx.await
+        .context("Could not subscribe to arena events")?;
+    let (callback_tx, callback_rx) = oneshot::channel();
+    router_tx.send(router::Action::Subscribe(callback_tx)).await
+        .map_err(|_| anyhow::anyhow!("Could not communicate with router"))?;
+    let mut messages_rx = callback_rx.await
+        .context("Could not subscribe to router")?;
+    let (callback_tx, callback_rx) = oneshot::channel();
+    tracking_tx.send(tracking::Action::Subscribe(callback_tx)).await
+        .map_err(|_| anyhow::anyhow!("Could not communicate with tracking system"))?;
+    let mut poses_rx = callback_rx.await
+        .context("Could not subscribe to tracking system")?;
+    let mut builderbot_updates = webui::subscribe_builderbot_updates(&arena_tx).await?;
+    let mut drone_updates = webui::subscribe_drone_updates(&arena_tx).await?;
+    let mut pipuck_updates = webui::subscribe_pipuck_updates(&arena_tx).await?;
+    let mut timers = FuturesUnordered::new();
+    call("on_start", vec![]);
+    loop {
+        tokio::select! {
+            Some(command) = command_rx.recv() => match command {
+                Command::After(delay, function) => timers.push(async move {
+                    tokio::time::sleep(delay).await;
+                    function
+                }),
+                command => if let Err(error) = execute(command, &arena_tx, &router_tx).await {
+                    log::warn!("Script request failed: {:#}", error);
+                }
+            },
+            Some(function) = timers.next() => call(&function, vec![]),
+            event = events_rx.recv() => match event {
+                Ok(event) => call("on_event", vec![event_to_dynamic(event)]),
+                Err(RecvError::Lagged(count)) => log::warn!("Script missed {} arena events", count),
+                Err(RecvError::Closed) => break,
+            },
+            message = messages_rx.recv() => match message {
+                /* avoid converting the messages unless the script uses them */
+                Ok((_, message)) => if defined("on_message") {
+                    call("on_message", vec![lua_to_dynamic(LuaType::clone(&message))]);
+                },
+                Err(RecvError::Lagged(count)) => log::warn!("Script missed {} messages", count),
+                Err(RecvError::Closed) => break,
+            },
+            poses = poses_rx.recv() => match poses {
+                /* avoid converting the poses unless the script uses them */
+                Ok(poses) => if defined("on_poses") {
+                    call("on_poses", vec![poses_to_dynamic(poses)]);
+                },
+                /* only the most recent poses are of interest */
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => break,
+            },
+            Some((desc, update)) = builderbot_updates.next() => match update {
+                Ok(shared::builderbot::Update::FernbedienungConnected(_)) =>
+                    call("on_connected", vec![desc.id.clone().into(), "fernbedienung".into()]),
+                Ok(shared::builderbot::Update::FernbedienungDisconnected) =>
+                    call("on_disconnected", vec![desc.id.clone().into(), "fernbedienung".into()]),
+                _ => continue,
+            },
+            Some((desc, update)) = drone_updates.next() => match update {
+                Ok(shared::drone::Update::FernbedienungConnected(_)) =>
+                    call("on_connected", vec![desc.id.clone().into(), "fernbedienung".into()]),
+                Ok(shared::drone::Update::FernbedienungDisconnected) =>
+                    call("on_disconnected", vec![desc.id.clone().into(), "fernbedienung".into()]),
+                Ok(shared::drone::Update::XbeeConnected(_)) =>
+                    call("on_connected", vec![desc.id.clone().into(), "xbee".into()]),
+                Ok(shared::drone::Update::XbeeDisconnected) =>
+                    call("on_disconnected", vec![desc.id.clone().into(), "xbee".into()]),
+                _ => continue,
+            },
+            Some((desc, update)) = pipuck_updates.next() => match update {
+                Ok(shared::pipuck::Update::FernbedienungConnected(_)) =>
+                    call("on_connected", vec![desc.id.clone().into(), "fernbedienung".into()]),
+                Ok(shared::pipuck::Update::FernbedienungDisconnected) =>
+                    call("on_disconnected", vec![desc.id.clone().into(), "fernbedienung".into()]),
+                _ => continue,
+            },
+        }
+    }
+    Ok(())
+}
+
+/* create an engine with the functions that the script can call */
+fn engine(command_tx: mpsc::UnboundedSender<Command>) -> Engine {
+    let mut engine = Engine::new();
+    engine.on_print(|text| log::info!("Script: {}", text));
+    let tx = command_tx.clone();
+    engine.register_fn("start_experiment", move || {
+        let _ = tx.send(Command::StartExperiment {
+            builderbot_software: None,
+            drone_software: None,
+            pipuck_software: None,
+            force: false,
+            group: None,
+        });
+    });
+    /* the options are the directories containing the software for each type of robot, the group on
+       which the experiment is started, and whether the readiness check is skipped */
+    let tx = command_tx.clone();
+    engine.register_fn("start_experiment", move |options: Map| {
+        let option = |name: &str| options.get(name).cloned()
+            .filter(|value| !value.is::<()>());
+        let _ = tx.send(Command::StartExperiment {
+            builderbot_software: option("builderbot").map(|path| path.to_string().into()),
+            drone_software: option("drone").map(|path| path.to_string().into()),
+            pipuck_software: option("pipuck").map(|path| path.to_string().into()),
+            force: option("force").and_then(|force| force.as_bool().ok()).unwrap_or(false),
+            group: option("group").map(|group| group.to_string()),
+        });
+    });
+    let tx = command_tx.clone();
+    engine.register_fn("stop_experiment", move || {
+        let _ = tx.send(Command::StopExperiment);
+    });
+    let tx = command_tx.clone();
+    engine.register_fn("send", move |to: &str, table: Map| {
+        match table.into_iter()
+            .map(|(key, value)| dynamic_to_lua(value)
+                .map(|value| (LuaType::String(key.to_string()), value)))
+            .collect::<anyhow::Result<Vec<_>>>() {
+            Ok(table) => {
+                let _ = tx.send(Command::Send { to: to.to_owned(), table });
+            },
+            Err(error) => log::warn!("Script could not send message to {}: {}", to, error),
+        }
+    });
+    let tx = command_tx.clone();
+    engine.register_fn("power_off_drone", move |id: &str| {
+        let _ = tx.send(Command::PowerOffDrone(id.to_owned()));
+    });
+    let tx = command_tx.clone();
+    engine.register_fn("after", move |seconds: rhai::INT, function: &str| {
+        let delay = Duration::from_secs(seconds.max(0) as u64);
+        let _ = tx.send(Command::After(delay, function.to_owned()));
+    });
+    let tx = command_tx;
+    engine.register_fn("after", move |seconds: rhai::FLOAT, function: &str| {
+        let delay = Duration::from_secs_f64(seconds.max(0.0));
+        let _ = tx.send(Command::After(delay, function.to_owned()));
+    });
+    engine
+}
+
+async fn execute(
+    command: Command,
+    arena_tx: &mpsc::Sender<arena::Action>,
+    router_tx: &mpsc::Sender<router::Action>,
+) -> anyhow::Result<()> {
+    match command {
+        Command::StartExperiment { builderbot_software, drone_software, pipuck_software, force, group } => {
+            let (callback_tx, callback_rx) = oneshot::channel();
+            let action = arena::Action::StartExperiment {
+                callback: callback_tx,
+                builderbot_software: load_software(builderbot_software.as_deref())?,
+                drone_software: load_software(drone_software.as_deref())?,
+                pipuck_software: load_software(pipuck_software.as_deref())?,
+                force,
+                group,
+            };
+            arena_tx.send(action).await
+                .map_err(|_| anyhow::anyhow!("Could not send action to arena"))?;
+            callback_rx.await
+                .map_err(|_| anyhow::anyhow!("No response from arena"))?
+        },
+        Command::StopExperiment => {
+            let (callback_tx, callback_rx) = oneshot::channel();
+            arena_tx.send(arena::Action::StopExperiment { callback: callback_tx }).await
+                .map_err(|_| anyhow::anyhow!("Could not send action to arena"))?;
+            callback_rx.await
+                .map_err(|_| anyhow::anyhow!("No response from arena"))?
+        },
+        Command::Send { to, table } => router_tx.send(router::Action::Send { to, table }).await
+            .map_err(|_| anyhow::anyhow!("Could not communicate with router")),
+        Command::PowerOffDrone(id) => {
+            /* power off the Up Core and then the Pixhawk */
+            for action in [XbeeAction::SetUpCorePower(false), XbeeAction::SetPixhawkPower(false)] {
+                let (callback_tx, callback_rx) = oneshot::channel();
+                let action = drone::Action::ExecuteXbeeAction(callback_tx, action);
+                arena_tx.send(arena::Action::ForwardDroneAction(id.clone(), action)).await
+                    .map_err(|_| anyhow::anyhow!("Could not send action to arena"))?;
+                callback_rx.await
+                    .map_err(|_| anyhow::anyhow!("No response from drone {}", id))?
+                    .context(format!("Could not power off drone {}", id))?;
+            }
+            Ok(())
+        },
+        Command::After(..) => Ok(()),
+    }
+}
+
+/* the software for a type of robot consists of the files in a directory */
+fn load_software(path: Option<&Path>) -> anyhow::Result<Software> {
+    let mut software = Software::default();
+    if let Some(path) = path {
+        let entries = std::fs::read_dir(path)
+            .context(format!("Could not read software directory {:?}", path))?;
+        for entry in entries {
+            let entry = entry.context(format!("Could not read software directory {:?}", path))?;
+            if entry.file_type()?.is_file() {
+                let contents = std::fs::read(entry.path())
+                    .context(format!("Could not read {:?}", entry.path()))?;
+                software.add(entry.file_name().to_string_lossy(), contents);
+            }
+        }
+    }
+    Ok(software)
+}
+
+fn event_to_dynamic(event: Event) -> Dynamic {
+    let mut map = Map::new();
+    map.insert("timestamp".into(), event.timestamp.into());
+    map.insert("severity".into(), format!("{:?}", event.severity).into());
+    map.insert("robot".into(), event.robot.map_or(Dynamic::UNIT, Dynamic::from));
+    map.insert("subsystem".into(), event.subsystem.into());
+    map.insert("message".into(), event.message.into());
+    map.into()
+}
+
+fn poses_to_dynamic(poses: Vec<Update>) -> Dynamic {
+    poses.into_iter()
+        .map(|pose| {
+            let mut map = Map::new();
+            map.insert("id".into(), (pose.id as rhai::INT).into());
+            map.insert("position".into(), pose.position.iter()
+                .map(|&value| Dynamic::from(value as rhai::FLOAT))
+                .collect::<Array>().into());
+            map.insert("orientation".into(), pose.orientation.iter()
+                .map(|&value| Dynamic::from(value as rhai::FLOAT))
+                .collect::<Array>().into());
+            Dynamic::from(map)
+        })
+        .collect::<Array>()
+        .into()
+}
+
+/* tables become object maps whose keys are the string representations of the keys of the table */
+fn lua_to_dynamic(value: LuaType) -> Dynamic {
+    match value {
+        LuaType::String(value) => value.into(),
+        LuaType::Number(value) => value.into(),
+        LuaType::Boolean(value) => value.into(),
+        LuaType::Vector2(x, y) => vec![Dynamic::from(x), Dynamic::from(y)].into(),
+        LuaType::Vector3(x, y, z) => vec![Dynamic::from(x), Dynamic::from(y), Dynamic::from(z)].into(),
+        LuaType::Quaternion(w, x, y, z) =>
+            vec![Dynamic::from(w), Dynamic::from(x), Dynamic::from(y), Dynamic::from(z)].into(),
+        LuaType::Table(entries) => entries.into_iter()
+            .map(|(key, value)| {
+                let key = match key {
+                    LuaType::String(key) => key,
+                    LuaType::Number(key) => key.to_string(),
+                    key => format!("{:?}", key),
+                };
+                (key.into(), lua_to_dynamic(value))
+            })
+            .collect::<Map>()
+            .into(),
+    }
+}
+
+/* object maps become tables and arrays become tables indexed from one */
+fn dynamic_to_lua(value: Dynamic) -> anyhow::Result<LuaType> {
+    if let Ok(value) = value.as_bool() {
+        Ok(LuaType::Boolean(value))
+    }
+    else if let Ok(value) = value.as_int() {
+        Ok(LuaType::Number(value as f64))
+    }
+    else if let Ok(value) = value.as_float() {
+        Ok(LuaType::Number(value))
+    }
+    else if value.is::<rhai::ImmutableString>() {
+        Ok(LuaType::String(value.to_string()))
+    }
+    else if value.is::<Array>() {
+        value.cast::<Array>().into_iter()
+            .enumerate()
+            .map(|(index, value)| dynamic_to_lua(value)
+                .map(|value| (LuaType::Number((index + 1) as f64), value)))
+            .collect::<anyhow::Result<Vec<_>>>()
+            .map(LuaType::Table)
+    }
+    else if value.is::<Map>() {
+        value.cast::<Map>().into_iter()
+            .map(|(key, value)| dynamic_to_lua(value)
+                .map(|value| (LuaType::String(key.to_string()), value)))
+            .collect::<anyhow::Result<Vec<_>>>()
+            .map(LuaType::Table)
+    }
+    else {
+        Err(anyhow::anyhow!("Values of type {} can not be sent", value.type_name()))
+    }
+}
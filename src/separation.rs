@@ -0,0 +1,112 @@
+use futures::StreamExt;
+use shared::{event::Severity, experiment::State};
+use std::collections::HashSet;
+use tokio::sync::{broadcast::error::RecvError, mpsc, oneshot};
+
+use crate::{arena, subscription, tracking};
+
+#[derive(Clone, Copy, Debug)]
+pub struct Configuration {
+    /* the minimum distance in meters between two drones */
+    pub distance: f32,
+    /* stop the experiment when two drones come too close */
+    pub stop: bool,
+}
+
+async fn report(arena_tx: &mpsc::Sender<arena::Action>, severity: Severity, message: String) -> anyhow::Result<()> {
+    let action = arena::Action::ReportEvent {
+        severity,
+        robot: None,
+        subsystem: String::from("separation"),
+        message,
+    };
+    arena_tx.send(action).await
+        .map_err(|_| anyhow::anyhow!("Could not communicate with arena"))
+}
+
+/// This function computes the distance between each pair of drones from the frames of the tracking
+/// system and reports an event, which is also recorded in the journal, when two drones come closer
+/// than the configured separation. If configured, the experiment is also stopped. Each pair is only
+/// reported again once it has been separated. If no separation has been configured, this function
+/// never completes.
+pub async fn new(
+    config: Option<Configuration>,
+    /* the identifier and the rigid body of each drone */
+    drones: Vec<(String, i32)>,
+    arena_tx: mpsc::Sender<arena::Action>,
+    tracking_tx: mpsc::Sender<tracking::Action>,
+) -> anyhow::Result<()> {
+    let config = match config {
+        Some(config) => config,
+        /* monitoring is disabled, never complete */
+        None => return futures::future::pending().await,
+    };
+    let (callback_tx, callback_rx) = oneshot::channel();
+    tracking_tx.send(tracking::Action::Subscribe(callback_tx)).await
+        .map_err(|_| anyhow::anyhow!("Could not communicate with tracking system"))?;
+    let mut frames = callback_rx.await
+        .map_err(|_| anyhow::anyhow!("Could not subscribe to tracking system"))?;
+    let (callback_tx, callback_rx) = oneshot::channel();
+    arena_tx.send(arena::Action::SubscribeExperimentState(callback_tx)).await
+        .map_err(|_| anyhow::anyhow!("Could not communicate with arena"))?;
+    let mut experiment_updates = callback_rx.await
+        .map(subscription::updates)
+        .map_err(|_| anyhow::anyhow!("Could not subscribe to experiment updates"))?;
+    let mut experiment_state = State::Standby;
+    /* the pairs of drones (as indices into drones) that are too close */
+    let mut violations: HashSet<(usize, usize)> = HashSet::new();
+    loop {
+        tokio::select! {
+            Some(Ok(state)) = experiment_updates.next() => experiment_state = state,
+            frame = frames.recv() => match frame {
+                Ok(frame) => {
+                    let positions = drones.iter()
+                        .map(|(_, optitrack_id)| frame.iter()
+                            .find(|update| update.id == *optitrack_id)
+                            .map(|update| update.position))
+                        .collect::<Vec<_>>();
+                    for a in 0..drones.len() {
+                        for b in (a + 1)..drones.len() {
+                            /* pairs where a drone is not visible keep their state */
+                            let (pa, pb) = match (positions[a], positions[b]) {
+                                (Some(pa), Some(pb)) => (pa, pb),
+                                _ => continue,
+                            };
+                            let distance = pa.iter().zip(pb.iter())
+                                .map(|(a, b)| (a - b) * (a - b))
+                                .sum::<f32>()
+                                .sqrt();
+                            let (id_a, id_b) = (&drones[a].0, &drones[b].0);
+                            if distance < config.distance {
+                                if violations.insert((a, b)) {
+                                    let message = format!("Drones {} and {} are {:.2} m apart (minimum {:.2} m)",
+                                        id_a, id_b, distance, config.distance);
+                                    report(&arena_tx, Severity::Warning, message).await?;
+                                    if config.stop && matches!(experiment_state, State::Active) {
+                                        let (callback_tx, callback_rx) = oneshot::channel();
+                                        arena_tx.send(arena::Action::StopExperiment { callback: callback_tx }).await
+                                            .map_err(|_| anyhow::anyhow!("Could not communicate with arena"))?;
+                                        let (severity, message) = match callback_rx.await {
+                                            Ok(Ok(_)) => (Severity::Warning, String::from("Stopped experiment since drones came too close")),
+                                            Ok(Err(error)) => (Severity::Error, format!("Could not stop experiment: {:#}", error)),
+                                            Err(_) => (Severity::Error, String::from("Could not stop experiment: no response from arena")),
+                                        };
+                                        report(&arena_tx, severity, message).await?;
+                                    }
+                                }
+                            }
+                            else if violations.remove(&(a, b)) {
+                                let message = format!("Drones {} and {} are separated again ({:.2} m apart)",
+                                    id_a, id_b, distance);
+                                report(&arena_tx, Severity::Info, message).await?;
+                            }
+                        }
+                    }
+                },
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => break,
+            }
+        }
+    }
+    Ok(())
+}
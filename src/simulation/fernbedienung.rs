@@ -0,0 +1,192 @@
+use anyhow::Context;
+use std::{collections::HashMap, net::Ipv4Addr, path::PathBuf, sync::{Arc, Mutex}};
+
+use bytes::BytesMut;
+use futures::{SinkExt, StreamExt, stream::FuturesUnordered};
+use macaddr::MacAddr6;
+use rand::Rng;
+use tokio::{net::{TcpListener, TcpStream}, sync::mpsc};
+use tokio_serde::{SymmetricallyFramed, formats::SymmetricalJson};
+use tokio_util::codec::{FramedRead, FramedWrite, LengthDelimitedCodec};
+use uuid::Uuid;
+
+use crate::network::fernbedienung::protocol::{self, process, transfer};
+
+use super::RECONNECT_DELAY;
+
+/* the range of the simulated signal strength in dBm */
+const SIGNAL_MIN: i32 = -80;
+const SIGNAL_MAX: i32 = -35;
+/* the processes that run until they are terminated, all other processes terminate immediately */
+const LONG_RUNNING_PROCESSES: &[&str] = &["argos3", "bash", "mjpg_streamer"];
+
+type Requests = SymmetricallyFramed<
+    FramedRead<tokio::io::ReadHalf<TcpStream>, LengthDelimitedCodec>,
+    protocol::Request,
+    SymmetricalJson<protocol::Request>>;
+
+type Responses = SymmetricallyFramed<
+    FramedWrite<tokio::io::WriteHalf<TcpStream>, LengthDelimitedCodec>,
+    protocol::Response,
+    SymmetricalJson<protocol::Response>>;
+
+/* the data of the transfers that have not been finished by their destination, file name, and checksum */
+type Partial = Arc<Mutex<HashMap<(PathBuf, PathBuf, String), Vec<u8>>>>;
+
+/// This function runs a simulated instance of the Fernbedienung service on the given address using version
+/// 2 of the protocol. The service reports the given MAC address and a signal strength that drifts between
+/// requests, accepts uploads without storing them, and echos the standard input of its processes. Like the
+/// real service, it keeps the data of an interrupted upload so that the upload can be resumed. After a halt
+/// or a reboot, the service stops listening and is restarted after a delay.
+pub async fn new(addr: Ipv4Addr, mac_addr: MacAddr6) -> anyhow::Result<()> {
+    let partial = Partial::default();
+    loop {
+        let listener = TcpListener::bind((addr, protocol::PORT_V2)).await
+            .context(format!("Could not bind simulated Fernbedienung service to {}", addr))?;
+        let (halt_tx, mut halt_rx) = mpsc::channel(1);
+        let mut connections = FuturesUnordered::new();
+        loop {
+            tokio::select! {
+                result = listener.accept() => {
+                    let (stream, _) = result
+                        .context(format!("Could not accept connection to simulated Fernbedienung service on {}", addr))?;
+                    connections.push(connection(stream, mac_addr, halt_tx.clone(), partial.clone()));
+                },
+                Some(_) = connections.next() => {},
+                Some(_) = halt_rx.recv() => break,
+            }
+        }
+        /* close the remaining connections as if the robot was shut down */
+        drop(connections);
+        drop(listener);
+        log::info!("Simulated Fernbedienung service on {} was halted", addr);
+        tokio::time::sleep(RECONNECT_DELAY).await;
+    }
+}
+
+async fn connection(stream: TcpStream, mac_addr: MacAddr6, halt_tx: mpsc::Sender<()>, partial: Partial) {
+    let (read, write) = tokio::io::split(stream);
+    let mut requests: Requests = SymmetricallyFramed::new(
+        FramedRead::new(read, LengthDelimitedCodec::new()),
+        SymmetricalJson::<protocol::Request>::default(),
+    );
+    let mut responses: Responses = SymmetricallyFramed::new(
+        FramedWrite::new(write, LengthDelimitedCodec::new()),
+        SymmetricalJson::<protocol::Response>::default(),
+    );
+    let (response_tx, mut response_rx) = mpsc::unbounded_channel();
+    /* the inputs of the running processes and the file and length of each transfer */
+    let mut processes: HashMap<Uuid, mpsc::UnboundedSender<process::Request>> = HashMap::new();
+    let mut transfers: HashMap<Uuid, ((PathBuf, PathBuf, String), u64)> = HashMap::new();
+    let mut signal = rand::thread_rng().gen_range(-65..=-45);
+    loop {
+        tokio::select! {
+            Some(response) = response_rx.recv() => {
+                if responses.send(response).await.is_err() {
+                    break;
+                }
+            },
+            request = requests.next() => match request {
+                Some(Ok(protocol::Request(uuid, request))) => {
+                    let respond = |response| {
+                        let _ = response_tx.send(protocol::Response(Some(uuid), response));
+                    };
+                    match request {
+                        protocol::RequestKind::Halt | protocol::RequestKind::Reboot => {
+                            let response = protocol::Response(Some(uuid), protocol::ResponseKind::Ok);
+                            let _ = responses.send(response).await;
+                            let _ = halt_tx.send(()).await;
+                            break;
+                        },
+                        protocol::RequestKind::Upload(_) => respond(protocol::ResponseKind::Ok),
+                        protocol::RequestKind::Transfer(transfer::Request::Start { filename, path, length, checksum }) => {
+                            /* resume with the data that was received before the transfer was interrupted */
+                            let file = (path, filename, checksum);
+                            let received = partial.lock().unwrap().entry(file.clone()).or_default().len() as u64;
+                            transfers.insert(uuid, (file, length));
+                            respond(protocol::ResponseKind::Resume(received));
+                        },
+                        protocol::RequestKind::Transfer(transfer::Request::Chunk(chunk)) => {
+                            let mut partial = partial.lock().unwrap();
+                            match transfers.get(&uuid).and_then(|(file, length)| partial.get_mut(file).map(|data| (*length, data))) {
+                                Some((length, data)) if (data.len() + chunk.len()) as u64 <= length => {
+                                    data.extend_from_slice(&chunk);
+                                    respond(protocol::ResponseKind::Received(data.len() as u64));
+                                },
+                                Some(_) => respond(protocol::ResponseKind::Error(String::from("Received more data than announced"))),
+                                None => respond(protocol::ResponseKind::Error(String::from("Transfer not started"))),
+                            }
+                        },
+                        protocol::RequestKind::Transfer(transfer::Request::Finish) => {
+                            match transfers.remove(&uuid) {
+                                Some((file, _)) => match partial.lock().unwrap().remove(&file) {
+                                    Some(data) if format!("{:x}", md5::compute(&data)) == file.2 =>
+                                        respond(protocol::ResponseKind::Ok),
+                                    _ => respond(protocol::ResponseKind::Error(String::from("Checksum mismatch"))),
+                                },
+                                None => respond(protocol::ResponseKind::Error(String::from("Transfer not started"))),
+                            }
+                        },
+                        protocol::RequestKind::Process(process::Request::Run(process)) => {
+                            signal = (signal + rand::thread_rng().gen_range(-2..=2)).clamp(SIGNAL_MIN, SIGNAL_MAX);
+                            let target = process.target.to_string_lossy().into_owned();
+                            if let Some(output) = output(&process, signal, mac_addr) {
+                                respond(protocol::ResponseKind::Process(
+                                    process::Response::StandardOutput(BytesMut::from(output.as_bytes()))));
+                            }
+                            match LONG_RUNNING_PROCESSES.contains(&target.as_str()) {
+                                true => {
+                                    let (input_tx, input_rx) = mpsc::unbounded_channel();
+                                    processes.insert(uuid, input_tx);
+                                    tokio::spawn(run(uuid, input_rx, response_tx.clone()));
+                                },
+                                false => respond(protocol::ResponseKind::Process(process::Response::Terminated(true))),
+                            }
+                        },
+                        protocol::RequestKind::Process(input) => {
+                            processes.retain(|_, input_tx| !input_tx.is_closed());
+                            if let Some(input_tx) = processes.get(&uuid) {
+                                let _ = input_tx.send(input);
+                            }
+                        },
+                    }
+                },
+                Some(Err(error)) => log::warn!("Could not deserialize request to simulated Fernbedienung service: {}", error),
+                None => break,
+            },
+        }
+    }
+}
+
+/* the standard output of the processes that the supervisor relies on */
+fn output(process: &process::Process, signal: i32, mac_addr: MacAddr6) -> Option<String> {
+    let target = process.target.to_string_lossy();
+    let args = process.args.iter().map(String::as_str).collect::<Vec<_>>();
+    match (target.as_ref(), args.as_slice()) {
+        ("iw", [.., "link"]) =>
+            Some(format!("Connected to 02:00:00:00:00:01 (on wlan0)\n\tsignal: {} dBm\n", signal)),
+        ("iw", [.., "info"]) =>
+            Some(format!("Interface wlan0\n\ttype managed\n\taddr {}\n", mac_addr)),
+        ("mktemp", _) =>
+            Some(format!("/tmp/tmp.{:010x}\n", rand::random::<u64>() & 0xff_ffff_ffff)),
+        _ => None,
+    }
+}
+
+/* a process that echos its standard input until it is terminated */
+async fn run(uuid: Uuid,
+             mut input_rx: mpsc::UnboundedReceiver<process::Request>,
+             response_tx: mpsc::UnboundedSender<protocol::Response>) {
+    while let Some(input) = input_rx.recv().await {
+        match input {
+            process::Request::StandardInput(input) => {
+                let response = process::Response::StandardOutput(input);
+                let _ = response_tx.send(protocol::Response(Some(uuid), protocol::ResponseKind::Process(response)));
+            },
+            process::Request::Terminate => break,
+            process::Request::Run(_) => {},
+        }
+    }
+    let response = process::Response::Terminated(true);
+    let _ = response_tx.send(protocol::Response(Some(uuid), protocol::ResponseKind::Process(response)));
+}
@@ -0,0 +1,74 @@
+use std::{convert::TryFrom, net::Ipv4Addr, time::Duration};
+use futures::{FutureExt, StreamExt, future::BoxFuture, stream::FuturesUnordered};
+use ipnet::Ipv4Net;
+use tokio::sync::mpsc;
+
+use crate::arena;
+use crate::network::{self, throttle};
+use crate::robot::{builderbot, drone, pipuck};
+
+mod fernbedienung;
+mod pixhawk;
+mod xbee;
+#[cfg(test)]
+mod tests;
+
+/* the addresses of the simulated devices are assigned from this network, these addresses must be local
+   since the simulated devices listen on them */
+const NETWORK: Ipv4Addr = Ipv4Addr::new(127, 42, 0, 0);
+/* how long it takes for a device to reappear after it has been halted or rebooted */
+const RECONNECT_DELAY: Duration = Duration::from_secs(10);
+
+/// This function replaces the network task when the robots are simulated. A simulated instance of the
+/// Fernbedienung service is run for each MAC address of a Fernbedienung device of the configured robots
+/// and a simulated Xbee with a simulated Pixhawk is run for each drone. These devices listen on local
+/// addresses, which are probed by the network task as if they belonged to real robots.
+pub async fn new(
+    builderbots: Vec<builderbot::Descriptor>,
+    drones: Vec<drone::Descriptor>,
+    pipucks: Vec<pipuck::Descriptor>,
+    limits: throttle::Limits,
+    arena_request_tx: mpsc::Sender<arena::Action>
+) {
+    let fernbedienung_macaddrs = builderbots.iter().map(|desc| desc.duovero_macaddr)
+        .chain(pipucks.iter().map(|desc| desc.rpi_macaddr))
+        .chain(drones.iter().map(|desc| desc.upcore_macaddr))
+        .collect::<Vec<_>>();
+    /* the smallest network that contains an address for each device */
+    let count = fernbedienung_macaddrs.len() + drones.len();
+    let prefix = 32 - (count as u32 + 2).next_power_of_two().trailing_zeros();
+    let net = match Ipv4Net::new(NETWORK, prefix.max(16) as u8) {
+        Ok(net) => net,
+        Err(error) => {
+            log::error!("Could not simulate {} devices: {}", count, error);
+            return;
+        }
+    };
+    let mut addrs = net.hosts();
+    let mut devices: FuturesUnordered<BoxFuture<'static, anyhow::Result<()>>> = Default::default();
+    for (mac_addr, addr) in fernbedienung_macaddrs.into_iter().zip(&mut addrs) {
+        devices.push(fernbedienung::new(addr, mac_addr).boxed());
+    }
+    for (desc, addr) in drones.iter().zip(&mut addrs) {
+        /* the identifier of the drone is set on the input pins of its Xbee */
+        let identifier = desc.xbee_identifier.unwrap_or_default();
+        let inputs = desc.profile.xbee_pins.identifier.iter()
+            .enumerate()
+            .filter_map(|(bit, &pin)| network::xbee::Pin::try_from(pin as usize).ok()
+                .map(|pin| (pin, identifier & (1 << bit) != 0)))
+            .collect();
+        devices.push(xbee::new(addr, desc.xbee_macaddr, inputs).boxed());
+        devices.push(pixhawk::new(addr, desc.profile.clone()).boxed());
+    }
+    log::info!("Simulating {} devices on {}", count, net);
+    let network_task = network::new(net, network::DEFAULT_PROBE_PARALLELISM, None, limits, Default::default(), arena_request_tx);
+    tokio::pin!(network_task);
+    loop {
+        tokio::select! {
+            Some(result) = devices.next() => if let Err(error) = result {
+                log::warn!("{:#}", error);
+            },
+            _ = &mut network_task => break,
+        }
+    }
+}
@@ -0,0 +1,81 @@
+use anyhow::Context;
+use std::{net::Ipv4Addr, time::{Duration, Instant}};
+use futures::{SinkExt, StreamExt};
+use mavlink::{MavHeader, common::{self, MavMessage}};
+use rand::Rng;
+use tokio::net::{TcpListener, TcpStream};
+use tokio_util::codec::Framed;
+use shared::drone::{Battery, Profile};
+
+use crate::robot::drone::codec::MavMessageCodec;
+
+/* the port of the serial communication service of an Xbee */
+const SERIAL_COMMUNICATION_PORT: u16 = 9750;
+/* how long it takes to discharge the battery of a drone, after which it is replaced with a full one */
+const BATTERY_DURATION: Duration = Duration::from_secs(30 * 60);
+const MESSAGE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// This function runs a simulated Pixhawk that is connected to the serial communication service of the
+/// Xbee with the given address. The Pixhawk sends heartbeats and the status of a discharging battery using
+/// the identifiers and the battery of the given profile and ignores the MAVLink messages that it receives.
+pub async fn new(addr: Ipv4Addr, profile: Profile) -> anyhow::Result<()> {
+    let listener = TcpListener::bind((addr, SERIAL_COMMUNICATION_PORT)).await
+        .context(format!("Could not bind simulated Pixhawk to {}", addr))?;
+    /* the battery starts with a random charge so that the drones do not discharge in lockstep */
+    let charge = rand::thread_rng().gen_range(0.6..1.0);
+    let start = Instant::now();
+    loop {
+        let (stream, _) = listener.accept().await
+            .context(format!("Could not accept connection to simulated Pixhawk on {}", addr))?;
+        let discharged = start.elapsed().as_secs_f32() / BATTERY_DURATION.as_secs_f32();
+        connection(stream, &profile, charge - discharged).await;
+        log::debug!("Connection to simulated Pixhawk on {} closed", addr);
+    }
+}
+
+async fn connection(stream: TcpStream, profile: &Profile, charge: f32) {
+    let Profile { battery: Battery { cells, empty_mv, full_mv }, pixhawk, .. } = *profile;
+    let mut framed = Framed::new(stream, MavMessageCodec::<MavMessage>::new());
+    let mut interval = tokio::time::interval(MESSAGE_INTERVAL);
+    let start = Instant::now();
+    let mut sequence: u8 = 0;
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {
+                let discharged = start.elapsed().as_secs_f32() / BATTERY_DURATION.as_secs_f32();
+                let charge = (charge - discharged).rem_euclid(1.0);
+                let cell_mv = empty_mv as f32 + charge * (full_mv - empty_mv) as f32;
+                let mut voltages = [u16::MAX; 10];
+                voltages[0] = (cell_mv * cells as f32) as u16;
+                let heartbeat = MavMessage::HEARTBEAT(common::HEARTBEAT_DATA {
+                    custom_mode: 0,
+                    mavtype: common::MavType::MAV_TYPE_QUADROTOR,
+                    autopilot: common::MavAutopilot::MAV_AUTOPILOT_PX4,
+                    base_mode: common::MavModeFlag::empty(),
+                    system_status: common::MavState::MAV_STATE_STANDBY,
+                    mavlink_version: 3,
+                });
+                let battery_status = MavMessage::BATTERY_STATUS(common::BATTERY_STATUS_DATA {
+                    voltages,
+                    current_battery: -1,
+                    battery_remaining: (charge * 100.0) as i8,
+                    ..Default::default()
+                });
+                for message in [heartbeat, battery_status] {
+                    let header = MavHeader {
+                        system_id: pixhawk.system_id,
+                        component_id: pixhawk.component_id,
+                        sequence,
+                    };
+                    sequence = sequence.wrapping_add(1);
+                    if framed.send((header, message)).await.is_err() {
+                        return;
+                    }
+                }
+            },
+            message = framed.next() => if message.is_none() {
+                return;
+            }
+        }
+    }
+}
@@ -0,0 +1,140 @@
+use std::{net::Ipv4Addr, time::Duration};
+use macaddr::MacAddr6;
+use tokio::{net::TcpStream, sync::{mpsc, oneshot}};
+
+use shared::experiment::software::Software;
+
+use crate::network::{fernbedienung::{self, protocol}, throttle, xbee};
+use crate::robot::{RestartPolicy, drone, pipuck};
+
+/* how long the simulated devices have to respond, this includes uploading the software */
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/* each test uses its own addresses since the tests share the loopback interface */
+const PIPUCK_RPI_ADDR: Ipv4Addr = Ipv4Addr::new(127, 42, 255, 1);
+const DRONE_XBEE_ADDR: Ipv4Addr = Ipv4Addr::new(127, 42, 255, 2);
+const DRONE_UPCORE_ADDR: Ipv4Addr = Ipv4Addr::new(127, 42, 255, 3);
+const UPLOAD_ADDR: Ipv4Addr = Ipv4Addr::new(127, 42, 255, 4);
+
+const CONFIG: &str = r#"<?xml version="1.0" ?>
+<argos-configuration>
+  <controllers>
+    <lua_controller id="controller">
+      <params script="control.lua" />
+    </lua_controller>
+  </controllers>
+</argos-configuration>"#;
+
+fn software() -> Software {
+    Software(vec![
+        (String::from("experiment.argos"), CONFIG.as_bytes().to_vec()),
+        (String::from("control.lua"), b"function init() end".to_vec()),
+    ])
+}
+
+/* start a simulated Fernbedienung service and connect to it once it accepts connections */
+async fn fernbedienung(addr: Ipv4Addr, mac_addr: MacAddr6, limits: throttle::Limits) -> fernbedienung::Device {
+    tokio::spawn(super::fernbedienung::new(addr, mac_addr));
+    tokio::time::timeout(TIMEOUT, async {
+        while TcpStream::connect((addr, protocol::PORT_V2)).await.is_err() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    }).await.expect("Simulated Fernbedienung service did not start");
+    let (return_addr_tx, _) = oneshot::channel();
+    fernbedienung::Device::new(addr, limits, return_addr_tx).await
+        .expect("Could not connect to simulated Fernbedienung service")
+}
+
+/* send an action with a callback to a robot and wait for the result */
+async fn execute<A>(action_tx: &mpsc::Sender<A>, action: impl FnOnce(oneshot::Sender<anyhow::Result<()>>) -> A)
+    -> anyhow::Result<()> {
+    let (callback_tx, callback_rx) = oneshot::channel();
+    if action_tx.send(action(callback_tx)).await.is_err() {
+        panic!("Could not communicate with robot");
+    }
+    tokio::time::timeout(TIMEOUT, callback_rx).await
+        .expect("Robot did not respond in time")
+        .expect("Robot did not respond")
+}
+
+#[tokio::test]
+async fn pipuck_runs_experiment_on_simulated_fernbedienung_service() {
+    let pipuck = pipuck::Instance::new(Vec::new(), 16);
+    let device = fernbedienung(PIPUCK_RPI_ADDR, MacAddr6::new(0x02, 0, 0, 0, 0xff, 0x01), Default::default()).await;
+    pipuck.action_tx.send(pipuck::Action::AssociateFernbedienung(device)).await
+        .expect("Could not associate Fernbedienung service");
+    /* ARGoS forwards its output to the journal */
+    let (journal_tx, _journal_rx) = mpsc::channel(8);
+    let setup = |journal_tx: mpsc::Sender<_>| move |callback|
+        pipuck::Action::SetupExperiment(callback, String::from("pipuck1"), software(), journal_tx, RestartPolicy::Never);
+    execute(&pipuck.action_tx, setup(journal_tx.clone())).await
+        .expect("Could not set up experiment");
+    execute(&pipuck.action_tx, pipuck::Action::StartExperiment).await
+        .expect("Could not start experiment");
+    pipuck.action_tx.send(pipuck::Action::StopExperiment).await
+        .expect("Could not stop experiment");
+    /* the experiment can only be set up again once the previous experiment has been stopped */
+    execute(&pipuck.action_tx, setup(journal_tx)).await
+        .expect("Could not set up experiment after stopping the previous experiment");
+}
+
+#[tokio::test]
+async fn drone_runs_experiment_on_simulated_xbee_and_fernbedienung_service() {
+    let profile = shared::drone::Profile::default();
+    tokio::spawn(super::xbee::new(DRONE_XBEE_ADDR, MacAddr6::new(0x02, 0, 0, 0, 0xff, 0x02), Vec::new()));
+    tokio::spawn(super::pixhawk::new(DRONE_XBEE_ADDR, profile.clone()));
+    let drone = drone::Instance::new(Vec::new(), profile, None, Default::default(), 16);
+    let (return_addr_tx, _) = oneshot::channel();
+    let device = xbee::Device::new(DRONE_XBEE_ADDR, return_addr_tx).await
+        .expect("Could not connect to simulated Xbee");
+    drone.action_tx.send(drone::Action::AssociateXbee(device)).await
+        .expect("Could not associate Xbee");
+    let device = fernbedienung(DRONE_UPCORE_ADDR, MacAddr6::new(0x02, 0, 0, 0, 0xff, 0x03), Default::default()).await;
+    drone.action_tx.send(drone::Action::AssociateFernbedienung(device)).await
+        .expect("Could not associate Fernbedienung service");
+    let (journal_tx, _journal_rx) = mpsc::channel(8);
+    let setup = |journal_tx: mpsc::Sender<_>| move |callback|
+        drone::Action::SetupExperiment(callback, String::from("drone1"), software(), journal_tx, RestartPolicy::Never);
+    execute(&drone.action_tx, setup(journal_tx.clone())).await
+        .expect("Could not set up experiment");
+    /* starting the experiment also enables the autonomous mode via the Xbee */
+    execute(&drone.action_tx, drone::Action::StartExperiment).await
+        .expect("Could not start experiment");
+    drone.action_tx.send(drone::Action::StopExperiment).await
+        .expect("Could not stop experiment");
+    execute(&drone.action_tx, setup(journal_tx)).await
+        .expect("Could not set up experiment after stopping the previous experiment");
+}
+
+#[tokio::test]
+async fn interrupted_upload_is_resumed_after_reconnecting() {
+    /* the upload takes a few seconds so that it is still in progress when the service is halted */
+    let limits = throttle::Limits { upload: Some(throttle::RateLimiter::new(256.0 * 1024.0)), camera: None };
+    let device = fernbedienung(UPLOAD_ADDR, MacAddr6::new(0x02, 0, 0, 0, 0xff, 0x04), limits).await;
+    let (return_addr_tx, _) = oneshot::channel();
+    let other = fernbedienung::Device::new(UPLOAD_ADDR, Default::default(), return_addr_tx).await
+        .expect("Could not connect to simulated Fernbedienung service");
+    let contents = (0..1024 * 1024).map(|index| index as u8).collect::<Vec<_>>();
+    let length = contents.len() as u64;
+    let (progress_tx, mut progress_rx) = mpsc::channel(64);
+    let upload = device.upload_with_progress("/tmp", "weights.bin", contents, progress_tx);
+    /* halt the service, which closes all connections, once the first chunk has been acknowledged */
+    let progress = async {
+        let mut progress = Vec::new();
+        while let Some((received, _)) = progress_rx.recv().await {
+            if received > 0 && progress.iter().all(|&received| received == 0) {
+                other.halt().await.expect("Could not halt simulated Fernbedienung service");
+            }
+            progress.push(received);
+        }
+        progress
+    };
+    let (result, progress) = tokio::time::timeout(Duration::from_secs(60), futures::future::join(upload, progress)).await
+        .expect("Upload did not complete in time");
+    result.expect("Could not resume upload");
+    /* the service resumes the upload with the data that it received before it was halted, it rejects the
+       upload if the chunks that it already has are sent again and the progress never returns to zero */
+    assert_eq!(progress.first(), Some(&0));
+    assert!(progress.windows(2).all(|window| window[0] <= window[1]), "progress {:?}", progress);
+    assert_eq!(progress.last(), Some(&length));
+}
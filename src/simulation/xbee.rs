@@ -0,0 +1,130 @@
+use anyhow::Context;
+use bytes::{Buf, BufMut, BytesMut};
+use std::{collections::HashMap, convert::TryFrom, net::Ipv4Addr};
+use macaddr::MacAddr6;
+use rand::Rng;
+use tokio::net::UdpSocket;
+
+use crate::network::xbee::{Pin, PinMode};
+
+/* the port of the configuration service, the requests and responses share a header of 12 bytes */
+const CONFIG_PORT: u16 = 0xBEE;
+const CONFIG_CMD_LEN: usize = 12;
+const CONFIG_CMD_REQ_ID: u8 = 0x02;
+const CONFIG_CMD_RESP_ID: u8 = 0x82;
+const CONFIG_CMD_RESP_OK: u8 = 0;
+const CONFIG_CMD_RESP_INVALID: u8 = 2;
+
+/// This function runs a simulated Xbee on the given address which responds to the remote AT commands
+/// that are sent to its configuration service. The parameters are kept in memory, the given pins are
+/// configured as inputs with the given levels (e.g., to simulate the identifier of a drone), and the link
+/// margin drifts between requests. There are no other nodes on the simulated network.
+pub async fn new(addr: Ipv4Addr, mac_addr: MacAddr6, inputs: Vec<(Pin, bool)>) -> anyhow::Result<()> {
+    let socket = UdpSocket::bind((addr, CONFIG_PORT)).await
+        .context(format!("Could not bind simulated Xbee to {}", addr))?;
+    let mut parameters: HashMap<[u8; 2], BytesMut> = HashMap::new();
+    parameters.insert([b'S', b'H'], BytesMut::from(&mac_addr.as_bytes()[..2]));
+    parameters.insert([b'S', b'L'], BytesMut::from(&mac_addr.as_bytes()[2..]));
+    parameters.insert([b'V', b'R'], BytesMut::from(&[0x20, 0x0A][..]));
+    parameters.insert([b'N', b'T'], BytesMut::from(&[0x00, 0x82][..]));
+    parameters.insert([b'B', b'D'], BytesMut::from(&9600u32.to_be_bytes()[..]));
+    parameters.insert([b'I', b'P'], BytesMut::from(&[0x00][..]));
+    for pin in (0..=14usize).filter_map(|index| Pin::try_from(index).ok()) {
+        parameters.insert(pin.into(), BytesMut::from(&[PinMode::Disable as u8][..]));
+    }
+    let mut levels: u16 = 0;
+    for (pin, level) in inputs {
+        parameters.insert(pin.into(), BytesMut::from(&[PinMode::Input as u8][..]));
+        if level {
+            levels |= 1 << pin as usize;
+        }
+    }
+    let mut link_margin: i32 = rand::thread_rng().gen_range(20..=35);
+    let mut buffer = vec![0; 1024];
+    loop {
+        let (length, source) = socket.recv_from(&mut buffer).await
+            .context(format!("Could not receive command on simulated Xbee {}", addr))?;
+        let mut request = &buffer[..length];
+        if request.len() < CONFIG_CMD_LEN {
+            continue;
+        }
+        /* skip the header, packet id, and encryption pad */
+        request.advance(6);
+        if request.get_u8() != CONFIG_CMD_REQ_ID {
+            continue;
+        }
+        /* skip the command options and whether the command is queued */
+        request.advance(1);
+        let frame_id = request.get_u8();
+        request.advance(1);
+        let at_command = [request.get_u8(), request.get_u8()];
+        let (status, data) = match (at_command, request.has_remaining()) {
+            /* write-only commands and parameters that are being set */
+            ([b'A', b'C'], _) | ([b'W', b'R'], _) => (CONFIG_CMD_RESP_OK, BytesMut::new()),
+            (_, true) => {
+                parameters.insert(at_command, BytesMut::from(request));
+                (CONFIG_CMD_RESP_OK, BytesMut::new())
+            },
+            /* the final response to a node discovery is empty */
+            ([b'N', b'D'], false) => (CONFIG_CMD_RESP_OK, BytesMut::new()),
+            ([b'L', b'M'], false) => {
+                link_margin = (link_margin + rand::thread_rng().gen_range(-2..=2)).clamp(5, 40);
+                (CONFIG_CMD_RESP_OK, BytesMut::from(&[link_margin as u8][..]))
+            },
+            ([b'I', b'S'], false) => (CONFIG_CMD_RESP_OK, samples(&parameters, levels)),
+            (_, false) => match parameters.get(&at_command) {
+                Some(value) => (CONFIG_CMD_RESP_OK, value.clone()),
+                None => (CONFIG_CMD_RESP_INVALID, BytesMut::new()),
+            },
+        };
+        /* commands with a frame identifier of zero are not acknowledged */
+        if frame_id != 0 {
+            let mut response = BytesMut::with_capacity(CONFIG_CMD_LEN + data.len());
+            response.put_u16(0x4242);
+            response.put_u16(0x0000);
+            response.put_u16(0x0000);
+            response.put_u8(CONFIG_CMD_RESP_ID);
+            response.put_u8(0x00);
+            response.put_u8(frame_id);
+            response.put(&at_command[..]);
+            response.put_u8(status);
+            response.put(&data[..]);
+            let _ = socket.send_to(&response, source).await;
+        }
+    }
+}
+
+/* the response to IS: one sample set, the digital mask, no analog channels, and the samples of the
+   pins that are configured as digital inputs or outputs */
+fn samples(parameters: &HashMap<[u8; 2], BytesMut>, levels: u16) -> BytesMut {
+    let register = |parameter: [u8; 2]| parameters.get(&parameter)
+        .and_then(|value| <[u8; 2]>::try_from(&value[..]).ok())
+        .map_or(0, u16::from_be_bytes);
+    let output_mask = register([b'O', b'M']);
+    let outputs = register([b'I', b'O']);
+    let mut digital_mask: u16 = 0;
+    let mut digital_samples: u16 = 0;
+    for pin in (0..=14usize).filter_map(|index| Pin::try_from(index).ok()) {
+        let bit = 1 << pin as usize;
+        let mode = parameters.get(&<[u8; 2]>::from(pin))
+            .and_then(|value| value.first().cloned());
+        let level = match mode {
+            Some(3) => levels & bit != 0,
+            Some(4) | Some(5) => match output_mask & bit != 0 {
+                true => outputs & bit != 0,
+                false => mode == Some(5),
+            },
+            _ => continue,
+        };
+        digital_mask |= bit;
+        if level {
+            digital_samples |= bit;
+        }
+    }
+    let mut response = BytesMut::with_capacity(6);
+    response.put_u8(1);
+    response.put_u16(digital_mask);
+    response.put_u8(0);
+    response.put_u16(digital_samples);
+    response
+}
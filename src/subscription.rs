@@ -0,0 +1,48 @@
+use futures::{Future, stream::{self, BoxStream}, StreamExt};
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio_stream::wrappers::{BroadcastStream, errors::BroadcastStreamRecvError};
+
+/* a subscription to the updates of an actor, the updates in the first field describe the state of the
+   actor when the subscription was made and are only sent to the new subscriber, they are followed by
+   every later update */
+pub type Subscription<U> = (Vec<U>, broadcast::Receiver<U>);
+
+pub type Updates<U> = BoxStream<'static, Result<U, BroadcastStreamRecvError>>;
+
+pub fn updates<U: Clone + Send + 'static>((initial, receiver): Subscription<U>) -> Updates<U> {
+    stream::iter(initial.into_iter().map(Ok))
+        .chain(BroadcastStream::new(receiver))
+        .boxed()
+}
+
+/* like updates, but a subscriber that falls behind is subscribed again so that the updates that it
+   missed are replaced with the current state of the actor. The lag is still reported so that it
+   can be logged by the subscriber */
+pub fn resubscribing<U, F, R>((initial, receiver): Subscription<U>, mut subscribe: F) -> Updates<U>
+    where U: Clone + Send + 'static,
+          F: FnMut() -> R + Send + 'static,
+          R: Future<Output = anyhow::Result<Subscription<U>>> + Send {
+    async_stream::stream! {
+        let (mut initial, mut receiver) = (initial, receiver);
+        loop {
+            for update in initial.drain(..) {
+                yield Ok(update);
+            }
+            match receiver.recv().await {
+                Ok(update) => yield Ok(update),
+                Err(RecvError::Lagged(count)) => {
+                    yield Err(BroadcastStreamRecvError::Lagged(count));
+                    match subscribe().await {
+                        Ok(subscription) => {
+                            initial = subscription.0;
+                            receiver = subscription.1;
+                        },
+                        /* keep the current receiver, which continues with the oldest buffered update */
+                        Err(error) => log::warn!("Could not resubscribe after missing {} updates: {}", count, error),
+                    }
+                },
+                Err(RecvError::Closed) => break,
+            }
+        }
+    }.boxed()
+}
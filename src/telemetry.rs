@@ -0,0 +1,148 @@
+use futures::StreamExt;
+use shared::telemetry::{Metric, Series, Window};
+use std::{collections::{HashMap, VecDeque}, time::{Duration, Instant}};
+use tokio::sync::{mpsc, oneshot};
+
+use crate::{arena, webui, robot::{builderbot, drone, pipuck}};
+
+/* how long the values of the metrics are kept */
+const RETENTION: Duration = Duration::from_secs(30 * 60);
+/* the minimum time between two values of the same metric that are kept */
+const PERIOD: Duration = Duration::from_secs(1);
+
+pub enum Action {
+    /* the values of the metrics of a robot within the given duration, oldest first */
+    Get {
+        robot: String,
+        window: Duration,
+        callback: oneshot::Sender<anyhow::Result<Window>>,
+    },
+}
+
+fn timestamp() -> f64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0.0, |duration| duration.as_secs_f64())
+}
+
+#[derive(Default)]
+struct Buffer {
+    samples: VecDeque<(f64, Option<i32>)>,
+    /* when the most recent value was kept */
+    last: Option<Instant>,
+}
+
+impl Buffer {
+    fn record(&mut self, value: Option<i32>) {
+        /* a disconnection is always kept so that the gap is visible */
+        if value.is_some() && self.last.is_some_and(|last| last.elapsed() < PERIOD) {
+            return;
+        }
+        self.last = Some(Instant::now());
+        let now = timestamp();
+        self.samples.push_back((now, value));
+        while self.samples.front().is_some_and(|&(time, _)| now - time > RETENTION.as_secs_f64()) {
+            self.samples.pop_front();
+        }
+    }
+}
+
+type Buffers = HashMap<String, HashMap<Metric, Buffer>>;
+
+fn record(buffers: &mut Buffers, robot: &str, metric: Metric, value: Option<i32>) {
+    if let Some(metrics) = buffers.get_mut(robot) {
+        metrics.entry(metric).or_default().record(value);
+    }
+}
+
+/// This function keeps the recent signal strengths, battery levels, and CPU temperatures of each
+/// robot, so that the user interface can plot them over the last minutes, e.g., to find out whether
+/// the Wi-Fi of a robot drops out intermittently. A disconnection of the link that reports a metric
+/// is kept as a missing value.
+pub async fn new(
+    arena_tx: mpsc::Sender<arena::Action>,
+    mut requests_rx: mpsc::Receiver<Action>
+) -> anyhow::Result<()> {
+    let mut builderbot_updates = webui::subscribe_builderbot_updates(&arena_tx).await?;
+    let mut drone_updates = webui::subscribe_drone_updates(&arena_tx).await?;
+    let mut pipuck_updates = webui::subscribe_pipuck_updates(&arena_tx).await?;
+    let mut buffers: Buffers = builderbot_updates.keys().map(|desc| desc.id.clone())
+        .chain(drone_updates.keys().map(|desc| desc.id.clone()))
+        .chain(pipuck_updates.keys().map(|desc| desc.id.clone()))
+        .map(|id| (id, HashMap::new()))
+        .collect();
+    loop {
+        tokio::select! {
+            Some((desc, Ok(update))) = builderbot_updates.next() => match update {
+                builderbot::Update::FernbedienungSignal(signal) =>
+                    record(&mut buffers, &desc.id, Metric::FernbedienungSignal, Some(signal)),
+                builderbot::Update::FernbedienungDisconnected => {
+                    record(&mut buffers, &desc.id, Metric::FernbedienungSignal, None);
+                    record(&mut buffers, &desc.id, Metric::Temperature, None);
+                },
+                builderbot::Update::Battery(battery) =>
+                    record(&mut buffers, &desc.id, Metric::Battery, Some(battery)),
+                builderbot::Update::Temperature(temperature) =>
+                    record(&mut buffers, &desc.id, Metric::Temperature, Some(temperature)),
+                _ => {},
+            },
+            Some((desc, Ok(update))) = drone_updates.next() => match update {
+                drone::Update::FernbedienungSignal(signal) =>
+                    record(&mut buffers, &desc.id, Metric::FernbedienungSignal, Some(signal)),
+                drone::Update::FernbedienungDisconnected => {
+                    record(&mut buffers, &desc.id, Metric::FernbedienungSignal, None);
+                    record(&mut buffers, &desc.id, Metric::Temperature, None);
+                },
+                drone::Update::XbeeSignal(signal) =>
+                    record(&mut buffers, &desc.id, Metric::XbeeSignal, Some(signal)),
+                /* the battery of a drone is reported by the Pixhawk through the Xbee */
+                drone::Update::XbeeDisconnected => {
+                    record(&mut buffers, &desc.id, Metric::XbeeSignal, None);
+                    record(&mut buffers, &desc.id, Metric::Battery, None);
+                },
+                drone::Update::Battery(battery) =>
+                    record(&mut buffers, &desc.id, Metric::Battery, Some(battery)),
+                drone::Update::Temperature(temperature) =>
+                    record(&mut buffers, &desc.id, Metric::Temperature, Some(temperature)),
+                _ => {},
+            },
+            Some((desc, Ok(update))) = pipuck_updates.next() => match update {
+                pipuck::Update::FernbedienungSignal(signal) =>
+                    record(&mut buffers, &desc.id, Metric::FernbedienungSignal, Some(signal)),
+                pipuck::Update::FernbedienungDisconnected => {
+                    record(&mut buffers, &desc.id, Metric::FernbedienungSignal, None);
+                    record(&mut buffers, &desc.id, Metric::Temperature, None);
+                },
+                pipuck::Update::Battery(battery) =>
+                    record(&mut buffers, &desc.id, Metric::Battery, Some(battery)),
+                pipuck::Update::Temperature(temperature) =>
+                    record(&mut buffers, &desc.id, Metric::Temperature, Some(temperature)),
+                _ => {},
+            },
+            request = requests_rx.recv() => match request {
+                Some(Action::Get { robot, window, callback }) => {
+                    let end = timestamp();
+                    let start = end - window.min(RETENTION).as_secs_f64();
+                    let result = buffers.get(&robot)
+                        .ok_or(anyhow::anyhow!("Robot {} is not in this arena", robot))
+                        .map(|metrics| {
+                            let mut series = metrics.iter()
+                                .map(|(metric, buffer)| Series {
+                                    metric: *metric,
+                                    samples: buffer.samples.iter()
+                                        .filter(|(time, _)| *time >= start)
+                                        .cloned()
+                                        .collect(),
+                                })
+                                .collect::<Vec<_>>();
+                            series.sort_by_key(|series| series.metric as u8);
+                            Window { robot, start, end, series }
+                        });
+                    let _ = callback.send(result);
+                },
+                None => break,
+            },
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,164 @@
+use anyhow::Context;
+use chrono::Local;
+use futures::StreamExt;
+use std::{collections::{HashMap, VecDeque}, path::PathBuf};
+use tokio::sync::{broadcast, mpsc, oneshot};
+use uuid::Uuid;
+
+use shared::ticket::{Resolution, Ticket};
+use crate::{arena, subscription::Subscription, webui, robot::{builderbot, drone, pipuck}};
+
+/* the number of recent telemetry entries to keep per robot */
+const TELEMETRY_HISTORY_LEN: usize = 64;
+
+pub enum Action {
+    Open {
+        callback: oneshot::Sender<anyhow::Result<()>>,
+        robot: String,
+        description: String,
+    },
+    Close {
+        callback: oneshot::Sender<anyhow::Result<()>>,
+        id: Uuid,
+        resolution: String,
+    },
+    Subscribe(oneshot::Sender<Subscription<Vec<Ticket>>>),
+}
+
+fn timestamp() -> String {
+    Local::now().format("%Y-%m-%d %H:%M:%S").to_string()
+}
+
+/* camera frames, terminal output, and upload progress are not useful as telemetry */
+fn describe_builderbot_update(update: &builderbot::Update) -> Option<String> {
+    match update {
+        builderbot::Update::Camera { .. } | builderbot::Update::Bash(_) |
+        builderbot::Update::Upload { .. } => None,
+        update => Some(format!("{:?}", update)),
+    }
+}
+
+fn describe_drone_update(update: &drone::Update) -> Option<String> {
+    match update {
+        drone::Update::Camera { .. } | drone::Update::Bash(_) | drone::Update::Mavlink(_) |
+        drone::Update::Upload { .. } |
+        drone::Update::PixhawkLogDownload(shared::drone::LogDownload::Progress(..)) => None,
+        update => Some(format!("{:?}", update)),
+    }
+}
+
+fn describe_pipuck_update(update: &pipuck::Update) -> Option<String> {
+    match update {
+        pipuck::Update::Camera { .. } | pipuck::Update::Bash(_) |
+        pipuck::Update::Upload { .. } => None,
+        update => Some(format!("{:?}", update)),
+    }
+}
+
+fn load(path: &PathBuf) -> anyhow::Result<HashMap<Uuid, Ticket>> {
+    match std::fs::read_to_string(path) {
+        Ok(contents) => serde_json::from_str::<Vec<Ticket>>(&contents)
+            .map(|tickets| tickets.into_iter().map(|ticket| (ticket.id, ticket)).collect())
+            .context(format!("Could not parse tickets in {:?}", path)),
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(HashMap::new()),
+        Err(error) => Err(error).context(format!("Could not read tickets from {:?}", path)),
+    }
+}
+
+fn save(path: &PathBuf, tickets: &[Ticket]) -> anyhow::Result<()> {
+    let contents = serde_json::to_string_pretty(tickets)
+        .context("Could not serialize tickets")?;
+    std::fs::write(path, contents)
+        .context(format!("Could not write tickets to {:?}", path))
+}
+
+fn sorted(tickets: &HashMap<Uuid, Ticket>) -> Vec<Ticket> {
+    let mut tickets = tickets.values().cloned().collect::<Vec<_>>();
+    tickets.sort_by(|a, b| a.opened.cmp(&b.opened));
+    tickets
+}
+
+fn record(telemetry: &mut HashMap<String, VecDeque<String>>, robot: &str, entry: String) {
+    let history = telemetry.entry(robot.to_owned()).or_default();
+    if history.len() == TELEMETRY_HISTORY_LEN {
+        history.pop_front();
+    }
+    history.push_back(format!("{} {}", timestamp(), entry));
+}
+
+pub async fn new(
+    path: PathBuf,
+    arena_tx: mpsc::Sender<arena::Action>,
+    mut requests_rx: mpsc::Receiver<Action>
+) -> anyhow::Result<()> {
+    let mut tickets = load(&path)?;
+    let (updates_tx, _) = broadcast::channel(8);
+    /* keep a short history of telemetry from each robot to attach to new tickets */
+    let mut telemetry: HashMap<String, VecDeque<String>> = HashMap::new();
+    let mut builderbot_updates = webui::subscribe_builderbot_updates(&arena_tx).await?;
+    let mut drone_updates = webui::subscribe_drone_updates(&arena_tx).await?;
+    let mut pipuck_updates = webui::subscribe_pipuck_updates(&arena_tx).await?;
+    loop {
+        tokio::select! {
+            Some((desc, Ok(update))) = builderbot_updates.next() => {
+                if let Some(entry) = describe_builderbot_update(&update) {
+                    record(&mut telemetry, &desc.id, entry);
+                }
+            },
+            Some((desc, Ok(update))) = drone_updates.next() => {
+                if let Some(entry) = describe_drone_update(&update) {
+                    record(&mut telemetry, &desc.id, entry);
+                }
+            },
+            Some((desc, Ok(update))) = pipuck_updates.next() => {
+                if let Some(entry) = describe_pipuck_update(&update) {
+                    record(&mut telemetry, &desc.id, entry);
+                }
+            },
+            request = requests_rx.recv() => match request {
+                Some(action) => match action {
+                    Action::Open { callback, robot, description } => {
+                        let ticket = Ticket {
+                            id: Uuid::new_v4(),
+                            telemetry: telemetry.get(&robot)
+                                .map(|history| history.iter().cloned().collect())
+                                .unwrap_or_default(),
+                            robot,
+                            description,
+                            opened: timestamp(),
+                            resolution: None,
+                        };
+                        log::info!("Opened ticket {} for {}", ticket.id, ticket.robot);
+                        tickets.insert(ticket.id, ticket);
+                        let tickets = sorted(&tickets);
+                        let _ = callback.send(save(&path, &tickets));
+                        let _ = updates_tx.send(tickets);
+                    },
+                    Action::Close { callback, id, resolution } => match tickets.get_mut(&id) {
+                        Some(ticket) if ticket.is_open() => {
+                            ticket.resolution = Some(Resolution {
+                                closed: timestamp(),
+                                description: resolution,
+                            });
+                            log::info!("Closed ticket {} for {}", ticket.id, ticket.robot);
+                            let tickets = sorted(&tickets);
+                            let _ = callback.send(save(&path, &tickets));
+                            let _ = updates_tx.send(tickets);
+                        },
+                        Some(_) => {
+                            let _ = callback.send(Err(anyhow::anyhow!("Ticket {} is already closed", id)));
+                        },
+                        None => {
+                            let _ = callback.send(Err(anyhow::anyhow!("Could not find ticket {}", id)));
+                        }
+                    },
+                    Action::Subscribe(callback) => {
+                        let _ = callback.send((vec![sorted(&tickets)], updates_tx.subscribe()));
+                    },
+                },
+                None => break,
+            }
+        }
+    }
+    Ok(())
+}
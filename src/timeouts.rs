@@ -0,0 +1,82 @@
+use std::{future::Future, time::Duration};
+
+/* the longest delay between two attempts, regardless of the number of retries */
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/* how long an action may take and how often it is attempted again if it fails or times out */
+#[derive(Clone, Copy, Debug)]
+pub struct Policy {
+    pub timeout: Duration,
+    pub retries: u32,
+    /* the delay before the first retry, which is doubled for each further retry */
+    pub backoff: Duration,
+}
+
+impl Policy {
+    const fn new(timeout: Duration) -> Self {
+        Policy { timeout, retries: 0, backoff: Duration::from_millis(100) }
+    }
+
+    /* run the action until it succeeds within the timeout or until the retries have been used up, in
+       which case the error of the last attempt is returned */
+    pub async fn run<F, R, T>(&self, mut action: F) -> anyhow::Result<T>
+        where F: FnMut() -> R,
+              R: Future<Output = anyhow::Result<T>> {
+        let mut attempt = 0;
+        loop {
+            let result = match tokio::time::timeout(self.timeout, action()).await {
+                Ok(result) => result,
+                Err(_) => Err(anyhow::anyhow!("Timeout after {:.1} s", self.timeout.as_secs_f64())),
+            };
+            match result {
+                Ok(value) => return Ok(value),
+                Err(error) if attempt >= self.retries => return Err(error),
+                Err(error) => {
+                    let backoff = self.backoff
+                        .checked_mul(1u32 << attempt.min(16))
+                        .map_or(MAX_BACKOFF, |backoff| backoff.min(MAX_BACKOFF));
+                    log::debug!("Attempt {} of {} failed, retrying in {:.1} s: {:#}",
+                        attempt + 1, self.retries + 1, backoff.as_secs_f64(), error);
+                    tokio::time::sleep(backoff).await;
+                    attempt += 1;
+                }
+            }
+        }
+    }
+}
+
+/* the timeouts and retries of the actions that are affected by the latency of the network */
+#[derive(Clone, Copy, Debug)]
+pub struct Configuration {
+    /* probing an address for an Xbee */
+    pub xbee: Policy,
+    /* probing an address for the fernbedienung service */
+    pub fernbedienung: Policy,
+    /* connecting to the serial communication service of the Xbee of a drone for MAVLink */
+    pub mavlink: Policy,
+    /* requesting the status of a charging dock */
+    pub dock: Policy,
+}
+
+impl Default for Configuration {
+    fn default() -> Self {
+        Configuration {
+            xbee: Policy::new(Duration::from_millis(500)),
+            fernbedienung: Policy::new(Duration::from_millis(500)),
+            mavlink: Policy::new(Duration::from_secs(1)),
+            dock: Policy::new(Duration::from_secs(2)),
+        }
+    }
+}
+
+impl Configuration {
+    /* the policies of the subsystems by the names of the nodes that configure them */
+    pub fn subsystems(&mut self) -> [(&'static str, &mut Policy); 4] {
+        [
+            ("xbee", &mut self.xbee),
+            ("fernbedienung", &mut self.fernbedienung),
+            ("mavlink", &mut self.mavlink),
+            ("dock", &mut self.dock),
+        ]
+    }
+}
@@ -0,0 +1,77 @@
+use futures::future::BoxFuture;
+use std::collections::HashMap;
+use tokio::sync::{broadcast, mpsc, oneshot};
+use shared::tracking_system::Update;
+
+pub mod optitrack;
+pub mod simulated;
+pub mod vicon;
+
+/* a motion capture system that provides the poses of rigid bodies */
+pub trait System: Send {
+    /* wait for the next frame of data from the system */
+    fn next_frame(&mut self) -> BoxFuture<'_, anyhow::Result<Vec<Update>>>;
+}
+
+#[derive(Debug)]
+pub enum Configuration {
+    Optitrack(optitrack::Configuration),
+    Vicon(vicon::Configuration),
+    Simulated(simulated::Configuration),
+}
+
+pub enum Action {
+    Subscribe(oneshot::Sender<broadcast::Receiver<Vec<Update>>>),
+    /* subscribe to the pose of a single rigid body, e.g., the body attached to a robot */
+    SubscribeRigidBody(i32, oneshot::Sender<broadcast::Receiver<Update>>),
+    /* get the identifiers of the rigid bodies in the most recent frame */
+    GetRigidBodies(oneshot::Sender<Vec<i32>>),
+}
+
+pub async fn new(config: Configuration, capacity: usize, mut requests: mpsc::Receiver<Action>) -> anyhow::Result<()> {
+    let mut system: Box<dyn System> = match config {
+        Configuration::Optitrack(config) => Box::new(optitrack::Optitrack::new(config).await?),
+        Configuration::Vicon(config) => Box::new(vicon::Vicon::new(config).await?),
+        Configuration::Simulated(config) => Box::new(simulated::Simulated::new(config)),
+    };
+    let (updates_tx, _) = broadcast::channel(capacity);
+    let mut rigid_body_txs: HashMap<i32, broadcast::Sender<Update>> = HashMap::new();
+    let mut rigid_bodies: Vec<i32> = Vec::new();
+    loop {
+        tokio::select! {
+            request = requests.recv() => match request {
+                Some(action) => match action {
+                    Action::Subscribe(callback) => {
+                        let _ = callback.send(updates_tx.subscribe());
+                    },
+                    Action::SubscribeRigidBody(id, callback) => {
+                        let rigid_body_tx = rigid_body_txs.entry(id)
+                            .or_insert_with(|| broadcast::channel(capacity).0);
+                        let _ = callback.send(rigid_body_tx.subscribe());
+                    },
+                    Action::GetRigidBodies(callback) => {
+                        let _ = callback.send(rigid_bodies.clone());
+                    },
+                },
+                None => break,
+            },
+            frame = system.next_frame() => match frame {
+                Ok(updates) => {
+                    rigid_bodies = updates.iter().map(|update| update.id).collect();
+                    /* drop the channels of rigid bodies that are no longer subscribed to */
+                    rigid_body_txs.retain(|_, rigid_body_tx| rigid_body_tx.receiver_count() > 0);
+                    for update in updates.iter() {
+                        if let Some(rigid_body_tx) = rigid_body_txs.get(&update.id) {
+                            let _ = rigid_body_tx.send(update.clone());
+                        }
+                    }
+                    let _ = updates_tx.send(updates);
+                },
+                Err(error) => {
+                    log::warn!("{}", error);
+                }
+            }
+        }
+    }
+    Ok(())
+}
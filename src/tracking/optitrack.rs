@@ -0,0 +1,150 @@
+use anyhow::Context;
+use bytes::{Buf, BytesMut};
+use natnet_decode::{
+    NatNet,
+    NatNetResponse,
+    ParseError,
+};
+use semver::Version;
+use std::{io::Cursor, net::Ipv4Addr, time::Duration};
+use futures::{FutureExt, StreamExt, future::BoxFuture};
+use tokio::{net::UdpSocket, time::Interval};
+use tokio_util::{udp::UdpFramed, codec::Decoder};
+use shared::tracking_system::Update;
+
+use super::System;
+
+#[derive(Debug)]
+struct NatNetCodec {
+    version: Version,
+}
+
+impl NatNetCodec {
+    fn new(version: Version) -> Self {
+        NatNetCodec { version }
+    }
+}
+
+const NAT_CONNECT: u16 = 0;
+const NAT_KEEPALIVE: u16 = 10;
+
+#[derive(Debug)]
+pub struct Configuration {
+    pub version: semver::Version,
+    pub bind_addr: Ipv4Addr,
+    pub bind_port: u16,
+    pub multicast_addr: Option<Ipv4Addr>,
+    pub iface_addr: Ipv4Addr,
+    pub server_addr: Option<Ipv4Addr>,
+    pub command_port: u16,
+}
+
+fn natnet_command(id: u16, payload: &str) -> Vec<u8> {
+    /* NatNet commands are a little endian message id and packet size followed by a C string */
+    let mut packet = Vec::with_capacity(payload.len() + 5);
+    packet.extend_from_slice(&id.to_le_bytes());
+    packet.extend_from_slice(&(payload.len() as u16 + 1).to_le_bytes());
+    packet.extend_from_slice(payload.as_bytes());
+    packet.push(0);
+    packet
+}
+
+impl Decoder for NatNetCodec {
+    type Item = NatNetResponse;
+    type Error = ParseError;
+
+    fn decode(&mut self, buf: &mut BytesMut) -> Result<Option<NatNetResponse>, ParseError> {
+        let mut cursor = Cursor::new(buf.as_ref());
+        match NatNet::unpack_with(&self.version, &mut cursor) {
+            Ok(response) => {
+                let position = cursor.position() as usize;
+                buf.advance(position);
+                Ok(Some(response))
+            }
+            Err(ParseError::NotEnoughBytes) => {
+                Ok(None)
+            }
+            Err(inner) => Err(inner)
+        }
+    }
+}
+
+pub struct Optitrack {
+    stream: UdpFramed<NatNetCodec>,
+    server_addr: Option<Ipv4Addr>,
+    unicast_server: Option<Ipv4Addr>,
+    command_port: u16,
+    keep_alive: Interval,
+}
+
+impl Optitrack {
+    pub async fn new(config: Configuration) -> anyhow::Result<Self> {
+        let socket = UdpSocket::bind((config.bind_addr, config.bind_port)).await
+            .context("Could not bind to port")?;
+        if let Some(multicast_addr) = config.multicast_addr {
+            socket.join_multicast_v4(multicast_addr, config.iface_addr)
+                .context("Could not join multicast group")?;
+        }
+        /* without a multicast group, ask the server to stream to us directly (unicast) */
+        let unicast_server = match (config.server_addr, config.multicast_addr) {
+            (Some(server_addr), None) => {
+                socket.send_to(&natnet_command(NAT_CONNECT, "Ping"), (server_addr, config.command_port)).await
+                    .context("Could not connect to NatNet server")?;
+                Some(server_addr)
+            },
+            (None, None) => anyhow::bail!("Either a multicast group or a server address must be provided"),
+            _ => None,
+        };
+        Ok(Optitrack {
+            stream: UdpFramed::new(socket, NatNetCodec::new(config.version)),
+            server_addr: config.server_addr,
+            unicast_server,
+            command_port: config.command_port,
+            keep_alive: tokio::time::interval(Duration::from_secs(1)),
+        })
+    }
+}
+
+impl System for Optitrack {
+    fn next_frame(&mut self) -> BoxFuture<'_, anyhow::Result<Vec<Update>>> {
+        async move {
+            loop {
+                tokio::select! {
+                    _ = self.keep_alive.tick(), if self.unicast_server.is_some() => {
+                        if let Some(server_addr) = self.unicast_server {
+                            let keep_alive = natnet_command(NAT_KEEPALIVE, "");
+                            if let Err(error) = self.stream.get_ref().send_to(&keep_alive, (server_addr, self.command_port)).await {
+                                log::warn!("Could not send keep alive to NatNet server: {}", error);
+                            }
+                        }
+                    },
+                    data = self.stream.next() => match data {
+                        Some(Ok((NatNetResponse::FrameOfData(frame), source))) => if self.server_addr
+                            .is_none_or(|server_addr| source.ip() == server_addr) {
+                            let updates = frame.rigid_bodies.iter()
+                                .map(|body| Update {
+                                    id: body.id,
+                                    position: [
+                                        body.position.x,
+                                        body.position.y,
+                                        body.position.z
+                                    ],
+                                    orientation: [
+                                        body.orientation.w,
+                                        body.orientation.i,
+                                        body.orientation.j,
+                                        body.orientation.k
+                                    ],
+                                })
+                                .collect::<Vec<_>>();
+                            return Ok(updates);
+                        },
+                        Some(Ok(_)) => {},
+                        Some(Err(error)) => anyhow::bail!("Could not decode optitrack data: {}", error),
+                        None => anyhow::bail!("Optitrack data stream closed"),
+                    }
+                }
+            }
+        }.boxed()
+    }
+}
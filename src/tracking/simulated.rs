@@ -0,0 +1,88 @@
+use futures::{FutureExt, future::BoxFuture};
+use std::{f32::consts::PI, time::Duration};
+use tokio::time::{Instant, Interval};
+use shared::tracking_system::Update;
+
+use super::System;
+
+/* frames are produced at 100 Hz, similar to the rate at which a motion capture system streams */
+const FRAME_INTERVAL: Duration = Duration::from_millis(10);
+/* the rigid bodies circle around the points of a grid with this spacing in meters */
+const GRID_SPACING: f32 = 1.0;
+const CIRCLE_RADIUS: f32 = 0.3;
+/* the angular velocities in radians per second and the altitude of the drones in meters */
+const GROUND_VELOCITY: f32 = 0.2;
+const AERIAL_VELOCITY: f32 = 0.4;
+const AERIAL_ALTITUDE: f32 = 1.5;
+
+#[derive(Debug)]
+pub struct Configuration {
+    /* the rigid bodies of the ground robots, which stay on the floor, and of the drones */
+    pub ground: Vec<i32>,
+    pub aerial: Vec<i32>,
+}
+
+struct RigidBody {
+    id: i32,
+    center: [f32; 2],
+    phase: f32,
+    aerial: bool,
+}
+
+pub struct Simulated {
+    rigid_bodies: Vec<RigidBody>,
+    start: Instant,
+    interval: Interval,
+}
+
+impl Simulated {
+    pub fn new(config: Configuration) -> Self {
+        let rigid_bodies = config.ground.into_iter().map(|id| (id, false))
+            .chain(config.aerial.into_iter().map(|id| (id, true)))
+            .collect::<Vec<_>>();
+        let columns = (rigid_bodies.len() as f32).sqrt().ceil().max(1.0) as usize;
+        let offset = (columns - 1) as f32 * GRID_SPACING / 2.0;
+        let rigid_bodies = rigid_bodies.into_iter()
+            .enumerate()
+            .map(|(index, (id, aerial))| RigidBody {
+                id,
+                center: [
+                    (index % columns) as f32 * GRID_SPACING - offset,
+                    (index / columns) as f32 * GRID_SPACING - offset,
+                ],
+                phase: rand::random::<f32>() * 2.0 * PI,
+                aerial,
+            })
+            .collect();
+        Simulated {
+            rigid_bodies,
+            start: Instant::now(),
+            interval: tokio::time::interval(FRAME_INTERVAL),
+        }
+    }
+}
+
+impl System for Simulated {
+    fn next_frame(&mut self) -> BoxFuture<'_, anyhow::Result<Vec<Update>>> {
+        async move {
+            self.interval.tick().await;
+            let time = self.start.elapsed().as_secs_f32();
+            let updates = self.rigid_bodies.iter().map(|rigid_body| {
+                let (velocity, altitude) = match rigid_body.aerial {
+                    true => (AERIAL_VELOCITY, AERIAL_ALTITUDE),
+                    false => (GROUND_VELOCITY, 0.0),
+                };
+                let angle = rigid_body.phase + velocity * time;
+                let [x, y] = rigid_body.center;
+                /* the rigid bodies face in the direction in which they are moving */
+                let yaw = angle + PI / 2.0;
+                Update {
+                    id: rigid_body.id,
+                    position: [x + CIRCLE_RADIUS * angle.cos(), y + CIRCLE_RADIUS * angle.sin(), altitude],
+                    orientation: [(yaw / 2.0).cos(), 0.0, 0.0, (yaw / 2.0).sin()],
+                }
+            }).collect();
+            Ok(updates)
+        }.boxed()
+    }
+}
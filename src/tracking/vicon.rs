@@ -0,0 +1,118 @@
+use anyhow::Context;
+use bytes::Buf;
+use futures::{FutureExt, future::BoxFuture};
+use std::{collections::HashMap, net::Ipv4Addr};
+use tokio::net::UdpSocket;
+use shared::tracking_system::Update;
+
+use super::System;
+
+/* Vicon Tracker sends the UDP object stream in packets of at most 1024 bytes */
+const MAX_PACKET_LEN: usize = 1024;
+const ITEM_OBJECT: u8 = 0;
+const OBJECT_NAME_LEN: usize = 24;
+
+#[derive(Debug)]
+pub struct Configuration {
+    pub bind_addr: Ipv4Addr,
+    pub bind_port: u16,
+    /* maps the object names in Vicon Tracker to rigid body identifiers */
+    pub objects: HashMap<String, i32>,
+}
+
+pub struct Vicon {
+    socket: UdpSocket,
+    objects: HashMap<String, i32>,
+    buffer: Vec<u8>,
+}
+
+impl Vicon {
+    pub async fn new(config: Configuration) -> anyhow::Result<Self> {
+        let socket = UdpSocket::bind((config.bind_addr, config.bind_port)).await
+            .context("Could not bind to port")?;
+        Ok(Vicon {
+            socket,
+            objects: config.objects,
+            buffer: vec![0; MAX_PACKET_LEN],
+        })
+    }
+}
+
+impl System for Vicon {
+    fn next_frame(&mut self) -> BoxFuture<'_, anyhow::Result<Vec<Update>>> {
+        async move {
+            let (len, _) = self.socket.recv_from(&mut self.buffer).await
+                .context("Could not receive Vicon data")?;
+            decode_packet(&self.buffer[..len], &self.objects)
+                .context("Could not decode Vicon data")
+        }.boxed()
+    }
+}
+
+fn multiply(a: [f64; 4], b: [f64; 4]) -> [f64; 4] {
+    let [aw, ax, ay, az] = a;
+    let [bw, bx, by, bz] = b;
+    [
+        aw * bw - ax * bx - ay * by - az * bz,
+        aw * bx + ax * bw + ay * bz - az * by,
+        aw * by - ax * bz + ay * bw + az * bx,
+        aw * bz + ax * by - ay * bx + az * bw,
+    ]
+}
+
+/* convert Euler XYZ angles (in radians) to a quaternion (w, x, y, z) */
+fn euler_xyz_to_quaternion([x, y, z]: [f64; 3]) -> [f64; 4] {
+    let qx = [(x / 2.0).cos(), (x / 2.0).sin(), 0.0, 0.0];
+    let qy = [(y / 2.0).cos(), 0.0, (y / 2.0).sin(), 0.0];
+    let qz = [(z / 2.0).cos(), 0.0, 0.0, (z / 2.0).sin()];
+    multiply(multiply(qx, qy), qz)
+}
+
+fn decode_packet(mut buf: &[u8], objects: &HashMap<String, i32>) -> anyhow::Result<Vec<Update>> {
+    if buf.remaining() < 5 {
+        anyhow::bail!("Could not decode packet header");
+    }
+    let _frame_number = buf.get_u32_le();
+    let item_count = buf.get_u8();
+    let mut updates = Vec::with_capacity(item_count as usize);
+    for _ in 0..item_count {
+        if buf.remaining() < 3 {
+            anyhow::bail!("Could not decode item header");
+        }
+        let item_id = buf.get_u8();
+        let item_len = buf.get_u16_le() as usize;
+        if buf.remaining() < item_len {
+            anyhow::bail!("Could not decode item");
+        }
+        let mut item = &buf[..item_len];
+        buf.advance(item_len);
+        /* skip items that are not objects */
+        if item_id != ITEM_OBJECT {
+            continue;
+        }
+        if item.remaining() < OBJECT_NAME_LEN + 6 * std::mem::size_of::<f64>() {
+            anyhow::bail!("Could not decode object");
+        }
+        let name = item[..OBJECT_NAME_LEN].split(|&byte| byte == 0)
+            .next()
+            .map(String::from_utf8_lossy)
+            .unwrap_or_default();
+        item.advance(OBJECT_NAME_LEN);
+        /* translations are in millimeters */
+        let position = [item.get_f64_le(), item.get_f64_le(), item.get_f64_le()];
+        let rotation = [item.get_f64_le(), item.get_f64_le(), item.get_f64_le()];
+        if let Some(&id) = objects.get(name.as_ref()) {
+            let [w, x, y, z] = euler_xyz_to_quaternion(rotation);
+            updates.push(Update {
+                id,
+                position: [
+                    (position[0] / 1000.0) as f32,
+                    (position[1] / 1000.0) as f32,
+                    (position[2] / 1000.0) as f32,
+                ],
+                orientation: [w as f32, x as f32, y as f32, z as f32],
+            });
+        }
+    }
+    Ok(updates)
+}
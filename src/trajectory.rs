@@ -0,0 +1,94 @@
+use serde::Serialize;
+use std::{collections::{HashMap, VecDeque}, time::{Duration, Instant}};
+use tokio::sync::{broadcast::error::RecvError, mpsc, oneshot};
+
+use crate::tracking;
+
+#[derive(Clone, Copy, Debug)]
+pub struct Configuration {
+    /* the number of poses that are kept for each robot */
+    pub length: usize,
+    /* the maximum number of poses per second that are kept for each robot */
+    pub rate: f64,
+}
+
+impl Default for Configuration {
+    fn default() -> Self {
+        Configuration { length: 300, rate: 5.0 }
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct Sample {
+    /* the time at which the pose was received in seconds since the epoch */
+    pub timestamp: f64,
+    pub position: [f32; 3],
+    pub orientation: [f32; 4],
+}
+
+pub enum Action {
+    /* the recent poses of each robot, oldest first */
+    Get(oneshot::Sender<HashMap<String, Vec<Sample>>>),
+}
+
+fn timestamp() -> f64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0.0, |duration| duration.as_secs_f64())
+}
+
+/// This function keeps the recent poses of each robot with a rigid body in a ring buffer, so that the
+/// trails of the robots can be drawn on the map and recent trajectories can be served without reading
+/// the journal. The poses are downsampled to the configured rate.
+pub async fn new(
+    config: Configuration,
+    /* the identifier and the rigid body of each robot */
+    robots: Vec<(String, i32)>,
+    tracking_tx: mpsc::Sender<tracking::Action>,
+    mut requests_rx: mpsc::Receiver<Action>
+) -> anyhow::Result<()> {
+    let (callback_tx, callback_rx) = oneshot::channel();
+    tracking_tx.send(tracking::Action::Subscribe(callback_tx)).await
+        .map_err(|_| anyhow::anyhow!("Could not communicate with tracking system"))?;
+    let mut frames = callback_rx.await
+        .map_err(|_| anyhow::anyhow!("Could not subscribe to tracking system"))?;
+    let period = Duration::from_secs_f64(1.0 / config.rate);
+    /* the poses of each robot and when the most recent pose was kept */
+    let mut trajectories: HashMap<i32, (String, VecDeque<Sample>, Option<Instant>)> = robots.into_iter()
+        .map(|(id, optitrack_id)| (optitrack_id, (id, VecDeque::with_capacity(config.length), None)))
+        .collect();
+    loop {
+        tokio::select! {
+            frame = frames.recv() => match frame {
+                Ok(frame) => for update in frame {
+                    if let Some((_, samples, last)) = trajectories.get_mut(&update.id) {
+                        if last.is_some_and(|last| last.elapsed() < period) {
+                            continue;
+                        }
+                        *last = Some(Instant::now());
+                        if samples.len() == config.length {
+                            samples.pop_front();
+                        }
+                        samples.push_back(Sample {
+                            timestamp: timestamp(),
+                            position: update.position,
+                            orientation: update.orientation,
+                        });
+                    }
+                },
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => break,
+            },
+            request = requests_rx.recv() => match request {
+                Some(Action::Get(callback)) => {
+                    let trajectories = trajectories.values()
+                        .map(|(id, samples, _)| (id.clone(), samples.iter().cloned().collect()))
+                        .collect();
+                    let _ = callback.send(trajectories);
+                },
+                None => break,
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,175 @@
+use anyhow::Context;
+use futures::StreamExt;
+use std::{collections::HashMap, path::PathBuf, time::{Duration, Instant}};
+use tokio::sync::{broadcast, mpsc, oneshot};
+
+use shared::{experiment::State, usage::Usage};
+use crate::{arena, subscription::{self, Subscription}, webui, robot::{builderbot, drone, pipuck}};
+
+/* how often the usage is accounted and written to disk */
+const SAVE_INTERVAL: Duration = Duration::from_secs(60);
+/* a rise of the battery level by this many percent is taken as the battery having been charged or
+   swapped, smaller rises are noise and are ignored */
+const CHARGE_THRESHOLD: i32 = 10;
+
+pub enum Action {
+    Subscribe(oneshot::Sender<Subscription<Vec<Usage>>>),
+}
+
+fn load(path: &PathBuf) -> anyhow::Result<HashMap<String, Usage>> {
+    match std::fs::read_to_string(path) {
+        Ok(contents) => serde_json::from_str::<Vec<Usage>>(&contents)
+            .map(|usage| usage.into_iter().map(|usage| (usage.robot.clone(), usage)).collect())
+            .context(format!("Could not parse usage in {:?}", path)),
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(HashMap::new()),
+        Err(error) => Err(error).context(format!("Could not read usage from {:?}", path)),
+    }
+}
+
+fn save(path: &PathBuf, usage: &[Usage]) -> anyhow::Result<()> {
+    let contents = serde_json::to_string_pretty(usage)
+        .context("Could not serialize usage")?;
+    std::fs::write(path, contents)
+        .context(format!("Could not write usage to {:?}", path))
+}
+
+fn sorted(usage: &HashMap<String, Usage>) -> Vec<Usage> {
+    let mut usage = usage.values().cloned().collect::<Vec<_>>();
+    usage.sort_by(|a, b| a.robot.cmp(&b.robot));
+    usage
+}
+
+/* the whole seconds since the instant, which is advanced by these seconds so that the remainder is
+   accounted the next time */
+fn take_seconds(instant: &mut Instant) -> u64 {
+    let seconds = instant.elapsed().as_secs();
+    *instant += Duration::from_secs(seconds);
+    seconds
+}
+
+fn usage_of<'a>(usage: &'a mut HashMap<String, Usage>, robot: &str) -> &'a mut Usage {
+    usage.entry(robot.to_owned())
+        .or_insert_with(|| Usage { robot: robot.to_owned(), ..Default::default() })
+}
+
+/* each drop of the battery level is measured against a reference, which is the most recent level
+   unless the level has risen by less than the charge threshold */
+fn record_battery(usage: &mut HashMap<String, Usage>, reference: &mut HashMap<String, i32>, robot: &str, level: i32) {
+    match reference.get_mut(robot) {
+        Some(previous) if level < *previous => {
+            usage_of(usage, robot).discharged += (*previous - level) as u64;
+            *previous = level;
+        },
+        Some(previous) if level >= *previous + CHARGE_THRESHOLD => *previous = level,
+        Some(_) => {},
+        None => {
+            reference.insert(robot.to_owned(), level);
+        }
+    }
+}
+
+/// This function keeps track of the usage of each robot, i.e., the number of experiments that it took
+/// part in, the time that it spent in experiments, the time that a drone was armed, and how much its
+/// battery was discharged. The usage is kept in a JSON file so that it is kept across restarts and
+/// can be used to schedule maintenance, e.g., replacing the propellers or retiring a battery.
+pub async fn new(
+    path: PathBuf,
+    arena_tx: mpsc::Sender<arena::Action>,
+    mut requests_rx: mpsc::Receiver<Action>
+) -> anyhow::Result<()> {
+    let mut usage = load(&path)?;
+    let (updates_tx, _) = broadcast::channel(8);
+    let mut builderbot_updates = webui::subscribe_builderbot_updates(&arena_tx).await?;
+    let mut drone_updates = webui::subscribe_drone_updates(&arena_tx).await?;
+    let mut pipuck_updates = webui::subscribe_pipuck_updates(&arena_tx).await?;
+    let (callback_tx, callback_rx) = oneshot::channel();
+    arena_tx.send(arena::Action::SubscribeExperimentState(callback_tx)).await
+        .map_err(|_| anyhow::anyhow!("Could not communicate with arena"))?;
+    let mut experiment_updates = callback_rx.await
+        .map(subscription::updates)
+        .map_err(|_| anyhow::anyhow!("Could not subscribe to experiment updates"))?;
+    /* the participants of the current experiment and the drones that are armed, with the instants
+       up to which their time has been accounted */
+    let mut experiment: Option<(Vec<String>, Instant)> = None;
+    let mut armed: HashMap<String, Instant> = HashMap::new();
+    let mut battery_reference: HashMap<String, i32> = HashMap::new();
+    let mut save_interval = tokio::time::interval(SAVE_INTERVAL);
+    loop {
+        tokio::select! {
+            Some((desc, Ok(update))) = builderbot_updates.next() => {
+                if let builderbot::Update::Battery(level) = update {
+                    record_battery(&mut usage, &mut battery_reference, &desc.id, level);
+                }
+            },
+            Some((desc, Ok(update))) = drone_updates.next() => match update {
+                drone::Update::Battery(level) =>
+                    record_battery(&mut usage, &mut battery_reference, &desc.id, level),
+                drone::Update::Armed(true) => {
+                    armed.entry(desc.id.clone()).or_insert_with(Instant::now);
+                },
+                /* the time that a drone was armed is accounted once it disarms or its Xbee disconnects */
+                drone::Update::Armed(false) | drone::Update::XbeeDisconnected => {
+                    if let Some(mut since) = armed.remove(&desc.id) {
+                        usage_of(&mut usage, &desc.id).flight_seconds += take_seconds(&mut since);
+                    }
+                },
+                _ => {},
+            },
+            Some((desc, Ok(update))) = pipuck_updates.next() => {
+                if let pipuck::Update::Battery(level) = update {
+                    record_battery(&mut usage, &mut battery_reference, &desc.id, level);
+                }
+            },
+            Some(Ok(state)) = experiment_updates.next() => match (state, experiment.take()) {
+                (State::Active, None) => {
+                    let (callback_tx, callback_rx) = oneshot::channel();
+                    arena_tx.send(arena::Action::GetParticipants(callback_tx)).await
+                        .map_err(|_| anyhow::anyhow!("Could not communicate with arena"))?;
+                    let participants = callback_rx.await
+                        .map_err(|_| anyhow::anyhow!("Could not get the participants of the experiment"))?;
+                    for robot in &participants {
+                        usage_of(&mut usage, robot).runs += 1;
+                    }
+                    experiment = Some((participants, Instant::now()));
+                },
+                (State::Active, running) => experiment = running,
+                (State::Standby, Some((participants, mut since))) => {
+                    let seconds = take_seconds(&mut since);
+                    for robot in participants {
+                        usage_of(&mut usage, &robot).experiment_seconds += seconds;
+                    }
+                    let usage = sorted(&usage);
+                    if let Err(error) = save(&path, &usage) {
+                        log::warn!("{:#}", error);
+                    }
+                    let _ = updates_tx.send(usage);
+                },
+                (State::Standby, None) => {},
+            },
+            _ = save_interval.tick() => {
+                /* account the time of the experiment and the flights that are still in progress */
+                if let Some((participants, since)) = experiment.as_mut() {
+                    let seconds = take_seconds(since);
+                    for robot in participants.iter() {
+                        usage_of(&mut usage, robot).experiment_seconds += seconds;
+                    }
+                }
+                for (robot, since) in armed.iter_mut() {
+                    usage_of(&mut usage, robot).flight_seconds += take_seconds(since);
+                }
+                let usage = sorted(&usage);
+                if let Err(error) = save(&path, &usage) {
+                    log::warn!("{:#}", error);
+                }
+                let _ = updates_tx.send(usage);
+            },
+            request = requests_rx.recv() => match request {
+                Some(Action::Subscribe(callback)) => {
+                    let _ = callback.send((vec![sorted(&usage)], updates_tx.subscribe()));
+                },
+                None => break,
+            }
+        }
+    }
+    Ok(())
+}
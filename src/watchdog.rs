@@ -0,0 +1,79 @@
+use std::{future::Future, time::{Duration, Instant}};
+
+/* a task that ran for at least this long before it exited is considered to have recovered, so that
+   the number of restarts only limits a task that keeps failing */
+const STABLE_AFTER: Duration = Duration::from_secs(60);
+
+#[derive(Debug)]
+pub struct Configuration {
+    /* the number of times that a task is restarted before the supervisor exits */
+    pub attempts: usize,
+    /* the delay before a task is restarted */
+    pub delay: Duration,
+}
+
+/* the core tasks either never complete or complete with an error */
+pub trait Outcome {
+    fn into_result(self) -> anyhow::Result<()>;
+}
+
+impl Outcome for () {
+    fn into_result(self) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
+
+impl Outcome for anyhow::Result<()> {
+    fn into_result(self) -> anyhow::Result<()> {
+        self
+    }
+}
+
+fn reason(name: &str, outcome: impl Outcome) -> anyhow::Error {
+    match outcome.into_result() {
+        Ok(_) => anyhow::anyhow!("{} task exited unexpectedly", name),
+        Err(error) => error.context(format!("{} task aborted", name)),
+    }
+}
+
+/// This function runs a core task that can not be restarted, e.g., because it owns the receiving end
+/// of a channel or the state of the robots, and returns why the task exited, so that the supervisor
+/// can report which task failed before exiting.
+pub async fn watch<T: Outcome>(name: &'static str, task: impl Future<Output = T>) -> anyhow::Error {
+    reason(name, task.await)
+}
+
+/// This function runs a core task that can be restarted since all of its inputs can be created
+/// again, e.g., the network task. Each time the task exits, the reason is logged and, if restarts
+/// have been configured, the task is created again by `task`. This function returns why the task
+/// exited once it can not be restarted anymore.
+pub async fn restartable<T, F, Fut>(
+    name: &'static str,
+    config: Option<&Configuration>,
+    mut task: F
+) -> anyhow::Error
+where
+    T: Outcome,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = T>,
+{
+    let mut restarts = 0;
+    loop {
+        let started = Instant::now();
+        let error = reason(name, task().await);
+        let config = match config {
+            Some(config) => config,
+            None => return error,
+        };
+        if started.elapsed() >= STABLE_AFTER {
+            restarts = 0;
+        }
+        if restarts >= config.attempts {
+            return error.context(format!("Gave up after restarting {} task {} times", name, restarts));
+        }
+        restarts += 1;
+        log::error!("{:#}", error);
+        log::info!("Restarting {} task in {:?} (attempt {} of {})", name, config.delay, restarts, config.attempts);
+        tokio::time::sleep(config.delay).await;
+    }
+}
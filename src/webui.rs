@@ -1,13 +1,13 @@
 use anyhow::Context;
 use futures::{FutureExt, SinkExt, StreamExt, TryFutureExt, TryStreamExt, stream::{self, FuturesUnordered}};
-use shared::{BackEndRequest, DownMessage, FrontEndRequest, UpMessage, tracking_system};
-use std::{net::SocketAddr, ops::Deref, sync::Arc};
-use tokio::{self, sync::{mpsc, oneshot}};
-use tokio_stream::{StreamMap, wrappers::{BroadcastStream, errors::BroadcastStreamRecvError}};
+use shared::{BackEndRequest, DownMessage, FrontEndRequest, UpMessage, UpdateProfile, control, logging, map, tracking_system};
+use std::{collections::HashMap, hash::Hash, net::SocketAddr, ops::Deref, sync::Arc, time::{Duration, Instant}};
+use tokio::{self, sync::{broadcast, mpsc, oneshot, watch}};
+use tokio_stream::{StreamMap, wrappers::{BroadcastStream, ReceiverStream, WatchStream, errors::BroadcastStreamRecvError}};
 use warp::Filter;
 use uuid::Uuid;
 
-use crate::{arena, optitrack, robot::{self, builderbot, drone, pipuck}};
+use crate::{api, arena, asset, audit, metrics, router, subscription, ticket, tracking, usage, robot::{self, builderbot, drone, pipuck}};
 
 // down message (from backend to the client)
 // up message (from client to the backend)
@@ -16,10 +16,50 @@ use crate::{arena, optitrack, robot::{self, builderbot, drone, pipuck}};
 const CLIENT_WASM_BYTES: &'static [u8] = include_bytes!(env!("CLIENT_WASM"));
 const CLIENT_JS_BYTES: &'static [u8] = include_bytes!(env!("CLIENT_JS"));
 
+#[derive(Clone, Debug)]
+pub struct MapConfiguration {
+    /* the rate in Hz at which map snapshots are sent to each client */
+    pub rate: f64,
+    pub bounds: Option<map::Bounds>,
+}
+
+/* an arena that can be selected in the web interface */
+#[derive(Clone, Debug)]
+pub struct Arena {
+    pub name: String,
+    pub arena_tx: mpsc::Sender<arena::Action>,
+    pub tracking_tx: mpsc::Sender<tracking::Action>,
+}
+
+impl Default for MapConfiguration {
+    fn default() -> Self {
+        MapConfiguration { rate: 2.0, bounds: None }
+    }
+}
+
+/* the services that are shared by the clients of all arenas */
+#[derive(Clone)]
+pub struct Services {
+    pub ticket_tx: mpsc::Sender<ticket::Action>,
+    pub usage_tx: mpsc::Sender<usage::Action>,
+    pub audit_tx: mpsc::Sender<audit::Action>,
+    pub asset_tx: mpsc::Sender<asset::Action>,
+    pub metrics_tx: mpsc::Sender<metrics::Action>,
+    pub log_records_tx: broadcast::Sender<logging::Record>,
+    /* rehearsal mode is shared by the clients of all arenas */
+    pub rehearsal_tx: Arc<watch::Sender<bool>>,
+    pub rehearsal_rx: watch::Receiver<bool>,
+    pub shutdown_rx: watch::Receiver<bool>,
+}
+
 pub async fn new(
     server_addr: SocketAddr,
-    arena_tx: mpsc::Sender<arena::Action>,
-    optitrack_tx: mpsc::Sender<optitrack::Action>
+    map_config: MapConfiguration,
+    /* the minimum time between two camera frames, signal strengths, or poses that are sent to a client */
+    update_interval: Duration,
+    /* the arenas that can be selected, the first arena is selected by default */
+    arenas: Vec<Arena>,
+    services: Services,
 ) {
     /* start the server */
     let wasm_route = warp::path("client_bg.wasm")
@@ -28,27 +68,257 @@ pub async fn new(
     let js_route = warp::path("client.js")
         .and(warp::path::end())
         .map(|| warp::reply::with_header(CLIENT_JS_BYTES, "content-type", "application/javascript"));
-    let arena_tx = warp::any().map(move || arena_tx.clone());
-    let optitrack_tx = warp::any().map(move || optitrack_tx.clone());
+    /* the arena is selected by the query parameter "arena", e.g., /socket?arena=cage1 */
+    let arena_names = Arc::new(arenas.iter().map(|arena| arena.name.clone()).collect::<Vec<_>>());
+    let arena_names = warp::any().map(move || arena_names.clone());
+    let arenas = Arc::new(arenas);
+    let arena = warp::query::<HashMap<String, String>>()
+        .and(warp::any().map(move || arenas.clone()))
+        .and_then(|query: HashMap<String, String>, arenas: Arc<Vec<Arena>>| async move {
+            let arena = match query.get("arena") {
+                Some(name) => arenas.iter().find(|arena| &arena.name == name),
+                None => arenas.first(),
+            };
+            arena.cloned().ok_or_else(warp::reject::not_found)
+        });
+    let map_config = Arc::new(map_config);
+    let map_config = warp::any().map(move || map_config.clone());
+    /* metrics in the Prometheus text format */
+    let metrics_tx = services.metrics_tx.clone();
+    let metrics_tx = warp::any().map(move || metrics_tx.clone());
+    let services = warp::any().map(move || services.clone());
+    let metrics_route = warp::path("metrics")
+        .and(warp::path::end())
+        .and(metrics_tx)
+        .and_then(|metrics_tx: mpsc::Sender<metrics::Action>| async move {
+            let (callback_tx, callback_rx) = oneshot::channel();
+            let metrics = metrics_tx.send(metrics::Action::Render(callback_tx))
+                .map_err(|_| anyhow::anyhow!("Could not communicate with metrics"))
+                .and_then(|_| callback_rx
+                    .map_err(|_| anyhow::anyhow!("No response from metrics")))
+                .await;
+            let reply = match metrics {
+                Ok(metrics) => warp::reply::with_status(metrics, warp::http::StatusCode::OK),
+                Err(error) => warp::reply::with_status(error.to_string(), warp::http::StatusCode::SERVICE_UNAVAILABLE),
+            };
+            Ok::<_, warp::Rejection>(warp::reply::with_header(reply, "content-type", "text/plain; version=0.0.4"))
+        });
+    /* plain JSON stream for third-party dashboards */
+    let api_route = warp::path!("api" / "stream")
+        .and(warp::ws())
+        .and(arena.clone())
+        .map(|websocket: warp::ws::Ws, arena: Arena| {
+            websocket.on_upgrade(move |socket| api::handle_client(socket, arena.arena_tx, arena.tracking_tx))
+        });
+    /* the state of the arena and all of its robots as JSON */
+    let state_route = warp::path!("api" / "state")
+        .and(arena.clone())
+        .and_then(|arena: Arena| async move {
+            let reply = match api::snapshot(&arena.arena_tx).await {
+                Ok(snapshot) => warp::reply::with_status(warp::reply::json(&snapshot), warp::http::StatusCode::OK),
+                Err(error) => warp::reply::with_status(warp::reply::json(&error.to_string()),
+                    warp::http::StatusCode::SERVICE_UNAVAILABLE),
+            };
+            Ok::<_, warp::Rejection>(reply)
+        });
+    /* the recent poses of each robot as JSON */
+    let trajectories_route = warp::path!("api" / "trajectories")
+        .and(arena.clone())
+        .and_then(|arena: Arena| async move {
+            let reply = match api::trajectories(&arena.arena_tx).await {
+                Ok(trajectories) => warp::reply::with_status(warp::reply::json(&trajectories), warp::http::StatusCode::OK),
+                Err(error) => warp::reply::with_status(warp::reply::json(&error.to_string()),
+                    warp::http::StatusCode::SERVICE_UNAVAILABLE),
+            };
+            Ok::<_, warp::Rejection>(reply)
+        });
+    /* capture a single image from a camera of a robot and download it as a JPEG file, e.g.,
+       /snapshot/drone/drone1?camera=/dev/camera0 */
+    let snapshot_route = warp::path!("snapshot" / String / String)
+        .and(warp::query::<HashMap<String, String>>())
+        .and(arena.clone())
+        .and_then(|kind: String, id: String, query: HashMap<String, String>, arena: Arena| async move {
+            let image = match query.get("camera") {
+                Some(camera) => capture_image(&arena.arena_tx, &kind, &id, camera).await
+                    .map(|image| (camera, image)),
+                None => Err(anyhow::anyhow!("Could not find query parameter \"camera\"")),
+            };
+            let reply = match image {
+                Ok((camera, image)) => {
+                    let camera = std::path::Path::new(camera).file_name()
+                        .and_then(|camera| camera.to_str())
+                        .unwrap_or("camera");
+                    let file_name = format!("{}-{}-{}.jpg", id, camera, chrono::Local::now().format("%Y%m%d-%H%M%S"));
+                    warp::http::Response::builder()
+                        .header("content-type", "image/jpeg")
+                        .header("content-disposition", format!("attachment; filename=\"{}\"", file_name))
+                        .body(image)
+                },
+                Err(error) => warp::http::Response::builder()
+                    .status(warp::http::StatusCode::SERVICE_UNAVAILABLE)
+                    .body(bytes::Bytes::from(error.to_string())),
+            };
+            Ok::<_, warp::Rejection>(reply)
+        });
+    /* retrieve the kernel messages of a robot and download them as a text file, e.g.,
+       /kernel_messages/drone/drone1 */
+    let kernel_messages_route = warp::path!("kernel_messages" / String / String)
+        .and(arena.clone())
+        .and_then(|kind: String, id: String, arena: Arena| async move {
+            let reply = match kernel_messages(&arena.arena_tx, &kind, &id).await {
+                Ok(messages) => {
+                    let file_name = format!("{}-dmesg-{}.txt", id, chrono::Local::now().format("%Y%m%d-%H%M%S"));
+                    warp::http::Response::builder()
+                        .header("content-type", "text/plain; charset=utf-8")
+                        .header("content-disposition", format!("attachment; filename=\"{}\"", file_name))
+                        .body(messages)
+                },
+                Err(error) => warp::http::Response::builder()
+                    .status(warp::http::StatusCode::SERVICE_UNAVAILABLE)
+                    .body(error.to_string()),
+            };
+            Ok::<_, warp::Rejection>(reply)
+        });
     let socket_route = warp::path("socket")
         .and(warp::path::end())
         .and(warp::ws())
-        .and(arena_tx)
-        .and(optitrack_tx)
-        .map(|websocket: warp::ws::Ws, arena_tx, optitrack_tx| {
-            websocket.on_upgrade(move |socket| handle_client(socket, arena_tx, optitrack_tx))
+        .and(arena)
+        .and(arena_names)
+        .and(services)
+        .and(map_config)
+        .and(warp::addr::remote())
+        .map(move |websocket: warp::ws::Ws, arena: Arena, arena_names, services, map_config, remote: Option<SocketAddr>| {
+            let client = arena::Controller {
+                id: Uuid::new_v4(),
+                name: remote.map_or(String::from("Unknown client"), |remote| remote.to_string()),
+            };
+            websocket.on_upgrade(move |socket|
+                handle_client(socket, client, arena, arena_names, services, map_config, update_interval))
         });
     let static_route = warp::get()
         .and(static_dir::static_dir!("client/public/"));
-    warp::serve(js_route.or(wasm_route).or(socket_route).or(static_route))
+    warp::serve(js_route.or(wasm_route).or(socket_route).or(api_route).or(state_route).or(trajectories_route).or(snapshot_route).or(kernel_messages_route).or(metrics_route).or(static_route))
         .run(server_addr).await   
 }
 
+async fn capture_image(
+    arena_tx: &mpsc::Sender<arena::Action>,
+    kind: &str,
+    id: &str,
+    camera: &str
+) -> anyhow::Result<bytes::Bytes> {
+    let (callback_tx, callback_rx) = oneshot::channel();
+    let (image_tx, image_rx) = oneshot::channel();
+    let action = robot::FernbedienungAction::CaptureImage(camera.to_owned(), image_tx);
+    let action = forward_fernbedienung_action(kind, id, callback_tx, action)?;
+    arena_tx.send(action).await
+        .map_err(|_| anyhow::anyhow!("Could not communicate with arena"))?;
+    callback_rx.await
+        .map_err(|_| anyhow::anyhow!("No response from {}", id))??;
+    image_rx.await
+        .map_err(|_| anyhow::anyhow!("Could not capture image from {} of {}", camera, id))
+}
+
+async fn kernel_messages(
+    arena_tx: &mpsc::Sender<arena::Action>,
+    kind: &str,
+    id: &str
+) -> anyhow::Result<String> {
+    let (callback_tx, callback_rx) = oneshot::channel();
+    let (messages_tx, messages_rx) = oneshot::channel();
+    let action = robot::FernbedienungAction::GetKernelMessages(messages_tx);
+    let action = forward_fernbedienung_action(kind, id, callback_tx, action)?;
+    arena_tx.send(action).await
+        .map_err(|_| anyhow::anyhow!("Could not communicate with arena"))?;
+    callback_rx.await
+        .map_err(|_| anyhow::anyhow!("No response from {}", id))??;
+    messages_rx.await
+        .map_err(|_| anyhow::anyhow!("Could not retrieve kernel messages of {}", id))
+}
+
+/* forward an action to the Fernbedienung task of a robot, where kind is the type of the robot */
+fn forward_fernbedienung_action(
+    kind: &str,
+    id: &str,
+    callback_tx: oneshot::Sender<anyhow::Result<()>>,
+    action: robot::FernbedienungAction
+) -> anyhow::Result<arena::Action> {
+    match kind {
+        "builderbot" => Ok(arena::Action::ForwardBuilderBotAction(id.to_owned(),
+            builderbot::Action::ExecuteFernbedienungAction(callback_tx, action))),
+        "drone" => Ok(arena::Action::ForwardDroneAction(id.to_owned(),
+            drone::Action::ExecuteFernbedienungAction(callback_tx, action))),
+        "pipuck" => Ok(arena::Action::ForwardPiPuckAction(id.to_owned(),
+            pipuck::Action::ExecuteFernbedienungAction(callback_tx, action))),
+        _ => Err(anyhow::anyhow!("Unknown type of robot \"{}\"", kind)),
+    }
+}
+
+/* the minimum time between two frames from the same camera */
+fn camera_interval(profile: UpdateProfile) -> Duration {
+    match profile {
+        UpdateProfile::Desktop => Duration::from_millis(0),
+        UpdateProfile::Tablet => Duration::from_millis(500),
+        UpdateProfile::Phone => Duration::from_millis(2000),
+    }
+}
+
+/* the minimum time between two updates from the tracking system */
+fn tracking_system_interval(profile: UpdateProfile) -> Duration {
+    match profile {
+        UpdateProfile::Desktop => Duration::from_millis(0),
+        UpdateProfile::Tablet => Duration::from_millis(100),
+        UpdateProfile::Phone => Duration::from_millis(500),
+    }
+}
+
+/* the maximum number of samples in each series of a telemetry chart */
+fn chart_samples(profile: UpdateProfile) -> Option<usize> {
+    match profile {
+        UpdateProfile::Desktop => None,
+        UpdateProfile::Tablet => Some(300),
+        UpdateProfile::Phone => Some(100),
+    }
+}
+
+/* drops updates that arrive sooner than an interval after the last forwarded update */
+struct Throttle<K> {
+    forwarded: HashMap<K, Instant>,
+}
+
+impl<K: Hash + Eq> Throttle<K> {
+    fn new() -> Self {
+        Throttle { forwarded: HashMap::new() }
+    }
+
+    fn permit(&mut self, key: K, interval: Duration) -> bool {
+        let now = Instant::now();
+        match self.forwarded.get(&key) {
+            Some(forwarded) if now.duration_since(*forwarded) < interval => false,
+            _ => {
+                self.forwarded.insert(key, now);
+                true
+            }
+        }
+    }
+}
+
 async fn handle_client(
     ws: warp::ws::WebSocket,
-    arena_tx: mpsc::Sender<arena::Action>,
-    optitrack_tx: mpsc::Sender<optitrack::Action>
+    client: arena::Controller,
+    arena: Arena,
+    arena_names: Arc<Vec<String>>,
+    services: Services,
+    map_config: Arc<MapConfiguration>,
+    update_interval: Duration,
 ) {
+    let Arena { name: arena_name, arena_tx, tracking_tx } = arena;
+    let Services { ticket_tx, usage_tx, audit_tx, asset_tx, log_records_tx, rehearsal_tx, rehearsal_rx, shutdown_rx, .. } = services;
+    let log_records_rx = log_records_tx.subscribe();
+    /* the update profile can be changed by the client at any time */
+    let (profile_tx, profile_rx) = watch::channel(UpdateProfile::default());
+    /* the messages of the router are only sent while the client inspects the router */
+    let (inspect_tx, inspect_rx) = watch::channel(false);
     /* subscribe to builderbot updates and map them to websocket messages */
     let builderbot_updates = match subscribe_builderbot_updates(&arena_tx).await {
         Ok(updates) => {
@@ -56,15 +326,28 @@ async fn handle_client(
                 .cloned()
                 .map(|desc| DownMessage::Request(Uuid::new_v4(), FrontEndRequest::AddBuilderBot(desc.deref().clone())))
                 .collect::<Vec<_>>();
+            let profile_rx = profile_rx.clone();
+            let mut camera_throttle = Throttle::new();
+            let mut signal_throttle = Throttle::new();
             let update_builderbot_messages = updates
-                .filter_map(|(desc, update)| async move {
-                    match update {
-                        Ok(update) => {
-                            Some(DownMessage::Request(Uuid::new_v4(), FrontEndRequest::UpdateBuilderBot(desc.id.clone(), update)))
-                        }
-                        Err(BroadcastStreamRecvError::Lagged(count)) => {
-                            log::warn!("Client missed {} messages for {}", count, desc);
-                            None
+                .filter_map(move |(desc, update)| {
+                    let forward = match &update {
+                        Ok(builderbot::Update::Camera { camera, .. }) => camera_throttle
+                            .permit((desc.id.clone(), camera.clone()), camera_interval(*profile_rx.borrow()).max(update_interval)),
+                        Ok(builderbot::Update::FernbedienungSignal(_)) => signal_throttle
+                            .permit((desc.id.clone(), "Fernbedienung"), update_interval),
+                        _ => true,
+                    };
+                    async move {
+                        match update {
+                            Ok(_) if !forward => None,
+                            Ok(update) => {
+                                Some(DownMessage::Request(Uuid::new_v4(), FrontEndRequest::UpdateBuilderBot(desc.id.clone(), update)))
+                            }
+                            Err(BroadcastStreamRecvError::Lagged(count)) => {
+                                log::warn!("Client missed {} messages for {}", count, desc);
+                                None
+                            }
                         }
                     }
                 });
@@ -86,15 +369,30 @@ async fn handle_client(
                 .cloned()
                 .map(|desc| DownMessage::Request(Uuid::new_v4(), FrontEndRequest::AddDrone(desc.deref().clone())))
                 .collect::<Vec<_>>();
+            let profile_rx = profile_rx.clone();
+            let mut camera_throttle = Throttle::new();
+            let mut signal_throttle = Throttle::new();
             let update_drone_messages = updates
-                .filter_map(|(desc, update)| async move {
-                    match update {
-                        Ok(update) => {
-                            Some(DownMessage::Request(Uuid::new_v4(), FrontEndRequest::UpdateDrone(desc.id.clone(), update)))
-                        }
-                        Err(BroadcastStreamRecvError::Lagged(count)) => {
-                            log::warn!("Client missed {} messages for {}", count, desc);
-                            None
+                .filter_map(move |(desc, update)| {
+                    let forward = match &update {
+                        Ok(drone::Update::Camera { camera, .. }) => camera_throttle
+                            .permit((desc.id.clone(), camera.clone()), camera_interval(*profile_rx.borrow()).max(update_interval)),
+                        Ok(drone::Update::FernbedienungSignal(_)) => signal_throttle
+                            .permit((desc.id.clone(), "Fernbedienung"), update_interval),
+                        Ok(drone::Update::XbeeSignal(_)) => signal_throttle
+                            .permit((desc.id.clone(), "Xbee"), update_interval),
+                        _ => true,
+                    };
+                    async move {
+                        match update {
+                            Ok(_) if !forward => None,
+                            Ok(update) => {
+                                Some(DownMessage::Request(Uuid::new_v4(), FrontEndRequest::UpdateDrone(desc.id.clone(), update)))
+                            }
+                            Err(BroadcastStreamRecvError::Lagged(count)) => {
+                                log::warn!("Client missed {} messages for {}", count, desc);
+                                None
+                            }
                         }
                     }
                 });
@@ -116,15 +414,28 @@ async fn handle_client(
                 .cloned()
                 .map(|desc| DownMessage::Request(Uuid::new_v4(), FrontEndRequest::AddPiPuck(desc.deref().clone())))
                 .collect::<Vec<_>>();
+            let profile_rx = profile_rx.clone();
+            let mut camera_throttle = Throttle::new();
+            let mut signal_throttle = Throttle::new();
             let update_pipuck_messages = updates
-                .filter_map(|(desc, update)| async move {
-                    match update {
-                        Ok(update) => {
-                            Some(DownMessage::Request(Uuid::new_v4(), FrontEndRequest::UpdatePiPuck(desc.id.clone(), update)))
-                        }
-                        Err(BroadcastStreamRecvError::Lagged(count)) => {
-                            log::warn!("Client missed {} messages for {}", count, desc);
-                            None
+                .filter_map(move |(desc, update)| {
+                    let forward = match &update {
+                        Ok(pipuck::Update::Camera { camera, .. }) => camera_throttle
+                            .permit((desc.id.clone(), camera.clone()), camera_interval(*profile_rx.borrow()).max(update_interval)),
+                        Ok(pipuck::Update::FernbedienungSignal(_)) => signal_throttle
+                            .permit((desc.id.clone(), "Fernbedienung"), update_interval),
+                        _ => true,
+                    };
+                    async move {
+                        match update {
+                            Ok(_) if !forward => None,
+                            Ok(update) => {
+                                Some(DownMessage::Request(Uuid::new_v4(), FrontEndRequest::UpdatePiPuck(desc.id.clone(), update)))
+                            }
+                            Err(BroadcastStreamRecvError::Lagged(count)) => {
+                                log::warn!("Client missed {} messages for {}", count, desc);
+                                None
+                            }
                         }
                     }
                 });
@@ -139,41 +450,392 @@ async fn handle_client(
             return;
         }
     };
-    /* subscribe to optitrack updates */
+    /* subscribe to tracking system updates */
     let (callback_tx, callback_rx) = oneshot::channel();
-    let optitrack_updates = optitrack_tx.send(optitrack::Action::Subscribe(callback_tx))
+    let tracking_updates = tracking_tx.send(tracking::Action::Subscribe(callback_tx))
         .map_err(|_| anyhow::anyhow!("Could not subscribe to tracking system updates"))
         .and_then(move |_| callback_rx
             .map_err(|_| anyhow::anyhow!("Could not subscribe to tracking system updates")));
-    let optitrack_stream = match optitrack_updates.await {
-        Ok(optitrack_updates) => {
-            BroadcastStream::new(optitrack_updates)
-                .filter_map(|item: Result<Vec<tracking_system::Update>, BroadcastStreamRecvError>| async move {
+    let tracking_stream = match tracking_updates.await {
+        Ok(tracking_updates) => {
+            let profile_rx = profile_rx.clone();
+            let mut tracking_system_throttle = Throttle::new();
+            BroadcastStream::new(tracking_updates)
+                .filter_map(move |item: Result<Vec<tracking_system::Update>, BroadcastStreamRecvError>| {
+                    let forward = tracking_system_throttle
+                        .permit((), tracking_system_interval(*profile_rx.borrow()).max(update_interval));
+                    async move {
+                        match item {
+                            Ok(_) if !forward => None,
+                            Ok(update) => {
+                                Some(DownMessage::Request(Uuid::new_v4(), FrontEndRequest::UpdateTrackingSystem(update)))
+                            }
+                            Err(BroadcastStreamRecvError::Lagged(count)) => {
+                                log::warn!("Client missed {} tracking system messages", count);
+                                None
+                            }
+                        }
+                    }
+                })
+                .map(|message| bincode::serialize(&message)
+                    .context("Could not serialize tracking system message"))
+                .map_ok(|encoded| warp::ws::Message::binary(encoded))
+        },
+        Err(error) => {
+            log::error!("Could not initialize client: {}", error);
+            return;
+        }
+    };
+    /* subscribe to ticket updates */
+    let (callback_tx, callback_rx) = oneshot::channel();
+    let ticket_updates = ticket_tx.send(ticket::Action::Subscribe(callback_tx))
+        .map_err(|_| anyhow::anyhow!("Could not subscribe to ticket updates"))
+        .and_then(move |_| callback_rx
+            .map_err(|_| anyhow::anyhow!("Could not subscribe to ticket updates")));
+    let ticket_stream = match ticket_updates.await {
+        Ok(ticket_updates) => {
+            subscription::updates(ticket_updates)
+                .filter_map(|item: Result<Vec<shared::ticket::Ticket>, BroadcastStreamRecvError>| async move {
                     match item {
-                        Ok(update) => {
-                            Some(DownMessage::Request(Uuid::new_v4(), FrontEndRequest::UpdateTrackingSystem(update)))
+                        Ok(tickets) => {
+                            Some(DownMessage::Request(Uuid::new_v4(), FrontEndRequest::UpdateTickets(tickets)))
                         }
                         Err(BroadcastStreamRecvError::Lagged(count)) => {
-                            log::warn!("Client missed {} tracking system messages", count);
+                            log::warn!("Client missed {} ticket messages", count);
                             None
                         }
                     }
                 })
                 .map(|message| bincode::serialize(&message)
-                    .context("Could not serialize tracking system message"))
-                .map_ok(|encoded| warp::ws::Message::binary(encoded))
+                    .context("Could not serialize ticket message"))
+                .map_ok(warp::ws::Message::binary)
+        },
+        Err(error) => {
+            log::error!("Could not initialize client: {}", error);
+            return;
+        }
+    };
+    /* subscribe to usage updates */
+    let (callback_tx, callback_rx) = oneshot::channel();
+    let usage_updates = usage_tx.send(usage::Action::Subscribe(callback_tx))
+        .map_err(|_| anyhow::anyhow!("Could not subscribe to usage updates"))
+        .and_then(move |_| callback_rx
+            .map_err(|_| anyhow::anyhow!("Could not subscribe to usage updates")));
+    let usage_stream = match usage_updates.await {
+        Ok(usage_updates) => {
+            subscription::updates(usage_updates)
+                .filter_map(|item: Result<Vec<shared::usage::Usage>, BroadcastStreamRecvError>| async move {
+                    match item {
+                        Ok(usage) => {
+                            Some(DownMessage::Request(Uuid::new_v4(), FrontEndRequest::UpdateUsage(usage)))
+                        }
+                        Err(BroadcastStreamRecvError::Lagged(count)) => {
+                            log::warn!("Client missed {} usage messages", count);
+                            None
+                        }
+                    }
+                })
+                .map(|message| bincode::serialize(&message)
+                    .context("Could not serialize usage message"))
+                .map_ok(warp::ws::Message::binary)
         },
         Err(error) => {
             log::error!("Could not initialize client: {}", error);
             return;
         }
     };
+    /* subscribe to the metadata of the robots */
+    let (callback_tx, callback_rx) = oneshot::channel();
+    let asset_updates = asset_tx.send(asset::Action::Subscribe(callback_tx))
+        .map_err(|_| anyhow::anyhow!("Could not subscribe to asset updates"))
+        .and_then(move |_| callback_rx
+            .map_err(|_| anyhow::anyhow!("Could not subscribe to asset updates")));
+    let asset_stream = match asset_updates.await {
+        Ok(asset_updates) => {
+            subscription::updates(asset_updates)
+                .filter_map(|item: Result<Vec<shared::asset::Asset>, BroadcastStreamRecvError>| async move {
+                    match item {
+                        Ok(assets) => {
+                            Some(DownMessage::Request(Uuid::new_v4(), FrontEndRequest::UpdateAssets(assets)))
+                        }
+                        Err(BroadcastStreamRecvError::Lagged(count)) => {
+                            log::warn!("Client missed {} asset messages", count);
+                            None
+                        }
+                    }
+                })
+                .map(|message| bincode::serialize(&message)
+                    .context("Could not serialize asset message"))
+                .map_ok(warp::ws::Message::binary)
+        },
+        Err(error) => {
+            log::error!("Could not initialize client: {}", error);
+            return;
+        }
+    };
+    /* subscribe to the audit trail */
+    let (callback_tx, callback_rx) = oneshot::channel();
+    let audit_updates = audit_tx.send(audit::Action::Subscribe(callback_tx))
+        .map_err(|_| anyhow::anyhow!("Could not subscribe to audit trail"))
+        .and_then(move |_| callback_rx
+            .map_err(|_| anyhow::anyhow!("Could not subscribe to audit trail")));
+    let audit_stream = match audit_updates.await {
+        Ok(audit_updates) => {
+            subscription::updates(audit_updates)
+                .filter_map(|item: Result<shared::audit::Entry, BroadcastStreamRecvError>| async move {
+                    match item {
+                        Ok(entry) => {
+                            Some(DownMessage::Request(Uuid::new_v4(), FrontEndRequest::AddAuditEntry(entry)))
+                        }
+                        Err(BroadcastStreamRecvError::Lagged(count)) => {
+                            log::warn!("Client missed {} audit entries", count);
+                            None
+                        }
+                    }
+                })
+                .map(|message| bincode::serialize(&message)
+                    .context("Could not serialize audit message"))
+                .map_ok(warp::ws::Message::binary)
+        },
+        Err(error) => {
+            log::error!("Could not initialize client: {}", error);
+            return;
+        }
+    };
+    /* subscribe to changes of the client that has control of the arena */
+    let (callback_tx, callback_rx) = oneshot::channel();
+    let control_updates = arena_tx.send(arena::Action::SubscribeControl(callback_tx))
+        .map_err(|_| anyhow::anyhow!("Could not subscribe to control updates"))
+        .and_then(move |_| callback_rx
+            .map_err(|_| anyhow::anyhow!("Could not subscribe to control updates")));
+    let control_stream = match control_updates.await {
+        Ok(control_updates) => {
+            let id = client.id;
+            WatchStream::new(control_updates)
+                .map(move |controller| match controller {
+                    Some(controller) if controller.id == id => control::Owner::This,
+                    Some(controller) => control::Owner::Other(controller.name),
+                    None => control::Owner::Nobody,
+                })
+                .map(|owner| DownMessage::Request(Uuid::new_v4(), FrontEndRequest::UpdateControl(owner)))
+                .map(|message| bincode::serialize(&message)
+                    .context("Could not serialize control message"))
+                .map_ok(warp::ws::Message::binary)
+        },
+        Err(error) => {
+            log::error!("Could not initialize client: {}", error);
+            return;
+        }
+    };
+    /* subscribe to changes of the robots that are quarantined */
+    let (callback_tx, callback_rx) = oneshot::channel();
+    let quarantined_updates = arena_tx.send(arena::Action::SubscribeQuarantined(callback_tx))
+        .map_err(|_| anyhow::anyhow!("Could not subscribe to quarantine updates"))
+        .and_then(move |_| callback_rx
+            .map_err(|_| anyhow::anyhow!("Could not subscribe to quarantine updates")));
+    let quarantined_stream = match quarantined_updates.await {
+        Ok(quarantined_updates) => {
+            WatchStream::new(quarantined_updates)
+                .map(|quarantined| DownMessage::Request(Uuid::new_v4(), FrontEndRequest::UpdateQuarantined(quarantined)))
+                .map(|message| bincode::serialize(&message)
+                    .context("Could not serialize quarantine message"))
+                .map_ok(warp::ws::Message::binary)
+        },
+        Err(error) => {
+            log::error!("Could not initialize client: {}", error);
+            return;
+        }
+    };
+    /* subscribe to the health of the robots */
+    let (callback_tx, callback_rx) = oneshot::channel();
+    let health_updates = arena_tx.send(arena::Action::SubscribeHealth(callback_tx))
+        .map_err(|_| anyhow::anyhow!("Could not subscribe to health updates"))
+        .and_then(move |_| callback_rx
+            .map_err(|_| anyhow::anyhow!("Could not subscribe to health updates")));
+    let health_stream = match health_updates.await {
+        Ok(health_updates) => {
+            WatchStream::new(health_updates)
+                .map(|health| DownMessage::Request(Uuid::new_v4(), FrontEndRequest::UpdateHealth(health)))
+                .map(|message| bincode::serialize(&message)
+                    .context("Could not serialize health message"))
+                .map_ok(warp::ws::Message::binary)
+        },
+        Err(error) => {
+            log::error!("Could not initialize client: {}", error);
+            return;
+        }
+    };
+    /* subscribe to the messages forwarded by the router that match a schema */
+    let decoded_stream = match subscribe_decoded_messages(&arena_tx).await {
+        Ok(Some((messages, schemas))) => {
+            let mut decoded_throttle = Throttle::new();
+            BroadcastStream::new(messages)
+                .filter_map(move |message| {
+                    let decoded = match message {
+                        Ok((_, message)) => schemas.decode(&message)
+                            .filter(|decoded| decoded_throttle
+                                .permit((decoded.schema.clone(), decoded.from.clone()), update_interval)),
+                        /* the missed messages are replaced by the next message from the same robot */
+                        Err(BroadcastStreamRecvError::Lagged(_)) => None,
+                    };
+                    futures::future::ready(decoded)
+                })
+                .left_stream()
+        },
+        /* without schemas, there is nothing to decode */
+        Ok(None) => stream::pending().right_stream(),
+        Err(error) => {
+            log::error!("Could not initialize client: {}", error);
+            return;
+        }
+    }
+        .map(|decoded| DownMessage::Request(Uuid::new_v4(), FrontEndRequest::AddDecodedMessage(decoded)))
+        .map(|message| bincode::serialize(&message)
+            .context("Could not serialize decoded message"))
+        .map_ok(warp::ws::Message::binary);
+    /* stream the messages forwarded by the router while the client inspects the router */
+    let inspector_stream = router_inspector(arena_tx.clone(), inspect_rx)
+        .map(|message| DownMessage::Request(Uuid::new_v4(), FrontEndRequest::AddRouterMessage(message)))
+        .map(|message| bincode::serialize(&message)
+            .context("Could not serialize router message"))
+        .map_ok(warp::ws::Message::binary);
+    /* subscribe to the events reported by the arena */
+    let (callback_tx, callback_rx) = oneshot::channel();
+    let event_updates = arena_tx.send(arena::Action::SubscribeEvents(callback_tx))
+        .map_err(|_| anyhow::anyhow!("Could not subscribe to arena events"))
+        .and_then(move |_| callback_rx
+            .map_err(|_| anyhow::anyhow!("Could not subscribe to arena events")));
+    let event_stream = match event_updates.await {
+        Ok(event_updates) => {
+            BroadcastStream::new(event_updates)
+                .filter_map(|item: Result<shared::event::Event, BroadcastStreamRecvError>| async move {
+                    match item {
+                        Ok(event) => {
+                            Some(DownMessage::Request(Uuid::new_v4(), FrontEndRequest::AddEvent(event)))
+                        }
+                        Err(BroadcastStreamRecvError::Lagged(count)) => {
+                            log::warn!("Client missed {} arena events", count);
+                            None
+                        }
+                    }
+                })
+                .map(|message| bincode::serialize(&message)
+                    .context("Could not serialize event message"))
+                .map_ok(warp::ws::Message::binary)
+        },
+        Err(error) => {
+            log::error!("Could not initialize client: {}", error);
+            return;
+        }
+    };
+    /* stream the log of the supervisor, lagging is reported to the client instead of being logged since
+       the warning would be sent to every client and could cause other clients to lag as well */
+    let log_stream = BroadcastStream::new(log_records_rx)
+        .map(|item: Result<logging::Record, BroadcastStreamRecvError>| match item {
+            Ok(record) => record,
+            Err(BroadcastStreamRecvError::Lagged(count)) => logging::Record {
+                timestamp: chrono::Local::now().format("%Y-%m-%d %H:%M:%S%.3f").to_string(),
+                level: logging::Level::Warn,
+                subsystem: String::from("webui"),
+                message: format!("{} log records were not sent to this client", count),
+            },
+        })
+        .map(|record| DownMessage::Request(Uuid::new_v4(), FrontEndRequest::AddLogRecord(record)))
+        .map(|message| bincode::serialize(&message)
+            .context("Could not serialize log message"));
+    /* the groups of robots do not change while the supervisor is running */
+    let (callback_tx, callback_rx) = oneshot::channel();
+    let groups = arena_tx.send(arena::Action::GetGroups(callback_tx))
+        .map_err(|_| anyhow::anyhow!("Could not get groups"))
+        .and_then(move |_| callback_rx
+            .map_err(|_| anyhow::anyhow!("Could not get groups")));
+    let groups = match groups.await {
+        Ok(groups) => groups,
+        Err(error) => {
+            log::error!("Could not initialize client: {}", error);
+            return;
+        }
+    };
+    /* experiment updates that are addressed to this client only */
+    let (experiment_updates_tx, experiment_updates_rx) = mpsc::channel(8);
+    let experiment_stream = ReceiverStream::new(experiment_updates_rx)
+        .map(|update| DownMessage::Request(Uuid::new_v4(), FrontEndRequest::UpdateExperiment(update)))
+        .map(|message| bincode::serialize(&message)
+            .context("Could not serialize experiment message"))
+        .map_ok(warp::ws::Message::binary);
+    /* periodic snapshots of the arena for the map */
+    let (map_snapshots_tx, map_snapshots_rx) = mpsc::channel(2);
+    tokio::spawn({
+        let arena_tx = arena_tx.clone();
+        let tracking_tx = tracking_tx.clone();
+        async move {
+            if let Err(error) = map_snapshots(map_config, arena_tx, tracking_tx, map_snapshots_tx).await {
+                log::warn!("Could not generate map snapshots: {}", error);
+            }
+        }
+    });
+    let map_stream = ReceiverStream::new(map_snapshots_rx)
+        .map(|snapshot| DownMessage::Request(Uuid::new_v4(), FrontEndRequest::UpdateMap(snapshot)))
+        .map(|message| bincode::serialize(&message)
+            .context("Could not serialize map message"))
+        .map_ok(warp::ws::Message::binary);
+    /* notify the client when the supervisor is shutting down */
+    let shutdown_stream = WatchStream::new(shutdown_rx)
+        .filter(|shutting_down| futures::future::ready(*shutting_down))
+        .map(|_| DownMessage::Request(Uuid::new_v4(), FrontEndRequest::Shutdown))
+        .map(|message| bincode::serialize(&message)
+            .context("Could not serialize shutdown message"))
+        .map_ok(warp::ws::Message::binary);
+    /* notify the client whenever rehearsal mode is enabled or disabled */
+    let rehearsal_stream = WatchStream::new(rehearsal_rx.clone())
+        .map(|rehearsal| DownMessage::Request(Uuid::new_v4(), FrontEndRequest::UpdateRehearsal(rehearsal)))
+        .map(|message| bincode::serialize(&message)
+            .context("Could not serialize rehearsal message"))
+        .map_ok(warp::ws::Message::binary);
     /* response to client requests and forward updates to client */
-    tokio::pin!(optitrack_stream);
+    tokio::pin!(shutdown_stream);
+    tokio::pin!(rehearsal_stream);
+    tokio::pin!(control_stream);
+    tokio::pin!(quarantined_stream);
+    tokio::pin!(health_stream);
+    tokio::pin!(decoded_stream);
+    tokio::pin!(inspector_stream);
+    tokio::pin!(event_stream);
+    tokio::pin!(log_stream);
+    tokio::pin!(map_stream);
+    tokio::pin!(ticket_stream);
+    tokio::pin!(usage_stream);
+    tokio::pin!(asset_stream);
+    tokio::pin!(audit_stream);
+    tokio::pin!(experiment_stream);
+    tokio::pin!(tracking_stream);
     tokio::pin!(builderbot_updates);
     tokio::pin!(pipuck_updates);
     tokio::pin!(drone_updates);
     let (mut websocket_tx, mut websocket_rx) = ws.split();
+    /* the protocol version is sent first so that an outdated user interface can ask to be refreshed */
+    match bincode::serialize(&DownMessage::Hello(shared::PROTOCOL_VERSION)) {
+        Ok(encoded) => if let Err(error) = websocket_tx.send(warp::ws::Message::binary(encoded)).await {
+            log::error!("Could not send protocol version to client: {}", error);
+        },
+        Err(error) => log::error!("Could not serialize protocol version message: {}", error),
+    }
+    /* the protocol version of the client, requests are only handled once it is known to match */
+    let mut client_version: Option<u32> = None;
+    let message = DownMessage::Request(Uuid::new_v4(), FrontEndRequest::UpdateGroups(groups));
+    match bincode::serialize(&message) {
+        Ok(encoded) => if let Err(error) = websocket_tx.send(warp::ws::Message::binary(encoded)).await {
+            log::error!("Could not send groups to client: {}", error);
+        },
+        Err(error) => log::error!("Could not serialize groups message: {}", error),
+    }
+    let message = DownMessage::Request(Uuid::new_v4(), FrontEndRequest::UpdateArenas(arena_names.to_vec()));
+    match bincode::serialize(&message) {
+        Ok(encoded) => if let Err(error) = websocket_tx.send(warp::ws::Message::binary(encoded)).await {
+            log::error!("Could not send arenas to client: {}", error);
+        },
+        Err(error) => log::error!("Could not serialize arenas message: {}", error),
+    }
     loop {
         tokio::select! {
             /* handle requests from client */
@@ -184,20 +846,92 @@ async fn handle_client(
                     }
                     match bincode::deserialize::<UpMessage>(message.as_bytes()) {
                         Ok(message) => match message {
+                            UpMessage::Hello(version) => {
+                                if version != shared::PROTOCOL_VERSION {
+                                    log::warn!("Client {} uses protocol version {} instead of {}, requests will be refused",
+                                        client.name, version, shared::PROTOCOL_VERSION);
+                                }
+                                client_version = Some(version);
+                            },
+                            /* requests from a client with a different protocol may have been decoded incorrectly */
+                            UpMessage::Request(uuid, _) if client_version != Some(shared::PROTOCOL_VERSION) => {
+                                log::warn!("Refused request {} from client {} with an unknown or different protocol version",
+                                    uuid, client.name);
+                            },
                             UpMessage::Request(uuid, request) => {
+                                let audited = describe(&request);
                                 let result = match request {
-                                    BackEndRequest::BuilderBotRequest(id, request) =>  
-                                        handle_builderbot_request(&arena_tx, id, request).await,
-                                    BackEndRequest::DroneRequest(id, request) => 
-                                        handle_drone_request(&arena_tx, id, request).await,
-                                    BackEndRequest::PiPuckRequest(id, request) =>  
-                                        handle_pipuck_request(&arena_tx, id, request).await,
-                                    BackEndRequest::ExperimentRequest(request) => 
-                                        handle_experiment_request(&arena_tx, request).await,
+                                    BackEndRequest::SetUpdateProfile(profile) => {
+                                        log::info!("Client requested the {:?} update profile", profile);
+                                        profile_tx.send(profile)
+                                            .map_err(|_| anyhow::anyhow!("Could not set update profile"))
+                                    },
+                                    BackEndRequest::ControlRequest(request) =>
+                                        handle_control_request(&arena_tx, &client, request).await,
+                                    BackEndRequest::InspectRouter(enabled) => inspect_tx.send(enabled)
+                                        .map_err(|_| anyhow::anyhow!("Could not inspect the message router")),
+                                    /* telemetry only reads the recent values and does not require control */
+                                    BackEndRequest::TelemetryRequest(robot, minutes) => {
+                                        let samples = chart_samples(*profile_rx.borrow());
+                                        match handle_telemetry_request(&arena_tx, robot, minutes, samples).await {
+                                            Ok(message) => websocket_tx.send(message).await
+                                                .context("Could not send telemetry to client"),
+                                            Err(error) => Err(error),
+                                        }
+                                    },
+                                    /* the remaining requests are only accepted from the client that has control */
+                                    request => match acquire_control(&arena_tx, &client, false).await {
+                                        Ok(_) => match request {
+                                            BackEndRequest::BuilderBotRequest(id, request) =>  
+                                                handle_builderbot_request(&arena_tx, id, request).await,
+                                            BackEndRequest::DroneRequest(id, request) => 
+                                                handle_drone_request(&arena_tx, id, request).await,
+                                            BackEndRequest::PiPuckRequest(id, request) =>  
+                                                handle_pipuck_request(&arena_tx, id, request).await,
+                                            BackEndRequest::PiPuckPowerRequest(ids, power) =>
+                                                handle_pipuck_power_request(&arena_tx, ids, power).await,
+                                            BackEndRequest::LandAllRequest(landing) =>
+                                                handle_land_all_request(&arena_tx, landing).await,
+                                            BackEndRequest::RecallPiPucksRequest =>
+                                                handle_recall_pipucks_request(&arena_tx).await,
+                                            BackEndRequest::ExperimentRequest(request) => 
+                                                handle_experiment_request(&arena_tx, &experiment_updates_tx, request).await,
+                                            BackEndRequest::GroupRequest(group, request) =>
+                                                handle_group_request(&arena_tx, group, request).await,
+                                            BackEndRequest::QuarantineRequest(id, quarantined) =>
+                                                handle_quarantine_request(&arena_tx, id, quarantined).await,
+                                            BackEndRequest::RunMaintenance =>
+                                                handle_maintenance_request(&arena_tx).await,
+                                            BackEndRequest::TicketRequest(request) =>
+                                                handle_ticket_request(&ticket_tx, request).await,
+                                            BackEndRequest::AssetRequest(asset) =>
+                                                handle_asset_request(&arena_tx, &asset_tx, asset).await,
+                                            BackEndRequest::SetRehearsal(enabled) =>
+                                                handle_rehearsal_request(&arena_tx, &rehearsal_tx, &client, enabled).await,
+                                            BackEndRequest::RouterRequest(request) =>
+                                                handle_router_request(&arena_tx, request).await,
+                                            BackEndRequest::SetUpdateProfile(_) |
+                                            BackEndRequest::InspectRouter(_) |
+                                            BackEndRequest::TelemetryRequest(..) |
+                                            BackEndRequest::ControlRequest(_) => unreachable!(),
+                                        },
+                                        Err(error) => Err(error),
+                                    },
                                 };
                                 if let Err(error) = result.as_ref() {
                                     log::warn!("Error processing request: {}", error);
                                 }
+                                if let Some((robot, action)) = audited {
+                                    let entry = shared::audit::Entry {
+                                        timestamp: chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+                                        operator: client.name.clone(),
+                                        arena: arena_name.clone(),
+                                        robot,
+                                        action,
+                                        error: result.as_ref().err().map(|error| format!("{:#}", error)),
+                                    };
+                                    record_audit_entry(&arena_tx, &audit_tx, entry).await;
+                                }
                                 let response = DownMessage::Response(uuid, result.map_err(|e| e.to_string()));
                                 match bincode::serialize(&response) {
                                     Ok(encoded) => {
@@ -214,16 +948,176 @@ async fn handle_client(
                             }
                         },
                         Err(_) => {
-                            log::warn!("Could not deserialize UpMessage");
+                            log::warn!("Could not deserialize UpMessage from client {}, the client may be outdated", client.name);
                         },
                     }
                 }
-                Err(error) => {
-                    log::warn!("{}", error);
+                Err(error) => {
+                    log::warn!("{}", error);
+                }
+            },
+            /* stream experiment updates to client */
+            Some(result) = experiment_stream.next() => {
+                match result {
+                    Ok(message) => {
+                        if let Err(error) = websocket_tx.send(message).await {
+                            log::error!("Could not send message to client: {}", error);
+                        }
+                    },
+                    Err(error) => log::error!("{}", error),
+                }
+            },
+            /* notify client of shutdown */
+            Some(result) = shutdown_stream.next() => {
+                match result {
+                    Ok(message) => {
+                        if let Err(error) = websocket_tx.send(message).await {
+                            log::error!("Could not send message to client: {}", error);
+                        }
+                    },
+                    Err(error) => log::error!("{}", error),
+                }
+            },
+            /* stream rehearsal mode to client */
+            Some(result) = rehearsal_stream.next() => {
+                match result {
+                    Ok(message) => {
+                        if let Err(error) = websocket_tx.send(message).await {
+                            log::error!("Could not send message to client: {}", error);
+                        }
+                    },
+                    Err(error) => log::error!("{}", error),
+                }
+            },
+            /* stream control updates to client */
+            Some(result) = control_stream.next() => {
+                match result {
+                    Ok(message) => {
+                        if let Err(error) = websocket_tx.send(message).await {
+                            log::error!("Could not send message to client: {}", error);
+                        }
+                    },
+                    Err(error) => log::error!("{}", error),
+                }
+            },
+            /* stream the quarantined robots to client */
+            Some(result) = quarantined_stream.next() => {
+                match result {
+                    Ok(message) => {
+                        if let Err(error) = websocket_tx.send(message).await {
+                            log::error!("Could not send message to client: {}", error);
+                        }
+                    },
+                    Err(error) => log::error!("{}", error),
+                }
+            },
+            /* stream the health of the robots to client */
+            Some(result) = health_stream.next() => {
+                match result {
+                    Ok(message) => {
+                        if let Err(error) = websocket_tx.send(message).await {
+                            log::error!("Could not send message to client: {}", error);
+                        }
+                    },
+                    Err(error) => log::error!("{}", error),
+                }
+            },
+            /* stream the decoded messages of the router to client */
+            Some(result) = decoded_stream.next() => {
+                match result {
+                    Ok(message) => {
+                        if let Err(error) = websocket_tx.send(message).await {
+                            log::error!("Could not send message to client: {}", error);
+                        }
+                    },
+                    Err(error) => log::error!("{}", error),
+                }
+            },
+            /* stream the inspected messages of the router to client */
+            Some(result) = inspector_stream.next() => {
+                match result {
+                    Ok(message) => {
+                        if let Err(error) = websocket_tx.send(message).await {
+                            log::error!("Could not send message to client: {}", error);
+                        }
+                    },
+                    Err(error) => log::error!("{}", error),
+                }
+            },
+            /* stream arena events to client */
+            Some(result) = event_stream.next() => {
+                match result {
+                    Ok(message) => {
+                        if let Err(error) = websocket_tx.send(message).await {
+                            log::error!("Could not send message to client: {}", error);
+                        }
+                    },
+                    Err(error) => log::error!("{}", error),
+                }
+            },
+            /* stream the log to client, errors are not logged since they would be sent to the client again */
+            Some(result) = log_stream.next() => {
+                if let Ok(encoded) = result {
+                    let _ = websocket_tx.send(warp::ws::Message::binary(encoded)).await;
+                }
+            },
+            /* stream map snapshots to client */
+            Some(result) = map_stream.next() => {
+                match result {
+                    Ok(message) => {
+                        if let Err(error) = websocket_tx.send(message).await {
+                            log::error!("Could not send message to client: {}", error);
+                        }
+                    },
+                    Err(error) => log::error!("{}", error),
+                }
+            },
+            /* stream ticket updates to client */
+            Some(result) = ticket_stream.next() => {
+                match result {
+                    Ok(message) => {
+                        if let Err(error) = websocket_tx.send(message).await {
+                            log::error!("Could not send message to client: {}", error);
+                        }
+                    },
+                    Err(error) => log::error!("{}", error),
+                }
+            },
+            /* stream usage updates to client */
+            Some(result) = usage_stream.next() => {
+                match result {
+                    Ok(message) => {
+                        if let Err(error) = websocket_tx.send(message).await {
+                            log::error!("Could not send message to client: {}", error);
+                        }
+                    },
+                    Err(error) => log::error!("{}", error),
+                }
+            },
+            /* stream the metadata of the robots to client */
+            Some(result) = asset_stream.next() => {
+                match result {
+                    Ok(message) => {
+                        if let Err(error) = websocket_tx.send(message).await {
+                            log::error!("Could not send message to client: {}", error);
+                        }
+                    },
+                    Err(error) => log::error!("{}", error),
+                }
+            },
+            /* stream the audit trail to client */
+            Some(result) = audit_stream.next() => {
+                match result {
+                    Ok(message) => {
+                        if let Err(error) = websocket_tx.send(message).await {
+                            log::error!("Could not send message to client: {}", error);
+                        }
+                    },
+                    Err(error) => log::error!("{}", error),
                 }
             },
-            /* stream optitrack updated to client */
-            Some(result) = optitrack_stream.next() => {
+            /* stream tracking system updates to client */
+            Some(result) = tracking_stream.next() => {
                 match result {
                     Ok(message) => {
                         if let Err(error) = websocket_tx.send(message).await {
@@ -266,11 +1160,265 @@ async fn handle_client(
             }
         }
     }
+    /* do not keep control of the arena once the client has disconnected */
+    let _ = arena_tx.send(arena::Action::ReleaseControl(client.id)).await;
+}
+
+/* the heading of a rigid body around the z axis from its orientation (w, x, y, z) */
+fn heading([w, x, y, z]: [f32; 4]) -> f32 {
+    (2.0 * (w * z + x * y)).atan2(1.0 - 2.0 * (y * y + z * z))
+}
+
+struct MapEntry {
+    kind: map::Kind,
+    optitrack_id: Option<i32>,
+    fernbedienung: bool,
+    xbee: bool,
+}
+
+impl MapEntry {
+    fn status(&self) -> map::Status {
+        match self.kind {
+            map::Kind::Drone => match (self.fernbedienung, self.xbee) {
+                (true, true) => map::Status::Connected,
+                (false, false) => map::Status::Disconnected,
+                _ => map::Status::PartiallyConnected,
+            },
+            _ => match self.fernbedienung {
+                true => map::Status::Connected,
+                false => map::Status::Disconnected,
+            }
+        }
+    }
+}
+
+/* track the connection status and pose of each robot and periodically send a snapshot to the client */
+async fn map_snapshots(
+    config: Arc<MapConfiguration>,
+    arena_tx: mpsc::Sender<arena::Action>,
+    tracking_tx: mpsc::Sender<tracking::Action>,
+    map_snapshots_tx: mpsc::Sender<map::Snapshot>
+) -> anyhow::Result<()> {
+    let mut builderbot_updates = subscribe_builderbot_updates(&arena_tx).await?;
+    let mut drone_updates = subscribe_drone_updates(&arena_tx).await?;
+    let mut pipuck_updates = subscribe_pipuck_updates(&arena_tx).await?;
+    let (callback_tx, callback_rx) = oneshot::channel();
+    tracking_tx.send(tracking::Action::Subscribe(callback_tx)).await
+        .map_err(|_| anyhow::anyhow!("Could not communicate with tracking system"))?;
+    let mut tracking_updates = callback_rx.await
+        .map(BroadcastStream::new)
+        .map_err(|_| anyhow::anyhow!("Could not subscribe to tracking system updates"))?;
+    let mut entries: HashMap<String, MapEntry> = HashMap::new();
+    for desc in builderbot_updates.keys() {
+        let entry = MapEntry { kind: map::Kind::BuilderBot, optitrack_id: desc.optitrack_id, fernbedienung: false, xbee: false };
+        entries.insert(desc.id.clone(), entry);
+    }
+    for desc in drone_updates.keys() {
+        let entry = MapEntry { kind: map::Kind::Drone, optitrack_id: desc.optitrack_id, fernbedienung: false, xbee: false };
+        entries.insert(desc.id.clone(), entry);
+    }
+    for desc in pipuck_updates.keys() {
+        let entry = MapEntry { kind: map::Kind::PiPuck, optitrack_id: desc.optitrack_id, fernbedienung: false, xbee: false };
+        entries.insert(desc.id.clone(), entry);
+    }
+    let mut poses: HashMap<i32, ([f32; 2], f32)> = HashMap::new();
+    let mut interval = tokio::time::interval(Duration::from_secs_f64(1.0 / config.rate));
+    loop {
+        tokio::select! {
+            Some((desc, update)) = builderbot_updates.next() => if let Some(entry) = entries.get_mut(&desc.id) {
+                match update {
+                    Ok(builderbot::Update::FernbedienungConnected(_)) => entry.fernbedienung = true,
+                    Ok(builderbot::Update::FernbedienungDisconnected) => entry.fernbedienung = false,
+                    _ => {}
+                }
+            },
+            Some((desc, update)) = drone_updates.next() => if let Some(entry) = entries.get_mut(&desc.id) {
+                match update {
+                    Ok(drone::Update::FernbedienungConnected(_)) => entry.fernbedienung = true,
+                    Ok(drone::Update::FernbedienungDisconnected) => entry.fernbedienung = false,
+                    Ok(drone::Update::XbeeConnected(_)) => entry.xbee = true,
+                    Ok(drone::Update::XbeeDisconnected) => entry.xbee = false,
+                    _ => {}
+                }
+            },
+            Some((desc, update)) = pipuck_updates.next() => if let Some(entry) = entries.get_mut(&desc.id) {
+                match update {
+                    Ok(pipuck::Update::FernbedienungConnected(_)) => entry.fernbedienung = true,
+                    Ok(pipuck::Update::FernbedienungDisconnected) => entry.fernbedienung = false,
+                    _ => {}
+                }
+            },
+            /* rigid bodies that are missing from the latest frame are no longer shown */
+            Some(frame) = tracking_updates.next() => if let Ok(updates) = frame {
+                poses = updates.into_iter()
+                    .map(|update| {
+                        let [x, y, _] = update.position;
+                        (update.id, ([x, y], heading(update.orientation)))
+                    })
+                    .collect();
+            },
+            _ = interval.tick() => {
+                /* the members of a federation do not keep trajectories and have no trails */
+                let mut trajectories = api::trajectories(&arena_tx).await.unwrap_or_default();
+                let mut robots = entries.iter()
+                    .map(|(id, entry)| map::Robot {
+                        id: id.clone(),
+                        kind: entry.kind,
+                        status: entry.status(),
+                        pose: entry.optitrack_id.and_then(|optitrack_id| poses.get(&optitrack_id).cloned()),
+                        trail: trajectories.remove(id)
+                            .map_or_else(Vec::new, |samples| samples.into_iter()
+                                .map(|sample| [sample.position[0], sample.position[1]])
+                                .collect()),
+                    })
+                    .collect::<Vec<_>>();
+                robots.sort_by(|a, b| a.id.cmp(&b.id));
+                let snapshot = map::Snapshot { bounds: config.bounds, robots };
+                /* stop once the client has disconnected */
+                if map_snapshots_tx.send(snapshot).await.is_err() {
+                    break;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn subscribe_builderbot(
+    arena_tx: mpsc::Sender<arena::Action>,
+    id: String
+) -> anyhow::Result<subscription::Subscription<builderbot::Update>> {
+    let (callback_tx, callback_rx) = oneshot::channel();
+    let action = builderbot::Action::Subscribe(callback_tx);
+    arena_tx.send(arena::Action::ForwardBuilderBotAction(id, action)).await
+        .map_err(|_| anyhow::anyhow!("Could not communicate with BuilderBot"))?;
+    callback_rx.await
+        .context("Could not subscribe to BuilderBot updates")
+}
+
+async fn subscribe_builderbot_frames(
+    arena_tx: mpsc::Sender<arena::Action>,
+    id: String
+) -> anyhow::Result<robot::FrameReceivers> {
+    let (callback_tx, callback_rx) = oneshot::channel();
+    let action = builderbot::Action::SubscribeFrames(callback_tx);
+    arena_tx.send(arena::Action::ForwardBuilderBotAction(id, action)).await
+        .map_err(|_| anyhow::anyhow!("Could not communicate with BuilderBot"))?;
+    callback_rx.await
+        .context("Could not subscribe to BuilderBot camera frames")
+}
+
+async fn router_schemas(
+    arena_tx: &mpsc::Sender<arena::Action>
+) -> anyhow::Result<Arc<router::schema::Registry>> {
+    let (callback_tx, callback_rx) = oneshot::channel();
+    arena_tx.send(arena::Action::GetSchemas(callback_tx)).await
+        .map_err(|_| anyhow::anyhow!("Could not communicate with arena"))?;
+    callback_rx.await
+        .map_err(|_| anyhow::anyhow!("Could not get message schemas"))
+}
+
+async fn subscribe_router(
+    arena_tx: &mpsc::Sender<arena::Action>
+) -> anyhow::Result<broadcast::Receiver<(SocketAddr, Arc<router::LuaType>)>> {
+    let (callback_tx, callback_rx) = oneshot::channel();
+    arena_tx.send(arena::Action::SubscribeRouter(callback_tx)).await
+        .map_err(|_| anyhow::anyhow!("Could not communicate with arena"))?;
+    callback_rx.await
+        .map_err(|_| anyhow::anyhow!("Could not subscribe to message router"))
+}
+
+/* the messages forwarded by the router and the schemas to decode them, if any schemas are registered */
+async fn subscribe_decoded_messages(
+    arena_tx: &mpsc::Sender<arena::Action>
+) -> anyhow::Result<Option<(broadcast::Receiver<(SocketAddr, Arc<router::LuaType>)>, Arc<router::schema::Registry>)>> {
+    let schemas = router_schemas(arena_tx).await?;
+    if schemas.is_empty() {
+        return Ok(None);
+    }
+    let messages = subscribe_router(arena_tx).await?;
+    Ok(Some((messages, schemas)))
+}
+
+/* the number of characters of a message that are shown by the inspector */
+const PREVIEW_LENGTH: usize = 200;
+
+fn inspected(message: &router::LuaType, schemas: &router::schema::Registry) -> shared::router::Message {
+    let name = |key| match router::schema::field(message, key) {
+        Some(router::LuaType::String(name)) => name.clone(),
+        _ => String::from("nil"),
+    };
+    /* messages that match a schema are shown with labeled fields */
+    let preview = match schemas.decode(message) {
+        Some(decoded) => {
+            let fields = decoded.fields.iter()
+                .map(|field| format!("{} = {}", field.label, field.value.as_deref().unwrap_or("missing")))
+                .collect::<Vec<_>>();
+            format!("{}: {}", decoded.schema, fields.join(", "))
+        },
+        None => router::schema::format(message),
+    };
+    shared::router::Message {
+        timestamp: chrono::Local::now().format("%H:%M:%S%.3f").to_string(),
+        from: name("fromS"),
+        to: name("toS"),
+        size: router::encoded_len(message),
+        preview: match preview.chars().count() > PREVIEW_LENGTH {
+            true => format!("{}...", preview.chars().take(PREVIEW_LENGTH).collect::<String>()),
+            false => preview,
+        },
+    }
+}
+
+/* the messages forwarded by the router, the router is only subscribed to while inspect_rx is true so
+   that clients that do not inspect the router do not receive every message */
+fn router_inspector(
+    arena_tx: mpsc::Sender<arena::Action>,
+    mut inspect_rx: watch::Receiver<bool>,
+) -> impl futures::Stream<Item = shared::router::Message> {
+    async_stream::stream! {
+        loop {
+            while !*inspect_rx.borrow() {
+                if inspect_rx.changed().await.is_err() {
+                    return;
+                }
+            }
+            let subscription = futures::future::try_join(router_schemas(&arena_tx), subscribe_router(&arena_tx));
+            let (schemas, messages) = match subscription.await {
+                Ok(subscription) => subscription,
+                Err(error) => {
+                    log::warn!("Could not inspect the message router: {}", error);
+                    return;
+                }
+            };
+            let mut messages = BroadcastStream::new(messages);
+            loop {
+                let message = tokio::select! {
+                    message = messages.next() => match message {
+                        Some(Ok((_, message))) => Some(inspected(&message, &schemas)),
+                        Some(Err(BroadcastStreamRecvError::Lagged(count))) => {
+                            log::warn!("Router inspector missed {} messages", count);
+                            None
+                        },
+                        None => return,
+                    },
+                    result = inspect_rx.changed() => match result {
+                        Ok(_) if *inspect_rx.borrow() => None,
+                        Ok(_) => break,
+                        Err(_) => return,
+                    },
+                };
+                if let Some(message) = message {
+                    yield message;
+                }
+            }
+        }
+    }
 }
 
-async fn subscribe_builderbot_updates(
+pub async fn subscribe_builderbot_updates(
     arena_tx: &mpsc::Sender<arena::Action>
-) -> anyhow::Result<StreamMap<Arc<builderbot::Descriptor>, BroadcastStream<builderbot::Update>>> {
+) -> anyhow::Result<StreamMap<Arc<builderbot::Descriptor>, subscription::Updates<builderbot::Update>>> {
     let (callback_tx, callback_rx) = oneshot::channel();
     let update_streams = arena_tx.send(arena::Action::GetBuilderBotDescriptors(callback_tx))
         .map_err(|_| anyhow::anyhow!("Could not communicate with BuilderBot"))
@@ -278,13 +1426,17 @@ async fn subscribe_builderbot_updates(
             .map(|result| result.context("Could not get BuilderBot descriptors")))
         .and_then(|builderbot_descs| builderbot_descs.into_iter()
             .map(|builderbot_desc| {
-                let (callback_tx, callback_rx) = oneshot::channel();
-                let action = builderbot::Action::Subscribe(callback_tx);
-                arena_tx.send(arena::Action::ForwardBuilderBotAction(builderbot_desc.id.clone(), action))
-                    .map_err(|_| anyhow::anyhow!("Could not communicate with BuilderBot"))
-                    .and_then(|_| callback_rx
-                        .map(|result| result.context("Could not subscribe to BuilderBot updates"))
-                        .map_ok(|receiver| (builderbot_desc, BroadcastStream::new(receiver))))
+                let (arena_tx, id) = (arena_tx.clone(), builderbot_desc.id.clone());
+                let subscribe = subscribe_builderbot(arena_tx.clone(), id.clone());
+                let subscribe_frames = subscribe_builderbot_frames(arena_tx.clone(), id.clone());
+                futures::future::try_join(subscribe, subscribe_frames)
+                    .map_ok(move |(updates, frame_rxs)| {
+                        /* subscribe again if the updates are not consumed fast enough */
+                        let resubscribe = move || subscribe_builderbot(arena_tx.clone(), id.clone());
+                        let updates = subscription::resubscribing(updates, resubscribe);
+                        let frames = robot::camera_updates(frame_rxs, |camera, result| builderbot::Update::Camera { camera, result });
+                        (builderbot_desc, stream::select(updates, frames).boxed())
+                    })
             })
             .collect::<FuturesUnordered<_>>()
             .try_collect::<Vec<_>>()
@@ -297,9 +1449,33 @@ async fn subscribe_builderbot_updates(
     Ok(builderbot_update_stream_map)
 }
 
-async fn subscribe_drone_updates(
+async fn subscribe_drone(
+    arena_tx: mpsc::Sender<arena::Action>,
+    id: String
+) -> anyhow::Result<subscription::Subscription<drone::Update>> {
+    let (callback_tx, callback_rx) = oneshot::channel();
+    let action = drone::Action::Subscribe(callback_tx);
+    arena_tx.send(arena::Action::ForwardDroneAction(id, action)).await
+        .map_err(|_| anyhow::anyhow!("Could not communicate with drone"))?;
+    callback_rx.await
+        .context("Could not subscribe to drone updates")
+}
+
+async fn subscribe_drone_frames(
+    arena_tx: mpsc::Sender<arena::Action>,
+    id: String
+) -> anyhow::Result<robot::FrameReceivers> {
+    let (callback_tx, callback_rx) = oneshot::channel();
+    let action = drone::Action::SubscribeFrames(callback_tx);
+    arena_tx.send(arena::Action::ForwardDroneAction(id, action)).await
+        .map_err(|_| anyhow::anyhow!("Could not communicate with drone"))?;
+    callback_rx.await
+        .context("Could not subscribe to drone camera frames")
+}
+
+pub async fn subscribe_drone_updates(
     arena_tx: &mpsc::Sender<arena::Action>
-) -> anyhow::Result<StreamMap<Arc<drone::Descriptor>, BroadcastStream<drone::Update>>> {
+) -> anyhow::Result<StreamMap<Arc<drone::Descriptor>, subscription::Updates<drone::Update>>> {
     let (callback_tx, callback_rx) = oneshot::channel();
     let update_streams = arena_tx.send(arena::Action::GetDroneDescriptors(callback_tx))
         .map_err(|_| anyhow::anyhow!("Could not communicate with drone"))
@@ -307,13 +1483,17 @@ async fn subscribe_drone_updates(
             .map(|result| result.context("Could not get drone descriptors")))
         .and_then(|drone_descs| drone_descs.into_iter()
             .map(|drone_desc| {
-                let (callback_tx, callback_rx) = oneshot::channel();
-                let action = drone::Action::Subscribe(callback_tx);
-                arena_tx.send(arena::Action::ForwardDroneAction(drone_desc.id.clone(), action))
-                    .map_err(|_| anyhow::anyhow!("Could not communicate with drone"))
-                    .and_then(|_| callback_rx
-                        .map(|result| result.context("Could not subscribe to drone updates"))
-                        .map_ok(|receiver| (drone_desc, BroadcastStream::new(receiver))))
+                let (arena_tx, id) = (arena_tx.clone(), drone_desc.id.clone());
+                let subscribe = subscribe_drone(arena_tx.clone(), id.clone());
+                let subscribe_frames = subscribe_drone_frames(arena_tx.clone(), id.clone());
+                futures::future::try_join(subscribe, subscribe_frames)
+                    .map_ok(move |(updates, frame_rxs)| {
+                        /* subscribe again if the updates are not consumed fast enough */
+                        let resubscribe = move || subscribe_drone(arena_tx.clone(), id.clone());
+                        let updates = subscription::resubscribing(updates, resubscribe);
+                        let frames = robot::camera_updates(frame_rxs, |camera, result| drone::Update::Camera { camera, result });
+                        (drone_desc, stream::select(updates, frames).boxed())
+                    })
             })
             .collect::<FuturesUnordered<_>>()
             .try_collect::<Vec<_>>()
@@ -325,9 +1505,33 @@ async fn subscribe_drone_updates(
     Ok(drone_update_stream_map)
 }
 
-async fn subscribe_pipuck_updates(
+async fn subscribe_pipuck(
+    arena_tx: mpsc::Sender<arena::Action>,
+    id: String
+) -> anyhow::Result<subscription::Subscription<pipuck::Update>> {
+    let (callback_tx, callback_rx) = oneshot::channel();
+    let action = pipuck::Action::Subscribe(callback_tx);
+    arena_tx.send(arena::Action::ForwardPiPuckAction(id, action)).await
+        .map_err(|_| anyhow::anyhow!("Could not communicate with Pi-Puck"))?;
+    callback_rx.await
+        .context("Could not subscribe to Pi-Puck updates")
+}
+
+async fn subscribe_pipuck_frames(
+    arena_tx: mpsc::Sender<arena::Action>,
+    id: String
+) -> anyhow::Result<robot::FrameReceivers> {
+    let (callback_tx, callback_rx) = oneshot::channel();
+    let action = pipuck::Action::SubscribeFrames(callback_tx);
+    arena_tx.send(arena::Action::ForwardPiPuckAction(id, action)).await
+        .map_err(|_| anyhow::anyhow!("Could not communicate with Pi-Puck"))?;
+    callback_rx.await
+        .context("Could not subscribe to Pi-Puck camera frames")
+}
+
+pub async fn subscribe_pipuck_updates(
     arena_tx: &mpsc::Sender<arena::Action>
-) -> anyhow::Result<StreamMap<Arc<pipuck::Descriptor>, BroadcastStream<pipuck::Update>>> {
+) -> anyhow::Result<StreamMap<Arc<pipuck::Descriptor>, subscription::Updates<pipuck::Update>>> {
     let (callback_tx, callback_rx) = oneshot::channel();
     let update_streams = arena_tx.send(arena::Action::GetPiPuckDescriptors(callback_tx))
         .map_err(|_| anyhow::anyhow!("Could not communicate with Pi-Puck"))
@@ -335,13 +1539,17 @@ async fn subscribe_pipuck_updates(
             .map(|result| result.context("Could not get Pi-Puck descriptors")))
         .and_then(|pipuck_descs| pipuck_descs.into_iter()
             .map(|pipuck_desc| {
-                let (callback_tx, callback_rx) = oneshot::channel();
-                let action = pipuck::Action::Subscribe(callback_tx);
-                arena_tx.send(arena::Action::ForwardPiPuckAction(pipuck_desc.id.clone(), action))
-                    .map_err(|_| anyhow::anyhow!("Could not communicate with Pi-Puck"))
-                    .and_then(|_| callback_rx
-                        .map(|result| result.context("Could not subscribe to Pi-Puck updates"))
-                        .map_ok(|receiver| (pipuck_desc, BroadcastStream::new(receiver))))
+                let (arena_tx, id) = (arena_tx.clone(), pipuck_desc.id.clone());
+                let subscribe = subscribe_pipuck(arena_tx.clone(), id.clone());
+                let subscribe_frames = subscribe_pipuck_frames(arena_tx.clone(), id.clone());
+                futures::future::try_join(subscribe, subscribe_frames)
+                    .map_ok(move |(updates, frame_rxs)| {
+                        /* subscribe again if the updates are not consumed fast enough */
+                        let resubscribe = move || subscribe_pipuck(arena_tx.clone(), id.clone());
+                        let updates = subscription::resubscribing(updates, resubscribe);
+                        let frames = robot::camera_updates(frame_rxs, |camera, result| pipuck::Update::Camera { camera, result });
+                        (pipuck_desc, stream::select(updates, frames).boxed())
+                    })
             })
             .collect::<FuturesUnordered<_>>()
             .try_collect::<Vec<_>>()
@@ -372,6 +1580,8 @@ async fn handle_builderbot_request(
             Action::ExecuteFernbedienungAction(callback_tx, FernbedienungAction::Bash(TerminalAction::Run(command))),
         Request::CameraStreamEnable(on) => 
             Action::ExecuteFernbedienungAction(callback_tx, FernbedienungAction::SetCameraStream(on)),
+        Request::CameraDeviceStreamEnable(camera, on) => 
+            Action::ExecuteFernbedienungAction(callback_tx, FernbedienungAction::SetCameraDeviceStream(camera, on)),
         Request::Identify => 
             Action::ExecuteFernbedienungAction(callback_tx, FernbedienungAction::Identify),
         Request::DuoVeroHalt => 
@@ -384,6 +1594,15 @@ async fn handle_builderbot_request(
     callback_rx.await.map_err(|_| anyhow::anyhow!("No response from arena"))?
 }
 
+/* AT commands consist of two upper case letters or digits, e.g., PL for the power level */
+fn at_command(parameter: &str) -> anyhow::Result<[u8; 2]> {
+    let parameter = parameter.trim().to_ascii_uppercase();
+    match parameter.as_bytes() {
+        &[a, b] if a.is_ascii_alphanumeric() && b.is_ascii_alphanumeric() => Ok([a, b]),
+        _ => Err(anyhow::anyhow!("\"{}\" is not a valid AT command", parameter)),
+    }
+}
+
 async fn handle_drone_request(
     arena_tx: &mpsc::Sender<arena::Action>,
     id: String,
@@ -402,6 +1621,8 @@ async fn handle_drone_request(
             Action::ExecuteFernbedienungAction(callback_tx, FernbedienungAction::Bash(TerminalAction::Run(command))),
         Request::CameraStreamEnable(on) => 
             Action::ExecuteFernbedienungAction(callback_tx, FernbedienungAction::SetCameraStream(on)),
+        Request::CameraDeviceStreamEnable(camera, on) => 
+            Action::ExecuteFernbedienungAction(callback_tx, FernbedienungAction::SetCameraDeviceStream(camera, on)),
         Request::Identify => 
             Action::ExecuteFernbedienungAction(callback_tx, FernbedienungAction::Identify),
         Request::PixhawkPowerEnable(on) => 
@@ -418,6 +1639,33 @@ async fn handle_drone_request(
             Action::ExecuteFernbedienungAction(callback_tx, FernbedienungAction::Halt),
         Request::UpCoreReboot =>
             Action::ExecuteFernbedienungAction(callback_tx, FernbedienungAction::Reboot),
+        Request::XbeeParameterGet(parameter) =>
+            Action::ExecuteXbeeAction(callback_tx, XbeeAction::GetParameter(at_command(&parameter)?)),
+        Request::XbeeParameterSet(parameter, value) =>
+            Action::ExecuteXbeeAction(callback_tx, XbeeAction::SetParameter(at_command(&parameter)?, value)),
+        Request::XbeeParameterWrite =>
+            Action::ExecuteXbeeAction(callback_tx, XbeeAction::WriteParameters),
+        Request::XbeeDiscover =>
+            Action::ExecuteXbeeAction(callback_tx, XbeeAction::DiscoverNodes),
+        Request::PixhawkLogList =>
+            Action::ExecuteXbeeAction(callback_tx, XbeeAction::ListLogs),
+        /* the logs are stored in the working directory alongside the journals */
+        Request::PixhawkLogDownload(log) => {
+            let path = format!("{}-{}-{}.ulg", id, log, chrono::Local::now().format("%Y%m%d-%H%M%S"));
+            Action::ExecuteXbeeAction(callback_tx, XbeeAction::DownloadLog(log, path.into()))
+        },
+        Request::MissionUpload(items) =>
+            Action::ExecuteXbeeAction(callback_tx, XbeeAction::UploadMission(items)),
+        Request::MissionStart =>
+            Action::ExecuteXbeeAction(callback_tx, XbeeAction::StartMission),
+        Request::PixhawkFirmwareUpload(firmware) =>
+            Action::ExecuteFernbedienungAction(callback_tx, FernbedienungAction::UploadPixhawkFirmware(firmware)),
+        /* supervised flights require the tracking system and are therefore handled by the arena */
+        Request::SupervisedFlight(target) => {
+            arena_tx.send(arena::Action::SetDroneSupervisedFlight { id, target, callback: callback_tx }).await
+                .map_err(|_| anyhow::anyhow!("Could not send action to arena"))?;
+            return callback_rx.await.map_err(|_| anyhow::anyhow!("No response from arena"))?;
+        },
     };
     arena_tx.send(arena::Action::ForwardDroneAction(id, action)).await
         .map_err(|_| anyhow::anyhow!("Could not send action to arena"))?;
@@ -442,6 +1690,8 @@ async fn handle_pipuck_request(
             Action::ExecuteFernbedienungAction(callback_tx, FernbedienungAction::Bash(TerminalAction::Run(command))),
         Request::CameraStreamEnable(on) => 
             Action::ExecuteFernbedienungAction(callback_tx, FernbedienungAction::SetCameraStream(on)),
+        Request::CameraDeviceStreamEnable(camera, on) => 
+            Action::ExecuteFernbedienungAction(callback_tx, FernbedienungAction::SetCameraDeviceStream(camera, on)),
         Request::Identify => 
             Action::ExecuteFernbedienungAction(callback_tx, FernbedienungAction::Identify),
         Request::RaspberryPiHalt => 
@@ -454,20 +1704,368 @@ async fn handle_pipuck_request(
     callback_rx.await.map_err(|_| anyhow::anyhow!("No response from arena"))?
 }
 
+async fn handle_pipuck_power_request(
+    arena_tx: &mpsc::Sender<arena::Action>,
+    ids: Vec<String>,
+    power: shared::pipuck::Power,
+) -> anyhow::Result<()> {
+    let (callback_tx, callback_rx) = oneshot::channel();
+    arena_tx.send(arena::Action::ExecutePiPuckPowerAction { ids, power, callback: callback_tx }).await
+        .map_err(|_| anyhow::anyhow!("Could not send action to arena"))?;
+    callback_rx.await.map_err(|_| anyhow::anyhow!("No response from arena"))?
+}
+
+async fn handle_land_all_request(
+    arena_tx: &mpsc::Sender<arena::Action>,
+    landing: shared::drone::Landing,
+) -> anyhow::Result<()> {
+    let (callback_tx, callback_rx) = oneshot::channel();
+    arena_tx.send(arena::Action::LandAll { landing, callback: callback_tx }).await
+        .map_err(|_| anyhow::anyhow!("Could not send action to arena"))?;
+    callback_rx.await.map_err(|_| anyhow::anyhow!("No response from arena"))?
+}
+
+async fn handle_recall_pipucks_request(
+    arena_tx: &mpsc::Sender<arena::Action>,
+) -> anyhow::Result<()> {
+    let (callback_tx, callback_rx) = oneshot::channel();
+    arena_tx.send(arena::Action::RecallPiPucks { callback: callback_tx }).await
+        .map_err(|_| anyhow::anyhow!("Could not send action to arena"))?;
+    callback_rx.await.map_err(|_| anyhow::anyhow!("No response from arena"))?
+}
+
+async fn handle_router_request(
+    arena_tx: &mpsc::Sender<arena::Action>,
+    request: shared::router::Request,
+) -> anyhow::Result<()> {
+    match request {
+        shared::router::Request::Replay { journal, speed, senders } => {
+            let (callback_tx, callback_rx) = oneshot::channel();
+            let action = arena::Action::ReplayRouterMessages {
+                journal: journal.into(),
+                speed,
+                senders,
+                callback: callback_tx,
+            };
+            arena_tx.send(action).await
+                .map_err(|_| anyhow::anyhow!("Could not send action to arena"))?;
+            callback_rx.await.map_err(|_| anyhow::anyhow!("No response from arena"))?
+                .map(|_| ())
+        },
+        shared::router::Request::StopReplay => {
+            let (callback_tx, callback_rx) = oneshot::channel();
+            arena_tx.send(arena::Action::StopRouterReplay(callback_tx)).await
+                .map_err(|_| anyhow::anyhow!("Could not send action to arena"))?;
+            callback_rx.await.map_err(|_| anyhow::anyhow!("No response from arena"))?
+        },
+    }
+}
+
+/* take control of the arena if it is not held by another client or if takeover is set */
+async fn acquire_control(
+    arena_tx: &mpsc::Sender<arena::Action>,
+    client: &arena::Controller,
+    takeover: bool,
+) -> anyhow::Result<()> {
+    let (callback_tx, callback_rx) = oneshot::channel();
+    let action = arena::Action::AcquireControl { client: client.clone(), takeover, callback: callback_tx };
+    arena_tx.send(action).await
+        .map_err(|_| anyhow::anyhow!("Could not send action to arena"))?;
+    callback_rx.await.map_err(|_| anyhow::anyhow!("No response from arena"))?
+}
+
+/* the robot that a request concerns, if any, and a description of the request for the audit trail,
+   the contents of uploads are left out. Requests that only concern the client are not audited */
+fn describe(request: &BackEndRequest) -> Option<(Option<String>, String)> {
+    use shared::experiment::Request as ExperimentRequest;
+    let described = match request {
+        BackEndRequest::BuilderBotRequest(id, request) =>
+            (Some(id.clone()), format!("BuilderBot: {:?}", request)),
+        BackEndRequest::DroneRequest(id, request) => {
+            let action = match request {
+                shared::drone::Request::MissionUpload(items) =>
+                    format!("MissionUpload ({} items)", items.len()),
+                shared::drone::Request::PixhawkFirmwareUpload(firmware) =>
+                    format!("PixhawkFirmwareUpload ({} bytes)", firmware.len()),
+                request => format!("{:?}", request),
+            };
+            (Some(id.clone()), format!("Drone: {}", action))
+        },
+        BackEndRequest::PiPuckRequest(id, request) =>
+            (Some(id.clone()), format!("Pi-Puck: {:?}", request)),
+        BackEndRequest::PiPuckPowerRequest(ids, power) =>
+            (None, format!("Pi-Pucks: {:?} ({})", power, ids.join(", "))),
+        BackEndRequest::RecallPiPucksRequest =>
+            (None, String::from("Pi-Pucks: Recall to docks")),
+        BackEndRequest::LandAllRequest(landing) =>
+            (None, format!("Drones: Land all ({:?})", landing)),
+        BackEndRequest::ExperimentRequest(request) => (None, match request {
+            ExperimentRequest::Start { force, group, .. } =>
+                format!("Experiment: Start (group: {}, force: {})", group.as_deref().unwrap_or("all robots"), force),
+            ExperimentRequest::Stop => String::from("Experiment: Stop"),
+            ExperimentRequest::CheckRigidBodies => String::from("Experiment: Check rigid bodies"),
+            ExperimentRequest::IdentifyRigidBodies => String::from("Experiment: Identify rigid bodies"),
+            ExperimentRequest::CheckReadiness { group, .. } =>
+                format!("Experiment: Check readiness (group: {})", group.as_deref().unwrap_or("all robots")),
+        }),
+        BackEndRequest::GroupRequest(group, request) =>
+            (None, format!("Group {}: {:?}", group, request)),
+        BackEndRequest::QuarantineRequest(id, true) =>
+            (Some(id.clone()), String::from("Quarantine")),
+        BackEndRequest::QuarantineRequest(id, false) =>
+            (Some(id.clone()), String::from("Release from quarantine")),
+        BackEndRequest::RunMaintenance =>
+            (None, String::from("Run maintenance")),
+        BackEndRequest::TicketRequest(shared::ticket::Request::Open { robot, description }) =>
+            (Some(robot.clone()), format!("Open ticket: {}", description)),
+        BackEndRequest::TicketRequest(shared::ticket::Request::Close { id, resolution }) =>
+            (None, format!("Close ticket {}: {}", id, resolution)),
+        BackEndRequest::AssetRequest(asset) =>
+            (Some(asset.robot.clone()), String::from("Edit metadata")),
+        BackEndRequest::SetRehearsal(true) =>
+            (None, String::from("Enable rehearsal mode")),
+        BackEndRequest::SetRehearsal(false) =>
+            (None, String::from("Disable rehearsal mode")),
+        BackEndRequest::ControlRequest(request) =>
+            (None, format!("Control: {:?}", request)),
+        BackEndRequest::RouterRequest(shared::router::Request::Replay { journal, speed, senders }) =>
+            (None, match senders.is_empty() {
+                true => format!("Router: Replay {} (speed: {})", journal, speed),
+                false => format!("Router: Replay {} (speed: {}, senders: {})", journal, speed, senders.join(", ")),
+            }),
+        BackEndRequest::RouterRequest(shared::router::Request::StopReplay) =>
+            (None, String::from("Router: Stop replay")),
+        BackEndRequest::SetUpdateProfile(_) |
+        BackEndRequest::InspectRouter(_) |
+        BackEndRequest::TelemetryRequest(..) => return None,
+    };
+    Some(described)
+}
+
+/* the identifiers of all robots in the arena */
+async fn robot_ids(arena_tx: &mpsc::Sender<arena::Action>) -> anyhow::Result<Vec<String>> {
+    let (builderbots_tx, builderbots_rx) = oneshot::channel();
+    let (drones_tx, drones_rx) = oneshot::channel();
+    let (pipucks_tx, pipucks_rx) = oneshot::channel();
+    arena_tx.send(arena::Action::GetBuilderBotDescriptors(builderbots_tx)).await
+        .map_err(|_| anyhow::anyhow!("Could not send action to arena"))?;
+    arena_tx.send(arena::Action::GetDroneDescriptors(drones_tx)).await
+        .map_err(|_| anyhow::anyhow!("Could not send action to arena"))?;
+    arena_tx.send(arena::Action::GetPiPuckDescriptors(pipucks_tx)).await
+        .map_err(|_| anyhow::anyhow!("Could not send action to arena"))?;
+    let builderbots = builderbots_rx.await
+        .map_err(|_| anyhow::anyhow!("No response from arena"))?;
+    let drones = drones_rx.await
+        .map_err(|_| anyhow::anyhow!("No response from arena"))?;
+    let pipucks = pipucks_rx.await
+        .map_err(|_| anyhow::anyhow!("No response from arena"))?;
+    let ids = builderbots.iter().map(|desc| desc.id.clone())
+        .chain(drones.iter().map(|desc| desc.id.clone()))
+        .chain(pipucks.iter().map(|desc| desc.id.clone()))
+        .collect();
+    Ok(ids)
+}
+
+async fn handle_asset_request(
+    arena_tx: &mpsc::Sender<arena::Action>,
+    asset_tx: &mpsc::Sender<asset::Action>,
+    asset: shared::asset::Asset,
+) -> anyhow::Result<()> {
+    /* the metadata can only be attached to the robots of the arena */
+    if !robot_ids(arena_tx).await?.contains(&asset.robot) {
+        anyhow::bail!("Could not find robot {}", asset.robot);
+    }
+    let (callback_tx, callback_rx) = oneshot::channel();
+    asset_tx.send(asset::Action::Set { callback: callback_tx, asset }).await
+        .map_err(|_| anyhow::anyhow!("Could not send action to asset store"))?;
+    callback_rx.await.map_err(|_| anyhow::anyhow!("No response from asset store"))?
+}
+
+/* the recent values of the metrics of a robot, encoded as a message for the client, where each series
+   is thinned out to at most the given number of samples */
+async fn handle_telemetry_request(
+    arena_tx: &mpsc::Sender<arena::Action>,
+    robot: String,
+    minutes: u32,
+    samples: Option<usize>,
+) -> anyhow::Result<warp::ws::Message> {
+    let (callback_tx, callback_rx) = oneshot::channel();
+    let window = Duration::from_secs(u64::from(minutes) * 60);
+    arena_tx.send(arena::Action::GetTelemetry { robot, window, callback: callback_tx }).await
+        .map_err(|_| anyhow::anyhow!("Could not send action to arena"))?;
+    let mut window = callback_rx.await
+        .map_err(|_| anyhow::anyhow!("No response from telemetry task"))??;
+    if let Some(samples) = samples {
+        for series in window.series.iter_mut() {
+            let step = series.samples.len().div_ceil(samples);
+            if step > 1 {
+                /* the disconnections and the most recent sample are always kept */
+                let last = series.samples.len() - 1;
+                series.samples = series.samples.drain(..)
+                    .enumerate()
+                    .filter(|(index, (_, value))| index % step == 0 || *index == last || value.is_none())
+                    .map(|(_, sample)| sample)
+                    .collect();
+            }
+        }
+    }
+    let message = DownMessage::Request(Uuid::new_v4(), FrontEndRequest::UpdateTelemetry(window));
+    bincode::serialize(&message)
+        .context("Could not serialize telemetry message")
+        .map(warp::ws::Message::binary)
+}
+
+async fn handle_rehearsal_request(
+    arena_tx: &mpsc::Sender<arena::Action>,
+    rehearsal_tx: &watch::Sender<bool>,
+    client: &arena::Controller,
+    enabled: bool,
+) -> anyhow::Result<()> {
+    rehearsal_tx.send(enabled)
+        .map_err(|_| anyhow::anyhow!("Could not set rehearsal mode"))?;
+    let message = match enabled {
+        true => format!("Rehearsal mode enabled by {}, destructive actions will not be sent to the robots", client.name),
+        false => format!("Rehearsal mode disabled by {}", client.name),
+    };
+    log::warn!("{}", message);
+    let action = arena::Action::ReportEvent {
+        severity: shared::event::Severity::Warning,
+        robot: None,
+        subsystem: String::from("rehearsal"),
+        message,
+    };
+    arena_tx.send(action).await
+        .map_err(|_| anyhow::anyhow!("Could not send action to arena"))
+}
+
+/* the audit trail is kept by the audit actor, while the arena records the entry in its journal */
+async fn record_audit_entry(
+    arena_tx: &mpsc::Sender<arena::Action>,
+    audit_tx: &mpsc::Sender<audit::Action>,
+    entry: shared::audit::Entry
+) {
+    if arena_tx.send(arena::Action::RecordAuditEntry(entry.clone())).await.is_err() {
+        log::error!("Could not communicate with arena");
+    }
+    if audit_tx.send(audit::Action::Record(entry)).await.is_err() {
+        log::error!("Could not communicate with audit trail");
+    }
+}
+
+async fn handle_control_request(
+    arena_tx: &mpsc::Sender<arena::Action>,
+    client: &arena::Controller,
+    request: control::Request,
+) -> anyhow::Result<()> {
+    match request {
+        control::Request::TakeOver => acquire_control(arena_tx, client, true).await,
+        control::Request::Release => arena_tx.send(arena::Action::ReleaseControl(client.id)).await
+            .map_err(|_| anyhow::anyhow!("Could not send action to arena")),
+    }
+}
+
+async fn handle_quarantine_request(
+    arena_tx: &mpsc::Sender<arena::Action>,
+    id: String,
+    quarantined: bool,
+) -> anyhow::Result<()> {
+    let (callback_tx, callback_rx) = oneshot::channel();
+    let action = arena::Action::SetQuarantined { id, quarantined, callback: callback_tx };
+    arena_tx.send(action).await
+        .map_err(|_| anyhow::anyhow!("Could not send action to arena"))?;
+    callback_rx.await.map_err(|_| anyhow::anyhow!("No response from arena"))?
+}
+
+async fn handle_maintenance_request(
+    arena_tx: &mpsc::Sender<arena::Action>,
+) -> anyhow::Result<()> {
+    let (callback_tx, callback_rx) = oneshot::channel();
+    arena_tx.send(arena::Action::RunMaintenance { callback: callback_tx }).await
+        .map_err(|_| anyhow::anyhow!("Could not send action to arena"))?;
+    callback_rx.await.map_err(|_| anyhow::anyhow!("No response from arena"))?
+}
+
+async fn handle_group_request(
+    arena_tx: &mpsc::Sender<arena::Action>,
+    group: String,
+    request: shared::group::Request,
+) -> anyhow::Result<()> {
+    let (callback_tx, callback_rx) = oneshot::channel();
+    let action = arena::Action::ExecuteGroupRequest { group, request, callback: callback_tx };
+    arena_tx.send(action).await
+        .map_err(|_| anyhow::anyhow!("Could not send action to arena"))?;
+    callback_rx.await.map_err(|_| anyhow::anyhow!("No response from arena"))?
+}
+
 async fn handle_experiment_request(
     arena_tx: &mpsc::Sender<arena::Action>,
+    experiment_updates_tx: &mpsc::Sender<shared::experiment::Update>,
     request: shared::experiment::Request,
 ) -> anyhow::Result<()> {
     use shared::experiment::Request;
     use arena::Action;
     let (callback_tx, callback_rx) = oneshot::channel();
     let action = match request {
-        Request::Start { builderbot_software, drone_software, pipuck_software } => 
-            Action::StartExperiment { callback: callback_tx, builderbot_software, drone_software, pipuck_software },
+        Request::Start { builderbot_software, drone_software, pipuck_software, force, group } => 
+            Action::StartExperiment { callback: callback_tx, builderbot_software, drone_software, pipuck_software, force, group },
         Request::Stop =>
             Action::StopExperiment { callback: callback_tx },
+        Request::CheckRigidBodies =>
+            Action::CheckRigidBodies { callback: callback_tx },
+        Request::IdentifyRigidBodies =>
+            return identify_rigid_bodies(arena_tx, experiment_updates_tx).await,
+        Request::CheckReadiness { builderbot_software, drone_software, pipuck_software, group } => {
+            let (callback_tx, callback_rx) = oneshot::channel();
+            let action = Action::CheckReadiness { callback: callback_tx, builderbot_software, drone_software, pipuck_software, group };
+            arena_tx.send(action).await
+                .map_err(|_| anyhow::anyhow!("Could not send action to arena"))?;
+            let readiness = callback_rx.await
+                .map_err(|_| anyhow::anyhow!("No response from arena"))?;
+            return experiment_updates_tx.send(shared::experiment::Update::Readiness(readiness)).await
+                .map_err(|_| anyhow::anyhow!("Could not send readiness report to client"));
+        },
     };
     arena_tx.send(action).await
         .map_err(|_| anyhow::anyhow!("Could not send action to arena"))?;
     callback_rx.await.map_err(|_| anyhow::anyhow!("No response from arena"))?
-}
\ No newline at end of file
+}
+
+async fn identify_rigid_bodies(
+    arena_tx: &mpsc::Sender<arena::Action>,
+    experiment_updates_tx: &mpsc::Sender<shared::experiment::Update>,
+) -> anyhow::Result<()> {
+    let (callback_tx, callback_rx) = oneshot::channel();
+    arena_tx.send(arena::Action::IdentifyRigidBodies { callback: callback_tx }).await
+        .map_err(|_| anyhow::anyhow!("Could not send action to arena"))?;
+    /* identification takes a while, forward the proposals to the client once they are available */
+    let experiment_updates_tx = experiment_updates_tx.clone();
+    tokio::spawn(async move {
+        match callback_rx.await {
+            Ok(Ok(proposals)) => {
+                let update = shared::experiment::Update::RigidBodyProposals(proposals);
+                let _ = experiment_updates_tx.send(update).await;
+            },
+            Ok(Err(error)) => log::warn!("Could not identify rigid bodies: {}", error),
+            Err(_) => log::warn!("No response from arena"),
+        }
+    });
+    Ok(())
+}
+
+async fn handle_ticket_request(
+    ticket_tx: &mpsc::Sender<ticket::Action>,
+    request: shared::ticket::Request,
+) -> anyhow::Result<()> {
+    use shared::ticket::Request;
+    use ticket::Action;
+    let (callback_tx, callback_rx) = oneshot::channel();
+    let action = match request {
+        Request::Open { robot, description } =>
+            Action::Open { callback: callback_tx, robot, description },
+        Request::Close { id, resolution } =>
+            Action::Close { callback: callback_tx, id, resolution },
+    };
+    ticket_tx.send(action).await
+        .map_err(|_| anyhow::anyhow!("Could not send action to ticket store"))?;
+    callback_rx.await.map_err(|_| anyhow::anyhow!("No response from ticket store"))?
+}
@@ -152,6 +152,8 @@ while True:
          message = {
             'timestamp': timestamp,
             'data': event[1],
+            # journals recorded before the runs were tagged do not have a run
+            'run': event[2] if len(event) > 2 else None,
          }
          if source in messages:
             messages[source].append(message)